@@ -1,19 +1,43 @@
 use colored::*;
-use serde_yaml::{self, Value};
 use std::fs;
 use std::path::Path;
 
-use wrkflw_models::ValidationResult;
-use wrkflw_validators::{validate_jobs, validate_triggers};
+use wrkflw_models::{Severity, ValidationResult};
+use wrkflw_parser::yaml;
+use wrkflw_validators::{
+    validate_expressions, validate_jobs, validate_permissions, validate_secret_usage,
+    validate_triggers, validate_yaml,
+};
 
 pub fn evaluate_workflow_file(path: &Path, verbose: bool) -> Result<ValidationResult, String> {
     let content = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
 
-    // Parse YAML content
-    let workflow: Value =
-        serde_yaml::from_str(&content).map_err(|e| format!("Invalid YAML: {}", e))?;
+    // Parse YAML content; of a broken file, the sections that parse are still checked
+    let recovered = yaml::parse_recovering(&content);
+    let workflow = recovered.value;
 
+    // Duplicate keys and aliases of missing anchors, where they are; the parser
+    // only says which mapping or alias it stopped at
     let mut result = ValidationResult::new();
+    result.in_rule("yaml", |result| validate_yaml(&content, false, result));
+    let explained = !result.is_valid;
+    for error in &recovered.errors {
+        let message = &error.message;
+        if explained && (message.contains("duplicate entry") || message.contains("unknown anchor"))
+        {
+            continue;
+        }
+        result.add_for(
+            "syntax",
+            Severity::Error,
+            format!("Invalid YAML: {}", error),
+        );
+    }
+    let unparsed = |section: &str| recovered.unparsed.iter().any(|path| path == section);
+    let unparsed_jobs = recovered
+        .unparsed
+        .iter()
+        .any(|path| path.starts_with("jobs."));
 
     // Check for required structure
     if !workflow.is_mapping() {
@@ -27,22 +51,39 @@ pub fn evaluate_workflow_file(path: &Path, verbose: bool) -> Result<ValidationRe
 
     // Check if jobs section exists
     match workflow.get("jobs") {
+        // Of jobs that all fail to parse, there's nothing more to say
+        Some(jobs) if unparsed_jobs && jobs.as_mapping().is_some_and(|jobs| jobs.is_empty()) => {}
         Some(jobs) if jobs.is_mapping() => {
-            validate_jobs(jobs, &mut result);
+            result.in_rule("jobs", |result| validate_jobs(jobs, result));
         }
         Some(_) => {
             result.add_issue("'jobs' section is not a mapping".to_string());
         }
+        None if unparsed("jobs") => {}
         None => {
             result.add_issue("Workflow is missing 'jobs' section".to_string());
         }
     }
 
+    // Check permissions blocks and the APIs steps use against them
+    result.in_rule("permissions", |result| {
+        validate_permissions(&workflow, result)
+    });
+
+    // Parse every expression, checking what it refers to
+    result.in_rule("expressions", |result| {
+        validate_expressions(&workflow, result)
+    });
+
+    // Check for credentials in the file and secrets passed where they can leak
+    validate_secret_usage(&workflow, &mut result);
+
     // Check for valid triggers
     match workflow.get("on") {
         Some(on) => {
-            validate_triggers(on, &mut result);
+            result.in_rule("triggers", |result| validate_triggers(on, result));
         }
+        None if unparsed("on") => {}
         None => {
             result.add_issue("Workflow is missing 'on' section (triggers)".to_string());
         }
@@ -0,0 +1,578 @@
+//! Content-addressed cache of the repositories remote reusable workflows are loaded
+//! from, under `~/.wrkflw/actions`.
+//!
+//! `objects/<commit>/` holds the files of a commit, shared by every ref resolving to
+//! it, and `refs/<owner>/<repo>/<ref>` records the commit a ref was last fetched at.
+//! The modification time of a ref file is when it was last used.
+
+use crate::checkout::{self, CheckoutOptions, Source, Submodules};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use wrkflw_parser::workflow::WorkflowDefinition;
+
+/// Whether the run going on is run with `--offline`
+pub fn is_offline() -> bool {
+    crate::context::current().offline
+}
+
+/// A ref of a repository on GitHub
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RepoRef {
+    pub owner: String,
+    pub repo: String,
+    pub git_ref: String,
+}
+
+impl RepoRef {
+    pub fn new(owner: &str, repo: &str, git_ref: &str) -> Self {
+        Self {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            git_ref: git_ref.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for RepoRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}@{}", self.owner, self.repo, self.git_ref)
+    }
+}
+
+/// Split a job-level `uses: owner/repo/path/to/workflow.yml@ref` into the
+/// repository ref and the workflow's path in it
+pub fn parse_workflow_uses(uses: &str) -> Option<(RepoRef, String)> {
+    let (left, git_ref) = uses.split_once('@')?;
+    let mut segments = left.splitn(3, '/');
+    let owner = segments.next()?;
+    let repo = segments.next()?;
+    let path = segments.next()?;
+    if [owner, repo, path, git_ref]
+        .iter()
+        .any(|part| part.is_empty() || part.contains('@'))
+    {
+        return None;
+    }
+    Some((RepoRef::new(owner, repo, git_ref), path.to_string()))
+}
+
+/// A ref in the cache
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedRef {
+    pub repo_ref: RepoRef,
+    pub commit: String,
+    pub last_used: SystemTime,
+}
+
+/// What a prune removed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneSummary {
+    pub refs: usize,
+    pub objects: usize,
+}
+
+/// Downloaded repositories under a cache directory
+pub struct ActionCache {
+    root: PathBuf,
+    server_url: String,
+    offline: bool,
+}
+
+impl ActionCache {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            server_url: "https://github.com".to_string(),
+            offline: false,
+        }
+    }
+
+    /// `~/.wrkflw/actions`
+    pub fn default_root() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join(".wrkflw")
+            .join("actions")
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Fetch from another server than github.com
+    pub fn server_url(mut self, server_url: &str) -> Self {
+        self.server_url = server_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Only use what is already cached, never fetching anything
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    fn object_dir(&self, commit: &str) -> PathBuf {
+        self.root.join("objects").join(commit)
+    }
+
+    fn ref_file(&self, repo_ref: &RepoRef) -> PathBuf {
+        self.root
+            .join("refs")
+            .join(&repo_ref.owner)
+            .join(&repo_ref.repo)
+            .join(urlencoding::encode(&repo_ref.git_ref).as_ref())
+    }
+
+    /// Point `repo_ref` at `commit`, marking it as just used
+    fn record(&self, repo_ref: &RepoRef, commit: &str) -> Result<(), String> {
+        let file = self.ref_file(repo_ref);
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(&file, format!("{}\n", commit))
+            .map_err(|e| format!("Failed to write {}: {}", file.display(), e))
+    }
+
+    /// The cached files of `repo_ref`, without fetching anything
+    pub fn lookup(&self, repo_ref: &RepoRef) -> Option<PathBuf> {
+        let commit = fs::read_to_string(self.ref_file(repo_ref))
+            .ok()
+            .map(|commit| commit.trim().to_string())
+            .or_else(|| is_full_sha(&repo_ref.git_ref).then(|| repo_ref.git_ref.to_lowercase()))?;
+        let dir = self.object_dir(&commit);
+        if !dir.is_dir() {
+            return None;
+        }
+        // Only a cache, so failing to mark it as used is not worth failing for
+        let _ = self.record(repo_ref, &commit);
+        Some(dir)
+    }
+
+    /// The files of `repo_ref`, downloading them unless its commit is cached
+    ///
+    /// Online, branches and tags are resolved again every time so that moved refs
+    /// are picked up; offline, the commit they were last fetched at is used.
+    pub fn fetch(&self, repo_ref: &RepoRef, token: Option<&str>) -> Result<PathBuf, String> {
+        if self.offline {
+            return self.lookup(repo_ref).ok_or_else(|| {
+                format!(
+                    "{} is not in the action cache at {} and cannot be fetched offline",
+                    repo_ref,
+                    self.root.display()
+                )
+            });
+        }
+        if is_full_sha(&repo_ref.git_ref) {
+            if let Some(dir) = self.lookup(repo_ref) {
+                return Ok(dir);
+            }
+        }
+
+        let url = self.url(&repo_ref.owner, &repo_ref.repo);
+        let commit = match self.resolve(repo_ref, token)? {
+            Some(commit) if self.object_dir(&commit).is_dir() => commit,
+            _ => self.download(repo_ref, &url, token)?,
+        };
+        self.record(repo_ref, &commit)?;
+        Ok(self.object_dir(&commit))
+    }
+
+    fn url(&self, owner: &str, repo: &str) -> String {
+        format!("{}/{}/{}.git", self.server_url, owner, repo)
+    }
+
+    /// The refs of `owner/repo` by name, such as `refs/tags/v1`, and their commits
+    pub fn remote_refs(
+        &self,
+        owner: &str,
+        repo: &str,
+        token: Option<&str>,
+    ) -> Result<HashMap<String, String>, String> {
+        let auth = token.map(checkout::auth_header);
+        let output = checkout::run_git(
+            Path::new("."),
+            auth.as_deref(),
+            &self.server_url,
+            &["ls-remote", &self.url(owner, repo)],
+        )?;
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let (sha, name) = line.split_once('\t')?;
+                Some((name.to_string(), sha.to_string()))
+            })
+            .collect())
+    }
+
+    /// The commit `repo_ref` points to, or `None` for abbreviated SHAs, which can
+    /// only be expanded once fetched
+    fn resolve(&self, repo_ref: &RepoRef, token: Option<&str>) -> Result<Option<String>, String> {
+        let git_ref = &repo_ref.git_ref;
+        if is_full_sha(git_ref) {
+            return Ok(Some(git_ref.to_lowercase()));
+        }
+        let refs = self.remote_refs(&repo_ref.owner, &repo_ref.repo, token)?;
+        if let Some(sha) = commit_of(&refs, git_ref) {
+            return Ok(Some(sha));
+        }
+        if git_ref.len() >= 7 && git_ref.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Ok(None);
+        }
+        Err(format!(
+            "Ref '{}' not found in {}",
+            git_ref,
+            self.url(&repo_ref.owner, &repo_ref.repo)
+        ))
+    }
+
+    /// Download `repo_ref` into `objects/`, returning its commit
+    fn download(
+        &self,
+        repo_ref: &RepoRef,
+        url: &str,
+        token: Option<&str>,
+    ) -> Result<String, String> {
+        wrkflw_logging::info(&format!("Downloading {} from {}", repo_ref, url));
+        let objects = self.root.join("objects");
+        fs::create_dir_all(&objects).map_err(|e| e.to_string())?;
+        // Staged next to its destination so that it can be renamed into place
+        let staging = tempfile::Builder::new()
+            .prefix(".staging-")
+            .tempdir_in(&objects)
+            .map_err(|e| format!("Failed to create staging directory: {}", e))?;
+
+        let options = CheckoutOptions {
+            repository: Some(format!("{}/{}", repo_ref.owner, repo_ref.repo)),
+            git_ref: Some(repo_ref.git_ref.clone()),
+            path: None,
+            fetch_depth: 1,
+            fetch_tags: false,
+            submodules: Submodules::None,
+            persist_credentials: false,
+            clean: true,
+            token: token.map(str::to_string),
+            server_url: self.server_url.clone(),
+        };
+        checkout::checkout(&options, &Source::Remote(url.to_string()), staging.path())?;
+        let commit = checkout::run_git(staging.path(), None, "", &["rev-parse", "HEAD"])?
+            .trim()
+            .to_string();
+        fs::remove_dir_all(staging.path().join(".git")).map_err(|e| e.to_string())?;
+
+        let dir = self.object_dir(&commit);
+        // Someone else may have downloaded the same commit meanwhile
+        if !dir.is_dir() {
+            fs::rename(staging.path(), &dir)
+                .map_err(|e| format!("Failed to move download to {}: {}", dir.display(), e))?;
+        }
+        Ok(commit)
+    }
+
+    /// Every cached ref, sorted
+    pub fn list(&self) -> Vec<CachedRef> {
+        let mut cached = Vec::new();
+        for owner in read_dir(&self.root.join("refs")) {
+            for repo in read_dir(&owner) {
+                for file in read_dir(&repo) {
+                    let (Some(owner), Some(repo), Some(git_ref)) = (
+                        file_name(&owner),
+                        file_name(&repo),
+                        file_name(&file)
+                            .and_then(|name| urlencoding::decode(&name).ok().map(String::from)),
+                    ) else {
+                        continue;
+                    };
+                    let Ok(commit) = fs::read_to_string(&file) else {
+                        continue;
+                    };
+                    let last_used = fs::metadata(&file)
+                        .and_then(|metadata| metadata.modified())
+                        .unwrap_or(SystemTime::UNIX_EPOCH);
+                    cached.push(CachedRef {
+                        repo_ref: RepoRef {
+                            owner,
+                            repo,
+                            git_ref,
+                        },
+                        commit: commit.trim().to_string(),
+                        last_used,
+                    });
+                }
+            }
+        }
+        cached.sort_by(|a, b| a.repo_ref.cmp(&b.repo_ref));
+        cached
+    }
+
+    /// Remove refs unused for longer than `older_than` (if given), refs whose files
+    /// are gone, and then files no ref points to anymore
+    pub fn prune(&self, older_than: Option<Duration>) -> Result<PruneSummary, String> {
+        let mut summary = PruneSummary::default();
+        let mut kept = BTreeSet::new();
+        for cached in self.list() {
+            let expired = older_than.is_some_and(|age| {
+                cached
+                    .last_used
+                    .elapsed()
+                    .is_ok_and(|elapsed| elapsed > age)
+            });
+            if expired || !self.object_dir(&cached.commit).is_dir() {
+                let file = self.ref_file(&cached.repo_ref);
+                fs::remove_file(&file)
+                    .map_err(|e| format!("Failed to remove {}: {}", file.display(), e))?;
+                summary.refs += 1;
+            } else {
+                kept.insert(cached.commit);
+            }
+        }
+        for owner in read_dir(&self.root.join("refs")) {
+            for repo in read_dir(&owner) {
+                let _ = fs::remove_dir(&repo);
+            }
+            let _ = fs::remove_dir(&owner);
+        }
+
+        for object in read_dir(&self.root.join("objects")) {
+            if file_name(&object).is_some_and(|commit| kept.contains(&commit)) {
+                continue;
+            }
+            fs::remove_dir_all(&object)
+                .map_err(|e| format!("Failed to remove {}: {}", object.display(), e))?;
+            // Interrupted downloads aren't worth reporting
+            if !file_name(&object).is_some_and(|name| name.starts_with(".staging-")) {
+                summary.objects += 1;
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Remote reusable workflows that `workflow` calls but are not cached, also
+    /// looking into the cached ones for the workflows they call in turn
+    pub fn missing_workflows(&self, workflow: &WorkflowDefinition) -> Vec<RepoRef> {
+        let called = |workflow: &WorkflowDefinition| -> Vec<(RepoRef, String)> {
+            workflow
+                .jobs
+                .values()
+                .filter_map(|job| job.uses.as_deref().and_then(parse_workflow_uses))
+                .collect()
+        };
+        let mut missing = BTreeSet::new();
+        let mut seen = BTreeSet::new();
+        let mut pending = called(workflow);
+        while let Some((repo_ref, path)) = pending.pop() {
+            if !seen.insert((repo_ref.clone(), path.clone())) {
+                continue;
+            }
+            match self.lookup(&repo_ref) {
+                Some(dir) => {
+                    if let Ok(workflow) = wrkflw_parser::workflow::parse_workflow(&dir.join(&path))
+                    {
+                        pending.extend(called(&workflow));
+                    }
+                }
+                None => {
+                    missing.insert(repo_ref);
+                }
+            }
+        }
+        missing.into_iter().collect()
+    }
+}
+
+/// The commit the branch or tag `git_ref` points to in `refs` from
+/// `ActionCache::remote_refs`
+pub fn commit_of(refs: &HashMap<String, String>, git_ref: &str) -> Option<String> {
+    // Branches win over tags like on GitHub, and annotated tags are peeled
+    let candidates = [
+        format!("refs/heads/{}", git_ref),
+        format!("refs/tags/{}^{{}}", git_ref),
+        format!("refs/tags/{}", git_ref),
+        format!("{}^{{}}", git_ref),
+        git_ref.to_string(),
+    ];
+    candidates
+        .iter()
+        .find_map(|name| refs.get(name.as_str()))
+        .cloned()
+}
+
+/// The tags in `refs` pointing to `commit`, sorted
+pub fn tags_at(refs: &HashMap<String, String>, commit: &str) -> Vec<String> {
+    let mut tags: Vec<String> = refs
+        .keys()
+        .filter_map(|name| name.strip_prefix("refs/tags/"))
+        .map(|tag| tag.trim_end_matches("^{}"))
+        .filter(|tag| {
+            let peeled = refs
+                .get(&format!("refs/tags/{}^{{}}", tag))
+                .or_else(|| refs.get(&format!("refs/tags/{}", tag)));
+            peeled.is_some_and(|sha| sha.eq_ignore_ascii_case(commit))
+        })
+        .map(str::to_string)
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+pub fn is_full_sha(git_ref: &str) -> bool {
+    git_ref.len() == 40 && git_ref.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn read_dir(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+    paths.sort();
+    paths
+}
+
+fn file_name(path: &Path) -> Option<String> {
+    path.file_name()?.to_str().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    /// A server with `me/tools`, whose `main` has commits `one` and `two`, with `one`
+    /// tagged `v1`
+    fn server() -> tempfile::TempDir {
+        let server = tempfile::tempdir().unwrap();
+        let dir = server.path().join("me").join("tools.git");
+        fs::create_dir_all(&dir).unwrap();
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .current_dir(&dir)
+                .args([
+                    "-c",
+                    "user.name=wrkflw",
+                    "-c",
+                    "user.email=wrkflw@example.com",
+                ])
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {:?}", args);
+        };
+        git(&["init", "-q", "-b", "main"]);
+        for message in ["one", "two"] {
+            fs::write(dir.join("file.txt"), message).unwrap();
+            git(&["add", "."]);
+            git(&["commit", "-q", "-m", message]);
+            if message == "one" {
+                git(&["tag", "-a", "v1", "-m", "v1"]);
+            }
+        }
+        server
+    }
+
+    #[test]
+    fn test_parse_workflow_uses() {
+        assert_eq!(
+            parse_workflow_uses("me/tools/.github/workflows/ci.yml@v1"),
+            Some((
+                RepoRef::new("me", "tools", "v1"),
+                ".github/workflows/ci.yml".to_string()
+            ))
+        );
+        assert_eq!(parse_workflow_uses("me/tools@v1"), None);
+        assert_eq!(parse_workflow_uses("me/tools/ci.yml"), None);
+        assert_eq!(parse_workflow_uses("me/tools/ci.yml@"), None);
+    }
+
+    #[test]
+    fn test_fetch_and_offline() {
+        let server = server();
+        let root = tempfile::tempdir().unwrap();
+        let cache = ActionCache::new(root.path())
+            .server_url(&format!("file://{}", server.path().display()));
+        let v1 = RepoRef::new("me", "tools", "v1");
+        let main = RepoRef::new("me", "tools", "main");
+
+        let v1_dir = cache.fetch(&v1, None).unwrap();
+        assert_eq!(fs::read_to_string(v1_dir.join("file.txt")).unwrap(), "one");
+        assert!(!v1_dir.join(".git").exists());
+        let main_dir = cache.fetch(&main, None).unwrap();
+        assert_eq!(
+            fs::read_to_string(main_dir.join("file.txt")).unwrap(),
+            "two"
+        );
+        assert_ne!(v1_dir, main_dir);
+
+        // The same commit under another name shares its files
+        let commit = file_name(&v1_dir).unwrap();
+        let by_sha = RepoRef::new("me", "tools", &commit);
+        assert_eq!(cache.fetch(&by_sha, None).unwrap(), v1_dir);
+        assert!(cache
+            .fetch(&RepoRef::new("me", "tools", "v2"), None)
+            .is_err());
+
+        let offline = ActionCache::new(root.path()).offline(true);
+        assert_eq!(offline.fetch(&v1, None).unwrap(), v1_dir);
+        let error = offline
+            .fetch(&RepoRef::new("me", "tools", "v2"), None)
+            .unwrap_err();
+        assert!(error.contains("me/tools@v2"), "{}", error);
+
+        let refs = cache.remote_refs("me", "tools", None).unwrap();
+        assert_eq!(commit_of(&refs, "v1"), Some(commit.clone()));
+        assert_eq!(tags_at(&refs, &commit), vec!["v1".to_string()]);
+        assert!(tags_at(&refs, &file_name(&main_dir).unwrap()).is_empty());
+
+        let listed: Vec<String> = cache
+            .list()
+            .iter()
+            .map(|cached| cached.repo_ref.to_string())
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                format!("me/tools@{}", commit),
+                "me/tools@main".to_string(),
+                "me/tools@v1".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prune() {
+        let server = server();
+        let root = tempfile::tempdir().unwrap();
+        let cache = ActionCache::new(root.path())
+            .server_url(&format!("file://{}", server.path().display()));
+        let v1 = RepoRef::new("me", "tools", "v1");
+        let main = RepoRef::new("me", "tools", "main");
+        cache.fetch(&v1, None).unwrap();
+        let main_dir = cache.fetch(&main, None).unwrap();
+        fs::create_dir_all(root.path().join("objects").join(".staging-x")).unwrap();
+
+        assert_eq!(cache.prune(None).unwrap(), PruneSummary::default());
+        assert!(!root.path().join("objects").join(".staging-x").exists());
+
+        fs::remove_file(cache.ref_file(&v1)).unwrap();
+        assert_eq!(
+            cache.prune(Some(Duration::from_secs(3600))).unwrap(),
+            PruneSummary {
+                refs: 0,
+                objects: 1
+            }
+        );
+        assert_eq!(cache.lookup(&main), Some(main_dir));
+
+        assert_eq!(
+            cache.prune(Some(Duration::ZERO)).unwrap(),
+            PruneSummary {
+                refs: 1,
+                objects: 1
+            }
+        );
+        assert!(cache.list().is_empty());
+        assert!(!root.path().join("refs").join("me").exists());
+    }
+}
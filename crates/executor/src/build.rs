@@ -0,0 +1,156 @@
+//! Building images with the `docker buildx` and `podman build` command lines,
+//! logging the Dockerfile steps as the builder gets to them.
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use wrkflw_runtime::container::{BuildOptions, ContainerError};
+
+/// Lines of builder output kept to explain a failed build
+const ERROR_CONTEXT_LINES: usize = 20;
+
+/// Arguments of `build` after the subcommand: the Dockerfile, tag, options and
+/// the build context, the Dockerfile's directory
+pub(crate) fn build_args(dockerfile: &Path, tag: &str, options: &BuildOptions) -> Vec<String> {
+    let context = dockerfile.parent().unwrap_or(Path::new("."));
+    let mut args = vec![
+        "-f".to_string(),
+        dockerfile.to_string_lossy().into_owned(),
+        "-t".to_string(),
+        tag.to_string(),
+    ];
+    for (name, value) in &options.build_args {
+        args.push("--build-arg".to_string());
+        args.push(format!("{}={}", name, value));
+    }
+    if let Some(target) = &options.target {
+        args.push("--target".to_string());
+        args.push(target.clone());
+    }
+    for (name, value) in &options.labels {
+        args.push("--label".to_string());
+        args.push(format!("{}={}", name, value));
+    }
+    args.push(context.to_string_lossy().into_owned());
+    args
+}
+
+/// The Dockerfile step a line of builder output starts, as BuildKit
+/// (`#5 [2/3] RUN make`), Podman (`STEP 2/3: RUN make`) and the classic Docker
+/// builder (`Step 2/3 : RUN make`) print it
+pub(crate) fn build_step(line: &str) -> Option<&str> {
+    let line = line.trim();
+    if let Some(rest) = line.strip_prefix('#') {
+        let (number, step) = rest.split_once(' ')?;
+        return (number.chars().all(|c| c.is_ascii_digit()) && step.starts_with('['))
+            .then_some(step);
+    }
+    (line.starts_with("STEP ") || line.starts_with("Step ")).then_some(line)
+}
+
+/// Run a build command, logging its steps, and fail with the end of its output
+pub(crate) async fn run_build(mut cmd: Command, tag: &str) -> Result<(), ContainerError> {
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| ContainerError::ImageBuild(format!("Failed to start the build: {}", e)))?;
+
+    let (Some(stdout), Some(stderr)) = (child.stdout.take(), child.stderr.take()) else {
+        return Err(ContainerError::ImageBuild(
+            "Failed to read the build output".to_string(),
+        ));
+    };
+    let mut stdout = BufReader::new(stdout).lines();
+    let mut stderr = BufReader::new(stderr).lines();
+    let mut recent = VecDeque::with_capacity(ERROR_CONTEXT_LINES);
+    let (mut stdout_open, mut stderr_open) = (true, true);
+    loop {
+        let line = tokio::select! {
+            line = stdout.next_line(), if stdout_open => match line {
+                Ok(Some(line)) => line,
+                _ => {
+                    stdout_open = false;
+                    continue;
+                }
+            },
+            line = stderr.next_line(), if stderr_open => match line {
+                Ok(Some(line)) => line,
+                _ => {
+                    stderr_open = false;
+                    continue;
+                }
+            },
+            else => break,
+        };
+        match build_step(&line) {
+            Some(step) => wrkflw_logging::info(&format!("Building image {}: {}", tag, step)),
+            None => wrkflw_logging::debug(&line),
+        }
+        if recent.len() == ERROR_CONTEXT_LINES {
+            recent.pop_front();
+        }
+        recent.push_back(line);
+    }
+
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| ContainerError::ImageBuild(format!("The build failed: {}", e)))?;
+    if !status.success() {
+        return Err(ContainerError::ImageBuild(format!(
+            "Failed to build image {}:\n{}",
+            tag,
+            Vec::from(recent).join("\n")
+        )));
+    }
+    wrkflw_logging::info(&format!("Built image {}", tag));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_step() {
+        assert_eq!(build_step("#5 [2/3] RUN make"), Some("[2/3] RUN make"));
+        assert_eq!(build_step("#5 DONE 0.3s"), None);
+        assert_eq!(build_step("#5 CACHED"), None);
+        assert_eq!(build_step("STEP 2/3: RUN make"), Some("STEP 2/3: RUN make"));
+        assert_eq!(
+            build_step("Step 2/3 : RUN make"),
+            Some("Step 2/3 : RUN make")
+        );
+        assert_eq!(build_step("--> Using cache 1a2b3c"), None);
+    }
+
+    #[test]
+    fn test_build_args() {
+        let options = BuildOptions {
+            build_args: [("VERSION".to_string(), "1.2".to_string())].into(),
+            target: Some("release".to_string()),
+            labels: [("wrkflw.action".to_string(), "./act".to_string())].into(),
+        };
+        assert_eq!(
+            build_args(Path::new("act/Dockerfile"), "img:1", &options),
+            vec![
+                "-f",
+                "act/Dockerfile",
+                "-t",
+                "img:1",
+                "--build-arg",
+                "VERSION=1.2",
+                "--target",
+                "release",
+                "--label",
+                "wrkflw.action=./act",
+                "act",
+            ]
+        );
+    }
+}
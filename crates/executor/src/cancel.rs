@@ -0,0 +1,211 @@
+//! Cancelling a run, or single jobs of it, from another thread. A run is cancelled
+//! through the `RunCancel` of its `ExecutionConfig`: jobs stop before their next
+//! step and finish as cancelled; cancelling the whole run also kills the containers
+//! and processes it still has running, leaving those of other runs be. The run then
+//! returns its result as usual, so callers still report it.
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+tokio::task_local! {
+    static CURRENT: RunCancel;
+}
+
+/// Cancels the runs given it; clones cancel the same runs. A new one for each run
+/// that should be cancelled on its own.
+#[derive(Debug, Clone, Default)]
+pub struct RunCancel {
+    token: CancellationToken,
+    jobs: Arc<Mutex<HashSet<String>>>,
+    active: Arc<AtomicUsize>,
+    containers: Arc<Mutex<Vec<(Engine, String)>>>,
+    processes: Arc<Mutex<Vec<u32>>>,
+}
+
+/// What runs a container
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Engine {
+    Docker,
+    Podman,
+}
+
+impl RunCancel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether a run given this is going
+    pub fn is_running(&self) -> bool {
+        self.active.load(Ordering::SeqCst) > 0
+    }
+
+    /// Cancel every job of the runs and kill what they're running
+    pub async fn cancel_run(&self) {
+        self.token.cancel();
+        wrkflw_logging::warning("Cancelling the run");
+
+        let (docker, podman): (Vec<_>, Vec<_>) = self
+            .containers
+            .lock()
+            .map(|mut containers| std::mem::take(&mut *containers))
+            .unwrap_or_default()
+            .into_iter()
+            .partition(|(engine, _)| *engine == Engine::Docker);
+        if !docker.is_empty() {
+            match bollard::Docker::connect_with_local_defaults() {
+                Ok(client) => {
+                    let ids = docker.into_iter().map(|(_, id)| id).collect();
+                    crate::docker::remove_containers(&client, ids).await
+                }
+                Err(e) => wrkflw_logging::error(&format!("Failed to stop containers: {}", e)),
+            }
+        }
+        crate::podman::remove_containers(podman.into_iter().map(|(_, id)| id).collect()).await;
+
+        let processes = self
+            .processes
+            .lock()
+            .map(|mut processes| std::mem::take(&mut *processes))
+            .unwrap_or_default();
+        wrkflw_runtime::emulation::kill_processes(processes).await;
+    }
+
+    /// Cancel `job` once its current step finishes
+    pub fn cancel_job(&self, job: &str) {
+        if let Ok(mut jobs) = self.jobs.lock() {
+            jobs.insert(job.to_string());
+        }
+        wrkflw_logging::warning(&format!("Cancelling job '{}'", job));
+    }
+
+    /// Whether the whole run was cancelled
+    pub fn is_run_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Completes once the whole run is cancelled
+    pub async fn cancelled(&self) {
+        self.token.cancelled().await
+    }
+
+    /// Whether `job`, or the whole run, was cancelled
+    pub fn is_cancelled(&self, job: &str) -> bool {
+        self.is_run_cancelled() || self.jobs.lock().is_ok_and(|jobs| jobs.contains(job))
+    }
+}
+
+/// Run `run` as a run `cancel` cancels, counted as going until it's done
+pub(crate) async fn cancellable_run<F: Future>(cancel: RunCancel, run: F) -> F::Output {
+    cancel.active.fetch_add(1, Ordering::SeqCst);
+    let _active = ActiveRun(cancel.active.clone());
+    let processes = cancel.processes.clone();
+    CURRENT
+        .scope(
+            cancel,
+            wrkflw_runtime::emulation::tracking_processes(processes, run),
+        )
+        .await
+}
+
+// Counts a run as going until dropped, also when the run is
+struct ActiveRun(Arc<AtomicUsize>);
+
+impl Drop for ActiveRun {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Keep container `id` among those the run going on has running
+pub(crate) fn track_container(engine: Engine, id: &str) {
+    let _ = CURRENT.try_with(|cancel| {
+        if let Ok(mut containers) = cancel.containers.lock() {
+            containers.push((engine, id.to_string()));
+        }
+    });
+}
+
+/// Drop container `id` from those the run going on has running
+pub(crate) fn untrack_container(engine: Engine, id: &str) {
+    let _ = CURRENT.try_with(|cancel| {
+        if let Ok(mut containers) = cancel.containers.lock() {
+            containers.retain(|(e, c)| *e != engine || c != id);
+        }
+    });
+}
+
+/// Whether `job`, or the whole run it's part of, was cancelled
+pub(crate) fn is_cancelled(job: &str) -> bool {
+    CURRENT
+        .try_with(|cancel| cancel.is_cancelled(job))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancels_only_the_given_job() {
+        let cancel = RunCancel::new();
+        cancel.cancel_job("cancel-test");
+        assert!(cancel.is_cancelled("cancel-test"));
+        assert!(!cancel.is_cancelled("cancel-test-other"));
+        assert!(!cancel.is_run_cancelled());
+    }
+
+    #[tokio::test]
+    async fn cancels_only_the_runs_given_it() {
+        let cancel = RunCancel::new();
+        let other = RunCancel::new();
+        cancel.token.cancel();
+
+        assert!(cancellable_run(cancel.clone(), async { is_cancelled("job") }).await);
+        assert!(!cancellable_run(other.clone(), async { is_cancelled("job") }).await);
+        assert!(!is_cancelled("job"));
+    }
+
+    #[tokio::test]
+    async fn tracks_what_each_run_has_running() {
+        let cancel = RunCancel::new();
+        let other = RunCancel::new();
+        cancellable_run(cancel.clone(), async {
+            track_container(Engine::Docker, "cancel-test-a");
+            track_container(Engine::Podman, "cancel-test-b");
+            untrack_container(Engine::Docker, "cancel-test-a");
+            wrkflw_runtime::emulation::track_process(u32::MAX);
+        })
+        .await;
+        cancellable_run(other.clone(), async {
+            track_container(Engine::Docker, "cancel-test-c");
+        })
+        .await;
+        wrkflw_runtime::emulation::untrack_process(u32::MAX);
+
+        assert_eq!(
+            *cancel.containers.lock().unwrap(),
+            vec![(Engine::Podman, "cancel-test-b".to_string())]
+        );
+        assert_eq!(*cancel.processes.lock().unwrap(), vec![u32::MAX]);
+        assert_eq!(
+            *other.containers.lock().unwrap(),
+            vec![(Engine::Docker, "cancel-test-c".to_string())]
+        );
+        assert!(other.processes.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn counts_the_runs_going() {
+        let cancel = RunCancel::new();
+        let running = cancellable_run(cancel.clone(), {
+            let cancel = cancel.clone();
+            async move { cancel.is_running() }
+        })
+        .await;
+        assert!(running);
+        assert!(!cancel.is_running());
+    }
+}
@@ -0,0 +1,400 @@
+//! Step `if:` conditions.
+//!
+//! Supports what step conditions are mostly made of: `success()`, `failure()`,
+//! `always()` and `cancelled()`, `!`, `&&`, `||`, parentheses, `==` and `!=`
+//! comparisons, and the references `ExpressionContext::lookup` resolves, such as
+//! `steps.<id>.outputs.<name>` or `env.<name>`. Like on GitHub, a condition without
+//! a status function only runs after the earlier steps succeeded.
+
+use crate::substitution::ExpressionContext;
+use std::collections::HashMap;
+
+/// How the job's steps so far went
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JobState {
+    pub failed: bool,
+    pub cancelled: bool,
+}
+
+/// Whether a step with `condition` runs. A condition wrkflw can't evaluate is
+/// warned about and treated as `success()`.
+pub fn step_should_run(
+    condition: Option<&str>,
+    state: JobState,
+    env: &HashMap<String, String>,
+) -> bool {
+    let succeeded = !state.failed && !state.cancelled;
+    let Some(condition) = condition else {
+        return succeeded;
+    };
+    match evaluate(condition, state, env) {
+        Ok(run) => run,
+        Err(e) => {
+            wrkflw_logging::warning(&format!(
+                "Can't evaluate condition '{}': {} - running the step if earlier steps succeeded",
+                condition, e
+            ));
+            succeeded
+        }
+    }
+}
+
+/// Evaluate a step condition, with or without `${{ }}`
+pub fn evaluate(
+    condition: &str,
+    state: JobState,
+    env: &HashMap<String, String>,
+) -> Result<bool, String> {
+    let condition = condition.trim();
+    let expression = condition
+        .strip_prefix("${{")
+        .and_then(|rest| rest.strip_suffix("}}"))
+        .unwrap_or(condition);
+
+    let tokens = tokenize(expression)?;
+    let checks_status = tokens
+        .iter()
+        .any(|token| matches!(token, Token::Word(word) if is_status_function(word)));
+    let mut parser = Parser {
+        tokens: &tokens,
+        pos: 0,
+        state,
+        expressions: ExpressionContext::from_env(env),
+        env,
+    };
+    let value = parser.or()?;
+    if parser.pos < tokens.len() {
+        return Err(format!("unexpected {}", tokens[parser.pos]));
+    }
+
+    let run = value.truthy();
+    Ok(if checks_status {
+        run
+    } else {
+        run && !state.failed && !state.cancelled
+    })
+}
+
+fn is_status_function(word: &str) -> bool {
+    matches!(word, "success()" | "failure()" | "always()" | "cancelled()")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Literal(String),
+    Not,
+    And,
+    Or,
+    Equal,
+    NotEqual,
+    Open,
+    Close,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "'{}'", word),
+            Token::Literal(literal) => write!(f, "'{}'", literal),
+            Token::Not => write!(f, "'!'"),
+            Token::And => write!(f, "'&&'"),
+            Token::Or => write!(f, "'||'"),
+            Token::Equal => write!(f, "'=='"),
+            Token::NotEqual => write!(f, "'!='"),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+        }
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '\'' => {
+                chars.next();
+                let mut literal = String::new();
+                loop {
+                    match chars.next() {
+                        // '' is an escaped quote
+                        Some('\'') if chars.peek() == Some(&'\'') => {
+                            chars.next();
+                            literal.push('\'');
+                        }
+                        Some('\'') => break,
+                        Some(c) => literal.push(c),
+                        None => return Err("unterminated string".to_string()),
+                    }
+                }
+                tokens.push(Token::Literal(literal));
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '!' | '=' | '&' | '|' => {
+                chars.next();
+                let token = match (c, chars.peek()) {
+                    ('!', Some('=')) => Token::NotEqual,
+                    ('!', _) => Token::Not,
+                    ('=', Some('=')) => Token::Equal,
+                    ('&', Some('&')) => Token::And,
+                    ('|', Some('|')) => Token::Or,
+                    _ => return Err(format!("unexpected '{}'", c)),
+                };
+                if token != Token::Not {
+                    chars.next();
+                }
+                tokens.push(token);
+            }
+            c if c.is_alphanumeric() || "_-.".contains(c) => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() || "_-.".contains(c)) {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                // Functions are only supported without arguments
+                if chars.peek() == Some(&'(') {
+                    chars.next();
+                    if chars.next() != Some(')') {
+                        return Err(format!("unsupported function '{}'", word));
+                    }
+                    word.push_str("()");
+                }
+                tokens.push(Token::Word(word));
+            }
+            c => return Err(format!("unexpected '{}'", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// A value of an expression
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Bool(bool),
+    String(String),
+}
+
+impl Value {
+    fn truthy(&self) -> bool {
+        match self {
+            Value::Bool(b) => *b,
+            Value::String(s) => !s.is_empty(),
+        }
+    }
+
+    fn text(&self) -> String {
+        match self {
+            Value::Bool(b) => b.to_string(),
+            Value::String(s) => s.clone(),
+        }
+    }
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    state: JobState,
+    expressions: ExpressionContext,
+    env: &'a HashMap<String, String>,
+}
+
+impl Parser<'_> {
+    fn next_is(&mut self, token: &Token) -> bool {
+        if self.tokens.get(self.pos) == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Value, String> {
+        let mut value = self.and()?;
+        while self.next_is(&Token::Or) {
+            let right = self.and()?;
+            value = Value::Bool(value.truthy() || right.truthy());
+        }
+        Ok(value)
+    }
+
+    fn and(&mut self) -> Result<Value, String> {
+        let mut value = self.not()?;
+        while self.next_is(&Token::And) {
+            let right = self.not()?;
+            value = Value::Bool(value.truthy() && right.truthy());
+        }
+        Ok(value)
+    }
+
+    fn not(&mut self) -> Result<Value, String> {
+        if self.next_is(&Token::Not) {
+            return Ok(Value::Bool(!self.not()?.truthy()));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Value, String> {
+        let left = self.operand()?;
+        let equal = if self.next_is(&Token::Equal) {
+            true
+        } else if self.next_is(&Token::NotEqual) {
+            false
+        } else {
+            return Ok(left);
+        };
+        let right = self.operand()?;
+        // Like GitHub, compare strings ignoring case
+        let same = left.text().to_lowercase() == right.text().to_lowercase();
+        Ok(Value::Bool(same == equal))
+    }
+
+    fn operand(&mut self) -> Result<Value, String> {
+        let Some(token) = self.tokens.get(self.pos) else {
+            return Err("unexpected end of condition".to_string());
+        };
+        self.pos += 1;
+        match token {
+            Token::Open => {
+                let value = self.or()?;
+                if !self.next_is(&Token::Close) {
+                    return Err("missing ')'".to_string());
+                }
+                Ok(value)
+            }
+            Token::Literal(literal) => Ok(Value::String(literal.clone())),
+            Token::Word(word) => self.word(word),
+            token => Err(format!("unexpected {}", token)),
+        }
+    }
+
+    fn word(&self, word: &str) -> Result<Value, String> {
+        let state = self.state;
+        Ok(match word {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            "null" => Value::String(String::new()),
+            "success()" => Value::Bool(!state.failed && !state.cancelled),
+            "failure()" => Value::Bool(state.failed),
+            "always()" => Value::Bool(true),
+            "cancelled()" => Value::Bool(state.cancelled),
+            _ if word.ends_with("()") => return Err(format!("unsupported function '{}'", word)),
+            _ if word.parse::<f64>().is_ok() => Value::String(word.to_string()),
+            _ => Value::String(
+                self.expressions
+                    .lookup(word, self.env)
+                    .ok_or_else(|| format!("unsupported reference '{}'", word))?,
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environment::{self, STEPS_CONTEXT_KEY};
+    use crate::substitution::StepContext;
+
+    const SUCCEEDED: JobState = JobState {
+        failed: false,
+        cancelled: false,
+    };
+    const FAILED: JobState = JobState {
+        failed: true,
+        cancelled: false,
+    };
+
+    fn env() -> HashMap<String, String> {
+        let mut env = HashMap::from([
+            ("GITHUB_REF".to_string(), "refs/heads/main".to_string()),
+            ("MATRIX_OS".to_string(), "ubuntu-latest".to_string()),
+        ]);
+        environment::record_step(
+            &mut env,
+            "tests",
+            StepContext {
+                outputs: HashMap::from([("coverage".to_string(), "high".to_string())]),
+                outcome: "failure".to_string(),
+                conclusion: "success".to_string(),
+            },
+        );
+        env
+    }
+
+    #[test]
+    fn test_status_functions() {
+        let env = env();
+        assert_eq!(evaluate("success()", FAILED, &env), Ok(false));
+        assert_eq!(evaluate("${{ failure() }}", FAILED, &env), Ok(true));
+        assert_eq!(evaluate("always()", FAILED, &env), Ok(true));
+        assert_eq!(evaluate("!cancelled()", FAILED, &env), Ok(true));
+        // Without a status function a condition implies success()
+        assert_eq!(evaluate("true", FAILED, &env), Ok(false));
+        assert_eq!(evaluate("true", SUCCEEDED, &env), Ok(true));
+        assert!(!step_should_run(None, FAILED, &env));
+        assert!(step_should_run(None, SUCCEEDED, &env));
+    }
+
+    #[test]
+    fn test_steps_context() {
+        let env = env();
+        assert!(env.contains_key(STEPS_CONTEXT_KEY));
+        assert_eq!(
+            evaluate("steps.tests.outcome == 'failure'", SUCCEEDED, &env),
+            Ok(true)
+        );
+        assert_eq!(
+            evaluate(
+                "${{ always() && steps.tests.conclusion != 'Success' }}",
+                FAILED,
+                &env
+            ),
+            Ok(false)
+        );
+        assert_eq!(
+            evaluate("steps.tests.outputs.coverage", SUCCEEDED, &env),
+            Ok(true)
+        );
+        assert_eq!(
+            evaluate("!steps.lint.outputs.coverage", SUCCEEDED, &env),
+            Ok(true)
+        );
+        assert_eq!(
+            evaluate(
+                "(matrix.os == 'windows-latest' || github.ref == 'refs/heads/main') && !failure()",
+                SUCCEEDED,
+                &env
+            ),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn test_unsupported_conditions() {
+        let env = env();
+        assert!(evaluate("needs.build.result == 'success'", SUCCEEDED, &env).is_err());
+        assert!(evaluate("contains(github.ref, 'main')", SUCCEEDED, &env).is_err());
+        assert!(evaluate("success() &&", SUCCEEDED, &env).is_err());
+        assert!(evaluate("'unterminated", SUCCEEDED, &env).is_err());
+        // Unsupported conditions run the step like success() would
+        assert!(step_should_run(
+            Some("needs.build.result == 'success'"),
+            SUCCEEDED,
+            &env
+        ));
+        assert!(!step_should_run(Some("hashFiles('x') != ''"), FAILED, &env));
+    }
+}
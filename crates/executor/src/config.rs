@@ -0,0 +1,393 @@
+//! Project settings read from `.wrkflw.toml` at the repository root.
+//!
+//! ```toml
+//! # Jobs whose `runs-on` labels are all listed here run in this image
+//! [[runners]]
+//! labels = ["self-hosted", "linux", "gpu"]
+//! image = "nvidia/cuda:12.4.1-runtime-ubuntu22.04"
+//!
+//! # ...or directly on this machine, whatever runtime was selected
+//! [[runners]]
+//! labels = ["self-hosted", "build-farm"]
+//! native = true
+//!
+//! # Colors of the TUI: a built-in theme or one defined below
+//! [tui]
+//! theme = "mine"
+//!
+//! [tui.themes.mine]
+//! base = "light"
+//! title = "#d75f00"
+//!
+//! # What jobs with `environment: production` get over the run's vars and secrets
+//! [environments.production]
+//! vars = { DEPLOY_URL = "https://example.com" }
+//! # `${{ secrets.API_KEY }}` is read as the secret PROD_API_KEY
+//! secrets = { API_KEY = "PROD_API_KEY" }
+//! # Protection rules of the GitHub environment, which are only warned about
+//! required_reviewers = ["octocat"]
+//! wait_timer = 30
+//!
+//! # Seconds container operations may take; 0 for no limit
+//! [timeouts]
+//! step = 1800
+//! pull = 300
+//!
+//! # Which actions and reusable workflows may be used (see `policy`)
+//! [actions]
+//! allow = ["actions/*", "my-org/*"]
+//! deny = ["*/unverified-*"]
+//!
+//! # How serious each validation rule's findings are, and which make
+//! # `wrkflw validate` fail
+//! [validation]
+//! fail_on = "warning"
+//! rules = { secret-leaks = "error", expressions = "off" }
+//!
+//! # What workflows must contain, checked by `wrkflw validate --policy`
+//! [policy.permissions]
+//! [policy.timeout-minutes]
+//! max = 60
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use crate::policy::{ActionPolicy, WorkflowPolicy};
+use crate::vars;
+use wrkflw_models::Severity;
+use wrkflw_runtime::timeouts::Timeouts;
+
+/// Name of the project configuration file
+pub const PROJECT_CONFIG_FILE: &str = ".wrkflw.toml";
+
+/// Contents of `.wrkflw.toml`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProjectConfig {
+    /// Local stand-ins for runners, tried in order
+    #[serde(default)]
+    pub runners: Vec<RunnerMapping>,
+    /// Settings of the TUI
+    #[serde(default)]
+    pub tui: TuiConfig,
+    /// Deployment environments by name
+    #[serde(default)]
+    pub environments: BTreeMap<String, EnvironmentConfig>,
+    /// Timeouts of container operations, over the defaults
+    #[serde(default)]
+    pub timeouts: Timeouts,
+    /// Which actions and reusable workflows may be used
+    #[serde(default)]
+    pub actions: ActionPolicy,
+    /// Severities of validation rules
+    #[serde(default)]
+    pub validation: ValidationConfig,
+    /// What workflows must contain
+    #[serde(default)]
+    pub policy: WorkflowPolicy,
+}
+
+/// The `[validation]` section of `.wrkflw.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ValidationConfig {
+    /// Least severity that makes validation fail; errors when unset
+    #[serde(default)]
+    pub fail_on: Option<Severity>,
+    /// Severity of the findings of each rule, by rule identifier
+    #[serde(default)]
+    pub rules: BTreeMap<String, RuleLevel>,
+}
+
+/// Severity a rule's findings are given, or `off` to drop them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+impl ValidationConfig {
+    /// The rules whose severity is changed, to their severity or `None` when off
+    pub fn severities(&self) -> HashMap<String, Option<Severity>> {
+        self.rules
+            .iter()
+            .map(|(rule, level)| {
+                let severity = match level {
+                    RuleLevel::Off => None,
+                    RuleLevel::Info => Some(Severity::Info),
+                    RuleLevel::Warning => Some(Severity::Warning),
+                    RuleLevel::Error => Some(Severity::Error),
+                };
+                (rule.clone(), severity)
+            })
+            .collect()
+    }
+}
+
+/// The `[tui]` section of `.wrkflw.toml`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TuiConfig {
+    /// Name of the theme to use, built-in or from `themes`
+    #[serde(default)]
+    pub theme: Option<String>,
+    /// User-defined themes: a palette role (or `base`, the theme to start
+    /// from) to a color name, `#rrggbb` or 256-color index
+    #[serde(default)]
+    pub themes: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// An `[environments.<name>]` section of `.wrkflw.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnvironmentConfig {
+    /// Variables of the environment, over the run's configuration variables
+    #[serde(default)]
+    pub vars: BTreeMap<String, String>,
+    /// Secrets of the environment: the name jobs use to the secret it is read
+    /// as, which can name a provider (`vault:PROD_API_KEY`)
+    #[serde(default)]
+    pub secrets: BTreeMap<String, String>,
+    /// Who has to approve a deployment on GitHub
+    #[serde(default)]
+    pub required_reviewers: Vec<String>,
+    /// Minutes GitHub waits before running a job in the environment
+    #[serde(default)]
+    pub wait_timer: Option<u64>,
+}
+
+/// A local stand-in for runners with the given labels
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RunnerMapping {
+    /// Labels of the runner; a job matches when it asks for a subset of them
+    pub labels: Vec<String>,
+    /// Image to run matching jobs in
+    #[serde(default)]
+    pub image: Option<String>,
+    /// Run matching jobs on the host instead of in a container
+    #[serde(default)]
+    pub native: bool,
+}
+
+impl RunnerMapping {
+    /// Whether a job with these `runs-on` labels can run here, which like on
+    /// GitHub needs every label to be present (case-insensitively)
+    pub fn matches(&self, runs_on: &[String]) -> bool {
+        !runs_on.is_empty()
+            && runs_on.iter().all(|wanted| {
+                self.labels
+                    .iter()
+                    .any(|label| label.eq_ignore_ascii_case(wanted))
+            })
+    }
+}
+
+impl ProjectConfig {
+    /// Read `.wrkflw.toml` from `dir`, or the defaults when there is none
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let path = dir.join(PROJECT_CONFIG_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::parse(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+    }
+
+    fn parse(content: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(content).map_err(|e| e.to_string())?;
+        for (idx, runner) in config.runners.iter().enumerate() {
+            let which = format!("runners[{}] ({})", idx, runner.labels.join(", "));
+            if runner.labels.is_empty() {
+                return Err(format!("{} has no labels", which));
+            }
+            match (&runner.image, runner.native) {
+                (Some(_), true) => return Err(format!("{} sets both `image` and `native`", which)),
+                (None, false) => return Err(format!("{} needs `image` or `native = true`", which)),
+                _ => {}
+            }
+        }
+        for (name, environment) in &config.environments {
+            for var in environment.vars.keys() {
+                vars::validate_name(var).map_err(|e| format!("environments.{}: {}", name, e))?;
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// The first runner mapping matching a job's `runs-on` labels
+pub fn find_runner<'a>(
+    runners: &'a [RunnerMapping],
+    runs_on: &[String],
+) -> Option<&'a RunnerMapping> {
+    runners.iter().find(|runner| runner.matches(runs_on))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(labels: &[&str]) -> Vec<String> {
+        labels.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_runners() {
+        let config = ProjectConfig::parse(
+            r#"
+            [[runners]]
+            labels = ["self-hosted", "linux", "gpu"]
+            image = "nvidia/cuda:12.4.1-runtime-ubuntu22.04"
+
+            [[runners]]
+            labels = ["self-hosted", "build-farm"]
+            native = true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.runners.len(), 2);
+        assert!(config.runners[1].native);
+
+        assert!(ProjectConfig::parse("").unwrap().runners.is_empty());
+        assert!(ProjectConfig::parse("[[runners]]\nlabels = [\"x\"]\n").is_err());
+        assert!(ProjectConfig::parse(
+            "[[runners]]\nlabels = [\"x\"]\nimage = \"a\"\nnative = true\n"
+        )
+        .is_err());
+        assert!(ProjectConfig::parse("[[runners]]\nlabels = [\"x\"]\nimgae = \"a\"\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_tui() {
+        let config = ProjectConfig::parse(
+            r##"
+            [tui]
+            theme = "mine"
+
+            [tui.themes.mine]
+            base = "light"
+            title = "#d75f00"
+            "##,
+        )
+        .unwrap();
+        assert_eq!(config.tui.theme.as_deref(), Some("mine"));
+        assert_eq!(config.tui.themes["mine"]["title"], "#d75f00");
+
+        assert!(ProjectConfig::parse("").unwrap().tui.theme.is_none());
+        assert!(ProjectConfig::parse("[tui]\ntheem = \"light\"\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_environments() {
+        let config = ProjectConfig::parse(
+            r#"
+            [environments.production]
+            vars = { DEPLOY_URL = "https://example.com" }
+            secrets = { API_KEY = "PROD_API_KEY" }
+            required_reviewers = ["octocat"]
+            wait_timer = 30
+
+            [environments.staging]
+            "#,
+        )
+        .unwrap();
+        let production = &config.environments["production"];
+        assert_eq!(production.vars["DEPLOY_URL"], "https://example.com");
+        assert_eq!(production.secrets["API_KEY"], "PROD_API_KEY");
+        assert_eq!(production.required_reviewers, ["octocat"]);
+        assert_eq!(production.wait_timer, Some(30));
+        assert_eq!(config.environments["staging"], EnvironmentConfig::default());
+
+        assert_eq!(config.environments.get("Production"), None);
+
+        assert!(ProjectConfig::parse("[environments.x]\nreviewers = [\"a\"]\n").is_err());
+        assert!(ProjectConfig::parse("[environments.x]\nvars = { GITHUB_X = \"a\" }\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_timeouts() {
+        let config = ProjectConfig::parse("[timeouts]\nstep = 1800\npull = 0\n").unwrap();
+        assert_eq!(config.timeouts.step, Some(1800));
+        assert_eq!(config.timeouts.pull(), None);
+        assert_eq!(config.timeouts.build, None);
+        assert!(ProjectConfig::parse("[timeouts]\nsteps = 10\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_actions() {
+        let config = ProjectConfig::parse(
+            "[actions]\nallow = [\"actions/*\"]\ndeny = [\"*/unverified-*\"]\n",
+        )
+        .unwrap();
+        assert_eq!(config.actions.allow, ["actions/*"]);
+        assert_eq!(config.actions.deny, ["*/unverified-*"]);
+        assert!(ProjectConfig::parse("").unwrap().actions.is_empty());
+        assert!(ProjectConfig::parse("[actions]\nallowed = [\"actions/*\"]\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_validation() {
+        let config = ProjectConfig::parse(
+            "[validation]\nfail_on = \"warning\"\nrules = { secret-leaks = \"error\", expressions = \"off\" }\n",
+        )
+        .unwrap();
+        assert_eq!(config.validation.fail_on, Some(Severity::Warning));
+        assert_eq!(
+            config.validation.severities(),
+            HashMap::from([
+                ("secret-leaks".to_string(), Some(Severity::Error)),
+                ("expressions".to_string(), None),
+            ])
+        );
+        assert!(ProjectConfig::parse("[validation]\nfail_on = \"fatal\"\n").is_err());
+        assert!(ProjectConfig::parse("[validation]\nrules = { jobs = \"loud\" }\n").is_err());
+    }
+
+    #[test]
+    fn test_find_runner() {
+        let config = ProjectConfig {
+            runners: vec![
+                RunnerMapping {
+                    labels: labels(&["self-hosted", "Linux", "gpu"]),
+                    image: Some("cuda".to_string()),
+                    native: false,
+                },
+                RunnerMapping {
+                    labels: labels(&["self-hosted", "linux", "farm"]),
+                    image: None,
+                    native: true,
+                },
+            ],
+            ..Default::default()
+        };
+        let runners = &config.runners;
+        assert_eq!(find_runner(&[], &labels(&["self-hosted"])), None);
+
+        let gpu = find_runner(runners, &labels(&["self-hosted", "gpu"])).unwrap();
+        assert_eq!(gpu.image.as_deref(), Some("cuda"));
+        assert!(
+            find_runner(runners, &labels(&["self-hosted", "farm"]))
+                .unwrap()
+                .native
+        );
+        // The first runner with all the labels wins
+        assert!(
+            !find_runner(runners, &labels(&["self-hosted", "linux"]))
+                .unwrap()
+                .native
+        );
+        assert_eq!(
+            find_runner(runners, &labels(&["self-hosted", "arm64"])),
+            None
+        );
+        assert_eq!(find_runner(runners, &labels(&["ubuntu-latest"])), None);
+        assert_eq!(find_runner(runners, &[]), None);
+    }
+}
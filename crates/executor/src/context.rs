@@ -0,0 +1,81 @@
+//! What the jobs of a run look up about it: settings of the command line and of
+//! `.wrkflw.toml`. They're kept here rather than in the environment jobs are given,
+//! which steps see.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::config::{EnvironmentConfig, RunnerMapping};
+use crate::filter::StepFilter;
+use crate::policy::ActionPolicy;
+use crate::runner::RunnerOsPolicy;
+use crate::workspace::{FileOwnership, WorkspaceStrategy};
+
+tokio::task_local! {
+    static CONTEXT: RefCell<Arc<RunContext>>;
+}
+
+/// Settings of a run for its jobs
+#[derive(Debug, Clone, Default)]
+pub struct RunContext {
+    /// Local stand-ins for runners, tried in order
+    pub runners: Vec<RunnerMapping>,
+    /// Deployment environments, by name
+    pub environments: BTreeMap<String, EnvironmentConfig>,
+    /// What actions and reusable workflows may be used, also by called workflows
+    pub action_policy: ActionPolicy,
+    /// What to do with Windows and macOS jobs
+    pub runner_os_policy: RunnerOsPolicy,
+    /// How Docker and Podman jobs get the project into their workspace
+    pub workspace: WorkspaceStrategy,
+    /// Who owns the files Docker and Podman jobs leave in their workspace
+    pub file_ownership: FileOwnership,
+    /// Nothing may be fetched over the network, with `--offline`
+    pub offline: bool,
+    /// Which steps of the jobs that run are run
+    pub steps: StepFilter,
+}
+
+/// Run `run` with a context of its own, empty until `set`
+pub(crate) async fn scoped<F: Future>(run: F) -> F::Output {
+    CONTEXT
+        .scope(RefCell::new(Arc::new(RunContext::default())), run)
+        .await
+}
+
+/// Set the context of the run going on, once known
+pub(crate) fn set(context: RunContext) {
+    let _ = CONTEXT.try_with(|current| *current.borrow_mut() = Arc::new(context));
+}
+
+/// The context of the run going on; empty outside of a run
+pub(crate) fn current() -> Arc<RunContext> {
+    CONTEXT
+        .try_with(|current| current.borrow().clone())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_context_is_per_run() {
+        let context = RunContext {
+            environments: BTreeMap::from([("production".to_string(), Default::default())]),
+            ..Default::default()
+        };
+        scoped(async {
+            assert!(current().environments.is_empty());
+            set(context);
+            assert!(current().environments.contains_key("production"));
+            // A run inside it, like a triggered pipeline, has its own
+            scoped(async { assert!(current().environments.is_empty()) }).await;
+            assert!(current().environments.contains_key("production"));
+        })
+        .await;
+        assert!(current().environments.is_empty());
+    }
+}
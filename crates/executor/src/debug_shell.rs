@@ -0,0 +1,103 @@
+//! `--debug-on-failure`: an interactive shell in the container of a failed step.
+//!
+//! The container has exited with the step's command, so it is committed to a
+//! throwaway image and the shell runs in a new container of that image, which
+//! has the step's environment, files and mounts (`--volumes-from`), starting in
+//! the step's working directory. The run goes on once the shell exits.
+
+use std::io::IsTerminal;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::process::Command;
+use tokio::sync::Mutex;
+use wrkflw_runtime::container::NetworkMode;
+
+/// Jobs failing at the same time take turns at the terminal
+static TERMINAL: Mutex<()> = Mutex::const_new(());
+
+/// Bash if the image has it, else sh
+const SHELL: &str = "command -v bash >/dev/null 2>&1 && exec bash || exec sh";
+
+/// Open a shell in a copy of `container`, which ran a failed step, with
+/// `program` (`docker` or `podman`); nothing happens without a terminal
+pub async fn open(program: &str, container: &str, working_dir: &Path, network_mode: NetworkMode) {
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        wrkflw_logging::warning(&format!(
+            "Not opening a debug shell in container {}: not running in a terminal",
+            container
+        ));
+        return;
+    }
+    let _turn = TERMINAL.lock().await;
+
+    let image = format!(
+        "wrkflw-debug-{}",
+        container
+            .trim_start_matches("wrkflw-")
+            .chars()
+            .take(12)
+            .collect::<String>()
+            .to_lowercase()
+    );
+    let committed = Command::new(program)
+        .args(["commit", container, &image])
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .await;
+    match committed {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => {
+            wrkflw_logging::error(&format!(
+                "Failed to open a debug shell: {} commit failed: {}",
+                program,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+            return;
+        }
+        Err(e) => {
+            wrkflw_logging::error(&format!("Failed to open a debug shell: {}", e));
+            return;
+        }
+    }
+
+    let working_dir = working_dir.to_string_lossy();
+    let mut args = vec![
+        "run",
+        "--rm",
+        "-it",
+        "--volumes-from",
+        container,
+        "-w",
+        &working_dir,
+        "--entrypoint",
+        "sh",
+    ];
+    if network_mode != NetworkMode::Bridge {
+        args.extend(["--network", network_mode.as_str()]);
+    }
+    args.extend([image.as_str(), "-c", SHELL]);
+
+    println!(
+        "\n🐞 The step failed; opening a shell in its container at {}. Exit the shell to continue the run.",
+        working_dir
+    );
+    let status = Command::new(program)
+        .args(&args)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()
+        .await;
+    if let Err(e) = status {
+        wrkflw_logging::error(&format!("Failed to open a debug shell: {}", e));
+    }
+    println!("🐞 Debug shell closed; continuing the run\n");
+
+    let _ = Command::new(program)
+        .args(["rmi", "-f", &image])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await;
+}
@@ -0,0 +1,167 @@
+//! Environments of GitLab pipelines. Jobs with an `environment:` don't deploy
+//! anywhere when run locally; each is recorded as the deployment it would make, with
+//! the name and URL its variables give the environment, e.g. a review app's
+//! `review/$CI_COMMIT_REF_SLUG`.
+
+use crate::engine::JobStatus;
+use crate::environment;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+use wrkflw_models::gitlab::Environment;
+use wrkflw_parser::workflow::JobEnvironment;
+
+lazy_static! {
+    static ref VARIABLE_PATTERN: Regex =
+        Regex::new(r"\$(?:\{([A-Za-z_][A-Za-z0-9_]*)\}|([A-Za-z_][A-Za-z0-9_]*))").unwrap();
+}
+
+/// A deployment a job would have made
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deployment {
+    pub environment: String,
+    pub job: String,
+    /// Where the deployment would be reached
+    pub url: Option<String>,
+    /// start, prepare, stop, verify or access
+    pub action: String,
+    /// Job that stops the environment
+    pub on_stop: Option<String>,
+    pub status: JobStatus,
+}
+
+/// `value` with the `$NAME` and `${NAME}` variables of `env` replaced; unknown ones
+/// expand to nothing, as in a job's shell
+pub fn expand(value: &str, env: &HashMap<String, String>) -> String {
+    VARIABLE_PATTERN
+        .replace_all(value, |captures: &regex::Captures| {
+            let name = captures.get(1).or(captures.get(2)).unwrap().as_str();
+            env.get(name).cloned().unwrap_or_default()
+        })
+        .into_owned()
+}
+
+/// The environment `environment` of a job whose variables are `env`, its name and
+/// URL expanded, with the predefined `CI_ENVIRONMENT_*` variables the job gets;
+/// the URL may use them too
+pub fn resolve(
+    environment: &Environment,
+    env: &HashMap<String, String>,
+) -> (JobEnvironment, HashMap<String, String>) {
+    let name = expand(environment.name(), env);
+    let tier = environment
+        .deployment_tier()
+        .unwrap_or_else(|| deployment_tier(&name));
+    let mut variables = HashMap::from([
+        ("CI_ENVIRONMENT_NAME".to_string(), name.clone()),
+        (
+            "CI_ENVIRONMENT_SLUG".to_string(),
+            environment::gitlab_slug(&name),
+        ),
+        (
+            "CI_ENVIRONMENT_ACTION".to_string(),
+            environment.action().to_string(),
+        ),
+        ("CI_ENVIRONMENT_TIER".to_string(), tier.to_string()),
+    ]);
+    let url = environment.url().map(|url| {
+        let mut env = env.clone();
+        env.extend(variables.clone());
+        expand(url, &env)
+    });
+    if let Some(url) = &url {
+        variables.insert("CI_ENVIRONMENT_URL".to_string(), url.clone());
+    }
+    (JobEnvironment { name, url }, variables)
+}
+
+/// The tier GitLab gives an environment named `name` that doesn't declare one
+fn deployment_tier(name: &str) -> &'static str {
+    let name = name.to_lowercase();
+    let is = |words: &[&str]| words.iter().any(|word| name.contains(word));
+    if is(&["prod", "live"]) {
+        "production"
+    } else if is(&["stag", "model", "demo"]) {
+        "staging"
+    } else if is(&["test", "qa", "uat"]) {
+        "testing"
+    } else if is(&["dev", "review", "trunk"]) {
+        "development"
+    } else {
+        "other"
+    }
+}
+
+impl fmt::Display for Deployment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self.status {
+            JobStatus::Success => "✅",
+            JobStatus::Failure => "❌",
+            JobStatus::Skipped => "⏭️",
+            JobStatus::Cancelled => "🚫",
+        };
+        let verb = match self.action.as_str() {
+            "stop" => "stopped by",
+            "prepare" => "prepared by",
+            "verify" | "access" => "used by",
+            _ => "deployed by",
+        };
+        write!(f, "{} {} ({} {})", status, self.environment, verb, self.job)?;
+        if let Some(url) = &self.url {
+            write!(f, " → {}", url)?;
+        }
+        if let Some(on_stop) = &self.on_stop {
+            write!(f, "; stop with {}", on_stop)?;
+        }
+        Ok(())
+    }
+}
+
+/// Summary of the `deployments` of a run, one per line
+pub fn summary(deployments: &[Deployment]) -> String {
+    let mut summary = String::from("Deployments:");
+    for deployment in deployments {
+        summary.push_str(&format!("\n  {}", deployment));
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn review_apps_are_named_after_their_branch() {
+        let environment: Environment = serde_yaml::from_str(
+            "name: review/$CI_COMMIT_REF_SLUG\nurl: https://${CI_ENVIRONMENT_SLUG}.example.com\non_stop: stop_review",
+        )
+        .unwrap();
+        let env = HashMap::from([(
+            "CI_COMMIT_REF_SLUG".to_string(),
+            "feature-login".to_string(),
+        )]);
+        let (resolved, env) = resolve(&environment, &env);
+        assert_eq!(resolved.name, "review/feature-login");
+        assert_eq!(
+            resolved.url.as_deref(),
+            Some("https://review-feature-login.example.com")
+        );
+        assert_eq!(env["CI_ENVIRONMENT_URL"], resolved.url.clone().unwrap());
+        assert_eq!(env["CI_ENVIRONMENT_TIER"], "development");
+        assert_eq!(env["CI_ENVIRONMENT_ACTION"], "start");
+
+        let deployment = Deployment {
+            environment: resolved.name,
+            job: "deploy_review".to_string(),
+            url: resolved.url,
+            action: environment.action().to_string(),
+            on_stop: environment.on_stop().map(str::to_string),
+            status: JobStatus::Success,
+        };
+        assert_eq!(
+            deployment.to_string(),
+            "✅ review/feature-login (deployed by deploy_review) → https://review-feature-login.example.com; stop with stop_review"
+        );
+    }
+}
@@ -1,3 +1,7 @@
+use crate::build;
+use crate::cancel;
+use crate::images::{ImageStore, PreparedImage, ENVIRONMENT_LABEL};
+use crate::pull::{self, PullPolicy, PullProgress};
 use async_trait::async_trait;
 use bollard::{
     container::{Config, CreateContainerOptions},
@@ -11,7 +15,11 @@ use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Mutex;
 use wrkflw_logging;
-use wrkflw_runtime::container::{ContainerError, ContainerOutput, ContainerRuntime};
+use wrkflw_runtime::container::{
+    BuildOptions, ContainerError, ContainerOutput, ContainerRuntime, NetworkMode,
+};
+use wrkflw_runtime::output::OutputCapture;
+use wrkflw_runtime::timeouts;
 use wrkflw_utils;
 use wrkflw_utils::fd;
 
@@ -22,9 +30,97 @@ static CREATED_NETWORKS: Lazy<Mutex<Vec<String>>> = Lazy::new(|| Mutex::new(Vec:
 static CUSTOMIZED_IMAGES: Lazy<Mutex<HashMap<String, String>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+/// cgroup limits applied to every job container
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResourceLimits {
+    /// Number of CPUs (may be fractional)
+    pub cpus: Option<f64>,
+    /// Memory limit in bytes
+    pub memory_bytes: Option<i64>,
+    /// Maximum number of processes
+    pub pids: Option<i64>,
+}
+
+impl ResourceLimits {
+    fn apply(&self, host_config: &mut HostConfig) {
+        if let Some(cpus) = self.cpus {
+            host_config.nano_cpus = Some((cpus * 1_000_000_000.0) as i64);
+        }
+        if let Some(memory) = self.memory_bytes {
+            host_config.memory = Some(memory);
+            // Disallow swap beyond the memory limit so the cap is effective
+            host_config.memory_swap = Some(memory);
+        }
+        if let Some(pids) = self.pids {
+            host_config.pids_limit = Some(pids);
+        }
+    }
+}
+
+/// How Docker runs job containers, the counterpart of [`crate::podman::PodmanOptions`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DockerRuntimeConfig {
+    /// Keep the containers of failed steps around for inspection
+    pub preserve_containers_on_failure: bool,
+    /// Open a shell in the container of a failed step before the run goes on
+    pub debug_on_failure: bool,
+    /// cgroup limits of job containers
+    pub limits: ResourceLimits,
+    /// Network mode of job containers
+    pub network_mode: NetworkMode,
+    /// When images are pulled, before a container is created
+    pub pull_policy: PullPolicy,
+    /// Bind mounts added to every job container, as for `docker run -v`
+    /// (`/host/path:/container/path[:ro]`)
+    pub extra_mounts: Vec<String>,
+    /// User commands run as, as for `docker run --user` (e.g. `1000:1000`);
+    /// the image's default when unset
+    pub user: Option<String>,
+    /// User namespace mode, as for `docker run --userns` (e.g. `host`)
+    pub userns_mode: Option<String>,
+}
+
+impl DockerRuntimeConfig {
+    fn apply(&self, host_config: &mut HostConfig) {
+        self.limits.apply(host_config);
+        if self.network_mode != NetworkMode::Bridge {
+            host_config.network_mode = Some(self.network_mode.as_str().to_string());
+            // host-gateway aliases are rejected without a bridge network
+            host_config.extra_hosts = None;
+        }
+        if !self.extra_mounts.is_empty() {
+            host_config
+                .binds
+                .get_or_insert_with(Vec::new)
+                .extend(self.extra_mounts.iter().cloned());
+        }
+        if let Some(userns_mode) = &self.userns_mode {
+            host_config.userns_mode = Some(userns_mode.clone());
+        }
+    }
+}
+
+/// Parse a memory size such as `512m`, `2g` or `1048576` into bytes
+pub fn parse_memory_limit(input: &str) -> Result<i64, String> {
+    let lower = input.trim().to_ascii_lowercase();
+    let value = lower.strip_suffix('b').unwrap_or(&lower);
+    let (digits, multiplier) = match value.chars().last() {
+        Some('k') => (&value[..value.len() - 1], 1i64 << 10),
+        Some('m') => (&value[..value.len() - 1], 1i64 << 20),
+        Some('g') => (&value[..value.len() - 1], 1i64 << 30),
+        _ => (value, 1),
+    };
+    digits
+        .parse::<i64>()
+        .ok()
+        .filter(|n| *n > 0)
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| format!("Invalid memory limit '{}'", input))
+}
+
 pub struct DockerRuntime {
     docker: Docker,
-    preserve_containers_on_failure: bool,
+    config: DockerRuntimeConfig,
 }
 
 impl DockerRuntime {
@@ -33,14 +129,42 @@ impl DockerRuntime {
     }
 
     pub fn new_with_config(preserve_containers_on_failure: bool) -> Result<Self, ContainerError> {
+        Self::new_with_limits(preserve_containers_on_failure, ResourceLimits::default())
+    }
+
+    pub fn new_with_limits(
+        preserve_containers_on_failure: bool,
+        limits: ResourceLimits,
+    ) -> Result<Self, ContainerError> {
+        Self::new_with_options(DockerRuntimeConfig {
+            preserve_containers_on_failure,
+            limits,
+            ..Default::default()
+        })
+    }
+
+    pub fn new_with_options(config: DockerRuntimeConfig) -> Result<Self, ContainerError> {
         let docker = Docker::connect_with_local_defaults().map_err(|e| {
             ContainerError::ContainerStart(format!("Failed to connect to Docker: {}", e))
         })?;
 
-        Ok(DockerRuntime {
-            docker,
-            preserve_containers_on_failure,
-        })
+        Ok(DockerRuntime { docker, config })
+    }
+
+    pub fn config(&self) -> &DockerRuntimeConfig {
+        &self.config
+    }
+
+    /// Run job containers with the given network mode
+    pub fn with_network_mode(mut self, network_mode: NetworkMode) -> Self {
+        self.config.network_mode = network_mode;
+        self
+    }
+
+    /// Pull images as the given policy says
+    pub fn with_pull_policy(mut self, pull_policy: PullPolicy) -> Self {
+        self.config.pull_policy = pull_policy;
+        self
     }
 
     // Add a method to store and retrieve customized images (e.g., with Python installed)
@@ -266,7 +390,8 @@ impl DockerRuntime {
 
         // Build the customized image
         let image_tag = format!("wrkflw-{}-{}", language, version.unwrap_or("latest"));
-        self.build_image(&dockerfile_path, &image_tag).await?;
+        self.build_image(&dockerfile_path, &image_tag, &BuildOptions::default())
+            .await?;
 
         // Store the customized image
         Self::set_language_specific_image("", language, version, &image_tag);
@@ -275,9 +400,22 @@ impl DockerRuntime {
     }
 }
 
+/// Whether `docker buildx` is installed, so that images are built with BuildKit
+fn buildx_available() -> bool {
+    static AVAILABLE: Lazy<bool> = Lazy::new(|| {
+        std::process::Command::new("docker")
+            .args(["buildx", "version"])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    });
+    *AVAILABLE
+}
+
 pub fn is_available() -> bool {
-    // Use a very short timeout for the entire availability check
-    let overall_timeout = std::time::Duration::from_secs(3);
+    // Use a short timeout for the entire availability check
+    let overall_timeout = timeouts::timeouts().availability();
 
     // Spawn a thread with the timeout to prevent blocking the main thread
     let handle = std::thread::spawn(move || {
@@ -398,7 +536,7 @@ pub fn is_available() -> bool {
     // Manual implementation of join with timeout
     let start = std::time::Instant::now();
 
-    while start.elapsed() < overall_timeout {
+    while overall_timeout.is_none_or(|limit| start.elapsed() < limit) {
         if handle.is_finished() {
             return match handle.join() {
                 Ok(result) => result,
@@ -417,11 +555,12 @@ pub fn is_available() -> bool {
     false
 }
 
-// Add container to tracking
+// Add container to tracking, also as one of the run going on
 pub fn track_container(id: &str) {
     if let Ok(mut containers) = RUNNING_CONTAINERS.lock() {
         containers.push(id.to_string());
     }
+    cancel::track_container(cancel::Engine::Docker, id);
 }
 
 // Remove container from tracking
@@ -429,6 +568,7 @@ pub fn untrack_container(id: &str) {
     if let Ok(mut containers) = RUNNING_CONTAINERS.lock() {
         containers.retain(|c| c != id);
     }
+    cancel::untrack_container(cancel::Engine::Docker, id);
 }
 
 // Add network to tracking
@@ -494,17 +634,20 @@ pub async fn cleanup_containers(docker: &Docker) -> Result<(), String> {
             }
         };
 
-    if containers_to_cleanup.is_empty() {
-        return Ok(());
+    remove_containers(docker, containers_to_cleanup).await;
+    Ok(())
+}
+
+// Stop and remove the given containers, like those of a single run
+pub async fn remove_containers(docker: &Docker, containers: Vec<String>) {
+    if containers.is_empty() {
+        return;
     }
 
-    wrkflw_logging::info(&format!(
-        "Cleaning up {} containers",
-        containers_to_cleanup.len()
-    ));
+    wrkflw_logging::info(&format!("Cleaning up {} containers", containers.len()));
 
     // Process each container with a timeout
-    for container_id in containers_to_cleanup {
+    for container_id in containers {
         // First try to stop the container
         match tokio::time::timeout(
             std::time::Duration::from_millis(1000),
@@ -542,8 +685,6 @@ pub async fn cleanup_containers(docker: &Docker) -> Result<(), String> {
         // Always untrack the container whether or not we succeeded to avoid future cleanup attempts
         untrack_container(&container_id);
     }
-
-    Ok(())
 }
 
 // Clean up all tracked networks
@@ -639,19 +780,26 @@ impl ContainerRuntime for DockerRuntime {
         // Print detailed debugging info
         wrkflw_logging::info(&format!("Docker: Running container with image: {}", image));
 
-        // Add a global timeout for all Docker operations to prevent freezing
-        let timeout_duration = std::time::Duration::from_secs(360); // Increased outer timeout to 6 minutes
-
-        // Run the entire container operation with a timeout
-        match tokio::time::timeout(
-            timeout_duration,
+        // Run the entire container operation with a timeout to prevent freezing
+        let limit = timeouts::timeouts().step();
+        match timeouts::within(
+            limit,
             self.run_container_inner(image, cmd, env_vars, working_dir, volumes),
         )
         .await
         {
-            Ok(result) => result,
+            Ok(result) => {
+                let (output, failed_container) = result?;
+                if let Some(container) = failed_container {
+                    self.debug_failed_container(&container, working_dir).await;
+                }
+                Ok(output)
+            }
             Err(_) => {
-                wrkflw_logging::error("Docker operation timed out after 360 seconds");
+                wrkflw_logging::error(&format!(
+                    "Docker operation timed out after {} seconds",
+                    limit.unwrap_or_default().as_secs()
+                ));
                 Err(ContainerError::ContainerExecution(
                     "Operation timed out".to_string(),
                 ))
@@ -660,33 +808,30 @@ impl ContainerRuntime for DockerRuntime {
     }
 
     async fn pull_image(&self, image: &str) -> Result<(), ContainerError> {
-        // Add a timeout for pull operations
-        let timeout_duration = std::time::Duration::from_secs(30);
-
-        match tokio::time::timeout(timeout_duration, self.pull_image_inner(image)).await {
-            Ok(result) => result,
-            Err(_) => {
-                wrkflw_logging::warning(&format!(
-                    "Pull of image {} timed out, continuing with existing image",
-                    image
-                ));
-                // Return success to allow continuing with existing image
-                Ok(())
-            }
-        }
+        let present = self.docker.inspect_image(image).await.is_ok();
+        pull::ensure_image(
+            self.config.pull_policy,
+            image,
+            present,
+            self.pull_image_inner(image),
+        )
+        .await
     }
 
-    async fn build_image(&self, dockerfile: &Path, tag: &str) -> Result<(), ContainerError> {
-        // Add a timeout for build operations
-        let timeout_duration = std::time::Duration::from_secs(120); // 2 minutes timeout for builds
-
-        match tokio::time::timeout(timeout_duration, self.build_image_inner(dockerfile, tag)).await
-        {
+    async fn build_image(
+        &self,
+        dockerfile: &Path,
+        tag: &str,
+        options: &BuildOptions,
+    ) -> Result<(), ContainerError> {
+        let limit = timeouts::timeouts().build();
+        match timeouts::within(limit, self.build_image_inner(dockerfile, tag, options)).await {
             Ok(result) => result,
             Err(_) => {
                 wrkflw_logging::error(&format!(
-                    "Building image {} timed out after 120 seconds",
-                    tag
+                    "Building image {} timed out after {} seconds",
+                    tag,
+                    limit.unwrap_or_default().as_secs()
                 ));
                 Err(ContainerError::ImageBuild(
                     "Operation timed out".to_string(),
@@ -701,10 +846,26 @@ impl ContainerRuntime for DockerRuntime {
         version: Option<&str>,
         additional_packages: Option<Vec<String>>,
     ) -> Result<String, ContainerError> {
-        // Check if we already have a customized image for this language and version
-        let key = format!("{}-{}", language, version.unwrap_or("latest"));
-        if let Some(customized_image) = Self::get_language_specific_image("", language, version) {
-            return Ok(customized_image);
+        // Reuse the image built for this environment by an earlier run, if still there
+        let store = ImageStore::new(ImageStore::default_file());
+        let image = PreparedImage::new(
+            "docker",
+            language,
+            version,
+            additional_packages.as_deref().unwrap_or_default(),
+        );
+        if let Some(prepared) = store.lookup(&image) {
+            if self.docker.inspect_image(&prepared.tag).await.is_ok() {
+                wrkflw_logging::info(&format!(
+                    "Reusing image {} for {}",
+                    prepared.tag,
+                    prepared.environment()
+                ));
+                if let Err(e) = store.record(&prepared) {
+                    wrkflw_logging::warning(&e);
+                }
+                return Ok(prepared.tag);
+            }
         }
 
         // Create a temporary Dockerfile for customization
@@ -820,13 +981,20 @@ impl ContainerRuntime for DockerRuntime {
         })?;
 
         // Build the customized image
-        let image_tag = format!("wrkflw-{}-{}", language, version.unwrap_or("latest"));
-        self.build_image(&dockerfile_path, &image_tag).await?;
+        let options = BuildOptions {
+            labels: [(ENVIRONMENT_LABEL.to_string(), image.environment())].into(),
+            ..Default::default()
+        };
+        self.build_image(&dockerfile_path, &image.tag, &options)
+            .await?;
 
         // Store the customized image
-        Self::set_language_specific_image("", language, version, &image_tag);
+        Self::set_language_specific_image("", language, version, &image.tag);
+        if let Err(e) = store.record(&image) {
+            wrkflw_logging::warning(&e);
+        }
 
-        Ok(image_tag)
+        Ok(image.tag)
     }
 }
 
@@ -839,14 +1007,8 @@ impl DockerRuntime {
         env_vars: &[(&str, &str)],
         working_dir: &Path,
         volumes: &[(&Path, &Path)],
-    ) -> Result<ContainerOutput, ContainerError> {
-        // First, try to pull the image if it's not available locally
-        if let Err(e) = self.pull_image_inner(image).await {
-            wrkflw_logging::warning(&format!(
-                "Failed to pull image {}: {}. Attempting to continue with existing image.",
-                image, e
-            ));
-        }
+    ) -> Result<(ContainerOutput, Option<String>), ContainerError> {
+        self.pull_image(image).await?;
 
         // Collect environment variables
         let mut env: Vec<String> = env_vars
@@ -899,7 +1061,7 @@ impl DockerRuntime {
         });
 
         // Configure host configuration based on platform
-        let host_config = if is_windows_image {
+        let mut host_config = if is_windows_image {
             HostConfig {
                 binds: Some(binds),
                 isolation: Some(bollard::models::HostConfigIsolationEnum::PROCESS),
@@ -908,9 +1070,12 @@ impl DockerRuntime {
         } else {
             HostConfig {
                 binds: Some(binds),
+                // Lets steps reach host-side services such as the OIDC token endpoint
+                extra_hosts: Some(vec!["host.docker.internal:host-gateway".to_string()]),
                 ..Default::default()
             }
         };
+        self.config.apply(&mut host_config);
 
         // Create container config with platform-specific settings
         let mut config = Config {
@@ -923,7 +1088,8 @@ impl DockerRuntime {
             user: if is_windows_image {
                 Some("ContainerAdministrator".to_string())
             } else {
-                None // Don't specify user for macOS emulation - use default root user
+                // Unless configured, don't specify user for macOS emulation - use default root user
+                self.config.user.clone()
             },
             // Map appropriate entrypoint for different platforms
             entrypoint: if is_macos_emu {
@@ -988,9 +1154,9 @@ impl DockerRuntime {
             }
         }
 
-        // Wait for container to finish with a timeout (300 seconds)
-        let wait_result = tokio::time::timeout(
-            std::time::Duration::from_secs(300),
+        // Wait for container to finish; run_container bounds the whole operation
+        let wait_result = timeouts::within(
+            timeouts::timeouts().step(),
             self.docker
                 .wait_container::<String>(&container.id, None)
                 .collect::<Vec<_>>(),
@@ -1008,43 +1174,38 @@ impl DockerRuntime {
             }
         };
 
-        // Get logs with a timeout
-        let logs_result = tokio::time::timeout(
-            std::time::Duration::from_secs(10),
-            self.docker
-                .logs::<String>(&container.id, None)
-                .collect::<Vec<_>>(),
-        )
-        .await;
-
-        let mut stdout = String::new();
-        let mut stderr = String::new();
-
-        if let Ok(logs) = logs_result {
-            for log in logs.into_iter().flatten() {
+        // Get logs with a timeout, keeping only what the output limit allows
+        let mut stdout = OutputCapture::new();
+        let mut stderr = OutputCapture::new();
+        let mut logs = self.docker.logs::<String>(&container.id, None);
+        let logs_result = tokio::time::timeout(std::time::Duration::from_secs(10), async {
+            while let Some(log) = logs.next().await {
                 match log {
-                    bollard::container::LogOutput::StdOut { message } => {
-                        stdout.push_str(&String::from_utf8_lossy(&message));
-                    }
-                    bollard::container::LogOutput::StdErr { message } => {
-                        stderr.push_str(&String::from_utf8_lossy(&message));
-                    }
+                    Ok(bollard::container::LogOutput::StdOut { message }) => stdout.push(&message),
+                    Ok(bollard::container::LogOutput::StdErr { message }) => stderr.push(&message),
                     _ => {}
                 }
             }
-        } else {
+        })
+        .await;
+        if logs_result.is_err() {
             wrkflw_logging::warning("Retrieving container logs timed out");
         }
-
-        // Clean up container with a timeout, but preserve on failure if configured
-        if exit_code == 0 || !self.preserve_containers_on_failure {
+        let stdout = stdout.into_string();
+        let stderr = stderr.into_string();
+
+        // Clean up container with a timeout, but preserve on failure if configured;
+        // a failed container to debug is cleaned up once the shell exits
+        let debug = exit_code != 0 && self.config.debug_on_failure;
+        let keep = self.config.preserve_containers_on_failure || debug;
+        if exit_code == 0 || !keep {
             let _ = tokio::time::timeout(
                 std::time::Duration::from_secs(10),
                 self.docker.remove_container(&container.id, None),
             )
             .await;
             untrack_container(&container.id);
-        } else {
+        } else if !debug {
             // Container failed and we want to preserve it for debugging
             wrkflw_logging::info(&format!(
                 "Preserving container {} for debugging (exit code: {}). Use 'docker exec -it {} bash' to inspect.",
@@ -1065,11 +1226,30 @@ impl DockerRuntime {
             wrkflw_logging::debug(&format!("STDERR: {}", stderr));
         }
 
-        Ok(ContainerOutput {
-            stdout,
-            stderr,
-            exit_code,
-        })
+        Ok((
+            ContainerOutput {
+                stdout,
+                stderr,
+                exit_code,
+            },
+            debug.then_some(container.id),
+        ))
+    }
+
+    /// Open a debug shell in a failed step's container, then remove it unless
+    /// it is to be preserved
+    async fn debug_failed_container(&self, container: &str, working_dir: &Path) {
+        crate::debug_shell::open("docker", container, working_dir, self.config.network_mode).await;
+        if self.config.preserve_containers_on_failure {
+            wrkflw_logging::info(&format!("Preserving container {} for debugging", container));
+        } else {
+            let _ = tokio::time::timeout(
+                std::time::Duration::from_secs(10),
+                self.docker.remove_container(container, None),
+            )
+            .await;
+        }
+        untrack_container(container);
     }
 
     async fn pull_image_inner(&self, image: &str) -> Result<(), ContainerError> {
@@ -1078,71 +1258,93 @@ impl DockerRuntime {
             ..Default::default()
         };
 
+        let started = std::time::Instant::now();
         let mut stream = self.docker.create_image(Some(options), None, None);
 
+        let mut progress = PullProgress::new(image);
+        let mut pulled = Ok(());
         while let Some(result) = stream.next().await {
-            if let Err(e) = result {
-                return Err(ContainerError::ImagePull(e.to_string()));
+            let info = match result {
+                Ok(info) => info,
+                Err(e) => {
+                    pulled = Err(ContainerError::ImagePull(e.to_string()));
+                    break;
+                }
+            };
+            let (Some(layer), Some(status)) = (info.id.as_deref(), info.status.as_deref()) else {
+                continue;
+            };
+            match status {
+                "Pulling fs layer" | "Waiting" => progress.layer(layer),
+                "Downloading" => {
+                    let detail = info.progress_detail.unwrap_or_default();
+                    progress.downloading(
+                        layer,
+                        detail.current.unwrap_or(0).max(0) as u64,
+                        detail.total.unwrap_or(0).max(0) as u64,
+                    );
+                }
+                "Download complete" | "Pull complete" | "Already exists" => {
+                    progress.layer_done(layer)
+                }
+                _ => {}
             }
         }
+        crate::timing::record_image_pull(started.elapsed());
+        if pulled.is_ok() {
+            progress.finish();
+        }
 
-        Ok(())
+        pulled
     }
 
-    async fn build_image_inner(&self, dockerfile: &Path, tag: &str) -> Result<(), ContainerError> {
-        let _context_dir = dockerfile.parent().unwrap_or(Path::new("."));
+    async fn build_image_inner(
+        &self,
+        dockerfile: &Path,
+        tag: &str,
+        options: &BuildOptions,
+    ) -> Result<(), ContainerError> {
+        // BuildKit, through buildx, when it's installed
+        if buildx_available() {
+            let mut cmd = tokio::process::Command::new("docker");
+            cmd.args(["buildx", "build", "--load", "--progress=plain"])
+                .args(build::build_args(dockerfile, tag, options));
+            return build::run_build(cmd, tag).await;
+        }
 
+        // Otherwise the classic builder, sent the Dockerfile's directory as context
+        if let Some(target) = &options.target {
+            wrkflw_logging::warning(&format!(
+                "Building all of {} for {}: building up to stage '{}' needs docker buildx",
+                dockerfile.display(),
+                tag,
+                target
+            ));
+        }
+        let context_dir = dockerfile.parent().unwrap_or(Path::new("."));
+        let dockerfile_name = dockerfile
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "Dockerfile".to_string());
         let tar_buffer = {
             let mut tar_builder = tar::Builder::new(Vec::new());
-
-            // Add Dockerfile to tar
-            if let Ok(file) = std::fs::File::open(dockerfile) {
-                let mut header = tar::Header::new_gnu();
-                let metadata = file.metadata().map_err(|e| {
-                    ContainerError::ContainerExecution(format!(
-                        "Failed to get file metadata: {}",
-                        e
-                    ))
-                })?;
-                let modified_time = metadata
-                    .modified()
-                    .map_err(|e| {
-                        ContainerError::ContainerExecution(format!(
-                            "Failed to get file modification time: {}",
-                            e
-                        ))
-                    })?
-                    .elapsed()
-                    .map_err(|e| {
-                        ContainerError::ContainerExecution(format!(
-                            "Failed to get elapsed time since modification: {}",
-                            e
-                        ))
-                    })?
-                    .as_secs();
-                header.set_size(metadata.len());
-                header.set_mode(0o644);
-                header.set_mtime(modified_time);
-                header.set_cksum();
-
-                tar_builder
-                    .append_data(&mut header, "Dockerfile", file)
-                    .map_err(|e| ContainerError::ImageBuild(e.to_string()))?;
-            } else {
-                return Err(ContainerError::ImageBuild(format!(
-                    "Cannot open Dockerfile at {}",
-                    dockerfile.display()
-                )));
-            }
-
+            tar_builder.append_dir_all(".", context_dir).map_err(|e| {
+                ContainerError::ImageBuild(format!(
+                    "Cannot read the build context {}: {}",
+                    context_dir.display(),
+                    e
+                ))
+            })?;
             tar_builder
                 .into_inner()
                 .map_err(|e| ContainerError::ImageBuild(e.to_string()))?
         };
 
-        let options = bollard::image::BuildImageOptions {
-            dockerfile: "Dockerfile",
-            t: tag,
+        let build_options = bollard::image::BuildImageOptions {
+            dockerfile: dockerfile_name,
+            t: tag.to_string(),
+            buildargs: options.build_args.clone().into_iter().collect(),
+            labels: options.labels.clone().into_iter().collect(),
             q: false,
             nocache: false,
             rm: true,
@@ -1151,15 +1353,23 @@ impl DockerRuntime {
 
         let mut stream = self
             .docker
-            .build_image(options, None, Some(tar_buffer.into()));
+            .build_image(build_options, None, Some(tar_buffer.into()));
 
         while let Some(result) = stream.next().await {
-            match result {
-                Ok(_) => {
-                    // For verbose output, we could log the build progress here
-                }
-                Err(e) => {
-                    return Err(ContainerError::ImageBuild(e.to_string()));
+            let info = result.map_err(|e| ContainerError::ImageBuild(e.to_string()))?;
+            if let Some(error) = info.error {
+                return Err(ContainerError::ImageBuild(format!(
+                    "Failed to build image {}: {}",
+                    tag, error
+                )));
+            }
+            for line in info.stream.iter().flat_map(|stream| stream.lines()) {
+                match build::build_step(line) {
+                    Some(step) => {
+                        wrkflw_logging::info(&format!("Building image {}: {}", tag, step))
+                    }
+                    None if !line.trim().is_empty() => wrkflw_logging::debug(line),
+                    None => {}
                 }
             }
         }
@@ -1168,6 +1378,19 @@ impl DockerRuntime {
     }
 }
 
+/// Remove an image from Docker; one already gone counts as removed
+pub async fn remove_image(tag: &str) -> Result<(), String> {
+    let docker = Docker::connect_with_local_defaults()
+        .map_err(|e| format!("Failed to connect to Docker: {}", e))?;
+    match docker.remove_image(tag, None, None).await {
+        Ok(_) => Ok(()),
+        Err(bollard::errors::Error::DockerResponseServerError {
+            status_code: 404, ..
+        }) => Ok(()),
+        Err(e) => Err(format!("Failed to remove image {}: {}", tag, e)),
+    }
+}
+
 // Public accessor functions for testing
 #[cfg(test)]
 pub fn get_tracked_containers() -> Vec<String> {
@@ -1186,3 +1409,59 @@ pub fn get_tracked_networks() -> Vec<String> {
         vec![]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_memory_limit() {
+        assert_eq!(parse_memory_limit("1024"), Ok(1024));
+        assert_eq!(parse_memory_limit("512m"), Ok(512 * 1024 * 1024));
+        assert_eq!(parse_memory_limit("2G"), Ok(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_memory_limit("64kb"), Ok(64 * 1024));
+        assert!(parse_memory_limit("lots").is_err());
+        assert!(parse_memory_limit("0").is_err());
+    }
+
+    #[test]
+    fn test_resource_limits_apply() {
+        let limits = ResourceLimits {
+            cpus: Some(0.5),
+            memory_bytes: Some(1 << 20),
+            pids: Some(100),
+        };
+        let mut host_config = HostConfig::default();
+        limits.apply(&mut host_config);
+        assert_eq!(host_config.nano_cpus, Some(500_000_000));
+        assert_eq!(host_config.memory, Some(1 << 20));
+        assert_eq!(host_config.memory_swap, Some(1 << 20));
+        assert_eq!(host_config.pids_limit, Some(100));
+    }
+
+    #[test]
+    fn test_runtime_config_apply() {
+        let config = DockerRuntimeConfig {
+            network_mode: NetworkMode::None,
+            extra_mounts: vec!["/srv/cache:/cache:ro".to_string()],
+            userns_mode: Some("host".to_string()),
+            ..Default::default()
+        };
+        let mut host_config = HostConfig {
+            binds: Some(vec!["/repo:/github/workspace".to_string()]),
+            extra_hosts: Some(vec!["host.docker.internal:host-gateway".to_string()]),
+            ..Default::default()
+        };
+        config.apply(&mut host_config);
+        assert_eq!(
+            host_config.binds,
+            Some(vec![
+                "/repo:/github/workspace".to_string(),
+                "/srv/cache:/cache:ro".to_string()
+            ])
+        );
+        assert_eq!(host_config.network_mode.as_deref(), Some("none"));
+        assert_eq!(host_config.extra_hosts, None);
+        assert_eq!(host_config.userns_mode.as_deref(), Some("host"));
+    }
+}
@@ -5,23 +5,53 @@ use regex;
 use serde_yaml::Value;
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use thiserror::Error;
+use tracing::Instrument;
 
 use ignore::{gitignore::GitignoreBuilder, Match};
 
+use crate::action_cache::{self, ActionCache};
+use crate::cancel;
+use crate::checkout;
+use crate::condition;
+use crate::config::{self, ProjectConfig};
+use crate::context::{self, RunContext};
 use crate::dependency;
+use crate::deployments::Deployment;
 use crate::docker;
 use crate::environment;
+use crate::filter::RunFilter;
+use crate::gitlab_vars::GitlabVariables;
+use crate::logs;
+use crate::oidc;
+use crate::paths;
 use crate::podman;
+use crate::policy::ActionPolicy;
+use crate::post;
+use crate::progress;
+use crate::pull::PullPolicy;
+use crate::resume::{self, JobProgress, Resume};
+use crate::runner::{self, RunnerOs, RunnerOsPolicy, RunnerPlacement};
+use crate::shell;
+use crate::stages;
+use crate::substitution::{self, ExpressionContext, StepContext};
+use crate::timing::{self, JobTiming};
+use crate::triggers::{self, Downstream};
+use crate::workspace::{FileOwnership, WorkspaceStrategy};
 use wrkflw_logging;
 use wrkflw_matrix::MatrixCombination;
 use wrkflw_models::gitlab::Pipeline;
 use wrkflw_parser::gitlab::{self, parse_pipeline};
 use wrkflw_parser::workflow::{self, parse_workflow, ActionInfo, Job, WorkflowDefinition};
-use wrkflw_runtime::container::ContainerRuntime;
+use wrkflw_runtime::container::{BuildOptions, ContainerRuntime, NetworkMode};
 use wrkflw_runtime::emulation;
+use wrkflw_runtime::sandbox::SandboxConfig;
+use wrkflw_runtime::timeouts::{self, Timeouts};
+use wrkflw_runtime::toolcache;
 use wrkflw_secrets::{SecretConfig, SecretManager, SecretMasker, SecretSubstitution};
 
 #[allow(unused_variables, unused_assignments)]
@@ -30,26 +60,111 @@ pub async fn execute_workflow(
     workflow_path: &Path,
     config: ExecutionConfig,
 ) -> Result<ExecutionResult, ExecutionError> {
+    if config.log_files {
+        logs::start(workflow_path);
+    }
+    wrkflw_runtime::output::set_output_limit(config.output_limit);
+    let project_timeouts = std::env::current_dir()
+        .ok()
+        .and_then(|dir| ProjectConfig::load(&dir).ok())
+        .map(|project| project.timeouts)
+        .unwrap_or_default();
+    timeouts::set_timeouts(project_timeouts.overridden_by(config.timeouts));
     wrkflw_logging::info(&format!("Executing workflow: {}", workflow_path.display()));
     wrkflw_logging::info(&format!("Runtime: {:?}", config.runtime_type));
 
     // Determine if this is a GitLab CI/CD pipeline or GitHub Actions workflow
     let is_gitlab = is_gitlab_pipeline(workflow_path);
 
-    if is_gitlab {
-        execute_gitlab_pipeline(workflow_path, config.clone()).await
-    } else {
-        execute_github_workflow(workflow_path, config.clone()).await
+    // Everything logged while running carries the workflow and runtime
+    let span = tracing::info_span!(
+        "workflow",
+        workflow = %workflow_path.file_name().unwrap_or_default().to_string_lossy(),
+        runtime = ?config.runtime_type,
+    );
+    let from = config.resume.clone().unwrap_or_default();
+    // Boxed, as the run is too large a future for the stack of a test's thread
+    let run = Box::pin(resume::resumable_run(from.clone(), async {
+        if is_gitlab {
+            execute_gitlab_pipeline(workflow_path, config.clone()).await
+        } else {
+            execute_github_workflow(workflow_path, config.clone()).await
+        }
+    }));
+    let ((result, failed), duration) = cancel::cancellable_run(
+        config.cancel.clone(),
+        context::scoped(timing::timed_run(run)),
+    )
+    .instrument(span)
+    .await;
+    let result = result.map(|result| {
+        // Jobs skipped for having succeeded in the run being rerun stay succeeded
+        // for a rerun of this one
+        let mut succeeded = from.succeeded;
+        succeeded.extend(
+            result
+                .jobs
+                .iter()
+                .filter(|job| job.status == JobStatus::Success)
+                .map(|job| job.name.clone()),
+        );
+        ExecutionResult {
+            duration,
+            resume: Resume {
+                succeeded,
+                jobs: failed,
+            },
+            ..result
+        }
+    });
+    if config.log_files {
+        logs::finish(result.as_ref().ok());
+    }
+    result
+}
+
+/// Result of a job that was skipped before it started
+fn skipped_job(name: &str, reason: &str) -> JobResult {
+    JobResult {
+        name: name.to_string(),
+        status: JobStatus::Skipped,
+        steps: Vec::new(),
+        logs: format!("Job skipped: {}", reason),
+        environment: None,
+        timing: JobTiming::default(),
+    }
+}
+
+/// Why jobs and steps that succeeded in the run being rerun are skipped
+const RERUN_SKIP_REASON: &str = "it succeeded in the run being rerun";
+
+/// Result of a step that succeeded in the run being rerun
+fn rerun_skipped_step(step: &workflow::Step, idx: usize) -> StepResult {
+    StepResult {
+        name: step_display_name(step, idx),
+        status: StepStatus::Skipped,
+        output: format!("Skipped: {}", RERUN_SKIP_REASON),
+        duration: Duration::ZERO,
+    }
+}
+
+/// Result of a job cancelled before it started
+fn cancelled_job(name: &str) -> JobResult {
+    JobResult {
+        name: name.to_string(),
+        status: JobStatus::Cancelled,
+        steps: Vec::new(),
+        logs: "Job cancelled".to_string(),
+        environment: None,
+        timing: JobTiming::default(),
     }
 }
 
 /// Determine if a file is a GitLab CI/CD pipeline
-fn is_gitlab_pipeline(path: &Path) -> bool {
-    // Check the file name
-    if let Some(file_name) = path.file_name() {
-        if let Some(file_name_str) = file_name.to_str() {
-            return file_name_str == ".gitlab-ci.yml" || file_name_str.ends_with("gitlab-ci.yml");
-        }
+pub(crate) fn is_gitlab_pipeline(path: &Path) -> bool {
+    // Check the file name and location
+    if path.file_name().is_some() {
+        return wrkflw_utils::is_gitlab_pipeline_file(path);
     }
 
     // If file name check fails, try to read and determine by content
@@ -80,43 +195,155 @@ async fn execute_github_workflow(
     // 1. Parse workflow file
     let workflow = parse_workflow(workflow_path)?;
 
+    if let Some(files) = &config.changed_files {
+        let filter = paths::PathFilter::for_event(&workflow.on_raw, "push");
+        let reason = if !workflow.on.iter().any(|event| event == "push") {
+            Some("the workflow isn't triggered by pushes")
+        } else if filter.is_some_and(|filter| !filter.matches(files)) {
+            Some("no changed files match the push path filters")
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            wrkflw_logging::info(&format!("Skipping the workflow: {}", reason));
+            let mut jobs: Vec<&String> = workflow.jobs.keys().collect();
+            jobs.sort();
+            return Ok(ExecutionResult {
+                jobs: jobs
+                    .into_iter()
+                    .map(|job| skipped_job(job, reason))
+                    .collect(),
+                failure_details: None,
+                duration: Duration::ZERO,
+                resume: Resume::default(),
+                deployments: Vec::new(),
+            });
+        }
+    }
+
     // 2. Resolve job dependencies and create execution plan
-    let execution_plan = dependency::resolve_dependencies(&workflow)?;
+    let mut execution_plan = dependency::resolve_dependencies(&workflow)?;
+    config
+        .filter
+        .check(&workflow)
+        .map_err(ExecutionError::Execution)?;
+    let left_out = config.filter.apply(&workflow, &mut execution_plan);
 
     // 3. Initialize appropriate runtime
-    let runtime = initialize_runtime(
-        config.runtime_type.clone(),
-        config.preserve_containers_on_failure,
-    )?;
+    let runtime = initialize_runtime(&config)?;
 
     // Create a temporary workspace directory
     let workspace_dir = tempfile::tempdir()
         .map_err(|e| ExecutionError::Execution(format!("Failed to create workspace: {}", e)))?;
 
     // 4. Set up GitHub-like environment
-    let mut env_context = environment::create_github_context(&workflow, workspace_dir.path());
-
+    let mut env_context = environment::create_github_context(
+        &workflow,
+        workflow_path,
+        config.event.as_deref(),
+        workspace_dir.path(),
+    );
+    let mut inputs: HashMap<String, String> = workflow
+        .dispatch_inputs()
+        .into_iter()
+        .filter_map(|input| Some((input.name, input.default?)))
+        .collect();
+    inputs.extend(config.inputs.clone());
+    if !inputs.is_empty() {
+        env_context.insert(
+            environment::INPUTS_CONTEXT_KEY.to_string(),
+            serde_json::to_string(&inputs).unwrap_or_default(),
+        );
+    }
+    if !config.vars.is_empty() {
+        env_context.insert(
+            environment::VARS_CONTEXT_KEY.to_string(),
+            serde_json::to_string(&config.vars).unwrap_or_default(),
+        );
+    }
     // Add runtime mode to environment
     env_context.insert(
         "WRKFLW_RUNTIME_MODE".to_string(),
-        match config.runtime_type {
-            RuntimeType::Emulation => "emulation".to_string(),
-            RuntimeType::SecureEmulation => "secure_emulation".to_string(),
-            RuntimeType::Docker => "docker".to_string(),
-            RuntimeType::Podman => "podman".to_string(),
-        },
+        config.runtime_type.mode().to_string(),
     );
 
+    // Jobs look up what to do with Windows/macOS runners, self-hosted labels and
+    // deployment environments in the run's context
+    let project_dir = std::env::current_dir().map_err(|e| {
+        ExecutionError::Execution(format!("Failed to get current directory: {}", e))
+    })?;
+    let project_config = ProjectConfig::load(&project_dir).map_err(ExecutionError::Execution)?;
+
+    // Nothing runs if the workflow uses actions the project doesn't allow
+    check_action_policy(&project_config.actions, &workflow, workflow_path)?;
+    context::set(RunContext {
+        runners: project_config.runners.clone(),
+        environments: project_config.environments.clone(),
+        action_policy: project_config.actions.clone(),
+        runner_os_policy: config.runner_os_policy.clone(),
+        // Jobs mount the project itself or a directory of their own, as the
+        // strategy says
+        workspace: config.workspace,
+        file_ownership: config.file_ownership,
+        offline: config.offline,
+        steps: config.filter.steps.clone(),
+    });
+
+    // Offline, everything to be fetched has to be cached already; list it all up front
+    if config.offline {
+        let cache = ActionCache::new(ActionCache::default_root());
+        let missing = cache.missing_workflows(&workflow);
+        if !missing.is_empty() {
+            return Err(ExecutionError::Execution(format!(
+                "Running offline, but these reusable workflows are not in the action cache at {}:\n{}\nRun once without --offline to cache them",
+                cache.root().display(),
+                missing
+                    .iter()
+                    .map(|repo_ref| format!("  - {}", repo_ref))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )));
+        }
+    }
+
     // Add flag to hide GitHub action messages when in emulation mode
     env_context.insert(
         "WRKFLW_HIDE_ACTION_MESSAGES".to_string(),
         "true".to_string(),
     );
 
+    // Make a GITHUB_TOKEN available to steps that reference it, masked like any secret
+    let (github_token, token_source) = environment::resolve_github_token(
+        config.github_token.as_deref(),
+        config.github_token_from_env,
+    );
+    wrkflw_logging::info(&format!("Using GITHUB_TOKEN from {}", token_source));
+    if matches!(token_source, environment::GithubTokenSource::Generated) {
+        wrkflw_logging::warning(
+            "No GITHUB_TOKEN given; steps calling the GitHub API will fail to authenticate (pass --github-token or --github-token-from-env)",
+        );
+        env_context.insert(
+            environment::GITHUB_TOKEN_PLACEHOLDER_KEY.to_string(),
+            "true".to_string(),
+        );
+    }
+    let secret_masker = Mutex::new(SecretMasker::new());
+    mask_secret(&secret_masker, &github_token);
+    env_context.insert(
+        environment::GITHUB_TOKEN_CONTEXT_KEY.to_string(),
+        github_token,
+    );
+
+    // Workflow env, then what the user gave over it
+    environment::apply_env_layer(&mut env_context, &workflow.env);
+    environment::set_user_env(&mut env_context, &config.env);
+
     // Setup GitHub environment files
-    environment::setup_github_environment_files(workspace_dir.path()).map_err(|e| {
-        ExecutionError::Execution(format!("Failed to setup GitHub env files: {}", e))
-    })?;
+    environment::setup_github_environment_files(workspace_dir.path())
+        .and_then(|_| environment::write_event_payload(&env_context, &inputs))
+        .map_err(|e| {
+            ExecutionError::Execution(format!("Failed to setup GitHub env files: {}", e))
+        })?;
 
     // 5. Initialize secrets management
     let secret_manager = if let Some(secrets_config) = &config.secrets_config {
@@ -136,10 +363,30 @@ async fn execute_github_workflow(
         })?)
     };
 
-    let secret_masker = SecretMasker::new();
+    // Serve OIDC tokens for jobs granted `id-token: write`; the server stops when dropped
+    let _oidc_server = if oidc::workflow_requests_id_token(&workflow) {
+        let (host, bind_all) = match config.runtime_type {
+            RuntimeType::Docker if docker::is_available() => ("host.docker.internal", true),
+            RuntimeType::Podman if podman::is_available() => ("host.containers.internal", true),
+            _ => ("127.0.0.1", false),
+        };
+        let server =
+            oidc::OidcTokenServer::start(oidc::OidcConfig::from_env(), &env_context, bind_all)
+                .await
+                .map_err(|e| {
+                    ExecutionError::Execution(format!("Failed to start OIDC token endpoint: {}", e))
+                })?;
+        env_context.extend(server.env_vars(host));
+        Some(server)
+    } else {
+        None
+    };
 
     // 6. Execute jobs according to the plan
-    let mut results = Vec::new();
+    let mut results: Vec<JobResult> = left_out
+        .iter()
+        .map(|(job, reason)| skipped_job(job, reason))
+        .collect();
     let mut has_failures = false;
     let mut failure_details = String::new();
 
@@ -168,6 +415,9 @@ async fn execute_github_workflow(
                         failure_details.push_str(&format!("  ❌ {}: {}\n", step.name, step.output));
                     }
                 }
+            } else if job_result.status == JobStatus::Cancelled {
+                has_failures = true;
+                failure_details.push_str(&format!("\n🚫 Job cancelled: {}\n", job_result.name));
             }
         }
 
@@ -186,6 +436,9 @@ async fn execute_github_workflow(
         } else {
             None
         },
+        duration: Duration::ZERO,
+        resume: Resume::default(),
+        deployments: Vec::new(),
     })
 }
 
@@ -199,37 +452,90 @@ async fn execute_gitlab_pipeline(
     // 1. Parse the GitLab pipeline file
     let pipeline = parse_pipeline(pipeline_path)
         .map_err(|e| ExecutionError::Parse(format!("Failed to parse GitLab pipeline: {}", e)))?;
+    run_gitlab_pipeline(&pipeline, pipeline_path, config, &[]).await
+}
 
+/// Run `pipeline`, read from `pipeline_path`; a child pipeline has the trigger jobs
+/// leading to it in `trigger_path`
+async fn run_gitlab_pipeline(
+    pipeline: &Pipeline,
+    pipeline_path: &Path,
+    config: ExecutionConfig,
+    trigger_path: &[String],
+) -> Result<ExecutionResult, ExecutionError> {
     // 2. Convert the GitLab pipeline to a format compatible with the workflow executor
-    let workflow = gitlab::convert_to_workflow_format(&pipeline);
+    let mut workflow = gitlab::convert_to_workflow_format(pipeline);
+    let project_dir = std::env::current_dir().map_err(|e| {
+        ExecutionError::Execution(format!("Failed to get current directory: {}", e))
+    })?;
+    let variables = GitlabVariables::load(&project_dir, config.gitlab_vars.clone())
+        .map_err(ExecutionError::Execution)?;
 
     // 3. Resolve job dependencies based on stages
-    let execution_plan = resolve_gitlab_dependencies(&pipeline, &workflow)?;
+    let mut execution_plan = resolve_gitlab_dependencies(pipeline)?;
+    config
+        .filter
+        .check(&workflow)
+        .map_err(ExecutionError::Execution)?;
+    let left_out = config.filter.apply(&workflow, &mut execution_plan);
+
+    // Jobs whose `changes:` a simulated push doesn't satisfy aren't in the pipeline
+    let mut results: Vec<JobResult> = left_out
+        .iter()
+        .map(|(job, reason)| skipped_job(job, reason))
+        .collect();
+    if let Some(files) = &config.changed_files {
+        let mut skipped: Vec<&String> = pipeline
+            .jobs
+            .iter()
+            .filter(|(_, job)| job.template != Some(true) && !paths::gitlab_job_runs(job, files))
+            .map(|(name, _)| name)
+            .collect();
+        skipped.sort();
+        for batch in &mut execution_plan {
+            batch.retain(|name| !skipped.contains(&name));
+        }
+        execution_plan.retain(|batch| !batch.is_empty());
+        results.extend(
+            skipped
+                .into_iter()
+                .map(|name| skipped_job(name, "no changed files match its `changes:`")),
+        );
+    }
 
     // 4. Initialize appropriate runtime
-    let runtime = initialize_runtime(
-        config.runtime_type.clone(),
-        config.preserve_containers_on_failure,
-    )?;
+    let runtime = initialize_runtime(&config)?;
 
     // Create a temporary workspace directory
     let workspace_dir = tempfile::tempdir()
         .map_err(|e| ExecutionError::Execution(format!("Failed to create workspace: {}", e)))?;
 
     // 5. Set up GitLab-like environment
-    let mut env_context = create_gitlab_context(&pipeline, workspace_dir.path());
+    let mut env_context = create_gitlab_context(
+        pipeline,
+        pipeline_path,
+        config.event.as_deref(),
+        workspace_dir.path(),
+        &variables,
+    );
+    variables.apply_to_jobs(pipeline, &env_context, &mut workflow);
+    environment::set_user_env(&mut env_context, &config.env);
 
     // Add runtime mode to environment
     env_context.insert(
         "WRKFLW_RUNTIME_MODE".to_string(),
-        match config.runtime_type {
-            RuntimeType::Emulation => "emulation".to_string(),
-            RuntimeType::SecureEmulation => "secure_emulation".to_string(),
-            RuntimeType::Docker => "docker".to_string(),
-            RuntimeType::Podman => "podman".to_string(),
-        },
+        config.runtime_type.mode().to_string(),
     );
 
+    // Jobs mount the project itself or a directory of their own, as the strategy
+    // says, and run the steps the filter selects
+    context::set(RunContext {
+        workspace: config.workspace,
+        file_ownership: config.file_ownership,
+        steps: config.filter.steps.clone(),
+        ..Default::default()
+    });
+
     // Setup environment files
     environment::setup_github_environment_files(workspace_dir.path()).map_err(|e| {
         ExecutionError::Execution(format!("Failed to setup environment files: {}", e))
@@ -253,42 +559,113 @@ async fn execute_gitlab_pipeline(
         })?)
     };
 
-    let secret_masker = SecretMasker::new();
+    let secret_masker = Mutex::new(SecretMasker::new());
 
-    // 7. Execute jobs according to the plan
-    let mut results = Vec::new();
+    // 7. Execute the jobs stage by stage; the jobs of a stage run in parallel
     let mut has_failures = false;
     let mut failure_details = String::new();
+    let mut deployments = Vec::new();
+
+    let mut pipeline_stages: Vec<(&str, Vec<Vec<String>>)> = Vec::new();
+    for batch in execution_plan {
+        let stage = stages::stage_of(&pipeline.jobs[&batch[0]]);
+        match pipeline_stages.last_mut() {
+            Some((name, batches)) if *name == stage => batches.push(batch),
+            _ => pipeline_stages.push((stage, vec![batch])),
+        }
+    }
+    let stage_count = pipeline_stages.len();
 
-    for job_batch in execution_plan {
-        // Execute jobs in parallel if they don't depend on each other
-        let job_results = execute_job_batch(
-            &job_batch,
-            &workflow,
-            runtime.as_ref(),
-            &env_context,
-            config.verbose,
-            secret_manager.as_ref(),
-            Some(&secret_masker),
-        )
-        .await?;
-
-        // Check for job failures and collect details
-        for job_result in &job_results {
-            if job_result.status == JobStatus::Failure {
-                has_failures = true;
-                failure_details.push_str(&format!("\n❌ Job failed: {}\n", job_result.name));
+    for (idx, (stage, batches)) in pipeline_stages.into_iter().enumerate() {
+        // Stages of child pipelines are shown under their trigger jobs
+        let stage = trigger_path
+            .iter()
+            .map(String::as_str)
+            .chain([stage])
+            .collect::<Vec<_>>()
+            .join(" › ");
+        progress::stage_started(&stage, idx, stage_count, batches.concat());
+        let mut stage_results = Vec::new();
+
+        for mut job_batch in batches {
+            // Jobs that don't run given their `when:` and the jobs before them
+            job_batch.retain(|name| {
+                let job = &pipeline.jobs[name];
+                match stages::skip_reason(job, has_failures, config.run_manual) {
+                    Some(reason) => {
+                        stage_results.push(skipped_job(name, reason));
+                        false
+                    }
+                    None => true,
+                }
+            });
+            if job_batch.is_empty() {
+                continue;
+            }
 
-                // Add step details for failed jobs
-                for step in &job_result.steps {
-                    if step.status == StepStatus::Failure {
-                        failure_details.push_str(&format!("  ❌ {}: {}\n", step.name, step.output));
+            // Trigger jobs run their downstream pipelines alongside the other jobs
+            let (trigger_jobs, job_batch): (Vec<String>, Vec<String>) = job_batch
+                .into_iter()
+                .partition(|name| pipeline.jobs[name].trigger.is_some());
+            let (job_results, trigger_results) = future::join(
+                execute_job_batch(
+                    &job_batch,
+                    &workflow,
+                    runtime.as_ref(),
+                    &env_context,
+                    config.verbose,
+                    secret_manager.as_ref(),
+                    Some(&secret_masker),
+                ),
+                future::join_all(
+                    trigger_jobs
+                        .iter()
+                        .map(|name| run_trigger_job(name, pipeline, &config, trigger_path)),
+                ),
+            )
+            .await;
+            let mut job_results = job_results?;
+            for (result, child_deployments) in trigger_results {
+                job_results.push(result);
+                deployments.extend(child_deployments);
+            }
+            deployments.extend(
+                job_results
+                    .iter()
+                    .filter_map(|result| gitlab_deployment(pipeline, &workflow, result)),
+            );
+
+            // Check for job failures and collect details
+            for job_result in &mut job_results {
+                if job_result.status == JobStatus::Failure {
+                    if stages::job_of(pipeline, &job_result.name)
+                        .is_some_and(stages::allows_failure)
+                    {
+                        job_result
+                            .logs
+                            .push_str("\n⚠️ Job failed, but is allowed to fail\n");
+                        continue;
+                    }
+                    has_failures = true;
+                    failure_details.push_str(&format!("\n❌ Job failed: {}\n", job_result.name));
+
+                    // Add step details for failed jobs
+                    for step in &job_result.steps {
+                        if step.status == StepStatus::Failure {
+                            failure_details
+                                .push_str(&format!("  ❌ {}: {}\n", step.name, step.output));
+                        }
                     }
+                } else if job_result.status == JobStatus::Cancelled {
+                    has_failures = true;
+                    failure_details.push_str(&format!("\n🚫 Job cancelled: {}\n", job_result.name));
                 }
             }
+            stage_results.extend(job_results);
         }
 
-        results.extend(job_results);
+        progress::stage_finished(&stage, stage_status(pipeline, &stage_results));
+        results.extend(stage_results);
     }
 
     // If there were failures, add detailed failure information to the result
@@ -303,119 +680,206 @@ async fn execute_gitlab_pipeline(
         } else {
             None
         },
+        duration: Duration::ZERO,
+        resume: Resume::default(),
+        deployments,
     })
 }
 
-/// Create an environment context for GitLab CI/CD pipeline execution
-fn create_gitlab_context(pipeline: &Pipeline, workspace_dir: &Path) -> HashMap<String, String> {
-    let mut env_context = HashMap::new();
-
-    // Add GitLab CI/CD environment variables
-    env_context.insert("CI".to_string(), "true".to_string());
-    env_context.insert("GITLAB_CI".to_string(), "true".to_string());
+/// Create an environment context for GitLab CI/CD pipeline execution: the
+/// pipeline's variables, over the predefined ones, and those of `variables`
+pub(crate) fn create_gitlab_context(
+    pipeline: &Pipeline,
+    pipeline_path: &Path,
+    event: Option<&str>,
+    workspace_dir: &Path,
+    variables: &GitlabVariables,
+) -> HashMap<String, String> {
+    let predefined = environment::create_gitlab_predefined(pipeline_path, event, workspace_dir);
+    let mut env_context = variables.pipeline_env(pipeline, predefined);
 
     // Add custom environment variable to indicate use in wrkflw
     env_context.insert("WRKFLW_CI".to_string(), "true".to_string());
 
-    // Add workspace directory
-    env_context.insert(
-        "CI_PROJECT_DIR".to_string(),
-        workspace_dir.to_string_lossy().to_string(),
-    );
-
     // Also add the workspace as the GitHub workspace for compatibility with emulation runtime
     env_context.insert(
         "GITHUB_WORKSPACE".to_string(),
         workspace_dir.to_string_lossy().to_string(),
     );
 
-    // Add global variables from the pipeline
-    if let Some(variables) = &pipeline.variables {
-        for (key, value) in variables {
-            env_context.insert(key.clone(), value.clone());
-        }
-    }
-
     env_context
 }
 
 /// Resolve GitLab CI/CD pipeline dependencies
-fn resolve_gitlab_dependencies(
+pub(crate) fn resolve_gitlab_dependencies(
     pipeline: &Pipeline,
-    workflow: &WorkflowDefinition,
 ) -> Result<Vec<Vec<String>>, ExecutionError> {
-    // For GitLab CI/CD pipelines, jobs within the same stage can run in parallel,
-    // but jobs in different stages run sequentially
-
-    // Get stages from the pipeline or create a default one
-    let stages = match &pipeline.stages {
-        Some(defined_stages) => defined_stages.clone(),
-        None => vec![
-            "build".to_string(),
-            "test".to_string(),
-            "deploy".to_string(),
-        ],
-    };
-
-    // Create an execution plan based on stages
-    let mut execution_plan = Vec::new();
-
-    // For each stage, collect the jobs that belong to it
-    for stage in stages {
-        let mut stage_jobs = Vec::new();
-
-        for (job_name, job) in &pipeline.jobs {
-            // Skip template jobs
-            if let Some(true) = job.template {
-                continue;
-            }
-
-            // Get the job's stage, or assume "test" if not specified
-            let default_stage = "test".to_string();
-            let job_stage = job.stage.as_ref().unwrap_or(&default_stage);
-
-            // If the job belongs to the current stage, add it to the batch
-            if job_stage == &stage {
-                stage_jobs.push(job_name.clone());
-            }
-        }
+    // Jobs of a stage run in parallel, after the jobs of the stages before and the
+    // jobs of their own stage they need
+    stages::job_batches(pipeline).map_err(ExecutionError::Execution)
+}
 
-        if !stage_jobs.is_empty() {
-            execution_plan.push(stage_jobs);
-        }
+/// The deployment the job of `pipeline` that ended with `result` would have made,
+/// if it has an environment; `workflow` has the environment it resolved to
+fn gitlab_deployment(
+    pipeline: &Pipeline,
+    workflow: &WorkflowDefinition,
+    result: &JobResult,
+) -> Option<Deployment> {
+    let environment = stages::job_of(pipeline, &result.name)?
+        .environment
+        .as_ref()?;
+    if result.status == JobStatus::Skipped {
+        return None;
     }
+    let resolved = workflow
+        .jobs
+        .get(&result.name)
+        .or_else(|| workflow.jobs.get(result.name.split_once(" (")?.0))
+        .and_then(|job| job.environment.clone())?;
+    Some(Deployment {
+        environment: resolved.name,
+        job: result.name.clone(),
+        url: resolved.url,
+        action: environment.action().to_string(),
+        on_stop: environment.on_stop().map(str::to_string),
+        status: result.status.clone(),
+    })
+}
 
-    // Also create a batch for jobs without a stage
-    let mut stageless_jobs = Vec::new();
-
-    for (job_name, job) in &pipeline.jobs {
-        // Skip template jobs
-        if let Some(true) = job.template {
-            continue;
+/// Run the trigger job `name` of `pipeline`: its child pipeline, whose jobs become
+/// the job's steps, with the deployments the child made; the pipeline of another
+/// project is only checked
+async fn run_trigger_job(
+    name: &str,
+    pipeline: &Pipeline,
+    config: &ExecutionConfig,
+    trigger_path: &[String],
+) -> (JobResult, Vec<Deployment>) {
+    let job = &pipeline.jobs[name];
+    let Some(trigger) = &job.trigger else {
+        return (skipped_job(name, "it triggers no pipeline"), Vec::new());
+    };
+    let failed = |reason: String| JobResult {
+        status: JobStatus::Failure,
+        logs: format!("Job failed: {}", reason),
+        ..skipped_job(name, "")
+    };
+    let downstream = std::env::current_dir()
+        .map_err(|e| format!("Failed to get current directory: {}", e))
+        .and_then(|dir| triggers::downstream(name, trigger, &dir));
+    let files = match downstream {
+        Ok(Downstream::Child(files)) => files,
+        Ok(Downstream::Project(project)) => {
+            let reason = format!(
+                "it triggers a pipeline of project {}, which doesn't run locally",
+                project
+            );
+            wrkflw_logging::warning(&format!("Job '{}' skipped: {}", name, reason));
+            return (skipped_job(name, &reason), Vec::new());
         }
+        Err(e) => return (failed(e), Vec::new()),
+    };
+    if trigger_path.len() >= triggers::MAX_DEPTH {
+        let reason = format!(
+            "child pipelines nest at most {} levels deep",
+            triggers::MAX_DEPTH
+        );
+        return (failed(reason), Vec::new());
+    }
+    let child = match triggers::child_pipeline(&files) {
+        Ok(child) => child,
+        Err(e) => return (failed(e), Vec::new()),
+    };
 
-        if job.stage.is_none() {
-            stageless_jobs.push(job_name.clone());
+    // The child gets the variables of the parent and of the trigger job, as
+    // variables of its pipeline
+    let mut gitlab_vars = pipeline.variables.clone().unwrap_or_default();
+    gitlab_vars.extend(job.variables.clone().unwrap_or_default());
+    gitlab_vars.extend(config.gitlab_vars.clone());
+    let child_config = ExecutionConfig {
+        event: Some("parent_pipeline".to_string()),
+        filter: RunFilter::default(),
+        gitlab_vars,
+        ..config.clone()
+    };
+    let mut child_path = trigger_path.to_vec();
+    child_path.push(name.to_string());
+    match Box::pin(run_gitlab_pipeline(
+        &child,
+        &files[0],
+        child_config,
+        &child_path,
+    ))
+    .await
+    {
+        Ok(mut result) => {
+            let deployments = std::mem::take(&mut result.deployments);
+            (
+                triggers::trigger_result(name, result, triggers::depends(trigger)),
+                deployments,
+            )
         }
+        Err(e) => (failed(e.to_string()), Vec::new()),
     }
+}
 
-    if !stageless_jobs.is_empty() {
-        execution_plan.push(stageless_jobs);
+/// How a stage whose jobs ended with `results` went
+fn stage_status(pipeline: &Pipeline, results: &[JobResult]) -> JobStatus {
+    let failed = |result: &&JobResult| {
+        result.status == JobStatus::Failure
+            && !stages::job_of(pipeline, &result.name).is_some_and(stages::allows_failure)
+    };
+    if results
+        .iter()
+        .any(|result| result.status == JobStatus::Cancelled)
+    {
+        JobStatus::Cancelled
+    } else if results.iter().any(|result| failed(&result)) {
+        JobStatus::Failure
+    } else if results
+        .iter()
+        .all(|result| result.status == JobStatus::Skipped)
+    {
+        JobStatus::Skipped
+    } else {
+        JobStatus::Success
     }
-
-    Ok(execution_plan)
 }
 
 // Determine if Docker/Podman is available or fall back to emulation
 fn initialize_runtime(
-    runtime_type: RuntimeType,
-    preserve_containers_on_failure: bool,
+    config: &ExecutionConfig,
 ) -> Result<Box<dyn ContainerRuntime>, ExecutionError> {
-    match runtime_type {
+    let preserve_containers_on_failure = config.preserve_containers_on_failure;
+    let run_as = config.file_ownership.run_as();
+    let memory_bytes = config
+        .memory_limit
+        .as_deref()
+        .map(docker::parse_memory_limit)
+        .transpose()
+        .map_err(ExecutionError::Runtime)?;
+    match config.runtime_type {
         RuntimeType::Docker => {
             if docker::is_available() {
+                let options = docker::DockerRuntimeConfig {
+                    preserve_containers_on_failure,
+                    limits: docker::ResourceLimits {
+                        cpus: config.cpu_limit,
+                        memory_bytes,
+                        pids: config.pids_limit,
+                    },
+                    network_mode: config.network_mode,
+                    pull_policy: config.pull_policy,
+                    user: config
+                        .docker_options
+                        .user
+                        .clone()
+                        .or(run_as.map(|(uid, gid)| format!("{}:{}", uid, gid))),
+                    ..config.docker_options.clone()
+                };
                 // Handle the Result returned by DockerRuntime::new()
-                match docker::DockerRuntime::new_with_config(preserve_containers_on_failure) {
+                match docker::DockerRuntime::new_with_options(options) {
                     Ok(docker_runtime) => Ok(Box::new(docker_runtime)),
                     Err(e) => {
                         wrkflw_logging::error(&format!(
@@ -433,7 +897,24 @@ fn initialize_runtime(
         RuntimeType::Podman => {
             if podman::is_available() {
                 // Handle the Result returned by PodmanRuntime::new()
-                match podman::PodmanRuntime::new_with_config(preserve_containers_on_failure) {
+                let options = podman::PodmanOptions {
+                    cpus: config.cpu_limit,
+                    memory: config.memory_limit.clone(),
+                    pids_limit: config.pids_limit,
+                    network_mode: config.network_mode,
+                    pull_policy: config.pull_policy,
+                    // Rootless Podman maps the invoking user to the same uid and gid
+                    userns: config
+                        .podman_options
+                        .userns
+                        .clone()
+                        .or(run_as.map(|_| "keep-id".to_string())),
+                    ..config.podman_options.clone()
+                };
+                match podman::PodmanRuntime::new_with_options(
+                    preserve_containers_on_failure,
+                    options,
+                ) {
                     Ok(podman_runtime) => Ok(Box::new(podman_runtime)),
                     Err(e) => {
                         wrkflw_logging::error(&format!(
@@ -448,9 +929,42 @@ fn initialize_runtime(
                 Ok(Box::new(emulation::EmulationRuntime::new()))
             }
         }
-        RuntimeType::Emulation => Ok(Box::new(emulation::EmulationRuntime::new())),
-        RuntimeType::SecureEmulation => Ok(Box::new(
-            wrkflw_runtime::secure_emulation::SecureEmulationRuntime::new(),
+        RuntimeType::Emulation => {
+            if config.network_mode == NetworkMode::None {
+                wrkflw_logging::warning(
+                    "Emulation mode cannot disable networking; use secure-emulation or a container runtime",
+                );
+            }
+            Ok(Box::new(emulation::EmulationRuntime::new()))
+        }
+        RuntimeType::SecureEmulation => {
+            let mut sandbox_config = config
+                .sandbox_config
+                .clone()
+                .unwrap_or_else(wrkflw_runtime::sandbox::create_workflow_sandbox_config);
+            if config.network_mode == NetworkMode::None {
+                sandbox_config.allow_network = false;
+            }
+            wrkflw_runtime::secure_emulation::SecureEmulationRuntime::new_with_config(
+                sandbox_config,
+            )
+            .map(|runtime| Box::new(runtime) as Box<dyn ContainerRuntime>)
+            .map_err(|e| ExecutionError::Runtime(e.to_string()))
+        }
+        #[cfg(feature = "wasm")]
+        RuntimeType::Wasm => {
+            let mut wasm_config = wrkflw_runtime::wasm::WasmConfig::default();
+            if let Some(bytes) = memory_bytes {
+                wasm_config.max_memory_mb = (bytes as u64) >> 20;
+            }
+            wrkflw_runtime::wasm::WasmRuntime::new(wasm_config)
+                .map(|runtime| Box::new(runtime) as Box<dyn ContainerRuntime>)
+                .map_err(|e| ExecutionError::Runtime(e.to_string()))
+        }
+        #[cfg(not(feature = "wasm"))]
+        RuntimeType::Wasm => Err(ExecutionError::Runtime(
+            "The WebAssembly runtime is not available in this build; rebuild wrkflw with `--features wasm`"
+                .to_string(),
         )),
     }
 }
@@ -461,6 +975,21 @@ pub enum RuntimeType {
     Podman,
     Emulation,
     SecureEmulation,
+    /// Experimental: plain `run:` commands executed as WASI modules
+    Wasm,
+}
+
+impl RuntimeType {
+    /// Value of `WRKFLW_RUNTIME_MODE`, which steps use to tell runtimes apart
+    pub fn mode(&self) -> &'static str {
+        match self {
+            RuntimeType::Emulation => "emulation",
+            RuntimeType::SecureEmulation => "secure_emulation",
+            RuntimeType::Docker => "docker",
+            RuntimeType::Podman => "podman",
+            RuntimeType::Wasm => "wasm",
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -469,11 +998,119 @@ pub struct ExecutionConfig {
     pub verbose: bool,
     pub preserve_containers_on_failure: bool,
     pub secrets_config: Option<SecretConfig>,
+    /// Token exposed as `secrets.GITHUB_TOKEN`/`github.token`; a placeholder no
+    /// API accepts when unset, unless `github_token_from_env`
+    pub github_token: Option<String>,
+    /// Without `github_token`, take the token from `$GITHUB_TOKEN`, `$GH_TOKEN` or
+    /// the gh CLI's credentials instead of a placeholder
+    pub github_token_from_env: bool,
+    /// CPU limit for job containers (number of CPUs, may be fractional)
+    pub cpu_limit: Option<f64>,
+    /// Memory limit for job containers (e.g. `512m`, `2g`)
+    pub memory_limit: Option<String>,
+    /// Maximum number of processes in job containers
+    pub pids_limit: Option<i64>,
+    /// Podman-specific options (user namespace, security options, SELinux labels)
+    pub podman_options: podman::PodmanOptions,
+    /// Docker-specific options (extra mounts, user, user namespace)
+    pub docker_options: docker::DockerRuntimeConfig,
+    /// Network mode for job containers; `None` also disables networking in secure emulation
+    pub network_mode: NetworkMode,
+    /// Sandbox used by secure emulation; defaults to the workflow preset
+    pub sandbox_config: Option<SandboxConfig>,
+    /// What to do with Windows and macOS jobs that can't run natively
+    pub runner_os_policy: RunnerOsPolicy,
+    /// Forbid network fetches, using only cached reusable workflows and tools
+    pub offline: bool,
+    /// Files a simulated push changes, relative to the repository root; workflows
+    /// and GitLab jobs that such a push wouldn't trigger are skipped
+    pub changed_files: Option<Vec<String>>,
+    /// Event the run simulates, as `GITHUB_EVENT_NAME`; defaults to the first
+    /// event the workflow is triggered by
+    pub event: Option<String>,
+    /// `workflow_dispatch` inputs, over the defaults the workflow declares
+    pub inputs: HashMap<String, String>,
+    /// Write the logs of the run under `.wrkflw/logs/` of the current directory
+    pub log_files: bool,
+    /// Variables from `--env`/`--env-file`, set in every job over the env the
+    /// workflow, its jobs and steps define
+    pub env: HashMap<String, String>,
+    /// Configuration variables, as `${{ vars.<name> }}`
+    pub vars: HashMap<String, String>,
+    /// Bytes of each output stream of a step kept in memory; longer output keeps its
+    /// start and end, with the whole saved to a file. 0 keeps everything.
+    pub output_limit: usize,
+    /// Timeouts of container operations, over those of `.wrkflw.toml`
+    pub timeouts: Timeouts,
+    /// When Docker and Podman pull job images
+    pub pull_policy: PullPolicy,
+    /// How the project gets into the workspace of Docker and Podman jobs
+    pub workspace: WorkspaceStrategy,
+    /// Who owns the files Docker and Podman jobs create in their workspace
+    pub file_ownership: FileOwnership,
+    /// A failed run to rerun from its failures, skipping the jobs and steps that
+    /// succeeded in it
+    pub resume: Option<Resume>,
+    /// The jobs and steps to run, when not all of them
+    pub filter: RunFilter,
+    /// Run the manual jobs of GitLab pipelines rather than skip them
+    pub run_manual: bool,
+    /// GitLab CI/CD variables over all others, the job's `variables:` included
+    pub gitlab_vars: HashMap<String, String>,
+    /// Cancels the run from another thread or task
+    pub cancel: cancel::RunCancel,
+}
+
+/// A run like `wrkflw run` with no options: in Docker, verbose off, no limits and no
+/// log files. Set what differs and take the rest with `..Default::default()`, so
+/// that fields added later need no changes.
+impl Default for ExecutionConfig {
+    fn default() -> Self {
+        ExecutionConfig {
+            runtime_type: RuntimeType::Docker,
+            verbose: false,
+            preserve_containers_on_failure: false,
+            secrets_config: None,
+            github_token: None,
+            github_token_from_env: false,
+            cpu_limit: None,
+            memory_limit: None,
+            pids_limit: None,
+            podman_options: Default::default(),
+            docker_options: Default::default(),
+            network_mode: Default::default(),
+            sandbox_config: None,
+            runner_os_policy: Default::default(),
+            offline: false,
+            changed_files: None,
+            event: None,
+            inputs: HashMap::new(),
+            log_files: false,
+            env: HashMap::new(),
+            vars: HashMap::new(),
+            output_limit: wrkflw_runtime::output::DEFAULT_OUTPUT_LIMIT,
+            timeouts: Default::default(),
+            pull_policy: Default::default(),
+            workspace: Default::default(),
+            file_ownership: Default::default(),
+            resume: None,
+            filter: Default::default(),
+            run_manual: false,
+            gitlab_vars: HashMap::new(),
+            cancel: Default::default(),
+        }
+    }
 }
 
 pub struct ExecutionResult {
     pub jobs: Vec<JobResult>,
     pub failure_details: Option<String>,
+    /// Wall-clock time of the whole run
+    pub duration: Duration,
+    /// What rerunning the run from its failed jobs and steps needs
+    pub resume: Resume,
+    /// Deployments to the environments of a GitLab pipeline's jobs
+    pub deployments: Vec<Deployment>,
 }
 
 pub struct JobResult {
@@ -481,6 +1118,9 @@ pub struct JobResult {
     pub status: JobStatus,
     pub steps: Vec<StepResult>,
     pub logs: String,
+    /// Deployment environment the job ran in
+    pub environment: Option<String>,
+    pub timing: JobTiming,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -489,6 +1129,8 @@ pub enum JobStatus {
     Success,
     Failure,
     Skipped,
+    /// Stopped by a cancellation before it finished
+    Cancelled,
 }
 
 #[derive(Debug, Clone)]
@@ -496,6 +1138,7 @@ pub struct StepResult {
     pub name: String,
     pub status: StepStatus,
     pub output: String,
+    pub duration: Duration,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -558,11 +1201,27 @@ async fn prepare_action(
 
         let dockerfile = action_dir.join("Dockerfile");
         if dockerfile.exists() {
-            // It's a Docker action, build it
-            let tag = format!("wrkflw-local-action:{}", uuid::Uuid::new_v4());
+            // It's a Docker action, build it under a tag of its own, so that the
+            // builder's cache and the image are reused by later runs
+            let action_path = action_dir
+                .canonicalize()
+                .unwrap_or_else(|_| action_dir.to_path_buf());
+            use sha2::Digest;
+            let digest = sha2::Sha256::digest(action_path.to_string_lossy().as_bytes());
+            let tag = format!(
+                "wrkflw-local-action:{}",
+                digest[..6]
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect::<String>()
+            );
+            let options = BuildOptions {
+                labels: [("wrkflw.action".to_string(), action.repository.clone())].into(),
+                ..Default::default()
+            };
 
             runtime
-                .build_image(&dockerfile, &tag)
+                .build_image(&dockerfile, &tag, &options)
                 .await
                 .map_err(|e| ExecutionError::Runtime(format!("Failed to build image: {}", e)))?;
 
@@ -580,7 +1239,7 @@ async fn prepare_action(
 }
 
 /// Determine the appropriate Docker image for a GitHub action
-fn determine_action_image(repository: &str) -> String {
+pub(crate) fn determine_action_image(repository: &str) -> String {
     // Handle specific well-known actions
     match repository {
         // PHP setup actions
@@ -641,7 +1300,7 @@ async fn execute_job_batch(
     env_context: &HashMap<String, String>,
     verbose: bool,
     secret_manager: Option<&SecretManager>,
-    secret_masker: Option<&SecretMasker>,
+    secret_masker: Option<&Mutex<SecretMasker>>,
 ) -> Result<Vec<JobResult>, ExecutionError> {
     // Execute jobs in parallel
     let futures = jobs.iter().map(|job_name| {
@@ -654,6 +1313,7 @@ async fn execute_job_batch(
             secret_manager,
             secret_masker,
         )
+        .instrument(tracing::info_span!("job", job = job_name.as_str()))
     });
 
     let result_arrays = future::join_all(futures).await;
@@ -678,7 +1338,7 @@ struct JobExecutionContext<'a> {
     env_context: &'a HashMap<String, String>,
     verbose: bool,
     secret_manager: Option<&'a SecretManager>,
-    secret_masker: Option<&'a SecretMasker>,
+    secret_masker: Option<&'a Mutex<SecretMasker>>,
 }
 
 /// Execute a job, expanding matrix if present
@@ -689,13 +1349,20 @@ async fn execute_job_with_matrix(
     env_context: &HashMap<String, String>,
     verbose: bool,
     secret_manager: Option<&SecretManager>,
-    secret_masker: Option<&SecretMasker>,
+    secret_masker: Option<&Mutex<SecretMasker>>,
 ) -> Result<Vec<JobResult>, ExecutionError> {
     // Get the job definition
     let job = workflow.jobs.get(job_name).ok_or_else(|| {
         ExecutionError::Execution(format!("Job '{}' not found in workflow", job_name))
     })?;
 
+    if cancel::is_cancelled(job_name) {
+        return Ok(vec![cancelled_job(job_name)]);
+    }
+    if resume::succeeded(job_name) {
+        return Ok(vec![skipped_job(job_name, RERUN_SKIP_REASON)]);
+    }
+
     // Evaluate job condition if present
     if let Some(if_condition) = &job.if_condition {
         let should_run = evaluate_job_condition(if_condition, env_context, workflow);
@@ -710,14 +1377,16 @@ async fn execute_job_with_matrix(
                 status: JobStatus::Skipped,
                 steps: Vec::new(),
                 logs: String::new(),
+                environment: None,
+                timing: JobTiming::default(),
             }]);
         }
     }
 
     // Check if this is a matrix job
-    if let Some(matrix_config) = &job.matrix {
+    if let Some(matrix_config) = job.matrix_config() {
         // Expand the matrix into combinations
-        let combinations = wrkflw_matrix::expand_matrix(matrix_config)
+        let combinations = wrkflw_matrix::expand_matrix(&matrix_config)
             .map_err(|e| ExecutionError::Execution(format!("Failed to expand matrix: {}", e)))?;
 
         if combinations.is_empty() {
@@ -767,7 +1436,7 @@ async fn execute_job_with_matrix(
             secret_manager,
             secret_masker,
         };
-        let result = execute_job(ctx).await?;
+        let result = timing::timed_job(execute_job(ctx)).await?;
         Ok(vec![result])
     }
 }
@@ -787,11 +1456,16 @@ async fn execute_job(ctx: JobExecutionContext<'_>) -> Result<JobResult, Executio
 
     // Clone context and add job-specific variables
     let mut job_env = ctx.env_context.clone();
+    oidc::restrict_to_permitted_job(&mut job_env, job, ctx.workflow);
+    job_env.insert("GITHUB_JOB".to_string(), ctx.job_name.to_string());
+    let environment = job
+        .environment
+        .as_ref()
+        .map(|environment| enter_environment(ctx.job_name, &environment.name, &mut job_env));
 
     // Add job-level environment variables
-    for (key, value) in &job.env {
-        job_env.insert(key.clone(), value.clone());
-    }
+    environment::apply_env_layer(&mut job_env, &job.env);
+    environment::apply_user_env(&mut job_env);
 
     // Execute job steps
     let mut step_results = Vec::new();
@@ -812,29 +1486,93 @@ async fn execute_job(ctx: JobExecutionContext<'_>) -> Result<JobResult, Executio
 
     // Execute job steps
     // Determine runner image (default if not provided)
-    let runner_image_value = get_runner_image_from_opt(&job.runs_on);
-
+    let runs_on = job.runs_on.clone().unwrap_or_default();
+    let runner = match resolve_runner(ctx.job_name, &runs_on, &mut job_env) {
+        Ok(runner) => runner,
+        Err(result) => return Ok(*result),
+    };
+    let native_runtime = runner.native.then(emulation::EmulationRuntime::new);
+    let runtime: &dyn ContainerRuntime = match &native_runtime {
+        Some(native_runtime) => native_runtime,
+        None => ctx.runtime,
+    };
+    let run_defaults = ctx.workflow.run_defaults(job);
+    let workspace =
+        context::current()
+            .workspace
+            .job_workspace(&job_env, &current_dir, job_dir.path());
+
+    let mut rerun = JobProgress::start(ctx.job_name, &mut job_env, workspace);
+    let step_filter = context::current().steps.clone();
+    let mut stopped = false;
+    let mut post_steps = Vec::new();
     for (idx, step) in job.steps.iter().enumerate() {
+        if cancel::is_cancelled(ctx.job_name) {
+            stopped = true;
+            break;
+        }
+        if rerun.skips(idx) {
+            let result = rerun_skipped_step(step, idx);
+            progress::step_started(ctx.job_name, idx, result.name.clone());
+            progress::step_finished(ctx.job_name, idx, &result);
+            job_logs.push_str(&format!("Step '{}' skipped\n", result.name));
+            step_results.push(result);
+            continue;
+        }
+        if let Some(reason) = step_filter.skip_reason(step, idx) {
+            let result = filtered_step(step, idx, reason, &mut job_env);
+            progress::step_started(ctx.job_name, idx, result.name.clone());
+            progress::step_finished(ctx.job_name, idx, &result);
+            job_logs.push_str(&format!("Step '{}' skipped\n", result.name));
+            step_results.push(result);
+            continue;
+        }
+        if !step_should_run(step, !job_success, &job_env) {
+            let result = skipped_step(step, idx, &mut job_env);
+            progress::step_started(ctx.job_name, idx, result.name.clone());
+            progress::step_finished(ctx.job_name, idx, &result);
+            job_logs.push_str(&format!("Step '{}' skipped\n", result.name));
+            step_results.push(result);
+            continue;
+        }
+        let env_before = rerun.before_step(&job_env);
+        let step_started = Instant::now();
+        progress::step_started(ctx.job_name, idx, step_display_name(step, idx));
         let step_result = execute_step(StepExecutionContext {
             step,
             step_idx: idx,
             job_env: &job_env,
-            working_dir: job_dir.path(),
-            runtime: ctx.runtime,
+            working_dir: workspace,
+            runtime,
             workflow: ctx.workflow,
-            runner_image: &runner_image_value,
+            run_defaults: &run_defaults,
+            runner_image: &runner.image,
             verbose: ctx.verbose,
             matrix_combination: &None,
             secret_manager: ctx.secret_manager,
             secret_masker: ctx.secret_masker,
         })
-        .await;
+        .await
+        .map(|result| StepResult {
+            duration: step_started.elapsed(),
+            ..result
+        });
+        environment::apply_github_env_files(&mut job_env);
+        post_steps.extend(post_step_of(
+            step,
+            idx,
+            &job_env,
+            workspace,
+            &current_dir,
+            &None,
+        ));
 
         match step_result {
             Ok(result) => {
                 // Check if step was successful
-                if result.status == StepStatus::Failure {
+                if record_step(step, &result.status, &mut job_env) {
                     job_success = false;
+                    rerun.step_failed(idx, env_before);
                 }
 
                 // Add step output to logs only in verbose mode or if there's an error
@@ -851,37 +1589,63 @@ async fn execute_job(ctx: JobExecutionContext<'_>) -> Result<JobResult, Executio
                     ));
                 }
 
+                progress::step_finished(ctx.job_name, idx, &result);
                 step_results.push(result);
             }
             Err(e) => {
-                job_success = false;
+                if record_step(step, &StepStatus::Failure, &mut job_env) {
+                    job_success = false;
+                    rerun.step_failed(idx, env_before);
+                }
                 job_logs.push_str(&format!("\n=== ERROR in step {} ===\n{}\n", idx + 1, e));
 
                 // Record the error as a failed step
-                step_results.push(StepResult {
-                    name: step
-                        .name
-                        .clone()
-                        .unwrap_or_else(|| format!("Step {}", idx + 1)),
+                let result = StepResult {
+                    name: step_display_name(step, idx),
                     status: StepStatus::Failure,
                     output: format!("Error: {}", e),
-                });
-
-                // Stop executing further steps
-                break;
+                    duration: step_started.elapsed(),
+                };
+                progress::step_finished(ctx.job_name, idx, &result);
+                step_results.push(result);
             }
         }
     }
 
+    let state = condition::JobState {
+        failed: !job_success,
+        cancelled: stopped || cancel::is_cancelled(ctx.job_name),
+    };
+    if !run_post_steps(
+        ctx.job_name,
+        post_steps,
+        state,
+        &job_env,
+        &mut step_results,
+        &mut job_logs,
+    ) {
+        job_success = false;
+    }
+
+    hand_back_workspace(runtime, &runner.image, workspace, &job_env).await;
+
+    // A step that failed after the cancellation was most likely killed by it
+    let cancelled = stopped || (!job_success && cancel::is_cancelled(ctx.job_name));
+    let in_job_dir = workspace == job_dir.path();
+    rerun.finish(!job_success && !cancelled, job_dir, in_job_dir);
     Ok(JobResult {
         name: ctx.job_name.to_string(),
-        status: if job_success {
+        status: if cancelled {
+            JobStatus::Cancelled
+        } else if job_success {
             JobStatus::Success
         } else {
             JobStatus::Failure
         },
         steps: step_results,
         logs: job_logs,
+        environment,
+        timing: JobTiming::default(),
     })
 }
 
@@ -896,10 +1660,8 @@ struct MatrixExecutionContext<'a> {
     runtime: &'a dyn ContainerRuntime,
     env_context: &'a HashMap<String, String>,
     verbose: bool,
-    #[allow(dead_code)] // Planned for future implementation
     secret_manager: Option<&'a SecretManager>,
-    #[allow(dead_code)] // Planned for future implementation
-    secret_masker: Option<&'a SecretMasker>,
+    secret_masker: Option<&'a Mutex<SecretMasker>>,
 }
 
 /// Execute a set of matrix combinations
@@ -917,28 +1679,26 @@ async fn execute_matrix_combinations(
             for combination in chunk {
                 let combination_name =
                     wrkflw_matrix::format_combination_name(ctx.job_name, combination);
+                if resume::succeeded(&combination_name) {
+                    results.push(skipped_job(&combination_name, RERUN_SKIP_REASON));
+                    continue;
+                }
                 results.push(JobResult {
                     name: combination_name,
                     status: JobStatus::Skipped,
                     steps: Vec::new(),
                     logs: "Job skipped due to previous matrix job failure".to_string(),
+                    environment: None,
+                    timing: JobTiming::default(),
                 });
             }
             continue;
         }
 
         // Process this chunk of combinations in parallel
-        let chunk_futures = chunk.iter().map(|combination| {
-            execute_matrix_job(
-                ctx.job_name,
-                ctx.job_template,
-                combination,
-                ctx.workflow,
-                ctx.runtime,
-                ctx.env_context,
-                ctx.verbose,
-            )
-        });
+        let chunk_futures = chunk
+            .iter()
+            .map(|combination| timing::timed_job(execute_matrix_job(&ctx, combination)));
 
         let chunk_results = future::join_all(chunk_futures).await;
 
@@ -969,28 +1729,40 @@ async fn execute_matrix_combinations(
 
 /// Execute a single matrix job combination
 async fn execute_matrix_job(
-    job_name: &str,
-    job_template: &Job,
+    ctx: &MatrixExecutionContext<'_>,
     combination: &MatrixCombination,
-    workflow: &WorkflowDefinition,
-    runtime: &dyn ContainerRuntime,
-    base_env_context: &HashMap<String, String>,
-    verbose: bool,
 ) -> Result<JobResult, ExecutionError> {
+    let MatrixExecutionContext {
+        job_name,
+        job_template,
+        workflow,
+        runtime,
+        env_context: base_env_context,
+        verbose,
+        ..
+    } = *ctx;
     // Create the matrix-specific job name
     let matrix_job_name = wrkflw_matrix::format_combination_name(job_name, combination);
 
+    if resume::succeeded(&matrix_job_name) {
+        return Ok(skipped_job(&matrix_job_name, RERUN_SKIP_REASON));
+    }
     wrkflw_logging::info(&format!("Executing matrix job: {}", matrix_job_name));
 
     // Clone the environment and add matrix-specific values
     let mut job_env = base_env_context.clone();
+    oidc::restrict_to_permitted_job(&mut job_env, job_template, workflow);
+    job_env.insert("GITHUB_JOB".to_string(), job_name.to_string());
     environment::add_matrix_context(&mut job_env, combination);
+    let environment = job_template.environment.as_ref().map(|environment| {
+        let name = substitution::preprocess_command(&environment.name, &combination.values);
+        enter_environment(&matrix_job_name, &name, &mut job_env)
+    });
 
     // Add job-level environment variables
-    for (key, value) in &job_template.env {
-        // TODO: Substitute matrix variable references in env values
-        job_env.insert(key.clone(), value.clone());
-    }
+    // TODO: Substitute matrix variable references in env values
+    environment::apply_env_layer(&mut job_env, &job_template.env);
+    environment::apply_user_env(&mut job_env);
 
     // Execute the job steps
     let mut step_results = Vec::new();
@@ -1005,31 +1777,103 @@ async fn execute_matrix_job(
         ExecutionError::Execution(format!("Failed to get current directory: {}", e))
     })?;
 
-    let job_success = if job_template.steps.is_empty() {
+    // `runs-on: ${{ matrix.os }}` is common, so resolve matrix values first
+    let runs_on: Vec<String> = job_template
+        .runs_on
+        .iter()
+        .flatten()
+        .map(|label| substitution::preprocess_command(label, &combination.values))
+        .collect();
+    let runner = match resolve_runner(&matrix_job_name, &runs_on, &mut job_env) {
+        Ok(runner) => runner,
+        Err(result) => return Ok(*result),
+    };
+    let native_runtime = runner.native.then(emulation::EmulationRuntime::new);
+    let runtime: &dyn ContainerRuntime = match &native_runtime {
+        Some(native_runtime) => native_runtime,
+        None => runtime,
+    };
+
+    let job_status = if job_template.steps.is_empty() {
         wrkflw_logging::warning(&format!("Job '{}' has no steps", matrix_job_name));
-        true
+        JobStatus::Success
     } else {
         // Execute each step
-        // Determine runner image (default if not provided)
-        let runner_image_value = get_runner_image_from_opt(&job_template.runs_on);
-
+        let run_defaults = workflow.run_defaults(job_template);
+        let workspace =
+            context::current()
+                .workspace
+                .job_workspace(&job_env, &current_dir, job_dir.path());
+
+        let cancelled = || cancel::is_cancelled(&matrix_job_name) || cancel::is_cancelled(job_name);
+        let mut rerun = JobProgress::start(&matrix_job_name, &mut job_env, workspace);
+        let step_filter = context::current().steps.clone();
+        let mut job_status = JobStatus::Success;
+        let mut post_steps = Vec::new();
         for (idx, step) in job_template.steps.iter().enumerate() {
-            match execute_step(StepExecutionContext {
+            if cancelled() {
+                job_status = JobStatus::Cancelled;
+                break;
+            }
+            if rerun.skips(idx) {
+                let result = rerun_skipped_step(step, idx);
+                progress::step_started(&matrix_job_name, idx, result.name.clone());
+                progress::step_finished(&matrix_job_name, idx, &result);
+                job_logs.push_str(&format!("Step: {}\nStatus: Skipped\n\n", result.name));
+                step_results.push(result);
+                continue;
+            }
+            if let Some(reason) = step_filter.skip_reason(step, idx) {
+                let result = filtered_step(step, idx, reason, &mut job_env);
+                progress::step_started(&matrix_job_name, idx, result.name.clone());
+                progress::step_finished(&matrix_job_name, idx, &result);
+                job_logs.push_str(&format!("Step: {}\nStatus: Skipped\n\n", result.name));
+                step_results.push(result);
+                continue;
+            }
+            if !step_should_run(step, job_status != JobStatus::Success, &job_env) {
+                let result = skipped_step(step, idx, &mut job_env);
+                progress::step_started(&matrix_job_name, idx, result.name.clone());
+                progress::step_finished(&matrix_job_name, idx, &result);
+                job_logs.push_str(&format!("Step: {}\nStatus: Skipped\n\n", result.name));
+                step_results.push(result);
+                continue;
+            }
+            let env_before = rerun.before_step(&job_env);
+            let step_started = Instant::now();
+            progress::step_started(&matrix_job_name, idx, step_display_name(step, idx));
+            let step_result = execute_step(StepExecutionContext {
                 step,
                 step_idx: idx,
                 job_env: &job_env,
-                working_dir: job_dir.path(),
+                working_dir: workspace,
                 runtime,
                 workflow,
-                runner_image: &runner_image_value,
+                run_defaults: &run_defaults,
+                runner_image: &runner.image,
                 verbose,
                 matrix_combination: &Some(combination.values.clone()),
-                secret_manager: None, // Matrix execution context doesn't have secrets yet
-                secret_masker: None,
+                secret_manager: ctx.secret_manager,
+                secret_masker: ctx.secret_masker,
             })
             .await
-            {
+            .map(|result| StepResult {
+                duration: step_started.elapsed(),
+                ..result
+            });
+            environment::apply_github_env_files(&mut job_env);
+            post_steps.extend(post_step_of(
+                step,
+                idx,
+                &job_env,
+                workspace,
+                &current_dir,
+                &Some(combination.values.clone()),
+            ));
+
+            match step_result {
                 Ok(result) => {
+                    progress::step_finished(&matrix_job_name, idx, &result);
                     job_logs.push_str(&format!("Step: {}\n", result.name));
                     job_logs.push_str(&format!("Status: {:?}\n", result.status));
 
@@ -1042,47 +1886,115 @@ async fn execute_matrix_job(
                         job_logs.push('\n');
                     }
 
-                    step_results.push(result.clone());
-
-                    if result.status != StepStatus::Success {
-                        // Step failed, abort job
-                        return Ok(JobResult {
-                            name: matrix_job_name,
-                            status: JobStatus::Failure,
-                            steps: step_results,
-                            logs: job_logs,
-                        });
+                    if record_step(step, &result.status, &mut job_env) {
+                        // Step failed; later steps only run if their `if:` allows it
+                        rerun.step_failed(idx, env_before);
+                        job_status = if cancelled() {
+                            JobStatus::Cancelled
+                        } else {
+                            JobStatus::Failure
+                        };
                     }
+                    step_results.push(result);
                 }
                 Err(e) => {
-                    // Log the error and abort the job
+                    progress::step_finished(
+                        &matrix_job_name,
+                        idx,
+                        &StepResult {
+                            name: step_display_name(step, idx),
+                            status: StepStatus::Failure,
+                            output: format!("Error: {}", e),
+                            duration: step_started.elapsed(),
+                        },
+                    );
+                    // Log the error and fail the job
                     job_logs.push_str(&format!("Step execution error: {}\n\n", e));
-                    return Ok(JobResult {
-                        name: matrix_job_name,
-                        status: JobStatus::Failure,
-                        steps: step_results,
-                        logs: job_logs,
-                    });
+                    if record_step(step, &StepStatus::Failure, &mut job_env) {
+                        rerun.step_failed(idx, env_before);
+                        job_status = if cancelled() {
+                            JobStatus::Cancelled
+                        } else {
+                            JobStatus::Failure
+                        };
+                    }
                 }
             }
         }
 
-        true
+        let state = condition::JobState {
+            failed: job_status == JobStatus::Failure,
+            cancelled: job_status == JobStatus::Cancelled || cancelled(),
+        };
+        if !run_post_steps(
+            &matrix_job_name,
+            post_steps,
+            state,
+            &job_env,
+            &mut step_results,
+            &mut job_logs,
+        ) && job_status == JobStatus::Success
+        {
+            job_status = JobStatus::Failure;
+        }
+
+        hand_back_workspace(runtime, &runner.image, workspace, &job_env).await;
+        let in_job_dir = workspace == job_dir.path();
+        rerun.finish(job_status == JobStatus::Failure, job_dir, in_job_dir);
+        job_status
     };
 
     // Return job result
     Ok(JobResult {
         name: matrix_job_name,
-        status: if job_success {
-            JobStatus::Success
-        } else {
-            JobStatus::Failure
-        },
+        status: job_status,
         steps: step_results,
         logs: job_logs,
+        environment,
+        timing: JobTiming::default(),
     })
 }
 
+/// With `--file-ownership chown`, give the files a container job created in its
+/// workspace back to the invoking user, running `chown` as the image's user
+async fn hand_back_workspace(
+    runtime: &dyn ContainerRuntime,
+    image: &str,
+    workspace: &Path,
+    job_env: &HashMap<String, String>,
+) {
+    let Some((uid, gid)) = context::current().file_ownership.chown_as(job_env) else {
+        return;
+    };
+    let owner = format!("{}:{}", uid, gid);
+    let container_workspace = Path::new("/github/workspace");
+    let chowned = runtime
+        .run_container(
+            image,
+            &["chown", "-R", &owner, "/github/workspace"],
+            &[],
+            container_workspace,
+            &[(workspace, container_workspace)],
+        )
+        .await;
+    match chowned {
+        Ok(output) if output.exit_code == 0 => wrkflw_logging::debug(&format!(
+            "Handed the workspace {} back to {}",
+            workspace.display(),
+            owner
+        )),
+        Ok(output) => wrkflw_logging::warning(&format!(
+            "Failed to hand the workspace back to {}: {}",
+            owner,
+            output.stderr.trim()
+        )),
+        Err(e) => wrkflw_logging::warning(&format!(
+            "Failed to hand the workspace back to {}: {}",
+            owner, e
+        )),
+    }
+}
+
 // Before the execute_step function, add this struct
 struct StepExecutionContext<'a> {
     step: &'a workflow::Step,
@@ -1091,21 +2003,213 @@ struct StepExecutionContext<'a> {
     working_dir: &'a Path,
     runtime: &'a dyn ContainerRuntime,
     workflow: &'a WorkflowDefinition,
+    /// Job and workflow `defaults.run`
+    run_defaults: &'a workflow::RunDefaults,
     runner_image: &'a str,
     verbose: bool,
-    #[allow(dead_code)]
     matrix_combination: &'a Option<HashMap<String, Value>>,
     secret_manager: Option<&'a SecretManager>,
-    #[allow(dead_code)] // Planned for future implementation
-    secret_masker: Option<&'a SecretMasker>,
+    /// Masks the run's secrets in the step's output
+    secret_masker: Option<&'a Mutex<SecretMasker>>,
 }
 
-async fn execute_step(ctx: StepExecutionContext<'_>) -> Result<StepResult, ExecutionError> {
-    let step_name = ctx
-        .step
-        .name
+/// The step's name, or its position in the job when it has none
+fn step_display_name(step: &workflow::Step, idx: usize) -> String {
+    step.name
         .clone()
-        .unwrap_or_else(|| format!("Step {}", ctx.step_idx + 1));
+        .unwrap_or_else(|| format!("Step {}", idx + 1))
+}
+
+/// Whether a step's `if:` lets it run after the job's earlier steps
+fn step_should_run(
+    step: &workflow::Step,
+    job_failed: bool,
+    job_env: &HashMap<String, String>,
+) -> bool {
+    // Cancelled jobs stop before their next step, so cancelled() is never true here
+    let state = condition::JobState {
+        failed: job_failed,
+        cancelled: false,
+    };
+    condition::step_should_run(step.if_condition.as_deref(), state, job_env)
+}
+
+/// The post step a step queues, when the action it runs has one
+fn post_step_of(
+    step: &workflow::Step,
+    idx: usize,
+    job_env: &HashMap<String, String>,
+    working_dir: &Path,
+    project_dir: &Path,
+    matrix_combination: &Option<HashMap<String, serde_yaml::Value>>,
+) -> Option<post::PostStep> {
+    let uses = step.uses.as_ref()?;
+    let expressions = ExpressionContext::from_env(job_env);
+    let mut inputs = step.with.clone().unwrap_or_default();
+    for value in inputs.values_mut() {
+        *value = expressions.substitute(value, job_env);
+        if let Some(matrix) = matrix_combination {
+            *value = substitution::preprocess_command(value, matrix);
+        }
+    }
+    post::PostStep::for_action(
+        uses,
+        &step_display_name(step, idx),
+        &inputs,
+        working_dir,
+        project_dir,
+    )
+}
+
+/// Run the post steps a job's actions queued, the last queued first, adding them to
+/// its results. Returns whether they all succeeded or were skipped.
+fn run_post_steps(
+    job_name: &str,
+    post_steps: Vec<post::PostStep>,
+    state: condition::JobState,
+    job_env: &HashMap<String, String>,
+    step_results: &mut Vec<StepResult>,
+    job_logs: &mut String,
+) -> bool {
+    let mut succeeded = true;
+    for post_step in post_steps.into_iter().rev() {
+        let idx = step_results.len();
+        let started = Instant::now();
+        progress::step_started(job_name, idx, post_step.name.clone());
+        let (status, output) =
+            if condition::step_should_run(Some(&post_step.condition), state, job_env) {
+                post_step.run()
+            } else {
+                (
+                    StepStatus::Skipped,
+                    format!("Skipped: condition '{}' not met", post_step.condition),
+                )
+            };
+        let result = StepResult {
+            name: post_step.name,
+            status,
+            output,
+            duration: started.elapsed(),
+        };
+        progress::step_finished(job_name, idx, &result);
+        if result.status == StepStatus::Failure {
+            succeeded = false;
+            job_logs.push_str(&format!(
+                "\n=== Output from step '{}' ===\n{}\n=== End output ===\n\n",
+                result.name, result.output
+            ));
+        } else {
+            job_logs.push_str(&format!(
+                "Step '{}' completed with status: {:?}\n",
+                result.name, result.status
+            ));
+        }
+        step_results.push(result);
+    }
+    succeeded
+}
+
+/// A step whose `if:` kept it from running
+fn skipped_step(
+    step: &workflow::Step,
+    idx: usize,
+    job_env: &mut HashMap<String, String>,
+) -> StepResult {
+    record_step(step, &StepStatus::Skipped, job_env);
+    StepResult {
+        name: step_display_name(step, idx),
+        status: StepStatus::Skipped,
+        output: match &step.if_condition {
+            Some(condition) => format!("Skipped: condition '{}' not met", condition),
+            None => "Skipped: an earlier step failed".to_string(),
+        },
+        duration: Duration::ZERO,
+    }
+}
+
+/// A step left out of the run with `--step` or `--skip-step`
+fn filtered_step(
+    step: &workflow::Step,
+    idx: usize,
+    reason: &str,
+    job_env: &mut HashMap<String, String>,
+) -> StepResult {
+    record_step(step, &StepStatus::Skipped, job_env);
+    StepResult {
+        name: step_display_name(step, idx),
+        status: StepStatus::Skipped,
+        output: format!("Skipped: {}", reason),
+        duration: Duration::ZERO,
+    }
+}
+
+/// Add how a step went and what it wrote to `GITHUB_OUTPUT` to the job's `steps`
+/// context. Returns whether the step fails the job, which a failed step with
+/// `continue-on-error` doesn't.
+fn record_step(
+    step: &workflow::Step,
+    status: &StepStatus,
+    job_env: &mut HashMap<String, String>,
+) -> bool {
+    let outputs = environment::take_step_outputs(job_env);
+    let outcome = match status {
+        StepStatus::Success => "success",
+        StepStatus::Failure => "failure",
+        StepStatus::Skipped => "skipped",
+    };
+    let fails_job = *status == StepStatus::Failure && !step.continue_on_error.unwrap_or(false);
+    if let Some(id) = &step.id {
+        let conclusion = if *status == StepStatus::Failure && !fails_job {
+            "success"
+        } else {
+            outcome
+        };
+        environment::record_step(
+            job_env,
+            id,
+            StepContext {
+                outputs,
+                outcome: outcome.to_string(),
+                conclusion: conclusion.to_string(),
+            },
+        );
+    }
+    fails_job
+}
+
+async fn execute_step(ctx: StepExecutionContext<'_>) -> Result<StepResult, ExecutionError> {
+    let span = tracing::info_span!(
+        "step",
+        step = %step_display_name(ctx.step, ctx.step_idx)
+    );
+    let masker = ctx.secret_masker;
+    let mut result = run_step(ctx).instrument(span).await?;
+    if let Some(masker) = masker.and_then(|masker| masker.lock().ok()) {
+        result.output = masker.mask(&result.output);
+    }
+    Ok(result)
+}
+
+/// Mask `value` in step output through `masker`, and in every log event
+fn mask_secret(masker: &Mutex<SecretMasker>, value: &str) {
+    if let Ok(mut masker) = masker.lock() {
+        masker.add_secret(value);
+    }
+    wrkflw_logging::add_mask(value);
+}
+
+/// Mask the secrets `substitution` resolved, once it substituted them into a step
+fn mask_resolved_secrets(ctx: &StepExecutionContext<'_>, substitution: &SecretSubstitution<'_>) {
+    for value in substitution.resolved_secrets().values() {
+        match ctx.secret_masker {
+            Some(masker) => mask_secret(masker, value),
+            None => wrkflw_logging::add_mask(value),
+        }
+    }
+}
+
+async fn run_step(ctx: StepExecutionContext<'_>) -> Result<StepResult, ExecutionError> {
+    let step_name = step_display_name(ctx.step, ctx.step_idx);
 
     if ctx.verbose {
         wrkflw_logging::info(&format!("  Executing step: {}", step_name));
@@ -1114,25 +2218,39 @@ async fn execute_step(ctx: StepExecutionContext<'_>) -> Result<StepResult, Execu
     // Prepare step environment
     let mut step_env = ctx.job_env.clone();
 
-    // Add step-level environment variables (with secret substitution)
-    for (key, value) in &ctx.step.env {
+    let expressions = ExpressionContext::take_from(&mut step_env);
+
+    // Add step-level environment variables (with secret substitution), evaluated
+    // against the job's environment
+    let step_values: Vec<(String, String)> = ctx
+        .step
+        .env
+        .iter()
+        .map(|(key, value)| (key.clone(), expressions.substitute(value, &step_env)))
+        .collect();
+    for (key, value) in step_values {
         let resolved_value = if let Some(secret_manager) = ctx.secret_manager {
             let mut substitution = SecretSubstitution::new(secret_manager);
-            match substitution.substitute(value).await {
+            let resolved = substitution.substitute(&value).await;
+            mask_resolved_secrets(&ctx, &substitution);
+            match resolved {
                 Ok(resolved) => resolved,
                 Err(e) => {
                     wrkflw_logging::error(&format!(
                         "Failed to resolve secrets in environment variable {}: {}",
                         key, e
                     ));
-                    value.clone()
+                    value
                 }
             }
         } else {
-            value.clone()
+            value
         };
-        step_env.insert(key.clone(), resolved_value);
+        step_env.insert(key, resolved_value);
     }
+    // Variables given by the user win over the step's own
+    environment::apply_user_env(&mut step_env);
+    step_env.remove(environment::USER_ENV_CONTEXT_KEY);
 
     // Execute the step based on its type
     let step_result = if let Some(uses) = &ctx.step.uses {
@@ -1141,48 +2259,9 @@ async fn execute_step(ctx: StepExecutionContext<'_>) -> Result<StepResult, Execu
 
         // Check if this is the checkout action
         if uses.starts_with("actions/checkout") {
-            // Get the current directory (assumes this is where your project is)
-            let current_dir = std::env::current_dir().map_err(|e| {
-                ExecutionError::Execution(format!("Failed to get current dir: {}", e))
-            })?;
-
-            // Copy the project files to the workspace
-            copy_directory_contents(&current_dir, ctx.working_dir)?;
-
-            // Add info for logs
-            let output = if ctx.verbose {
-                let mut detailed_output =
-                    "Emulated checkout: Copied current directory to workspace\n\n".to_string();
-
-                // Add checkout action details
-                detailed_output.push_str("Checkout Details:\n");
-                detailed_output.push_str("  - Source: Local directory\n");
-                detailed_output
-                    .push_str(&format!("  - Destination: {}\n", ctx.working_dir.display()));
-
-                // Add a summary count instead of listing all files
-                if let Ok(entries) = std::fs::read_dir(&current_dir) {
-                    let entry_count = entries.count();
-                    detailed_output.push_str(&format!(
-                        "\nCopied {} top-level items to workspace\n",
-                        entry_count
-                    ));
-                }
-
-                detailed_output
-            } else {
-                "Emulated checkout: Copied current directory to workspace".to_string()
-            };
-
-            if ctx.verbose {
-                println!("  Emulated actions/checkout: copied project files to workspace");
-            }
-
-            StepResult {
-                name: step_name,
-                status: StepStatus::Success,
-                output,
-            }
+            checkout_step(&ctx, step_name)?
+        } else if let Some(tool) = installable_tool(uses, ctx.job_env) {
+            setup_tool(tool, &ctx, step_name).await
         } else {
             // Get action info
             let image = prepare_action(&action_info, ctx.runtime).await?;
@@ -1234,6 +2313,7 @@ async fn execute_step(ctx: StepExecutionContext<'_>) -> Result<StepResult, Execu
                             name: step_name,
                             status: StepStatus::Success,
                             output: format!("Using system Rust: {}", rustc_version.trim()),
+                            duration: Duration::ZERO,
                         });
                     }
 
@@ -1339,6 +2419,7 @@ async fn execute_step(ctx: StepExecutionContext<'_>) -> Result<StepResult, Execu
                                                 StepStatus::Failure
                                             },
                                             output: format!("{}\n{}", stdout, stderr),
+                                            duration: Duration::ZERO,
                                         });
                                     }
                                     Err(e) => {
@@ -1346,6 +2427,7 @@ async fn execute_step(ctx: StepExecutionContext<'_>) -> Result<StepResult, Execu
                                             name: step_name,
                                             status: StepStatus::Failure,
                                             output: format!("Failed to execute command: {}", e),
+                                            duration: Duration::ZERO,
                                         });
                                     }
                                 }
@@ -1524,7 +2606,8 @@ async fn execute_step(ctx: StepExecutionContext<'_>) -> Result<StepResult, Execu
                 // Convert 'with' parameters to environment variables
                 if let Some(with_params) = &ctx.step.with {
                     for (key, value) in with_params {
-                        step_env.insert(format!("INPUT_{}", key.to_uppercase()), value.clone());
+                        let value = expressions.substitute(value, &step_env);
+                        step_env.insert(format!("INPUT_{}", key.to_uppercase()), value);
                     }
                 }
 
@@ -1627,6 +2710,7 @@ async fn execute_step(ctx: StepExecutionContext<'_>) -> Result<StepResult, Execu
                             name: step_name,
                             status: StepStatus::Failure,
                             output: format!("{}\n{}", output_text, error_details),
+                            duration: Duration::ZERO,
                         });
                     }
 
@@ -1643,6 +2727,7 @@ async fn execute_step(ctx: StepExecutionContext<'_>) -> Result<StepResult, Execu
 {}",
                             output.exit_code, output.stdout, output.stderr
                         ),
+                        duration: Duration::ZERO,
                     }
                 } else {
                     StepResult {
@@ -1652,6 +2737,7 @@ async fn execute_step(ctx: StepExecutionContext<'_>) -> Result<StepResult, Execu
                             "Exit code: {}\n{}\n{}",
                             output.exit_code, output.stdout, output.stderr
                         ),
+                        duration: Duration::ZERO,
                     }
                 }
             }
@@ -1663,28 +2749,74 @@ async fn execute_step(ctx: StepExecutionContext<'_>) -> Result<StepResult, Execu
         let mut error_details = None;
 
         // Perform secret substitution if secret manager is available
+        let run = &match ctx.matrix_combination {
+            Some(matrix) => substitution::preprocess_command(run, matrix),
+            None => run.clone(),
+        };
+        let run_with_token = expressions.substitute(run, &step_env);
         let resolved_run = if let Some(secret_manager) = ctx.secret_manager {
             let mut substitution = SecretSubstitution::new(secret_manager);
-            match substitution.substitute(run).await {
+            let resolved = substitution.substitute(&run_with_token).await;
+            mask_resolved_secrets(&ctx, &substitution);
+            match resolved {
                 Ok(resolved) => resolved,
                 Err(e) => {
                     return Ok(StepResult {
                         name: step_name,
                         status: StepStatus::Failure,
                         output: format!("Secret substitution failed: {}", e),
+                        duration: Duration::ZERO,
                     });
                 }
             }
         } else {
-            run.clone()
+            run_with_token
         };
 
         // Check if this is a cargo command
         let is_cargo_cmd = resolved_run.trim().starts_with("cargo");
 
-        // For complex shell commands, use bash to execute them properly
-        // This handles quotes, pipes, redirections, and command substitutions correctly
-        let cmd_parts = vec!["bash", "-c", &resolved_run];
+        // Step settings override the job's and workflow's `defaults.run`
+        // Windows runners default to PowerShell Core
+        let os_default_shell = (ctx.job_env.get("RUNNER_OS").map(String::as_str)
+            == Some(RunnerOs::Windows.name()))
+        .then_some("pwsh");
+        let shell = ctx
+            .step
+            .shell
+            .as_deref()
+            .or(ctx.run_defaults.shell.as_deref())
+            .or(os_default_shell);
+        let step_working_dir = ctx
+            .step
+            .working_directory
+            .as_deref()
+            .or(ctx.run_defaults.working_directory.as_deref());
+
+        let command = match shell::shell_command(shell, &resolved_run, |script| {
+            // Custom shells read the script from a file; the GitHub files directory
+            // is mounted at the same path in containers
+            let github_dir = ctx
+                .job_env
+                .get("GITHUB_ENV")
+                .and_then(|path| Path::new(path).parent())
+                .ok_or_else(|| "No directory available for the step script".to_string())?;
+            let script_path = github_dir.join(format!("step_{}_script", ctx.step_idx));
+            fs::write(&script_path, script)
+                .map_err(|e| format!("Failed to write step script: {}", e))?;
+            Ok(script_path.to_string_lossy().into_owned())
+        }) {
+            Ok(command) => command,
+            Err(e) => {
+                return Ok(StepResult {
+                    name: step_name,
+                    status: StepStatus::Failure,
+                    output: e,
+                    duration: Duration::ZERO,
+                });
+            }
+        };
+        let cmd_parts: Vec<&str> = command.iter().map(String::as_str).collect();
 
         // Convert environment variables to the required format
         let env_vars: Vec<(&str, &str)> = step_env
@@ -1694,6 +2826,11 @@ async fn execute_step(ctx: StepExecutionContext<'_>) -> Result<StepResult, Execu
 
         // Define the standard workspace path inside the container
         let container_workspace = Path::new("/github/workspace");
+        // Relative working directories are resolved against the workspace
+        let container_working_dir = match step_working_dir {
+            Some(dir) => container_workspace.join(dir),
+            None => container_workspace.to_path_buf(),
+        };
 
         // Set up volume mapping from host working dir to container workspace
         let mut volumes: Vec<(&Path, &Path)> = vec![(ctx.working_dir, container_workspace)];
@@ -1714,7 +2851,7 @@ async fn execute_step(ctx: StepExecutionContext<'_>) -> Result<StepResult, Execu
                 ctx.runner_image,
                 &cmd_parts,
                 &env_vars,
-                container_workspace,
+                &container_working_dir,
                 &volumes,
             )
             .await
@@ -1780,12 +2917,14 @@ async fn execute_step(ctx: StepExecutionContext<'_>) -> Result<StepResult, Execu
             name: step_name,
             status,
             output,
+            duration: Duration::ZERO,
         }
     } else {
         return Ok(StepResult {
             name: step_name,
             status: StepStatus::Skipped,
             output: "Step has neither 'uses' nor 'run'".to_string(),
+            duration: Duration::ZERO,
         });
     };
 
@@ -1999,14 +3138,432 @@ fn get_runner_image(runs_on: &str) -> String {
     .to_string()
 }
 
-fn get_runner_image_from_opt(runs_on: &Option<Vec<String>>) -> String {
-    let default = "ubuntu-latest";
-    let ro = runs_on
-        .as_ref()
-        .and_then(|vec| vec.first())
-        .map(|s| s.as_str())
-        .unwrap_or(default);
-    get_runner_image(ro)
+/// Give a job the vars and secrets of its deployment environment from
+/// `.wrkflw.toml`, returning the environment's name
+///
+/// Protection rules can't be honored locally, so they are only warned about.
+/// Fail with every use of an action or reusable workflow in `workflow` that
+/// `policy` forbids
+fn check_action_policy(
+    policy: &ActionPolicy,
+    workflow: &WorkflowDefinition,
+    path: &Path,
+) -> Result<(), ExecutionError> {
+    let violations = policy.violations(workflow);
+    if violations.is_empty() {
+        return Ok(());
+    }
+    Err(ExecutionError::Execution(format!(
+        "{} uses actions the policy in {} forbids:\n{}",
+        path.display(),
+        config::PROJECT_CONFIG_FILE,
+        violations
+            .iter()
+            .map(|violation| format!("  - {}", violation))
+            .collect::<Vec<_>>()
+            .join("\n")
+    )))
+}
+
+fn enter_environment(job_name: &str, name: &str, job_env: &mut HashMap<String, String>) -> String {
+    // `environment: ${{ inputs.target }}` is common
+    let name = ExpressionContext::from_env(job_env).substitute(name, job_env);
+    let context = context::current();
+    let Some(environment) = context.environments.get(&name) else {
+        wrkflw_logging::info(&format!(
+            "Job '{}' deploys to '{}', which is not configured in {}; using the run's vars and secrets",
+            job_name,
+            name,
+            config::PROJECT_CONFIG_FILE
+        ));
+        return name;
+    };
+    wrkflw_logging::info(&format!(
+        "Job '{}' deploys to environment '{}'",
+        job_name, name
+    ));
+    if !environment.required_reviewers.is_empty() {
+        wrkflw_logging::warning(&format!(
+            "Environment '{}' requires a review by {} on GitHub; running job '{}' without one",
+            name,
+            environment.required_reviewers.join(", "),
+            job_name
+        ));
+    }
+    if let Some(minutes) = environment.wait_timer.filter(|minutes| *minutes > 0) {
+        wrkflw_logging::warning(&format!(
+            "Environment '{}' has a {}-minute wait timer on GitHub; running job '{}' without waiting",
+            name, minutes, job_name
+        ));
+    }
+    environment::enter_environment(job_env, environment);
+    name
+}
+
+/// Where a job's steps run
+pub(crate) struct JobRunner {
+    pub(crate) image: String,
+    /// On the host, whichever runtime was selected
+    pub(crate) native: bool,
+}
+
+/// Decide where a job runs from its `runs-on` labels and set `RUNNER_OS`
+///
+/// Runners mapped in `.wrkflw.toml` take precedence. Returns the result of a job
+/// that is skipped or failed because no local runner can host it.
+pub(crate) fn resolve_runner(
+    job_name: &str,
+    runs_on: &[String],
+    job_env: &mut HashMap<String, String>,
+) -> Result<JobRunner, Box<JobResult>> {
+    if let Some(mapping) = config::find_runner(&context::current().runners, runs_on).cloned() {
+        let labels = runs_on.join(", ");
+        if mapping.native {
+            wrkflw_logging::info(&format!(
+                "Running job '{}' ({}) natively as configured in {}",
+                job_name,
+                labels,
+                config::PROJECT_CONFIG_FILE
+            ));
+            job_env.insert("WRKFLW_RUNTIME_MODE".to_string(), "emulation".to_string());
+            job_env.insert("RUNNER_OS".to_string(), RunnerOs::host().name().to_string());
+            return Ok(JobRunner {
+                image: get_runner_image("ubuntu-latest"),
+                native: true,
+            });
+        }
+        let image = mapping.image.unwrap_or_default();
+        wrkflw_logging::info(&format!(
+            "Running job '{}' ({}) in {} as configured in {}",
+            job_name,
+            labels,
+            image,
+            config::PROJECT_CONFIG_FILE
+        ));
+        job_env.insert("RUNNER_OS".to_string(), RunnerOs::Linux.name().to_string());
+        return Ok(JobRunner {
+            image,
+            native: false,
+        });
+    }
+
+    let emulated = matches!(
+        job_env.get("WRKFLW_RUNTIME_MODE").map(String::as_str),
+        Some("emulation" | "secure_emulation")
+    );
+    let policy = context::current().runner_os_policy.clone();
+    let default_image =
+        || get_runner_image(runs_on.first().map_or("ubuntu-latest", |s| s.as_str()));
+
+    let (image, os) = match runner::place_job(runs_on, emulated, &policy) {
+        RunnerPlacement::Default => (default_image(), RunnerOs::Linux),
+        RunnerPlacement::Image(image) => {
+            wrkflw_logging::warning(&format!(
+                "Job '{}' targets {}; running it in {} instead",
+                job_name,
+                runs_on.join(", "),
+                image
+            ));
+            (image, RunnerOs::Linux)
+        }
+        RunnerPlacement::Native(os) => {
+            wrkflw_logging::info(&format!(
+                "Running job '{}' natively on the {} host",
+                job_name,
+                os.name()
+            ));
+            (default_image(), os)
+        }
+        RunnerPlacement::Skip(reason) => {
+            wrkflw_logging::warning(&format!("Job '{}': {}", job_name, reason));
+            return Err(Box::new(JobResult {
+                name: job_name.to_string(),
+                status: JobStatus::Skipped,
+                steps: Vec::new(),
+                logs: reason,
+                environment: None,
+                timing: JobTiming::default(),
+            }));
+        }
+        RunnerPlacement::Fail(reason) => {
+            wrkflw_logging::error(&format!("Job '{}': {}", job_name, reason));
+            return Err(Box::new(JobResult {
+                name: job_name.to_string(),
+                status: JobStatus::Failure,
+                steps: Vec::new(),
+                logs: reason,
+                environment: None,
+                timing: JobTiming::default(),
+            }));
+        }
+    };
+
+    job_env.insert("RUNNER_OS".to_string(), os.name().to_string());
+    Ok(JobRunner {
+        image,
+        native: false,
+    })
+}
+
+/// A step's `with:` inputs, with matrix values such as `${{ matrix.node }}` filled in
+fn step_inputs(ctx: &StepExecutionContext<'_>) -> HashMap<String, String> {
+    let mut inputs = ctx.step.with.clone().unwrap_or_default();
+    if let Some(matrix) = ctx.matrix_combination {
+        for value in inputs.values_mut() {
+            *value = substitution::preprocess_command(value, matrix);
+        }
+    }
+    inputs
+}
+
+/// Emulate `actions/checkout` in the job's workspace
+///
+/// Emulation runs steps in the project directory itself, so a plain checkout of the
+/// project is a no-op there. Anything else is checked out into the job directory,
+/// which then becomes the workspace so the project is never overwritten. The same
+/// goes for containers with the project bind-mounted as the workspace, where only
+/// checkouts into a `path:` are possible.
+fn checkout_step(
+    ctx: &StepExecutionContext<'_>,
+    step_name: String,
+) -> Result<StepResult, ExecutionError> {
+    let step_result = |status, output| StepResult {
+        name: step_name.clone(),
+        status,
+        output,
+        duration: Duration::ZERO,
+    };
+    let mut options = match checkout::CheckoutOptions::from_inputs(&step_inputs(ctx)) {
+        Ok(options) => options,
+        Err(e) => return Ok(step_result(StepStatus::Failure, e)),
+    };
+    let project_dir = std::env::current_dir()
+        .map_err(|e| ExecutionError::Execution(format!("Failed to get current dir: {}", e)))?;
+    let own_repository = ctx
+        .job_env
+        .get("GITHUB_REPOSITORY")
+        .cloned()
+        .unwrap_or_default();
+    let emulated = matches!(
+        ctx.job_env.get("WRKFLW_RUNTIME_MODE").map(String::as_str),
+        Some("emulation" | "secure_emulation")
+    );
+    let workspace = ctx.job_env.get("GITHUB_WORKSPACE").map(Path::new);
+
+    let mut notes = Vec::new();
+    if emulated {
+        if workspace == Some(project_dir.as_path()) {
+            if options.is_working_tree(&own_repository) {
+                return Ok(step_result(
+                    StepStatus::Success,
+                    "Emulated checkout: using the project directory as the workspace".to_string(),
+                ));
+            }
+            // Keep the project next to a checkout into a subdirectory
+            if options.path.is_some() {
+                copy_directory_contents(&project_dir, ctx.working_dir)?;
+            }
+        }
+        if workspace != Some(ctx.working_dir) {
+            let moved = [(
+                "GITHUB_WORKSPACE".to_string(),
+                ctx.working_dir.to_string_lossy().into_owned(),
+            )];
+            environment::append_github_env(ctx.job_env, &moved).map_err(|e| {
+                ExecutionError::Execution(format!("Failed to move the workspace: {}", e))
+            })?;
+            notes.push(format!(
+                "The workspace is now {}",
+                ctx.working_dir.display()
+            ));
+        }
+    }
+
+    let strategy = context::current().workspace;
+    if !emulated && ctx.working_dir == project_dir {
+        if options.is_working_tree(&own_repository) {
+            return Ok(step_result(
+                StepStatus::Success,
+                "Bind-mounted workspace: using the project directory as it is".to_string(),
+            ));
+        }
+        if options.path.is_none() {
+            return Ok(step_result(
+                StepStatus::Failure,
+                format!(
+                    "Checking out {} would overwrite the bind-mounted project; check it out into a `path:` or run with --workspace copy or clone",
+                    options.repository.as_deref().unwrap_or(&own_repository)
+                ),
+            ));
+        }
+    }
+
+    let target = match &options.path {
+        Some(path) => ctx.working_dir.join(path),
+        None => ctx.working_dir.to_path_buf(),
+    };
+    // A placeholder token would only make GitHub refuse public repositories
+    if options.token.is_none()
+        && !ctx
+            .job_env
+            .contains_key(environment::GITHUB_TOKEN_PLACEHOLDER_KEY)
+    {
+        options.token = ctx
+            .job_env
+            .get(environment::GITHUB_TOKEN_CONTEXT_KEY)
+            .filter(|token| !token.is_empty())
+            .cloned();
+    }
+
+    let own = options.is_own_repository(&own_repository);
+    let source = if !own {
+        let repository = options.repository.as_deref().unwrap_or_default();
+        if action_cache::is_offline() {
+            return Ok(step_result(
+                StepStatus::Failure,
+                format!("Cannot check out {} while running offline", repository),
+            ));
+        }
+        checkout::Source::Remote(format!("{}/{}", options.server_url, repository))
+    } else if project_dir.join(".git").exists() {
+        checkout::Source::Local(project_dir.clone())
+    } else {
+        // Without git there is only the working tree to copy
+        if strategy == WorkspaceStrategy::Clone {
+            return Ok(step_result(
+                StepStatus::Failure,
+                format!(
+                    "Cannot clone the project for --workspace clone: {} is not a git repository",
+                    project_dir.display()
+                ),
+            ));
+        }
+        if let Some(git_ref) = &options.git_ref {
+            return Ok(step_result(
+                StepStatus::Failure,
+                format!(
+                    "Cannot check out ref '{}': {} is not a git repository",
+                    git_ref,
+                    project_dir.display()
+                ),
+            ));
+        }
+        copy_directory_contents(&project_dir, &target)?;
+        notes.insert(
+            0,
+            "Emulated checkout: copied the project into the workspace".to_string(),
+        );
+        return Ok(step_result(StepStatus::Success, notes.join("\n")));
+    };
+
+    match checkout::checkout(&options, &source, &target) {
+        Ok(summary) => {
+            notes.insert(0, summary);
+            // Like running in place, the checkout includes uncommitted changes, unless
+            // a fresh clone was asked for
+            if own && options.git_ref.is_none() && strategy != WorkspaceStrategy::Clone {
+                copy_directory_contents(&project_dir, &target)?;
+                notes.push("Copied uncommitted changes from the project".to_string());
+            }
+            wrkflw_logging::info(&notes[0]);
+            Ok(step_result(StepStatus::Success, notes.join("\n")))
+        }
+        Err(e) => Ok(step_result(StepStatus::Failure, e)),
+    }
+}
+
+/// The tool a setup action installs, when it can be installed on the host
+///
+/// Only plain emulation installs tools; containers come with their own toolchains.
+pub(crate) fn installable_tool(
+    uses: &str,
+    job_env: &HashMap<String, String>,
+) -> Option<toolcache::Tool> {
+    let emulated = job_env.get("WRKFLW_RUNTIME_MODE").map(String::as_str) == Some("emulation");
+    toolcache::Tool::from_action(uses).filter(|_| emulated && toolcache::supported_host())
+}
+
+/// Emulate `actions/setup-*` by installing the requested version into the tool cache
+/// and adding it to PATH for the following steps
+async fn setup_tool(
+    tool: toolcache::Tool,
+    ctx: &StepExecutionContext<'_>,
+    step_name: String,
+) -> StepResult {
+    let inputs = step_inputs(ctx);
+    let cache = toolcache::ToolCache::new(
+        ctx.job_env
+            .get("RUNNER_TOOL_CACHE")
+            .map(PathBuf::from)
+            .unwrap_or_else(toolcache::ToolCache::default_root),
+    )
+    .offline(action_cache::is_offline());
+
+    let setup = match cache.setup(tool, &inputs, ctx.working_dir).await {
+        Ok(Some(setup)) => setup,
+        Ok(None) => {
+            let found = which::which(tool.command());
+            return StepResult {
+                name: step_name,
+                status: StepStatus::Success,
+                output: match found {
+                    Ok(path) => format!(
+                        "No {} version requested; using {}",
+                        tool.name(),
+                        path.display()
+                    ),
+                    Err(_) => format!(
+                        "No {} version requested and none is installed; later steps using it will fail",
+                        tool.name()
+                    ),
+                },
+                duration: Duration::ZERO,
+            };
+        }
+        Err(e) => {
+            return StepResult {
+                name: step_name,
+                status: StepStatus::Failure,
+                output: e,
+                duration: Duration::ZERO,
+            }
+        }
+    };
+
+    let path: Vec<String> = setup
+        .path
+        .iter()
+        .map(|dir| dir.to_string_lossy().into_owned())
+        .collect();
+    if let Err(e) = environment::append_github_path(ctx.job_env, &path)
+        .and_then(|_| environment::append_github_env(ctx.job_env, &setup.env))
+    {
+        return StepResult {
+            name: step_name,
+            status: StepStatus::Failure,
+            output: format!("Failed to update GITHUB_PATH/GITHUB_ENV: {}", e),
+            duration: Duration::ZERO,
+        };
+    }
+
+    let message = format!(
+        "{} {} {} {}",
+        if setup.downloaded {
+            "Installed"
+        } else {
+            "Using cached"
+        },
+        tool.name(),
+        setup.version,
+        setup.dir.display()
+    );
+    wrkflw_logging::info(&message);
+    StepResult {
+        name: step_name,
+        status: StepStatus::Success,
+        output: message,
+        duration: Duration::ZERO,
+    }
 }
 
 async fn execute_reusable_workflow_job(
@@ -2024,10 +3581,8 @@ async fn execute_reusable_workflow_job(
     enum UsesRef<'a> {
         LocalPath(&'a str),
         Remote {
-            owner: String,
-            repo: String,
+            repo_ref: action_cache::RepoRef,
             path: String,
-            r#ref: String,
         },
     }
 
@@ -2035,31 +3590,10 @@ async fn execute_reusable_workflow_job(
         UsesRef::LocalPath(uses)
     } else {
         // Expect format owner/repo/path/to/workflow.yml@ref
-        let parts: Vec<&str> = uses.split('@').collect();
-        if parts.len() != 2 {
-            return Err(ExecutionError::Execution(format!(
-                "Invalid reusable workflow reference: {}",
-                uses
-            )));
-        }
-        let left = parts[0];
-        let r#ref = parts[1].to_string();
-        let mut segs = left.splitn(3, '/');
-        let owner = segs.next().unwrap_or("").to_string();
-        let repo = segs.next().unwrap_or("").to_string();
-        let path = segs.next().unwrap_or("").to_string();
-        if owner.is_empty() || repo.is_empty() || path.is_empty() {
-            return Err(ExecutionError::Execution(format!(
-                "Invalid reusable workflow reference: {}",
-                uses
-            )));
-        }
-        UsesRef::Remote {
-            owner,
-            repo,
-            path,
-            r#ref,
-        }
+        let (repo_ref, path) = action_cache::parse_workflow_uses(uses).ok_or_else(|| {
+            ExecutionError::Execution(format!("Invalid reusable workflow reference: {}", uses))
+        })?;
+        UsesRef::Remote { repo_ref, path }
     };
 
     // Load workflow file
@@ -2078,38 +3612,16 @@ async fn execute_reusable_workflow_job(
             }
             path
         }
-        UsesRef::Remote {
-            owner,
-            repo,
-            path,
-            r#ref,
-        } => {
-            // Clone minimal repository and checkout ref
-            let tempdir = tempfile::tempdir().map_err(|e| {
-                ExecutionError::Execution(format!("Failed to create temp dir: {}", e))
-            })?;
-            let repo_url = format!("https://github.com/{}/{}.git", owner, repo);
-
-            // Clone into a subdirectory within tempdir to get clean structure
-            let repo_dir = tempdir.path().join("cloned_repo");
-
-            // git clone
-            let status = Command::new("git")
-                .arg("clone")
-                .arg("--depth")
-                .arg("1")
-                .arg("--branch")
-                .arg(&r#ref)
-                .arg(&repo_url)
-                .arg(&repo_dir)
-                .status()
-                .map_err(|e| ExecutionError::Execution(format!("Failed to execute git: {}", e)))?;
-            if !status.success() {
-                return Err(ExecutionError::Execution(format!(
-                    "Failed to clone {}@{}",
-                    repo_url, r#ref
-                )));
-            }
+        UsesRef::Remote { repo_ref, path } => {
+            let repo_dir = ActionCache::new(ActionCache::default_root())
+                .offline(action_cache::is_offline())
+                .fetch(&repo_ref, None)
+                .map_err(|e| {
+                    ExecutionError::Execution(format!(
+                        "Failed to fetch reusable workflow {}: {}",
+                        uses, e
+                    ))
+                })?;
             let joined = repo_dir.join(path);
 
             if !joined.exists() {
@@ -2119,8 +3631,8 @@ async fn execute_reusable_workflow_job(
                 )));
             }
 
-            // Parse called workflow while keeping tempdir alive
             let called = parse_workflow(&joined)?;
+            check_action_policy(&context::current().action_policy, &called, &joined)?;
 
             // Create child env context
             let mut child_env = ctx.env_context.clone();
@@ -2181,6 +3693,7 @@ async fn execute_reusable_workflow_job(
                     StepStatus::Success
                 },
                 output: logs.clone(),
+                duration: Duration::ZERO,
             };
 
             return Ok(JobResult {
@@ -2192,12 +3705,15 @@ async fn execute_reusable_workflow_job(
                 },
                 steps: vec![summary_step],
                 logs,
+                environment: None,
+                timing: JobTiming::default(),
             });
         }
     };
 
     // Parse called workflow (for local paths)
     let called = parse_workflow(&workflow_path)?;
+    check_action_policy(&context::current().action_policy, &called, &workflow_path)?;
 
     // Create child env context
     let mut child_env = ctx.env_context.clone();
@@ -2255,6 +3771,7 @@ async fn execute_reusable_workflow_job(
             StepStatus::Success
         },
         output: logs.clone(),
+        duration: Duration::ZERO,
     };
 
     Ok(JobResult {
@@ -2266,6 +3783,8 @@ async fn execute_reusable_workflow_job(
         },
         steps: vec![summary_step],
         logs,
+        environment: None,
+        timing: JobTiming::default(),
     })
 }
 
@@ -2421,8 +3940,13 @@ async fn execute_composite_action(
                         name: "Composite Action".to_string(),
                         on: vec![],
                         on_raw: serde_yaml::Value::Null,
+                        permissions: None,
+                        defaults: None,
+                        env: HashMap::new(),
                         jobs: HashMap::new(),
                     },
+                    // Composite run steps must declare their shell
+                    run_defaults: &workflow::RunDefaults::default(),
                     runner_image,
                     verbose,
                     matrix_combination: &None,
@@ -2443,6 +3967,7 @@ async fn execute_composite_action(
                             .unwrap_or_else(|| "Composite Action".to_string()),
                         status: StepStatus::Failure,
                         output: step_outputs.join("\n"),
+                        duration: Duration::ZERO,
                     });
                 }
             }
@@ -2492,6 +4017,7 @@ async fn execute_composite_action(
                     .unwrap_or_else(|| "Composite Action".to_string()),
                 status: StepStatus::Success,
                 output,
+                duration: Duration::ZERO,
             })
         }
         _ => Err(ExecutionError::Execution(
@@ -2503,6 +4029,17 @@ async fn execute_composite_action(
 // Helper function to convert YAML step to our Step struct
 fn convert_yaml_to_step(step_yaml: &serde_yaml::Value) -> Result<workflow::Step, String> {
     // Extract step properties
+    let id = step_yaml
+        .get("id")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
+    let if_condition = step_yaml.get("if").and_then(|v| match v {
+        serde_yaml::Value::String(s) => Some(s.clone()),
+        serde_yaml::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    });
+
     let name = step_yaml
         .get("name")
         .and_then(|v| v.as_str())
@@ -2523,6 +4060,11 @@ fn convert_yaml_to_step(step_yaml: &serde_yaml::Value) -> Result<workflow::Step,
         .and_then(|v| v.as_str())
         .map(|s| s.to_string());
 
+    let working_directory = step_yaml
+        .get("working-directory")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
     let with = step_yaml.get("with").and_then(|v| v.as_mapping()).map(|m| {
         let mut with_map = HashMap::new();
         for (k, v) in m {
@@ -2554,18 +4096,22 @@ fn convert_yaml_to_step(step_yaml: &serde_yaml::Value) -> Result<workflow::Step,
     let continue_on_error = step_yaml.get("continue-on-error").and_then(|v| v.as_bool());
 
     Ok(workflow::Step {
+        id,
         name,
+        if_condition,
         uses,
         run: final_run,
         with,
         env,
         continue_on_error,
+        shell,
+        working_directory,
     })
 }
 
 /// Evaluate a job condition expression
 /// This is a simplified implementation that handles basic GitHub Actions expressions
-fn evaluate_job_condition(
+pub(crate) fn evaluate_job_condition(
     condition: &str,
     env_context: &HashMap<String, String>,
     workflow: &WorkflowDefinition,
@@ -2583,6 +4129,11 @@ fn evaluate_job_condition(
         return false;
     }
 
+    // Handle github.<name> == 'value' against the run's github context
+    if let Some(result) = substitution::evaluate_github_comparison(condition, env_context) {
+        return result;
+    }
+
     // Handle github.event.pull_request.draft == false
     if condition.contains("github.event.pull_request.draft == false") {
         // For local execution, assume this is always true (not a draft)
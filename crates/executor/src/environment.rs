@@ -1,8 +1,89 @@
+use crate::config::EnvironmentConfig;
+use crate::runs::RunStore;
+use crate::substitution::{ExpressionContext, StepContext};
 use chrono::Utc;
 use serde_yaml::Value;
 use std::{collections::HashMap, fs, io, path::Path};
 use wrkflw_matrix::MatrixCombination;
 use wrkflw_parser::workflow::WorkflowDefinition;
+use wrkflw_utils::auth::TokenSource;
+
+/// Context key carrying the run's `GITHUB_TOKEN`. Steps only see the token
+/// through `${{ secrets.GITHUB_TOKEN }}` or `${{ github.token }}`.
+pub const GITHUB_TOKEN_CONTEXT_KEY: &str = "WRKFLW_GITHUB_TOKEN";
+
+/// Context key set when the run's `GITHUB_TOKEN` is a generated placeholder, so
+/// that nothing sends it to GitHub as a credential
+pub const GITHUB_TOKEN_PLACEHOLDER_KEY: &str = "WRKFLW_GITHUB_TOKEN_PLACEHOLDER";
+
+/// Context key carrying the run's `workflow_dispatch` inputs as a JSON object. Steps
+/// only see them through `${{ inputs.<name> }}` or `${{ github.event.inputs.<name> }}`.
+pub const INPUTS_CONTEXT_KEY: &str = "WRKFLW_INPUTS";
+
+/// Context key carrying the run's configuration variables as a JSON object. Steps
+/// only see them through `${{ vars.<name> }}`.
+pub const VARS_CONTEXT_KEY: &str = "WRKFLW_VARS";
+
+/// Context key carrying the secrets of a job's deployment environment as a JSON
+/// object, from the name jobs use to the secret it is read as.
+pub const SECRET_ALIASES_CONTEXT_KEY: &str = "WRKFLW_SECRET_ALIASES";
+
+/// Context key carrying the variables given with `--env`/`--env-file` as a JSON
+/// object, so jobs and steps can put them back over the env they define.
+pub const USER_ENV_CONTEXT_KEY: &str = "WRKFLW_USER_ENV";
+
+/// Context key carrying the outputs, outcome and conclusion of the job's steps
+/// that have an `id:` as a JSON object, for `${{ steps.<id>.* }}`
+pub const STEPS_CONTEXT_KEY: &str = "WRKFLW_STEPS";
+
+/// Where the `GITHUB_TOKEN` for a run came from
+#[derive(Debug, Clone, PartialEq)]
+pub enum GithubTokenSource {
+    /// Passed explicitly through the execution config (e.g. `--github-token`)
+    Config,
+    /// Found in the environment or the gh CLI's credentials, when the run opted in
+    Discovered(TokenSource),
+    /// A random placeholder that no API will accept
+    Generated,
+}
+
+impl std::fmt::Display for GithubTokenSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GithubTokenSource::Config => write!(f, "configuration"),
+            GithubTokenSource::Discovered(source) => source.fmt(f),
+            GithubTokenSource::Generated => write!(f, "generated placeholder"),
+        }
+    }
+}
+
+/// Resolve the token exposed to a run: the explicit value, then, only with
+/// `from_env`, whatever `wrkflw_utils::auth::github_token` finds, falling back to
+/// a fake `ghs_` token. The host's credentials never reach a run unasked.
+pub fn resolve_github_token(explicit: Option<&str>, from_env: bool) -> (String, GithubTokenSource) {
+    if let Some(token) = explicit.filter(|t| !t.is_empty()) {
+        return (token.to_string(), GithubTokenSource::Config);
+    }
+
+    if from_env {
+        if let Some(credential) = wrkflw_utils::auth::github_token() {
+            return (
+                credential.token,
+                GithubTokenSource::Discovered(credential.source),
+            );
+        }
+    }
+
+    let random = format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    (
+        format!("ghs_{}", &random[..36]),
+        GithubTokenSource::Generated,
+    )
+}
 
 pub fn setup_github_environment_files(workspace_dir: &Path) -> io::Result<()> {
     // Create necessary directories
@@ -24,60 +105,362 @@ pub fn setup_github_environment_files(workspace_dir: &Path) -> io::Result<()> {
     Ok(())
 }
 
+/// PATH of job containers before any `GITHUB_PATH` additions
+const CONTAINER_PATH: &str = "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin";
+
+/// Append directories to the `GITHUB_PATH` file, highest precedence first
+pub fn append_github_path(env: &HashMap<String, String>, dirs: &[String]) -> io::Result<()> {
+    // Each line is prepended to PATH in turn, so the last line ends up first
+    let lines: String = dirs.iter().rev().map(|dir| format!("{}\n", dir)).collect();
+    append_to_env_file(env, "GITHUB_PATH", &lines)
+}
+
+/// Append variables to the `GITHUB_ENV` file
+pub fn append_github_env(
+    env: &HashMap<String, String>,
+    vars: &[(String, String)],
+) -> io::Result<()> {
+    let lines: String = vars
+        .iter()
+        .map(|(key, value)| format!("{}={}\n", key, value))
+        .collect();
+    append_to_env_file(env, "GITHUB_ENV", &lines)
+}
+
+fn append_to_env_file(env: &HashMap<String, String>, key: &str, content: &str) -> io::Result<()> {
+    let path = env
+        .get(key)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{} is not set", key)))?;
+    let mut file = fs::OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)?;
+    io::Write::write_all(&mut file, content.as_bytes())
+}
+
+/// Apply what a step wrote to `GITHUB_ENV` and `GITHUB_PATH` to the environment of
+/// the job's later steps, then empty the files for the next step
+pub fn apply_github_env_files(job_env: &mut HashMap<String, String>) {
+    if let Some(path) = job_env.get("GITHUB_ENV").cloned() {
+        if let Ok(content) = fs::read_to_string(&path) {
+            job_env.extend(parse_env_file(&content));
+            let _ = fs::write(&path, "");
+        }
+    }
+
+    if let Some(path) = job_env.get("GITHUB_PATH").cloned() {
+        if let Ok(content) = fs::read_to_string(&path) {
+            let emulated = matches!(
+                job_env.get("WRKFLW_RUNTIME_MODE").map(String::as_str),
+                Some("emulation" | "secure_emulation")
+            );
+            for dir in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                let current = job_env.get("PATH").cloned().unwrap_or_else(|| {
+                    if emulated {
+                        std::env::var("PATH").unwrap_or_default()
+                    } else {
+                        CONTAINER_PATH.to_string()
+                    }
+                });
+                job_env.insert("PATH".to_string(), format!("{}:{}", dir, current));
+            }
+            let _ = fs::write(&path, "");
+        }
+    }
+}
+
+/// Take the outputs a step wrote to `GITHUB_OUTPUT`, emptying the file for the
+/// next step
+pub fn take_step_outputs(job_env: &HashMap<String, String>) -> HashMap<String, String> {
+    let Some(path) = job_env.get("GITHUB_OUTPUT") else {
+        return HashMap::new();
+    };
+    match fs::read_to_string(path) {
+        Ok(content) => {
+            let _ = fs::write(path, "");
+            parse_env_file(&content).into_iter().collect()
+        }
+        Err(_) => HashMap::new(),
+    }
+}
+
+/// Add what the step with `id` left to the job's `steps` context
+pub fn record_step(job_env: &mut HashMap<String, String>, id: &str, step: StepContext) {
+    let mut steps = ExpressionContext::from_env(job_env).steps;
+    steps.insert(id.to_string(), step);
+    job_env.insert(
+        STEPS_CONTEXT_KEY.to_string(),
+        serde_json::to_string(&steps).unwrap_or_default(),
+    );
+}
+
+/// `NAME=value` lines and `NAME<<DELIMITER` multiline values
+fn parse_env_file(content: &str) -> Vec<(String, String)> {
+    let mut vars = Vec::new();
+    let mut lines = content.lines();
+    while let Some(line) = lines.next() {
+        if let Some((name, delimiter)) = line.split_once("<<") {
+            let mut value = Vec::new();
+            for line in lines.by_ref() {
+                if line == delimiter {
+                    break;
+                }
+                value.push(line);
+            }
+            vars.push((name.trim().to_string(), value.join("\n")));
+        } else if let Some((name, value)) = line.split_once('=') {
+            if !name.trim().is_empty() {
+                vars.push((name.trim().to_string(), value.to_string()));
+            }
+        }
+    }
+    vars
+}
+
+/// Add a level of `env:` (the workflow's, a job's or a step's) over `env`. Like on
+/// GitHub, its `${{ }}` expressions see the levels below it but not each other.
+pub fn apply_env_layer(env: &mut HashMap<String, String>, layer: &HashMap<String, String>) {
+    let expressions = ExpressionContext::from_env(env);
+    let values: Vec<(String, String)> = layer
+        .iter()
+        .map(|(key, value)| (key.clone(), expressions.substitute(value, env)))
+        .collect();
+    env.extend(values);
+}
+
+/// Give a job the variables and secrets of its deployment environment, over the
+/// run's own
+pub fn enter_environment(env: &mut HashMap<String, String>, environment: &EnvironmentConfig) {
+    if !environment.vars.is_empty() {
+        let mut vars = ExpressionContext::from_env(env).vars;
+        vars.extend(environment.vars.clone());
+        env.insert(
+            VARS_CONTEXT_KEY.to_string(),
+            serde_json::to_string(&vars).unwrap_or_default(),
+        );
+    }
+    if !environment.secrets.is_empty() {
+        env.insert(
+            SECRET_ALIASES_CONTEXT_KEY.to_string(),
+            serde_json::to_string(&environment.secrets).unwrap_or_default(),
+        );
+    }
+}
+
+/// Add variables given by the user to the run's context
+pub fn set_user_env(env_context: &mut HashMap<String, String>, vars: &HashMap<String, String>) {
+    if vars.is_empty() {
+        return;
+    }
+    env_context.extend(vars.clone());
+    env_context.insert(
+        USER_ENV_CONTEXT_KEY.to_string(),
+        serde_json::to_string(vars).unwrap_or_default(),
+    );
+}
+
+/// Put the variables given by the user back over what a job or step set
+pub fn apply_user_env(env: &mut HashMap<String, String>) {
+    let vars: HashMap<String, String> = env
+        .get(USER_ENV_CONTEXT_KEY)
+        .and_then(|vars| serde_json::from_str(vars).ok())
+        .unwrap_or_default();
+    env.extend(vars);
+}
+
+/// Variables of a `.env` file: `NAME=value` lines, optionally after `export`, with
+/// blank lines and `#` comments ignored. Values in double quotes may span lines and
+/// use `\n`, `\t`, `\"` and `\\` escapes; values in single quotes are taken as they
+/// are; unquoted values end at a ` #` comment and are trimmed.
+pub fn parse_dotenv(content: &str) -> Result<Vec<(String, String)>, String> {
+    let mut vars = Vec::new();
+    let mut lines = content.lines().enumerate();
+    while let Some((idx, line)) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+        let (name, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("line {}: expected NAME=value", idx + 1))?;
+        let name = name.trim();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        {
+            return Err(format!(
+                "line {}: invalid variable name '{}'",
+                idx + 1,
+                name
+            ));
+        }
+        let value = value.trim_start();
+
+        let value = if let Some(rest) = value.strip_prefix('"') {
+            let mut raw = rest.to_string();
+            let end = loop {
+                if let Some(end) = closing_quote(&raw) {
+                    break end;
+                }
+                let Some((_, next)) = lines.next() else {
+                    return Err(format!("line {}: unterminated double quote", idx + 1));
+                };
+                raw.push('\n');
+                raw.push_str(next);
+            };
+            unescape(&raw[..end])
+        } else if let Some(rest) = value.strip_prefix('\'') {
+            let end = rest
+                .find('\'')
+                .ok_or_else(|| format!("line {}: unterminated single quote", idx + 1))?;
+            rest[..end].to_string()
+        } else {
+            let end = value.find(" #").unwrap_or(value.len());
+            value[..end].trim_end().to_string()
+        };
+        vars.push((name.to_string(), value));
+    }
+    Ok(vars)
+}
+
+/// Variables of the `.env` file at `path`
+pub fn load_dotenv(path: &Path) -> Result<Vec<(String, String)>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_dotenv(&content).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// Position of the quote closing a double-quoted value, if read yet
+fn closing_quote(raw: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (pos, c) in raw.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(pos),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// The run's `github` context as the `GITHUB_*` variables of GitHub's runners, from
+/// the git repository in the current directory and the simulated `event`
 pub fn create_github_context(
     workflow: &WorkflowDefinition,
+    workflow_path: &Path,
+    event: Option<&str>,
     workspace_dir: &Path,
 ) -> HashMap<String, String> {
     let mut env = HashMap::new();
+    let event_name = event
+        .map(str::to_string)
+        .unwrap_or_else(|| get_event_name(workflow));
+    let remote = get_remote("github.com");
+    let git_ref = get_current_ref();
+    let (ref_name, ref_type) = ref_parts(&git_ref);
+    let actor = get_actor();
+
+    env.insert("CI".to_string(), "true".to_string());
+    env.insert("GITHUB_ACTIONS".to_string(), "true".to_string());
 
     // Basic GitHub environment variables
     env.insert("GITHUB_WORKFLOW".to_string(), workflow.name.clone());
     env.insert("GITHUB_ACTION".to_string(), "run".to_string());
-    env.insert("GITHUB_ACTOR".to_string(), "wrkflw".to_string());
-    env.insert("GITHUB_REPOSITORY".to_string(), get_repo_name());
-    env.insert("GITHUB_EVENT_NAME".to_string(), get_event_name(workflow));
-    env.insert("GITHUB_WORKSPACE".to_string(), get_workspace_path());
-    env.insert("GITHUB_SHA".to_string(), get_current_sha());
-    env.insert("GITHUB_REF".to_string(), get_current_ref());
-
-    // File paths for GitHub Actions
-    env.insert(
-        "GITHUB_OUTPUT".to_string(),
-        workspace_dir
-            .join("github")
-            .join("output")
-            .to_string_lossy()
-            .to_string(),
-    );
+    env.insert("GITHUB_ACTOR".to_string(), actor.clone());
+    env.insert("GITHUB_TRIGGERING_ACTOR".to_string(), actor);
     env.insert(
-        "GITHUB_ENV".to_string(),
-        workspace_dir
-            .join("github")
-            .join("env")
-            .to_string_lossy()
+        "GITHUB_REPOSITORY_OWNER".to_string(),
+        remote
+            .repository
+            .split('/')
+            .next()
+            .unwrap_or_default()
             .to_string(),
     );
+    env.insert("GITHUB_REPOSITORY".to_string(), remote.repository.clone());
+    env.insert("GITHUB_WORKSPACE".to_string(), get_workspace_path());
+    env.insert("GITHUB_SHA".to_string(), get_current_sha());
+
+    // Pull requests run on their branch; head and base are only set for them
+    let pull_request = event_name.starts_with("pull_request");
     env.insert(
-        "GITHUB_PATH".to_string(),
-        workspace_dir
-            .join("github")
-            .join("path")
-            .to_string_lossy()
-            .to_string(),
+        "GITHUB_HEAD_REF".to_string(),
+        if pull_request {
+            ref_name.clone()
+        } else {
+            String::new()
+        },
     );
     env.insert(
-        "GITHUB_STEP_SUMMARY".to_string(),
-        workspace_dir
-            .join("github")
-            .join("step_summary")
-            .to_string_lossy()
-            .to_string(),
+        "GITHUB_BASE_REF".to_string(),
+        if pull_request {
+            get_default_branch()
+        } else {
+            String::new()
+        },
     );
+    env.insert("GITHUB_REF".to_string(), git_ref);
+    env.insert("GITHUB_REF_NAME".to_string(), ref_name);
+    env.insert("GITHUB_REF_TYPE".to_string(), ref_type.to_string());
+    env.insert("GITHUB_EVENT_NAME".to_string(), event_name);
 
-    // Time-related variables
+    // Where the repository is hosted
+    let (server_url, api_url, graphql_url) = remote.urls();
+    env.insert("GITHUB_SERVER_URL".to_string(), server_url);
+    env.insert("GITHUB_API_URL".to_string(), api_url);
+    env.insert("GITHUB_GRAPHQL_URL".to_string(), graphql_url);
+
+    // File paths for GitHub Actions
+    for (name, file) in [
+        ("GITHUB_OUTPUT", "output"),
+        ("GITHUB_ENV", "env"),
+        ("GITHUB_PATH", "path"),
+        ("GITHUB_STEP_SUMMARY", "step_summary"),
+        ("GITHUB_EVENT_PATH", "event.json"),
+    ] {
+        env.insert(
+            name.to_string(),
+            workspace_dir
+                .join("github")
+                .join(file)
+                .to_string_lossy()
+                .to_string(),
+        );
+    }
+
+    // Run-related variables: the number counts the runs of the workflow in the history
     let now = Utc::now();
+    let workflow_file = workflow_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let run_number = std::env::current_dir()
+        .map(|dir| RunStore::for_project(&dir).next_run_number(&workflow_file))
+        .unwrap_or(1);
     env.insert("GITHUB_RUN_ID".to_string(), format!("{}", now.timestamp()));
-    env.insert("GITHUB_RUN_NUMBER".to_string(), "1".to_string());
+    env.insert("GITHUB_RUN_NUMBER".to_string(), run_number.to_string());
+    env.insert("GITHUB_RUN_ATTEMPT".to_string(), "1".to_string());
 
     // Path-related variables
     env.insert("RUNNER_TEMP".to_string(), get_temp_dir());
@@ -86,7 +469,195 @@ pub fn create_github_context(
     env
 }
 
-/// Add matrix context variables to the environment
+/// GitLab's predefined `CI_*` variables of a pipeline, from the git repository in
+/// the current directory and the simulated `event`
+pub fn create_gitlab_predefined(
+    pipeline_path: &Path,
+    event: Option<&str>,
+    workspace_dir: &Path,
+) -> HashMap<String, String> {
+    let mut env = HashMap::new();
+    let remote = get_remote("gitlab.com");
+    let git_ref = get_current_ref();
+    let (ref_name, ref_type) = ref_parts(&git_ref);
+    let sha = get_current_sha();
+
+    env.insert("CI".to_string(), "true".to_string());
+    env.insert("GITLAB_CI".to_string(), "true".to_string());
+    env.insert("CI_SERVER".to_string(), "yes".to_string());
+
+    // Where the project is hosted
+    let server_url = format!("https://{}", remote.host);
+    let project_name = remote
+        .repository
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let namespace = remote
+        .repository
+        .rsplit_once('/')
+        .map(|(namespace, _)| namespace.to_string())
+        .unwrap_or_default();
+    env.insert("CI_SERVER_HOST".to_string(), remote.host.clone());
+    env.insert("CI_SERVER_URL".to_string(), server_url.clone());
+    env.insert(
+        "CI_PROJECT_URL".to_string(),
+        format!("{}/{}", server_url, remote.repository),
+    );
+    env.insert("CI_PROJECT_NAME".to_string(), project_name);
+    env.insert("CI_PROJECT_NAMESPACE".to_string(), namespace);
+    env.insert(
+        "CI_PROJECT_PATH_SLUG".to_string(),
+        gitlab_slug(&remote.repository),
+    );
+    env.insert("CI_PROJECT_PATH".to_string(), remote.repository);
+    env.insert(
+        "CI_PROJECT_DIR".to_string(),
+        workspace_dir.to_string_lossy().to_string(),
+    );
+    env.insert("CI_DEFAULT_BRANCH".to_string(), get_default_branch());
+
+    // The commit and ref being built
+    env.insert(
+        "CI_COMMIT_SHORT_SHA".to_string(),
+        sha.chars().take(8).collect(),
+    );
+    env.insert("CI_COMMIT_SHA".to_string(), sha);
+    env.insert(
+        "CI_COMMIT_BEFORE_SHA".to_string(),
+        "0000000000000000000000000000000000000000".to_string(),
+    );
+    env.insert("CI_COMMIT_REF_SLUG".to_string(), gitlab_slug(&ref_name));
+    env.insert(
+        match ref_type {
+            "tag" => "CI_COMMIT_TAG",
+            _ => "CI_COMMIT_BRANCH",
+        }
+        .to_string(),
+        ref_name.clone(),
+    );
+    env.insert("CI_COMMIT_REF_NAME".to_string(), ref_name);
+    let message = git(&["log", "-1", "--format=%B"]).unwrap_or_default();
+    env.insert(
+        "CI_COMMIT_TITLE".to_string(),
+        message.lines().next().unwrap_or_default().to_string(),
+    );
+    env.insert("CI_COMMIT_MESSAGE".to_string(), message);
+    env.insert(
+        "CI_COMMIT_AUTHOR".to_string(),
+        git(&["log", "-1", "--format=%an <%ae>"]).unwrap_or_default(),
+    );
+    env.insert(
+        "CI_COMMIT_TIMESTAMP".to_string(),
+        git(&["log", "-1", "--format=%cI"]).unwrap_or_default(),
+    );
+    env.insert("GITLAB_USER_NAME".to_string(), get_actor());
+    env.insert(
+        "GITLAB_USER_EMAIL".to_string(),
+        git(&["config", "user.email"]).unwrap_or_default(),
+    );
+
+    // The pipeline: its number counts the runs of the pipeline in the history
+    let pipeline_file = pipeline_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let run_number = std::env::current_dir()
+        .map(|dir| RunStore::for_project(&dir).next_run_number(&pipeline_file))
+        .unwrap_or(1);
+    env.insert(
+        "CI_PIPELINE_ID".to_string(),
+        Utc::now().timestamp().to_string(),
+    );
+    env.insert("CI_PIPELINE_IID".to_string(), run_number.to_string());
+    env.insert(
+        "CI_PIPELINE_SOURCE".to_string(),
+        event.unwrap_or("push").to_string(),
+    );
+
+    env
+}
+
+/// GitLab's predefined variables of the job `name` of `stage`, the `index`th job
+/// of the pipeline whose variables are `pipeline_env`
+pub fn gitlab_job_predefined(
+    pipeline_env: &HashMap<String, String>,
+    name: &str,
+    stage: &str,
+    index: usize,
+) -> HashMap<String, String> {
+    let pipeline_id: u64 = pipeline_env
+        .get("CI_PIPELINE_ID")
+        .and_then(|id| id.parse().ok())
+        .unwrap_or_default();
+    let job_id = pipeline_id * 1000 + index as u64 + 1;
+    let mut env = HashMap::from([
+        ("CI_JOB_NAME".to_string(), name.to_string()),
+        ("CI_JOB_NAME_SLUG".to_string(), gitlab_slug(name)),
+        ("CI_JOB_STAGE".to_string(), stage.to_string()),
+        ("CI_JOB_ID".to_string(), job_id.to_string()),
+    ]);
+    if let Some(project_url) = pipeline_env.get("CI_PROJECT_URL") {
+        env.insert(
+            "CI_JOB_URL".to_string(),
+            format!("{}/-/jobs/{}", project_url, job_id),
+        );
+    }
+    env
+}
+
+// Lowercase, with runs of anything but letters and digits replaced by `-`, at most
+// 63 characters and without `-` at either end, as GitLab's `*_SLUG` variables
+pub(crate) fn gitlab_slug(value: &str) -> String {
+    let mut slug = String::new();
+    for c in value.to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.truncate(63);
+    slug.trim_matches('-').to_string()
+}
+
+/// Write the payload of the simulated event to `GITHUB_EVENT_PATH`: what the
+/// `github` context has of it, plus the `workflow_dispatch` inputs
+pub fn write_event_payload(
+    env: &HashMap<String, String>,
+    inputs: &HashMap<String, String>,
+) -> io::Result<()> {
+    let Some(path) = env.get("GITHUB_EVENT_PATH") else {
+        return Ok(());
+    };
+    let get = |name: &str| env.get(name).cloned().unwrap_or_default();
+    let repository = get("GITHUB_REPOSITORY");
+    let mut payload = serde_json::json!({
+        "ref": get("GITHUB_REF"),
+        "after": get("GITHUB_SHA"),
+        "head_commit": { "id": get("GITHUB_SHA") },
+        "repository": {
+            "full_name": repository,
+            "name": repository.split('/').nth(1).unwrap_or_default(),
+            "owner": { "login": get("GITHUB_REPOSITORY_OWNER") },
+        },
+        "sender": { "login": get("GITHUB_ACTOR") },
+    });
+    if get("GITHUB_EVENT_NAME").starts_with("pull_request") {
+        payload["pull_request"] = serde_json::json!({
+            "number": 1,
+            "draft": false,
+            "head": { "ref": get("GITHUB_HEAD_REF"), "sha": get("GITHUB_SHA") },
+            "base": { "ref": get("GITHUB_BASE_REF") },
+        });
+    }
+    if !inputs.is_empty() {
+        payload["inputs"] = serde_json::json!(inputs);
+    }
+    fs::write(path, payload.to_string())
+}
+
 pub fn add_matrix_context(
     env: &mut HashMap<String, String>,
     matrix_combination: &MatrixCombination,
@@ -131,56 +702,82 @@ fn value_to_string(value: &Value) -> String {
     }
 }
 
-fn get_repo_name() -> String {
-    // Try to detect from git if available
-    if let Ok(output) = std::process::Command::new("git")
-        .args(["remote", "get-url", "origin"])
-        .output()
-    {
-        if output.status.success() {
-            let url = String::from_utf8_lossy(&output.stdout);
-            if let Some(repo) = extract_repo_from_url(&url) {
-                return repo;
-            }
-        }
-    }
-
-    // Fallback to directory name
-    let current_dir = std::env::current_dir().unwrap_or_default();
-    format!(
-        "wrkflw/{}",
-        current_dir
-            .file_name()
-            .unwrap_or_default()
-            .to_string_lossy()
-    )
+// Output of a git command in the current directory, if it succeeded
+fn git(args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new("git").args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
 
-fn extract_repo_from_url(url: &str) -> Option<String> {
-    // Extract owner/repo from common git URLs
-    let url = url.trim();
+/// Host and `owner/repo` of the repository
+#[derive(Debug, PartialEq)]
+struct Remote {
+    host: String,
+    repository: String,
+}
 
-    // Handle SSH URLs: git@github.com:owner/repo.git
-    if url.starts_with("git@") {
-        let parts: Vec<&str> = url.split(':').collect();
-        if parts.len() == 2 {
-            let repo_part = parts[1].trim_end_matches(".git");
-            return Some(repo_part.to_string());
+impl Remote {
+    /// Server, REST API and GraphQL URLs: github.com's, or those of a GitHub
+    /// Enterprise Server on any other host
+    fn urls(&self) -> (String, String, String) {
+        if self.host == "github.com" {
+            (
+                "https://github.com".to_string(),
+                "https://api.github.com".to_string(),
+                "https://api.github.com/graphql".to_string(),
+            )
+        } else {
+            let server = format!("https://{}", self.host);
+            (
+                server.clone(),
+                format!("{}/api/v3", server),
+                format!("{}/api/graphql", server),
+            )
         }
     }
+}
 
-    // Handle HTTPS URLs: https://github.com/owner/repo.git
-    if url.starts_with("http") {
-        let without_protocol = url.split("://").nth(1)?;
-        let parts: Vec<&str> = without_protocol.split('/').collect();
-        if parts.len() >= 3 {
-            let owner = parts[1];
-            let repo = parts[2].trim_end_matches(".git");
-            return Some(format!("{}/{}", owner, repo));
-        }
+// The `origin` remote, or `default_host` and the directory's name without one
+fn get_remote(default_host: &str) -> Remote {
+    if let Some(remote) = git(&["remote", "get-url", "origin"]).and_then(|url| parse_remote(&url)) {
+        return remote;
     }
 
-    None
+    let current_dir = std::env::current_dir().unwrap_or_default();
+    Remote {
+        host: default_host.to_string(),
+        repository: format!(
+            "wrkflw/{}",
+            current_dir
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+        ),
+    }
+}
+
+// Host and owner/repo of SSH (`git@host:owner/repo.git`, `ssh://git@host/owner/repo`)
+// and HTTPS (`https://host/owner/repo.git`) URLs
+fn parse_remote(url: &str) -> Option<Remote> {
+    let url = url.trim();
+    let (host, path) = if let Some((_, rest)) = url.split_once("://") {
+        let rest = rest.rsplit_once('@').map_or(rest, |(_, rest)| rest);
+        rest.split_once('/')?
+    } else {
+        let rest = url.rsplit_once('@').map_or(url, |(_, rest)| rest);
+        rest.split_once(':')?
+    };
+    // Drop a port
+    let host = host.split(':').next().unwrap_or(host);
+    let mut parts = path.trim_matches('/').split('/');
+    let owner = parts.next().filter(|owner| !owner.is_empty())?;
+    let repo = parts.next()?.trim_end_matches(".git");
+    Some(Remote {
+        host: host.to_string(),
+        repository: format!("{}/{}", owner, repo),
+    })
 }
 
 fn get_event_name(workflow: &WorkflowDefinition) -> String {
@@ -199,44 +796,277 @@ fn get_workspace_path() -> String {
 }
 
 fn get_current_sha() -> String {
-    if let Ok(output) = std::process::Command::new("git")
-        .args(["rev-parse", "HEAD"])
-        .output()
-    {
-        if output.status.success() {
-            return String::from_utf8_lossy(&output.stdout).trim().to_string();
-        }
-    }
-
-    "0000000000000000000000000000000000000000".to_string()
+    git(&["rev-parse", "HEAD"])
+        .unwrap_or_else(|| "0000000000000000000000000000000000000000".to_string())
 }
 
+// The checked out branch, or the tag a detached HEAD is at
 fn get_current_ref() -> String {
-    if let Ok(output) = std::process::Command::new("git")
-        .args(["symbolic-ref", "--short", "HEAD"])
-        .output()
-    {
-        if output.status.success() {
-            return format!(
-                "refs/heads/{}",
-                String::from_utf8_lossy(&output.stdout).trim()
-            );
-        }
+    if let Some(branch) = git(&["symbolic-ref", "--short", "HEAD"]) {
+        return format!("refs/heads/{}", branch);
+    }
+    if let Some(tag) = git(&["describe", "--tags", "--exact-match", "HEAD"]) {
+        return format!("refs/tags/{}", tag);
     }
 
     "refs/heads/main".to_string()
 }
 
+// The branch `origin/HEAD` points at
+fn get_default_branch() -> String {
+    git(&["symbolic-ref", "--short", "refs/remotes/origin/HEAD"])
+        .and_then(|head| head.strip_prefix("origin/").map(str::to_string))
+        .unwrap_or_else(|| "main".to_string())
+}
+
+// Short name and type (`branch` or `tag`) of a ref
+fn ref_parts(git_ref: &str) -> (String, &'static str) {
+    if let Some(tag) = git_ref.strip_prefix("refs/tags/") {
+        (tag.to_string(), "tag")
+    } else {
+        (
+            git_ref
+                .strip_prefix("refs/heads/")
+                .unwrap_or(git_ref)
+                .to_string(),
+            "branch",
+        )
+    }
+}
+
+// Whoever is configured as git's user
+fn get_actor() -> String {
+    git(&["config", "user.name"])
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "wrkflw".to_string())
+}
+
 fn get_temp_dir() -> String {
     let temp_dir = std::env::temp_dir();
     temp_dir.join("wrkflw").to_string_lossy().to_string()
 }
 
 fn get_tool_cache_dir() -> String {
-    let home_dir = dirs::home_dir().unwrap_or_default();
-    home_dir
-        .join(".wrkflw")
-        .join("tools")
+    wrkflw_runtime::toolcache::ToolCache::default_root()
         .to_string_lossy()
         .to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_github_token() {
+        assert_eq!(
+            resolve_github_token(Some("ghp_explicit"), false),
+            ("ghp_explicit".to_string(), GithubTokenSource::Config)
+        );
+
+        // Without opting in, the host's token is never looked up
+        let (token, source) = resolve_github_token(None, false);
+        assert_eq!(source, GithubTokenSource::Generated);
+        assert!(token.starts_with("ghs_"));
+        assert_eq!(token.len(), 40);
+        let (other, _) = resolve_github_token(Some(""), false);
+        assert_ne!(token, other);
+    }
+
+    #[test]
+    fn test_gitlab_slug() {
+        assert_eq!(gitlab_slug("feature/Add_Login"), "feature-add-login");
+        assert_eq!(gitlab_slug("acme/web.app"), "acme-web-app");
+        assert_eq!(gitlab_slug("--v1.0--"), "v1-0");
+        assert_eq!(gitlab_slug(&"a".repeat(70)).len(), 63);
+    }
+
+    #[test]
+    fn test_parse_remote() {
+        let github = Remote {
+            host: "github.com".to_string(),
+            repository: "owner/repo".to_string(),
+        };
+        assert_eq!(
+            parse_remote("git@github.com:owner/repo.git\n"),
+            Some(github)
+        );
+        assert_eq!(
+            parse_remote("https://github.com/owner/repo"),
+            parse_remote("ssh://git@github.com:22/owner/repo.git")
+        );
+
+        let enterprise = parse_remote("https://user@ghe.example.com/team/app.git").unwrap();
+        assert_eq!(enterprise.repository, "team/app");
+        assert_eq!(
+            enterprise.urls(),
+            (
+                "https://ghe.example.com".to_string(),
+                "https://ghe.example.com/api/v3".to_string(),
+                "https://ghe.example.com/api/graphql".to_string()
+            )
+        );
+        assert_eq!(parse_remote("/srv/git/repo"), None);
+    }
+
+    #[test]
+    fn test_ref_parts() {
+        assert_eq!(
+            ref_parts("refs/heads/feature/x"),
+            ("feature/x".to_string(), "branch")
+        );
+        assert_eq!(ref_parts("refs/tags/v1.0"), ("v1.0".to_string(), "tag"));
+    }
+
+    #[test]
+    fn test_write_event_payload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("event.json");
+        let env = HashMap::from([
+            (
+                "GITHUB_EVENT_PATH".to_string(),
+                path.to_string_lossy().into_owned(),
+            ),
+            ("GITHUB_EVENT_NAME".to_string(), "pull_request".to_string()),
+            ("GITHUB_REPOSITORY".to_string(), "owner/repo".to_string()),
+            ("GITHUB_HEAD_REF".to_string(), "feature".to_string()),
+            ("GITHUB_BASE_REF".to_string(), "main".to_string()),
+        ]);
+        let inputs = HashMap::from([("level".to_string(), "debug".to_string())]);
+        write_event_payload(&env, &inputs).unwrap();
+
+        let payload: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(payload["repository"]["name"], "repo");
+        assert_eq!(payload["pull_request"]["head"]["ref"], "feature");
+        assert_eq!(payload["pull_request"]["base"]["ref"], "main");
+        assert_eq!(payload["inputs"]["level"], "debug");
+    }
+
+    #[test]
+    fn test_parse_env_file() {
+        let vars = parse_env_file("A=1\nEMPTY=\nB<<EOF\nline 1\nline 2\nEOF\nC=x=y\n");
+        assert_eq!(
+            vars,
+            vec![
+                ("A".to_string(), "1".to_string()),
+                ("EMPTY".to_string(), String::new()),
+                ("B".to_string(), "line 1\nline 2".to_string()),
+                ("C".to_string(), "x=y".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_dotenv() {
+        let content = "# Settings\n\nexport API_URL=http://localhost:8080 # local\nEMPTY=\nNAME = \"Jane \\\"J\\\" Doe\"\nRAW='a \\n #b'\nKEY=\"line 1\nline 2\\tend\"\n";
+        assert_eq!(
+            parse_dotenv(content).unwrap(),
+            vec![
+                ("API_URL".to_string(), "http://localhost:8080".to_string()),
+                ("EMPTY".to_string(), String::new()),
+                ("NAME".to_string(), "Jane \"J\" Doe".to_string()),
+                ("RAW".to_string(), "a \\n #b".to_string()),
+                ("KEY".to_string(), "line 1\nline 2\tend".to_string()),
+            ]
+        );
+        assert!(parse_dotenv("A=1\nnot a variable\n")
+            .unwrap_err()
+            .starts_with("line 2"));
+        assert!(parse_dotenv("A=\"open\n").is_err());
+    }
+
+    #[test]
+    fn test_apply_env_layer() {
+        let mut env = HashMap::from([
+            ("GITHUB_SHA".to_string(), "abc123".to_string()),
+            (
+                VARS_CONTEXT_KEY.to_string(),
+                r#"{"REGISTRY":"ghcr.io"}"#.to_string(),
+            ),
+        ]);
+        // workflow → job → step
+        apply_env_layer(
+            &mut env,
+            &HashMap::from([(
+                "IMAGE".to_string(),
+                "${{ vars.REGISTRY }}/app:${{ github.sha }}".to_string(),
+            )]),
+        );
+        apply_env_layer(
+            &mut env,
+            &HashMap::from([
+                ("TAG".to_string(), "${{ env.IMAGE }}".to_string()),
+                ("SAME_LEVEL".to_string(), "[${{ env.TAG }}]".to_string()),
+            ]),
+        );
+        assert_eq!(env["IMAGE"], "ghcr.io/app:abc123");
+        assert_eq!(env["TAG"], "ghcr.io/app:abc123");
+        assert_eq!(env["SAME_LEVEL"], "[]");
+    }
+
+    #[test]
+    fn test_enter_environment() {
+        let mut env = HashMap::from([(
+            VARS_CONTEXT_KEY.to_string(),
+            r#"{"REGISTRY":"ghcr.io","REGION":"eu"}"#.to_string(),
+        )]);
+        enter_environment(
+            &mut env,
+            &EnvironmentConfig {
+                vars: [("REGION".to_string(), "us".to_string())].into(),
+                secrets: [("API_KEY".to_string(), "PROD_API_KEY".to_string())].into(),
+                ..Default::default()
+            },
+        );
+        let context = ExpressionContext::from_env(&env);
+        assert_eq!(context.vars["REGISTRY"], "ghcr.io");
+        assert_eq!(context.vars["REGION"], "us");
+        assert_eq!(context.secrets["API_KEY"], "PROD_API_KEY");
+    }
+
+    #[test]
+    fn test_user_env() {
+        let mut context = HashMap::from([("CI".to_string(), "true".to_string())]);
+        set_user_env(
+            &mut context,
+            &HashMap::from([("DEPLOY".to_string(), "staging".to_string())]),
+        );
+        assert_eq!(context["DEPLOY"], "staging");
+
+        // A job setting the same variable doesn't override it
+        let mut job_env = context.clone();
+        job_env.insert("DEPLOY".to_string(), "production".to_string());
+        apply_user_env(&mut job_env);
+        assert_eq!(job_env["DEPLOY"], "staging");
+    }
+
+    #[test]
+    fn test_apply_github_env_files() {
+        let dir = tempfile::tempdir().unwrap();
+        setup_github_environment_files(dir.path()).unwrap();
+        let mut env = HashMap::from([
+            (
+                "GITHUB_ENV".to_string(),
+                dir.path().join("github/env").to_string_lossy().into_owned(),
+            ),
+            (
+                "GITHUB_PATH".to_string(),
+                dir.path()
+                    .join("github/path")
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+            ("PATH".to_string(), "/usr/bin".to_string()),
+        ]);
+
+        append_github_path(&env, &["/tools/bin".to_string(), "/tools".to_string()]).unwrap();
+        append_github_env(&env, &[("JAVA_HOME".to_string(), "/jdk".to_string())]).unwrap();
+        apply_github_env_files(&mut env);
+        assert_eq!(env["PATH"], "/tools/bin:/tools:/usr/bin");
+        assert_eq!(env["JAVA_HOME"], "/jdk");
+
+        // Applied once only
+        apply_github_env_files(&mut env);
+        assert_eq!(env["PATH"], "/tools/bin:/tools:/usr/bin");
+    }
+}
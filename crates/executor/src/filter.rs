@@ -0,0 +1,273 @@
+//! Running part of a workflow: only some of its jobs (`--job`), all but some
+//! (`--skip-job`), and likewise for the steps of the jobs that run (`--step`,
+//! `--skip-step`). Jobs left out are skipped; the jobs needing them run as if they
+//! had succeeded, unless the jobs they need are run along (`--with-needs`).
+
+use std::collections::BTreeSet;
+use wrkflw_parser::workflow::{Step, WorkflowDefinition};
+
+/// Which jobs and steps of a workflow a run runs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunFilter {
+    /// Jobs to run, by id; none runs them all
+    pub jobs: Vec<String>,
+    /// Also run the jobs the selected ones need, and the jobs those need
+    pub with_needs: bool,
+    /// Jobs not to run, by id
+    pub skip_jobs: Vec<String>,
+    /// Which steps of the jobs that run are run
+    pub steps: StepFilter,
+}
+
+/// Which steps of the jobs that run a run runs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StepFilter {
+    /// Steps to run, by id, name or number (from 1); none runs them all
+    pub only: Vec<String>,
+    /// Steps not to run
+    pub skip: Vec<String>,
+}
+
+impl StepFilter {
+    pub fn is_empty(&self) -> bool {
+        self.only.is_empty() && self.skip.is_empty()
+    }
+
+    /// Why step `idx` isn't run, if it isn't
+    pub fn skip_reason(&self, step: &Step, idx: usize) -> Option<&'static str> {
+        let matches = |selector: &String| step_matches(step, idx, selector);
+        if self.skip.iter().any(matches) {
+            Some("skipped with --skip-step")
+        } else if !self.only.is_empty() && !self.only.iter().any(matches) {
+            Some("not selected with --step")
+        } else {
+            None
+        }
+    }
+}
+
+impl RunFilter {
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty() && self.skip_jobs.is_empty() && self.steps.is_empty()
+    }
+
+    /// Check that the jobs and steps named exist in `workflow`
+    pub fn check(&self, workflow: &WorkflowDefinition) -> Result<(), String> {
+        let mut job_ids: Vec<&String> = workflow.jobs.keys().collect();
+        job_ids.sort();
+        for job in self.jobs.iter().chain(&self.skip_jobs) {
+            if !workflow.jobs.contains_key(job) {
+                return Err(format!(
+                    "No job '{}' in the workflow; its jobs are: {}",
+                    job,
+                    job_ids
+                        .iter()
+                        .map(|id| id.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+        }
+        let running = self.running_jobs(workflow);
+        for selector in self.steps.only.iter().chain(&self.steps.skip) {
+            let found = running.iter().any(|job| {
+                workflow.jobs[*job]
+                    .steps
+                    .iter()
+                    .enumerate()
+                    .any(|(idx, step)| step_matches(step, idx, selector))
+            });
+            if !found {
+                return Err(format!("No step '{}' in the jobs that run", selector));
+            }
+        }
+        Ok(())
+    }
+
+    /// Why `job` isn't run, if it isn't
+    pub fn skip_reason(&self, job: &str, workflow: &WorkflowDefinition) -> Option<&'static str> {
+        if self.skip_jobs.iter().any(|skipped| skipped == job) {
+            Some("skipped with --skip-job")
+        } else if !self.running_jobs(workflow).contains(job) {
+            Some("not selected with --job")
+        } else {
+            None
+        }
+    }
+
+    /// Take the jobs not to run out of `plan`, returning them with why they don't
+    pub fn apply(
+        &self,
+        workflow: &WorkflowDefinition,
+        plan: &mut Vec<Vec<String>>,
+    ) -> Vec<(String, &'static str)> {
+        let mut left_out = Vec::new();
+        for batch in plan.iter_mut() {
+            batch.retain(|job| match self.skip_reason(job, workflow) {
+                Some(reason) => {
+                    left_out.push((job.clone(), reason));
+                    false
+                }
+                None => true,
+            });
+        }
+        plan.retain(|batch| !batch.is_empty());
+        left_out.sort();
+        left_out
+    }
+
+    /// Ids of the jobs of `workflow` selected to run, with the jobs they need when
+    /// those run along
+    fn running_jobs<'a>(&self, workflow: &'a WorkflowDefinition) -> BTreeSet<&'a str> {
+        let mut running: BTreeSet<&str> = workflow
+            .jobs
+            .keys()
+            .map(String::as_str)
+            .filter(|job| self.jobs.is_empty() || self.jobs.iter().any(|id| id == job))
+            .collect();
+        if self.with_needs {
+            let mut pending: Vec<&str> = running.iter().copied().collect();
+            while let Some(job) = pending.pop() {
+                for need in workflow.jobs[job].needs.iter().flatten() {
+                    if let Some((need, _)) = workflow.jobs.get_key_value(need) {
+                        if running.insert(need) {
+                            pending.push(need);
+                        }
+                    }
+                }
+            }
+        }
+        running.retain(|job| !self.skip_jobs.iter().any(|skipped| skipped == job));
+        running
+    }
+}
+
+/// Whether `selector` names step `idx`: its id, its name or its number from 1
+fn step_matches(step: &Step, idx: usize, selector: &str) -> bool {
+    step.id.as_deref() == Some(selector)
+        || step.name.as_deref() == Some(selector)
+        || selector.parse() == Ok(idx + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKFLOW: &str = r#"
+name: CI
+on: push
+jobs:
+  lint:
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo lint
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo build
+  test:
+    needs: build
+    runs-on: ubuntu-latest
+    steps:
+      - id: unit
+        run: echo unit
+      - name: Integration
+        run: echo integration
+  deploy:
+    needs: [lint, test]
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo deploy
+"#;
+
+    fn workflow() -> WorkflowDefinition {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ci.yml");
+        std::fs::write(&path, WORKFLOW).unwrap();
+        wrkflw_parser::workflow::parse_workflow(&path).unwrap()
+    }
+
+    fn filter(jobs: &[&str], skip_jobs: &[&str]) -> RunFilter {
+        RunFilter {
+            jobs: jobs.iter().map(|job| job.to_string()).collect(),
+            skip_jobs: skip_jobs.iter().map(|job| job.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn selected_jobs_run_with_or_without_their_needs() {
+        let workflow = workflow();
+        let only_deploy = filter(&["deploy"], &[]);
+        assert_eq!(
+            only_deploy.running_jobs(&workflow),
+            BTreeSet::from(["deploy"])
+        );
+        assert_eq!(
+            only_deploy.skip_reason("test", &workflow),
+            Some("not selected with --job")
+        );
+
+        let with_needs = RunFilter {
+            with_needs: true,
+            ..filter(&["deploy"], &["lint"])
+        };
+        assert_eq!(
+            with_needs.running_jobs(&workflow),
+            BTreeSet::from(["build", "deploy", "test"])
+        );
+        assert_eq!(
+            with_needs.skip_reason("lint", &workflow),
+            Some("skipped with --skip-job")
+        );
+
+        let mut plan = vec![
+            vec!["build".to_string(), "lint".to_string()],
+            vec!["test".to_string()],
+            vec!["deploy".to_string()],
+        ];
+        let left_out = filter(&["test"], &[]).apply(&workflow, &mut plan);
+        assert_eq!(plan, vec![vec!["test".to_string()]]);
+        assert_eq!(
+            left_out
+                .iter()
+                .map(|(job, _)| job.as_str())
+                .collect::<Vec<_>>(),
+            vec!["build", "deploy", "lint"]
+        );
+    }
+
+    #[test]
+    fn unknown_jobs_and_steps_are_errors() {
+        let workflow = workflow();
+        let err = filter(&["tests"], &[]).check(&workflow).unwrap_err();
+        assert!(err.contains("build, deploy, lint, test"), "{}", err);
+
+        let steps = |only: &[&str]| RunFilter {
+            steps: StepFilter {
+                only: only.iter().map(|step| step.to_string()).collect(),
+                skip: Vec::new(),
+            },
+            ..filter(&["test"], &[])
+        };
+        assert!(steps(&["unit", "Integration", "2"])
+            .check(&workflow)
+            .is_ok());
+        assert!(steps(&["3"]).check(&workflow).is_err());
+    }
+
+    #[test]
+    fn steps_are_picked_by_id_name_or_number() {
+        let workflow = workflow();
+        let job = &workflow.jobs["test"];
+        let filter = StepFilter {
+            only: vec!["unit".to_string(), "2".to_string()],
+            skip: vec!["Integration".to_string()],
+        };
+        assert_eq!(filter.skip_reason(&job.steps[0], 0), None);
+        assert_eq!(
+            filter.skip_reason(&job.steps[1], 1),
+            Some("skipped with --skip-step")
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! CI/CD variables of GitLab pipelines run locally. Jobs see them in GitLab's order
+//! of precedence, each level over the ones before: the predefined `CI_*`
+//! variables, the instance's (`~/.wrkflw/gitlab-vars.toml`), the project's
+//! (`.wrkflw/gitlab-vars.toml`), the pipeline's `variables:`, the job's
+//! `variables:`, and those given with `--gitlab-var`.
+
+use crate::deployments;
+use crate::environment;
+use crate::stages;
+use crate::vars::{VarsFile, GITLAB_VARS_FILE};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use wrkflw_models::gitlab::Pipeline;
+use wrkflw_parser::workflow::WorkflowDefinition;
+
+/// The variables of a pipeline's run that don't come from the pipeline
+#[derive(Debug, Clone, Default)]
+pub struct GitlabVariables {
+    pub instance: BTreeMap<String, String>,
+    pub project: BTreeMap<String, String>,
+    /// Variables over all others, e.g. from `--gitlab-var`
+    pub overrides: HashMap<String, String>,
+}
+
+impl GitlabVariables {
+    /// The instance's and `project_dir`'s variables, under `overrides`
+    pub fn load(project_dir: &Path, overrides: HashMap<String, String>) -> Result<Self, String> {
+        let instance = match dirs::home_dir() {
+            Some(home) => VarsFile::new(home.join(".wrkflw").join(GITLAB_VARS_FILE)).load()?,
+            None => BTreeMap::new(),
+        };
+        let project = VarsFile::new(project_dir.join(".wrkflw").join(GITLAB_VARS_FILE)).load()?;
+        Ok(GitlabVariables {
+            instance,
+            project,
+            overrides,
+        })
+    }
+
+    /// The variables all jobs of `pipeline` see, over its `predefined` ones
+    pub fn pipeline_env(
+        &self,
+        pipeline: &Pipeline,
+        mut predefined: HashMap<String, String>,
+    ) -> HashMap<String, String> {
+        predefined.extend(self.instance.clone());
+        predefined.extend(self.project.clone());
+        predefined.extend(pipeline.variables.clone().unwrap_or_default());
+        predefined.extend(self.overrides.clone());
+        predefined
+    }
+
+    /// Give each job of `workflow`, converted from `pipeline`, its variables over
+    /// those of the pipeline, `pipeline_env`
+    pub fn apply_to_jobs(
+        &self,
+        pipeline: &Pipeline,
+        pipeline_env: &HashMap<String, String>,
+        workflow: &mut WorkflowDefinition,
+    ) {
+        let mut names: Vec<&String> = pipeline.jobs.keys().collect();
+        names.sort();
+        for (index, name) in names.into_iter().enumerate() {
+            let Some(job) = workflow.jobs.get_mut(name) else {
+                continue;
+            };
+            let gitlab_job = &pipeline.jobs[name];
+            let mut env = environment::gitlab_job_predefined(
+                pipeline_env,
+                name,
+                stages::stage_of(gitlab_job),
+                index,
+            );
+            env.extend(self.instance.clone());
+            env.extend(self.project.clone());
+            env.extend(pipeline.variables.clone().unwrap_or_default());
+            env.extend(gitlab_job.variables.clone().unwrap_or_default());
+            env.extend(self.overrides.clone());
+            if let Some(environment) = &gitlab_job.environment {
+                // Its name and URL may use any variable the job sees
+                let mut scope = pipeline_env.clone();
+                scope.extend(env.clone());
+                let (resolved, variables) = deployments::resolve(environment, &scope);
+                env.extend(variables);
+                job.environment = Some(resolved);
+            }
+            job.env = env;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn later_levels_win() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".gitlab-ci.yml");
+        std::fs::write(
+            &path,
+            r#"
+variables:
+  LEVEL: pipeline
+  PIPELINE_ONLY: "1"
+build:
+  stage: build
+  variables:
+    LEVEL: job
+  script: [make]
+lint:
+  script: [make lint]
+"#,
+        )
+        .unwrap();
+        let pipeline = wrkflw_parser::gitlab::parse_pipeline(&path).unwrap();
+        let mut workflow = wrkflw_parser::gitlab::convert_to_workflow_format(&pipeline);
+
+        let levels = |level: &str| BTreeMap::from([("LEVEL".to_string(), level.to_string())]);
+        let mut variables = GitlabVariables {
+            instance: levels("instance"),
+            project: BTreeMap::from([("PROJECT_ONLY".to_string(), "1".to_string())]),
+            overrides: HashMap::new(),
+        };
+        let predefined = HashMap::from([
+            ("CI_PIPELINE_ID".to_string(), "7".to_string()),
+            ("LEVEL".to_string(), "predefined".to_string()),
+        ]);
+        let pipeline_env = variables.pipeline_env(&pipeline, predefined.clone());
+        assert_eq!(pipeline_env["LEVEL"], "pipeline");
+        assert_eq!(pipeline_env["PROJECT_ONLY"], "1");
+
+        variables.apply_to_jobs(&pipeline, &pipeline_env, &mut workflow);
+        let build = &workflow.jobs["build"].env;
+        assert_eq!(build["LEVEL"], "job");
+        assert_eq!(build["CI_JOB_NAME"], "build");
+        assert_eq!(build["CI_JOB_STAGE"], "build");
+        assert_eq!(build["CI_JOB_ID"], "7001");
+        let lint = &workflow.jobs["lint"].env;
+        assert_eq!(lint["LEVEL"], "pipeline");
+        assert_eq!(lint["CI_JOB_STAGE"], "test");
+
+        variables.overrides = HashMap::from([("LEVEL".to_string(), "cli".to_string())]);
+        let pipeline_env = variables.pipeline_env(&pipeline, predefined);
+        variables.apply_to_jobs(&pipeline, &pipeline_env, &mut workflow);
+        assert_eq!(workflow.jobs["build"].env["LEVEL"], "cli");
+    }
+}
@@ -0,0 +1,532 @@
+//! Job dependency graphs: which jobs wait on which, with matrix jobs expanded into
+//! their combinations and called workflows nested under the jobs calling them.
+
+use crate::action_cache::{self, ActionCache};
+use crate::dependency;
+use crate::engine;
+use serde_yaml::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use wrkflw_matrix::MatrixCombination;
+use wrkflw_parser::gitlab::{self, parse_pipeline};
+use wrkflw_parser::workflow::{parse_workflow, Job, WorkflowDefinition};
+
+/// How deep called workflows are followed, the nesting limit GitHub enforces
+pub const MAX_CALL_DEPTH: usize = 4;
+
+/// The jobs of a workflow and what they need
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkflowGraph {
+    pub name: String,
+    /// Jobs grouped by stage; a job only needs jobs from earlier stages
+    pub stages: Vec<Vec<GraphJob>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphJob {
+    pub id: String,
+    /// Jobs this one waits for
+    pub needs: Vec<String>,
+    /// One name per matrix combination, empty unless the job has a matrix
+    pub instances: Vec<String>,
+    /// The reusable workflow the job calls
+    pub call: Option<WorkflowCall>,
+    /// Why the job couldn't be expanded
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkflowCall {
+    pub uses: String,
+    /// The called workflow's graph, or why it couldn't be loaded
+    pub graph: Result<Box<WorkflowGraph>, String>,
+}
+
+/// Output formats for `wrkflw graph`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+    /// Indented plain text
+    Ascii,
+}
+
+/// Build the dependency graph of a GitHub workflow or GitLab pipeline
+///
+/// Local called workflows are read relative to the current directory and remote ones
+/// from the action cache; nothing is fetched.
+pub fn build_graph(path: &Path) -> Result<WorkflowGraph, String> {
+    let project_dir =
+        std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?;
+    let builder = GraphBuilder {
+        project_dir,
+        cache: ActionCache::new(ActionCache::default_root()),
+    };
+
+    if engine::is_gitlab_pipeline(path) {
+        let pipeline =
+            parse_pipeline(path).map_err(|e| format!("Failed to parse GitLab pipeline: {}", e))?;
+        let workflow = gitlab::convert_to_workflow_format(&pipeline);
+        let stages = engine::resolve_gitlab_dependencies(&pipeline).map_err(|e| e.to_string())?;
+        Ok(builder.graph(&workflow, stages, true, &mut Vec::new()))
+    } else {
+        builder.workflow_graph(path, &mut Vec::new())
+    }
+}
+
+struct GraphBuilder {
+    project_dir: PathBuf,
+    cache: ActionCache,
+}
+
+impl GraphBuilder {
+    /// `calling` holds the workflows currently being expanded, outermost first
+    fn workflow_graph(
+        &self,
+        path: &Path,
+        calling: &mut Vec<PathBuf>,
+    ) -> Result<WorkflowGraph, String> {
+        let workflow = parse_workflow(path)?;
+        let stages = dependency::resolve_dependencies(&workflow)?;
+        calling.push(path.to_path_buf());
+        let graph = self.graph(&workflow, stages, false, calling);
+        calling.pop();
+        Ok(graph)
+    }
+
+    fn graph(
+        &self,
+        workflow: &WorkflowDefinition,
+        stages: Vec<Vec<String>>,
+        gitlab: bool,
+        calling: &mut Vec<PathBuf>,
+    ) -> WorkflowGraph {
+        let mut graph_stages = Vec::new();
+        let mut previous: Vec<String> = Vec::new();
+        for mut names in stages {
+            names.sort();
+            let mut jobs = Vec::new();
+            for id in &names {
+                let Some(job) = workflow.jobs.get(id) else {
+                    continue;
+                };
+                let mut needs = job.needs.clone().unwrap_or_default();
+                // GitLab jobs without `needs:` wait for the whole previous stage
+                if gitlab && job.needs.is_none() {
+                    needs = previous.clone();
+                }
+                needs.sort();
+                jobs.push(self.job(id, job, needs, calling));
+            }
+            previous = names;
+            graph_stages.push(jobs);
+        }
+        WorkflowGraph {
+            name: workflow.name.clone(),
+            stages: graph_stages,
+        }
+    }
+
+    fn job(&self, id: &str, job: &Job, needs: Vec<String>, calling: &mut Vec<PathBuf>) -> GraphJob {
+        let mut note = None;
+        let instances = match job
+            .matrix_config()
+            .map(|m| wrkflw_matrix::expand_matrix(&m))
+        {
+            None => Vec::new(),
+            Some(Ok(combinations)) => combinations
+                .iter()
+                .map(|combination| combination_name(id, combination))
+                .collect(),
+            Some(Err(e)) => {
+                note = Some(format!("Failed to expand matrix: {}", e));
+                Vec::new()
+            }
+        };
+        let call = job.uses.as_ref().map(|uses| WorkflowCall {
+            uses: uses.clone(),
+            graph: self.called_graph(uses, calling).map(Box::new),
+        });
+        GraphJob {
+            id: id.to_string(),
+            needs,
+            instances,
+            call,
+            note,
+        }
+    }
+
+    fn called_graph(
+        &self,
+        uses: &str,
+        calling: &mut Vec<PathBuf>,
+    ) -> Result<WorkflowGraph, String> {
+        let path = if uses.starts_with("./") || uses.starts_with('/') {
+            self.project_dir.join(uses)
+        } else {
+            let (repo_ref, path) = action_cache::parse_workflow_uses(uses)
+                .ok_or_else(|| "invalid reusable workflow reference".to_string())?;
+            let dir = self
+                .cache
+                .lookup(&repo_ref)
+                .ok_or_else(|| format!("{} is not in the action cache", repo_ref))?;
+            dir.join(path)
+        };
+        if calling.contains(&path) {
+            return Err("the workflow calls itself".to_string());
+        }
+        if calling.len() > MAX_CALL_DEPTH {
+            return Err(format!(
+                "workflows are nested more than {} levels deep",
+                MAX_CALL_DEPTH
+            ));
+        }
+        if !path.exists() {
+            return Err(format!("{} does not exist", path.display()));
+        }
+        self.workflow_graph(&path, calling)
+    }
+}
+
+/// Like `wrkflw_matrix::format_combination_name`, with the values in a stable order
+fn combination_name(job: &str, combination: &MatrixCombination) -> String {
+    let mut values: Vec<_> = combination.values.iter().collect();
+    values.sort_by(|a, b| a.0.cmp(b.0));
+    let values: Vec<String> = values
+        .iter()
+        .map(|(key, value)| match value {
+            Value::String(s) => format!("{}: {}", key, s),
+            other => format!(
+                "{}: {}",
+                key,
+                serde_yaml::to_string(other).unwrap_or_default().trim()
+            ),
+        })
+        .collect();
+    format!("{} ({})", job, values.join(", "))
+}
+
+impl WorkflowGraph {
+    /// Every job in stage order
+    pub fn jobs(&self) -> impl Iterator<Item = &GraphJob> {
+        self.stages.iter().flatten()
+    }
+
+    /// Jobs that need `id`
+    pub fn dependents(&self, id: &str) -> Vec<&str> {
+        self.jobs()
+            .filter(|job| job.needs.iter().any(|need| need == id))
+            .map(|job| job.id.as_str())
+            .collect()
+    }
+
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Ascii => self.to_string(),
+            GraphFormat::Dot => {
+                let mut out = format!(
+                    "digraph \"{}\" {{\n  rankdir=LR;\n  node [shape=box, style=rounded];\n",
+                    dot_escape(&self.name)
+                );
+                Emitter::new(GraphFormat::Dot, &mut out).emit(self, 1);
+                out.push_str("}\n");
+                out
+            }
+            GraphFormat::Mermaid => {
+                let mut out = "flowchart LR\n".to_string();
+                Emitter::new(GraphFormat::Mermaid, &mut out).emit(self, 1);
+                out
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum NodeKind {
+    Job,
+    Call,
+    Unresolved,
+}
+
+/// Writes DOT or Mermaid, numbering nodes and clusters as it goes
+struct Emitter<'a> {
+    format: GraphFormat,
+    out: &'a mut String,
+    next_id: usize,
+}
+
+impl<'a> Emitter<'a> {
+    fn new(format: GraphFormat, out: &'a mut String) -> Self {
+        Emitter {
+            format,
+            out,
+            next_id: 0,
+        }
+    }
+
+    /// Write a graph's nodes and edges, returning the nodes of jobs that need nothing
+    fn emit(&mut self, graph: &WorkflowGraph, depth: usize) -> Vec<String> {
+        let mut nodes: HashMap<&str, Vec<String>> = HashMap::new();
+        let mut edges = Vec::new();
+        let mut roots = Vec::new();
+
+        for job in graph.jobs() {
+            let kind = if job.call.is_some() {
+                NodeKind::Call
+            } else {
+                NodeKind::Job
+            };
+            let ids = if job.instances.is_empty() {
+                vec![self.node(depth, &job.id, kind)]
+            } else {
+                self.open_cluster(depth, &format!("{} (matrix)", job.id), false);
+                let ids = job
+                    .instances
+                    .iter()
+                    .map(|instance| self.node(depth + 1, instance, kind))
+                    .collect();
+                self.close_cluster(depth);
+                ids
+            };
+
+            if let Some(call) = &job.call {
+                let called = match &call.graph {
+                    Ok(called) => {
+                        self.open_cluster(depth, &call.uses, true);
+                        let called_roots = self.emit(called, depth + 1);
+                        self.close_cluster(depth);
+                        called_roots
+                    }
+                    Err(e) => vec![self.node(
+                        depth,
+                        &format!("{}: {}", call.uses, e),
+                        NodeKind::Unresolved,
+                    )],
+                };
+                for from in &ids {
+                    for to in &called {
+                        edges.push((from.clone(), to.clone(), true));
+                    }
+                }
+            }
+
+            if job.needs.is_empty() {
+                roots.extend(ids.iter().cloned());
+            }
+            for need in &job.needs {
+                for from in nodes.get(need.as_str()).into_iter().flatten() {
+                    for to in &ids {
+                        edges.push((from.clone(), to.clone(), false));
+                    }
+                }
+            }
+            nodes.insert(&job.id, ids);
+        }
+
+        let pad = "  ".repeat(depth);
+        for (from, to, call) in edges {
+            let line = match (self.format, call) {
+                (GraphFormat::Dot, false) => format!("{} -> {};", from, to),
+                (GraphFormat::Dot, true) => format!("{} -> {} [style=dashed];", from, to),
+                (_, false) => format!("{} --> {}", from, to),
+                (_, true) => format!("{} -.-> {}", from, to),
+            };
+            self.out.push_str(&format!("{}{}\n", pad, line));
+        }
+        roots
+    }
+
+    fn id(&mut self) -> String {
+        self.next_id += 1;
+        format!("n{}", self.next_id)
+    }
+
+    fn node(&mut self, depth: usize, label: &str, kind: NodeKind) -> String {
+        let id = self.id();
+        let pad = "  ".repeat(depth);
+        let line = match (self.format, kind) {
+            (GraphFormat::Dot, NodeKind::Job) => {
+                format!("{} [label=\"{}\"];", id, dot_escape(label))
+            }
+            (GraphFormat::Dot, NodeKind::Call) => {
+                format!("{} [label=\"{}\", shape=component];", id, dot_escape(label))
+            }
+            (GraphFormat::Dot, NodeKind::Unresolved) => format!(
+                "{} [label=\"{}\", shape=note, color=red];",
+                id,
+                dot_escape(label)
+            ),
+            (_, NodeKind::Job) => format!("{}[\"{}\"]", id, mermaid_escape(label)),
+            (_, NodeKind::Call) => format!("{}[[\"{}\"]]", id, mermaid_escape(label)),
+            (_, NodeKind::Unresolved) => format!("{}>\"{}\"]", id, mermaid_escape(label)),
+        };
+        self.out.push_str(&format!("{}{}\n", pad, line));
+        id
+    }
+
+    fn open_cluster(&mut self, depth: usize, label: &str, dashed: bool) {
+        let id = self.id();
+        let pad = "  ".repeat(depth);
+        match self.format {
+            GraphFormat::Dot => {
+                self.out
+                    .push_str(&format!("{}subgraph cluster_{} {{\n", pad, id));
+                self.out
+                    .push_str(&format!("{}  label=\"{}\";\n", pad, dot_escape(label)));
+                if dashed {
+                    self.out.push_str(&format!("{}  style=dashed;\n", pad));
+                }
+            }
+            _ => self.out.push_str(&format!(
+                "{}subgraph {}[\"{}\"]\n",
+                pad,
+                id,
+                mermaid_escape(label)
+            )),
+        }
+    }
+
+    fn close_cluster(&mut self, depth: usize) {
+        let pad = "  ".repeat(depth);
+        match self.format {
+            GraphFormat::Dot => self.out.push_str(&format!("{}}}\n", pad)),
+            _ => self.out.push_str(&format!("{}end\n", pad)),
+        }
+    }
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+impl fmt::Display for WorkflowGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "🔀 Job graph for '{}'", self.name)?;
+        write_ascii(self, f, "")
+    }
+}
+
+fn write_ascii(graph: &WorkflowGraph, f: &mut fmt::Formatter<'_>, indent: &str) -> fmt::Result {
+    for (idx, stage) in graph.stages.iter().enumerate() {
+        writeln!(f, "{}Stage {}:", indent, idx + 1)?;
+        for job in stage {
+            if job.needs.is_empty() {
+                writeln!(f, "{}  ● {}", indent, job.id)?;
+            } else {
+                writeln!(f, "{}  ● {} ← {}", indent, job.id, job.needs.join(", "))?;
+            }
+            if let Some(note) = &job.note {
+                writeln!(f, "{}      ⚠️  {}", indent, note)?;
+            }
+            for instance in &job.instances {
+                writeln!(f, "{}      ◦ {}", indent, instance)?;
+            }
+            if let Some(call) = &job.call {
+                match &call.graph {
+                    Ok(called) => {
+                        writeln!(f, "{}      ↳ calls {}", indent, call.uses)?;
+                        write_ascii(called, f, &format!("{}        ", indent))?;
+                    }
+                    Err(e) => writeln!(f, "{}      ↳ calls {}: {}", indent, call.uses, e)?,
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_build_and_render_graph() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ci.yml");
+        let called = dir.path().join("deploy.yml");
+        fs::write(
+            &called,
+            r#"
+name: Deploy
+on: workflow_call
+jobs:
+  push:
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo push
+"#,
+        )
+        .unwrap();
+        fs::write(
+            &path,
+            format!(
+                r#"
+name: CI
+on: push
+jobs:
+  lint:
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo lint
+  test:
+    needs: lint
+    runs-on: ubuntu-latest
+    strategy:
+      matrix:
+        node: [18, 20]
+    steps:
+      - run: echo test
+  deploy:
+    needs: [test]
+    uses: {}
+  release:
+    needs: [deploy]
+    uses: octo/missing/.github/workflows/release.yml@v1
+"#,
+                called.display()
+            ),
+        )
+        .unwrap();
+
+        let graph = build_graph(&path).unwrap();
+        assert_eq!(graph.stages.len(), 4);
+        let test = &graph.stages[1][0];
+        assert_eq!(test.needs, vec!["lint"]);
+        assert_eq!(test.instances, vec!["test (node: 18)", "test (node: 20)"]);
+        assert_eq!(graph.dependents("test"), vec!["deploy"]);
+        let called_graph = graph.stages[2][0].call.as_ref().unwrap().graph.as_ref();
+        assert_eq!(called_graph.unwrap().name, "Deploy");
+        let release = graph.stages[3][0].call.as_ref().unwrap();
+        assert!(release
+            .graph
+            .as_ref()
+            .unwrap_err()
+            .contains("not in the action cache"));
+
+        let ascii = graph.render(GraphFormat::Ascii);
+        assert!(ascii.contains("● test ← lint"));
+        assert!(ascii.contains("◦ test (node: 20)"));
+        assert!(ascii.contains("● push"));
+
+        let dot = graph.render(GraphFormat::Dot);
+        assert!(dot.starts_with("digraph \"CI\" {"));
+        // lint feeds both matrix combinations, which both feed the calling job
+        assert!(dot.contains("n1 -> n3;") && dot.contains("n1 -> n4;"));
+        assert!(dot.contains("n3 -> n5;") && dot.contains("n4 -> n5;"));
+        assert!(dot.contains("n5 -> n7 [style=dashed];"));
+
+        let mermaid = graph.render(GraphFormat::Mermaid);
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains("n5[[\"deploy\"]]"));
+        assert!(mermaid.contains("n5 -.-> n7"));
+        assert!(mermaid.contains("subgraph n2[\"test (matrix)\"]"));
+    }
+}
@@ -0,0 +1,245 @@
+//! Language environment images, remembered across runs.
+//!
+//! `prepare_language_environment` records each image it builds in
+//! `~/.wrkflw/images.json`, by runtime and by language, version and packages, so
+//! later runs reuse it for as long as the runtime still has it.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Label put on the language environment images wrkflw builds
+pub const ENVIRONMENT_LABEL: &str = "wrkflw.language-environment";
+
+/// An image built for a language environment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreparedImage {
+    /// `docker` or `podman`
+    pub runtime: String,
+    pub language: String,
+    pub version: Option<String>,
+    /// Installed on top of the language's image, sorted
+    pub packages: Vec<String>,
+    pub tag: String,
+    pub last_used: SystemTime,
+}
+
+impl PreparedImage {
+    pub fn new(runtime: &str, language: &str, version: Option<&str>, packages: &[String]) -> Self {
+        let packages = normalize(packages);
+        PreparedImage {
+            runtime: runtime.to_string(),
+            language: language.to_string(),
+            version: version.map(str::to_string),
+            tag: image_tag(language, version, &packages),
+            packages,
+            last_used: SystemTime::now(),
+        }
+    }
+
+    /// The environment, like `python:3.11 +requests,flask`
+    pub fn environment(&self) -> String {
+        let mut environment = format!(
+            "{}:{}",
+            self.language,
+            self.version.as_deref().unwrap_or("latest")
+        );
+        if !self.packages.is_empty() {
+            environment.push_str(&format!(" +{}", self.packages.join(",")));
+        }
+        environment
+    }
+
+    fn is_for(&self, other: &PreparedImage) -> bool {
+        self.runtime == other.runtime
+            && self.language == other.language
+            && self.version == other.version
+            && self.packages == other.packages
+    }
+}
+
+fn normalize(packages: &[String]) -> Vec<String> {
+    let mut packages = packages.to_vec();
+    packages.sort();
+    packages.dedup();
+    packages
+}
+
+/// `wrkflw-<language>-<version>`, followed by a hash of the packages when there are any
+fn image_tag(language: &str, version: Option<&str>, packages: &[String]) -> String {
+    let tag = format!("wrkflw-{}-{}", language, version.unwrap_or("latest"));
+    if packages.is_empty() {
+        return tag;
+    }
+    let digest = Sha256::digest(packages.join("\n").as_bytes());
+    let hash: String = digest[..6].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}", tag, hash)
+}
+
+/// The prepared images recorded in a file
+pub struct ImageStore {
+    file: PathBuf,
+}
+
+impl ImageStore {
+    pub fn new(file: impl Into<PathBuf>) -> Self {
+        Self { file: file.into() }
+    }
+
+    /// `~/.wrkflw/images.json`
+    pub fn default_file() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join(".wrkflw")
+            .join("images.json")
+    }
+
+    pub fn file(&self) -> &Path {
+        &self.file
+    }
+
+    /// Every image recorded, most recently used first
+    pub fn list(&self) -> Vec<PreparedImage> {
+        let Ok(content) = fs::read_to_string(&self.file) else {
+            return Vec::new();
+        };
+        let mut images: Vec<PreparedImage> = match serde_json::from_str(&content) {
+            Ok(images) => images,
+            Err(e) => {
+                wrkflw_logging::warning(&format!(
+                    "Ignoring unreadable image records in {}: {}",
+                    self.file.display(),
+                    e
+                ));
+                Vec::new()
+            }
+        };
+        images.sort_by_key(|image| std::cmp::Reverse(image.last_used));
+        images
+    }
+
+    /// The image recorded for the same runtime and environment as `wanted`
+    pub fn lookup(&self, wanted: &PreparedImage) -> Option<PreparedImage> {
+        self.list().into_iter().find(|image| image.is_for(wanted))
+    }
+
+    /// Record `image` as just used, replacing what was recorded for its environment
+    pub fn record(&self, image: &PreparedImage) -> Result<(), String> {
+        let mut images = self.list();
+        images.retain(|recorded| !recorded.is_for(image) && !same_image(recorded, image));
+        images.push(PreparedImage {
+            last_used: SystemTime::now(),
+            ..image.clone()
+        });
+        self.save(&images)
+    }
+
+    /// Stop recording `image`
+    pub fn forget(&self, image: &PreparedImage) -> Result<(), String> {
+        let mut images = self.list();
+        images.retain(|recorded| !same_image(recorded, image));
+        self.save(&images)
+    }
+
+    /// The images not used for at least `older_than`, or all of them without it
+    pub fn unused(&self, older_than: Option<Duration>) -> Vec<PreparedImage> {
+        let now = SystemTime::now();
+        self.list()
+            .into_iter()
+            .filter(|image| {
+                older_than.is_none_or(|older_than| {
+                    now.duration_since(image.last_used).unwrap_or_default() >= older_than
+                })
+            })
+            .collect()
+    }
+
+    fn save(&self, images: &[PreparedImage]) -> Result<(), String> {
+        let dir = self.file.parent().unwrap_or(Path::new("."));
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let content = serde_json::to_string_pretty(images)
+            .map_err(|e| format!("Failed to serialize image records: {}", e))?;
+        // Written next to the file and renamed, so concurrent runs never read half of it
+        let temp = tempfile::NamedTempFile::new_in(dir)
+            .and_then(|mut temp| {
+                std::io::Write::write_all(&mut temp, content.as_bytes())?;
+                Ok(temp)
+            })
+            .map_err(|e| format!("Failed to write {}: {}", self.file.display(), e))?;
+        temp.persist(&self.file)
+            .map_err(|e| format!("Failed to write {}: {}", self.file.display(), e.error))?;
+        Ok(())
+    }
+}
+
+fn same_image(a: &PreparedImage, b: &PreparedImage) -> bool {
+    a.runtime == b.runtime && a.tag == b.tag
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packages(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_image_tag() {
+        let plain = PreparedImage::new("docker", "python", Some("3.11"), &[]);
+        assert_eq!(plain.tag, "wrkflw-python-3.11");
+        assert_eq!(plain.environment(), "python:3.11");
+
+        let with_packages = PreparedImage::new(
+            "docker",
+            "python",
+            Some("3.11"),
+            &packages(&["requests", "flask"]),
+        );
+        let reordered = PreparedImage::new(
+            "docker",
+            "python",
+            Some("3.11"),
+            &packages(&["flask", "requests", "flask"]),
+        );
+        assert_eq!(with_packages.tag, reordered.tag);
+        assert!(with_packages.tag.starts_with("wrkflw-python-3.11-"));
+        assert_ne!(with_packages.tag, plain.tag);
+        assert_eq!(with_packages.environment(), "python:3.11 +flask,requests");
+    }
+
+    #[test]
+    fn test_record_lookup_and_forget() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ImageStore::new(dir.path().join("images.json"));
+        let node = PreparedImage::new("docker", "node", None, &packages(&["yarn"]));
+        assert_eq!(store.lookup(&node), None);
+
+        store.record(&node).unwrap();
+        store
+            .record(&PreparedImage::new(
+                "podman",
+                "node",
+                None,
+                &packages(&["yarn"]),
+            ))
+            .unwrap();
+        let found = store.lookup(&node).unwrap();
+        assert_eq!(
+            (found.runtime.as_str(), found.tag),
+            ("docker", node.tag.clone())
+        );
+
+        store.record(&node).unwrap();
+        assert_eq!(store.list().len(), 2);
+        assert!(store.unused(Some(Duration::from_secs(3600))).is_empty());
+        assert_eq!(store.unused(None).len(), 2);
+
+        store.forget(&node).unwrap();
+        assert_eq!(store.lookup(&node), None);
+        assert_eq!(store.list().len(), 1);
+    }
+}
@@ -0,0 +1,252 @@
+//! JUnit XML reports of runs: a test suite per job and a test case per step, the
+//! format CI dashboards and IDE test viewers read.
+
+use crate::engine::{ExecutionResult, JobResult, JobStatus, StepStatus};
+use std::fmt::Write;
+
+/// Render the results of a run of `workflow` as a JUnit XML report
+pub fn junit_report(workflow: &str, result: &ExecutionResult) -> String {
+    let mut tests = 0;
+    let mut failures = 0;
+    let mut skipped = 0;
+    let mut suites = String::new();
+    for job in &result.jobs {
+        let suite = JobSuite::new(job);
+        tests += suite.tests;
+        failures += suite.failures;
+        skipped += suite.skipped;
+        suite.write(workflow, job, &mut suites);
+    }
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        out,
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">",
+        escape(workflow),
+        tests,
+        failures,
+        skipped,
+        result.duration.as_secs_f64()
+    );
+    out.push_str(&suites);
+    out.push_str("</testsuites>\n");
+    out
+}
+
+struct JobSuite {
+    tests: usize,
+    failures: usize,
+    skipped: usize,
+}
+
+impl JobSuite {
+    fn new(job: &JobResult) -> Self {
+        // A job without steps is reported as a single case of its own
+        if job.steps.is_empty() {
+            return JobSuite {
+                tests: 1,
+                failures: usize::from(job.status == JobStatus::Failure),
+                skipped: usize::from(matches!(
+                    job.status,
+                    JobStatus::Skipped | JobStatus::Cancelled
+                )),
+            };
+        }
+        let count = |status| {
+            job.steps
+                .iter()
+                .filter(|step| step.status == status)
+                .count()
+        };
+        JobSuite {
+            tests: job.steps.len(),
+            failures: count(StepStatus::Failure),
+            skipped: count(StepStatus::Skipped),
+        }
+    }
+
+    fn write(&self, workflow: &str, job: &JobResult, out: &mut String) {
+        let classname = escape(&format!("{}.{}", workflow, job.name));
+        let _ = writeln!(
+            out,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{:.3}\">",
+            escape(&job.name),
+            self.tests,
+            self.failures,
+            self.skipped,
+            job.timing.duration.as_secs_f64()
+        );
+        if let Some(environment) = &job.environment {
+            let _ = writeln!(
+                out,
+                "    <properties>\n      <property name=\"environment\" value=\"{}\"/>\n    </properties>",
+                escape(environment)
+            );
+        }
+
+        if job.steps.is_empty() {
+            let _ = write!(
+                out,
+                "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\"",
+                classname,
+                escape(&job.name),
+                job.timing.duration.as_secs_f64()
+            );
+            match job.status {
+                JobStatus::Success => out.push_str("/>\n"),
+                JobStatus::Skipped | JobStatus::Cancelled => {
+                    let _ = writeln!(
+                        out,
+                        ">\n      <skipped message=\"{}\"/>\n    </testcase>",
+                        escape(job.logs.trim())
+                    );
+                }
+                JobStatus::Failure => {
+                    let _ = writeln!(
+                        out,
+                        ">\n      <failure message=\"{}\">{}</failure>\n    </testcase>",
+                        escape(first_line(&job.logs)),
+                        escape(&job.logs)
+                    );
+                }
+            }
+        }
+
+        for step in &job.steps {
+            let _ = writeln!(
+                out,
+                "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\">",
+                classname,
+                escape(&step.name),
+                step.duration.as_secs_f64()
+            );
+            match step.status {
+                StepStatus::Success => {}
+                StepStatus::Skipped => out.push_str("      <skipped/>\n"),
+                StepStatus::Failure => {
+                    let _ = writeln!(
+                        out,
+                        "      <failure message=\"{}\">{}</failure>",
+                        escape(first_line(&step.output)),
+                        escape(&step.output)
+                    );
+                }
+            }
+            // A failure already carries the output
+            if step.status != StepStatus::Failure && !step.output.is_empty() {
+                let _ = writeln!(
+                    out,
+                    "      <system-out>{}</system-out>",
+                    escape(&step.output)
+                );
+            }
+            out.push_str("    </testcase>\n");
+        }
+        out.push_str("  </testsuite>\n");
+    }
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("")
+}
+
+/// Escape text for attributes and elements, dropping ANSI colors and characters
+/// XML 1.0 doesn't allow
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{1b}' => {
+                // CSI sequences end with a letter
+                if chars.peek() == Some(&'[') {
+                    for c in chars.by_ref() {
+                        if c.is_ascii_alphabetic() {
+                            break;
+                        }
+                    }
+                }
+            }
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c < ' ' || c == '\u{fffe}' || c == '\u{ffff}' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::StepResult;
+    use crate::timing::JobTiming;
+    use std::time::Duration;
+
+    fn step(name: &str, status: StepStatus, output: &str) -> StepResult {
+        StepResult {
+            name: name.to_string(),
+            status,
+            output: output.to_string(),
+            duration: Duration::from_millis(250),
+        }
+    }
+
+    #[test]
+    fn test_junit_report() {
+        let job = |name: &str, status, steps| JobResult {
+            name: name.to_string(),
+            status,
+            steps,
+            logs: "Job skipped: `if:` is false".to_string(),
+            environment: (name == "deploy").then(|| "production".to_string()),
+            timing: JobTiming::default(),
+        };
+        let result = ExecutionResult {
+            jobs: vec![
+                job(
+                    "test",
+                    JobStatus::Failure,
+                    vec![
+                        step("Build", StepStatus::Success, ""),
+                        step(
+                            "Run <tests>",
+                            StepStatus::Failure,
+                            "\u{1b}[31merror\u{1b}[0m: 1 \"test\" failed\nExit code: 1",
+                        ),
+                        step("Upload", StepStatus::Skipped, ""),
+                    ],
+                ),
+                job("deploy", JobStatus::Skipped, Vec::new()),
+            ],
+            failure_details: None,
+            duration: Duration::from_secs(3),
+            deployments: Vec::new(),
+            resume: Default::default(),
+        };
+
+        let xml = junit_report("ci.yml", &result);
+        assert!(xml.starts_with("<?xml"));
+        assert!(xml.contains(
+            "<testsuites name=\"ci.yml\" tests=\"4\" failures=\"1\" skipped=\"2\" time=\"3.000\">"
+        ));
+        assert!(xml.contains(
+            "<testsuite name=\"test\" tests=\"3\" failures=\"1\" errors=\"0\" skipped=\"1\""
+        ));
+        assert!(xml.contains(
+            "<testcase classname=\"ci.yml.test\" name=\"Run &lt;tests&gt;\" time=\"0.250\">"
+        ));
+        assert!(xml.contains("<failure message=\"error: 1 &quot;test&quot; failed\">"));
+        assert!(xml.contains("<skipped message=\"Job skipped: `if:` is false\"/>"));
+        assert_eq!(xml.matches("<property ").count(), 1);
+        assert!(xml.contains("<property name=\"environment\" value=\"production\"/>"));
+        assert!(!xml.contains('\u{1b}'));
+    }
+}
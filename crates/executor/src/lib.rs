@@ -2,15 +2,56 @@
 
 #![allow(unused_variables, unused_assignments)]
 
+pub mod action_cache;
+pub mod build;
+pub mod cancel;
+pub mod checkout;
+pub mod condition;
+pub mod config;
+pub mod context;
+pub mod debug_shell;
 pub mod dependency;
+pub mod deployments;
 pub mod docker;
 pub mod engine;
 pub mod environment;
+pub mod filter;
+pub mod gitlab_vars;
+pub mod graph;
+pub mod images;
+pub mod junit;
+pub mod logs;
+pub mod oidc;
+pub mod paths;
+pub mod plan;
 pub mod podman;
+pub mod policy;
+pub mod post;
+pub mod progress;
+pub mod pull;
+pub mod resume;
+pub mod runner;
+pub mod runs;
+pub mod shell;
+pub mod stages;
 pub mod substitution;
+pub mod timing;
+pub mod triggers;
+pub mod vars;
+pub mod workspace;
 
 // Re-export public items
 pub use docker::cleanup_resources;
 pub use engine::{
     execute_workflow, ExecutionConfig, JobResult, JobStatus, RuntimeType, StepResult, StepStatus,
 };
+pub use graph::{build_graph, GraphFormat, WorkflowGraph};
+pub use plan::{plan_workflow, ExecutionPlan};
+pub use pull::PullPolicy;
+pub use runner::{ForeignRunnerAction, RunnerOsPolicy};
+pub use timing::{JobTiming, TimingReport};
+pub use workspace::{FileOwnership, WorkspaceStrategy};
+pub use wrkflw_runtime::container::NetworkMode;
+pub use wrkflw_runtime::output::DEFAULT_OUTPUT_LIMIT;
+pub use wrkflw_runtime::sandbox::SandboxConfig;
+pub use wrkflw_runtime::timeouts::Timeouts;
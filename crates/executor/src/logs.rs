@@ -0,0 +1,393 @@
+//! Logs of runs, written while they run under `.wrkflw/logs/<id>/` in the project:
+//! the output of every step in a file per job (`jobs/<job>.log`), and the same with
+//! wrkflw's own messages in `combined.log`. `run.json` tells which workflow the run
+//! is of and, once it finished, whether it succeeded. Everything written is masked
+//! with `wrkflw_logging::mask` first, and a `.gitignore` keeps the logs out of git.
+
+use crate::engine::{ExecutionResult, JobStatus, StepResult, StepStatus};
+use chrono::{DateTime, Local};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Everything logged during a run
+pub const COMBINED_LOG: &str = "combined.log";
+/// Directory of the per-job logs in a run's directory
+pub const JOBS_DIR: &str = "jobs";
+/// Summary of a run in its directory
+pub const RUN_FILE: &str = "run.json";
+
+/// Runs whose logs are kept; older ones are removed when a run starts
+const MAX_RUNS: usize = 50;
+
+/// A run with logs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogRun {
+    /// Name of the run's directory
+    pub id: String,
+    pub workflow: String,
+    pub path: PathBuf,
+    pub started: DateTime<Local>,
+    /// Whether the run succeeded; not known while it runs
+    #[serde(default)]
+    pub success: Option<bool>,
+}
+
+impl LogRun {
+    pub fn is_finished(&self) -> bool {
+        self.success.is_some()
+    }
+}
+
+/// Run logs of a project
+pub struct LogStore {
+    root: PathBuf,
+}
+
+impl LogStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        LogStore { root: root.into() }
+    }
+
+    /// `.wrkflw/logs` of `project_dir`
+    pub fn for_project(project_dir: &Path) -> Self {
+        Self::new(project_dir.join(".wrkflw").join("logs"))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Runs with logs, newest first
+    pub fn list(&self) -> Vec<LogRun> {
+        let Ok(entries) = fs::read_dir(&self.root) else {
+            return Vec::new();
+        };
+        let mut runs: Vec<LogRun> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let json = fs::read_to_string(entry.path().join(RUN_FILE)).ok()?;
+                serde_json::from_str(&json).ok()
+            })
+            .collect();
+        runs.sort_by(|a, b| b.started.cmp(&a.started).then(b.id.cmp(&a.id)));
+        runs
+    }
+
+    /// A run by id, or the newest one for `latest`
+    pub fn load(&self, id: &str) -> Result<LogRun, String> {
+        if id == "latest" {
+            return self
+                .list()
+                .into_iter()
+                .next()
+                .ok_or_else(|| format!("No run logs in {}", self.root.display()));
+        }
+        let file = self.root.join(id).join(RUN_FILE);
+        let json = fs::read_to_string(&file).map_err(|_| format!("No logs of run '{}'", id))?;
+        serde_json::from_str(&json).map_err(|e| format!("Failed to read {}: {}", file.display(), e))
+    }
+
+    /// Jobs of `run` that logged something, by name
+    pub fn jobs(&self, run: &LogRun) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(self.root.join(&run.id).join(JOBS_DIR)) else {
+            return Vec::new();
+        };
+        let mut jobs: Vec<String> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                name.strip_suffix(".log").map(str::to_string)
+            })
+            .collect();
+        jobs.sort();
+        jobs
+    }
+
+    /// The combined log of `run`, or the log of one of its jobs
+    pub fn log_file(&self, run: &LogRun, job: Option<&str>) -> Result<PathBuf, String> {
+        let dir = self.root.join(&run.id);
+        let Some(job) = job else {
+            return Ok(dir.join(COMBINED_LOG));
+        };
+        let file = dir.join(JOBS_DIR).join(job_file_name(job));
+        if file.is_file() {
+            Ok(file)
+        } else {
+            Err(format!(
+                "Run {} has no log of job '{}' (jobs: {})",
+                run.id,
+                job,
+                self.jobs(run).join(", ")
+            ))
+        }
+    }
+
+    // Create the directory of a run of the workflow at `path` starting now, removing
+    // the logs of the oldest runs beyond `MAX_RUNS`
+    fn start(&self, path: &Path) -> Result<LogRun, String> {
+        for old in self.list().iter().skip(MAX_RUNS - 1) {
+            let _ = fs::remove_dir_all(self.root.join(&old.id));
+        }
+
+        let started = Local::now();
+        let id = crate::runs::new_run_id(&self.root, started);
+        let dir = self.root.join(&id);
+        fs::create_dir_all(dir.join(JOBS_DIR))
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        self.ignore_in_git()?;
+        let run = LogRun {
+            id,
+            workflow: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            path: path.to_path_buf(),
+            started,
+            success: None,
+        };
+        self.save(&run)?;
+        Ok(run)
+    }
+
+    // Keep the logs out of commits of the project they're in
+    fn ignore_in_git(&self) -> Result<(), String> {
+        let file = self.root.join(".gitignore");
+        if file.exists() {
+            return Ok(());
+        }
+        fs::write(&file, "# Run logs written by wrkflw\n*\n")
+            .map_err(|e| format!("Failed to write {}: {}", file.display(), e))
+    }
+
+    fn save(&self, run: &LogRun) -> Result<(), String> {
+        let file = self.root.join(&run.id).join(RUN_FILE);
+        let json = serde_json::to_string_pretty(run)
+            .map_err(|e| format!("Failed to serialize the run: {}", e))?;
+        fs::write(&file, json).map_err(|e| format!("Failed to write {}: {}", file.display(), e))
+    }
+}
+
+/// File the log of `job` is written to: its name with anything but letters, digits,
+/// `-`, `_` and `.` replaced
+pub fn job_file_name(job: &str) -> String {
+    let name: String = job
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}.log", name)
+}
+
+// The run being logged
+struct ActiveRun {
+    store: LogStore,
+    run: LogRun,
+}
+
+static ACTIVE: Lazy<Mutex<Option<ActiveRun>>> = Lazy::new(|| Mutex::new(None));
+
+// Id of the run logged last
+static LAST_RUN: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+/// Id of the last run whose logs were written
+pub fn last_run_id() -> Option<String> {
+    LAST_RUN.lock().ok().and_then(|last| last.clone())
+}
+
+/// Start writing the logs of a run of the workflow at `path`, under `.wrkflw/logs`
+/// of the current directory
+pub(crate) fn start(path: &Path) {
+    let store = match std::env::current_dir() {
+        Ok(dir) => LogStore::for_project(&dir),
+        Err(e) => {
+            wrkflw_logging::warning(&format!("Not writing log files: {}", e));
+            return;
+        }
+    };
+    let run = match store.start(path) {
+        Ok(run) => run,
+        Err(e) => {
+            wrkflw_logging::warning(&format!("Not writing log files: {}", e));
+            return;
+        }
+    };
+    let combined = store.root.join(&run.id).join(COMBINED_LOG);
+    if let Err(e) = wrkflw_logging::set_run_log(Some(&combined)) {
+        wrkflw_logging::warning(&format!("Failed to open {}: {}", combined.display(), e));
+    }
+    if let Ok(mut last) = LAST_RUN.lock() {
+        *last = Some(run.id.clone());
+    }
+    if let Ok(mut active) = ACTIVE.lock() {
+        *active = Some(ActiveRun { store, run });
+    }
+}
+
+/// Finish the logs of the current run with the result of its jobs
+pub(crate) fn finish(result: Option<&ExecutionResult>) {
+    let Some(ActiveRun { store, mut run }) = ACTIVE.lock().ok().and_then(|mut a| a.take()) else {
+        return;
+    };
+    let _ = wrkflw_logging::set_run_log(None);
+
+    let mut summary = String::new();
+    for job in result
+        .map(|result| result.jobs.as_slice())
+        .unwrap_or_default()
+    {
+        summary.push_str(&format!(
+            "{} {} ({:.1}s)\n",
+            job_icon(&job.status),
+            job.name,
+            job.timing.duration.as_secs_f64()
+        ));
+    }
+    let success = result.is_some_and(|result| result.failure_details.is_none());
+    summary.push_str(&format!(
+        "Run {}: {}\n",
+        run.id,
+        if success { "success" } else { "failure" }
+    ));
+    append(&store.root.join(&run.id).join(COMBINED_LOG), &summary);
+
+    run.success = Some(success);
+    if let Err(e) = store.save(&run) {
+        wrkflw_logging::warning(&e);
+    }
+}
+
+pub(crate) fn step_started(job: &str, name: &str) {
+    write_job(
+        job,
+        &format!("[{}] ▶ {}\n", Local::now().format("%H:%M:%S"), name),
+    );
+}
+
+pub(crate) fn step_finished(job: &str, result: &StepResult) {
+    let mut text = result.output.clone();
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text.push_str(&format!(
+        "[{}] {} {} ({:.1}s)\n",
+        Local::now().format("%H:%M:%S"),
+        match result.status {
+            StepStatus::Success => "✅",
+            StepStatus::Failure => "❌",
+            StepStatus::Skipped => "⏭️",
+        },
+        result.name,
+        result.duration.as_secs_f64()
+    ));
+    write_job(job, &text);
+}
+
+// Append to the log of `job` and, with the job's name in front of each line, to
+// the combined log
+fn write_job(job: &str, text: &str) {
+    let Ok(active) = ACTIVE.lock() else {
+        return;
+    };
+    let Some(ActiveRun { store, run }) = active.as_ref() else {
+        return;
+    };
+    let dir = store.root.join(&run.id);
+    append(&dir.join(JOBS_DIR).join(job_file_name(job)), text);
+    let prefixed: String = text
+        .lines()
+        .map(|line| format!("{} | {}\n", job, line))
+        .collect();
+    append(&dir.join(COMBINED_LOG), &prefixed);
+}
+
+// Append `text` with the run's secrets masked
+fn append(file: &Path, text: &str) {
+    let text = wrkflw_logging::mask(text);
+    let written = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file)
+        .and_then(|mut f| f.write_all(text.as_bytes()));
+    if let Err(e) = written {
+        wrkflw_logging::debug(&format!("Failed to write {}: {}", file.display(), e));
+    }
+}
+
+fn job_icon(status: &JobStatus) -> &'static str {
+    match status {
+        JobStatus::Success => "✅",
+        JobStatus::Failure => "❌",
+        JobStatus::Skipped => "⏭️",
+        JobStatus::Cancelled => "🚫",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_file_name() {
+        assert_eq!(job_file_name("build"), "build.log");
+        assert_eq!(
+            job_file_name("test (ubuntu-latest, 18)"),
+            "test__ubuntu-latest__18_.log"
+        );
+    }
+
+    #[test]
+    fn test_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LogStore::new(dir.path());
+        let run = store.start(Path::new(".github/workflows/ci.yml")).unwrap();
+        assert_eq!(run.workflow, "ci.yml");
+        assert!(!run.is_finished());
+        assert_eq!(
+            fs::read_to_string(dir.path().join(".gitignore")).unwrap(),
+            "# Run logs written by wrkflw\n*\n"
+        );
+
+        let job_log = dir
+            .path()
+            .join(&run.id)
+            .join(JOBS_DIR)
+            .join(job_file_name("build"));
+        fs::write(&job_log, "ok\n").unwrap();
+        assert_eq!(store.jobs(&run), vec!["build".to_string()]);
+        assert_eq!(store.log_file(&run, Some("build")).unwrap(), job_log);
+        assert!(store.log_file(&run, Some("lint")).is_err());
+        assert_eq!(
+            store.log_file(&run, None).unwrap(),
+            dir.path().join(&run.id).join(COMBINED_LOG)
+        );
+
+        let finished = LogRun {
+            success: Some(true),
+            ..run.clone()
+        };
+        store.save(&finished).unwrap();
+        assert_eq!(store.load("latest").unwrap(), finished);
+        assert_eq!(store.load(&run.id).unwrap(), finished);
+        assert!(store.load("missing").is_err());
+        // The .gitignore isn't taken for a run
+        assert_eq!(store.list().len(), 1);
+    }
+
+    #[test]
+    fn test_append_masks_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("job.log");
+        wrkflw_logging::add_mask("hunter2VALUEXYZ");
+        append(&file, "token: hunter2VALUEXYZ\n");
+        assert_eq!(fs::read_to_string(&file).unwrap(), "token: ***\n");
+    }
+}
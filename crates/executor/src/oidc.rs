@@ -0,0 +1,467 @@
+//! Local emulation of the GitHub Actions OIDC token endpoint.
+//!
+//! Jobs granted `id-token: write` receive `ACTIONS_ID_TOKEN_REQUEST_URL` and
+//! `ACTIONS_ID_TOKEN_REQUEST_TOKEN`, pointing at a small HTTP server that mints
+//! HS256-signed tokens with GitHub-like claims. The tokens are not trusted by
+//! any cloud provider; they exist so steps such as
+//! `aws-actions/configure-aws-credentials` can be exercised locally. A fixed
+//! token can be served instead by setting `WRKFLW_OIDC_TOKEN`.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use hmac::{Hmac, Mac};
+use serde_json::{json, Map, Value};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use wrkflw_parser::workflow::{Job, PermissionLevel, WorkflowDefinition};
+
+/// Issuer used by GitHub-hosted OIDC tokens
+pub const DEFAULT_ISSUER: &str = "https://token.actions.githubusercontent.com";
+
+/// Variable holding the token endpoint URL
+pub const REQUEST_URL_VAR: &str = "ACTIONS_ID_TOKEN_REQUEST_URL";
+
+/// Variable holding the bearer token for the endpoint
+pub const REQUEST_TOKEN_VAR: &str = "ACTIONS_ID_TOKEN_REQUEST_TOKEN";
+
+/// Lifetime of minted tokens, matching GitHub's
+const TOKEN_LIFETIME_SECS: i64 = 300;
+
+/// Largest request head the server will read
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// User-configurable token settings
+#[derive(Debug, Clone, Default)]
+pub struct OidcConfig {
+    /// Serve this token verbatim instead of minting one
+    pub static_token: Option<String>,
+    /// Override the `iss` claim
+    pub issuer: Option<String>,
+    /// Override the `sub` claim
+    pub subject: Option<String>,
+    /// Additional claims merged into every minted token
+    pub extra_claims: Map<String, Value>,
+}
+
+impl OidcConfig {
+    /// Read settings from `WRKFLW_OIDC_TOKEN`, `WRKFLW_OIDC_ISSUER`,
+    /// `WRKFLW_OIDC_SUBJECT` and `WRKFLW_OIDC_CLAIMS` (a JSON object)
+    pub fn from_env() -> Self {
+        let extra_claims = std::env::var("WRKFLW_OIDC_CLAIMS")
+            .ok()
+            .and_then(
+                |raw| match serde_json::from_str::<Map<String, Value>>(&raw) {
+                    Ok(claims) => Some(claims),
+                    Err(e) => {
+                        wrkflw_logging::warning(&format!(
+                            "Ignoring WRKFLW_OIDC_CLAIMS, expected a JSON object: {}",
+                            e
+                        ));
+                        None
+                    }
+                },
+            )
+            .unwrap_or_default();
+
+        Self {
+            static_token: std::env::var("WRKFLW_OIDC_TOKEN").ok(),
+            issuer: std::env::var("WRKFLW_OIDC_ISSUER").ok(),
+            subject: std::env::var("WRKFLW_OIDC_SUBJECT").ok(),
+            extra_claims,
+        }
+    }
+}
+
+/// Whether a job may request an OIDC token (`id-token: write` on the job,
+/// or on the workflow when the job does not set its own permissions)
+pub fn job_requests_id_token(job: &Job, workflow: &WorkflowDefinition) -> bool {
+    let permissions = job.permissions.as_ref().or(workflow.permissions.as_ref());
+    permissions.is_some_and(|p| p.level("id-token") == PermissionLevel::Write)
+}
+
+/// Remove the token request variables from a job environment unless the job
+/// was granted `id-token: write`
+pub fn restrict_to_permitted_job(
+    job_env: &mut HashMap<String, String>,
+    job: &Job,
+    workflow: &WorkflowDefinition,
+) {
+    if !job_requests_id_token(job, workflow) {
+        job_env.remove(REQUEST_URL_VAR);
+        job_env.remove(REQUEST_TOKEN_VAR);
+    }
+}
+
+/// Whether any job in the workflow may request an OIDC token
+pub fn workflow_requests_id_token(workflow: &WorkflowDefinition) -> bool {
+    workflow
+        .jobs
+        .values()
+        .any(|job| job_requests_id_token(job, workflow))
+}
+
+struct TokenIssuer {
+    config: OidcConfig,
+    signing_key: [u8; 32],
+    request_token: String,
+    base_claims: Map<String, Value>,
+}
+
+/// Running token endpoint; stops when dropped
+pub struct OidcTokenServer {
+    issuer: Arc<TokenIssuer>,
+    port: u16,
+    handle: JoinHandle<()>,
+}
+
+impl OidcTokenServer {
+    /// Start the endpoint. `context` is the GitHub environment of the run and
+    /// supplies the repository, ref and workflow claims. With `bind_all` the
+    /// server listens on all interfaces so containers can reach it.
+    pub async fn start(
+        config: OidcConfig,
+        context: &HashMap<String, String>,
+        bind_all: bool,
+    ) -> io::Result<Self> {
+        let addr = if bind_all { "0.0.0.0:0" } else { "127.0.0.1:0" };
+        let listener = TcpListener::bind(addr).await?;
+        let port = listener.local_addr()?.port();
+
+        let mut signing_key = [0u8; 32];
+        signing_key[..16].copy_from_slice(uuid::Uuid::new_v4().as_bytes());
+        signing_key[16..].copy_from_slice(uuid::Uuid::new_v4().as_bytes());
+
+        let issuer = Arc::new(TokenIssuer {
+            config,
+            signing_key,
+            request_token: uuid::Uuid::new_v4().simple().to_string(),
+            base_claims: base_claims(context),
+        });
+
+        let server_issuer = issuer.clone();
+        let handle = tokio::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else {
+                    continue;
+                };
+                let issuer = server_issuer.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handle_connection(stream, &issuer).await {
+                        wrkflw_logging::debug(&format!("OIDC endpoint connection error: {}", e));
+                    }
+                });
+            }
+        });
+
+        wrkflw_logging::info(&format!(
+            "Started local OIDC token endpoint on port {}",
+            port
+        ));
+
+        Ok(Self {
+            issuer,
+            port,
+            handle,
+        })
+    }
+
+    /// Port the endpoint listens on
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Environment variables that point a job at this endpoint, as seen from `host`
+    pub fn env_vars(&self, host: &str) -> HashMap<String, String> {
+        HashMap::from([
+            (
+                REQUEST_URL_VAR.to_string(),
+                format!("http://{}:{}/token?api-version=2.0", host, self.port),
+            ),
+            (
+                REQUEST_TOKEN_VAR.to_string(),
+                self.issuer.request_token.clone(),
+            ),
+        ])
+    }
+}
+
+impl Drop for OidcTokenServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+impl TokenIssuer {
+    fn mint(&self, audience: Option<&str>) -> String {
+        if let Some(token) = &self.config.static_token {
+            return token.clone();
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let mut claims = self.base_claims.clone();
+        claims.insert(
+            "iss".to_string(),
+            json!(self.config.issuer.as_deref().unwrap_or(DEFAULT_ISSUER)),
+        );
+        if let Some(subject) = &self.config.subject {
+            claims.insert("sub".to_string(), json!(subject));
+        }
+        let default_audience = claims
+            .get("repository_owner")
+            .and_then(Value::as_str)
+            .map(|owner| format!("https://github.com/{}", owner))
+            .unwrap_or_else(|| "sts.amazonaws.com".to_string());
+        claims.insert(
+            "aud".to_string(),
+            json!(audience.unwrap_or(&default_audience)),
+        );
+        claims.insert("iat".to_string(), json!(now));
+        claims.insert("nbf".to_string(), json!(now));
+        claims.insert("exp".to_string(), json!(now + TOKEN_LIFETIME_SECS));
+        claims.insert("jti".to_string(), json!(uuid::Uuid::new_v4().to_string()));
+        for (key, value) in &self.config.extra_claims {
+            claims.insert(key.clone(), value.clone());
+        }
+
+        sign_hs256(&Value::Object(claims), &self.signing_key)
+    }
+}
+
+/// Claims derived from the run context that do not change between requests
+fn base_claims(context: &HashMap<String, String>) -> Map<String, Value> {
+    let get = |key: &str| context.get(key).cloned().unwrap_or_default();
+    let repository = get("GITHUB_REPOSITORY");
+    let git_ref = get("GITHUB_REF");
+    let owner = repository.split('/').next().unwrap_or_default().to_string();
+
+    let mut claims = Map::new();
+    claims.insert(
+        "sub".to_string(),
+        json!(format!("repo:{}:ref:{}", repository, git_ref)),
+    );
+    claims.insert("repository".to_string(), json!(repository));
+    claims.insert("repository_owner".to_string(), json!(owner));
+    claims.insert("ref".to_string(), json!(git_ref));
+    claims.insert("sha".to_string(), json!(get("GITHUB_SHA")));
+    claims.insert("workflow".to_string(), json!(get("GITHUB_WORKFLOW")));
+    claims.insert("actor".to_string(), json!(get("GITHUB_ACTOR")));
+    claims.insert("event_name".to_string(), json!(get("GITHUB_EVENT_NAME")));
+    claims.insert("run_id".to_string(), json!(get("GITHUB_RUN_ID")));
+    claims.insert("run_number".to_string(), json!(get("GITHUB_RUN_NUMBER")));
+    claims.insert("runner_environment".to_string(), json!("self-hosted"));
+    claims
+}
+
+fn sign_hs256(claims: &Value, key: &[u8]) -> String {
+    let header = URL_SAFE_NO_PAD.encode(br#"{"alg":"HS256","typ":"JWT"}"#);
+    let payload = URL_SAFE_NO_PAD.encode(claims.to_string());
+    let signing_input = format!("{}.{}", header, payload);
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(signing_input.as_bytes());
+    let signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+
+    format!("{}.{}", signing_input, signature)
+}
+
+async fn handle_connection(mut stream: TcpStream, issuer: &TokenIssuer) -> io::Result<()> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 || buf.len() + n > MAX_REQUEST_BYTES {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    let request = String::from_utf8_lossy(&buf);
+    let (status, body) = respond(&request, issuer);
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+fn respond(request: &str, issuer: &TokenIssuer) -> (&'static str, String) {
+    let mut lines = request.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default();
+    let target = request_line.next().unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    if method != "GET" || path != "/token" {
+        return ("404 Not Found", json!({"message": "Not Found"}).to_string());
+    }
+
+    let authorized = lines
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(name, _)| name.trim().eq_ignore_ascii_case("authorization"))
+        .any(|(_, value)| {
+            value.trim().strip_prefix("Bearer ") == Some(issuer.request_token.as_str())
+        });
+    if !authorized {
+        return (
+            "401 Unauthorized",
+            json!({"message": "Bad credentials"}).to_string(),
+        );
+    }
+
+    let audience = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == "audience")
+        .map(|(_, value)| {
+            urlencoding::decode(value)
+                .map(|v| v.into_owned())
+                .unwrap_or_else(|_| value.to_string())
+        });
+
+    let token = issuer.mint(audience.as_deref());
+    ("200 OK", json!({ "value": token }).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_context() -> HashMap<String, String> {
+        HashMap::from([
+            ("GITHUB_REPOSITORY".to_string(), "octo/repo".to_string()),
+            ("GITHUB_REF".to_string(), "refs/heads/main".to_string()),
+            ("GITHUB_WORKFLOW".to_string(), "CI".to_string()),
+        ])
+    }
+
+    async fn get(port: u16, path: &str, token: Option<&str>) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        let auth = token
+            .map(|t| format!("Authorization: Bearer {}\r\n", t))
+            .unwrap_or_default();
+        let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n{}\r\n", path, auth);
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    fn decode_claims(token: &str) -> Value {
+        let payload = token.split('.').nth(1).unwrap();
+        serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_mints_signed_token_for_audience() {
+        let server = OidcTokenServer::start(OidcConfig::default(), &test_context(), false)
+            .await
+            .unwrap();
+        let env = server.env_vars("127.0.0.1");
+        let request_token = &env[REQUEST_TOKEN_VAR];
+        assert!(env[REQUEST_URL_VAR].contains("/token?api-version=2.0"));
+
+        let response = get(
+            server.port(),
+            "/token?api-version=2.0&audience=sts.amazonaws.com",
+            Some(request_token),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 200"));
+
+        let body: Value = serde_json::from_str(response.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        let token = body["value"].as_str().unwrap();
+        assert_eq!(token.split('.').count(), 3);
+
+        let claims = decode_claims(token);
+        assert_eq!(claims["aud"], "sts.amazonaws.com");
+        assert_eq!(claims["iss"], DEFAULT_ISSUER);
+        assert_eq!(claims["sub"], "repo:octo/repo:ref:refs/heads/main");
+        assert_eq!(claims["repository_owner"], "octo");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_missing_request_token() {
+        let server = OidcTokenServer::start(OidcConfig::default(), &test_context(), false)
+            .await
+            .unwrap();
+        let response = get(server.port(), "/token", None).await;
+        assert!(response.starts_with("HTTP/1.1 401"));
+
+        let response = get(server.port(), "/token", Some("wrong")).await;
+        assert!(response.starts_with("HTTP/1.1 401"));
+    }
+
+    #[tokio::test]
+    async fn test_serves_configured_token_and_claims() {
+        let issuer = TokenIssuer {
+            config: OidcConfig {
+                static_token: Some("fixed.token.value".to_string()),
+                ..Default::default()
+            },
+            signing_key: [7u8; 32],
+            request_token: "t".to_string(),
+            base_claims: base_claims(&test_context()),
+        };
+        assert_eq!(issuer.mint(None), "fixed.token.value");
+
+        let mut extra_claims = Map::new();
+        extra_claims.insert("environment".to_string(), json!("production"));
+        let issuer = TokenIssuer {
+            config: OidcConfig {
+                subject: Some("repo:octo/repo:environment:production".to_string()),
+                extra_claims,
+                ..Default::default()
+            },
+            ..issuer
+        };
+        let claims = decode_claims(&issuer.mint(None));
+        assert_eq!(claims["sub"], "repo:octo/repo:environment:production");
+        assert_eq!(claims["environment"], "production");
+        assert_eq!(claims["aud"], "https://github.com/octo");
+    }
+
+    #[test]
+    fn test_job_requests_id_token() {
+        let yaml = r#"
+name: OIDC
+on: push
+permissions:
+  id-token: write
+jobs:
+  inherits:
+    runs-on: ubuntu-latest
+    steps: []
+  overrides:
+    runs-on: ubuntu-latest
+    permissions:
+      contents: read
+    steps: []
+"#;
+        let workflow: WorkflowDefinition = serde_yaml::from_str(yaml).unwrap();
+        assert!(job_requests_id_token(&workflow.jobs["inherits"], &workflow));
+        assert!(!job_requests_id_token(
+            &workflow.jobs["overrides"],
+            &workflow
+        ));
+        assert!(workflow_requests_id_token(&workflow));
+
+        let mut job_env = HashMap::from([
+            (
+                REQUEST_URL_VAR.to_string(),
+                "http://127.0.0.1:1/token".to_string(),
+            ),
+            (REQUEST_TOKEN_VAR.to_string(), "t".to_string()),
+        ]);
+        restrict_to_permitted_job(&mut job_env, &workflow.jobs["inherits"], &workflow);
+        assert_eq!(job_env.len(), 2);
+        restrict_to_permitted_job(&mut job_env, &workflow.jobs["overrides"], &workflow);
+        assert!(job_env.is_empty());
+    }
+}
@@ -0,0 +1,352 @@
+//! `paths`/`paths-ignore` filters of workflow triggers and `changes:` conditions of
+//! GitLab jobs, matched against changed files the way GitHub and GitLab match them,
+//! and the `branches`/`tags` filters of triggers, matched against refs.
+
+use regex::Regex;
+use serde_yaml::Value;
+use std::path::Path;
+use std::process::Command;
+use wrkflw_models::gitlab::{Except, Job, Only};
+use wrkflw_parser::workflow::parse_workflow;
+
+/// Path filters of one event of a workflow's `on:`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PathFilter {
+    pub paths: Vec<String>,
+    pub paths_ignore: Vec<String>,
+}
+
+impl PathFilter {
+    /// Filters of `event` in a workflow's `on:`; None if it has none
+    pub fn for_event(on: &Value, event: &str) -> Option<Self> {
+        let config = on.get(event)?;
+        let filter = PathFilter {
+            paths: filter_list(config, "paths"),
+            paths_ignore: filter_list(config, "paths-ignore"),
+        };
+        (!filter.paths.is_empty() || !filter.paths_ignore.is_empty()).then_some(filter)
+    }
+
+    /// Whether changes to `files`, relative to the repository root, trigger the event
+    pub fn matches<S: AsRef<str>>(&self, files: &[S]) -> bool {
+        let files: Vec<&str> = files.iter().map(AsRef::as_ref).collect();
+        // `paths`: at least one file has to match; later patterns override earlier
+        // ones, so `!` can exclude part of what a pattern before it included
+        if !self.paths.is_empty()
+            && !files
+                .iter()
+                .any(|file| last_match(&self.paths, file).unwrap_or(false))
+        {
+            return false;
+        }
+        // `paths-ignore`: runs unless every file is ignored
+        if !self.paths_ignore.is_empty()
+            && files
+                .iter()
+                .all(|file| last_match(&self.paths_ignore, file).unwrap_or(false))
+        {
+            return false;
+        }
+        true
+    }
+}
+
+/// Branch and tag filters of one event of a workflow's `on:`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RefFilter {
+    pub branches: Vec<String>,
+    pub branches_ignore: Vec<String>,
+    pub tags: Vec<String>,
+    pub tags_ignore: Vec<String>,
+}
+
+impl RefFilter {
+    /// Filters of `event` in a workflow's `on:`; None if it has none
+    pub fn for_event(on: &Value, event: &str) -> Option<Self> {
+        let config = on.get(event)?;
+        let filter = RefFilter {
+            branches: filter_list(config, "branches"),
+            branches_ignore: filter_list(config, "branches-ignore"),
+            tags: filter_list(config, "tags"),
+            tags_ignore: filter_list(config, "tags-ignore"),
+        };
+        (filter != RefFilter::default()).then_some(filter)
+    }
+
+    /// Whether the event triggers for `git_ref` (`refs/heads/<branch>` or
+    /// `refs/tags/<tag>`; for pull requests, their base branch)
+    pub fn matches(&self, git_ref: &str) -> bool {
+        // Filtering only branches leaves out every tag, and the other way round
+        let names = |include: &[String], ignore: &[String], name: &str| {
+            if !include.is_empty() {
+                last_match(include, name).unwrap_or(false)
+            } else {
+                !last_match(ignore, name).unwrap_or(false)
+            }
+        };
+        if let Some(branch) = git_ref.strip_prefix("refs/heads/") {
+            if self.branches.is_empty() && self.branches_ignore.is_empty() {
+                return self.tags.is_empty() && self.tags_ignore.is_empty();
+            }
+            names(&self.branches, &self.branches_ignore, branch)
+        } else if let Some(tag) = git_ref.strip_prefix("refs/tags/") {
+            if self.tags.is_empty() && self.tags_ignore.is_empty() {
+                return self.branches.is_empty() && self.branches_ignore.is_empty();
+            }
+            names(&self.tags, &self.tags_ignore, tag)
+        } else {
+            false
+        }
+    }
+}
+
+/// The patterns under `key` in the configuration of a trigger
+fn filter_list(config: &Value, key: &str) -> Vec<String> {
+    config
+        .get(key)
+        .and_then(Value::as_sequence)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether the last of `patterns` that matches `file` includes it (false for a `!`
+/// pattern); None if none matches
+fn last_match(patterns: &[String], file: &str) -> Option<bool> {
+    patterns.iter().rev().find_map(|pattern| {
+        let (negated, pattern) = match pattern.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, pattern.as_str()),
+        };
+        glob_match(pattern, file).then_some(!negated)
+    })
+}
+
+/// Filters of the workflow at `path` for pushes; None if it has none or isn't a
+/// GitHub workflow
+pub fn workflow_push_filter(path: &Path) -> Option<PathFilter> {
+    let workflow = parse_workflow(path).ok()?;
+    PathFilter::for_event(&workflow.on_raw, "push")
+}
+
+/// Whether a push changing `files` adds the GitLab `job` to the pipeline, going by
+/// its `changes:` conditions; other conditions can't be checked locally and are
+/// taken as met
+pub fn gitlab_job_runs<S: AsRef<str>>(job: &Job, files: &[S]) -> bool {
+    let changed = |patterns: &[String]| {
+        files.iter().any(|file| {
+            patterns
+                .iter()
+                .any(|pattern| gitlab_glob_match(pattern, file.as_ref()))
+        })
+    };
+    if let Some(rules) = &job.rules {
+        // The first rule that applies decides; without one the job isn't added
+        return rules
+            .iter()
+            .find(|rule| {
+                rule.changes
+                    .as_ref()
+                    .is_none_or(|changes| changed(changes.paths()))
+            })
+            .is_some_and(|rule| rule.when.as_deref() != Some("never"));
+    }
+    if let Some(Only::Complex {
+        changes: Some(patterns),
+        ..
+    }) = &job.only
+    {
+        if !changed(patterns) {
+            return false;
+        }
+    }
+    if let Some(Except::Complex {
+        changes: Some(patterns),
+        ..
+    }) = &job.except
+    {
+        if changed(patterns) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Files changed since the merge base of HEAD and `base`, committed or not, relative
+/// to the repository root: what a push of the work on top of `base` would change
+pub fn changed_since(base: &str) -> Result<Vec<String>, String> {
+    let git = |args: &[&str]| -> Result<String, String> {
+        let output = Command::new("git")
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run git: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+    let merge_base = git(&["merge-base", base, "HEAD"])?;
+    let mut files: Vec<String> = git(&["diff", "--name-only", merge_base.trim()])?
+        .lines()
+        .chain(git(&["ls-files", "--others", "--exclude-standard", "--full-name"])?.lines())
+        .map(str::to_string)
+        .collect();
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Match a file path against a GitHub filter pattern: `*` matches within a
+/// directory, `**` across directories, `?` and `+` repeat the character before them
+/// zero or one and one or more times, and `[...]` matches a character class
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    glob_regex(pattern, false).is_some_and(|regex| regex.is_match(path))
+}
+
+/// Match a file path against a GitLab `changes:` pattern, where unlike GitHub's `?`
+/// is any one character and `{a,b}` either alternative
+pub fn gitlab_glob_match(pattern: &str, path: &str) -> bool {
+    glob_regex(pattern, true).is_some_and(|regex| regex.is_match(path))
+}
+
+fn glob_regex(pattern: &str, gitlab: bool) -> Option<Regex> {
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    let mut braces = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' if gitlab => regex.push_str("[^/]"),
+            '?' | '+' if !gitlab => regex.push(c),
+            '{' if gitlab => {
+                braces += 1;
+                regex.push_str("(?:");
+            }
+            ',' if gitlab && braces > 0 => regex.push('|'),
+            '}' if gitlab && braces > 0 => {
+                braces -= 1;
+                regex.push(')');
+            }
+            '[' => {
+                regex.push('[');
+                for c in chars.by_ref() {
+                    if c == '\\' {
+                        regex.push_str("\\\\");
+                        continue;
+                    }
+                    regex.push(c);
+                    if c == ']' {
+                        break;
+                    }
+                }
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.md", "README.md"));
+        assert!(!glob_match("*.md", "docs/README.md"));
+        assert!(glob_match("**.md", "docs/README.md"));
+        assert!(glob_match("docs/**", "docs/a/b.txt"));
+        assert!(glob_match("**/src/**", "crates/x/src/lib.rs"));
+        assert!(glob_match("**/Cargo.toml", "Cargo.toml"));
+        assert!(glob_match("v1.[0-9]", "v1.7"));
+        assert!(glob_match("colou?r.txt", "color.txt"));
+        assert!(!glob_match("src/*.rs", "src/a/b.rs"));
+
+        assert!(gitlab_glob_match("**/*.{rb,js}", "app/models/user.rb"));
+        assert!(gitlab_glob_match("src/?.rs", "src/a.rs"));
+        assert!(!gitlab_glob_match("src/?.rs", "src/ab.rs"));
+    }
+
+    #[test]
+    fn test_gitlab_job_runs() {
+        let job = |yaml: &str| -> Job { serde_yaml::from_str(yaml).unwrap() };
+
+        let rules = job(
+            "script: [make]\nrules:\n  - changes: ['docs/**']\n    when: never\n  - changes:\n      paths: ['src/**/*.{c,h}']\n",
+        );
+        assert!(gitlab_job_runs(&rules, &["src/lib/a.c"]));
+        assert!(!gitlab_job_runs(&rules, &["docs/a.md", "src/a.c"]));
+        assert!(!gitlab_job_runs(&rules, &["README.md"]));
+
+        let only = job("script: [make]\nonly:\n  changes: ['Dockerfile']\n");
+        assert!(gitlab_job_runs(&only, &["Dockerfile"]));
+        assert!(!gitlab_job_runs(&only, &["src/a.c"]));
+
+        let except = job("script: [make]\nexcept:\n  changes: ['*.md']\n");
+        assert!(!gitlab_job_runs(&except, &["README.md"]));
+        assert!(gitlab_job_runs(&job("script: [make]\n"), &["README.md"]));
+    }
+
+    #[test]
+    fn test_path_filter() {
+        let on: Value = serde_yaml::from_str(
+            "push:\n  paths:\n    - 'src/**'\n    - '!src/**/*.md'\npull_request:\n  paths-ignore:\n    - 'docs/**'\n",
+        )
+        .unwrap();
+        let push = PathFilter::for_event(&on, "push").unwrap();
+        assert!(push.matches(&["src/lib.rs"]));
+        assert!(!push.matches(&["src/notes/readme.md"]));
+        assert!(!push.matches(&["README.md"]));
+        assert!(push.matches(&["README.md", "src/main.rs"]));
+
+        let pull_request = PathFilter::for_event(&on, "pull_request").unwrap();
+        assert!(!pull_request.matches(&["docs/a.md", "docs/b.md"]));
+        assert!(pull_request.matches(&["docs/a.md", "src/lib.rs"]));
+
+        assert!(PathFilter::for_event(&Value::String("push".to_string()), "push").is_none());
+    }
+
+    #[test]
+    fn test_ref_filter() {
+        let on: Value = serde_yaml::from_str(
+            "push:\n  branches: ['main', 'releases/**', '!releases/**-alpha']\npull_request:\n  branches-ignore: ['wip/*']\ncreate:\n  tags: ['v*']\n",
+        )
+        .unwrap();
+        let push = RefFilter::for_event(&on, "push").unwrap();
+        assert!(push.matches("refs/heads/main"));
+        assert!(push.matches("refs/heads/releases/1.0"));
+        assert!(!push.matches("refs/heads/releases/1.0-alpha"));
+        assert!(!push.matches("refs/heads/feature"));
+        assert!(!push.matches("refs/tags/v1.0"));
+
+        let pull_request = RefFilter::for_event(&on, "pull_request").unwrap();
+        assert!(pull_request.matches("refs/heads/main"));
+        assert!(!pull_request.matches("refs/heads/wip/x"));
+
+        let tags = RefFilter::for_event(&on, "create").unwrap();
+        assert!(tags.matches("refs/tags/v1.0"));
+        assert!(!tags.matches("refs/heads/main"));
+
+        let paths_only: Value = serde_yaml::from_str("push:\n  paths: ['src/**']\n").unwrap();
+        assert!(RefFilter::for_event(&paths_only, "push").is_none());
+    }
+}
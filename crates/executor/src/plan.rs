@@ -0,0 +1,753 @@
+//! Dry runs: what `wrkflw run` would do with a workflow, worked out without starting
+//! a runtime, pulling an image or running a step.
+
+use crate::action_cache::{self, ActionCache};
+use crate::checkout::CheckoutOptions;
+use crate::config::ProjectConfig;
+use crate::context::{self, RunContext};
+use crate::dependency;
+use crate::docker;
+use crate::engine::{self, ExecutionConfig, ExecutionError, RuntimeType};
+use crate::environment;
+use crate::filter::RunFilter;
+use crate::gitlab_vars::GitlabVariables;
+use crate::podman;
+use crate::runner::RunnerOs;
+use crate::substitution;
+use crate::triggers::{self, Downstream};
+use serde_yaml::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::path::Path;
+use wrkflw_parser::gitlab::{self, parse_pipeline};
+use wrkflw_parser::workflow::{parse_workflow, Job, RunDefaults, Step, WorkflowDefinition};
+use wrkflw_runtime::toolcache;
+use wrkflw_secrets::{SecretManager, SecretSubstitution};
+
+/// What running a workflow would do
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionPlan {
+    pub workflow: String,
+    /// Events the workflow is triggered by
+    pub triggers: Vec<String>,
+    /// The event a local run simulates
+    pub event: String,
+    /// Runtime the jobs would use, after any fallback
+    pub runtime: String,
+    /// Jobs in the order they run; the jobs of a stage run in parallel
+    pub stages: Vec<Vec<PlannedJob>>,
+    /// The GitLab stage each stage of a pipeline's jobs is in; none for workflows
+    pub stage_names: Vec<String>,
+    /// Images that would be pulled
+    pub images: BTreeSet<String>,
+    /// Secrets referenced but not available from any provider
+    pub unresolved_secrets: BTreeSet<String>,
+    pub notes: Vec<String>,
+}
+
+/// Whether a job would run
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobDisposition {
+    Run,
+    Skip(String),
+    Fail(String),
+}
+
+/// A job, or one combination of a matrix job
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedJob {
+    pub name: String,
+    /// Where it runs, e.g. `in node:16-buster-slim` or `on the host`
+    pub runner: String,
+    pub disposition: JobDisposition,
+    pub steps: Vec<PlannedStep>,
+}
+
+/// A step of a planned job
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedStep {
+    pub name: String,
+    /// `run (<shell>)`, the action used, or the reusable workflow called
+    pub kind: String,
+    /// The command to run, or how the action is handled
+    pub detail: String,
+}
+
+impl PlannedStep {
+    /// Whether the step is known to fail when run
+    pub fn would_fail(&self) -> bool {
+        self.detail.starts_with("fails: ")
+    }
+}
+
+/// Work out what running the workflow or GitLab pipeline at `path` would do
+pub async fn plan_workflow(
+    path: &Path,
+    config: &ExecutionConfig,
+) -> Result<ExecutionPlan, ExecutionError> {
+    context::scoped(plan(path, config)).await
+}
+
+async fn plan(path: &Path, config: &ExecutionConfig) -> Result<ExecutionPlan, ExecutionError> {
+    let placeholder_workspace = std::env::temp_dir();
+    let mut stage_names = Vec::new();
+    let mut held = HashMap::new();
+    let mut child_pipelines = HashMap::new();
+    let mut failing = HashMap::new();
+    let (workflow, stages, mut env_context) = if engine::is_gitlab_pipeline(path) {
+        let pipeline = parse_pipeline(path).map_err(|e| {
+            ExecutionError::Parse(format!("Failed to parse GitLab pipeline: {}", e))
+        })?;
+        let mut workflow = gitlab::convert_to_workflow_format(&pipeline);
+        let stages = engine::resolve_gitlab_dependencies(&pipeline)?;
+        stage_names = stages
+            .iter()
+            .map(|batch| crate::stages::stage_of(&pipeline.jobs[&batch[0]]).to_string())
+            .collect();
+        for (name, job) in &pipeline.jobs {
+            if let Some(reason) = crate::stages::skip_reason(job, false, config.run_manual) {
+                held.insert(name.clone(), reason.to_string());
+            }
+            let Some(trigger) = &job.trigger else {
+                continue;
+            };
+            let project_dir = std::env::current_dir().unwrap_or_default();
+            match triggers::downstream(name, trigger, &project_dir) {
+                Ok(Downstream::Child(files)) => {
+                    let files: Vec<String> = files
+                        .iter()
+                        .map(|file| {
+                            file.strip_prefix(&project_dir)
+                                .unwrap_or(file)
+                                .display()
+                                .to_string()
+                        })
+                        .collect();
+                    child_pipelines.insert(name.clone(), files.join(", "));
+                }
+                Ok(Downstream::Project(project)) => {
+                    held.entry(name.clone()).or_insert_with(|| {
+                        format!(
+                            "it triggers a pipeline of project {}, which doesn't run locally",
+                            project
+                        )
+                    });
+                }
+                Err(e) => {
+                    failing.insert(name.clone(), e);
+                }
+            }
+        }
+        let project_dir = std::env::current_dir().map_err(|e| {
+            ExecutionError::Execution(format!("Failed to get current directory: {}", e))
+        })?;
+        let variables = GitlabVariables::load(&project_dir, config.gitlab_vars.clone())
+            .map_err(ExecutionError::Execution)?;
+        let env_context = engine::create_gitlab_context(
+            &pipeline,
+            path,
+            config.event.as_deref(),
+            &placeholder_workspace,
+            &variables,
+        );
+        variables.apply_to_jobs(&pipeline, &env_context, &mut workflow);
+        (workflow, stages, env_context)
+    } else {
+        let workflow = parse_workflow(path)?;
+        let stages = dependency::resolve_dependencies(&workflow)?;
+        let env_context = environment::create_github_context(
+            &workflow,
+            path,
+            config.event.as_deref(),
+            &placeholder_workspace,
+        );
+        (workflow, stages, env_context)
+    };
+
+    config
+        .filter
+        .check(&workflow)
+        .map_err(ExecutionError::Execution)?;
+
+    let mut notes = Vec::new();
+    let available = match config.runtime_type {
+        RuntimeType::Docker => docker::is_available(),
+        RuntimeType::Podman => podman::is_available(),
+        _ => true,
+    };
+    let runtime_type = if available {
+        config.runtime_type.clone()
+    } else {
+        notes.push(format!(
+            "{:?} is not available; the run would fall back to emulation",
+            config.runtime_type
+        ));
+        RuntimeType::Emulation
+    };
+    env_context.insert(
+        "WRKFLW_RUNTIME_MODE".to_string(),
+        runtime_type.mode().to_string(),
+    );
+    if let Some(event) = &config.event {
+        env_context.insert("GITHUB_EVENT_NAME".to_string(), event.clone());
+    }
+    let project_dir = std::env::current_dir().map_err(|e| {
+        ExecutionError::Execution(format!("Failed to get current directory: {}", e))
+    })?;
+    let project_config = ProjectConfig::load(&project_dir).map_err(ExecutionError::Execution)?;
+    context::set(RunContext {
+        runners: project_config.runners,
+        runner_os_policy: config.runner_os_policy.clone(),
+        offline: config.offline,
+        ..Default::default()
+    });
+
+    let secret_manager = match &config.secrets_config {
+        Some(secrets_config) => SecretManager::new(secrets_config.clone()).await,
+        None => SecretManager::default().await,
+    };
+    let secret_manager = match secret_manager {
+        Ok(manager) => Some(manager),
+        Err(e) => {
+            notes.push(format!("Secrets can't be looked up: {}", e));
+            None
+        }
+    };
+
+    let mut planner = Planner {
+        workflow: &workflow,
+        env_context: &env_context,
+        project_dir: &project_dir,
+        filter: &config.filter,
+        held,
+        child_pipelines,
+        failing,
+        images: BTreeSet::new(),
+        secret_refs: BTreeSet::new(),
+    };
+    let stages = stages
+        .iter()
+        .map(|stage| {
+            let mut names = stage.clone();
+            names.sort();
+            names
+                .iter()
+                .flat_map(|name| planner.plan_job(name))
+                .collect()
+        })
+        .collect();
+
+    let mut unresolved_secrets = BTreeSet::new();
+    for (provider, name) in &planner.secret_refs {
+        let found = match (&secret_manager, provider) {
+            (None, _) => false,
+            (Some(manager), Some(provider)) => manager
+                .get_secret_from_provider(provider, name)
+                .await
+                .is_ok(),
+            (Some(manager), None) => manager.get_secret(name).await.is_ok(),
+        };
+        if !found {
+            unresolved_secrets.insert(match provider {
+                Some(provider) => format!("{}:{}", provider, name),
+                None => name.clone(),
+            });
+        }
+    }
+
+    Ok(ExecutionPlan {
+        workflow: workflow.name.clone(),
+        triggers: workflow.on.clone(),
+        event: env_context
+            .get("GITHUB_EVENT_NAME")
+            .cloned()
+            .unwrap_or_default(),
+        runtime: format!("{:?}", runtime_type),
+        stages,
+        stage_names,
+        images: planner.images,
+        unresolved_secrets,
+        notes,
+    })
+}
+
+struct Planner<'a> {
+    workflow: &'a WorkflowDefinition,
+    env_context: &'a HashMap<String, String>,
+    project_dir: &'a Path,
+    filter: &'a RunFilter,
+    /// Jobs that don't run for reasons of their own, e.g. GitLab manual jobs
+    held: HashMap<String, String>,
+    /// GitLab trigger jobs running a child pipeline, with its files
+    child_pipelines: HashMap<String, String>,
+    /// Jobs known to fail, with why
+    failing: HashMap<String, String>,
+    images: BTreeSet<String>,
+    /// Provider (if named) and name of every referenced secret
+    secret_refs: BTreeSet<(Option<String>, String)>,
+}
+
+impl Planner<'_> {
+    fn plan_job(&mut self, job_name: &str) -> Vec<PlannedJob> {
+        let Some(job) = self.workflow.jobs.get(job_name) else {
+            return Vec::new();
+        };
+        let planned = |disposition, steps| PlannedJob {
+            name: job_name.to_string(),
+            runner: String::new(),
+            disposition,
+            steps,
+        };
+
+        if let Some(reason) = self
+            .filter
+            .skip_reason(job_name, self.workflow)
+            .or_else(|| self.held.get(job_name).map(String::as_str))
+        {
+            return vec![planned(
+                JobDisposition::Skip(format!("Skipped: {}", reason)),
+                Vec::new(),
+            )];
+        }
+        if let Some(reason) = self.failing.get(job_name) {
+            return vec![planned(JobDisposition::Fail(reason.clone()), Vec::new())];
+        }
+        if let Some(files) = self.child_pipelines.get(job_name) {
+            let step = PlannedStep {
+                name: "Trigger child pipeline".to_string(),
+                kind: "child pipeline".to_string(),
+                detail: files.clone(),
+            };
+            return vec![planned(JobDisposition::Run, vec![step])];
+        }
+        if let Some(condition) = &job.if_condition {
+            if !engine::evaluate_job_condition(condition, self.env_context, self.workflow) {
+                return vec![planned(
+                    JobDisposition::Skip(format!("Skipped: `if: {}` is false", condition)),
+                    Vec::new(),
+                )];
+            }
+        }
+        if let Some(uses) = &job.uses {
+            let step = self.plan_reusable_workflow(uses);
+            return vec![planned(JobDisposition::Run, vec![step])];
+        }
+
+        let Some(matrix) = job.matrix_config() else {
+            return vec![self.plan_job_run(job_name, job, None)];
+        };
+        match wrkflw_matrix::expand_matrix(&matrix) {
+            Ok(combinations) => combinations
+                .iter()
+                .map(|combination| {
+                    let name = wrkflw_matrix::format_combination_name(job_name, combination);
+                    self.plan_job_run(&name, job, Some(&combination.values))
+                })
+                .collect(),
+            Err(e) => vec![planned(
+                JobDisposition::Fail(format!("Failed to expand matrix: {}", e)),
+                Vec::new(),
+            )],
+        }
+    }
+
+    fn plan_job_run(
+        &mut self,
+        name: &str,
+        job: &Job,
+        matrix: Option<&HashMap<String, Value>>,
+    ) -> PlannedJob {
+        let mut job_env = self.env_context.clone();
+        job_env.extend(job.env.clone());
+        let runs_on: Vec<String> = job
+            .runs_on
+            .iter()
+            .flatten()
+            .map(|label| match matrix {
+                Some(matrix) => substitution::preprocess_command(label, matrix),
+                None => label.clone(),
+            })
+            .collect();
+
+        let runner = match engine::resolve_runner(name, &runs_on, &mut job_env) {
+            Ok(runner) => runner,
+            Err(result) => {
+                let disposition = match result.status {
+                    engine::JobStatus::Skipped => JobDisposition::Skip(result.logs),
+                    _ => JobDisposition::Fail(result.logs),
+                };
+                return PlannedJob {
+                    name: name.to_string(),
+                    runner: String::new(),
+                    disposition,
+                    steps: Vec::new(),
+                };
+            }
+        };
+        if runner.native {
+            job_env.insert("WRKFLW_RUNTIME_MODE".to_string(), "emulation".to_string());
+        }
+        let in_containers = matches!(
+            job_env.get("WRKFLW_RUNTIME_MODE").map(String::as_str),
+            Some("docker" | "podman")
+        );
+        let runner_description = if in_containers {
+            self.images.insert(runner.image.clone());
+            for service in job.services.values() {
+                self.images.insert(service.image.clone());
+            }
+            format!("in {}", runner.image)
+        } else {
+            "on the host".to_string()
+        };
+
+        let run_defaults = self.workflow.run_defaults(job);
+        let filter = self.filter;
+        let steps = job
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(idx, step)| match filter.steps.skip_reason(step, idx) {
+                Some(reason) => PlannedStep {
+                    name: step
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| format!("Step {}", idx + 1)),
+                    kind: "skipped".to_string(),
+                    detail: reason.to_string(),
+                },
+                None => self.plan_step(idx, step, &job_env, &run_defaults, matrix, in_containers),
+            })
+            .collect();
+        PlannedJob {
+            name: name.to_string(),
+            runner: runner_description,
+            disposition: JobDisposition::Run,
+            steps,
+        }
+    }
+
+    fn plan_step(
+        &mut self,
+        idx: usize,
+        step: &Step,
+        job_env: &HashMap<String, String>,
+        run_defaults: &RunDefaults,
+        matrix: Option<&HashMap<String, Value>>,
+        in_containers: bool,
+    ) -> PlannedStep {
+        let resolve = |text: &str| match matrix {
+            Some(matrix) => substitution::preprocess_command(text, matrix),
+            None => text.to_string(),
+        };
+        let inputs: HashMap<String, String> = step
+            .with
+            .iter()
+            .flatten()
+            .map(|(key, value)| (key.clone(), resolve(value)))
+            .collect();
+        for text in step
+            .env
+            .values()
+            .chain(inputs.values())
+            .chain(step.run.iter())
+        {
+            self.note_secrets(text);
+        }
+        let name = step
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("Step {}", idx + 1));
+
+        if let Some(uses) = &step.uses {
+            let detail = self.plan_action(uses, &inputs, job_env, in_containers);
+            return PlannedStep {
+                name,
+                kind: uses.clone(),
+                detail,
+            };
+        }
+        let Some(run) = &step.run else {
+            return PlannedStep {
+                name,
+                kind: "nothing".to_string(),
+                detail: "Step has neither 'uses' nor 'run'".to_string(),
+            };
+        };
+
+        let windows =
+            job_env.get("RUNNER_OS").map(String::as_str) == Some(RunnerOs::Windows.name());
+        let shell = step
+            .shell
+            .as_deref()
+            .or(run_defaults.shell.as_deref())
+            .unwrap_or(if windows { "pwsh" } else { "bash" });
+        let mut kind = format!("run ({})", shell);
+        if let Some(dir) = step
+            .working_directory
+            .as_deref()
+            .or(run_defaults.working_directory.as_deref())
+        {
+            kind.push_str(&format!(" in {}", dir));
+        }
+        PlannedStep {
+            name,
+            kind,
+            detail: resolve(run).trim_end().to_string(),
+        }
+    }
+
+    /// How a `uses:` step would be handled, mirroring `execute_step`
+    fn plan_action(
+        &mut self,
+        uses: &str,
+        inputs: &HashMap<String, String>,
+        job_env: &HashMap<String, String>,
+        in_containers: bool,
+    ) -> String {
+        let action = self.workflow.resolve_action(uses);
+        if uses.starts_with("actions/checkout") {
+            let options = match CheckoutOptions::from_inputs(inputs) {
+                Ok(options) => options,
+                Err(e) => return format!("fails: {}", e),
+            };
+            let own = job_env
+                .get("GITHUB_REPOSITORY")
+                .map(String::as_str)
+                .unwrap_or_default();
+            let into = match &options.path {
+                Some(path) => format!(" into {}", path.display()),
+                None => String::new(),
+            };
+            let at = options.git_ref.as_deref().unwrap_or("the current branch");
+            return if !in_containers && options.is_working_tree(own) {
+                "uses the project directory as the workspace".to_string()
+            } else if options.is_own_repository(own) {
+                format!("checks out {} of the project{}", at, into)
+            } else if action_cache::is_offline() {
+                "fails: other repositories can't be checked out offline".to_string()
+            } else {
+                let repository = options.repository.as_deref().unwrap_or_default();
+                format!("clones {} at {}{}", repository, at, into)
+            };
+        }
+        if let Some(tool) = engine::installable_tool(uses, job_env) {
+            return match toolcache::requested_version(tool, inputs, self.project_dir) {
+                Ok(Some(version)) => {
+                    format!("installs {} {} into the tool cache", tool.name(), version)
+                }
+                Ok(None) => format!("uses the host's {}", tool.command()),
+                Err(e) => format!("fails: {}", e),
+            };
+        }
+        if action.is_docker {
+            let image = action
+                .repository
+                .trim_start_matches("docker://")
+                .to_string();
+            self.images.insert(image.clone());
+            return format!("runs {}", image);
+        }
+        if action.is_local {
+            let dir = self.project_dir.join(&action.repository);
+            if dir.join("Dockerfile").exists() {
+                return format!("builds and runs the Dockerfile in {}", action.repository);
+            }
+            let composite = ["action.yml", "action.yaml"].iter().any(|file| {
+                std::fs::read_to_string(dir.join(file))
+                    .ok()
+                    .and_then(|content| serde_yaml::from_str::<Value>(&content).ok())
+                    .and_then(|action| action["runs"]["using"].as_str().map(str::to_string))
+                    .is_some_and(|using| using == "composite")
+            });
+            return if composite {
+                format!("runs the composite action in {}", action.repository)
+            } else if dir.exists() {
+                format!("emulates the local action in {}", action.repository)
+            } else {
+                format!("fails: {} does not exist", action.repository)
+            };
+        }
+        if in_containers {
+            self.images
+                .insert(engine::determine_action_image(&action.repository));
+        }
+        if uses.starts_with("actions-rs/toolchain") || uses.starts_with("dtolnay/rust-toolchain") {
+            return "uses the host's Rust toolchain".to_string();
+        }
+        match inputs.get("command") {
+            Some(command) => format!("runs the `{}` command it is given", command),
+            None => "emulated; the action itself is not run".to_string(),
+        }
+    }
+
+    fn plan_reusable_workflow(&mut self, uses: &str) -> PlannedStep {
+        let detail = if uses.starts_with("./") || uses.starts_with('/') {
+            if self.project_dir.join(uses).exists() {
+                "runs the local workflow".to_string()
+            } else {
+                format!("fails: {} does not exist", uses)
+            }
+        } else {
+            match action_cache::parse_workflow_uses(uses) {
+                None => "fails: invalid reusable workflow reference".to_string(),
+                Some((repo_ref, _)) => {
+                    let cached = ActionCache::new(ActionCache::default_root()).lookup(&repo_ref);
+                    match (cached, action_cache::is_offline()) {
+                        (Some(_), _) => format!("runs {} from the action cache", repo_ref),
+                        (None, true) => format!("fails: {} is not cached", repo_ref),
+                        (None, false) => format!("fetches and runs {}", repo_ref),
+                    }
+                }
+            }
+        };
+        PlannedStep {
+            name: format!("Run reusable workflow: {}", uses),
+            kind: "reusable workflow".to_string(),
+            detail,
+        }
+    }
+
+    fn note_secrets(&mut self, text: &str) {
+        for secret in SecretSubstitution::extract_secret_refs(text) {
+            // Provided separately, see `environment::resolve_github_token`
+            if secret.provider.is_none() && secret.name == "GITHUB_TOKEN" {
+                continue;
+            }
+            self.secret_refs.insert((secret.provider, secret.name));
+        }
+    }
+}
+
+impl fmt::Display for ExecutionPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "📋 Plan for '{}'", self.workflow)?;
+        writeln!(
+            f,
+            "   Triggered by: {} (simulating {})",
+            self.triggers.join(", "),
+            self.event
+        )?;
+        writeln!(f, "   Runtime: {}", self.runtime)?;
+        for note in &self.notes {
+            writeln!(f, "   ⚠️  {}", note)?;
+        }
+
+        for (idx, stage) in self.stages.iter().enumerate() {
+            match self.stage_names.get(idx) {
+                Some(name) => writeln!(f, "\nStage {} ({}):", idx + 1, name)?,
+                None => writeln!(f, "\nStage {}:", idx + 1)?,
+            }
+            for job in stage {
+                match &job.disposition {
+                    JobDisposition::Run if job.runner.is_empty() => {
+                        writeln!(f, "  ▶ {}", job.name)?
+                    }
+                    JobDisposition::Run => writeln!(f, "  ▶ {} ({})", job.name, job.runner)?,
+                    JobDisposition::Skip(reason) => writeln!(f, "  ⏭ {}: {}", job.name, reason)?,
+                    JobDisposition::Fail(reason) => {
+                        writeln!(f, "  ❌ {}: would fail, {}", job.name, reason)?
+                    }
+                }
+                for (idx, step) in job.steps.iter().enumerate() {
+                    writeln!(f, "      {}. {} [{}]", idx + 1, step.name, step.kind)?;
+                    for line in step.detail.lines() {
+                        writeln!(f, "           {}", line)?;
+                    }
+                }
+            }
+        }
+
+        if !self.images.is_empty() {
+            writeln!(f, "\nImages to pull:")?;
+            for image in &self.images {
+                writeln!(f, "  - {}", image)?;
+            }
+        }
+        if !self.unresolved_secrets.is_empty() {
+            writeln!(f, "\nUnresolved secrets:")?;
+            for secret in &self.unresolved_secrets {
+                writeln!(f, "  - {}", secret)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn config() -> ExecutionConfig {
+        ExecutionConfig {
+            runtime_type: RuntimeType::Emulation,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plan_workflow() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ci.yml");
+        fs::write(
+            &path,
+            r#"
+name: CI
+on: [push, pull_request]
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    strategy:
+      matrix:
+        node: [18, 20]
+    steps:
+      - uses: actions/setup-node@v4
+        with:
+          node-version: ${{ matrix.node }}
+      - name: Test
+        run: npm test -- --token ${{ secrets.WRKFLW_PLAN_TEST_MISSING }}
+  never:
+    if: false
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo never
+  deploy:
+    needs: test
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo ${{ secrets.GITHUB_TOKEN }}
+"#,
+        )
+        .unwrap();
+
+        let plan = plan_workflow(&path, &config()).await.unwrap();
+        assert_eq!(plan.triggers, vec!["push", "pull_request"]);
+        assert_eq!(plan.event, "push");
+        assert_eq!(plan.stages.len(), 2);
+
+        let first: Vec<&str> = plan.stages[0].iter().map(|job| job.name.as_str()).collect();
+        assert_eq!(first.len(), 3, "{:?}", first);
+        let never = plan.stages[0]
+            .iter()
+            .find(|job| job.name == "never")
+            .unwrap();
+        assert!(matches!(never.disposition, JobDisposition::Skip(_)));
+        let tests: Vec<&PlannedJob> = plan.stages[0]
+            .iter()
+            .filter(|job| job.name.starts_with("test"))
+            .collect();
+        assert_eq!(tests.len(), 2);
+        assert!(tests.iter().any(|job| job.steps[1].detail
+            == "npm test -- --token ${{ secrets.WRKFLW_PLAN_TEST_MISSING }}"));
+
+        assert!(plan.images.is_empty());
+        assert_eq!(
+            plan.unresolved_secrets,
+            BTreeSet::from(["WRKFLW_PLAN_TEST_MISSING".to_string()])
+        );
+        let text = plan.to_string();
+        assert!(text.contains("Stage 2:"), "{}", text);
+        assert!(text.contains("Unresolved secrets:"), "{}", text);
+    }
+}
@@ -1,3 +1,7 @@
+use crate::build;
+use crate::cancel;
+use crate::images::{ImageStore, PreparedImage, ENVIRONMENT_LABEL};
+use crate::pull::{self, PullPolicy, PullProgress};
 use async_trait::async_trait;
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
@@ -7,7 +11,11 @@ use std::sync::Mutex;
 use tempfile;
 use tokio::process::Command;
 use wrkflw_logging;
-use wrkflw_runtime::container::{ContainerError, ContainerOutput, ContainerRuntime};
+use wrkflw_runtime::container::{
+    BuildOptions, ContainerError, ContainerOutput, ContainerRuntime, NetworkMode,
+};
+use wrkflw_runtime::output::OutputCapture;
+use wrkflw_runtime::timeouts;
 use wrkflw_utils;
 use wrkflw_utils::fd;
 
@@ -17,8 +25,148 @@ static RUNNING_CONTAINERS: Lazy<Mutex<Vec<String>>> = Lazy::new(|| Mutex::new(Ve
 static CUSTOMIZED_IMAGES: Lazy<Mutex<HashMap<String, String>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+/// SELinux relabeling applied to bind mounts (`:z` or `:Z`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelinuxLabel {
+    /// `:z` - content is shared between containers
+    Shared,
+    /// `:Z` - content is private to the container
+    Private,
+    /// Never relabel, even when SELinux is enforcing
+    Disabled,
+}
+
+impl SelinuxLabel {
+    fn suffix(self) -> Option<&'static str> {
+        match self {
+            SelinuxLabel::Shared => Some("z"),
+            SelinuxLabel::Private => Some("Z"),
+            SelinuxLabel::Disabled => None,
+        }
+    }
+}
+
+/// Options passed to `podman run` for rootless and SELinux-enforcing hosts
+#[derive(Debug, Clone, Default)]
+pub struct PodmanOptions {
+    /// Value for `--userns` (e.g. `keep-id`, `host`, `auto`)
+    pub userns: Option<String>,
+    /// Values for repeated `--security-opt` flags
+    pub security_opts: Vec<String>,
+    /// Volume relabeling; when unset, `:z` is used if SELinux is enforcing
+    pub volume_label: Option<SelinuxLabel>,
+    /// CPU limit passed as `--cpus`
+    pub cpus: Option<f64>,
+    /// Memory limit passed as `--memory` (e.g. `512m`, `2g`)
+    pub memory: Option<String>,
+    /// Process limit passed as `--pids-limit`
+    pub pids_limit: Option<i64>,
+    /// Network mode passed as `--network` (omitted for the default bridge)
+    pub network_mode: NetworkMode,
+    /// When images are pulled, before `podman run`
+    pub pull_policy: PullPolicy,
+    /// Bind mounts added to every job container, as for `podman run -v`
+    /// (`/host/path:/container/path[:ro]`), relabeled like the workspace
+    pub extra_mounts: Vec<String>,
+    /// Open a shell in the container of a failed step before the run goes on
+    pub debug_on_failure: bool,
+}
+
+impl PodmanOptions {
+    fn effective_volume_label(&self) -> Option<&'static str> {
+        match self.volume_label {
+            Some(label) => label.suffix(),
+            None if selinux_enforcing() => SelinuxLabel::Shared.suffix(),
+            None => None,
+        }
+    }
+
+    /// Arguments inserted into `podman run` before the image
+    fn run_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(userns) = &self.userns {
+            args.push(format!("--userns={}", userns));
+        }
+        for opt in &self.security_opts {
+            args.push("--security-opt".to_string());
+            args.push(opt.clone());
+        }
+        if let Some(cpus) = self.cpus {
+            args.push(format!("--cpus={}", cpus));
+        }
+        if let Some(memory) = &self.memory {
+            args.push(format!("--memory={}", memory));
+        }
+        if let Some(pids) = self.pids_limit {
+            args.push(format!("--pids-limit={}", pids));
+        }
+        if self.network_mode != NetworkMode::Bridge {
+            args.push(format!("--network={}", self.network_mode.as_str()));
+        }
+        for mount in &self.extra_mounts {
+            args.push("-v".to_string());
+            args.push(self.mount_arg(mount));
+        }
+        args
+    }
+
+    fn volume_arg(&self, host_path: &Path, container_path: &Path) -> String {
+        self.mount_arg(&format!(
+            "{}:{}",
+            host_path.to_string_lossy(),
+            container_path.to_string_lossy()
+        ))
+    }
+
+    /// A `-v` value with the volume label added to its options
+    fn mount_arg(&self, mount: &str) -> String {
+        let mut volume = mount.to_string();
+        if let Some(label) = self.effective_volume_label() {
+            // `host:container:ro` already has options, `host:container` starts them
+            volume.push(if mount.matches(':').count() >= 2 {
+                ','
+            } else {
+                ':'
+            });
+            volume.push_str(label);
+        }
+        volume
+    }
+}
+
+/// Whether the host has SELinux in enforcing mode
+fn selinux_enforcing() -> bool {
+    static ENFORCING: Lazy<bool> = Lazy::new(|| {
+        std::fs::read_to_string("/sys/fs/selinux/enforce")
+            .map(|s| s.trim() == "1")
+            .unwrap_or(false)
+    });
+    *ENFORCING
+}
+
+/// Read a pipe of a podman command to its end, as much of it as the output limit allows
+async fn capture(pipe: Option<impl tokio::io::AsyncRead + Unpin>) -> String {
+    use tokio::io::AsyncReadExt;
+    let mut capture = OutputCapture::new();
+    if let Some(mut pipe) = pipe {
+        let mut buffer = [0u8; 8192];
+        loop {
+            match pipe.read(&mut buffer).await {
+                Ok(0) => break,
+                Ok(n) => capture.push(&buffer[..n]),
+                Err(e) => {
+                    wrkflw_logging::debug(&format!("Failed to read podman output: {}", e));
+                    break;
+                }
+            }
+        }
+    }
+    capture.into_string()
+}
+
 pub struct PodmanRuntime {
     preserve_containers_on_failure: bool,
+    options: PodmanOptions,
 }
 
 impl PodmanRuntime {
@@ -27,6 +175,13 @@ impl PodmanRuntime {
     }
 
     pub fn new_with_config(preserve_containers_on_failure: bool) -> Result<Self, ContainerError> {
+        Self::new_with_options(preserve_containers_on_failure, PodmanOptions::default())
+    }
+
+    pub fn new_with_options(
+        preserve_containers_on_failure: bool,
+        options: PodmanOptions,
+    ) -> Result<Self, ContainerError> {
         // Check if podman command is available
         if !is_available() {
             return Err(ContainerError::ContainerStart(
@@ -36,6 +191,7 @@ impl PodmanRuntime {
 
         Ok(PodmanRuntime {
             preserve_containers_on_failure,
+            options,
         })
     }
 
@@ -140,9 +296,8 @@ impl PodmanRuntime {
         args: &[&str],
         input: Option<&str>,
     ) -> Result<ContainerOutput, ContainerError> {
-        let timeout_duration = std::time::Duration::from_secs(360); // 6 minutes timeout
-
-        let result = tokio::time::timeout(timeout_duration, async {
+        let limit = timeouts::timeouts().step();
+        let result = timeouts::within(limit, async {
             let mut cmd = Command::new("podman");
             cmd.args(args);
 
@@ -177,14 +332,20 @@ impl PodmanRuntime {
                 }
             }
 
-            let output = child.wait_with_output().await.map_err(|e| {
+            // Read both pipes as podman writes, keeping only what the output limit allows
+            let (stdout, stderr, status) = tokio::join!(
+                capture(child.stdout.take()),
+                capture(child.stderr.take()),
+                child.wait()
+            );
+            let status = status.map_err(|e| {
                 ContainerError::ContainerExecution(format!("Podman command failed: {}", e))
             })?;
 
             Ok(ContainerOutput {
-                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-                exit_code: output.status.code().unwrap_or(-1),
+                stdout,
+                stderr,
+                exit_code: status.code().unwrap_or(-1),
             })
         })
         .await;
@@ -192,7 +353,10 @@ impl PodmanRuntime {
         match result {
             Ok(output) => output,
             Err(_) => {
-                wrkflw_logging::error("Podman operation timed out after 360 seconds");
+                wrkflw_logging::error(&format!(
+                    "Podman command timed out after {} seconds",
+                    limit.unwrap_or_default().as_secs()
+                ));
                 Err(ContainerError::ContainerExecution(
                     "Operation timed out".to_string(),
                 ))
@@ -202,8 +366,8 @@ impl PodmanRuntime {
 }
 
 pub fn is_available() -> bool {
-    // Use a very short timeout for the entire availability check
-    let overall_timeout = std::time::Duration::from_secs(3);
+    // Use a short timeout for the entire availability check
+    let overall_timeout = timeouts::timeouts().availability();
 
     // Spawn a thread with the timeout to prevent blocking the main thread
     let handle = std::thread::spawn(move || {
@@ -315,7 +479,7 @@ pub fn is_available() -> bool {
     // Manual implementation of join with timeout
     let start = std::time::Instant::now();
 
-    while start.elapsed() < overall_timeout {
+    while overall_timeout.is_none_or(|limit| start.elapsed() < limit) {
         if handle.is_finished() {
             return match handle.join() {
                 Ok(result) => result,
@@ -334,11 +498,12 @@ pub fn is_available() -> bool {
     false
 }
 
-// Add container to tracking
+// Add container to tracking, also as one of the run going on
 pub fn track_container(id: &str) {
     if let Ok(mut containers) = RUNNING_CONTAINERS.lock() {
         containers.push(id.to_string());
     }
+    cancel::track_container(cancel::Engine::Podman, id);
 }
 
 // Remove container from tracking
@@ -346,6 +511,7 @@ pub fn untrack_container(id: &str) {
     if let Ok(mut containers) = RUNNING_CONTAINERS.lock() {
         containers.retain(|c| c != id);
     }
+    cancel::untrack_container(cancel::Engine::Podman, id);
 }
 
 // Clean up all tracked resources
@@ -387,17 +553,20 @@ pub async fn cleanup_containers() -> Result<(), String> {
             }
         };
 
-    if containers_to_cleanup.is_empty() {
-        return Ok(());
+    remove_containers(containers_to_cleanup).await;
+    Ok(())
+}
+
+// Stop and remove the given containers, like those of a single run
+pub async fn remove_containers(containers: Vec<String>) {
+    if containers.is_empty() {
+        return;
     }
 
-    wrkflw_logging::info(&format!(
-        "Cleaning up {} containers",
-        containers_to_cleanup.len()
-    ));
+    wrkflw_logging::info(&format!("Cleaning up {} containers", containers.len()));
 
     // Process each container with a timeout
-    for container_id in containers_to_cleanup {
+    for container_id in containers {
         // First try to stop the container
         let stop_result = tokio::time::timeout(
             std::time::Duration::from_millis(1000),
@@ -457,8 +626,6 @@ pub async fn cleanup_containers() -> Result<(), String> {
         // Always untrack the container whether or not we succeeded to avoid future cleanup attempts
         untrack_container(&container_id);
     }
-
-    Ok(())
 }
 
 #[async_trait]
@@ -474,18 +641,26 @@ impl ContainerRuntime for PodmanRuntime {
         // Print detailed debugging info
         wrkflw_logging::info(&format!("Podman: Running container with image: {}", image));
 
-        let timeout_duration = std::time::Duration::from_secs(360); // 6 minutes timeout
-
         // Run the entire container operation with a timeout
-        match tokio::time::timeout(
-            timeout_duration,
+        let limit = timeouts::timeouts().step();
+        match timeouts::within(
+            limit,
             self.run_container_inner(image, cmd, env_vars, working_dir, volumes),
         )
         .await
         {
-            Ok(result) => result,
+            Ok(result) => {
+                let (output, failed_container) = result?;
+                if let Some(container) = failed_container {
+                    self.debug_failed_container(&container, working_dir).await;
+                }
+                Ok(output)
+            }
             Err(_) => {
-                wrkflw_logging::error("Podman operation timed out after 360 seconds");
+                wrkflw_logging::error(&format!(
+                    "Podman operation timed out after {} seconds",
+                    limit.unwrap_or_default().as_secs()
+                ));
                 Err(ContainerError::ContainerExecution(
                     "Operation timed out".to_string(),
                 ))
@@ -494,33 +669,33 @@ impl ContainerRuntime for PodmanRuntime {
     }
 
     async fn pull_image(&self, image: &str) -> Result<(), ContainerError> {
-        // Add a timeout for pull operations
-        let timeout_duration = std::time::Duration::from_secs(30);
-
-        match tokio::time::timeout(timeout_duration, self.pull_image_inner(image)).await {
-            Ok(result) => result,
-            Err(_) => {
-                wrkflw_logging::warning(&format!(
-                    "Pull of image {} timed out, continuing with existing image",
-                    image
-                ));
-                // Return success to allow continuing with existing image
-                Ok(())
-            }
-        }
+        let present = self
+            .execute_podman_command(&["image", "exists", image], None)
+            .await
+            .is_ok_and(|output| output.exit_code == 0);
+        pull::ensure_image(
+            self.options.pull_policy,
+            image,
+            present,
+            self.pull_image_inner(image),
+        )
+        .await
     }
 
-    async fn build_image(&self, dockerfile: &Path, tag: &str) -> Result<(), ContainerError> {
-        // Add a timeout for build operations
-        let timeout_duration = std::time::Duration::from_secs(120); // 2 minutes timeout for builds
-
-        match tokio::time::timeout(timeout_duration, self.build_image_inner(dockerfile, tag)).await
-        {
+    async fn build_image(
+        &self,
+        dockerfile: &Path,
+        tag: &str,
+        options: &BuildOptions,
+    ) -> Result<(), ContainerError> {
+        let limit = timeouts::timeouts().build();
+        match timeouts::within(limit, self.build_image_inner(dockerfile, tag, options)).await {
             Ok(result) => result,
             Err(_) => {
                 wrkflw_logging::error(&format!(
-                    "Building image {} timed out after 120 seconds",
-                    tag
+                    "Building image {} timed out after {} seconds",
+                    tag,
+                    limit.unwrap_or_default().as_secs()
                 ));
                 Err(ContainerError::ImageBuild(
                     "Operation timed out".to_string(),
@@ -535,10 +710,30 @@ impl ContainerRuntime for PodmanRuntime {
         version: Option<&str>,
         additional_packages: Option<Vec<String>>,
     ) -> Result<String, ContainerError> {
-        // Check if we already have a customized image for this language and version
-        let key = format!("{}-{}", language, version.unwrap_or("latest"));
-        if let Some(customized_image) = Self::get_language_specific_image("", language, version) {
-            return Ok(customized_image);
+        // Reuse the image built for this environment by an earlier run, if still there
+        let store = ImageStore::new(ImageStore::default_file());
+        let image = PreparedImage::new(
+            "podman",
+            language,
+            version,
+            additional_packages.as_deref().unwrap_or_default(),
+        );
+        if let Some(prepared) = store.lookup(&image) {
+            if self
+                .execute_podman_command(&["image", "exists", &prepared.tag], None)
+                .await
+                .is_ok_and(|output| output.exit_code == 0)
+            {
+                wrkflw_logging::info(&format!(
+                    "Reusing image {} for {}",
+                    prepared.tag,
+                    prepared.environment()
+                ));
+                if let Err(e) = store.record(&prepared) {
+                    wrkflw_logging::warning(&e);
+                }
+                return Ok(prepared.tag);
+            }
         }
 
         // Create a temporary Dockerfile for customization
@@ -654,13 +849,20 @@ impl ContainerRuntime for PodmanRuntime {
         })?;
 
         // Build the customized image
-        let image_tag = format!("wrkflw-{}-{}", language, version.unwrap_or("latest"));
-        self.build_image(&dockerfile_path, &image_tag).await?;
+        let options = BuildOptions {
+            labels: [(ENVIRONMENT_LABEL.to_string(), image.environment())].into(),
+            ..Default::default()
+        };
+        self.build_image(&dockerfile_path, &image.tag, &options)
+            .await?;
 
         // Store the customized image
-        Self::set_language_specific_image("", language, version, &image_tag);
+        Self::set_language_specific_image("", language, version, &image.tag);
+        if let Err(e) = store.record(&image) {
+            wrkflw_logging::warning(&e);
+        }
 
-        Ok(image_tag)
+        Ok(image.tag)
     }
 }
 
@@ -673,7 +875,7 @@ impl PodmanRuntime {
         env_vars: &[(&str, &str)],
         working_dir: &Path,
         volumes: &[(&Path, &Path)],
-    ) -> Result<ContainerOutput, ContainerError> {
+    ) -> Result<(ContainerOutput, Option<String>), ContainerError> {
         wrkflw_logging::debug(&format!("Running command in Podman: {:?}", cmd));
         wrkflw_logging::debug(&format!("Environment: {:?}", env_vars));
         wrkflw_logging::debug(&format!("Working directory: {}", working_dir.display()));
@@ -693,18 +895,26 @@ impl PodmanRuntime {
 
         // Prepare volume mount strings
         for (host_path, container_path) in volumes {
-            volume_strings.push(format!(
-                "{}:{}",
-                host_path.to_string_lossy(),
-                container_path.to_string_lossy()
-            ));
+            volume_strings.push(self.options.volume_arg(host_path, container_path));
         }
-
-        let mut args = vec!["run", "--name", &container_name, "-w", &working_dir_str];
+        let option_strings = self.options.run_args();
+
+        // Pulled above, with progress, as the pull policy says
+        self.pull_image(image).await?;
+        let mut args = vec![
+            "run",
+            "--pull=never",
+            "--name",
+            &container_name,
+            "-w",
+            &working_dir_str,
+        ];
 
         // Only use --rm if we don't want to preserve containers on failure
-        // When preserve_containers_on_failure is true, we skip --rm so failed containers remain
-        if !self.preserve_containers_on_failure {
+        // When preserve_containers_on_failure is true, we skip --rm so failed containers remain,
+        // as we do for failed containers to open a debug shell in
+        let keep = self.preserve_containers_on_failure || self.options.debug_on_failure;
+        if !keep {
             args.insert(1, "--rm"); // Insert after "run"
         }
 
@@ -720,6 +930,11 @@ impl PodmanRuntime {
             args.push(volume_string);
         }
 
+        // Add userns, security and resource options
+        for option in &option_strings {
+            args.push(option);
+        }
+
         // Add the image
         args.push(image);
 
@@ -737,7 +952,7 @@ impl PodmanRuntime {
             Ok(output) => {
                 if output.exit_code == 0 {
                     // Success - always clean up successful containers
-                    if self.preserve_containers_on_failure {
+                    if keep {
                         // We didn't use --rm, so manually remove successful container
                         let cleanup_result = tokio::time::timeout(
                             std::time::Duration::from_millis(1000),
@@ -768,7 +983,9 @@ impl PodmanRuntime {
                     untrack_container(&container_name);
                 } else {
                     // Failed container
-                    if self.preserve_containers_on_failure {
+                    if self.options.debug_on_failure {
+                        // Cleaned up once the debug shell exits
+                    } else if self.preserve_containers_on_failure {
                         // Failed and we want to preserve - don't clean up but untrack from auto-cleanup
                         wrkflw_logging::info(&format!(
                             "Preserving failed container {} for debugging (exit code: {}). Use 'podman exec -it {} bash' to inspect.",
@@ -783,7 +1000,7 @@ impl PodmanRuntime {
             }
             Err(_) => {
                 // Command failed to execute properly - clean up if container exists and not preserving
-                if !self.preserve_containers_on_failure {
+                if !keep {
                     // Container was created with --rm, so it should be auto-removed
                     untrack_container(&container_name);
                 } else {
@@ -830,39 +1047,114 @@ impl PodmanRuntime {
             }
         }
 
-        result
+        result.map(|output| {
+            let debug = output.exit_code != 0 && self.options.debug_on_failure;
+            (output, debug.then_some(container_name))
+        })
+    }
+
+    /// Open a debug shell in a failed step's container, then remove it unless
+    /// it is to be preserved
+    async fn debug_failed_container(&self, container: &str, working_dir: &Path) {
+        crate::debug_shell::open("podman", container, working_dir, self.options.network_mode).await;
+        if self.preserve_containers_on_failure {
+            wrkflw_logging::info(&format!(
+                "Preserving failed container {} for debugging",
+                container
+            ));
+        } else {
+            let _ = tokio::time::timeout(
+                std::time::Duration::from_secs(10),
+                Command::new("podman")
+                    .args(["rm", "-f", container])
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .output(),
+            )
+            .await;
+        }
+        untrack_container(container);
     }
 
     async fn pull_image_inner(&self, image: &str) -> Result<(), ContainerError> {
-        let args = vec!["pull", image];
-        let output = self.execute_podman_command(&args, None).await?;
+        use tokio::io::{AsyncBufReadExt, BufReader};
+
+        let started = std::time::Instant::now();
+        let mut child = Command::new("podman")
+            .args(["pull", image])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                ContainerError::ImagePull(format!("Failed to spawn podman pull: {}", e))
+            })?;
 
-        if output.exit_code != 0 {
+        // Podman reports each layer as `Copying blob <digest>`, then again with
+        // `done` or `skipped` after it when it isn't writing to a terminal
+        let mut progress = PullProgress::new(image);
+        let mut messages = Vec::new();
+        if let Some(stderr) = child.stderr.take() {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let Some(blob) = line.trim().strip_prefix("Copying blob ") else {
+                    messages.push(line);
+                    continue;
+                };
+                let digest = blob.split_whitespace().next().unwrap_or_default();
+                let digest = digest.trim_start_matches("sha256:");
+                let layer = &digest[..digest.len().min(12)];
+                if blob.contains(" done") || blob.contains(" skipped") {
+                    progress.layer_done(layer);
+                } else {
+                    progress.layer(layer);
+                }
+            }
+        }
+        let status = child.wait().await;
+        crate::timing::record_image_pull(started.elapsed());
+        let status =
+            status.map_err(|e| ContainerError::ImagePull(format!("Podman pull failed: {}", e)))?;
+
+        if !status.success() {
             return Err(ContainerError::ImagePull(format!(
                 "Failed to pull image {}: {}",
-                image, output.stderr
+                image,
+                messages.join("\n")
             )));
         }
-
+        progress.finish();
         Ok(())
     }
 
-    async fn build_image_inner(&self, dockerfile: &Path, tag: &str) -> Result<(), ContainerError> {
-        let context_dir = dockerfile.parent().unwrap_or(Path::new("."));
-        let dockerfile_str = dockerfile.to_string_lossy().to_string();
-        let context_dir_str = context_dir.to_string_lossy().to_string();
-        let args = vec!["build", "-f", &dockerfile_str, "-t", tag, &context_dir_str];
-
-        let output = self.execute_podman_command(&args, None).await?;
-
-        if output.exit_code != 0 {
-            return Err(ContainerError::ImageBuild(format!(
-                "Failed to build image {}: {}",
-                tag, output.stderr
-            )));
-        }
+    async fn build_image_inner(
+        &self,
+        dockerfile: &Path,
+        tag: &str,
+        options: &BuildOptions,
+    ) -> Result<(), ContainerError> {
+        // Buildah, behind `podman build`, keeps the layers of each step for later builds
+        let mut cmd = Command::new("podman");
+        cmd.args(["build", "--layers"])
+            .args(build::build_args(dockerfile, tag, options));
+        build::run_build(cmd, tag).await
+    }
+}
 
+/// Remove an image from Podman; one already gone counts as removed
+pub async fn remove_image(tag: &str) -> Result<(), String> {
+    let output = Command::new("podman")
+        .args(["rmi", tag])
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run podman: {}", e))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if output.status.success() || stderr.contains("image not known") {
         Ok(())
+    } else {
+        Err(format!("Failed to remove image {}: {}", tag, stderr.trim()))
     }
 }
 
@@ -875,3 +1167,56 @@ pub fn get_tracked_containers() -> Vec<String> {
         vec![]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_args_include_userns_security_and_limits() {
+        let options = PodmanOptions {
+            userns: Some("keep-id".to_string()),
+            security_opts: vec!["label=disable".to_string()],
+            cpus: Some(1.5),
+            memory: Some("512m".to_string()),
+            pids_limit: Some(256),
+            network_mode: NetworkMode::None,
+            ..Default::default()
+        };
+        assert_eq!(
+            options.run_args(),
+            vec![
+                "--userns=keep-id",
+                "--security-opt",
+                "label=disable",
+                "--cpus=1.5",
+                "--memory=512m",
+                "--pids-limit=256",
+                "--network=none"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_volume_arg_applies_selinux_label() {
+        let host = Path::new("/tmp/ws");
+        let container = Path::new("/github/workspace");
+        let mut options = PodmanOptions {
+            volume_label: Some(SelinuxLabel::Private),
+            ..Default::default()
+        };
+        assert_eq!(
+            options.volume_arg(host, container),
+            "/tmp/ws:/github/workspace:Z"
+        );
+        assert_eq!(
+            options.mount_arg("/srv/cache:/cache:ro"),
+            "/srv/cache:/cache:ro,Z"
+        );
+        options.volume_label = Some(SelinuxLabel::Disabled);
+        assert_eq!(
+            options.volume_arg(host, container),
+            "/tmp/ws:/github/workspace"
+        );
+    }
+}
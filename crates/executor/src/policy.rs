@@ -0,0 +1,545 @@
+//! Which actions and reusable workflows a project may use, from the `[actions]`
+//! section of `.wrkflw.toml`:
+//!
+//! ```toml
+//! [actions]
+//! # Only these may be used...
+//! allow = ["actions/*", "docker/*", "my-org/*"]
+//! # ...except these
+//! deny = ["*/unverified-*", "my-org/legacy@v1"]
+//! ```
+//!
+//! Patterns match `owner/repo[/path]`, or the whole reference when they contain an
+//! `@`, case-insensitively, with `*` matching anything. Local actions and workflows
+//! are always allowed.
+//!
+//! The `[policy]` section requires content of GitHub workflows, checked by
+//! `wrkflw validate --policy`; each requirement is on when its table is there:
+//!
+//! ```toml
+//! [policy]
+//! # Exit code of a validation failing only on requirements; 1 when unset
+//! exit_code = 3
+//!
+//! # The workflow, or else every job, sets `permissions:`
+//! [policy.permissions]
+//! message = "See https://wiki.example.com/ci/permissions"
+//!
+//! # Jobs set `timeout-minutes:`, at most `max`
+//! [policy.timeout-minutes]
+//! max = 60
+//! exit_code = 4
+//!
+//! # Third-party actions and workflows are pinned to a commit SHA, but for the
+//! # `trusted` ones
+//! [policy.pinned-actions]
+//! trusted = ["my-org/*"]
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::fmt;
+use std::path::Path;
+use wrkflw_models::{Severity, ValidationResult};
+use wrkflw_parser::pin;
+use wrkflw_parser::workflow::{self, WorkflowDefinition};
+
+use crate::config::ProjectConfig;
+
+/// The `[actions]` section of `.wrkflw.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ActionPolicy {
+    /// Patterns of what may be used; anything when empty
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Patterns of what may not be used, even when allowed
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+/// A use of an action or reusable workflow the policy forbids
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub job: String,
+    /// The step's name or number, for actions
+    pub step: Option<String>,
+    pub uses: String,
+    pub reason: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Job '{}'", self.job)?;
+        if let Some(step) = &self.step {
+            write!(f, ", step '{}'", step)?;
+        }
+        write!(f, ": {} is not allowed ({})", self.uses, self.reason)
+    }
+}
+
+impl ActionPolicy {
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+
+    /// Why `uses` is forbidden, if it is
+    pub fn check(&self, uses: &str) -> Option<String> {
+        if uses.starts_with("./") || uses.starts_with('/') || uses.starts_with("docker://") {
+            return None;
+        }
+        if let Some(pattern) = self.deny.iter().find(|pattern| matches(pattern, uses)) {
+            return Some(format!("denied by `{}`", pattern));
+        }
+        if !self.allow.is_empty() && !self.allow.iter().any(|pattern| matches(pattern, uses)) {
+            return Some("matches no `allow` pattern".to_string());
+        }
+        None
+    }
+
+    /// The uses of actions and reusable workflows in `workflow` the policy forbids,
+    /// sorted by job
+    pub fn violations(&self, workflow: &WorkflowDefinition) -> Vec<Violation> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        let mut jobs: Vec<_> = workflow.jobs.iter().collect();
+        jobs.sort_by_key(|(name, _)| *name);
+
+        let mut violations = Vec::new();
+        for (job_name, job) in jobs {
+            let mut uses = Vec::new();
+            if let Some(workflow) = &job.uses {
+                uses.push((None, workflow));
+            }
+            for (idx, step) in job.steps.iter().enumerate() {
+                if let Some(action) = &step.uses {
+                    let step_name = step
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| format!("Step {}", idx + 1));
+                    uses.push((Some(step_name), action));
+                }
+            }
+            for (step, uses) in uses {
+                if let Some(reason) = self.check(uses) {
+                    violations.push(Violation {
+                        job: job_name.clone(),
+                        step,
+                        uses: uses.clone(),
+                        reason,
+                    });
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// Validation issues for the uses of actions in the workflow at `path` that the
+/// policy of the project, the current directory like for runs, forbids
+pub fn workflow_file_issues(path: &Path) -> Vec<String> {
+    let config = std::env::current_dir()
+        .map_err(|e| e.to_string())
+        .and_then(|dir| ProjectConfig::load(&dir));
+    let policy = match config {
+        Ok(config) => config.actions,
+        Err(e) => return vec![e],
+    };
+    if policy.is_empty() {
+        return Vec::new();
+    }
+    // Workflows that don't parse are reported as such by the validator
+    workflow::parse_workflow(path)
+        .map(|workflow| {
+            policy
+                .violations(&workflow)
+                .iter()
+                .map(Violation::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Rule of workflows and jobs without `permissions:`
+pub const PERMISSIONS_RULE: &str = "policy-permissions";
+
+/// Rule of jobs without `timeout-minutes:`, or with too many
+pub const TIMEOUT_RULE: &str = "policy-timeout-minutes";
+
+/// Rule of third-party actions not pinned to a commit
+pub const PINNED_ACTIONS_RULE: &str = "policy-pinned-actions";
+
+/// The `[policy]` section of `.wrkflw.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkflowPolicy {
+    /// Exit code of a validation failing only on requirements, unless theirs set one
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    #[serde(default)]
+    pub permissions: Option<Requirement>,
+    #[serde(default, rename = "timeout-minutes")]
+    pub timeout_minutes: Option<TimeoutRequirement>,
+    #[serde(default, rename = "pinned-actions")]
+    pub pinned_actions: Option<PinRequirement>,
+}
+
+/// A requirement with nothing to set but how it's reported
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Requirement {
+    /// Added to the findings, e.g. where the requirement is explained
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+}
+
+/// The `[policy.timeout-minutes]` requirement
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TimeoutRequirement {
+    /// Most minutes a job may set
+    #[serde(default)]
+    pub max: Option<u64>,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+}
+
+/// The `[policy.pinned-actions]` requirement
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PinRequirement {
+    /// Patterns, like those of `[actions]`, of actions that needn't be pinned
+    #[serde(default)]
+    pub trusted: Vec<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+}
+
+impl WorkflowPolicy {
+    pub fn is_empty(&self) -> bool {
+        self.permissions.is_none()
+            && self.timeout_minutes.is_none()
+            && self.pinned_actions.is_none()
+    }
+
+    /// The rules of the requirements, in the order they're checked
+    pub fn rules(&self) -> Vec<&'static str> {
+        let mut rules = Vec::new();
+        if self.permissions.is_some() {
+            rules.push(PERMISSIONS_RULE);
+        }
+        if self.timeout_minutes.is_some() {
+            rules.push(TIMEOUT_RULE);
+        }
+        if self.pinned_actions.is_some() {
+            rules.push(PINNED_ACTIONS_RULE);
+        }
+        rules
+    }
+
+    /// Exit code of a validation failing on the requirement of `rule`
+    pub fn exit_code(&self, rule: &str) -> i32 {
+        let own = match rule {
+            PERMISSIONS_RULE => self.permissions.as_ref().and_then(|r| r.exit_code),
+            TIMEOUT_RULE => self.timeout_minutes.as_ref().and_then(|r| r.exit_code),
+            PINNED_ACTIONS_RULE => self.pinned_actions.as_ref().and_then(|r| r.exit_code),
+            _ => None,
+        };
+        own.or(self.exit_code).unwrap_or(1)
+    }
+
+    /// The requirements the GitHub workflow `content` doesn't meet, as errors of
+    /// their rules. Workflows that don't parse are reported as such by the validator.
+    pub fn check(&self, content: &str) -> ValidationResult {
+        let mut result = ValidationResult::new();
+        let Ok(workflow) = serde_yaml::from_str::<Value>(content) else {
+            return result;
+        };
+        let mut jobs: Vec<(&str, &Value)> = workflow
+            .get("jobs")
+            .and_then(Value::as_mapping)
+            .map(|jobs| {
+                jobs.iter()
+                    .filter_map(|(name, job)| Some((name.as_str()?, job)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        jobs.sort_by_key(|(name, _)| *name);
+        let report =
+            |result: &mut ValidationResult, rule, message: String, note: &Option<String>| {
+                let message = match note {
+                    Some(note) => format!("{} — {}", message, note),
+                    None => message,
+                };
+                result.add_for(rule, Severity::Error, message);
+            };
+
+        if let Some(requirement) = &self.permissions {
+            if workflow.get("permissions").is_none() {
+                let without: Vec<&str> = jobs
+                    .iter()
+                    .filter(|(_, job)| job.get("permissions").is_none())
+                    .map(|(name, _)| *name)
+                    .collect();
+                if without.len() == jobs.len() {
+                    let message = "Workflow doesn't set `permissions:`, and neither do its jobs";
+                    report(
+                        &mut result,
+                        PERMISSIONS_RULE,
+                        message.to_string(),
+                        &requirement.message,
+                    );
+                } else {
+                    for name in without {
+                        let message = format!(
+                            "Job '{}' doesn't set `permissions:`, and neither does the workflow",
+                            name
+                        );
+                        report(&mut result, PERMISSIONS_RULE, message, &requirement.message);
+                    }
+                }
+            }
+        }
+
+        if let Some(requirement) = &self.timeout_minutes {
+            // Jobs calling reusable workflows can't set one
+            for (name, job) in jobs.iter().filter(|(_, job)| job.get("uses").is_none()) {
+                let message = match job.get("timeout-minutes") {
+                    None => format!("Job '{}' doesn't set `timeout-minutes:`", name),
+                    Some(minutes) => match (minutes.as_f64(), requirement.max) {
+                        (Some(minutes), Some(max)) if minutes > max as f64 => format!(
+                            "Job '{}' sets `timeout-minutes: {}`, more than the {} allowed",
+                            name, minutes, max
+                        ),
+                        // Expressions are only known when the job runs
+                        _ => continue,
+                    },
+                };
+                report(&mut result, TIMEOUT_RULE, message, &requirement.message);
+            }
+        }
+
+        if let Some(requirement) = &self.pinned_actions {
+            let lines: Vec<&str> = content.lines().collect();
+            for uses in pin::uses_refs(content) {
+                let trusted = requirement
+                    .trusted
+                    .iter()
+                    .any(|pattern| matches(pattern, &uses.action));
+                if uses.is_pinned() || !uses.is_third_party() || trusted {
+                    continue;
+                }
+                let column = lines
+                    .get(uses.line - 1)
+                    .and_then(|line| line.find(&uses.action))
+                    .map_or(1, |idx| idx + 1);
+                let message = format!(
+                    "'{}@{}' at line {} column {} isn't pinned to a commit SHA",
+                    uses.action, uses.git_ref, uses.line, column
+                );
+                report(
+                    &mut result,
+                    PINNED_ACTIONS_RULE,
+                    message,
+                    &requirement.message,
+                );
+            }
+        }
+        result
+    }
+}
+
+/// Whether `pattern` matches `uses`
+fn matches(pattern: &str, uses: &str) -> bool {
+    let subject = if pattern.contains('@') {
+        uses
+    } else {
+        uses.split('@').next().unwrap_or(uses)
+    };
+    glob(&pattern.to_lowercase(), &subject.to_lowercase())
+}
+
+/// Match `text` against `pattern`, where `*` matches any run of characters
+fn glob(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No `*`: the whole text has to be the pattern
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(allow: &[&str], deny: &[&str]) -> ActionPolicy {
+        ActionPolicy {
+            allow: allow.iter().map(|p| p.to_string()).collect(),
+            deny: deny.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_glob() {
+        assert!(glob("actions/*", "actions/checkout"));
+        assert!(glob("actions/*", "actions/cache/save"));
+        assert!(!glob("actions/*", "action/checkout"));
+        assert!(glob("*/unverified-*", "someone/unverified-tool"));
+        assert!(!glob("*/unverified-*", "someone/tool"));
+        assert!(glob("a*b*c", "abc"));
+        assert!(!glob("a*bc*c", "abc"));
+        assert!(glob("exact/name", "exact/name"));
+        assert!(!glob("exact/name", "exact/name2"));
+    }
+
+    #[test]
+    fn test_check() {
+        let allowed = policy(
+            &["actions/*", "Docker/*"],
+            &["*/unverified-*", "docker/login-action@v1"],
+        );
+        assert_eq!(allowed.check("actions/checkout@v4"), None);
+        assert_eq!(allowed.check("docker/login-action@v3"), None);
+        assert_eq!(
+            allowed.check("docker/login-action@v1"),
+            Some("denied by `docker/login-action@v1`".to_string())
+        );
+        assert_eq!(
+            allowed.check("actions/unverified-thing@v1"),
+            Some("denied by `*/unverified-*`".to_string())
+        );
+        assert_eq!(
+            allowed.check("someone/tool@v1"),
+            Some("matches no `allow` pattern".to_string())
+        );
+        assert_eq!(allowed.check("./local-action"), None);
+        assert_eq!(allowed.check("docker://alpine:3"), None);
+
+        // Without `allow`, only what's denied is forbidden
+        let deny_only = policy(&[], &["*/unverified-*"]);
+        assert_eq!(deny_only.check("someone/tool@v1"), None);
+        assert!(deny_only.check("someone/unverified-tool@v1").is_some());
+    }
+
+    #[test]
+    fn test_workflow_policy() {
+        let policy: WorkflowPolicy = toml::from_str(
+            r#"
+exit_code = 3
+[permissions]
+message = "see the wiki"
+[timeout-minutes]
+max = 60
+exit_code = 4
+[pinned-actions]
+trusted = ["my-org/*"]
+"#,
+        )
+        .unwrap();
+        let content = r#"on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    permissions:
+      contents: read
+    timeout-minutes: 90
+    steps:
+      - uses: actions/checkout@v4
+      - uses: someone/tool@v1
+      - uses: my-org/tool@main
+      - uses: someone/other@0123456789abcdef0123456789abcdef01234567
+  test:
+    runs-on: ubuntu-latest
+    timeout-minutes: ${{ inputs.minutes }}
+    steps:
+      - run: make test
+  deploy:
+    uses: someone/workflows/.github/workflows/deploy.yml@main
+"#;
+        let result = policy.check(content);
+        let findings: Vec<(&str, &str)> = result
+            .findings
+            .iter()
+            .map(|finding| (finding.rule.as_str(), finding.message.as_str()))
+            .collect();
+        assert_eq!(
+            findings,
+            vec![
+                (PERMISSIONS_RULE, "Job 'deploy' doesn't set `permissions:`, and neither does the workflow — see the wiki"),
+                (PERMISSIONS_RULE, "Job 'test' doesn't set `permissions:`, and neither does the workflow — see the wiki"),
+                (TIMEOUT_RULE, "Job 'build' sets `timeout-minutes: 90`, more than the 60 allowed"),
+                (PINNED_ACTIONS_RULE, "'someone/tool@v1' at line 10 column 15 isn't pinned to a commit SHA"),
+                (PINNED_ACTIONS_RULE, "'someone/workflows/.github/workflows/deploy.yml@main' at line 19 column 11 isn't pinned to a commit SHA"),
+            ]
+        );
+        assert_eq!(policy.exit_code(PERMISSIONS_RULE), 3);
+        assert_eq!(policy.exit_code(TIMEOUT_RULE), 4);
+
+        let result =
+            policy.check("on: push\njobs:\n  build:\n    runs-on: ubuntu-latest\n    steps: []\n");
+        assert_eq!(
+            result.findings[0].message,
+            "Workflow doesn't set `permissions:`, and neither do its jobs — see the wiki"
+        );
+        assert_eq!(
+            result.findings[1].message,
+            "Job 'build' doesn't set `timeout-minutes:`"
+        );
+        assert!(WorkflowPolicy::default().check(content).findings.is_empty());
+    }
+
+    #[test]
+    fn test_violations_and_env() {
+        let workflow: WorkflowDefinition = serde_yaml::from_str(
+            r#"
+name: ci
+on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+      - name: Tool
+        uses: someone/tool@v1
+      - uses: someone/other@v2
+  deploy:
+    uses: someone/workflows/.github/workflows/deploy.yml@main
+"#,
+        )
+        .unwrap();
+        let policy = policy(&["actions/*"], &[]);
+        let violations: Vec<String> = policy
+            .violations(&workflow)
+            .iter()
+            .map(Violation::to_string)
+            .collect();
+        assert_eq!(
+            violations,
+            vec![
+                "Job 'build', step 'Tool': someone/tool@v1 is not allowed (matches no `allow` pattern)",
+                "Job 'build', step 'Step 3': someone/other@v2 is not allowed (matches no `allow` pattern)",
+                "Job 'deploy': someone/workflows/.github/workflows/deploy.yml@main is not allowed (matches no `allow` pattern)",
+            ]
+        );
+        assert!(ActionPolicy::default().violations(&workflow).is_empty());
+    }
+}
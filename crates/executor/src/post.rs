@@ -0,0 +1,195 @@
+//! Post steps: what actions with a `post:` entrypoint run when their job ends.
+//!
+//! Like on GitHub, every action step that ran queues the post step of its action,
+//! and once the job's steps are done the queue runs last in first out, each post
+//! step under the action's `post-if:`. wrkflw does the cleanup of `actions/checkout`
+//! itself; post steps of other actions are emulated, as their main steps are.
+
+use crate::action_cache::{ActionCache, RepoRef};
+use crate::checkout::{self, CheckoutOptions};
+use crate::engine::StepStatus;
+use serde_yaml::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Well-known actions with a post step, and their `post-if:`
+const KNOWN_POST_STEPS: &[(&str, &str)] = &[
+    ("actions/cache@", "success()"),
+    ("actions/setup-node@", "success()"),
+    ("actions/setup-python@", "success()"),
+    ("actions/setup-go@", "success()"),
+    ("actions/setup-java@", "success()"),
+    ("docker/setup-buildx-action@", "always()"),
+    ("docker/login-action@", "always()"),
+];
+
+/// A post step queued by an action step
+#[derive(Debug, Clone, PartialEq)]
+pub struct PostStep {
+    /// `Post <name of the step>`
+    pub name: String,
+    /// The action's `post-if:`
+    pub condition: String,
+    pub action: PostAction,
+}
+
+/// What a post step does
+#[derive(Debug, Clone, PartialEq)]
+pub enum PostAction {
+    /// Remove the token `actions/checkout` persisted in the repository it checked out
+    RemoveCredentials {
+        repository: PathBuf,
+        server_url: String,
+    },
+    /// Report the post step of an action wrkflw emulates
+    Emulated { uses: String },
+}
+
+impl PostStep {
+    /// The post step of the action `uses`, run by the step `step_name` with `inputs`
+    /// in `working_dir`, if the action has one
+    pub fn for_action(
+        uses: &str,
+        step_name: &str,
+        inputs: &HashMap<String, String>,
+        working_dir: &Path,
+        project_dir: &Path,
+    ) -> Option<Self> {
+        let post_step = |condition: &str, action| PostStep {
+            name: format!("Post {}", step_name),
+            condition: condition.to_string(),
+            action,
+        };
+        if uses.starts_with("actions/checkout@") {
+            let options = CheckoutOptions::from_inputs(inputs).ok()?;
+            let repository = match &options.path {
+                Some(path) => working_dir.join(path),
+                None => working_dir.to_path_buf(),
+            };
+            // Checkouts of the project in place don't persist anything
+            if !options.persist_credentials || repository == project_dir {
+                return None;
+            }
+            return Some(post_step(
+                "always()",
+                PostAction::RemoveCredentials {
+                    repository,
+                    server_url: options.server_url,
+                },
+            ));
+        }
+
+        let emulated = PostAction::Emulated {
+            uses: uses.to_string(),
+        };
+        if let Some(runs) = action_definition(uses, project_dir).and_then(|action| {
+            action
+                .get("runs")
+                .filter(|runs| runs.get("post").is_some() || runs.get("post-entrypoint").is_some())
+                .cloned()
+        }) {
+            let condition = runs
+                .get("post-if")
+                .and_then(Value::as_str)
+                .unwrap_or("always()");
+            return Some(post_step(condition, emulated));
+        }
+        KNOWN_POST_STEPS
+            .iter()
+            .find(|(prefix, _)| uses.starts_with(prefix))
+            .map(|(_, condition)| post_step(condition, emulated))
+    }
+
+    /// Run the post step
+    pub fn run(&self) -> (StepStatus, String) {
+        match &self.action {
+            PostAction::RemoveCredentials {
+                repository,
+                server_url,
+            } => match checkout::remove_credentials(repository, server_url) {
+                Ok(true) => (
+                    StepStatus::Success,
+                    format!(
+                        "Removed the credentials persisted in {}",
+                        repository.display()
+                    ),
+                ),
+                Ok(false) => (
+                    StepStatus::Success,
+                    "No persisted credentials to remove".to_string(),
+                ),
+                Err(e) => (StepStatus::Failure, e),
+            },
+            PostAction::Emulated { uses } => (
+                StepStatus::Success,
+                format!("Would execute the post step of GitHub action: {}", uses),
+            ),
+        }
+    }
+}
+
+/// The `action.yml` of a local action, or of a remote one the action cache holds
+fn action_definition(uses: &str, project_dir: &Path) -> Option<Value> {
+    let dir = if uses.starts_with("./") {
+        project_dir.join(uses)
+    } else {
+        let (left, git_ref) = uses.split_once('@')?;
+        let mut segments = left.splitn(3, '/');
+        let (owner, repo) = (segments.next()?, segments.next()?);
+        let repo_dir = ActionCache::new(ActionCache::default_root())
+            .lookup(&RepoRef::new(owner, repo, git_ref))?;
+        match segments.next() {
+            Some(path) => repo_dir.join(path),
+            None => repo_dir,
+        }
+    };
+    ["action.yml", "action.yaml"].iter().find_map(|file| {
+        fs::read_to_string(dir.join(file))
+            .ok()
+            .and_then(|content| serde_yaml::from_str(&content).ok())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_post_steps() {
+        let project = tempfile::tempdir().unwrap();
+        let workspace = Path::new("/tmp/job");
+        let inputs = HashMap::new();
+        let for_action = |uses: &str, inputs: &HashMap<String, String>| {
+            PostStep::for_action(uses, "Step 1", inputs, workspace, project.path())
+        };
+
+        let checkout = for_action("actions/checkout@v4", &inputs).unwrap();
+        assert_eq!(checkout.name, "Post Step 1");
+        assert_eq!(checkout.condition, "always()");
+        assert!(matches!(
+            checkout.action,
+            PostAction::RemoveCredentials { repository, .. } if repository == workspace
+        ));
+        let no_credentials =
+            HashMap::from([("persist-credentials".to_string(), "false".to_string())]);
+        assert_eq!(for_action("actions/checkout@v4", &no_credentials), None);
+
+        assert_eq!(
+            for_action("actions/cache@v4", &inputs).map(|post| post.condition),
+            Some("success()".to_string())
+        );
+        assert_eq!(for_action("actions/upload-artifact@v4", &inputs), None);
+
+        let action_dir = project.path().join("cleanup");
+        fs::create_dir_all(&action_dir).unwrap();
+        fs::write(
+            action_dir.join("action.yml"),
+            "runs:\n  using: node20\n  main: main.js\n  post: post.js\n  post-if: failure()\n",
+        )
+        .unwrap();
+        let local = for_action("./cleanup", &inputs).unwrap();
+        assert_eq!(local.condition, "failure()");
+        assert_eq!(local.run().0, StepStatus::Success);
+    }
+}
@@ -0,0 +1,162 @@
+//! Steps of the running jobs, and stages of running GitLab pipelines, as they
+//! start and finish, for front ends that show a run while it happens rather than
+//! only its result.
+
+use crate::engine::{JobStatus, StepResult};
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+
+/// Something that happened to a step of a running job
+#[derive(Debug, Clone)]
+pub enum ProgressEvent {
+    /// The step at `step` of `job` started
+    StepStarted {
+        job: String,
+        step: usize,
+        name: String,
+    },
+    /// The step at `step` of `job` finished
+    StepFinished {
+        job: String,
+        step: usize,
+        result: StepResult,
+    },
+    /// Stage `index` of the `total` stages of a GitLab pipeline started, with
+    /// `jobs` in it
+    StageStarted {
+        stage: String,
+        index: usize,
+        total: usize,
+        jobs: Vec<String>,
+    },
+    /// A stage finished: failed if a job failed it, skipped if none of its jobs ran
+    StageFinished { stage: String, status: JobStatus },
+}
+
+// `None` while nobody listens, so runs without a listener don't keep their events
+static EVENTS: Lazy<Mutex<Option<Vec<ProgressEvent>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Record the events of the runs from now on, dropping those not taken yet
+pub fn listen() {
+    if let Ok(mut events) = EVENTS.lock() {
+        *events = Some(Vec::new());
+    }
+}
+
+/// Stop recording events
+pub fn stop() {
+    if let Ok(mut events) = EVENTS.lock() {
+        *events = None;
+    }
+}
+
+/// The events recorded since the last call, oldest first
+pub fn take() -> Vec<ProgressEvent> {
+    match EVENTS.lock() {
+        Ok(mut events) => events.as_mut().map(std::mem::take).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    }
+}
+
+pub(crate) fn step_started(job: &str, step: usize, name: String) {
+    crate::logs::step_started(job, &name);
+    record(ProgressEvent::StepStarted {
+        job: job.to_string(),
+        step,
+        name,
+    });
+}
+
+pub(crate) fn step_finished(job: &str, step: usize, result: &StepResult) {
+    crate::logs::step_finished(job, result);
+    record(ProgressEvent::StepFinished {
+        job: job.to_string(),
+        step,
+        result: result.clone(),
+    });
+}
+
+pub(crate) fn stage_started(stage: &str, index: usize, total: usize, jobs: Vec<String>) {
+    wrkflw_logging::info(&format!(
+        "Stage {}/{} '{}': {}",
+        index + 1,
+        total,
+        stage,
+        jobs.join(", ")
+    ));
+    record(ProgressEvent::StageStarted {
+        stage: stage.to_string(),
+        index,
+        total,
+        jobs,
+    });
+}
+
+pub(crate) fn stage_finished(stage: &str, status: JobStatus) {
+    wrkflw_logging::info(&format!("Stage '{}' finished: {:?}", stage, status));
+    record(ProgressEvent::StageFinished {
+        stage: stage.to_string(),
+        status,
+    });
+}
+
+fn record(event: ProgressEvent) {
+    if let Ok(mut events) = EVENTS.lock() {
+        if let Some(events) = events.as_mut() {
+            events.push(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::StepStatus;
+    use std::time::Duration;
+
+    #[test]
+    fn records_only_while_listening() {
+        // Other tests may run steps meanwhile, so only look at this job's events
+        let job = "progress-test";
+        let events_of_job = || {
+            take()
+                .into_iter()
+                .filter(|event| match event {
+                    ProgressEvent::StepStarted { job: name, .. }
+                    | ProgressEvent::StepFinished { job: name, .. } => name == job,
+                    _ => false,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        step_started(job, 0, "Checkout".to_string());
+        listen();
+        step_started(job, 0, "Checkout".to_string());
+        step_finished(
+            job,
+            0,
+            &StepResult {
+                name: "Checkout".to_string(),
+                status: StepStatus::Success,
+                output: String::new(),
+                duration: Duration::from_secs(1),
+            },
+        );
+
+        let events = events_of_job();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            &events[0],
+            ProgressEvent::StepStarted { step: 0, name, .. } if name == "Checkout"
+        ));
+        assert!(matches!(
+            &events[1],
+            ProgressEvent::StepFinished { result, .. } if result.status == StepStatus::Success
+        ));
+        assert!(events_of_job().is_empty());
+
+        stop();
+        step_started(job, 1, "Test".to_string());
+        assert!(events_of_job().is_empty());
+    }
+}
@@ -0,0 +1,206 @@
+//! When job images are pulled, and reporting how pulls are getting on.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use wrkflw_runtime::container::ContainerError;
+use wrkflw_runtime::output::format_size;
+use wrkflw_runtime::timeouts;
+
+/// Pull progress is logged at most this often
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// When the image of a job or Docker action is pulled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PullPolicy {
+    /// Pull before every use, to get the latest version
+    Always,
+    /// Pull only images not present locally
+    #[default]
+    Missing,
+    /// Never pull; images have to be present locally
+    Never,
+}
+
+impl PullPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PullPolicy::Always => "always",
+            PullPolicy::Missing => "missing",
+            PullPolicy::Never => "never",
+        }
+    }
+}
+
+/// Make `image` available as `policy` says: `present` tells whether it already is,
+/// and `pull` pulls it. A pull that fails or times out falls back to the image
+/// already present, and is an error without one.
+pub(crate) async fn ensure_image<F>(
+    policy: PullPolicy,
+    image: &str,
+    present: bool,
+    pull: F,
+) -> Result<(), ContainerError>
+where
+    F: Future<Output = Result<(), ContainerError>>,
+{
+    match policy {
+        PullPolicy::Never if !present => {
+            return Err(ContainerError::ImagePull(format!(
+                "Image {} is not present locally and the pull policy is 'never'",
+                image
+            )))
+        }
+        PullPolicy::Never | PullPolicy::Missing if present => {
+            wrkflw_logging::debug(&format!("Using image {} present locally", image));
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let limit = timeouts::timeouts().pull();
+    let pulled = match timeouts::within(limit, pull).await {
+        Ok(pulled) => pulled,
+        Err(_) => Err(ContainerError::ImagePull(format!(
+            "Pull of image {} timed out after {} seconds",
+            image,
+            limit.unwrap_or_default().as_secs()
+        ))),
+    };
+    match pulled {
+        Err(e) if present => {
+            wrkflw_logging::warning(&format!("{}; using the image present locally", e));
+            Ok(())
+        }
+        pulled => pulled,
+    }
+}
+
+#[derive(Debug, Default)]
+struct Layer {
+    current: u64,
+    total: u64,
+    done: bool,
+}
+
+/// Progress of pulling one image across its layers, logged as it goes
+pub(crate) struct PullProgress {
+    image: String,
+    layers: BTreeMap<String, Layer>,
+    started: Instant,
+    last_report: Instant,
+}
+
+impl PullProgress {
+    pub(crate) fn new(image: &str) -> Self {
+        wrkflw_logging::info(&format!("Pulling image {}", image));
+        let now = Instant::now();
+        PullProgress {
+            image: image.to_string(),
+            layers: BTreeMap::new(),
+            started: now,
+            last_report: now,
+        }
+    }
+
+    /// A layer about to be downloaded, its size not known yet
+    pub(crate) fn layer(&mut self, id: &str) {
+        self.layers.entry(id.to_string()).or_default();
+    }
+
+    /// Bytes of a layer downloaded so far, out of its size
+    pub(crate) fn downloading(&mut self, id: &str, current: u64, total: u64) {
+        let layer = self.layers.entry(id.to_string()).or_default();
+        layer.current = current;
+        layer.total = total;
+        self.report();
+    }
+
+    /// A layer downloaded, or already there
+    pub(crate) fn layer_done(&mut self, id: &str) {
+        let layer = self.layers.entry(id.to_string()).or_default();
+        layer.done = true;
+        layer.current = layer.total;
+        self.report();
+    }
+
+    fn report(&mut self) {
+        if self.last_report.elapsed() >= REPORT_INTERVAL {
+            self.last_report = Instant::now();
+            wrkflw_logging::info(&format!("Pulling image {}: {}", self.image, self.summary()));
+        }
+    }
+
+    /// The overall percentage, when layer sizes are known, and the layers done
+    fn summary(&self) -> String {
+        let done = self.layers.values().filter(|layer| layer.done).count();
+        let layers = format!("{}/{} layers done", done, self.layers.len());
+        let total: u64 = self.layers.values().map(|layer| layer.total).sum();
+        if total == 0 {
+            return layers;
+        }
+        let current: u64 = self.layers.values().map(|layer| layer.current).sum();
+        format!(
+            "{}% of {}, {}",
+            current * 100 / total,
+            format_size(total as usize),
+            layers
+        )
+    }
+
+    pub(crate) fn finish(&self) {
+        wrkflw_logging::info(&format!(
+            "Pulled image {} ({} layers) in {:.1}s",
+            self.image,
+            self.layers.len(),
+            self.started.elapsed().as_secs_f64()
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pull_progress_summary() {
+        let mut progress = PullProgress::new("alpine:3.20");
+        progress.layer("a");
+        progress.layer("b");
+        assert_eq!(progress.summary(), "0/2 layers done");
+
+        progress.downloading("a", 512, 1024);
+        progress.downloading("b", 0, 1024);
+        assert_eq!(progress.summary(), "25% of 2.0 KiB, 0/2 layers done");
+
+        progress.layer_done("a");
+        assert_eq!(progress.summary(), "50% of 2.0 KiB, 1/2 layers done");
+    }
+
+    #[tokio::test]
+    async fn test_ensure_image() {
+        let pulled = |result: Result<(), ContainerError>| async move { result };
+        let failed = || Err(ContainerError::ImagePull("no route".to_string()));
+
+        assert!(
+            ensure_image(PullPolicy::Never, "img", false, pulled(Ok(())))
+                .await
+                .is_err()
+        );
+        assert!(
+            ensure_image(PullPolicy::Missing, "img", true, pulled(failed()))
+                .await
+                .is_ok()
+        );
+        assert!(
+            ensure_image(PullPolicy::Always, "img", true, pulled(failed()))
+                .await
+                .is_ok()
+        );
+        assert!(
+            ensure_image(PullPolicy::Always, "img", false, pulled(failed()))
+                .await
+                .is_err()
+        );
+    }
+}
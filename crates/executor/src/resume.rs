@@ -0,0 +1,301 @@
+//! Rerunning a failed run from its failures: jobs that succeeded are skipped, and
+//! each failed job starts again at the step that failed it, in the workspace and
+//! with the environment (steps context, `GITHUB_ENV`, `GITHUB_PATH`) it had then.
+
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+tokio::task_local! {
+    static RUN: RunState;
+}
+
+/// What a rerun of a failed run needs to pick up where it stopped
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Resume {
+    /// Jobs, and matrix combinations, that succeeded, here or in the run this one
+    /// reran
+    pub succeeded: BTreeSet<String>,
+    /// Where each failed job stopped
+    pub jobs: BTreeMap<String, ResumePoint>,
+}
+
+/// Where a failed job stopped
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResumePoint {
+    /// Index of the step that failed the job
+    pub step: usize,
+    /// What the job's steps before it changed of the job's environment
+    pub env: HashMap<String, String>,
+    /// The job's workspace as the step left it; none for jobs that ran in the
+    /// project itself
+    #[serde(skip)]
+    pub workspace: Option<JobWorkspace>,
+}
+
+/// Workspace of a failed job
+#[derive(Debug, Clone)]
+pub enum JobWorkspace {
+    /// The directory the job ran in, kept until the run is saved
+    Kept(Arc<TempDir>),
+    /// A copy saved with a recorded run
+    Saved(PathBuf),
+}
+
+impl JobWorkspace {
+    pub fn path(&self) -> &Path {
+        match self {
+            JobWorkspace::Kept(dir) => dir.path(),
+            JobWorkspace::Saved(path) => path,
+        }
+    }
+}
+
+impl Resume {
+    /// Whether a rerun would run anything other than the whole workflow again
+    pub fn is_empty(&self) -> bool {
+        self.succeeded.is_empty() && self.jobs.is_empty()
+    }
+}
+
+struct RunState {
+    /// The run being rerun
+    from: Resume,
+    /// Failed jobs of this run
+    failed: RefCell<BTreeMap<String, ResumePoint>>,
+}
+
+/// Run `run`, resuming `from`, and return where its failed jobs stopped
+pub(crate) async fn resumable_run<F: Future>(
+    from: Resume,
+    run: F,
+) -> (F::Output, BTreeMap<String, ResumePoint>) {
+    if RUN.try_with(|_| ()).is_ok() {
+        // A called workflow's jobs belong to the outermost run
+        return (run.await, BTreeMap::new());
+    }
+    let state = RunState {
+        from,
+        failed: RefCell::new(BTreeMap::new()),
+    };
+    RUN.scope(state, async {
+        let output = run.await;
+        (output, RUN.with(|state| state.failed.take()))
+    })
+    .await
+}
+
+/// Whether `job` succeeded in the run being rerun
+pub(crate) fn succeeded(job: &str) -> bool {
+    RUN.try_with(|state| state.from.succeeded.contains(job))
+        .unwrap_or(false)
+}
+
+/// What a job's steps go through to be rerun from where it failed, and to be rerun
+/// from where it fails now
+pub(crate) struct JobProgress {
+    job: String,
+    /// The job's environment before its first step
+    base: HashMap<String, String>,
+    /// Steps before this one succeeded in the run being rerun
+    resume_from: usize,
+    /// The step that failed the job and the environment before it
+    failed_at: Option<(usize, HashMap<String, String>)>,
+}
+
+impl JobProgress {
+    /// Start `job`, whose environment is `job_env` and workspace `workspace`; when
+    /// the run resumes the job, restores the environment and workspace it failed with
+    pub(crate) fn start(
+        job: &str,
+        job_env: &mut HashMap<String, String>,
+        workspace: &Path,
+    ) -> Self {
+        let base = job_env.clone();
+        let point = RUN
+            .try_with(|state| state.from.jobs.get(job).cloned())
+            .ok()
+            .flatten();
+        let mut resume_from = 0;
+        if let Some(point) = point {
+            let restored = match &point.workspace {
+                Some(saved) => copy_tree(saved.path(), workspace).map_err(|e| {
+                    format!(
+                        "Failed to restore its workspace from {}: {}",
+                        saved.path().display(),
+                        e
+                    )
+                }),
+                None => Ok(()),
+            };
+            match restored {
+                Ok(()) => {
+                    job_env.extend(point.env);
+                    resume_from = point.step;
+                    wrkflw_logging::info(&format!(
+                        "Resuming job '{}' at step {}",
+                        job,
+                        point.step + 1
+                    ));
+                }
+                Err(e) => wrkflw_logging::warning(&format!(
+                    "Running job '{}' from its first step: {}",
+                    job, e
+                )),
+            }
+        }
+        JobProgress {
+            job: job.to_string(),
+            base,
+            resume_from,
+            failed_at: None,
+        }
+    }
+
+    /// Whether step `idx` succeeded in the run being rerun
+    pub(crate) fn skips(&self, idx: usize) -> bool {
+        idx < self.resume_from
+    }
+
+    /// The environment to keep before running a step, while the job hasn't failed
+    pub(crate) fn before_step(
+        &self,
+        job_env: &HashMap<String, String>,
+    ) -> Option<HashMap<String, String>> {
+        self.failed_at.is_none().then(|| job_env.clone())
+    }
+
+    /// Step `idx`, run with `env_before`, failed the job
+    pub(crate) fn step_failed(&mut self, idx: usize, env_before: Option<HashMap<String, String>>) {
+        if let (None, Some(env)) = (&self.failed_at, env_before) {
+            self.failed_at = Some((idx, env));
+        }
+    }
+
+    /// The job finished; if it failed, keep where and, when `job_dir` is its
+    /// workspace (`in_job_dir`), in what workspace
+    pub(crate) fn finish(self, failed: bool, job_dir: TempDir, in_job_dir: bool) {
+        let Some((step, env_before)) = self.failed_at.filter(|_| failed) else {
+            return;
+        };
+        let env = env_before
+            .into_iter()
+            .filter(|(key, value)| self.base.get(key) != Some(value))
+            .collect();
+        let workspace = in_job_dir.then(|| JobWorkspace::Kept(Arc::new(job_dir)));
+        let _ = RUN.try_with(|state| {
+            state.failed.borrow_mut().insert(
+                self.job,
+                ResumePoint {
+                    step,
+                    env,
+                    workspace,
+                },
+            )
+        });
+    }
+}
+
+/// Copy the files under `from` into `to`, hidden and ignored ones included
+pub(crate) fn copy_tree(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            copy_tree(&entry.path(), &target)?;
+        } else if file_type.is_symlink() {
+            let link = fs::read_link(entry.path())?;
+            let _ = fs::remove_file(&target);
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(link, &target)?;
+            #[cfg(not(unix))]
+            fs::copy(entry.path(), &target).map(|_| ())?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn failed_job_keeps_what_changed_before_the_failing_step() {
+        let from = Resume {
+            succeeded: BTreeSet::from(["build".to_string()]),
+            jobs: BTreeMap::new(),
+        };
+        let (_, failed) = resumable_run(from, async {
+            assert!(succeeded("build"));
+            assert!(!succeeded("test"));
+
+            let job_dir = tempfile::tempdir().unwrap();
+            let workspace = job_dir.path().to_path_buf();
+            let mut job_env = HashMap::from([("CI".to_string(), "true".to_string())]);
+            let mut progress = JobProgress::start("test", &mut job_env, &workspace);
+            assert!(!progress.skips(0));
+
+            let before = progress.before_step(&job_env);
+            job_env.insert("FROM_STEP_1".to_string(), "1".to_string());
+            assert!(before.is_some());
+
+            let before = progress.before_step(&job_env);
+            job_env.insert("FROM_STEP_2".to_string(), "2".to_string());
+            progress.step_failed(1, before);
+            assert!(progress.before_step(&job_env).is_none());
+
+            fs::write(workspace.join("out.txt"), "built").unwrap();
+            progress.finish(true, job_dir, true);
+        })
+        .await;
+
+        let point = &failed["test"];
+        assert_eq!(point.step, 1);
+        assert_eq!(
+            point.env,
+            HashMap::from([("FROM_STEP_1".to_string(), "1".to_string())])
+        );
+        let workspace = point.workspace.as_ref().unwrap().path();
+        assert_eq!(
+            fs::read_to_string(workspace.join("out.txt")).unwrap(),
+            "built"
+        );
+    }
+
+    #[tokio::test]
+    async fn resumed_job_skips_its_succeeded_steps() {
+        let saved = tempfile::tempdir().unwrap();
+        fs::create_dir(saved.path().join(".cache")).unwrap();
+        fs::write(saved.path().join(".cache").join("deps"), "ok").unwrap();
+        let from = Resume {
+            succeeded: BTreeSet::new(),
+            jobs: BTreeMap::from([(
+                "test".to_string(),
+                ResumePoint {
+                    step: 2,
+                    env: HashMap::from([("FROM_STEP_1".to_string(), "1".to_string())]),
+                    workspace: Some(JobWorkspace::Saved(saved.path().to_path_buf())),
+                },
+            )]),
+        };
+        resumable_run(from, async {
+            let job_dir = tempfile::tempdir().unwrap();
+            let mut job_env = HashMap::new();
+            let progress = JobProgress::start("test", &mut job_env, job_dir.path());
+            assert!(progress.skips(1));
+            assert!(!progress.skips(2));
+            assert_eq!(job_env["FROM_STEP_1"], "1");
+            assert!(job_dir.path().join(".cache").join("deps").is_file());
+        })
+        .await;
+    }
+}
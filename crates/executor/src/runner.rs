@@ -0,0 +1,211 @@
+//! Runner operating systems, and what to do with jobs local containers can't host.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Operating system of a runner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunnerOs {
+    Linux,
+    Windows,
+    MacOs,
+}
+
+impl RunnerOs {
+    /// The OS a job's `runs-on` labels ask for (`windows-latest`, `macos-14`,
+    /// or self-hosted labels such as `[self-hosted, windows]`)
+    pub fn from_labels(labels: &[String]) -> Self {
+        for label in labels {
+            let label = label.to_ascii_lowercase();
+            if label.starts_with("windows") {
+                return RunnerOs::Windows;
+            }
+            if label.starts_with("macos") {
+                return RunnerOs::MacOs;
+            }
+        }
+        RunnerOs::Linux
+    }
+
+    /// The OS wrkflw itself runs on
+    pub fn host() -> Self {
+        if cfg!(windows) {
+            RunnerOs::Windows
+        } else if cfg!(target_os = "macos") {
+            RunnerOs::MacOs
+        } else {
+            RunnerOs::Linux
+        }
+    }
+
+    /// Value of `RUNNER_OS`
+    pub fn name(&self) -> &'static str {
+        match self {
+            RunnerOs::Linux => "Linux",
+            RunnerOs::Windows => "Windows",
+            RunnerOs::MacOs => "macOS",
+        }
+    }
+}
+
+/// What to do with a Windows or macOS job that has to run in a Linux container
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ForeignRunnerAction {
+    /// Fail the job, explaining why
+    #[default]
+    Fail,
+    /// Skip the job
+    Skip,
+    /// Run it anyway in this image
+    Image(String),
+}
+
+impl fmt::Display for ForeignRunnerAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ForeignRunnerAction::Fail => write!(f, "fail"),
+            ForeignRunnerAction::Skip => write!(f, "skip"),
+            ForeignRunnerAction::Image(image) => write!(f, "{}", image),
+        }
+    }
+}
+
+impl FromStr for ForeignRunnerAction {
+    type Err = String;
+
+    /// `fail`, `skip`, or an image name
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "" => Err("expected fail, skip or an image name".to_string()),
+            "fail" => Ok(ForeignRunnerAction::Fail),
+            "skip" => Ok(ForeignRunnerAction::Skip),
+            image => Ok(ForeignRunnerAction::Image(image.to_string())),
+        }
+    }
+}
+
+/// Actions for Windows and macOS jobs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunnerOsPolicy {
+    pub windows: ForeignRunnerAction,
+    pub macos: ForeignRunnerAction,
+}
+
+/// How a job gets run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunnerPlacement {
+    /// The usual image for its `runs-on` label
+    Default,
+    /// A Linux container image chosen for a Windows/macOS job
+    Image(String),
+    /// Directly on the host, whose OS matches the job's
+    Native(RunnerOs),
+    /// Skipped, with the reason
+    Skip(String),
+    /// Failed, with the reason
+    Fail(String),
+}
+
+/// Decide where a job runs, given its `runs-on` labels
+///
+/// `emulated` is true for the emulation runtimes, which run natively when the host OS
+/// matches the job's.
+pub fn place_job(runs_on: &[String], emulated: bool, policy: &RunnerOsPolicy) -> RunnerPlacement {
+    let os = RunnerOs::from_labels(runs_on);
+    if os == RunnerOs::Linux {
+        return RunnerPlacement::Default;
+    }
+    if emulated && os == RunnerOs::host() {
+        return RunnerPlacement::Native(os);
+    }
+
+    let (action, flag) = match os {
+        RunnerOs::Windows => (&policy.windows, "--windows-runners"),
+        _ => (&policy.macos, "--macos-runners"),
+    };
+    let label = runs_on.join(", ");
+    match action {
+        ForeignRunnerAction::Image(image) => RunnerPlacement::Image(image.clone()),
+        ForeignRunnerAction::Skip => RunnerPlacement::Skip(format!(
+            "Skipped: runs-on '{}' needs a {} runner",
+            label,
+            os.name()
+        )),
+        ForeignRunnerAction::Fail => RunnerPlacement::Fail(format!(
+            "runs-on '{}' needs a {} runner, which local Linux containers cannot emulate. \
+             Use emulation mode on a {} host, `{} skip` to skip such jobs, \
+             or `{} <image>` to run them in a Linux image anyway",
+            label,
+            os.name(),
+            os.name(),
+            flag,
+            flag
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(labels: &[&str]) -> Vec<String> {
+        labels.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_os_from_labels() {
+        assert_eq!(
+            RunnerOs::from_labels(&labels(&["windows-2022"])),
+            RunnerOs::Windows
+        );
+        assert_eq!(
+            RunnerOs::from_labels(&labels(&["self-hosted", "macOS", "arm64"])),
+            RunnerOs::MacOs
+        );
+        assert_eq!(
+            RunnerOs::from_labels(&labels(&["ubuntu-latest"])),
+            RunnerOs::Linux
+        );
+    }
+
+    #[test]
+    fn test_placement() {
+        let policy = RunnerOsPolicy {
+            windows: ForeignRunnerAction::Skip,
+            macos: ForeignRunnerAction::Image("rust:latest".to_string()),
+        };
+        assert_eq!(
+            place_job(&labels(&["ubuntu-latest"]), false, &policy),
+            RunnerPlacement::Default
+        );
+        assert_eq!(
+            place_job(&labels(&["macos-latest"]), false, &policy),
+            RunnerPlacement::Image("rust:latest".to_string())
+        );
+
+        let host_os = match RunnerOs::host() {
+            RunnerOs::Windows => "windows-latest",
+            RunnerOs::MacOs => "macos-latest",
+            RunnerOs::Linux => "ubuntu-latest",
+        };
+        assert!(matches!(
+            place_job(&labels(&[host_os]), true, &RunnerOsPolicy::default()),
+            RunnerPlacement::Native(_) | RunnerPlacement::Default
+        ));
+
+        if RunnerOs::host() != RunnerOs::Windows {
+            assert!(matches!(
+                place_job(&labels(&["windows-latest"]), true, &policy),
+                RunnerPlacement::Skip(_)
+            ));
+            assert!(matches!(
+                place_job(
+                    &labels(&["windows-latest"]),
+                    false,
+                    &RunnerOsPolicy::default()
+                ),
+                RunnerPlacement::Fail(msg) if msg.contains("--windows-runners")
+            ));
+        }
+    }
+}
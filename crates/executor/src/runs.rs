@@ -0,0 +1,775 @@
+//! History of local runs: a summary of every run (workflow, commit, runtime, job and
+//! step results) and, when asked for, its step logs in the layout of GitHub's log
+//! archives (`<job>/<n>_<step>.txt` plus `<n>_<job>.txt`, zipped), kept under
+//! `.wrkflw/runs/<timestamp>/` in the project. Failed runs also keep what rerunning
+//! them from their failures needs under `resume/`: the workspaces of their failed
+//! jobs are only kept for the latest run of a workflow.
+
+use crate::engine::{ExecutionResult, JobStatus, StepStatus};
+use crate::resume::{self, JobWorkspace, Resume};
+use chrono::{DateTime, Duration as ChronoDuration, Local, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::ZipArchive;
+
+/// Log archive in a run's directory
+pub const LOGS_FILE: &str = "logs.zip";
+/// Summary of a run in its directory
+pub const RUN_FILE: &str = "run.json";
+/// Directory of a failed run with what rerunning it from its failures needs
+pub const RESUME_DIR: &str = "resume";
+/// Where the failed jobs of a run stopped, in its resume directory next to their
+/// workspaces
+const RESUME_FILE: &str = "resume.json";
+
+/// Job durations that changed by less than this many seconds aren't worth reporting
+const DURATION_NOISE_SECS: f64 = 1.0;
+
+/// What a recorded run recorded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunRecord {
+    /// Name of the run's directory
+    pub id: String,
+    pub workflow: String,
+    pub path: PathBuf,
+    /// `HEAD` of the repository the workflow is in, if it is in one
+    #[serde(default)]
+    pub commit: Option<String>,
+    /// Runtime mode the run used (`docker`, `emulation`, ...)
+    #[serde(default)]
+    pub runtime: String,
+    pub started: DateTime<Local>,
+    pub duration_secs: f64,
+    pub success: bool,
+    pub jobs: Vec<JobRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub name: String,
+    pub status: String,
+    pub duration_secs: f64,
+    /// Deployment environment the job ran in
+    #[serde(default)]
+    pub environment: Option<String>,
+    pub steps: Vec<StepRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepRecord {
+    pub name: String,
+    pub status: String,
+    pub duration_secs: f64,
+}
+
+/// Recorded runs of a project
+pub struct RunStore {
+    root: PathBuf,
+}
+
+impl RunStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        RunStore { root: root.into() }
+    }
+
+    /// `.wrkflw/runs` of `project_dir`
+    pub fn for_project(project_dir: &Path) -> Self {
+        Self::new(project_dir.join(".wrkflw").join("runs"))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Record a run of the workflow at `path` on `runtime` that finished now, with its
+    /// step logs if `save_logs`
+    pub fn save(
+        &self,
+        path: &Path,
+        result: &ExecutionResult,
+        runtime: &str,
+        save_logs: bool,
+    ) -> Result<RunRecord, String> {
+        let duration = ChronoDuration::from_std(result.duration).unwrap_or_default();
+        let started = Local::now() - duration;
+
+        let id = new_run_id(&self.root, started);
+        let dir = self.root.join(&id);
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+        let record = RunRecord {
+            id,
+            workflow: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            path: path.to_path_buf(),
+            commit: current_commit(path),
+            runtime: runtime.to_string(),
+            started,
+            duration_secs: result.duration.as_secs_f64(),
+            success: result.failure_details.is_none(),
+            jobs: result
+                .jobs
+                .iter()
+                .map(|job| JobRecord {
+                    name: job.name.clone(),
+                    status: job_status(&job.status).to_string(),
+                    duration_secs: job.timing.duration.as_secs_f64(),
+                    environment: job.environment.clone(),
+                    steps: job
+                        .steps
+                        .iter()
+                        .map(|step| StepRecord {
+                            name: step.name.clone(),
+                            status: step_status(&step.status).to_string(),
+                            duration_secs: step.duration.as_secs_f64(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+        };
+
+        if save_logs {
+            write_logs(&dir.join(LOGS_FILE), started.with_timezone(&Utc), result)?;
+        }
+        if !record.success {
+            self.drop_workspaces(path);
+            save_resume(&dir.join(RESUME_DIR), &result.resume)?;
+        }
+        let json = serde_json::to_string_pretty(&record)
+            .map_err(|e| format!("Failed to serialize the run: {}", e))?;
+        fs::write(dir.join(RUN_FILE), json)
+            .map_err(|e| format!("Failed to write {}: {}", dir.join(RUN_FILE).display(), e))?;
+        Ok(record)
+    }
+
+    /// What rerunning the failed `run` from its failures needs: the jobs that
+    /// succeeded, and where the failed ones stopped. Runs recorded without it rerun
+    /// their failed jobs from the start.
+    pub fn resume(&self, run: &RunRecord) -> Result<Resume, String> {
+        if run.success {
+            return Err(format!(
+                "Run {} succeeded; there is nothing to rerun",
+                run.id
+            ));
+        }
+        let dir = self.root.join(&run.id).join(RESUME_DIR);
+        let file = dir.join(RESUME_FILE);
+        let Ok(json) = fs::read_to_string(&file) else {
+            return Ok(Resume {
+                succeeded: run
+                    .jobs
+                    .iter()
+                    .filter(|job| job.status == "success")
+                    .map(|job| job.name.clone())
+                    .collect(),
+                jobs: Default::default(),
+            });
+        };
+        let mut resume: Resume = serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+        for (job, point) in resume.jobs.iter_mut() {
+            let workspace = dir.join(file_name(job));
+            point.workspace = workspace.is_dir().then_some(JobWorkspace::Saved(workspace));
+        }
+        Ok(resume)
+    }
+
+    /// Drop the workspaces kept for earlier runs of the workflow at `path`; their
+    /// failed jobs rerun from the start
+    fn drop_workspaces(&self, path: &Path) {
+        for run in self.list().iter().filter(|run| run.path == path) {
+            let dir = self.root.join(&run.id).join(RESUME_DIR);
+            let file = dir.join(RESUME_FILE);
+            let Some(mut resume) = fs::read_to_string(&file)
+                .ok()
+                .and_then(|json| serde_json::from_str::<Resume>(&json).ok())
+            else {
+                continue;
+            };
+            if resume.jobs.is_empty() {
+                continue;
+            }
+            for job in resume.jobs.keys() {
+                let _ = fs::remove_dir_all(dir.join(file_name(job)));
+            }
+            resume.jobs.clear();
+            if let Ok(json) = serde_json::to_string_pretty(&resume) {
+                let _ = fs::write(&file, json);
+            }
+        }
+    }
+
+    /// Recorded runs, newest first
+    pub fn list(&self) -> Vec<RunRecord> {
+        let Ok(entries) = fs::read_dir(&self.root) else {
+            return Vec::new();
+        };
+        let mut runs: Vec<RunRecord> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let json = fs::read_to_string(entry.path().join(RUN_FILE)).ok()?;
+                serde_json::from_str(&json).ok()
+            })
+            .collect();
+        runs.sort_by(|a, b| b.started.cmp(&a.started).then(b.id.cmp(&a.id)));
+        runs
+    }
+
+    /// A recorded run by id, or the newest one for `latest`
+    pub fn load(&self, id: &str) -> Result<RunRecord, String> {
+        if id == "latest" {
+            return self
+                .list()
+                .into_iter()
+                .next()
+                .ok_or_else(|| format!("No runs recorded in {}", self.root.display()));
+        }
+        let file = self.root.join(id).join(RUN_FILE);
+        let json = fs::read_to_string(&file).map_err(|_| format!("No recorded run '{}'", id))?;
+        serde_json::from_str(&json).map_err(|e| format!("Failed to read {}: {}", file.display(), e))
+    }
+
+    /// The run of the same workflow before `run`
+    pub fn previous(&self, run: &RunRecord) -> Option<RunRecord> {
+        self.list()
+            .into_iter()
+            .skip_while(|other| other.id != run.id)
+            .skip(1)
+            .find(|other| other.path == run.path)
+    }
+
+    /// `github.run_number` of the next run of the workflow file named `workflow`: one
+    /// more than its recorded runs
+    pub fn next_run_number(&self, workflow: &str) -> usize {
+        self.list()
+            .iter()
+            .filter(|run| run.workflow == workflow)
+            .count()
+            + 1
+    }
+
+    /// Whether the step logs of `run` were saved
+    pub fn has_logs(&self, run: &RunRecord) -> bool {
+        self.root.join(&run.id).join(LOGS_FILE).is_file()
+    }
+
+    /// Log of a job of a run, or of one of its steps (numbered from 1)
+    pub fn read_log(
+        &self,
+        run: &RunRecord,
+        job: &str,
+        step: Option<usize>,
+    ) -> Result<String, String> {
+        let job_idx = run
+            .jobs
+            .iter()
+            .position(|record| record.name == job)
+            .ok_or_else(|| format!("Run {} has no job '{}'", run.id, job))?;
+        let name = match step {
+            None => job_log_name(job_idx, job),
+            Some(number) => {
+                let step = number
+                    .checked_sub(1)
+                    .and_then(|idx| run.jobs[job_idx].steps.get(idx))
+                    .ok_or_else(|| format!("Job '{}' has no step {}", job, number))?;
+                step_log_name(job, number, &step.name)
+            }
+        };
+
+        if !self.has_logs(run) {
+            return Err(format!(
+                "Run {} was recorded without logs; use `wrkflw run --save-logs`",
+                run.id
+            ));
+        }
+        let file = self.root.join(&run.id).join(LOGS_FILE);
+        let archive = fs::File::open(&file)
+            .map_err(|e| format!("Failed to open {}: {}", file.display(), e))?;
+        let mut archive = ZipArchive::new(archive)
+            .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+        let mut entry = archive
+            .by_name(&name)
+            .map_err(|e| format!("{} in {}: {}", name, file.display(), e))?;
+        let mut log = String::new();
+        entry
+            .read_to_string(&mut log)
+            .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+        Ok(log)
+    }
+}
+
+/// What changed from one run to another
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunChange {
+    JobAdded(String),
+    JobRemoved(String),
+    StepAdded {
+        job: String,
+        step: String,
+    },
+    StepRemoved {
+        job: String,
+        step: String,
+    },
+    /// A job, or one of its steps, ended differently
+    Status {
+        job: String,
+        step: Option<String>,
+        from: String,
+        to: String,
+    },
+    /// A job took noticeably longer or shorter
+    Duration {
+        job: String,
+        from_secs: f64,
+        to_secs: f64,
+    },
+}
+
+impl fmt::Display for RunChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunChange::JobAdded(job) => write!(f, "+ job {}", job),
+            RunChange::JobRemoved(job) => write!(f, "- job {}", job),
+            RunChange::StepAdded { job, step } => write!(f, "+ step {} › {}", job, step),
+            RunChange::StepRemoved { job, step } => write!(f, "- step {} › {}", job, step),
+            RunChange::Status {
+                job,
+                step: None,
+                from,
+                to,
+            } => write!(f, "~ job {}: {} → {}", job, from, to),
+            RunChange::Status {
+                job,
+                step: Some(step),
+                from,
+                to,
+            } => write!(f, "~ step {} › {}: {} → {}", job, step, from, to),
+            RunChange::Duration {
+                job,
+                from_secs,
+                to_secs,
+            } => write!(
+                f,
+                "~ job {}: {:.1}s → {:.1}s ({:+.1}s)",
+                job,
+                from_secs,
+                to_secs,
+                to_secs - from_secs
+            ),
+        }
+    }
+}
+
+/// Changes from run `from` to run `to`, matching jobs and steps by name
+pub fn diff_runs(from: &RunRecord, to: &RunRecord) -> Vec<RunChange> {
+    let mut changes = Vec::new();
+    for job in &to.jobs {
+        let Some(before) = from.jobs.iter().find(|before| before.name == job.name) else {
+            changes.push(RunChange::JobAdded(job.name.clone()));
+            continue;
+        };
+        if before.status != job.status {
+            changes.push(RunChange::Status {
+                job: job.name.clone(),
+                step: None,
+                from: before.status.clone(),
+                to: job.status.clone(),
+            });
+        }
+        for step in &job.steps {
+            match before.steps.iter().find(|b| b.name == step.name) {
+                None => changes.push(RunChange::StepAdded {
+                    job: job.name.clone(),
+                    step: step.name.clone(),
+                }),
+                Some(b) if b.status != step.status => changes.push(RunChange::Status {
+                    job: job.name.clone(),
+                    step: Some(step.name.clone()),
+                    from: b.status.clone(),
+                    to: step.status.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+        for step in &before.steps {
+            if !job.steps.iter().any(|s| s.name == step.name) {
+                changes.push(RunChange::StepRemoved {
+                    job: job.name.clone(),
+                    step: step.name.clone(),
+                });
+            }
+        }
+        if (job.duration_secs - before.duration_secs).abs() >= DURATION_NOISE_SECS {
+            changes.push(RunChange::Duration {
+                job: job.name.clone(),
+                from_secs: before.duration_secs,
+                to_secs: job.duration_secs,
+            });
+        }
+    }
+    for job in &from.jobs {
+        if !to.jobs.iter().any(|j| j.name == job.name) {
+            changes.push(RunChange::JobRemoved(job.name.clone()));
+        }
+    }
+    changes
+}
+
+/// Id of a run started at `started`: its time, with a suffix if a directory of
+/// `root` already has that name
+pub(crate) fn new_run_id(root: &Path, started: DateTime<Local>) -> String {
+    let base = started.format("%Y%m%d-%H%M%S").to_string();
+    let mut id = base.clone();
+    let mut suffix = 1;
+    while root.join(&id).exists() {
+        suffix += 1;
+        id = format!("{}-{}", base, suffix);
+    }
+    id
+}
+
+/// Write `resume` to `dir`, with copies of the workspaces of the failed jobs; jobs
+/// whose workspace can't be copied rerun from the start
+fn save_resume(dir: &Path, resume: &Resume) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let mut saved = resume.clone();
+    saved.jobs.retain(|job, point| {
+        let Some(workspace) = point.workspace.take() else {
+            return true;
+        };
+        let copy = dir.join(file_name(job));
+        match resume::copy_tree(workspace.path(), &copy) {
+            Ok(()) => true,
+            Err(e) => {
+                wrkflw_logging::warning(&format!(
+                    "Failed to keep the workspace of job '{}' for a rerun: {}",
+                    job, e
+                ));
+                let _ = fs::remove_dir_all(&copy);
+                false
+            }
+        }
+    });
+    let json = serde_json::to_string_pretty(&saved)
+        .map_err(|e| format!("Failed to serialize the run: {}", e))?;
+    let file = dir.join(RESUME_FILE);
+    fs::write(&file, json).map_err(|e| format!("Failed to write {}: {}", file.display(), e))
+}
+
+/// `HEAD` of the repository containing `path`
+fn current_commit(path: &Path) -> Option<String> {
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn job_status(status: &JobStatus) -> &'static str {
+    match status {
+        JobStatus::Success => "success",
+        JobStatus::Failure => "failure",
+        JobStatus::Skipped => "skipped",
+        JobStatus::Cancelled => "cancelled",
+    }
+}
+
+fn step_status(status: &StepStatus) -> &'static str {
+    match status {
+        StepStatus::Success => "success",
+        StepStatus::Failure => "failure",
+        StepStatus::Skipped => "skipped",
+    }
+}
+
+/// GitHub drops characters that aren't allowed in file names on some platforms
+fn file_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|'))
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn job_log_name(job_idx: usize, job: &str) -> String {
+    format!("{}_{}.txt", job_idx, file_name(job))
+}
+
+fn step_log_name(job: &str, number: usize, step: &str) -> String {
+    format!("{}/{}_{}.txt", file_name(job), number, file_name(step))
+}
+
+/// Lines prefixed with when they were logged, as GitHub does; output isn't timed
+/// line by line, so every line of a step gets the time the step started
+fn timestamped(text: &str, at: DateTime<Utc>) -> String {
+    let stamp = at.to_rfc3339_opts(SecondsFormat::Nanos, true);
+    let mut out = String::new();
+    for line in text.lines() {
+        out.push_str(&stamp);
+        out.push(' ');
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+fn write_logs(file: &Path, started: DateTime<Utc>, result: &ExecutionResult) -> Result<(), String> {
+    let archive = fs::File::create(file)
+        .map_err(|e| format!("Failed to create {}: {}", file.display(), e))?;
+    let mut zip = zip::ZipWriter::new(archive);
+    let options = SimpleFileOptions::default();
+    let mut add = |name: &str, contents: &str| -> Result<(), String> {
+        zip.start_file(name, options)
+            .and_then(|_| Ok(zip.write_all(contents.as_bytes())?))
+            .map_err(|e| format!("Failed to add {} to {}: {}", name, file.display(), e))
+    };
+
+    for (job_idx, job) in result.jobs.iter().enumerate() {
+        let mut at = started + ChronoDuration::from_std(job.timing.queued).unwrap_or_default();
+        let mut job_log = String::new();
+        for (idx, step) in job.steps.iter().enumerate() {
+            let mut step_log = timestamped(&format!("##[group]Run {}", step.name), at);
+            step_log.push_str(&timestamped(&step.output, at));
+            if step.status == StepStatus::Failure {
+                step_log.push_str(&timestamped("##[error]Step failed", at));
+            }
+            add(&step_log_name(&job.name, idx + 1, &step.name), &step_log)?;
+            job_log.push_str(&step_log);
+            at += ChronoDuration::from_std(step.duration).unwrap_or_default();
+        }
+        if job.steps.is_empty() {
+            job_log.push_str(&timestamped(&job.logs, at));
+        }
+        add(&job_log_name(job_idx, &job.name), &job_log)?;
+    }
+    zip.finish()
+        .map_err(|e| format!("Failed to write {}: {}", file.display(), e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{JobResult, StepResult};
+    use crate::timing::JobTiming;
+    use std::time::Duration;
+
+    #[test]
+    fn test_save_list_and_read_logs() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = RunStore::for_project(dir.path());
+        let result = ExecutionResult {
+            jobs: vec![JobResult {
+                name: "build".to_string(),
+                status: JobStatus::Failure,
+                steps: vec![
+                    StepResult {
+                        name: "Checkout".to_string(),
+                        status: StepStatus::Success,
+                        output: "checked out".to_string(),
+                        duration: Duration::from_secs(1),
+                    },
+                    StepResult {
+                        name: "Run cargo test / unit".to_string(),
+                        status: StepStatus::Failure,
+                        output: "test a ... ok\ntest b ... FAILED".to_string(),
+                        duration: Duration::from_secs(2),
+                    },
+                ],
+                logs: String::new(),
+                environment: Some("staging".to_string()),
+                timing: JobTiming::default(),
+            }],
+            failure_details: Some("build failed".to_string()),
+            duration: Duration::from_secs(3),
+            deployments: Vec::new(),
+            resume: Default::default(),
+        };
+
+        let first = store
+            .save(Path::new("ci.yml"), &result, "docker", true)
+            .unwrap();
+        let second = store
+            .save(Path::new("ci.yml"), &result, "emulation", false)
+            .unwrap();
+        assert_ne!(first.id, second.id);
+        assert!(!first.success);
+        assert_eq!(second.runtime, "emulation");
+        assert!(store.read_log(&second, "build", None).is_err());
+        assert_eq!(store.previous(&second).unwrap().id, first.id);
+        assert!(store.previous(&first).is_none());
+
+        let runs = store.list();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(store.next_run_number("ci.yml"), 3);
+        assert_eq!(store.next_run_number("release.yml"), 1);
+        assert_eq!(store.load("latest").unwrap().id, second.id);
+
+        let run = store.load(&first.id).unwrap();
+        assert_eq!(run.jobs[0].environment.as_deref(), Some("staging"));
+        let step = store.read_log(&run, "build", Some(2)).unwrap();
+        let lines: Vec<&str> = step.lines().collect();
+        assert!(lines[0].ends_with("Z ##[group]Run Run cargo test / unit"));
+        assert!(lines[2].ends_with(" test b ... FAILED"));
+        assert!(lines[3].ends_with(" ##[error]Step failed"));
+
+        let job = store.read_log(&run, "build", None).unwrap();
+        assert!(job.contains("checked out") && job.contains("FAILED"));
+
+        let archive = fs::File::open(store.root().join(&run.id).join(LOGS_FILE)).unwrap();
+        let archive = ZipArchive::new(archive).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "0_build.txt",
+                "build/1_Checkout.txt",
+                "build/2_Run cargo test  unit.txt"
+            ]
+        );
+        assert!(store.read_log(&run, "build", Some(3)).is_err());
+    }
+
+    #[test]
+    fn test_resume_keeps_the_latest_failed_workspaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = RunStore::for_project(dir.path());
+        let job_dir = tempfile::tempdir().unwrap();
+        fs::write(job_dir.path().join(".built"), "yes").unwrap();
+        let job = |name: &str, status| JobResult {
+            name: name.to_string(),
+            status,
+            steps: Vec::new(),
+            logs: String::new(),
+            environment: None,
+            timing: JobTiming::default(),
+        };
+        let result = ExecutionResult {
+            jobs: vec![
+                job("lint", JobStatus::Success),
+                job("test (1)", JobStatus::Failure),
+            ],
+            failure_details: Some("test failed".to_string()),
+            duration: Duration::from_secs(1),
+            deployments: Vec::new(),
+            resume: Resume {
+                succeeded: ["lint".to_string()].into(),
+                jobs: [(
+                    "test (1)".to_string(),
+                    resume::ResumePoint {
+                        step: 2,
+                        env: [("VERSION".to_string(), "1.2".to_string())].into(),
+                        workspace: Some(JobWorkspace::Kept(std::sync::Arc::new(job_dir))),
+                    },
+                )]
+                .into(),
+            },
+        };
+
+        let first = store
+            .save(Path::new("ci.yml"), &result, "emulation", false)
+            .unwrap();
+        let resume = store.resume(&first).unwrap();
+        assert!(resume.succeeded.contains("lint"));
+        let point = &resume.jobs["test (1)"];
+        assert_eq!((point.step, point.env["VERSION"].as_str()), (2, "1.2"));
+        let workspace = point.workspace.as_ref().unwrap().path();
+        assert_eq!(fs::read_to_string(workspace.join(".built")).unwrap(), "yes");
+
+        // A later run of the workflow drops the workspaces of the earlier one
+        store
+            .save(Path::new("ci.yml"), &result, "emulation", false)
+            .unwrap();
+        let resume = store.resume(&first).unwrap();
+        assert!(resume.succeeded.contains("lint") && resume.jobs.is_empty());
+        assert!(!workspace.exists());
+
+        let passed = ExecutionResult {
+            failure_details: None,
+            ..result
+        };
+        let run = store
+            .save(Path::new("ci.yml"), &passed, "emulation", false)
+            .unwrap();
+        assert!(store.resume(&run).is_err());
+    }
+
+    #[test]
+    fn test_diff_runs() {
+        let step = |name: &str, status: &str| StepRecord {
+            name: name.to_string(),
+            status: status.to_string(),
+            duration_secs: 1.0,
+        };
+        let job = |name: &str, status: &str, duration_secs, steps| JobRecord {
+            name: name.to_string(),
+            status: status.to_string(),
+            duration_secs,
+            environment: None,
+            steps,
+        };
+        let run = |jobs| RunRecord {
+            id: "1".to_string(),
+            workflow: "ci.yml".to_string(),
+            path: PathBuf::from("ci.yml"),
+            commit: None,
+            runtime: "docker".to_string(),
+            started: Local::now(),
+            duration_secs: 10.0,
+            success: true,
+            jobs,
+        };
+        let from = run(vec![
+            job(
+                "build",
+                "success",
+                5.0,
+                vec![step("Checkout", "success"), step("Lint", "success")],
+            ),
+            job("docs", "success", 1.0, Vec::new()),
+        ]);
+        let to = run(vec![
+            job(
+                "build",
+                "failure",
+                5.4,
+                vec![step("Checkout", "success"), step("Test", "failure")],
+            ),
+            job("deploy", "skipped", 0.0, Vec::new()),
+        ]);
+
+        let changes: Vec<String> = diff_runs(&from, &to)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                "~ job build: success → failure",
+                "+ step build › Test",
+                "- step build › Lint",
+                "+ job deploy",
+                "- job docs",
+            ]
+        );
+        assert!(diff_runs(&from, &from).is_empty());
+    }
+}
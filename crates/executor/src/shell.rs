@@ -0,0 +1,100 @@
+//! Command lines for the `shell:` of `run:` steps, following the GitHub runner.
+
+/// Argv running `script` under `shell`, or the default shell when unset
+///
+/// Custom shells are templates such as `perl {0}`, where `{0}` is replaced with the
+/// path of a file holding the script; `write_script` creates that file from the
+/// script and returns its path as seen by the step.
+pub fn shell_command<F>(
+    shell: Option<&str>,
+    script: &str,
+    write_script: F,
+) -> Result<Vec<String>, String>
+where
+    F: FnOnce(&str) -> Result<String, String>,
+{
+    let argv = |parts: &[&str]| parts.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+
+    let command = match shell.map(str::trim) {
+        None => argv(&["bash", "-e", "-c", script]),
+        Some("bash") => argv(&[
+            "bash",
+            "--noprofile",
+            "--norc",
+            "-eo",
+            "pipefail",
+            "-c",
+            script,
+        ]),
+        Some("sh") => argv(&["sh", "-e", "-c", script]),
+        Some(shell @ ("pwsh" | "powershell")) => {
+            // The runner stops on errors and reports the last native exit code
+            let wrapped = format!(
+                "$ErrorActionPreference = 'stop'\n{}\nif ((Test-Path -LiteralPath variable:\\LASTEXITCODE)) {{ exit $LASTEXITCODE }}",
+                script
+            );
+            argv(&[shell, "-command", &wrapped])
+        }
+        Some("python") => argv(&["python", "-c", script]),
+        Some("cmd") => argv(&["cmd", "/D", "/E:ON", "/V:OFF", "/S", "/C", script]),
+        Some(template) if template.contains("{0}") => {
+            let path = write_script(script)?;
+            template
+                .split_whitespace()
+                .map(|part| part.replace("{0}", &path))
+                .collect()
+        }
+        Some(other) => {
+            return Err(format!(
+                "Unsupported shell '{}'; use bash, sh, pwsh, powershell, python, cmd or a template such as '{} {{0}}'",
+                other, other
+            ))
+        }
+    };
+    Ok(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_file(_: &str) -> Result<String, String> {
+        panic!("only custom shells write a script file")
+    }
+
+    #[test]
+    fn test_builtin_shells() {
+        assert_eq!(
+            shell_command(None, "make", no_file).unwrap(),
+            ["bash", "-e", "-c", "make"]
+        );
+        assert_eq!(
+            shell_command(Some("bash"), "make", no_file).unwrap()[..5],
+            ["bash", "--noprofile", "--norc", "-eo", "pipefail"]
+        );
+        assert_eq!(
+            shell_command(Some("sh"), "make", no_file).unwrap(),
+            ["sh", "-e", "-c", "make"]
+        );
+        assert_eq!(
+            shell_command(Some("python"), "print(1)", no_file).unwrap(),
+            ["python", "-c", "print(1)"]
+        );
+
+        let pwsh = shell_command(Some("pwsh"), "Get-ChildItem", no_file).unwrap();
+        assert_eq!(pwsh[..2], ["pwsh", "-command"]);
+        assert!(pwsh[2].contains("Get-ChildItem"));
+    }
+
+    #[test]
+    fn test_custom_shell_template() {
+        let command = shell_command(Some("perl -w {0}"), "print 1;", |script| {
+            assert_eq!(script, "print 1;");
+            Ok("/tmp/step.sh".to_string())
+        })
+        .unwrap();
+        assert_eq!(command, ["perl", "-w", "/tmp/step.sh"]);
+
+        assert!(shell_command(Some("fish"), "ls", no_file).is_err());
+    }
+}
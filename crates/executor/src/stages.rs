@@ -0,0 +1,228 @@
+//! Stages of GitLab pipelines: which jobs run in each and in what order, and which
+//! of them run at all given their `when:` and how the jobs before them went.
+
+use std::collections::BTreeSet;
+use wrkflw_models::gitlab::{Job, Pipeline};
+
+/// Stage of the jobs that don't name one
+pub const DEFAULT_STAGE: &str = "test";
+
+/// Why manual jobs are skipped
+pub const MANUAL_SKIP_REASON: &str = "manual job; run it with --run-manual";
+
+/// Stages of `pipeline` in the order they run: `.pre`, the stages it declares
+/// (build, test and deploy when it declares none), then `.post`
+pub fn stage_names(pipeline: &Pipeline) -> Vec<String> {
+    let declared = match &pipeline.stages {
+        Some(stages) => stages.clone(),
+        None => vec![
+            "build".to_string(),
+            "test".to_string(),
+            "deploy".to_string(),
+        ],
+    };
+    let mut names = vec![".pre".to_string()];
+    names.extend(
+        declared
+            .into_iter()
+            .filter(|stage| stage != ".pre" && stage != ".post"),
+    );
+    names.push(".post".to_string());
+    names
+}
+
+/// The stage `job` runs in
+pub fn stage_of(job: &Job) -> &str {
+    job.stage.as_deref().unwrap_or(DEFAULT_STAGE)
+}
+
+/// The job of `pipeline` a job result named `name` came from; `parallel:` jobs run
+/// as several named after it
+pub fn job_of<'a>(pipeline: &'a Pipeline, name: &str) -> Option<&'a Job> {
+    pipeline.jobs.get(name).or_else(|| {
+        let (base, _) = name.split_once(" (")?;
+        pipeline.jobs.get(base)
+    })
+}
+
+/// The jobs of `pipeline` in batches that run one after the other: stage by stage,
+/// and within a stage, each job after the jobs of its stage it `needs`
+pub fn job_batches(pipeline: &Pipeline) -> Result<Vec<Vec<String>>, String> {
+    let stages = stage_names(pipeline);
+    let position = |stage: &str| stages.iter().position(|name| name == stage);
+
+    let mut jobs: Vec<(&String, &Job)> = pipeline
+        .jobs
+        .iter()
+        .filter(|(name, job)| job.template != Some(true) && !name.starts_with('.'))
+        .collect();
+    jobs.sort_by_key(|(name, _)| name.as_str());
+
+    let mut stage_jobs = vec![Vec::new(); stages.len()];
+    for (name, job) in &jobs {
+        let stage = position(stage_of(job)).ok_or_else(|| {
+            format!(
+                "Job '{}' is in stage '{}', which isn't one of the pipeline's stages ({})",
+                name,
+                stage_of(job),
+                stages.join(", ")
+            )
+        })?;
+        for need in job.needs.iter().flatten() {
+            let Some(needed) = pipeline.jobs.get(need.job()) else {
+                continue;
+            };
+            if position(stage_of(needed)).is_some_and(|needed| needed > stage) {
+                return Err(format!(
+                    "Job '{}' needs '{}', which is in the later stage '{}'",
+                    name,
+                    need.job(),
+                    stage_of(needed)
+                ));
+            }
+        }
+        stage_jobs[stage].push((*name, *job));
+    }
+
+    let mut batches = Vec::new();
+    for (stage, mut pending) in stages.iter().zip(stage_jobs) {
+        let in_stage: BTreeSet<&str> = pending.iter().map(|(name, _)| name.as_str()).collect();
+        let mut done = BTreeSet::new();
+        while !pending.is_empty() {
+            let (ready, waiting): (Vec<_>, Vec<_>) = pending.into_iter().partition(|(_, job)| {
+                job.needs
+                    .iter()
+                    .flatten()
+                    .all(|need| !in_stage.contains(need.job()) || done.contains(need.job()))
+            });
+            if ready.is_empty() {
+                let names: Vec<&str> = waiting.iter().map(|(name, _)| name.as_str()).collect();
+                return Err(format!(
+                    "Jobs of stage '{}' need each other: {}",
+                    stage,
+                    names.join(", ")
+                ));
+            }
+            done.extend(ready.iter().map(|(name, _)| name.as_str()));
+            batches.push(ready.into_iter().map(|(name, _)| name.clone()).collect());
+            pending = waiting;
+        }
+    }
+    Ok(batches)
+}
+
+/// Why `job` doesn't run, if it doesn't, when an earlier job `failed` the pipeline
+pub fn skip_reason(job: &Job, failed: bool, run_manual: bool) -> Option<&'static str> {
+    match job.when.as_deref() {
+        Some("manual") if !run_manual => Some(MANUAL_SKIP_REASON),
+        Some("never") => Some("`when: never`"),
+        Some("always") => None,
+        Some("on_failure") if !failed => Some("it runs only when an earlier job fails"),
+        Some("on_failure") => None,
+        _ if failed => Some("an earlier job failed"),
+        _ => None,
+    }
+}
+
+/// Whether the pipeline goes on when `job` fails: `allow_failure: true`, which
+/// manual jobs default to
+pub fn allows_failure(job: &Job) -> bool {
+    job.allow_failure
+        .unwrap_or(job.when.as_deref() == Some("manual"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pipeline(yaml: &str) -> Pipeline {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".gitlab-ci.yml");
+        std::fs::write(&path, yaml).unwrap();
+        wrkflw_parser::gitlab::parse_pipeline(&path).unwrap()
+    }
+
+    #[test]
+    fn jobs_run_stage_by_stage_after_the_jobs_they_need() {
+        let pipeline = pipeline(
+            r#"
+stages: [build, test]
+compile:
+  stage: build
+  script: [make]
+lint:
+  script: [make lint]
+unit:
+  stage: test
+  needs: [compile]
+  script: [make test]
+report:
+  stage: test
+  needs: [unit]
+  script: [make report]
+cleanup:
+  stage: .post
+  script: [make clean]
+"#,
+        );
+        let batches = job_batches(&pipeline).unwrap();
+        assert_eq!(
+            batches,
+            vec![
+                vec!["compile".to_string()],
+                vec!["lint".to_string(), "unit".to_string()],
+                vec!["report".to_string()],
+                vec!["cleanup".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn needing_a_later_stage_is_an_error() {
+        let pipeline = pipeline(
+            r#"
+compile:
+  stage: build
+  needs: [unit]
+  script: [make]
+unit:
+  stage: test
+  script: [make test]
+"#,
+        );
+        let err = job_batches(&pipeline).unwrap_err();
+        assert!(err.contains("later stage 'test'"), "{}", err);
+    }
+
+    #[test]
+    fn when_and_allow_failure_decide_what_runs() {
+        let pipeline = pipeline(
+            r#"
+deploy:
+  when: manual
+  script: [deploy]
+notify:
+  when: on_failure
+  script: [notify]
+flaky:
+  allow_failure: true
+  script: [flaky]
+"#,
+        );
+        let deploy = &pipeline.jobs["deploy"];
+        assert_eq!(skip_reason(deploy, false, false), Some(MANUAL_SKIP_REASON));
+        assert_eq!(skip_reason(deploy, false, true), None);
+        assert!(allows_failure(deploy));
+
+        let notify = &pipeline.jobs["notify"];
+        assert!(skip_reason(notify, false, false).is_some());
+        assert_eq!(skip_reason(notify, true, false), None);
+
+        let flaky = &pipeline.jobs["flaky"];
+        assert!(allows_failure(flaky));
+        assert_eq!(
+            skip_reason(flaky, true, false),
+            Some("an earlier job failed")
+        );
+    }
+}
@@ -1,11 +1,254 @@
+use crate::environment::{
+    GITHUB_TOKEN_CONTEXT_KEY, GITHUB_TOKEN_PLACEHOLDER_KEY, INPUTS_CONTEXT_KEY,
+    SECRET_ALIASES_CONTEXT_KEY, STEPS_CONTEXT_KEY, VARS_CONTEXT_KEY,
+};
 use lazy_static::lazy_static;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
 use std::collections::HashMap;
 
 lazy_static! {
     static ref MATRIX_PATTERN: Regex =
         Regex::new(r"\$\{\{\s*matrix\.([a-zA-Z0-9_]+)\s*\}\}").unwrap();
+    static ref GITHUB_TOKEN_PATTERN: Regex =
+        Regex::new(r"\$\{\{\s*(?:secrets\.GITHUB_TOKEN|github\.token)\s*\}\}").unwrap();
+    static ref INPUTS_PATTERN: Regex =
+        Regex::new(r"\$\{\{\s*(?:github\.event\.)?inputs\.([a-zA-Z0-9_-]+)\s*\}\}").unwrap();
+    static ref ENV_PATTERN: Regex = Regex::new(r"\$\{\{\s*env\.([a-zA-Z0-9_.-]+)\s*\}\}").unwrap();
+    static ref VARS_PATTERN: Regex = Regex::new(r"\$\{\{\s*vars\.([a-zA-Z0-9_]+)\s*\}\}").unwrap();
+    static ref SECRETS_PATTERN: Regex =
+        Regex::new(r"\$\{\{\s*secrets\.([a-zA-Z0-9_][a-zA-Z0-9_-]*)\s*\}\}").unwrap();
+    static ref GITHUB_PATTERN: Regex = Regex::new(r"\$\{\{\s*github\.([a-z_]+)\s*\}\}").unwrap();
+    static ref STEPS_PATTERN: Regex = Regex::new(
+        r"\$\{\{\s*steps\.([a-zA-Z0-9_-]+)\.((?:outputs\.[a-zA-Z0-9_-]+)|outcome|conclusion)\s*\}\}"
+    )
+    .unwrap();
+    static ref GITHUB_COMPARISON_PATTERN: Regex =
+        Regex::new(r"^\s*(?:\$\{\{)?\s*github\.([a-z_]+)\s*(==|!=)\s*'([^']*)'\s*(?:\}\})?\s*$")
+            .unwrap();
+}
+
+/// Replace `${{ secrets.GITHUB_TOKEN }}` and `${{ github.token }}` with the run's token
+pub fn substitute_github_token(text: &str, token: &str) -> String {
+    GITHUB_TOKEN_PATTERN
+        .replace_all(text, regex::NoExpand(token))
+        .into_owned()
+}
+
+/// Replace `${{ inputs.<name> }}` and `${{ github.event.inputs.<name> }}` with the
+/// run's inputs; like on GitHub, inputs that weren't given are empty
+pub fn substitute_inputs(text: &str, inputs: &HashMap<String, String>) -> String {
+    INPUTS_PATTERN
+        .replace_all(text, |caps: &regex::Captures| {
+            inputs.get(&caps[1]).cloned().unwrap_or_default()
+        })
+        .into_owned()
+}
+
+/// Replace `${{ env.<name> }}` with the variable from `env`; like on GitHub, variables
+/// that aren't set are empty
+pub fn substitute_env(text: &str, env: &HashMap<String, String>) -> String {
+    ENV_PATTERN
+        .replace_all(text, |caps: &regex::Captures| {
+            env.get(&caps[1]).cloned().unwrap_or_default()
+        })
+        .into_owned()
+}
+
+/// Replace `${{ github.<name> }}` with the run's `GITHUB_<NAME>` variable from `env`
+/// (`github.sha` is `GITHUB_SHA`, `github.ref_name` is `GITHUB_REF_NAME`, ...)
+pub fn substitute_github(text: &str, env: &HashMap<String, String>) -> String {
+    GITHUB_PATTERN
+        .replace_all(text, |caps: &regex::Captures| {
+            env.get(&github_variable(&caps[1]))
+                .cloned()
+                .unwrap_or_default()
+        })
+        .into_owned()
+}
+
+/// Replace `${{ vars.<name> }}` with the run's configuration variables; like on
+/// GitHub, variables that aren't set are empty
+pub fn substitute_vars(text: &str, vars: &HashMap<String, String>) -> String {
+    VARS_PATTERN
+        .replace_all(text, |caps: &regex::Captures| {
+            vars.get(&caps[1]).cloned().unwrap_or_default()
+        })
+        .into_owned()
+}
+
+/// Point `${{ secrets.<name> }}` at the secret `aliases` reads it as, leaving the
+/// rest for the secret manager
+pub fn substitute_secret_aliases(text: &str, aliases: &HashMap<String, String>) -> String {
+    SECRETS_PATTERN
+        .replace_all(text, |caps: &regex::Captures| match aliases.get(&caps[1]) {
+            Some(alias) => format!("${{{{ secrets.{} }}}}", alias),
+            None => caps[0].to_string(),
+        })
+        .into_owned()
+}
+
+/// Replace `${{ steps.<id>.outputs.<name> }}`, `${{ steps.<id>.outcome }}` and
+/// `${{ steps.<id>.conclusion }}` with what the job's earlier steps left; like on
+/// GitHub, steps and outputs that don't exist (yet) are empty
+pub fn substitute_steps(text: &str, steps: &HashMap<String, StepContext>) -> String {
+    STEPS_PATTERN
+        .replace_all(text, |caps: &regex::Captures| {
+            steps
+                .get(&caps[1])
+                .and_then(|step| step.get(&caps[2]))
+                .unwrap_or_default()
+        })
+        .into_owned()
+}
+
+/// What a step with an `id:` leaves for the job's later steps
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StepContext {
+    /// What the step wrote to `GITHUB_OUTPUT`
+    pub outputs: HashMap<String, String>,
+    /// `success`, `failure` or `skipped`, before `continue-on-error` is applied
+    pub outcome: String,
+    /// The outcome after `continue-on-error`: a failed step that may fail is a
+    /// `success`
+    pub conclusion: String,
+}
+
+impl StepContext {
+    /// `outcome`, `conclusion` or `outputs.<name>`
+    fn get(&self, property: &str) -> Option<String> {
+        match property {
+            "outcome" => Some(self.outcome.clone()),
+            "conclusion" => Some(self.conclusion.clone()),
+            _ => self
+                .outputs
+                .get(property.strip_prefix("outputs.")?)
+                .cloned(),
+        }
+    }
+}
+
+/// What `${{ }}` expressions can refer to besides `env` and `github`, which the run
+/// carries in its context under `environment::*_CONTEXT_KEY` keys
+#[derive(Debug, Default)]
+pub struct ExpressionContext {
+    pub token: String,
+    pub inputs: HashMap<String, String>,
+    pub vars: HashMap<String, String>,
+    /// Secrets of the job's deployment environment, by the name they are read as
+    pub secrets: HashMap<String, String>,
+    /// The job's earlier steps that have an `id:`, by id
+    pub steps: HashMap<String, StepContext>,
+}
+
+impl ExpressionContext {
+    pub fn from_env(env: &HashMap<String, String>) -> Self {
+        fn json<T: serde::de::DeserializeOwned + Default>(
+            env: &HashMap<String, String>,
+            key: &str,
+        ) -> T {
+            env.get(key)
+                .and_then(|values| serde_json::from_str(values).ok())
+                .unwrap_or_default()
+        }
+        ExpressionContext {
+            token: env
+                .get(GITHUB_TOKEN_CONTEXT_KEY)
+                .cloned()
+                .unwrap_or_default(),
+            inputs: json(env, INPUTS_CONTEXT_KEY),
+            vars: json(env, VARS_CONTEXT_KEY),
+            secrets: json(env, SECRET_ALIASES_CONTEXT_KEY),
+            steps: json(env, STEPS_CONTEXT_KEY),
+        }
+    }
+
+    /// Take the context out of a step's environment: the token, inputs, vars,
+    /// secrets and steps only reach the step through explicit references
+    pub fn take_from(env: &mut HashMap<String, String>) -> Self {
+        let context = Self::from_env(env);
+        for key in [
+            GITHUB_TOKEN_CONTEXT_KEY,
+            GITHUB_TOKEN_PLACEHOLDER_KEY,
+            INPUTS_CONTEXT_KEY,
+            VARS_CONTEXT_KEY,
+            SECRET_ALIASES_CONTEXT_KEY,
+            STEPS_CONTEXT_KEY,
+        ] {
+            env.remove(key);
+        }
+        context
+    }
+
+    /// Resolve the `${{ }}` expressions wrkflw evaluates itself in `text`: the
+    /// token, inputs, vars, steps, `github.*` values and the variables of `env`;
+    /// secrets of the deployment environment are pointed at the secret they are
+    /// read as
+    pub fn substitute(&self, text: &str, env: &HashMap<String, String>) -> String {
+        let text =
+            substitute_secret_aliases(&substitute_github_token(text, &self.token), &self.secrets);
+        let text = substitute_inputs(&text, &self.inputs);
+        let text = substitute_vars(&text, &self.vars);
+        let text = substitute_steps(&text, &self.steps);
+        substitute_env(&substitute_github(&text, env), env)
+    }
+
+    /// The value of a reference such as `steps.build.outputs.version` or
+    /// `matrix.os`, as `substitute` would resolve `${{ <reference> }}`; `None` for
+    /// contexts wrkflw doesn't evaluate itself
+    pub fn lookup(&self, reference: &str, env: &HashMap<String, String>) -> Option<String> {
+        let (context, name) = reference.split_once('.')?;
+        let value =
+            |values: &HashMap<String, String>| values.get(name).cloned().unwrap_or_default();
+        match context {
+            "env" => Some(value(env)),
+            "vars" => Some(value(&self.vars)),
+            "inputs" => Some(value(&self.inputs)),
+            "matrix" => Some(
+                env.get(&format!("MATRIX_{}", name.to_uppercase()))
+                    .cloned()
+                    .unwrap_or_default(),
+            ),
+            "steps" => {
+                let (id, property) = name.split_once('.')?;
+                Some(
+                    self.steps
+                        .get(id)
+                        .and_then(|step| step.get(property))
+                        .unwrap_or_default(),
+                )
+            }
+            "github" => match name {
+                "token" => Some(self.token.clone()),
+                _ if name.starts_with("event.inputs.") => Some(
+                    self.inputs
+                        .get(&name["event.inputs.".len()..])
+                        .cloned()
+                        .unwrap_or_default(),
+                ),
+                _ if name.contains('.') => None,
+                _ => Some(env.get(&github_variable(name)).cloned().unwrap_or_default()),
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Evaluate a condition of the form `github.<name> == 'value'` (or `!=`) against
+/// the run's `GITHUB_*` variables; `None` for anything else
+pub fn evaluate_github_comparison(condition: &str, env: &HashMap<String, String>) -> Option<bool> {
+    let caps = GITHUB_COMPARISON_PATTERN.captures(condition)?;
+    let value = env
+        .get(&github_variable(&caps[1]))
+        .map(String::as_str)
+        .unwrap_or_default();
+    let equal = value == &caps[3];
+    Some(if &caps[2] == "==" { equal } else { !equal })
+}
+
+fn github_variable(name: &str) -> String {
+    format!("GITHUB_{}", name.to_uppercase())
 }
 
 /// Preprocesses a command string to replace GitHub-style matrix variable references
@@ -98,6 +341,124 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_substitute_inputs() {
+        let inputs = HashMap::from([("environment".to_string(), "staging".to_string())]);
+        assert_eq!(
+            substitute_inputs(
+                "deploy ${{ inputs.environment }} ${{github.event.inputs.environment}} '${{ inputs.missing }}' ${{ matrix.os }}",
+                &inputs
+            ),
+            "deploy staging staging '' ${{ matrix.os }}"
+        );
+    }
+
+    #[test]
+    fn test_substitute_env() {
+        let env = HashMap::from([("API_URL".to_string(), "http://localhost".to_string())]);
+        assert_eq!(
+            substitute_env(
+                "curl ${{ env.API_URL }}/health${{env.MISSING}} ${{ inputs.name }}",
+                &env
+            ),
+            "curl http://localhost/health ${{ inputs.name }}"
+        );
+    }
+
+    #[test]
+    fn test_substitute_github() {
+        let env = HashMap::from([
+            ("GITHUB_SHA".to_string(), "abc123".to_string()),
+            ("GITHUB_REF_NAME".to_string(), "main".to_string()),
+        ]);
+        let context = ExpressionContext {
+            token: "ghs_token".to_string(),
+            vars: HashMap::from([("REGION".to_string(), "eu".to_string())]),
+            secrets: HashMap::from([("API_KEY".to_string(), "vault:PROD_API_KEY".to_string())]),
+            ..Default::default()
+        };
+        assert_eq!(
+            context.substitute(
+                "${{ github.sha }}@${{github.ref_name}} ${{ github.token }} ${{ github.event.inputs.x }} ${{ vars.REGION }}${{ vars.MISSING }}",
+                &env
+            ),
+            "abc123@main ghs_token  eu"
+        );
+        assert_eq!(
+            context.substitute(
+                "${{ secrets.API_KEY }} ${{secrets.OTHER}} ${{ secrets.GITHUB_TOKEN }}",
+                &env
+            ),
+            "${{ secrets.vault:PROD_API_KEY }} ${{secrets.OTHER}} ghs_token"
+        );
+    }
+
+    #[test]
+    fn test_substitute_steps() {
+        let context = ExpressionContext {
+            steps: HashMap::from([(
+                "build".to_string(),
+                StepContext {
+                    outputs: HashMap::from([("version".to_string(), "1.2.3".to_string())]),
+                    outcome: "failure".to_string(),
+                    conclusion: "success".to_string(),
+                },
+            )]),
+            ..Default::default()
+        };
+        let env = HashMap::new();
+        assert_eq!(
+            context.substitute(
+                "v${{ steps.build.outputs.version }} ${{steps.build.outcome}}/${{ steps.build.conclusion }} '${{ steps.build.outputs.missing }}${{ steps.later.outcome }}'",
+                &env
+            ),
+            "v1.2.3 failure/success ''"
+        );
+        assert_eq!(
+            context.lookup("steps.build.outputs.version", &env),
+            Some("1.2.3".to_string())
+        );
+        assert_eq!(
+            context.lookup("steps.later.conclusion", &env),
+            Some(String::new())
+        );
+        assert_eq!(context.lookup("needs.build.result", &env), None);
+    }
+
+    #[test]
+    fn test_evaluate_github_comparison() {
+        let env = HashMap::from([("GITHUB_REF".to_string(), "refs/heads/main".to_string())]);
+        assert_eq!(
+            evaluate_github_comparison("github.ref == 'refs/heads/main'", &env),
+            Some(true)
+        );
+        assert_eq!(
+            evaluate_github_comparison("${{ github.ref != 'refs/heads/main' }}", &env),
+            Some(false)
+        );
+        assert_eq!(
+            evaluate_github_comparison("github.event_name == 'push'", &env),
+            Some(false)
+        );
+        assert_eq!(
+            evaluate_github_comparison("github.ref == 'a' && always()", &env),
+            None
+        );
+    }
+
+    #[test]
+    fn test_substitute_github_token() {
+        let text = "curl -H \"Authorization: token ${{ secrets.GITHUB_TOKEN }}\" && echo ${{github.token}}";
+        assert_eq!(
+            substitute_github_token(text, "ghs_abc$1"),
+            "curl -H \"Authorization: token ghs_abc$1\" && echo ghs_abc$1"
+        );
+        assert_eq!(
+            substitute_github_token("${{ secrets.OTHER }}", "t"),
+            "${{ secrets.OTHER }}"
+        );
+    }
+
     #[test]
     fn test_process_without_matrix() {
         let cmd = "echo \"Value: ${{ matrix.value }}\"";
@@ -0,0 +1,330 @@
+//! Where the time of a run went: how long each job waited for the jobs it needs,
+//! pulled images and ran, and how long each of its steps took.
+
+use crate::engine::{ExecutionResult, JobResult};
+use serde::Serialize;
+use std::cell::Cell;
+use std::fmt;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+tokio::task_local! {
+    static RUN_STARTED: Instant;
+    static IMAGE_PULLS: Cell<Duration>;
+}
+
+/// Time spent on one job
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct JobTiming {
+    /// From the start of the run until the job started
+    pub queued: Duration,
+    /// Pulling images, part of `duration`
+    pub image_pull: Duration,
+    /// From the start of the job until it finished
+    pub duration: Duration,
+}
+
+/// Run `run` as a workflow run, the reference point of its jobs' queue times
+pub(crate) async fn timed_run<F: Future>(run: F) -> (F::Output, Duration) {
+    let started = Instant::now();
+    let output = match RUN_STARTED.try_with(|_| ()) {
+        // Jobs of a called workflow are queued relative to the outermost run
+        Ok(()) => run.await,
+        Err(_) => RUN_STARTED.scope(started, run).await,
+    };
+    (output, started.elapsed())
+}
+
+/// Run `job`, filling in the timing of the result
+pub(crate) async fn timed_job<F, E>(job: F) -> Result<JobResult, E>
+where
+    F: Future<Output = Result<JobResult, E>>,
+{
+    let queued = RUN_STARTED
+        .try_with(|started| started.elapsed())
+        .unwrap_or_default();
+    let started = Instant::now();
+    let (result, image_pull) = IMAGE_PULLS
+        .scope(Cell::new(Duration::ZERO), async {
+            let result = job.await;
+            (result, IMAGE_PULLS.with(Cell::get))
+        })
+        .await;
+    result.map(|mut job| {
+        job.timing = JobTiming {
+            queued,
+            image_pull,
+            duration: started.elapsed(),
+        };
+        job
+    })
+}
+
+/// Add the time an image pull took to the job it was made for
+pub(crate) fn record_image_pull(elapsed: Duration) {
+    let _ = IMAGE_PULLS.try_with(|pulls| pulls.set(pulls.get() + elapsed));
+}
+
+/// Timings of a finished run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimingReport {
+    pub workflow: String,
+    /// Wall-clock time of the whole run, in seconds
+    pub total_secs: f64,
+    pub jobs: Vec<JobTimingReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JobTimingReport {
+    pub name: String,
+    pub status: String,
+    pub queued_secs: f64,
+    pub image_pull_secs: f64,
+    pub duration_secs: f64,
+    pub steps: Vec<StepTimingReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StepTimingReport {
+    pub name: String,
+    pub status: String,
+    pub duration_secs: f64,
+}
+
+impl TimingReport {
+    pub fn new(workflow: &str, result: &ExecutionResult) -> Self {
+        let jobs = result
+            .jobs
+            .iter()
+            .map(|job| JobTimingReport {
+                name: job.name.clone(),
+                status: format!("{:?}", job.status).to_lowercase(),
+                queued_secs: job.timing.queued.as_secs_f64(),
+                image_pull_secs: job.timing.image_pull.as_secs_f64(),
+                duration_secs: job.timing.duration.as_secs_f64(),
+                steps: job
+                    .steps
+                    .iter()
+                    .map(|step| StepTimingReport {
+                        name: step.name.clone(),
+                        status: format!("{:?}", step.status).to_lowercase(),
+                        duration_secs: step.duration.as_secs_f64(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        TimingReport {
+            workflow: workflow.to_string(),
+            total_secs: result.duration.as_secs_f64(),
+            jobs,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Folded stacks (`workflow;job;step milliseconds` per line), the input of
+    /// flamegraph.pl, inferno and speedscope
+    pub fn to_folded(&self) -> String {
+        let frame = |name: &str| name.replace([';', '\n'], " ");
+        let mut out = String::new();
+        for job in &self.jobs {
+            let job_frame = format!("{};{}", frame(&self.workflow), frame(&job.name));
+            let mut accounted = 0;
+            let mut line = |stack: String, secs: f64| {
+                let millis = (secs * 1000.0).round() as u64;
+                if millis > 0 {
+                    out.push_str(&format!("{} {}\n", stack, millis));
+                }
+                millis
+            };
+            accounted += line(format!("{};image pull", job_frame), job.image_pull_secs);
+            for (idx, step) in job.steps.iter().enumerate() {
+                let stack = format!("{};{}. {}", job_frame, idx + 1, frame(&step.name));
+                accounted += line(stack, step.duration_secs);
+            }
+            // Set-up and tear-down outside of any step
+            let total = (job.duration_secs * 1000.0).round() as u64;
+            if total > accounted {
+                out.push_str(&format!("{} {}\n", job_frame, total - accounted));
+            }
+        }
+        out
+    }
+
+    /// The `count` slowest steps, slowest first, with the job they belong to
+    pub fn slowest_steps(&self, count: usize) -> Vec<(&JobTimingReport, &StepTimingReport)> {
+        let mut steps: Vec<_> = self
+            .jobs
+            .iter()
+            .flat_map(|job| job.steps.iter().map(move |step| (job, step)))
+            .filter(|(_, step)| step.status != "skipped")
+            .collect();
+        steps.sort_by(|a, b| b.1.duration_secs.total_cmp(&a.1.duration_secs));
+        steps.truncate(count);
+        steps
+    }
+}
+
+fn secs(secs: f64) -> String {
+    if secs >= 59.95 {
+        let secs = secs.round() as u64;
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{:.1}s", secs)
+    }
+}
+
+impl fmt::Display for TimingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "⏱️  Timing for '{}' ({} in total)",
+            self.workflow,
+            secs(self.total_secs)
+        )?;
+        let width = self
+            .jobs
+            .iter()
+            .map(|job| job.name.chars().count())
+            .chain(
+                self.jobs
+                    .iter()
+                    .flat_map(|job| job.steps.iter())
+                    .map(|step| step.name.chars().count() + 2),
+            )
+            .max()
+            .unwrap_or(0)
+            .max(3);
+        writeln!(
+            f,
+            "  {:<width$}  {:>8}  {:>8}  {:>8}",
+            "Job",
+            "Queued",
+            "Pull",
+            "Total",
+            width = width
+        )?;
+        for job in &self.jobs {
+            writeln!(
+                f,
+                "  {:<width$}  {:>8}  {:>8}  {:>8}",
+                job.name,
+                secs(job.queued_secs),
+                secs(job.image_pull_secs),
+                secs(job.duration_secs),
+                width = width
+            )?;
+            for step in &job.steps {
+                let duration = if step.status == "skipped" {
+                    "-".to_string()
+                } else {
+                    secs(step.duration_secs)
+                };
+                writeln!(
+                    f,
+                    "    {:<width$}  {:>8}  {:>8}  {:>8}",
+                    step.name,
+                    "",
+                    "",
+                    duration,
+                    width = width - 2
+                )?;
+            }
+        }
+
+        let slowest = self.slowest_steps(3);
+        if !slowest.is_empty() {
+            writeln!(f, "\nSlowest steps:")?;
+            for (job, step) in slowest {
+                writeln!(
+                    f,
+                    "  {:>8}  {} › {}",
+                    secs(step.duration_secs),
+                    job.name,
+                    step.name
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{JobStatus, StepResult, StepStatus};
+
+    fn step(name: &str, millis: u64) -> StepResult {
+        StepResult {
+            name: name.to_string(),
+            status: StepStatus::Success,
+            output: String::new(),
+            duration: Duration::from_millis(millis),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timed_job_records_queue_and_pull_time() {
+        let (timing, _) = timed_run(async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            let job = timed_job(async {
+                record_image_pull(Duration::from_millis(5));
+                record_image_pull(Duration::from_millis(7));
+                Ok::<_, ()>(JobResult {
+                    name: "build".to_string(),
+                    status: JobStatus::Success,
+                    steps: Vec::new(),
+                    logs: String::new(),
+                    environment: None,
+                    timing: JobTiming::default(),
+                })
+            })
+            .await;
+            job.unwrap().timing
+        })
+        .await;
+        assert!(timing.queued >= Duration::from_millis(20));
+        assert_eq!(timing.image_pull, Duration::from_millis(12));
+    }
+
+    #[test]
+    fn test_timing_report() {
+        let result = ExecutionResult {
+            jobs: vec![JobResult {
+                name: "build".to_string(),
+                status: JobStatus::Success,
+                steps: vec![step("Checkout", 200), step("Run tests", 1500)],
+                logs: String::new(),
+                environment: None,
+                timing: JobTiming {
+                    queued: Duration::ZERO,
+                    image_pull: Duration::from_millis(300),
+                    duration: Duration::from_millis(2100),
+                },
+            }],
+            failure_details: None,
+            duration: Duration::from_millis(2200),
+            deployments: Vec::new(),
+            resume: Default::default(),
+        };
+        let report = TimingReport::new("CI", &result);
+
+        let folded = report.to_folded();
+        assert!(folded.contains("CI;build;image pull 300\n"));
+        assert!(folded.contains("CI;build;2. Run tests 1500\n"));
+        assert!(folded.contains("CI;build 100\n"));
+
+        let table = report.to_string();
+        assert!(table.contains("2.2s in total"));
+        assert!(table
+            .lines()
+            .any(|line| line.contains("build") && line.ends_with("2.1s")));
+        let slowest = report.slowest_steps(1);
+        assert_eq!(slowest[0].1.name, "Run tests");
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["jobs"][0]["steps"][1]["duration_secs"], 1.5);
+    }
+}
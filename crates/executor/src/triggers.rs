@@ -0,0 +1,252 @@
+//! `trigger:` jobs of GitLab pipelines. A child pipeline made of files of the
+//! project runs along with its parent, and its jobs show up under the trigger job;
+//! a pipeline of another project doesn't run locally, its trigger is only checked.
+
+use crate::engine::{ExecutionResult, JobResult, JobStatus, StepResult, StepStatus};
+use crate::timing::JobTiming;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use wrkflw_models::gitlab::{Include, Pipeline, Trigger, TriggerInclude};
+use wrkflw_parser::gitlab::parse_pipeline;
+
+/// How deep child pipelines may nest, as on GitLab
+pub const MAX_DEPTH: usize = 2;
+
+/// The pipeline a trigger job runs
+#[derive(Debug, PartialEq)]
+pub enum Downstream {
+    /// A child pipeline made of these files of the project
+    Child(Vec<PathBuf>),
+    /// The pipeline of another project
+    Project(String),
+}
+
+/// What the `trigger:` of job `name` runs, its files looked up in `project_dir`
+pub fn downstream(name: &str, trigger: &Trigger, project_dir: &Path) -> Result<Downstream, String> {
+    let include = match trigger {
+        Trigger::Project(project) => return project_downstream(name, project),
+        Trigger::Detailed {
+            project: Some(project),
+            ..
+        } => return project_downstream(name, project),
+        Trigger::Detailed {
+            include: Some(include),
+            ..
+        } => include,
+        Trigger::Detailed { .. } => {
+            return Err(format!(
+                "Job '{}' has a `trigger:` with neither `project` nor `include`",
+                name
+            ))
+        }
+    };
+
+    let locals: Vec<Option<&str>> = match include {
+        TriggerInclude::Local(file) => vec![Some(file.as_str())],
+        TriggerInclude::List(includes) => includes
+            .iter()
+            .map(|include| match include {
+                Include::Local(file) => Some(file.as_str()),
+                Include::Detailed { local, .. } => local.as_deref(),
+            })
+            .collect(),
+    };
+    let mut files = Vec::new();
+    for local in locals {
+        let Some(local) = local else {
+            return Err(format!(
+                "Job '{}' includes its child pipeline from outside the project's files, which only GitLab can resolve",
+                name
+            ));
+        };
+        let file = project_dir.join(local.trim_start_matches('/'));
+        if !file.is_file() {
+            return Err(format!(
+                "Child pipeline file '{}' of job '{}' not found",
+                local, name
+            ));
+        }
+        files.push(file);
+    }
+    if files.is_empty() {
+        return Err(format!(
+            "Job '{}' triggers a child pipeline of no files",
+            name
+        ));
+    }
+    Ok(Downstream::Child(files))
+}
+
+fn project_downstream(name: &str, project: &str) -> Result<Downstream, String> {
+    let project = project.trim().trim_matches('/');
+    if !project.contains('/') || project.split('/').any(str::is_empty) {
+        return Err(format!(
+            "Job '{}' triggers '{}', which isn't a project path like group/project",
+            name, project
+        ));
+    }
+    Ok(Downstream::Project(project.to_string()))
+}
+
+/// Whether the trigger job waits for its pipeline and takes its status
+pub fn depends(trigger: &Trigger) -> bool {
+    matches!(
+        trigger,
+        Trigger::Detailed {
+            strategy: Some(strategy),
+            ..
+        } if strategy == "depend"
+    )
+}
+
+/// The child pipeline made of `files`, merged in order
+pub fn child_pipeline(files: &[PathBuf]) -> Result<Pipeline, String> {
+    let mut merged: Option<Pipeline> = None;
+    for file in files {
+        let pipeline = parse_pipeline(file)
+            .map_err(|e| format!("Failed to parse child pipeline {}: {}", file.display(), e))?;
+        merged = Some(match merged {
+            None => pipeline,
+            Some(mut merged) => {
+                merged.jobs.extend(pipeline.jobs);
+                if let Some(variables) = pipeline.variables {
+                    merged
+                        .variables
+                        .get_or_insert_with(Default::default)
+                        .extend(variables);
+                }
+                merged.stages = merged.stages.or(pipeline.stages);
+                merged
+            }
+        });
+    }
+    merged.ok_or_else(|| "A child pipeline needs at least one file".to_string())
+}
+
+/// Result of the trigger job `name` whose child pipeline ran with `child`: one
+/// step per job of the child
+pub fn trigger_result(name: &str, child: ExecutionResult, depend: bool) -> JobResult {
+    let failed = child.failure_details.is_some();
+    let steps: Vec<StepResult> = child.jobs.iter().map(nested_step).collect();
+    let mut logs = format!(
+        "Child pipeline {}\n",
+        if failed { "failed" } else { "succeeded" }
+    );
+    for job in &child.jobs {
+        logs.push_str(&format!("- {}: {:?}\n", job.name, job.status));
+    }
+    if failed && !depend {
+        logs.push_str("The job doesn't fail with it; `strategy: depend` would make it\n");
+    }
+    JobResult {
+        name: name.to_string(),
+        status: if failed && depend {
+            JobStatus::Failure
+        } else {
+            JobStatus::Success
+        },
+        steps,
+        logs,
+        environment: None,
+        timing: JobTiming::default(),
+    }
+}
+
+/// A job of a child pipeline as a step of its trigger job
+fn nested_step(job: &JobResult) -> StepResult {
+    let mut output = String::new();
+    for step in &job.steps {
+        output.push_str(&format!(
+            "{} {}\n",
+            match step.status {
+                StepStatus::Success => "✅",
+                StepStatus::Failure => "❌",
+                StepStatus::Skipped => "⏭️",
+            },
+            step.name
+        ));
+        if step.status == StepStatus::Failure {
+            output.push_str(&step.output);
+            if !step.output.ends_with('\n') {
+                output.push('\n');
+            }
+        }
+    }
+    if job.steps.is_empty() {
+        output.push_str(&job.logs);
+    }
+    StepResult {
+        name: job.name.clone(),
+        status: match job.status {
+            JobStatus::Success => StepStatus::Success,
+            JobStatus::Failure | JobStatus::Cancelled => StepStatus::Failure,
+            JobStatus::Skipped => StepStatus::Skipped,
+        },
+        output,
+        duration: job.steps.iter().map(|step| step.duration).sum::<Duration>(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trigger(yaml: &str) -> Trigger {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn triggers_run_child_pipelines_of_project_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("ci")).unwrap();
+        std::fs::write(dir.path().join("ci").join("child.yml"), "").unwrap();
+
+        assert_eq!(
+            downstream("docs", &trigger("include: ci/child.yml"), dir.path()),
+            Ok(Downstream::Child(vec![dir
+                .path()
+                .join("ci")
+                .join("child.yml")]))
+        );
+        assert_eq!(
+            downstream(
+                "docs",
+                &trigger("include:\n  - local: /ci/child.yml\nstrategy: depend"),
+                dir.path()
+            ),
+            Ok(Downstream::Child(vec![dir
+                .path()
+                .join("ci")
+                .join("child.yml")]))
+        );
+        assert!(depends(&trigger("include: ci/child.yml\nstrategy: depend")));
+
+        let err = downstream("docs", &trigger("include: ci/missing.yml"), dir.path()).unwrap_err();
+        assert!(err.contains("not found"), "{}", err);
+        let err = downstream(
+            "docs",
+            &trigger("include:\n  - artifact: generated.yml\n    job: generate"),
+            dir.path(),
+        )
+        .unwrap_err();
+        assert!(err.contains("only GitLab"), "{}", err);
+    }
+
+    #[test]
+    fn project_triggers_are_only_checked() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            downstream("deploy", &trigger("acme/deployer"), dir.path()),
+            Ok(Downstream::Project("acme/deployer".to_string()))
+        );
+        assert_eq!(
+            downstream(
+                "deploy",
+                &trigger("project: acme/deployer\nbranch: main"),
+                dir.path()
+            ),
+            Ok(Downstream::Project("acme/deployer".to_string()))
+        );
+        assert!(downstream("deploy", &trigger("deployer"), dir.path()).is_err());
+    }
+}
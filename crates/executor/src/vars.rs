@@ -0,0 +1,145 @@
+//! Configuration variables (`${{ vars.NAME }}`) for local runs, kept in
+//! `.wrkflw/vars.toml` of the project as a flat table:
+//!
+//! ```toml
+//! REGISTRY = "ghcr.io/acme"
+//! DEPLOY_REGION = "eu-west-1"
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the variables file in a project's `.wrkflw` directory
+pub const VARS_FILE: &str = "vars.toml";
+
+/// Name of the file of GitLab CI/CD variables: the project's in its `.wrkflw`
+/// directory, the instance's in `~/.wrkflw`
+pub const GITLAB_VARS_FILE: &str = "gitlab-vars.toml";
+
+/// The variables file of a project
+pub struct VarsFile {
+    path: PathBuf,
+}
+
+impl VarsFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        VarsFile { path: path.into() }
+    }
+
+    /// `.wrkflw/vars.toml` of `project_dir`
+    pub fn for_project(project_dir: &Path) -> Self {
+        Self::new(project_dir.join(".wrkflw").join(VARS_FILE))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Variables by name; none when there is no file
+    pub fn load(&self) -> Result<BTreeMap<String, String>, String> {
+        if !self.path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let content = fs::read_to_string(&self.path)
+            .map_err(|e| format!("Failed to read {}: {}", self.path.display(), e))?;
+        let vars: BTreeMap<String, String> = toml::from_str(&content)
+            .map_err(|e| format!("Invalid {}: {}", self.path.display(), e))?;
+        for name in vars.keys() {
+            validate_name(name).map_err(|e| format!("Invalid {}: {}", self.path.display(), e))?;
+        }
+        Ok(vars)
+    }
+
+    pub fn save(&self, vars: &BTreeMap<String, String>) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let content = toml::to_string(vars)
+            .map_err(|e| format!("Failed to serialize the variables: {}", e))?;
+        fs::write(&self.path, content)
+            .map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))
+    }
+
+    /// Add or change a variable
+    pub fn set(&self, name: &str, value: &str) -> Result<(), String> {
+        validate_name(name)?;
+        let mut vars = self.load()?;
+        vars.insert(name.to_string(), value.to_string());
+        self.save(&vars)
+    }
+
+    /// Remove a variable, returning whether it was there
+    pub fn remove(&self, name: &str) -> Result<bool, String> {
+        let mut vars = self.load()?;
+        if vars.remove(name).is_none() {
+            return Ok(false);
+        }
+        self.save(&vars)?;
+        Ok(true)
+    }
+}
+
+/// Check a variable name against GitHub's rules: letters, digits and `_`, not
+/// starting with a digit or `GITHUB_`
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("a variable name can't be empty".to_string());
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!(
+            "'{}' is not a valid variable name: only letters, digits and _ are allowed",
+            name
+        ));
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(format!("variable name '{}' starts with a digit", name));
+    }
+    if name.to_ascii_uppercase().starts_with("GITHUB_") {
+        return Err(format!(
+            "variable name '{}' starts with GITHUB_, which is reserved",
+            name
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vars_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = VarsFile::for_project(dir.path());
+        assert!(file.load().unwrap().is_empty());
+
+        file.set("REGISTRY", "ghcr.io/acme").unwrap();
+        file.set("REGION", "eu").unwrap();
+        file.set("REGION", "us").unwrap();
+        assert_eq!(
+            file.load().unwrap(),
+            BTreeMap::from([
+                ("REGION".to_string(), "us".to_string()),
+                ("REGISTRY".to_string(), "ghcr.io/acme".to_string()),
+            ])
+        );
+
+        assert!(file.remove("REGION").unwrap());
+        assert!(!file.remove("REGION").unwrap());
+        assert_eq!(file.load().unwrap().len(), 1);
+
+        fs::write(file.path(), "nested = { a = 1 }\n").unwrap();
+        assert!(file.load().is_err());
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("DEPLOY_REGION_2").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("MY-VAR").is_err());
+        assert!(validate_name("2FAST").is_err());
+        assert!(validate_name("github_repo").is_err());
+    }
+}
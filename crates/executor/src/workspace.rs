@@ -0,0 +1,204 @@
+//! What job containers see of the host: the workspace, extra bind mounts, and who
+//! owns the files they leave there.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// How the project gets into the workspace of Docker and Podman jobs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WorkspaceStrategy {
+    /// Mount the project directory itself; steps change the project in place
+    Bind,
+    /// `actions/checkout` copies the project, uncommitted changes included, into a
+    /// directory of the job's own
+    #[default]
+    Copy,
+    /// `actions/checkout` clones the project's committed history into a directory of
+    /// the job's own, leaving uncommitted changes out
+    Clone,
+}
+
+impl WorkspaceStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkspaceStrategy::Bind => "bind",
+            WorkspaceStrategy::Copy => "copy",
+            WorkspaceStrategy::Clone => "clone",
+        }
+    }
+
+    /// The directory mounted as the job's workspace. Emulated jobs keep their own
+    /// directory, as they already run in the project.
+    pub fn job_workspace<'a>(
+        &self,
+        env: &HashMap<String, String>,
+        project_dir: &'a Path,
+        job_dir: &'a Path,
+    ) -> &'a Path {
+        if *self == WorkspaceStrategy::Bind && runs_in_containers(env) {
+            project_dir
+        } else {
+            job_dir
+        }
+    }
+}
+
+/// Who owns the files Docker and Podman jobs create in their workspace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileOwnership {
+    /// Whoever the image runs commands as, often root
+    #[default]
+    Container,
+    /// The invoking user, by running containers as their uid and gid
+    User,
+    /// The invoking user, by handing the workspace back to them after each job;
+    /// commands still run as the image's user
+    Chown,
+}
+
+impl FileOwnership {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FileOwnership::Container => "container",
+            FileOwnership::User => "user",
+            FileOwnership::Chown => "chown",
+        }
+    }
+
+    /// The uid and gid containers run as, when they should run as the invoking user
+    pub fn run_as(&self) -> Option<(u32, u32)> {
+        (*self == FileOwnership::User).then(host_user).flatten()
+    }
+
+    /// The uid and gid to hand the workspace of a finished job back to, if any
+    pub fn chown_as(&self, env: &HashMap<String, String>) -> Option<(u32, u32)> {
+        (*self == FileOwnership::Chown && runs_in_containers(env))
+            .then(host_user)
+            .flatten()
+    }
+}
+
+/// The invoking user's uid and gid. `None` when there's nothing to map: for root,
+/// whom files made by containers already belong to, and off Unix.
+pub fn host_user() -> Option<(u32, u32)> {
+    #[cfg(unix)]
+    {
+        // SAFETY: getuid and getgid always succeed and touch no memory
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+        (uid != 0).then_some((uid, gid))
+    }
+    #[cfg(not(unix))]
+    {
+        None
+    }
+}
+
+fn runs_in_containers(env: &HashMap<String, String>) -> bool {
+    matches!(
+        env.get("WRKFLW_RUNTIME_MODE").map(String::as_str),
+        Some("docker" | "podman")
+    )
+}
+
+/// Check a `--mount` of the form `HOST:CONTAINER[:ro|rw]`, making the host path
+/// absolute against `base_dir` so runtimes don't take it for a named volume
+pub fn parse_mount(spec: &str, base_dir: &Path) -> Result<String, String> {
+    let parts: Vec<&str> = spec.split(':').collect();
+    let (host, container, mode) = match parts.as_slice() {
+        [host, container] => (*host, *container, None),
+        [host, container, mode] => (*host, *container, Some(*mode)),
+        _ => {
+            return Err(format!(
+                "Invalid mount '{}': expected HOST:CONTAINER[:ro|rw]",
+                spec
+            ))
+        }
+    };
+    if host.is_empty() {
+        return Err(format!("Invalid mount '{}': the host path is empty", spec));
+    }
+    if !container.starts_with('/') {
+        return Err(format!(
+            "Invalid mount '{}': the container path must be absolute",
+            spec
+        ));
+    }
+    if let Some(mode) = mode.filter(|mode| !matches!(*mode, "ro" | "rw")) {
+        return Err(format!(
+            "Invalid mount '{}': unknown mode '{}', expected ro or rw",
+            spec, mode
+        ));
+    }
+
+    let host = match host.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        None if host == "~" => dirs::home_dir().unwrap_or_default(),
+        None => base_dir.join(host),
+    };
+    let mut mount = format!("{}:{}", display(&host), container);
+    if let Some(mode) = mode {
+        mount.push(':');
+        mount.push_str(mode);
+    }
+    Ok(mount)
+}
+
+fn display(path: &Path) -> String {
+    // Drop `.` components, which `join` keeps, without resolving symlinks
+    path.components()
+        .filter(|component| *component != std::path::Component::CurDir)
+        .collect::<PathBuf>()
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mount() {
+        let base = Path::new("/home/me/project");
+        assert_eq!(
+            parse_mount("/srv/data:/data", base),
+            Ok("/srv/data:/data".to_string())
+        );
+        assert_eq!(
+            parse_mount("./cache:/cache:ro", base),
+            Ok("/home/me/project/cache:/cache:ro".to_string())
+        );
+        assert!(parse_mount("/srv/data", base).is_err());
+        assert!(parse_mount("/srv/data:data", base).is_err());
+        assert!(parse_mount("/srv/data:/data:rx", base).is_err());
+        assert!(parse_mount(":/data", base).is_err());
+    }
+
+    #[test]
+    fn test_job_workspace() {
+        let (project, job) = (Path::new("/project"), Path::new("/tmp/job"));
+        let env =
+            |mode: &str| HashMap::from([("WRKFLW_RUNTIME_MODE".to_string(), mode.to_string())]);
+        let strategy = WorkspaceStrategy::Bind;
+        assert_eq!(
+            strategy.job_workspace(&env("podman"), project, job),
+            project
+        );
+        assert_eq!(strategy.job_workspace(&env("emulation"), project, job), job);
+        assert_eq!(
+            WorkspaceStrategy::Copy.job_workspace(&env("docker"), project, job),
+            job
+        );
+    }
+
+    #[test]
+    fn test_file_ownership() {
+        let env =
+            |mode: &str| HashMap::from([("WRKFLW_RUNTIME_MODE".to_string(), mode.to_string())]);
+        assert_eq!(FileOwnership::Chown.chown_as(&env("docker")), host_user());
+        assert_eq!(FileOwnership::Chown.chown_as(&env("emulation")), None);
+        assert_eq!(FileOwnership::Container.chown_as(&env("docker")), None);
+        assert_eq!(FileOwnership::Chown.run_as(), None);
+        assert_eq!(FileOwnership::User.run_as(), host_user());
+        assert_eq!(FileOwnership::Container.run_as(), None);
+    }
+}
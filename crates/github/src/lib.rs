@@ -1,5 +1,7 @@
 // github crate
 
+pub mod runs;
+
 use lazy_static::lazy_static;
 use regex::Regex;
 use reqwest::header;
@@ -21,11 +23,16 @@ pub enum GithubError {
     #[error("Failed to parse Git repository URL: {0}")]
     GitParseError(String),
 
-    #[error("GitHub token not found. Please set GITHUB_TOKEN environment variable")]
+    #[error(
+        "GitHub token not found. Set GITHUB_TOKEN or GH_TOKEN, or log in with `gh auth login`"
+    )]
     TokenNotFound,
 
     #[error("API error: {status} - {message}")]
     ApiError { status: u16, message: String },
+
+    #[error("Unexpected API response: {0}")]
+    ResponseError(String),
 }
 
 /// Information about a GitHub repository
@@ -146,11 +153,11 @@ pub async fn trigger_workflow(
     branch: Option<&str>,
     inputs: Option<HashMap<String, String>>,
 ) -> Result<(), GithubError> {
-    // Get GitHub token from environment
-    let token = std::env::var("GITHUB_TOKEN").map_err(|_| GithubError::TokenNotFound)?;
-
-    // Trim the token to remove any leading or trailing whitespace
-    let trimmed_token = token.trim();
+    // Get GitHub token from the environment or the gh CLI
+    let token = wrkflw_utils::auth::github_token()
+        .ok_or(GithubError::TokenNotFound)?
+        .token;
+    let trimmed_token = token.as_str();
 
     // Convert token to HeaderValue
     let token_header = header::HeaderValue::from_str(&format!("Bearer {}", trimmed_token))
@@ -0,0 +1,525 @@
+//! Workflow runs on GitHub: finding the run a dispatch started, following its jobs
+//! and steps until it completes, downloading its logs, and the Actions variables
+//! runs see
+
+use crate::{GithubError, RepoInfo};
+use reqwest::header;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::io::Read;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const API_URL: &str = "https://api.github.com";
+
+/// How often a run is polled while it's followed
+pub const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long a dispatched run may take to show up in the API
+const DISPATCH_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WorkflowRun {
+    pub id: u64,
+    pub run_number: u64,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub event: String,
+    #[serde(default)]
+    pub head_branch: Option<String>,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub conclusion: Option<String>,
+    pub html_url: String,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub run_started_at: Option<String>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+impl WorkflowRun {
+    pub fn is_completed(&self) -> bool {
+        self.status.as_deref() == Some("completed")
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.conclusion.as_deref() == Some("success")
+    }
+
+    /// The conclusion once completed, else the status
+    pub fn state(&self) -> &str {
+        state(self.status.as_deref(), self.conclusion.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RunJob {
+    pub id: u64,
+    pub name: String,
+    pub status: String,
+    #[serde(default)]
+    pub conclusion: Option<String>,
+    #[serde(default)]
+    pub steps: Vec<RunStep>,
+}
+
+impl RunJob {
+    pub fn state(&self) -> &str {
+        state(Some(&self.status), self.conclusion.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RunStep {
+    pub number: u64,
+    pub name: String,
+    pub status: String,
+    #[serde(default)]
+    pub conclusion: Option<String>,
+}
+
+impl RunStep {
+    pub fn state(&self) -> &str {
+        state(Some(&self.status), self.conclusion.as_deref())
+    }
+}
+
+fn state<'a>(status: Option<&'a str>, conclusion: Option<&'a str>) -> &'a str {
+    match (status, conclusion) {
+        (Some("completed"), Some(conclusion)) => conclusion,
+        (Some(status), _) => status,
+        (None, _) => "unknown",
+    }
+}
+
+#[derive(Deserialize)]
+struct RunsPage {
+    workflow_runs: Vec<WorkflowRun>,
+}
+
+#[derive(Deserialize)]
+struct VariablesPage {
+    total_count: usize,
+    variables: Vec<Variable>,
+}
+
+#[derive(Deserialize)]
+struct Variable {
+    name: String,
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct JobsPage {
+    jobs: Vec<RunJob>,
+}
+
+/// A job or step of a followed run that changed state since the last poll
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunUpdate {
+    Job {
+        job: String,
+        state: String,
+    },
+    Step {
+        job: String,
+        step: String,
+        state: String,
+    },
+}
+
+impl RunUpdate {
+    pub fn state(&self) -> &str {
+        match self {
+            RunUpdate::Job { state, .. } | RunUpdate::Step { state, .. } => state,
+        }
+    }
+}
+
+impl fmt::Display for RunUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunUpdate::Job { job, state } => write!(f, "{}: {}", job, state),
+            RunUpdate::Step { job, step, state } => write!(f, "{} › {}: {}", job, step, state),
+        }
+    }
+}
+
+/// Jobs and steps whose state in `after` differs from `before`, new ones included
+pub fn run_updates(before: &[RunJob], after: &[RunJob]) -> Vec<RunUpdate> {
+    let mut updates = Vec::new();
+    for job in after {
+        let previous = before.iter().find(|previous| previous.id == job.id);
+        if previous.is_none_or(|previous| previous.state() != job.state()) {
+            updates.push(RunUpdate::Job {
+                job: job.name.clone(),
+                state: job.state().to_string(),
+            });
+        }
+        for step in &job.steps {
+            let previous_step = previous
+                .and_then(|previous| previous.steps.iter().find(|s| s.number == step.number));
+            // Steps nothing happened to yet aren't news
+            if previous_step.is_none() && step.status == "queued" {
+                continue;
+            }
+            if previous_step.is_none_or(|previous| previous.state() != step.state()) {
+                updates.push(RunUpdate::Step {
+                    job: job.name.clone(),
+                    step: step.name.clone(),
+                    state: step.state().to_string(),
+                });
+            }
+        }
+    }
+    updates
+}
+
+/// File name of a workflow given by name or path, as the API identifies workflows
+pub fn workflow_file(workflow: &str) -> String {
+    let name = Path::new(workflow)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(workflow);
+    if name.ends_with(".yml") || name.ends_with(".yaml") {
+        name.to_string()
+    } else {
+        format!("{}.yml", name)
+    }
+}
+
+/// Access to the Actions runs of a repository, authenticated with the token
+/// `wrkflw_utils::auth::github_token` finds
+pub struct RunsClient {
+    http: reqwest::Client,
+    token: String,
+    repo: RepoInfo,
+}
+
+impl RunsClient {
+    pub fn from_env(repo: RepoInfo) -> Result<Self, GithubError> {
+        let token = wrkflw_utils::auth::github_token().ok_or(GithubError::TokenNotFound)?;
+        Ok(RunsClient {
+            http: reqwest::Client::new(),
+            token: token.token,
+            repo,
+        })
+    }
+
+    pub fn repo(&self) -> &RepoInfo {
+        &self.repo
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!(
+            "{}/repos/{}/{}/actions/{}",
+            API_URL, self.repo.owner, self.repo.repo, path
+        )
+    }
+
+    /// Send an authenticated API request, turning error statuses into errors
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+    ) -> Result<reqwest::Response, GithubError> {
+        let response = self
+            .http
+            .request(method, self.url(path))
+            .header(header::AUTHORIZATION, format!("Bearer {}", self.token))
+            .header(header::ACCEPT, "application/vnd.github+json")
+            .header(header::USER_AGENT, "wrkflw-cli")
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let message = response
+                .text()
+                .await
+                .unwrap_or_else(|_| format!("Unknown error (HTTP {})", status));
+            return Err(GithubError::ApiError { status, message });
+        }
+        Ok(response)
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T, GithubError> {
+        let body = self.send(reqwest::Method::GET, path).await?.text().await?;
+        serde_json::from_str(&body)
+            .map_err(|e| GithubError::ResponseError(format!("{} ({})", e, path)))
+    }
+
+    /// Most recent runs of a workflow, newest first
+    pub async fn workflow_runs(&self, workflow: &str) -> Result<Vec<WorkflowRun>, GithubError> {
+        let page: RunsPage = self
+            .get_json(&format!(
+                "workflows/{}/runs?per_page=20",
+                workflow_file(workflow)
+            ))
+            .await?;
+        Ok(page.workflow_runs)
+    }
+
+    /// Most recent runs of any workflow of the repository, newest first
+    pub async fn recent_runs(&self, limit: usize) -> Result<Vec<WorkflowRun>, GithubError> {
+        let page: RunsPage = self
+            .get_json(&format!("runs?per_page={}", limit.clamp(1, 100)))
+            .await?;
+        Ok(page.workflow_runs)
+    }
+
+    /// Re-run a completed run, or only its failed jobs and the jobs depending on them
+    pub async fn rerun(&self, run_id: u64, failed_only: bool) -> Result<(), GithubError> {
+        let action = if failed_only {
+            "rerun-failed-jobs"
+        } else {
+            "rerun"
+        };
+        self.send(
+            reqwest::Method::POST,
+            &format!("runs/{}/{}", run_id, action),
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn run(&self, run_id: u64) -> Result<WorkflowRun, GithubError> {
+        self.get_json(&format!("runs/{}", run_id)).await
+    }
+
+    /// Jobs of the latest attempt of a run
+    pub async fn run_jobs(&self, run_id: u64) -> Result<Vec<RunJob>, GithubError> {
+        let page: JobsPage = self
+            .get_json(&format!("runs/{}/jobs?per_page=100", run_id))
+            .await?;
+        Ok(page.jobs)
+    }
+
+    /// The `workflow_dispatch` run of `workflow` that isn't among `known`, waiting
+    /// for it to show up, as dispatching doesn't say which run it started
+    pub async fn wait_for_dispatched_run(
+        &self,
+        workflow: &str,
+        known: &HashSet<u64>,
+    ) -> Result<WorkflowRun, GithubError> {
+        let started = Instant::now();
+        loop {
+            let runs = self.workflow_runs(workflow).await?;
+            if let Some(run) = runs
+                .into_iter()
+                .find(|run| run.event == "workflow_dispatch" && !known.contains(&run.id))
+            {
+                return Ok(run);
+            }
+            if started.elapsed() > DISPATCH_TIMEOUT {
+                return Err(GithubError::ResponseError(format!(
+                    "No new run of {} showed up within {}s",
+                    workflow_file(workflow),
+                    DISPATCH_TIMEOUT.as_secs()
+                )));
+            }
+            tokio::time::sleep(Duration::from_secs(2)).await;
+        }
+    }
+
+    /// Poll a run until it completes, passing each change of its jobs and steps to
+    /// `on_update`; returns the completed run and its jobs
+    pub async fn follow(
+        &self,
+        run_id: u64,
+        mut on_update: impl FnMut(&RunUpdate),
+    ) -> Result<(WorkflowRun, Vec<RunJob>), GithubError> {
+        let mut jobs: Vec<RunJob> = Vec::new();
+        loop {
+            let run = self.run(run_id).await?;
+            let current = self.run_jobs(run_id).await?;
+            for update in run_updates(&jobs, &current) {
+                on_update(&update);
+            }
+            jobs = current;
+            if run.is_completed() {
+                return Ok((run, jobs));
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// The Actions variables runs of the repository see: those its organization
+    /// shares with it, overridden by its own
+    pub async fn variables(&self) -> Result<BTreeMap<String, String>, GithubError> {
+        let mut vars = BTreeMap::new();
+        // Repositories of users have no organization variables
+        match self.all_variables("organization-variables").await {
+            Ok(org) => vars.extend(org),
+            Err(GithubError::ApiError {
+                status: 403 | 404, ..
+            }) => {}
+            Err(e) => return Err(e),
+        }
+        vars.extend(self.all_variables("variables").await?);
+        Ok(vars)
+    }
+
+    async fn all_variables(&self, path: &str) -> Result<Vec<(String, String)>, GithubError> {
+        let mut vars = Vec::new();
+        for page in 1.. {
+            let page: VariablesPage = self
+                .get_json(&format!("{}?per_page=30&page={}", path, page))
+                .await?;
+            let done =
+                page.variables.is_empty() || vars.len() + page.variables.len() >= page.total_count;
+            vars.extend(page.variables.into_iter().map(|var| (var.name, var.value)));
+            if done {
+                break;
+            }
+        }
+        Ok(vars)
+    }
+
+    /// Download the log archive of a run to `dest`
+    pub async fn download_logs(&self, run_id: u64, dest: &Path) -> Result<(), GithubError> {
+        // Answered with a redirect to the archive, which reqwest follows without
+        // sending the token along to the other host
+        let bytes = self
+            .send(reqwest::Method::GET, &format!("runs/{}/logs", run_id))
+            .await?
+            .bytes()
+            .await?;
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(dest, &bytes)?;
+        Ok(())
+    }
+}
+
+/// Where the log archive of a run is saved in `project_dir`
+pub fn logs_file(project_dir: &Path, run_id: u64) -> std::path::PathBuf {
+    project_dir
+        .join(".wrkflw")
+        .join("remote")
+        .join(format!("github-{}-logs.zip", run_id))
+}
+
+/// Log of a step in a run's log archive, laid out as `<job>/<number>_<step>.txt`
+pub fn read_step_log(archive: &Path, job: &str, step: u64) -> Option<String> {
+    let file = std::fs::File::open(archive).ok()?;
+    let mut zip = zip::ZipArchive::new(file).ok()?;
+    // Characters not allowed in file names are dropped from job directories
+    let job_dir: String = job
+        .chars()
+        .filter(|c| !matches!(c, '/' | '\\' | ':' | '<' | '>' | '|' | '*' | '?' | '"'))
+        .collect();
+    let prefix = format!("{}/{}_", job_dir, step);
+    let name = zip
+        .file_names()
+        .find(|name| name.starts_with(&prefix))?
+        .to_string();
+    let mut log = String::new();
+    zip.by_name(&name).ok()?.read_to_string(&mut log).ok()?;
+    Some(log)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(id: u64, status: &str, conclusion: Option<&str>, steps: Vec<RunStep>) -> RunJob {
+        RunJob {
+            id,
+            name: format!("job{}", id),
+            status: status.to_string(),
+            conclusion: conclusion.map(str::to_string),
+            steps,
+        }
+    }
+
+    fn step(number: u64, status: &str, conclusion: Option<&str>) -> RunStep {
+        RunStep {
+            number,
+            name: format!("step{}", number),
+            status: status.to_string(),
+            conclusion: conclusion.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_run_updates() {
+        let queued = vec![job(1, "queued", None, vec![])];
+        assert_eq!(
+            run_updates(&[], &queued),
+            vec![RunUpdate::Job {
+                job: "job1".to_string(),
+                state: "queued".to_string()
+            }]
+        );
+
+        let running = vec![job(
+            1,
+            "in_progress",
+            None,
+            vec![
+                step(1, "completed", Some("success")),
+                step(2, "queued", None),
+            ],
+        )];
+        let updates: Vec<String> = run_updates(&queued, &running)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(updates, vec!["job1: in_progress", "job1 › step1: success"]);
+
+        assert!(run_updates(&running, &running).is_empty());
+
+        let done = vec![job(
+            1,
+            "completed",
+            Some("failure"),
+            vec![
+                step(1, "completed", Some("success")),
+                step(2, "completed", Some("failure")),
+            ],
+        )];
+        let updates: Vec<String> = run_updates(&running, &done)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(updates, vec!["job1: failure", "job1 › step2: failure"]);
+    }
+
+    #[test]
+    fn test_workflow_file() {
+        assert_eq!(workflow_file("ci"), "ci.yml");
+        assert_eq!(workflow_file(".github/workflows/ci.yml"), "ci.yml");
+        assert_eq!(workflow_file("release.yaml"), "release.yaml");
+    }
+
+    #[test]
+    fn test_read_step_log() {
+        use std::io::Write;
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("logs.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&archive).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file("build/1_Set up job.txt", options).unwrap();
+        zip.write_all(b"setting up\n").unwrap();
+        zip.start_file("test (ubuntu, 20)/2_Run tests.txt", options)
+            .unwrap();
+        zip.write_all(b"ok\n").unwrap();
+        zip.finish().unwrap();
+
+        assert_eq!(
+            read_step_log(&archive, "build", 1).as_deref(),
+            Some("setting up\n")
+        );
+        assert_eq!(
+            read_step_log(&archive, "test (ubuntu, 20)", 2).as_deref(),
+            Some("ok\n")
+        );
+        assert!(read_step_log(&archive, "build", 2).is_none());
+    }
+}
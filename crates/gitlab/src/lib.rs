@@ -1,5 +1,8 @@
 // gitlab crate
 
+pub mod lint;
+pub mod pipelines;
+
 use lazy_static::lazy_static;
 use regex::Regex;
 use reqwest::header;
@@ -19,11 +22,14 @@ pub enum GitlabError {
     #[error("Failed to parse Git repository URL: {0}")]
     GitParseError(String),
 
-    #[error("GitLab token not found. Please set GITLAB_TOKEN environment variable")]
+    #[error("GitLab token not found. Set GITLAB_TOKEN, or log in with `glab auth login`")]
     TokenNotFound,
 
     #[error("API error: {status} - {message}")]
     ApiError { status: u16, message: String },
+
+    #[error("Unexpected API response: {0}")]
+    ResponseError(String),
 }
 
 /// Information about a GitLab repository
@@ -124,16 +130,16 @@ pub async fn list_pipelines(_repo_info: &RepoInfo) -> Result<Vec<String>, Gitlab
     Ok(vec!["gitlab-ci".to_string()])
 }
 
-/// Trigger a pipeline on GitLab
+/// Trigger a pipeline on GitLab, returning its id
 pub async fn trigger_pipeline(
     branch: Option<&str>,
     variables: Option<HashMap<String, String>>,
-) -> Result<(), GitlabError> {
-    // Get GitLab token from environment
-    let token = std::env::var("GITLAB_TOKEN").map_err(|_| GitlabError::TokenNotFound)?;
-
-    // Trim the token to remove any leading or trailing whitespace
-    let trimmed_token = token.trim();
+) -> Result<u64, GitlabError> {
+    // Get GitLab token from the environment or the glab CLI
+    let token = wrkflw_utils::auth::gitlab_token()
+        .ok_or(GitlabError::TokenNotFound)?
+        .token;
+    let trimmed_token = token.as_str();
 
     // Get repository information
     let repo_info = get_repo_info()?;
@@ -224,7 +230,7 @@ pub async fn trigger_pipeline(
 
     // Parse response to get pipeline ID
     let pipeline_info: serde_json::Value = response.json().await?;
-    let pipeline_id = pipeline_info["id"].as_i64().unwrap_or(0);
+    let pipeline_id = pipeline_info["id"].as_u64().unwrap_or(0);
     let pipeline_url = format!(
         "https://gitlab.com/{}/{}/pipelines/{}",
         repo_info.namespace, repo_info.project, pipeline_id
@@ -233,7 +239,7 @@ pub async fn trigger_pipeline(
     println!("Pipeline triggered successfully!");
     println!("View pipeline at: {}", pipeline_url);
 
-    Ok(())
+    Ok(pipeline_id)
 }
 
 #[cfg(test)]
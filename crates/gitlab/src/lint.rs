@@ -0,0 +1,69 @@
+//! Checking pipelines with GitLab's CI lint API, which also catches what only the
+//! server can tell, like includes that don't exist
+
+use crate::pipelines::PipelinesClient;
+use crate::GitlabError;
+use serde::Deserialize;
+
+/// Response of `POST /projects/:id/ci/lint`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LintResult {
+    pub valid: bool,
+    #[serde(default)]
+    pub errors: Vec<String>,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+impl LintResult {
+    /// Add the server's errors to `local` issues, once each
+    pub fn merge_into(&self, local: &mut Vec<String>) {
+        for error in &self.errors {
+            let error = format!("GitLab lint: {}", error);
+            if !local.contains(&error) {
+                local.push(error);
+            }
+        }
+    }
+}
+
+impl PipelinesClient {
+    /// Lint `content` as a pipeline of the project, resolving its includes there
+    pub async fn lint(&self, content: &str) -> Result<LintResult, GitlabError> {
+        let body = self
+            .post("ci/lint", &serde_json::json!({ "content": content }))
+            .await?;
+        serde_json::from_str(&body)
+            .map_err(|e| GitlabError::ResponseError(format!("{} (ci/lint)", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_lint_result() {
+        let result: LintResult = serde_json::from_str(
+            r#"{
+                "valid": false,
+                "errors": ["Local file `ci/missing.yml` does not exist!", "Local file `ci/missing.yml` does not exist!"],
+                "warnings": [],
+                "merged_yaml": null,
+                "includes": []
+            }"#,
+        )
+        .unwrap();
+        assert!(!result.valid);
+
+        let mut issues = vec!["Job 'build' has no script".to_string()];
+        result.merge_into(&mut issues);
+        assert_eq!(
+            issues,
+            vec![
+                "Job 'build' has no script",
+                "GitLab lint: Local file `ci/missing.yml` does not exist!",
+            ]
+        );
+    }
+}
@@ -0,0 +1,319 @@
+//! Pipelines on GitLab: starting and listing them, following the jobs of one until
+//! it finishes, and fetching the traces of its jobs
+
+use crate::{GitlabError, RepoInfo};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+const API_URL: &str = "https://gitlab.com/api/v4";
+
+/// How often a pipeline is polled while it's followed
+pub const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Statuses a pipeline or job doesn't leave anymore, `manual` waiting for someone
+const FINISHED: &[&str] = &["success", "failed", "canceled", "skipped", "manual"];
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Pipeline {
+    pub id: u64,
+    #[serde(default)]
+    pub iid: Option<u64>,
+    pub status: String,
+    #[serde(rename = "ref", default)]
+    pub git_ref: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+    pub web_url: String,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+    /// Seconds the pipeline ran; only in responses for a single pipeline
+    #[serde(default)]
+    pub duration: Option<f64>,
+}
+
+impl Pipeline {
+    pub fn is_finished(&self) -> bool {
+        FINISHED.contains(&self.status.as_str())
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.status == "success"
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PipelineJob {
+    pub id: u64,
+    pub name: String,
+    pub stage: String,
+    pub status: String,
+    #[serde(default)]
+    pub duration: Option<f64>,
+    #[serde(default)]
+    pub allow_failure: bool,
+}
+
+impl PipelineJob {
+    pub fn is_finished(&self) -> bool {
+        FINISHED.contains(&self.status.as_str())
+    }
+
+    /// Whether the job ran, and so has a trace
+    pub fn ran(&self) -> bool {
+        matches!(self.status.as_str(), "success" | "failed" | "canceled")
+    }
+}
+
+/// A change of a followed pipeline: a job changed status, or a job that ran finished
+/// and this is its trace
+#[derive(Debug, Clone, PartialEq)]
+pub enum PipelineUpdate {
+    Job(PipelineJob),
+    Trace { job: String, trace: String },
+}
+
+impl fmt::Display for PipelineUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PipelineUpdate::Job(job) => write!(f, "{} › {}: {}", job.stage, job.name, job.status),
+            PipelineUpdate::Trace { job, trace } => write!(f, "--- {} ---\n{}", job, trace),
+        }
+    }
+}
+
+/// Jobs whose status in `after` differs from `before`, new ones included
+pub fn job_changes<'a>(before: &[PipelineJob], after: &'a [PipelineJob]) -> Vec<&'a PipelineJob> {
+    after
+        .iter()
+        .filter(|job| {
+            before
+                .iter()
+                .find(|previous| previous.id == job.id)
+                .is_none_or(|previous| previous.status != job.status)
+        })
+        .collect()
+}
+
+/// Body of a request starting a pipeline, variables sorted by name
+fn trigger_payload(git_ref: &str, variables: &HashMap<String, String>) -> serde_json::Value {
+    let mut variables: Vec<_> = variables.iter().collect();
+    variables.sort();
+    serde_json::json!({
+        "ref": git_ref,
+        "variables": variables
+            .into_iter()
+            .map(|(key, value)| serde_json::json!({ "key": key, "value": value }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Access to the pipelines of a project, authenticated with the token
+/// `wrkflw_utils::auth::gitlab_token` finds
+pub struct PipelinesClient {
+    http: reqwest::Client,
+    token: String,
+    repo: RepoInfo,
+}
+
+impl PipelinesClient {
+    pub fn from_env(repo: RepoInfo) -> Result<Self, GitlabError> {
+        let token = wrkflw_utils::auth::gitlab_token().ok_or(GitlabError::TokenNotFound)?;
+        Ok(PipelinesClient {
+            http: reqwest::Client::new(),
+            token: token.token,
+            repo,
+        })
+    }
+
+    pub fn repo(&self) -> &RepoInfo {
+        &self.repo
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!(
+            "{}/projects/{}%2F{}/{}",
+            API_URL,
+            urlencoding::encode(&self.repo.namespace),
+            urlencoding::encode(&self.repo.project),
+            path
+        )
+    }
+
+    async fn get(&self, path: &str) -> Result<String, GitlabError> {
+        let response = self
+            .http
+            .get(self.url(path))
+            .header("PRIVATE-TOKEN", &self.token)
+            .send()
+            .await?;
+        Self::body(response).await
+    }
+
+    pub(crate) async fn post(
+        &self,
+        path: &str,
+        payload: &serde_json::Value,
+    ) -> Result<String, GitlabError> {
+        let response = self
+            .http
+            .post(self.url(path))
+            .header("PRIVATE-TOKEN", &self.token)
+            .json(payload)
+            .send()
+            .await?;
+        Self::body(response).await
+    }
+
+    async fn body(response: reqwest::Response) -> Result<String, GitlabError> {
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let message = response
+                .text()
+                .await
+                .unwrap_or_else(|_| format!("Unknown error (HTTP {})", status));
+            return Err(GitlabError::ApiError { status, message });
+        }
+        Ok(response.text().await?)
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T, GitlabError> {
+        let body = self.get(path).await?;
+        serde_json::from_str(&body)
+            .map_err(|e| GitlabError::ResponseError(format!("{} ({})", e, path)))
+    }
+
+    /// Start a pipeline for `git_ref` with the given variables
+    pub async fn trigger(
+        &self,
+        git_ref: &str,
+        variables: &HashMap<String, String>,
+    ) -> Result<Pipeline, GitlabError> {
+        let body = self
+            .post("pipeline", &trigger_payload(git_ref, variables))
+            .await?;
+        serde_json::from_str(&body)
+            .map_err(|e| GitlabError::ResponseError(format!("{} (pipeline)", e)))
+    }
+
+    /// Most recent pipelines of the project, newest first
+    pub async fn recent_pipelines(&self, limit: usize) -> Result<Vec<Pipeline>, GitlabError> {
+        self.get_json(&format!("pipelines?per_page={}", limit.clamp(1, 100)))
+            .await
+    }
+
+    pub async fn pipeline(&self, pipeline_id: u64) -> Result<Pipeline, GitlabError> {
+        self.get_json(&format!("pipelines/{}", pipeline_id)).await
+    }
+
+    pub async fn pipeline_jobs(&self, pipeline_id: u64) -> Result<Vec<PipelineJob>, GitlabError> {
+        self.get_json(&format!("pipelines/{}/jobs?per_page=100", pipeline_id))
+            .await
+    }
+
+    /// Log of a job as its runner wrote it
+    pub async fn job_trace(&self, job_id: u64) -> Result<String, GitlabError> {
+        self.get(&format!("jobs/{}/trace", job_id)).await
+    }
+
+    /// Poll a pipeline until it finishes, passing job status changes and the traces
+    /// of jobs as they finish to `on_update`; returns the finished pipeline
+    pub async fn follow(
+        &self,
+        pipeline_id: u64,
+        mut on_update: impl FnMut(&PipelineUpdate),
+    ) -> Result<Pipeline, GitlabError> {
+        let mut jobs: Vec<PipelineJob> = Vec::new();
+        loop {
+            let pipeline = self.pipeline(pipeline_id).await?;
+            let mut current = self.pipeline_jobs(pipeline_id).await?;
+            // Listed newest first; report in the order jobs were created
+            current.sort_by_key(|job| job.id);
+            for job in job_changes(&jobs, &current) {
+                on_update(&PipelineUpdate::Job(job.clone()));
+                if job.ran() {
+                    let trace = self
+                        .job_trace(job.id)
+                        .await
+                        .unwrap_or_else(|e| format!("(trace not available: {})", e));
+                    on_update(&PipelineUpdate::Trace {
+                        job: job.name.clone(),
+                        trace,
+                    });
+                }
+            }
+            jobs = current;
+            if pipeline.is_finished() {
+                return Ok(pipeline);
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(id: u64, status: &str) -> PipelineJob {
+        PipelineJob {
+            id,
+            name: format!("job{}", id),
+            stage: "test".to_string(),
+            status: status.to_string(),
+            duration: None,
+            allow_failure: false,
+        }
+    }
+
+    #[test]
+    fn test_job_changes() {
+        let before = vec![job(1, "running"), job(2, "pending")];
+        let after = vec![job(1, "success"), job(2, "pending"), job(3, "created")];
+        let changed: Vec<u64> = job_changes(&before, &after)
+            .iter()
+            .map(|job| job.id)
+            .collect();
+        assert_eq!(changed, vec![1, 3]);
+        assert!(job_changes(&after, &after).is_empty());
+
+        assert!(job(1, "success").ran());
+        assert!(!job(1, "skipped").ran());
+        assert!(job(1, "manual").is_finished());
+        assert!(!job(1, "running").is_finished());
+    }
+
+    #[test]
+    fn test_trigger_payload() {
+        let variables = HashMap::from([
+            ("DEPLOY".to_string(), "true".to_string()),
+            ("ENV".to_string(), "staging".to_string()),
+        ]);
+        assert_eq!(
+            trigger_payload("main", &variables),
+            serde_json::json!({
+                "ref": "main",
+                "variables": [
+                    { "key": "DEPLOY", "value": "true" },
+                    { "key": "ENV", "value": "staging" },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_deserialize_pipeline() {
+        let pipeline: Pipeline = serde_json::from_str(
+            r#"{"id": 47, "iid": 12, "project_id": 1, "status": "running", "ref": "main",
+                "sha": "a91957a8", "source": "push", "web_url": "https://gitlab.com/g/p/-/pipelines/47",
+                "created_at": "2024-05-01T10:00:00.000Z", "updated_at": "2024-05-01T10:02:00.000Z"}"#,
+        )
+        .unwrap();
+        assert_eq!(pipeline.git_ref.as_deref(), Some("main"));
+        assert!(!pipeline.is_finished());
+    }
+}
@@ -0,0 +1,115 @@
+// Log file that rotates once it grows past a size
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// When a log file is rotated and how many rotated files are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogFileOptions {
+    /// Size in bytes past which the file is rotated
+    pub max_size: u64,
+    /// Rotated files kept next to the log file, as `<file>.1` (newest) to `<file>.N`
+    pub max_files: usize,
+}
+
+impl Default for LogFileOptions {
+    fn default() -> Self {
+        LogFileOptions {
+            max_size: 10 * 1024 * 1024,
+            max_files: 5,
+        }
+    }
+}
+
+pub(crate) struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    options: LogFileOptions,
+}
+
+impl RotatingFile {
+    /// Open `path` for appending, creating it and its directory if needed
+    pub(crate) fn open(path: &Path, options: LogFileOptions) -> io::Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(RotatingFile {
+            path: path.to_path_buf(),
+            file,
+            size,
+            options,
+        })
+    }
+
+    /// Append a line, rotating first if it would take the file past its maximum size
+    pub(crate) fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.options.max_size {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn rotated(&self, idx: usize) -> PathBuf {
+        let mut name = OsString::from(self.path.as_os_str());
+        name.push(format!(".{}", idx));
+        PathBuf::from(name)
+    }
+
+    // Shift `<file>.N-1` to `<file>.N` down to the current file becoming `<file>.1`,
+    // dropping the oldest, then start over with an empty file
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.options.max_files > 0 {
+            for idx in (1..self.options.max_files).rev() {
+                let from = self.rotated(idx);
+                if from.exists() {
+                    fs::rename(&from, self.rotated(idx + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated(1))?;
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("wrkflw.log");
+        let options = LogFileOptions {
+            max_size: 10,
+            max_files: 2,
+        };
+        let mut file = RotatingFile::open(&path, options).unwrap();
+        for line in ["first", "second", "third", "fourth"] {
+            file.write_line(line).unwrap();
+        }
+
+        let read = |name: &str| fs::read_to_string(dir.path().join("logs").join(name)).unwrap();
+        assert_eq!(read("wrkflw.log"), "fourth\n");
+        assert_eq!(read("wrkflw.log.1"), "third\n");
+        assert_eq!(read("wrkflw.log.2"), "second\n");
+        assert!(!dir.path().join("logs").join("wrkflw.log.3").exists());
+
+        // Reopening appends to what's there
+        let mut file = RotatingFile::open(&path, options).unwrap();
+        file.write_line("5").unwrap();
+        assert_eq!(read("wrkflw.log"), "fourth\n5\n");
+    }
+}
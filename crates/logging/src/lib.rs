@@ -1,6 +1,30 @@
+//! Logging for wrkflw, built on `tracing`
+//!
+//! wrkflw's crates log through the `info`, `warning`, `error` and `debug` functions
+//! here, or with `tracing` macros directly to attach fields; the executor runs jobs
+//! and steps in spans carrying the workflow, job, step and runtime. Events are kept
+//! in an in-memory buffer the TUI reads, printed from the log level up, as text or
+//! JSON lines, and optionally written to a rotating log file and to the log of the
+//! run in progress. A program embedding wrkflw can take the events it would print
+//! with `add_log_callback`. Values given to `add_mask`, such as the secrets of a
+//! run, are replaced with `***` in every event before it goes anywhere.
+
+mod file;
+mod subscriber;
+
+pub use file::LogFileOptions;
+pub use subscriber::Record;
+
 use chrono::Local;
+use file::RotatingFile;
 use once_cell::sync::Lazy;
-use std::sync::{Arc, Mutex};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use subscriber::WrkflwSubscriber;
 
 // Thread-safe log storage
 static LOGS: Lazy<Arc<Mutex<Vec<String>>>> = Lazy::new(|| Arc::new(Mutex::new(Vec::new())));
@@ -8,6 +32,40 @@ static LOGS: Lazy<Arc<Mutex<Vec<String>>>> = Lazy::new(|| Arc::new(Mutex::new(Ve
 // Current log level
 static LOG_LEVEL: Lazy<Arc<Mutex<LogLevel>>> = Lazy::new(|| Arc::new(Mutex::new(LogLevel::Info)));
 
+// Format of printed and written logs
+static LOG_FORMAT: Lazy<Mutex<LogFormat>> = Lazy::new(|| Mutex::new(LogFormat::Text));
+
+// The log file, if logging to one
+static LOG_FILE: Lazy<Mutex<Option<RotatingFile>>> = Lazy::new(|| Mutex::new(None));
+
+// Log of the run in progress, if it's written to one
+static RUN_LOG: Lazy<Mutex<Option<File>>> = Lazy::new(|| Mutex::new(None));
+
+// Whether our subscriber is the global one; not when the embedding program set its own
+static INSTALLED: OnceLock<bool> = OnceLock::new();
+
+/// Receives the events that would be printed, in place of printing them
+pub type LogCallback = Arc<dyn Fn(&Record) + Send + Sync>;
+
+/// Identifies a callback given to `add_log_callback`, to remove it again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallbackId(u64);
+
+// Where printed events go instead, if anywhere; each added callback gets them all
+static CALLBACKS: Lazy<Mutex<Vec<(CallbackId, LogCallback)>>> =
+    Lazy::new(|| Mutex::new(Vec::new()));
+
+static NEXT_CALLBACK: AtomicU64 = AtomicU64::new(0);
+
+// Values masked out of every event, longest first so none is left half masked
+static MASKS: Lazy<Mutex<Vec<String>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// What masked values are replaced with
+pub const MASK: &str = "***";
+
+/// Values shorter than this aren't masked, or every event would be full of `***`
+const MIN_MASK_LEN: usize = 3;
+
 // Log levels
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum LogLevel {
@@ -26,6 +84,33 @@ impl LogLevel {
             LogLevel::Error => "❌",
         }
     }
+
+    /// Name of the level in JSON logs
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warning => "warning",
+            LogLevel::Error => "error",
+        }
+    }
+}
+
+/// How logs are printed and written to the log file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// `[HH:MM:SS] <level> message (field=value, ...)`, warnings and errors on stderr
+    Text,
+    /// One JSON object per line, all on stderr
+    Json,
+}
+
+/// Install wrkflw's subscriber as the global `tracing` subscriber; false if another
+/// one was installed first, in which case events go there and the log functions
+/// still fill the buffer
+pub fn init() -> bool {
+    *INSTALLED
+        .get_or_init(|| tracing::subscriber::set_global_default(WrkflwSubscriber::new()).is_ok())
 }
 
 // Set the current log level
@@ -45,28 +130,170 @@ pub fn get_log_level() -> LogLevel {
     }
 }
 
-// Log a message with timestamp and level
-pub fn log(level: LogLevel, message: &str) {
-    let timestamp = Local::now().format("%H:%M:%S").to_string();
+/// Set the format of printed and written logs
+pub fn set_log_format(format: LogFormat) {
+    if let Ok(mut current_format) = LOG_FORMAT.lock() {
+        *current_format = format;
+    }
+}
 
-    // Always include timestamp in [HH:MM:SS] format to ensure consistency
-    let formatted = format!("[{}] {} {}", timestamp, level.prefix(), message);
+pub fn get_log_format() -> LogFormat {
+    LOG_FORMAT
+        .lock()
+        .map(|format| *format)
+        .unwrap_or(LogFormat::Text)
+}
 
-    if let Ok(mut logs) = LOGS.lock() {
-        logs.push(formatted.clone());
+/// Also write every event, debug ones included, to `path`, rotating it as `options`
+/// says
+pub fn log_to_file(path: &Path, options: LogFileOptions) -> io::Result<()> {
+    let file = RotatingFile::open(path, options)?;
+    if let Ok(mut log_file) = LOG_FILE.lock() {
+        *log_file = Some(file);
+    }
+    init();
+    Ok(())
+}
+
+/// Hand the events from the log level up to `callback` instead of printing them,
+/// until it's removed with `remove_log_callback`; they're still kept and written to
+/// the log files. Events aren't tied to a run, so with several callbacks added each
+/// gets every event.
+pub fn add_log_callback(callback: LogCallback) -> CallbackId {
+    let id = CallbackId(NEXT_CALLBACK.fetch_add(1, Ordering::Relaxed));
+    if let Ok(mut callbacks) = CALLBACKS.lock() {
+        callbacks.push((id, callback));
+    }
+    init();
+    id
+}
+
+/// Stop handing events to the callback `id`; once none is left they're printed again
+pub fn remove_log_callback(id: CallbackId) {
+    if let Ok(mut callbacks) = CALLBACKS.lock() {
+        callbacks.retain(|(added, _)| *added != id);
+    }
+}
+
+/// Also append every event, as text, to `path` until called with `None`; the log
+/// of the run in progress
+pub fn set_run_log(path: Option<&Path>) -> io::Result<()> {
+    let file = match path {
+        Some(path) => {
+            if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                fs::create_dir_all(dir)?;
+            }
+            Some(OpenOptions::new().create(true).append(true).open(path)?)
+        }
+        None => None,
+    };
+    if let Ok(mut run_log) = RUN_LOG.lock() {
+        *run_log = file;
+    }
+    init();
+    Ok(())
+}
+
+/// Replace `value` with `***` in every event from now on, as GitHub's `add-mask`
+/// does; for secrets and tokens a run resolves. Values stay masked for the rest of
+/// the process, so concurrent runs never show each other's secrets either.
+pub fn add_mask(value: &str) {
+    if value.len() < MIN_MASK_LEN {
+        return;
+    }
+    if let Ok(mut masks) = MASKS.lock() {
+        if !masks.iter().any(|mask| mask == value) {
+            masks.push(value.to_string());
+            masks.sort_by_key(|mask| std::cmp::Reverse(mask.len()));
+        }
+    }
+}
+
+/// `text` with the values given to `add_mask` replaced with `***`, for output
+/// that is shown or saved without going through the log functions
+pub fn mask(text: &str) -> String {
+    let Ok(masks) = MASKS.lock() else {
+        return text.to_string();
+    };
+    let mut text = text.to_string();
+    for value in masks.iter() {
+        if text.contains(value.as_str()) {
+            text = text.replace(value.as_str(), MASK);
+        }
+    }
+    text
+}
+
+// Store, print and write an event
+pub(crate) fn emit(mut record: Record) {
+    record.message = mask(&record.message);
+    for value in record.fields.values_mut() {
+        *value = mask(value);
+    }
+    let text = record.text();
+    let format = get_log_format();
+
+    if let Ok(mut log_file) = LOG_FILE.lock() {
+        if let Some(file) = log_file.as_mut() {
+            // Nowhere to report a failing log file but the log itself
+            let _ = file.write_line(&record.format(format));
+        }
     }
 
     // Print to console if the message level is >= the current log level
     // This ensures Debug messages only show up when the Debug level is set
-    if let Ok(current_level) = LOG_LEVEL.lock() {
-        if level >= *current_level {
-            // Print to stdout/stderr based on level
-            match level {
-                LogLevel::Error | LogLevel::Warning => eprintln!("{}", formatted),
-                _ => println!("{}", formatted),
+    // Cloned out so a callback that logs doesn't deadlock on the lock
+    let callbacks: Vec<LogCallback> = CALLBACKS
+        .lock()
+        .map(|callbacks| {
+            callbacks
+                .iter()
+                .map(|(_, callback)| callback.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    if record.level >= get_log_level() {
+        if !callbacks.is_empty() {
+            for callback in &callbacks {
+                callback(&record);
+            }
+        } else {
+            match (format, record.level) {
+                (LogFormat::Json, _) => eprintln!("{}", record.json()),
+                (LogFormat::Text, LogLevel::Error | LogLevel::Warning) => eprintln!("{}", text),
+                (LogFormat::Text, _) => println!("{}", text),
             }
         }
     }
+
+    if let Ok(mut run_log) = RUN_LOG.lock() {
+        if let Some(file) = run_log.as_mut() {
+            let _ = writeln!(file, "{}", text);
+        }
+    }
+
+    if let Ok(mut logs) = LOGS.lock() {
+        logs.push(text);
+    }
+}
+
+// Log a message with timestamp and level
+pub fn log(level: LogLevel, message: &str) {
+    if !init() {
+        emit(Record {
+            timestamp: Local::now(),
+            level,
+            target: module_path!().to_string(),
+            message: message.to_string(),
+            fields: BTreeMap::new(),
+        });
+    }
+    match level {
+        LogLevel::Debug => tracing::debug!("{}", message),
+        LogLevel::Info => tracing::info!("{}", message),
+        LogLevel::Warning => tracing::warn!("{}", message),
+        LogLevel::Error => tracing::error!("{}", message),
+    }
 }
 
 // Get all logs
@@ -105,3 +332,55 @@ pub fn warning(message: &str) {
 pub fn error(message: &str) {
     log(LogLevel::Error, message);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask() {
+        add_mask("hunter2VALUE");
+        add_mask("hunter2VALUE-longer");
+        add_mask("ab");
+
+        assert_eq!(
+            mask("token=hunter2VALUE-longer, again hunter2VALUE"),
+            "token=***, again ***"
+        );
+        // Too short to mask
+        assert_eq!(mask("ab cd"), "ab cd");
+    }
+
+    #[test]
+    fn test_log_callbacks() {
+        let seen = |id: &'static str| {
+            let records = Arc::new(Mutex::new(Vec::new()));
+            let callback_records = records.clone();
+            let callback: LogCallback = Arc::new(move |record: &Record| {
+                if record.message.starts_with(id) {
+                    callback_records
+                        .lock()
+                        .unwrap()
+                        .push(record.message.clone());
+                }
+            });
+            (records, callback)
+        };
+        let (first, first_callback) = seen("callback-test");
+        let (second, second_callback) = seen("callback-test");
+        let first_id = add_log_callback(first_callback);
+        let second_id = add_log_callback(second_callback);
+
+        error("callback-test both");
+        // Removing one callback leaves the other in place
+        remove_log_callback(first_id);
+        error("callback-test second");
+        remove_log_callback(second_id);
+
+        assert_eq!(*first.lock().unwrap(), vec!["callback-test both"]);
+        assert_eq!(
+            *second.lock().unwrap(),
+            vec!["callback-test both", "callback-test second"]
+        );
+    }
+}
@@ -0,0 +1,311 @@
+// The `tracing` subscriber behind wrkflw's logs
+//
+// Events from wrkflw crates are stored in the in-memory buffer the TUI reads,
+// printed when at or above the log level, and written to the log file if there is
+// one. Fields of the spans an event happens in (workflow, job, step, runtime) are
+// added to those of the event.
+use crate::{LogFormat, LogLevel};
+use chrono::{DateTime, Local};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::span::{Attributes, Id, Record as SpanRecord};
+use tracing::subscriber::Interest;
+use tracing::{Event, Level, Metadata, Subscriber};
+
+/// A log event with the fields of its spans
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub timestamp: DateTime<Local>,
+    pub level: LogLevel,
+    pub target: String,
+    pub message: String,
+    pub fields: BTreeMap<String, String>,
+}
+
+impl Record {
+    /// The line kept in the buffer and printed in the text format:
+    /// `[HH:MM:SS] <level> message (job=build, step=Test)`
+    pub fn text(&self) -> String {
+        let mut line = format!(
+            "[{}] {} {}",
+            self.timestamp.format("%H:%M:%S"),
+            self.level.prefix(),
+            self.message
+        );
+        if !self.fields.is_empty() {
+            let fields: Vec<String> = self
+                .fields
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect();
+            line.push_str(&format!(" ({})", fields.join(", ")));
+        }
+        line
+    }
+
+    /// One JSON object, the fields next to `timestamp`, `level`, `target` and `message`
+    pub fn json(&self) -> String {
+        let mut object = serde_json::Map::new();
+        object.insert("timestamp".into(), self.timestamp.to_rfc3339().into());
+        object.insert("level".into(), self.level.as_str().into());
+        object.insert("target".into(), self.target.clone().into());
+        object.insert("message".into(), self.message.clone().into());
+        for (key, value) in &self.fields {
+            object
+                .entry(key.clone())
+                .or_insert_with(|| value.clone().into());
+        }
+        serde_json::Value::Object(object).to_string()
+    }
+
+    pub fn format(&self, format: LogFormat) -> String {
+        match format {
+            LogFormat::Text => self.text(),
+            LogFormat::Json => self.json(),
+        }
+    }
+}
+
+impl From<&Level> for LogLevel {
+    fn from(level: &Level) -> Self {
+        match *level {
+            Level::ERROR => LogLevel::Error,
+            Level::WARN => LogLevel::Warning,
+            Level::INFO => LogLevel::Info,
+            _ => LogLevel::Debug,
+        }
+    }
+}
+
+// The message and fields of an event or span
+#[derive(Default)]
+struct FieldVisitor {
+    message: Option<String>,
+    fields: BTreeMap<String, String>,
+}
+
+impl FieldVisitor {
+    fn insert(&mut self, field: &Field, value: String) {
+        if field.name() == "message" {
+            self.message = Some(value);
+        } else {
+            self.fields.insert(field.name().to_string(), value);
+        }
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, format!("{:?}", value));
+    }
+}
+
+struct SpanData {
+    fields: BTreeMap<String, String>,
+    refs: usize,
+}
+
+thread_local! {
+    // Spans entered on this thread, innermost last
+    static CURRENT: RefCell<Vec<Id>> = const { RefCell::new(Vec::new()) };
+}
+
+pub(crate) struct WrkflwSubscriber {
+    next_id: AtomicU64,
+    spans: Mutex<HashMap<u64, SpanData>>,
+}
+
+impl WrkflwSubscriber {
+    pub fn new() -> Self {
+        WrkflwSubscriber {
+            next_id: AtomicU64::new(1),
+            spans: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Fields of the spans `event` happens in, outermost first so inner ones win
+    fn span_fields(&self, event: &Event<'_>) -> BTreeMap<String, String> {
+        let ids: Vec<u64> = match event.parent() {
+            Some(parent) => vec![parent.into_u64()],
+            None if event.is_root() => Vec::new(),
+            None => CURRENT.with(|current| current.borrow().iter().map(Id::into_u64).collect()),
+        };
+        let mut fields = BTreeMap::new();
+        if let Ok(spans) = self.spans.lock() {
+            for id in ids {
+                if let Some(span) = spans.get(&id) {
+                    fields.extend(span.fields.clone());
+                }
+            }
+        }
+        fields
+    }
+}
+
+impl Subscriber for WrkflwSubscriber {
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if self.enabled(metadata) {
+            Interest::always()
+        } else {
+            Interest::never()
+        }
+    }
+
+    // Only wrkflw's own events: dependencies log through `tracing` too
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target().starts_with("wrkflw")
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(LevelFilter::DEBUG)
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut visitor = FieldVisitor::default();
+        span.record(&mut visitor);
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut spans) = self.spans.lock() {
+            spans.insert(
+                id,
+                SpanData {
+                    fields: visitor.fields,
+                    refs: 1,
+                },
+            );
+        }
+        Id::from_u64(id)
+    }
+
+    fn record(&self, span: &Id, values: &SpanRecord<'_>) {
+        let mut visitor = FieldVisitor::default();
+        values.record(&mut visitor);
+        if let Ok(mut spans) = self.spans.lock() {
+            if let Some(span) = spans.get_mut(&span.into_u64()) {
+                span.fields.extend(visitor.fields);
+            }
+        }
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let mut fields = self.span_fields(event);
+        fields.extend(visitor.fields);
+        crate::emit(Record {
+            timestamp: Local::now(),
+            level: event.metadata().level().into(),
+            target: event.metadata().target().to_string(),
+            message: visitor.message.unwrap_or_default(),
+            fields,
+        });
+    }
+
+    fn enter(&self, span: &Id) {
+        CURRENT.with(|current| current.borrow_mut().push(span.clone()));
+    }
+
+    fn exit(&self, span: &Id) {
+        CURRENT.with(|current| {
+            let mut current = current.borrow_mut();
+            if let Some(pos) = current.iter().rposition(|id| id == span) {
+                current.remove(pos);
+            }
+        });
+    }
+
+    fn clone_span(&self, id: &Id) -> Id {
+        if let Ok(mut spans) = self.spans.lock() {
+            if let Some(span) = spans.get_mut(&id.into_u64()) {
+                span.refs += 1;
+            }
+        }
+        id.clone()
+    }
+
+    fn try_close(&self, id: Id) -> bool {
+        let Ok(mut spans) = self.spans.lock() else {
+            return false;
+        };
+        let Some(span) = spans.get_mut(&id.into_u64()) else {
+            return false;
+        };
+        span.refs -= 1;
+        if span.refs == 0 {
+            spans.remove(&id.into_u64());
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn record() -> Record {
+        Record {
+            timestamp: Local.with_ymd_and_hms(2024, 5, 1, 10, 30, 0).unwrap(),
+            level: LogLevel::Info,
+            target: "wrkflw_executor::engine".to_string(),
+            message: "Executing step".to_string(),
+            fields: BTreeMap::from([
+                ("job".to_string(), "build".to_string()),
+                ("step".to_string(), "Run tests".to_string()),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_text_format() {
+        assert_eq!(
+            record().text(),
+            "[10:30:00] ℹ️ Executing step (job=build, step=Run tests)"
+        );
+        let record = Record {
+            fields: BTreeMap::new(),
+            ..record()
+        };
+        assert_eq!(record.text(), "[10:30:00] ℹ️ Executing step");
+    }
+
+    #[test]
+    fn test_json_format() {
+        let json: serde_json::Value = serde_json::from_str(&record().json()).unwrap();
+        assert_eq!(json["level"], "info");
+        assert_eq!(json["message"], "Executing step");
+        assert_eq!(json["target"], "wrkflw_executor::engine");
+        assert_eq!(json["job"], "build");
+        assert_eq!(json["step"], "Run tests");
+        assert!(json["timestamp"]
+            .as_str()
+            .unwrap()
+            .starts_with("2024-05-01T10:30:00"));
+    }
+
+    #[test]
+    fn test_span_fields() {
+        let subscriber = WrkflwSubscriber::new();
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!(target: "wrkflw_test", "job", job = "build");
+            let _guard = span.enter();
+            tracing::info!(target: "wrkflw_test", step = "Checkout", "step started");
+        });
+        let logs = crate::get_logs();
+        assert!(logs
+            .iter()
+            .any(|log| log.ends_with("step started (job=build, step=Checkout)")));
+    }
+}
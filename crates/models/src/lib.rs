@@ -1,6 +1,65 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// How serious a validation finding is, from least to most
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn parse(severity: &str) -> Option<Self> {
+        match severity {
+            "info" => Some(Self::Info),
+            "warning" => Some(Self::Warning),
+            "error" => Some(Self::Error),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Something validation found, with the rule that found it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Finding {
+    pub rule: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Rule of findings added before any rule is set
+pub const DEFAULT_RULE: &str = "structure";
+
 pub struct ValidationResult {
     pub is_valid: bool,
     pub issues: Vec<String>,
+    /// Findings worth reporting that do not make the workflow invalid
+    pub warnings: Vec<String>,
+    /// Findings for information only
+    pub infos: Vec<String>,
+    /// All of the above, with their rules
+    pub findings: Vec<Finding>,
+    /// Findings left out of the above by suppression comments
+    pub suppressed: Vec<Finding>,
+    /// Rule the findings added next are recorded under
+    rule: String,
 }
 
 impl Default for ValidationResult {
@@ -14,20 +73,162 @@ impl ValidationResult {
         ValidationResult {
             is_valid: true,
             issues: Vec::new(),
+            warnings: Vec::new(),
+            infos: Vec::new(),
+            findings: Vec::new(),
+            suppressed: Vec::new(),
+            rule: DEFAULT_RULE.to_string(),
+        }
+    }
+
+    /// Record the findings added from now on under `rule`
+    /// The result having `findings`, as recorded by another result
+    pub fn from_findings(findings: Vec<Finding>) -> Self {
+        let mut result = ValidationResult::new();
+        for finding in findings {
+            result.add_for(&finding.rule, finding.severity, finding.message);
+        }
+        result
+    }
+
+    pub fn set_rule(&mut self, rule: &str) {
+        self.rule = rule.to_string();
+    }
+
+    /// Run `validate` with the findings it adds recorded under `rule`
+    pub fn in_rule(&mut self, rule: &str, validate: impl FnOnce(&mut Self)) {
+        let previous = std::mem::replace(&mut self.rule, rule.to_string());
+        validate(self);
+        self.rule = previous;
+    }
+
+    pub fn add(&mut self, severity: Severity, message: String) {
+        let rule = self.rule.clone();
+        self.add_for(&rule, severity, message);
+    }
+
+    pub fn add_for(&mut self, rule: &str, severity: Severity, message: String) {
+        match severity {
+            Severity::Error => {
+                self.is_valid = false;
+                self.issues.push(message.clone());
+            }
+            Severity::Warning => self.warnings.push(message.clone()),
+            Severity::Info => self.infos.push(message.clone()),
         }
+        self.findings.push(Finding {
+            rule: rule.to_string(),
+            severity,
+            message,
+        });
     }
 
     pub fn add_issue(&mut self, issue: String) {
-        self.is_valid = false;
-        self.issues.push(issue);
+        self.add(Severity::Error, issue);
+    }
+
+    pub fn add_warning(&mut self, warning: String) {
+        self.add(Severity::Warning, warning);
+    }
+
+    pub fn add_info(&mut self, info: String) {
+        self.add(Severity::Info, info);
+    }
+
+    /// The result with the findings of the rules in `overrides` given their
+    /// severity there, or dropped for `None`
+    pub fn with_severities(self, overrides: &HashMap<String, Option<Severity>>) -> Self {
+        if overrides.is_empty() {
+            return self;
+        }
+        let mut result = ValidationResult::new();
+        result.rule = self.rule;
+        result.suppressed = self.suppressed;
+        for finding in self.findings {
+            let severity = match overrides.get(&finding.rule) {
+                Some(None) => continue,
+                Some(Some(severity)) => *severity,
+                None => finding.severity,
+            };
+            result.add_for(&finding.rule, severity, finding.message);
+        }
+        result
+    }
+
+    /// The result with the findings `is_suppressed` holds for moved to
+    /// `suppressed`
+    pub fn without_suppressed(self, is_suppressed: impl Fn(&Finding) -> bool) -> Self {
+        let mut result = ValidationResult::new();
+        result.rule = self.rule;
+        result.suppressed = self.suppressed;
+        for finding in self.findings {
+            if is_suppressed(&finding) {
+                result.suppressed.push(finding);
+            } else {
+                result.add_for(&finding.rule, finding.severity, finding.message);
+            }
+        }
+        result
+    }
+
+    /// The severity of the most serious finding, if any
+    pub fn worst(&self) -> Option<Severity> {
+        self.findings.iter().map(|finding| finding.severity).max()
+    }
+}
+
+/// Access level granted to the `GITHUB_TOKEN` for one permission scope
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PermissionLevel {
+    None,
+    Read,
+    Write,
+}
+
+impl PermissionLevel {
+    pub fn parse(level: &str) -> Option<Self> {
+        match level {
+            "none" => Some(Self::None),
+            "read" => Some(Self::Read),
+            "write" => Some(Self::Write),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Read => "read",
+            Self::Write => "write",
+        }
     }
 }
 
 // GitLab pipeline models
 pub mod gitlab {
-    use serde::{Deserialize, Serialize};
+    use serde::{Deserialize, Deserializer, Serialize};
     use std::collections::HashMap;
 
+    // `extends` names a single job or a list of them
+    fn deserialize_extends<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum StringOrVec {
+            String(String),
+            Vec(Vec<String>),
+        }
+
+        let value = Option::<StringOrVec>::deserialize(deserializer)?;
+        match value {
+            Some(StringOrVec::String(s)) => Ok(Some(vec![s])),
+            Some(StringOrVec::Vec(v)) => Ok(Some(v)),
+            None => Ok(None),
+        }
+    }
+
     /// Represents a GitLab CI/CD pipeline configuration
     #[derive(Debug, Serialize, Deserialize, Clone)]
     pub struct Pipeline {
@@ -111,6 +312,10 @@ pub mod gitlab {
         #[serde(skip_serializing_if = "Option::is_none")]
         pub dependencies: Option<Vec<String>>,
 
+        /// Jobs to wait for regardless of stages; empty to start right away
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub needs: Option<Vec<Need>>,
+
         /// Artifacts to store after job execution
         #[serde(skip_serializing_if = "Option::is_none")]
         pub artifacts: Option<Artifacts>,
@@ -139,19 +344,168 @@ pub mod gitlab {
         #[serde(skip_serializing_if = "Option::is_none")]
         pub timeout: Option<String>,
 
-        /// Mark job as parallel and specify instance count
+        /// Instance count, or a matrix of variables to run the job with
         #[serde(skip_serializing_if = "Option::is_none")]
-        pub parallel: Option<usize>,
+        pub parallel: Option<Parallel>,
+
+        /// Downstream pipeline the job triggers instead of running a script
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub trigger: Option<Trigger>,
+
+        /// Environment the job deploys to
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub environment: Option<Environment>,
 
         /// Flag to indicate this is a template job
         #[serde(skip_serializing_if = "Option::is_none")]
         pub template: Option<bool>,
 
         /// List of jobs this job extends from
-        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "deserialize_extends"
+        )]
         pub extends: Option<Vec<String>>,
     }
 
+    /// A job in `needs:`
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    #[serde(untagged)]
+    pub enum Need {
+        /// Job name
+        Job(String),
+        /// Job name with options
+        Detailed {
+            job: String,
+            /// Whether to download the job's artifacts
+            #[serde(skip_serializing_if = "Option::is_none")]
+            artifacts: Option<bool>,
+            /// Whether the job may be missing from the pipeline
+            #[serde(skip_serializing_if = "Option::is_none")]
+            optional: Option<bool>,
+        },
+    }
+
+    impl Need {
+        pub fn job(&self) -> &str {
+            match self {
+                Need::Job(job) | Need::Detailed { job, .. } => job,
+            }
+        }
+    }
+
+    /// `trigger:` of a job
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    #[serde(untagged)]
+    pub enum Trigger {
+        /// Path of the project whose pipeline runs
+        Project(String),
+        /// A pipeline of another project, or a child pipeline of this one
+        Detailed {
+            /// Path of the project whose pipeline runs
+            #[serde(skip_serializing_if = "Option::is_none")]
+            project: Option<String>,
+            /// Branch of the project to run the pipeline of
+            #[serde(skip_serializing_if = "Option::is_none")]
+            branch: Option<String>,
+            /// Files the child pipeline is made of
+            #[serde(skip_serializing_if = "Option::is_none")]
+            include: Option<TriggerInclude>,
+            /// `depend` for the job to wait for the downstream pipeline and take
+            /// its status
+            #[serde(skip_serializing_if = "Option::is_none")]
+            strategy: Option<String>,
+        },
+    }
+
+    /// `environment:` of a job
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    #[serde(untagged)]
+    pub enum Environment {
+        /// Name of the environment
+        Name(String),
+        /// Environment with options
+        Detailed {
+            name: String,
+            /// Where the deployment can be reached
+            #[serde(skip_serializing_if = "Option::is_none")]
+            url: Option<String>,
+            /// Job that stops the environment
+            #[serde(skip_serializing_if = "Option::is_none")]
+            on_stop: Option<String>,
+            /// start (the default), prepare, stop, verify or access
+            #[serde(skip_serializing_if = "Option::is_none")]
+            action: Option<String>,
+            /// production, staging, testing, development or other
+            #[serde(skip_serializing_if = "Option::is_none")]
+            deployment_tier: Option<String>,
+        },
+    }
+
+    impl Environment {
+        pub fn name(&self) -> &str {
+            match self {
+                Environment::Name(name) | Environment::Detailed { name, .. } => name,
+            }
+        }
+
+        pub fn url(&self) -> Option<&str> {
+            match self {
+                Environment::Name(_) => None,
+                Environment::Detailed { url, .. } => url.as_deref(),
+            }
+        }
+
+        pub fn on_stop(&self) -> Option<&str> {
+            match self {
+                Environment::Name(_) => None,
+                Environment::Detailed { on_stop, .. } => on_stop.as_deref(),
+            }
+        }
+
+        pub fn action(&self) -> &str {
+            match self {
+                Environment::Detailed {
+                    action: Some(action),
+                    ..
+                } => action,
+                _ => "start",
+            }
+        }
+
+        pub fn deployment_tier(&self) -> Option<&str> {
+            match self {
+                Environment::Name(_) => None,
+                Environment::Detailed {
+                    deployment_tier, ..
+                } => deployment_tier.as_deref(),
+            }
+        }
+    }
+
+    /// `trigger: include:` of a job
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    #[serde(untagged)]
+    pub enum TriggerInclude {
+        /// Path of a file of the project
+        Local(String),
+        /// Files of the project, generated artifacts, or templates
+        List(Vec<Include>),
+    }
+
+    /// `parallel:` of a job
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    #[serde(untagged)]
+    pub enum Parallel {
+        /// Number of instances
+        Count(usize),
+        /// One job per combination of the values of each entry
+        Matrix {
+            matrix: Vec<std::collections::BTreeMap<String, serde_yaml::Value>>,
+        },
+    }
+
     /// Docker image configuration
     #[derive(Debug, Serialize, Deserialize, Clone)]
     #[serde(untagged)]
@@ -206,7 +560,7 @@ pub mod gitlab {
     pub struct Cache {
         /// Cache key
         #[serde(skip_serializing_if = "Option::is_none")]
-        pub key: Option<String>,
+        pub key: Option<CacheKey>,
         /// Paths to cache
         #[serde(skip_serializing_if = "Option::is_none")]
         pub paths: Option<Vec<String>>,
@@ -218,6 +572,18 @@ pub mod gitlab {
         pub policy: Option<String>,
     }
 
+    /// Cache key, given as is or computed from the content of files
+    #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+    #[serde(untagged)]
+    pub enum CacheKey {
+        Key(String),
+        Files {
+            files: Vec<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            prefix: Option<String>,
+        },
+    }
+
     /// Rule for conditional job execution
     #[derive(Debug, Serialize, Deserialize, Clone)]
     pub struct Rule {
@@ -230,6 +596,31 @@ pub mod gitlab {
         /// Variables to set if condition is true
         #[serde(skip_serializing_if = "Option::is_none")]
         pub variables: Option<HashMap<String, String>>,
+        /// Files whose changes make the rule apply
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub changes: Option<Changes>,
+    }
+
+    /// `changes:` of a rule
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    #[serde(untagged)]
+    pub enum Changes {
+        /// Simple list of path patterns
+        Paths(Vec<String>),
+        /// Path patterns and the ref to compare with
+        Detailed {
+            paths: Vec<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            compare_to: Option<String>,
+        },
+    }
+
+    impl Changes {
+        pub fn paths(&self) -> &[String] {
+            match self {
+                Changes::Paths(paths) | Changes::Detailed { paths, .. } => paths,
+            }
+        }
     }
 
     /// Only/except configuration
@@ -336,3 +727,401 @@ pub mod gitlab {
         },
     }
 }
+
+// Azure Pipelines models
+pub mod azure {
+    use serde::{Deserialize, Serialize};
+    use serde_yaml::Value;
+    use std::collections::BTreeMap;
+
+    /// Represents an Azure Pipelines configuration (`azure-pipelines.yml`)
+    ///
+    /// A pipeline has stages, jobs or steps at the top level; the ones it leaves
+    /// out are implied. Values that may hold template expressions stay raw YAML.
+    #[derive(Debug, Serialize, Deserialize, Clone, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Pipeline {
+        /// Run number format
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub name: Option<String>,
+
+        /// Branches and paths whose pushes start the pipeline, or `none`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub trigger: Option<Value>,
+
+        /// Pull request trigger
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub pr: Option<Value>,
+
+        /// Scheduled triggers
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub schedules: Option<Value>,
+
+        /// Runtime parameters
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub parameters: Option<Value>,
+
+        /// Variables, as a mapping or a list of variables, groups and templates
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub variables: Option<Value>,
+
+        /// Default agent pool
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub pool: Option<Value>,
+
+        /// Repositories, pipelines, containers and packages the pipeline uses
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub resources: Option<Value>,
+
+        /// Template the whole pipeline extends
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub extends: Option<Value>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub stages: Option<Vec<Stage>>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub jobs: Option<Vec<Job>>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub steps: Option<Vec<Step>>,
+
+        /// Keys not listed above, kept so validation can report them
+        #[serde(flatten)]
+        pub other: BTreeMap<String, Value>,
+    }
+
+    /// A stage, or a template of stages
+    #[derive(Debug, Serialize, Deserialize, Clone, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Stage {
+        /// Stage name
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub stage: Option<String>,
+
+        /// Path of a template of stages
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub template: Option<String>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub display_name: Option<String>,
+
+        /// Stages that must finish first; empty to run at the start
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub depends_on: Option<DependsOn>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub condition: Option<String>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub jobs: Option<Vec<Job>>,
+
+        /// Keys not listed above
+        #[serde(flatten)]
+        pub other: BTreeMap<String, Value>,
+    }
+
+    /// A job, a deployment job, or a template of jobs
+    #[derive(Debug, Serialize, Deserialize, Clone, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Job {
+        /// Job name
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub job: Option<String>,
+
+        /// Deployment job name
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub deployment: Option<String>,
+
+        /// Path of a template of jobs
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub template: Option<String>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub display_name: Option<String>,
+
+        /// Jobs of the same stage that must finish first
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub depends_on: Option<DependsOn>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub condition: Option<String>,
+
+        /// Environment a deployment job deploys to
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub environment: Option<Value>,
+
+        /// Matrix or parallel strategy, or the lifecycle hooks of a deployment job
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub strategy: Option<Value>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub steps: Option<Vec<Step>>,
+
+        /// Keys not listed above
+        #[serde(flatten)]
+        pub other: BTreeMap<String, Value>,
+    }
+
+    /// A step: a script, a task, a checkout or download, or a template of steps
+    #[derive(Debug, Serialize, Deserialize, Clone, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Step {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub script: Option<String>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub bash: Option<String>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub pwsh: Option<String>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub powershell: Option<String>,
+
+        /// Task reference, `Name@MajorVersion`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub task: Option<String>,
+
+        /// Repository to check out, `self` or `none`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub checkout: Option<String>,
+
+        /// Pipeline artifacts to download
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub download: Option<String>,
+
+        /// Path to publish as a pipeline artifact
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub publish: Option<String>,
+
+        /// Path of a template of steps
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub template: Option<String>,
+
+        /// Reference name, used in output variables
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub name: Option<String>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub display_name: Option<String>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub condition: Option<String>,
+
+        /// Task inputs
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub inputs: Option<Value>,
+
+        /// Keys not listed above
+        #[serde(flatten)]
+        pub other: BTreeMap<String, Value>,
+    }
+
+    /// `dependsOn`: one name or a list of them
+    #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+    #[serde(untagged)]
+    pub enum DependsOn {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    impl DependsOn {
+        pub fn names(&self) -> Vec<&str> {
+            match self {
+                DependsOn::One(name) => vec![name.as_str()],
+                DependsOn::Many(names) => names.iter().map(String::as_str).collect(),
+            }
+        }
+    }
+
+    /// Whether a mapping key is a template expression (`${{ if ... }}:`) whose
+    /// value is only known once the template is expanded
+    pub fn is_template_expression(key: &str) -> bool {
+        key.trim_start().starts_with("${{")
+    }
+
+    impl Stage {
+        /// Whether the item is a template expression rather than a stage
+        pub fn is_expression(&self) -> bool {
+            self.stage.is_none()
+                && self.template.is_none()
+                && self.other.keys().any(|key| is_template_expression(key))
+        }
+    }
+
+    impl Job {
+        pub fn name(&self) -> Option<&str> {
+            self.job.as_deref().or(self.deployment.as_deref())
+        }
+
+        /// Whether the item is a template expression rather than a job
+        pub fn is_expression(&self) -> bool {
+            self.name().is_none()
+                && self.template.is_none()
+                && self.other.keys().any(|key| is_template_expression(key))
+        }
+    }
+
+    impl Step {
+        /// Whether the item is a template expression rather than a step
+        pub fn is_expression(&self) -> bool {
+            self.other.keys().any(|key| is_template_expression(key))
+        }
+    }
+
+    impl Pipeline {
+        /// Paths of the templates the pipeline references, at any level
+        pub fn templates(&self) -> Vec<&str> {
+            let mut templates: Vec<&str> = self
+                .extends
+                .as_ref()
+                .and_then(|extends| extends.get("template"))
+                .and_then(Value::as_str)
+                .into_iter()
+                .collect();
+            let mut jobs: Vec<&Job> = self.jobs.iter().flatten().collect();
+            for stage in self.stages.iter().flatten() {
+                templates.extend(stage.template.as_deref());
+                jobs.extend(stage.jobs.iter().flatten());
+            }
+            let mut steps: Vec<&Step> = self.steps.iter().flatten().collect();
+            for job in jobs {
+                templates.extend(job.template.as_deref());
+                steps.extend(job.steps.iter().flatten());
+            }
+            templates.extend(
+                steps
+                    .into_iter()
+                    .filter_map(|step| step.template.as_deref()),
+            );
+            templates
+        }
+    }
+}
+
+// Jenkins declarative pipeline models
+pub mod jenkins {
+    use serde::{Deserialize, Serialize};
+
+    /// A directive or step of a Jenkinsfile: `name args`, `name(args)` or
+    /// `name(args) { body }`
+    ///
+    /// Declarative pipelines are Groovy, so rather than one type per section the
+    /// file is kept as a tree of these, and validation checks which directives
+    /// may appear where.
+    #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+    pub struct Node {
+        /// Identifier the statement starts with, e.g. `stage` or `sh`
+        pub name: String,
+
+        /// Source text of each top-level argument, quotes included
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub args: Vec<String>,
+
+        /// Statements of the closure following it, if any
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub body: Option<Vec<Node>>,
+
+        /// Whether it's `name = value`, the value being the only argument
+        #[serde(default)]
+        pub assigned: bool,
+
+        /// 1-based position of the name in the file
+        pub line: usize,
+        pub column: usize,
+    }
+
+    impl Node {
+        /// Statements of the body named `name`
+        pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Node> {
+            self.body
+                .iter()
+                .flatten()
+                .filter(move |node| node.name == name)
+        }
+
+        /// The first statement of the body named `name`
+        pub fn child(&self, name: &str) -> Option<&Node> {
+            self.body.iter().flatten().find(|node| node.name == name)
+        }
+
+        /// The first argument if it's a plain string literal, e.g. the name of
+        /// `stage('Build')`
+        pub fn string_arg(&self) -> Option<&str> {
+            self.args.first().and_then(|arg| unquote(arg))
+        }
+
+        /// `at line L column C`, for issues
+        pub fn position(&self) -> String {
+            format!("at line {} column {}", self.line, self.column)
+        }
+    }
+
+    /// The content of a Groovy string literal without interpolation, quotes removed
+    pub fn unquote(text: &str) -> Option<&str> {
+        for quote in ["'''", "\"\"\"", "'", "\""] {
+            if let Some(inner) = text
+                .strip_prefix(quote)
+                .and_then(|rest| rest.strip_suffix(quote))
+            {
+                if quote.starts_with('"') && inner.contains("${") {
+                    return None;
+                }
+                return Some(inner);
+            }
+        }
+        None
+    }
+
+    /// A Jenkinsfile: its top-level statements, normally a single `pipeline` block
+    #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+    pub struct Jenkinsfile {
+        pub nodes: Vec<Node>,
+    }
+
+    impl Jenkinsfile {
+        /// The `pipeline { }` block of a declarative pipeline
+        pub fn pipeline(&self) -> Option<&Node> {
+            self.nodes.iter().find(|node| node.name == "pipeline")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_severity_overrides() {
+        let mut result = ValidationResult::new();
+        result.add_issue("Workflow is missing 'on' section (triggers)".to_string());
+        result.in_rule("secret-leaks", |result| {
+            result.add_warning("prints secrets.TOKEN to the log".to_string())
+        });
+        result.set_rule("expressions");
+        result.add_issue("Unrecognized function 'contain'".to_string());
+        assert_eq!(result.worst(), Some(Severity::Error));
+        assert_eq!(result.findings[1].rule, "secret-leaks");
+
+        let overrides = HashMap::from([
+            ("secret-leaks".to_string(), Some(Severity::Error)),
+            ("expressions".to_string(), None),
+        ]);
+        let result = result.with_severities(&overrides);
+        assert_eq!(
+            result.issues,
+            vec![
+                "Workflow is missing 'on' section (triggers)",
+                "prints secrets.TOKEN to the log",
+            ]
+        );
+        assert!(result.warnings.is_empty());
+        assert_eq!(result.findings.len(), 2);
+
+        let mut info = ValidationResult::new();
+        info.add_info("Uses a deprecated runner".to_string());
+        assert!(info.is_valid);
+        assert_eq!(info.worst(), Some(Severity::Info));
+    }
+}
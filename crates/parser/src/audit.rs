@@ -0,0 +1,383 @@
+//! Deprecations in GitHub Actions workflows and actions, and what replaces them
+//!
+//! Files are audited line by line rather than parsed, so that fixes rewrite only
+//! the deprecated text and keep comments and formatting as they are.
+
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+
+lazy_static! {
+    static ref USES: Regex =
+        Regex::new(r#"^(\s*(?:-\s*)?uses:\s*['"]?)([\w.-]+/[\w./-]+)@v(\d+)((?:\.\d+)*)"#).unwrap();
+    static ref RUNNER_LABEL: Regex = Regex::new(
+        r"\b(ubuntu-(?:16|18|20)\.04|macos-(?:10\.15|11|12|13)|windows-(?:2016|2019))\b"
+    )
+    .unwrap();
+    static ref NODE_RUNTIME: Regex =
+        Regex::new(r#"^(\s*using:\s*['"]?)(node12|node16)(['"]?\s*)$"#).unwrap();
+    static ref WORKFLOW_COMMAND: Regex =
+        Regex::new(r"::(set-output|set-env|add-path|save-state)\b").unwrap();
+    static ref ECHO_COMMAND: Regex = Regex::new(
+        r#"echo\s+(["']?)::(set-output|set-env|save-state) name=([\w-]+)::(.*?)(["']?)\s*$"#
+    )
+    .unwrap();
+    static ref ECHO_ADD_PATH: Regex =
+        Regex::new(r#"echo\s+(["']?)::add-path::(.*?)(["']?)\s*$"#).unwrap();
+}
+
+/// Actions whose earlier major versions are deprecated: the action, the major
+/// version to use and why the earlier ones are
+const ACTION_VERSIONS: &[(&str, u32, &str)] = &[
+    (
+        "actions/checkout",
+        4,
+        "earlier versions run on Node.js 12 or 16",
+    ),
+    (
+        "actions/setup-node",
+        4,
+        "earlier versions run on Node.js 12 or 16",
+    ),
+    (
+        "actions/setup-python",
+        5,
+        "earlier versions run on Node.js 12 or 16",
+    ),
+    (
+        "actions/setup-go",
+        5,
+        "earlier versions run on Node.js 12 or 16",
+    ),
+    (
+        "actions/setup-java",
+        4,
+        "earlier versions run on Node.js 12 or 16",
+    ),
+    (
+        "actions/setup-dotnet",
+        4,
+        "earlier versions run on Node.js 12 or 16",
+    ),
+    (
+        "actions/github-script",
+        7,
+        "earlier versions run on Node.js 12 or 16",
+    ),
+    (
+        "actions/cache",
+        4,
+        "earlier versions use the cache service GitHub retired",
+    ),
+    (
+        "actions/upload-artifact",
+        4,
+        "earlier versions use the artifact storage GitHub retired",
+    ),
+    (
+        "actions/download-artifact",
+        4,
+        "earlier versions use the artifact storage GitHub retired",
+    ),
+    (
+        "docker/login-action",
+        3,
+        "earlier versions run on Node.js 16",
+    ),
+    (
+        "docker/setup-buildx-action",
+        3,
+        "earlier versions run on Node.js 16",
+    ),
+    (
+        "docker/setup-qemu-action",
+        3,
+        "earlier versions run on Node.js 16",
+    ),
+    (
+        "docker/build-push-action",
+        6,
+        "earlier versions run on Node.js 16",
+    ),
+];
+
+/// Archived actions and what to use instead
+const ARCHIVED_ACTIONS: &[(&str, &str)] = &[
+    ("actions/create-release", "softprops/action-gh-release@v2"),
+    (
+        "actions/upload-release-asset",
+        "softprops/action-gh-release@v2",
+    ),
+    ("actions-rs/toolchain", "dtolnay/rust-toolchain"),
+    ("actions-rs/cargo", "plain `cargo` commands in `run:` steps"),
+];
+
+/// Runner images GitHub no longer offers and the one to move to
+const RUNNER_IMAGES: &[(&str, &str)] = &[
+    ("ubuntu-16.04", "ubuntu-22.04"),
+    ("ubuntu-18.04", "ubuntu-22.04"),
+    ("ubuntu-20.04", "ubuntu-22.04"),
+    ("macos-10.15", "macos-15"),
+    ("macos-11", "macos-15"),
+    ("macos-12", "macos-15"),
+    ("macos-13", "macos-15"),
+    ("windows-2016", "windows-2022"),
+    ("windows-2019", "windows-2022"),
+];
+
+/// A deprecated construct
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// 1-based
+    pub line: usize,
+    pub message: String,
+    /// What to use instead
+    pub suggestion: String,
+    /// Whether the audit rewrote it in `Audit::fixed`
+    pub fixable: bool,
+}
+
+/// The result of an audit
+#[derive(Debug, Clone, PartialEq)]
+pub struct Audit {
+    pub findings: Vec<Finding>,
+    /// The file with every fixable finding fixed
+    pub fixed: String,
+}
+
+impl Audit {
+    pub fn fixable(&self) -> usize {
+        self.findings.iter().filter(|f| f.fixable).count()
+    }
+}
+
+/// Audit the content of a workflow or `action.yml`
+pub fn audit(content: &str) -> Audit {
+    let mut findings = Vec::new();
+    let mut fixed = String::with_capacity(content.len());
+    for (idx, line) in content.split_inclusive('\n').enumerate() {
+        let (text, ending) = match line.strip_suffix('\n') {
+            Some(text) => (text, "\n"),
+            None => (line, ""),
+        };
+        let mut found = |message: String, suggestion: String, fixable: bool| {
+            findings.push(Finding {
+                line: idx + 1,
+                message,
+                suggestion,
+                fixable,
+            })
+        };
+        let text = if text.trim_start().starts_with('#') {
+            text.to_string()
+        } else {
+            let text = audit_uses(text, &mut found);
+            let text = audit_runner_labels(&text, &mut found);
+            let text = audit_node_runtime(&text, &mut found);
+            audit_workflow_commands(&text, &mut found)
+        };
+        fixed.push_str(&text);
+        fixed.push_str(ending);
+    }
+    Audit { findings, fixed }
+}
+
+fn audit_uses(text: &str, found: &mut impl FnMut(String, String, bool)) -> String {
+    if let Some((action, replacement)) = ARCHIVED_ACTIONS.iter().find(|(action, _)| {
+        text.contains(&format!("uses: {}@", action))
+            || text.contains(&format!("uses: '{}@", action))
+            || text.contains(&format!("uses: \"{}@", action))
+    }) {
+        found(
+            format!("{} is archived and no longer maintained", action),
+            format!("use {}", replacement),
+            false,
+        );
+        return text.to_string();
+    }
+
+    let Some(caps) = USES.captures(text) else {
+        return text.to_string();
+    };
+    let (action, major) = (&caps[2], caps[3].parse::<u32>().unwrap_or(u32::MAX));
+    let Some((_, current, why)) = ACTION_VERSIONS
+        .iter()
+        .find(|(name, current, _)| *name == action && major < *current)
+    else {
+        return text.to_string();
+    };
+    found(
+        format!("{}@v{}{} is deprecated: {}", action, major, &caps[4], why),
+        format!("use {}@v{}", action, current),
+        true,
+    );
+    USES.replace(text, |caps: &Captures| {
+        format!("{}{}@v{}", &caps[1], &caps[2], current)
+    })
+    .into_owned()
+}
+
+fn audit_runner_labels(text: &str, found: &mut impl FnMut(String, String, bool)) -> String {
+    let mut labels = Vec::new();
+    let fixed = RUNNER_LABEL.replace_all(text, |caps: &Captures| {
+        let whole = caps.get(0).unwrap();
+        // Labels such as ubuntu-20.04-arm or ubuntu-18.04-large are self-hosted ones
+        let next = text[whole.end()..].chars().next();
+        let label = whole.as_str();
+        match RUNNER_IMAGES.iter().find(|(old, _)| *old == label) {
+            Some((old, new)) if !matches!(next, Some('-' | '.')) => {
+                labels.push((*old, *new));
+                new.to_string()
+            }
+            _ => label.to_string(),
+        }
+    });
+    for (old, new) in labels {
+        found(
+            format!("the {} runner image has been retired", old),
+            format!("use {}", new),
+            true,
+        );
+    }
+    fixed.into_owned()
+}
+
+fn audit_node_runtime(text: &str, found: &mut impl FnMut(String, String, bool)) -> String {
+    let Some(caps) = NODE_RUNTIME.captures(text) else {
+        return text.to_string();
+    };
+    found(
+        format!(
+            "`using: {}` runs the action on a Node.js version GitHub Actions has dropped",
+            &caps[2]
+        ),
+        "use `using: node20` and check the action works on Node.js 20".to_string(),
+        true,
+    );
+    format!("{}node20{}", &caps[1], &caps[3])
+}
+
+fn audit_workflow_commands(text: &str, found: &mut impl FnMut(String, String, bool)) -> String {
+    let Some(caps) = WORKFLOW_COMMAND.captures(text) else {
+        return text.to_string();
+    };
+    let command = caps[1].to_string();
+    let file = match command.as_str() {
+        "set-output" => "GITHUB_OUTPUT",
+        "set-env" => "GITHUB_ENV",
+        "add-path" => "GITHUB_PATH",
+        _ => "GITHUB_STATE",
+    };
+    let message = format!(
+        "the `::{}` workflow command is disabled on GitHub for security reasons",
+        command
+    );
+
+    let rewritten = if command == "add-path" {
+        ECHO_ADD_PATH.captures(text).and_then(|caps| {
+            (caps[1] == caps[3]).then(|| {
+                let quote = &caps[1];
+                ECHO_ADD_PATH
+                    .replace(text, |_: &Captures| {
+                        format!("echo {0}{1}{0} >> \"${2}\"", quote, &caps[2], file)
+                    })
+                    .into_owned()
+            })
+        })
+    } else {
+        ECHO_COMMAND.captures(text).and_then(|caps| {
+            (caps[1] == caps[5]).then(|| {
+                let quote = &caps[1];
+                ECHO_COMMAND
+                    .replace(text, |_: &Captures| {
+                        format!(
+                            "echo {0}{1}={2}{0} >> \"${3}\"",
+                            quote, &caps[3], &caps[4], file
+                        )
+                    })
+                    .into_owned()
+            })
+        })
+    };
+    let suggestion = match command.as_str() {
+        "add-path" => format!("append the directory to \"${}\"", file),
+        _ => format!("append `name=value` to \"${}\"", file),
+    };
+    found(message, suggestion, rewritten.is_some());
+    rewritten.unwrap_or_else(|| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_and_fix() {
+        let workflow = r#"jobs:
+  build:
+    runs-on: ubuntu-18.04
+    strategy:
+      matrix:
+        os: [ubuntu-20.04-arm, windows-2019, macos-14]
+    steps:
+      # actions/checkout@v2 in a comment is left alone
+      - uses: actions/checkout@v2
+      - uses: "actions/cache@v3.0.1"
+      - uses: actions/checkout@v4
+      - uses: actions-rs/toolchain@v1
+      - run: |
+          echo "::set-output name=version::1.2.3"
+          echo ::add-path::/opt/bin
+          printf '::set-env name=A::b\n'
+"#;
+        let audit = audit(workflow);
+        let lines: Vec<(usize, bool)> = audit
+            .findings
+            .iter()
+            .map(|finding| (finding.line, finding.fixable))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                (3, true),
+                (6, true),
+                (9, true),
+                (10, true),
+                (12, false),
+                (14, true),
+                (15, true),
+                (16, false),
+            ]
+        );
+        assert_eq!(audit.fixable(), 6);
+        assert_eq!(
+            audit.fixed,
+            r#"jobs:
+  build:
+    runs-on: ubuntu-22.04
+    strategy:
+      matrix:
+        os: [ubuntu-20.04-arm, windows-2022, macos-14]
+    steps:
+      # actions/checkout@v2 in a comment is left alone
+      - uses: actions/checkout@v4
+      - uses: "actions/cache@v4"
+      - uses: actions/checkout@v4
+      - uses: actions-rs/toolchain@v1
+      - run: |
+          echo "version=1.2.3" >> "$GITHUB_OUTPUT"
+          echo /opt/bin >> "$GITHUB_PATH"
+          printf '::set-env name=A::b\n'
+"#
+        );
+        // Fixed files have nothing fixable left
+        assert_eq!(super::audit(&audit.fixed).fixable(), 0);
+    }
+
+    #[test]
+    fn test_node_runtime() {
+        let action = "runs:\n  using: 'node12'\n  main: index.js\n";
+        let audit = audit(action);
+        assert_eq!(audit.findings.len(), 1);
+        assert_eq!(audit.fixed, "runs:\n  using: 'node20'\n  main: index.js\n");
+    }
+}
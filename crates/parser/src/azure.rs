@@ -0,0 +1,125 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use wrkflw_models::azure::Pipeline;
+
+#[derive(Error, Debug)]
+pub enum AzureParserError {
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("YAML parsing error: {0}")]
+    YamlError(#[from] serde_yaml::Error),
+}
+
+/// Parse an Azure Pipelines file
+pub fn parse_pipeline(pipeline_path: &Path) -> Result<Pipeline, AzureParserError> {
+    let pipeline_content = fs::read_to_string(pipeline_path)?;
+    Ok(serde_yaml::from_str(&pipeline_content)?)
+}
+
+/// Local templates the pipeline at `pipeline_path` references that don't exist.
+///
+/// Relative paths are resolved from the pipeline's directory and absolute ones
+/// from the repository root; templates in other repositories (`path@repo`) and
+/// paths built from expressions aren't checked.
+pub fn missing_templates(pipeline: &Pipeline, pipeline_path: &Path) -> Vec<String> {
+    let dir = pipeline_path.parent().unwrap_or(Path::new("."));
+    let root = repository_root(dir);
+    pipeline
+        .templates()
+        .into_iter()
+        .filter(|template| {
+            !template.contains('@') && !template.contains("$(") && !template.contains("${{")
+        })
+        .filter(|template| {
+            let path = match template.strip_prefix('/') {
+                Some(from_root) => root.join(from_root),
+                None => dir.join(template),
+            };
+            !path.is_file()
+        })
+        .map(|template| format!("Template '{}' not found", template))
+        .collect()
+}
+
+/// The closest directory from `dir` up that's a git repository's root, else `dir`
+fn repository_root(dir: &Path) -> PathBuf {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    dir.ancestors()
+        .find(|ancestor| ancestor.join(".git").exists())
+        .unwrap_or(&dir)
+        .to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pipeline() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("azure-pipelines.yml");
+        fs::write(
+            &path,
+            r#"
+trigger:
+  - main
+pool:
+  vmImage: ubuntu-latest
+stages:
+  - stage: Build
+    jobs:
+      - job: Compile
+        steps:
+          - checkout: self
+          - script: cargo build
+            displayName: Build
+          - task: PublishBuildArtifacts@1
+            inputs:
+              pathToPublish: target
+          - template: steps/test.yml
+  - stage: Deploy
+    dependsOn: Build
+    jobs:
+      - deployment: Production
+        environment: production
+        strategy:
+          runOnce:
+            deploy:
+              steps:
+                - script: ./deploy.sh
+  - template: stages/release.yml@templates
+"#,
+        )
+        .unwrap();
+        fs::create_dir(dir.path().join("steps")).unwrap();
+        fs::write(dir.path().join("steps/test.yml"), "steps: []\n").unwrap();
+
+        let pipeline = parse_pipeline(&path).unwrap();
+        let stages = pipeline.stages.as_ref().unwrap();
+        assert_eq!(stages.len(), 3);
+        assert_eq!(
+            stages[1].depends_on.as_ref().unwrap().names(),
+            vec!["Build"]
+        );
+        let jobs = stages[0].jobs.as_ref().unwrap();
+        let steps = jobs[0].steps.as_ref().unwrap();
+        assert_eq!(steps[2].task.as_deref(), Some("PublishBuildArtifacts@1"));
+        assert_eq!(
+            stages[1].jobs.as_ref().unwrap()[0].name(),
+            Some("Production")
+        );
+        assert_eq!(
+            pipeline.templates(),
+            vec!["stages/release.yml@templates", "steps/test.yml"]
+        );
+        assert!(missing_templates(&pipeline, &path).is_empty());
+
+        fs::remove_file(dir.path().join("steps/test.yml")).unwrap();
+        assert_eq!(
+            missing_templates(&pipeline, &path),
+            vec!["Template 'steps/test.yml' not found"]
+        );
+    }
+}
@@ -0,0 +1,1729 @@
+//! Translating GitHub Actions workflows into GitLab CI/CD pipelines and back
+//!
+//! Files are parsed into the same models the executor runs, which carry the
+//! translation; the raw YAML is read alongside for the order of jobs and for the
+//! keys the models leave out, so that what can't be translated is reported. Each
+//! such construct gets a `# TODO:` comment above the job or section it concerns.
+
+use crate::{gitlab, workflow};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_yaml::{Mapping, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use wrkflw_models::gitlab::{CacheKey, Image, Job as GitlabJob, Parallel, Pipeline, Service};
+
+lazy_static! {
+    static ref EXPRESSION: Regex = Regex::new(r"\$\{\{\s*(.*?)\s*\}\}").unwrap();
+    static ref SHELL_VARIABLE: Regex =
+        Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)\}|\$([A-Za-z_][A-Za-z0-9_]*)").unwrap();
+    static ref DURATION_PART: Regex =
+        Regex::new(r"(\d+)\s*(hours?|hrs?|h|minutes?|mins?|m|seconds?|secs?|s)\b").unwrap();
+    static ref EXPIRY_PART: Regex = Regex::new(r"(\d+)\s*(days?|d|weeks?|w|months?|mo)\b").unwrap();
+}
+
+/// Predefined variables of GitLab, the environment variable GitHub sets for the
+/// same value, and the context expression holding it
+const PREDEFINED: &[(&str, &str, &str)] = &[
+    ("CI_COMMIT_SHA", "GITHUB_SHA", "github.sha"),
+    ("CI_COMMIT_REF_NAME", "GITHUB_REF_NAME", "github.ref_name"),
+    ("CI_COMMIT_REF_SLUG", "GITHUB_REF_NAME", "github.ref_name"),
+    ("CI_COMMIT_BRANCH", "GITHUB_REF_NAME", "github.ref_name"),
+    ("CI_PROJECT_DIR", "GITHUB_WORKSPACE", "github.workspace"),
+    ("CI_PROJECT_PATH", "GITHUB_REPOSITORY", "github.repository"),
+    ("CI_PIPELINE_ID", "GITHUB_RUN_ID", "github.run_id"),
+    ("CI_PIPELINE_IID", "GITHUB_RUN_NUMBER", "github.run_number"),
+    ("CI_JOB_NAME", "GITHUB_JOB", "github.job"),
+    ("GITLAB_USER_LOGIN", "GITHUB_ACTOR", "github.actor"),
+    (
+        "CI_PIPELINE_SOURCE",
+        "GITHUB_EVENT_NAME",
+        "github.event_name",
+    ),
+];
+
+/// The result of a conversion
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conversion {
+    /// The converted file, TODO comments included
+    pub yaml: String,
+    /// What couldn't be translated, as in the TODO comments
+    pub todos: Vec<String>,
+}
+
+/// YAML being written, section by section
+#[derive(Default)]
+struct Output {
+    yaml: String,
+    todos: Vec<String>,
+}
+
+impl Output {
+    fn comment(&mut self, text: &str) {
+        self.yaml.push_str(&format!("# {}\n", text));
+    }
+
+    /// Write `key: value` indented by `indent`, preceded by a TODO comment for each
+    /// of `todos`
+    fn entry(&mut self, indent: usize, key: &str, value: Value, todos: Vec<String>) {
+        let padding = " ".repeat(indent);
+        for todo in &todos {
+            self.yaml
+                .push_str(&format!("{}# TODO: {}\n", padding, todo));
+        }
+        let mut entry = Mapping::new();
+        entry.insert(key.into(), value);
+        for line in serde_yaml::to_string(&entry).unwrap_or_default().lines() {
+            if line.is_empty() {
+                self.yaml.push('\n');
+            } else {
+                self.yaml.push_str(&format!("{}{}\n", padding, line));
+            }
+        }
+        self.todos
+            .extend(todos.into_iter().map(|todo| format!("{}: {}", key, todo)));
+    }
+
+    fn finish(self) -> Conversion {
+        Conversion {
+            yaml: self.yaml,
+            todos: self.todos,
+        }
+    }
+}
+
+fn mapping<K: Into<String>>(entries: impl IntoIterator<Item = (K, Value)>) -> Value {
+    Value::Mapping(
+        entries
+            .into_iter()
+            .map(|(key, value)| (Value::String(key.into()), value))
+            .collect(),
+    )
+}
+
+fn strings<S: Into<String>>(items: impl IntoIterator<Item = S>) -> Value {
+    Value::Sequence(
+        items
+            .into_iter()
+            .map(|item| Value::String(item.into()))
+            .collect(),
+    )
+}
+
+/// A scalar as the string a variable would hold
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(flag) => Some(flag.to_string()),
+        _ => None,
+    }
+}
+
+fn read_raw(path: &Path) -> Result<Value, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_yaml::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Keys of a raw mapping, in the order of the file
+fn keys(raw: Option<&Value>) -> Vec<String> {
+    raw.and_then(Value::as_mapping)
+        .map(|mapping| {
+            mapping
+                .keys()
+                .filter_map(|key| key.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Keys of a raw mapping that aren't in `handled`
+fn untranslated_keys(raw: Option<&Value>, handled: &[&str]) -> Vec<String> {
+    keys(raw)
+        .into_iter()
+        .filter(|key| !handled.contains(&key.as_str()))
+        .collect()
+}
+
+// GitHub Actions to GitLab CI/CD
+
+/// Translate `${{ }}` expressions and GitHub's environment variables in `text`
+/// into GitLab variables, noting the expressions that have no equivalent
+fn github_text(text: &str, todos: &mut Vec<String>) -> String {
+    let text = EXPRESSION.replace_all(text, |captures: &regex::Captures| {
+        let expression = &captures[1];
+        let variable = ["secrets.", "env.", "matrix.", "vars."]
+            .iter()
+            .find_map(|prefix| expression.strip_prefix(prefix))
+            .filter(|name| name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+            .map(str::to_string)
+            .or_else(|| {
+                PREDEFINED
+                    .iter()
+                    .find(|(_, _, context)| *context == expression)
+                    .map(|(gitlab, _, _)| gitlab.to_string())
+            });
+        match variable {
+            Some(variable) => format!("${}", variable),
+            None => {
+                let todo = format!("`{}` isn't translated", &captures[0]);
+                if !todos.contains(&todo) {
+                    todos.push(todo);
+                }
+                captures[0].to_string()
+            }
+        }
+    });
+    SHELL_VARIABLE
+        .replace_all(&text, |captures: &regex::Captures| {
+            let name = captures
+                .get(1)
+                .or_else(|| captures.get(2))
+                .map(|name| name.as_str())
+                .unwrap_or_default();
+            if let Some((gitlab, _, _)) = PREDEFINED.iter().find(|(_, github, _)| *github == name)
+            {
+                return format!("${}", gitlab);
+            }
+            if matches!(
+                name,
+                "GITHUB_OUTPUT" | "GITHUB_ENV" | "GITHUB_PATH" | "GITHUB_STEP_SUMMARY"
+            ) {
+                let todo = format!(
+                    "`${}` has no GitLab equivalent; pass values between jobs with dotenv artifacts",
+                    name
+                );
+                if !todos.contains(&todo) {
+                    todos.push(todo);
+                }
+            }
+            captures[0].to_string()
+        })
+        .into_owned()
+}
+
+/// Rules of `workflow:` for GitHub's triggers
+fn github_triggers(on: &Value, todos: &mut Vec<String>) -> Vec<Value> {
+    let events: Vec<(String, Option<&Value>)> = match on {
+        Value::String(event) => vec![(event.clone(), None)],
+        Value::Sequence(events) => events
+            .iter()
+            .filter_map(|event| event.as_str().map(|event| (event.to_string(), None)))
+            .collect(),
+        Value::Mapping(events) => events
+            .iter()
+            .filter_map(|(event, config)| {
+                event
+                    .as_str()
+                    .map(|event| (event.to_string(), Some(config)))
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let mut rules = Vec::new();
+    for (event, config) in events {
+        let source = match event.as_str() {
+            "push" => "push",
+            "pull_request" | "pull_request_target" => "merge_request_event",
+            "schedule" => "schedule",
+            "workflow_dispatch" => "web",
+            _ => {
+                todos.push(format!("trigger `{}` isn't translated", event));
+                continue;
+            }
+        };
+        let mut condition = format!("$CI_PIPELINE_SOURCE == \"{}\"", source);
+        let config = config.and_then(Value::as_mapping);
+        for filter in config.into_iter().flat_map(|config| config.keys()) {
+            let filter = filter.as_str().unwrap_or_default();
+            let branches: Option<Vec<String>> = config
+                .and_then(|config| config.get(filter))
+                .and_then(Value::as_sequence)
+                .map(|branches| branches.iter().filter_map(scalar).collect());
+            match (filter, branches) {
+                ("branches", Some(branches))
+                    if !branches.is_empty() && branches.iter().all(|b| !b.contains('*')) =>
+                {
+                    let variable = if source == "merge_request_event" {
+                        "$CI_MERGE_REQUEST_TARGET_BRANCH_NAME"
+                    } else {
+                        "$CI_COMMIT_BRANCH"
+                    };
+                    let any = branches
+                        .iter()
+                        .map(|branch| format!("{} == \"{}\"", variable, branch))
+                        .collect::<Vec<_>>()
+                        .join(" || ");
+                    condition = format!("{} && ({})", condition, any);
+                }
+                ("types", _) if event.starts_with("pull_request") => {}
+                _ => todos.push(format!(
+                    "`{}` filter of `{}` isn't translated",
+                    filter, event
+                )),
+            }
+        }
+        if event == "schedule" {
+            todos.push(
+                "schedules are set up in the project's CI/CD settings, not in the pipeline"
+                    .to_string(),
+            );
+        }
+        rules.push(mapping([("if", Value::String(condition))]));
+    }
+    rules
+}
+
+/// GitLab image for a GitHub-hosted runner label
+fn runner_image(label: &str) -> Option<String> {
+    let version = label.strip_prefix("ubuntu-")?;
+    Some(format!("ubuntu:{}", version))
+}
+
+/// Stage of each job: one more than the latest of the jobs it needs
+fn github_stages(workflow: &workflow::WorkflowDefinition) -> HashMap<String, usize> {
+    fn depth(
+        name: &str,
+        workflow: &workflow::WorkflowDefinition,
+        depths: &mut HashMap<String, usize>,
+        visiting: &mut HashSet<String>,
+    ) -> usize {
+        if let Some(depth) = depths.get(name) {
+            return *depth;
+        }
+        if !visiting.insert(name.to_string()) {
+            return 1;
+        }
+        let needs = workflow
+            .jobs
+            .get(name)
+            .and_then(|job| job.needs.clone())
+            .unwrap_or_default();
+        let depth = 1 + needs
+            .iter()
+            .filter(|need| workflow.jobs.contains_key(*need))
+            .map(|need| depth(need, workflow, depths, visiting))
+            .max()
+            .unwrap_or(0);
+        depths.insert(name.to_string(), depth);
+        depth
+    }
+
+    let mut depths = HashMap::new();
+    for name in workflow.jobs.keys() {
+        depth(name, workflow, &mut depths, &mut HashSet::new());
+    }
+    depths
+}
+
+/// Convert a GitHub Actions workflow into a GitLab CI/CD pipeline
+pub fn github_to_gitlab(path: &Path) -> Result<Conversion, String> {
+    let workflow = workflow::parse_workflow(path)?;
+    let raw = read_raw(path)?;
+    let mut out = Output::default();
+    out.comment(&format!(
+        "Converted from the GitHub Actions workflow {} by `wrkflw convert`",
+        path.display()
+    ));
+    out.yaml.push('\n');
+
+    let mut todos = Vec::new();
+    let rules = github_triggers(&workflow.on_raw, &mut todos);
+    if !rules.is_empty() {
+        out.entry(
+            0,
+            "workflow",
+            mapping([("rules", Value::Sequence(rules))]),
+            todos,
+        );
+        out.yaml.push('\n');
+    }
+
+    let mut todos = Vec::new();
+    let variables: Vec<(String, Value)> = raw
+        .get("env")
+        .and_then(Value::as_mapping)
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, value)| Some((key.as_str()?.to_string(), scalar(value)?)))
+        .map(|(key, value)| (key, Value::String(github_text(&value, &mut todos))))
+        .collect();
+    if !variables.is_empty() {
+        out.entry(0, "variables", mapping(variables), todos);
+        out.yaml.push('\n');
+    }
+
+    let mut todos: Vec<String> = untranslated_keys(
+        Some(&raw),
+        &[
+            "name",
+            "on",
+            "env",
+            "jobs",
+            "run-name",
+            "permissions",
+            "defaults",
+        ],
+    )
+    .into_iter()
+    .map(|key| format!("`{}` isn't translated", key))
+    .collect();
+    if raw.get("permissions").is_some() {
+        todos.push(
+            "`permissions` aren't translated; the job token's access is set in the project's settings"
+                .to_string(),
+        );
+    }
+    let depths = github_stages(&workflow);
+    let stage_count = depths.values().copied().max().unwrap_or(1);
+    out.entry(
+        0,
+        "stages",
+        strings((1..=stage_count).map(|stage| format!("stage-{}", stage))),
+        todos,
+    );
+
+    for name in keys(raw.get("jobs")) {
+        let Some(job) = workflow.jobs.get(&name) else {
+            continue;
+        };
+        let raw_job = raw.get("jobs").and_then(|jobs| jobs.get(&name));
+        let stage = depths.get(&name).copied().unwrap_or(1);
+        let (value, todos) = github_job(&workflow, job, raw_job, stage);
+        let key = if gitlab::RESERVED_KEYS.contains(&name.as_str()) || name.starts_with('.') {
+            format!("{}-job", name.trim_start_matches('.'))
+        } else {
+            name
+        };
+        out.yaml.push('\n');
+        out.entry(0, &key, value, todos);
+    }
+
+    Ok(out.finish())
+}
+
+fn github_job(
+    workflow: &workflow::WorkflowDefinition,
+    job: &workflow::Job,
+    raw: Option<&Value>,
+    stage: usize,
+) -> (Value, Vec<String>) {
+    let mut todos = Vec::new();
+    let mut entries: Vec<(String, Value)> = vec![(
+        "stage".to_string(),
+        Value::String(format!("stage-{}", stage)),
+    )];
+
+    if let Some(uses) = &job.uses {
+        todos.push(format!(
+            "calls the reusable workflow `{}`; convert it too and `include` it, or trigger it as a downstream pipeline",
+            uses
+        ));
+        entries.push((
+            "script".to_string(),
+            strings([format!("echo 'TODO: run {}'", uses)]),
+        ));
+        return (mapping(entries), todos);
+    }
+
+    for key in untranslated_keys(
+        raw,
+        &[
+            "name",
+            "runs-on",
+            "needs",
+            "steps",
+            "env",
+            "strategy",
+            "services",
+            "if",
+            "outputs",
+            "defaults",
+            "container",
+            "timeout-minutes",
+            "continue-on-error",
+            "environment",
+        ],
+    ) {
+        todos.push(format!("`{}` isn't translated", key));
+    }
+
+    // Image: the job's container, else one matching the hosted runner
+    let container = raw
+        .and_then(|raw| raw.get("container"))
+        .and_then(|container| {
+            container
+                .as_str()
+                .or_else(|| container.get("image").and_then(Value::as_str))
+        });
+    let runs_on = job.runs_on.clone().unwrap_or_default();
+    let image = container.map(str::to_string).or_else(|| {
+        let image = runs_on.first().and_then(|label| runner_image(label));
+        if image.is_none() && !runs_on.is_empty() {
+            todos.push(format!(
+                "`runs-on: {}` has no GitLab image; set `image` or runner `tags`",
+                runs_on.join(", ")
+            ));
+        }
+        image
+    });
+    if let Some(image) = image {
+        entries.push(("image".to_string(), Value::String(image)));
+    }
+
+    if let Some(needs) = &job.needs {
+        entries.push(("needs".to_string(), strings(needs.clone())));
+    }
+
+    if let Some(condition) = &job.if_condition {
+        todos.push(format!(
+            "runs only `if: {}`; translate it into `rules`",
+            condition
+        ));
+    }
+
+    let mut variables: Vec<(String, String)> = job
+        .env
+        .iter()
+        .map(|(key, value)| (key.clone(), github_text(value, &mut todos)))
+        .collect();
+    variables.sort();
+    let mut add_variable = |key: &str, value: String, todos: &mut Vec<String>| match variables
+        .iter()
+        .find(|(existing, _)| existing == key)
+    {
+        Some((_, existing)) if *existing != value => todos.push(format!(
+            "`{}` is set to different values by steps, but GitLab variables are per job",
+            key
+        )),
+        Some(_) => {}
+        None => variables.push((key.to_string(), value)),
+    };
+
+    // Services, which get the job's variables in GitLab
+    let mut service_names: Vec<&String> = job.services.keys().collect();
+    service_names.sort();
+    let mut services = Vec::new();
+    for name in service_names {
+        let service = &job.services[name];
+        services.push(mapping([
+            ("name", Value::String(service.image.clone())),
+            ("alias", Value::String(name.clone())),
+        ]));
+        let mut service_env: Vec<_> = service.env.iter().collect();
+        service_env.sort();
+        for (key, value) in service_env {
+            let value = github_text(value, &mut todos);
+            add_variable(key, value, &mut todos);
+        }
+        if service.options.is_some() || service.volumes.is_some() {
+            todos.push(format!(
+                "`options` and `volumes` of service `{}` aren't translated",
+                name
+            ));
+        }
+    }
+
+    // Matrix
+    let mut parallel = None;
+    if let Some(matrix) = job
+        .strategy
+        .as_ref()
+        .and_then(|strategy| strategy.matrix.as_ref())
+    {
+        match matrix.as_mapping() {
+            Some(matrix) => {
+                let mut dimensions = Vec::new();
+                for (key, values) in matrix {
+                    let key = key.as_str().unwrap_or_default();
+                    match values.as_sequence() {
+                        _ if key == "include" || key == "exclude" => todos.push(format!(
+                            "matrix `{}` isn't translated; list the combinations in `parallel: matrix`",
+                            key
+                        )),
+                        Some(values) => dimensions.push((
+                            key.to_string(),
+                            strings(values.iter().filter_map(scalar)),
+                        )),
+                        None => todos.push(format!("matrix `{}` isn't a list of values", key)),
+                    }
+                }
+                if !dimensions.is_empty() {
+                    parallel = Some(mapping([(
+                        "matrix",
+                        Value::Sequence(vec![mapping(dimensions)]),
+                    )]));
+                }
+            }
+            None => todos.push("the matrix is computed by an expression".to_string()),
+        }
+    }
+
+    // Steps
+    let defaults = workflow.run_defaults(job);
+    let raw_steps: Vec<&Value> = raw
+        .and_then(|raw| raw.get("steps"))
+        .and_then(Value::as_sequence)
+        .map(|steps| steps.iter().collect())
+        .unwrap_or_default();
+    let mut script = Vec::new();
+    let mut directory: Option<String> = None;
+    let mut cache = None;
+    let mut artifacts = None;
+    for (index, step) in job.steps.iter().enumerate() {
+        let raw_step = raw_steps.get(index).copied();
+        let label = step
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("{}", index + 1));
+        let condition = raw_step.and_then(|step| step.get("if")).and_then(scalar);
+
+        for key in untranslated_keys(
+            raw_step,
+            &[
+                "name",
+                "id",
+                "uses",
+                "run",
+                "with",
+                "env",
+                "shell",
+                "working-directory",
+                "if",
+                "continue-on-error",
+            ],
+        ) {
+            todos.push(format!("`{}` of step `{}` isn't translated", key, label));
+        }
+        if step.continue_on_error == Some(true)
+            || raw_step
+                .and_then(|step| step.get("continue-on-error"))
+                .is_some()
+        {
+            todos.push(format!(
+                "step `{}` may fail; GitLab only has `allow_failure` for whole jobs",
+                label
+            ));
+        }
+        let mut step_env: Vec<_> = step.env.iter().collect();
+        step_env.sort();
+        for (key, value) in step_env {
+            let value = github_text(value, &mut todos);
+            add_variable(key, value, &mut todos);
+        }
+        let with = |key: &str| step.with.as_ref().and_then(|with| with.get(key)).cloned();
+
+        if let Some(uses) = &step.uses {
+            let action = uses.split('@').next().unwrap_or_default();
+            match action {
+                "actions/checkout" => {
+                    // GitLab clones the repository before the script
+                    if let Some(depth) = with("fetch-depth") {
+                        add_variable("GIT_DEPTH", depth, &mut todos);
+                    }
+                    if with("submodules").is_some_and(|s| s == "true" || s == "recursive") {
+                        let strategy = if with("submodules").as_deref() == Some("true") {
+                            "normal"
+                        } else {
+                            "recursive"
+                        };
+                        add_variable("GIT_SUBMODULE_STRATEGY", strategy.to_string(), &mut todos);
+                    }
+                }
+                "actions/cache" | "actions/cache/restore" | "actions/cache/save" => {
+                    let mut entries = Vec::new();
+                    if let Some(key) = with("key") {
+                        entries.push(("key", Value::String(github_text(&key, &mut todos))));
+                    }
+                    let paths = with("path").unwrap_or_default();
+                    entries.push((
+                        "paths",
+                        strings(paths.lines().map(str::trim).filter(|p| !p.is_empty())),
+                    ));
+                    if action.ends_with("restore") {
+                        entries.push(("policy", Value::String("pull".to_string())));
+                    } else if action.ends_with("save") {
+                        entries.push(("policy", Value::String("push".to_string())));
+                    }
+                    cache = Some(mapping(entries));
+                }
+                "actions/upload-artifact" => {
+                    let mut entries = Vec::new();
+                    if let Some(name) = with("name") {
+                        entries.push(("name", Value::String(github_text(&name, &mut todos))));
+                    }
+                    let paths = with("path").unwrap_or_default();
+                    entries.push((
+                        "paths",
+                        strings(paths.lines().map(str::trim).filter(|p| !p.is_empty())),
+                    ));
+                    match condition.as_deref() {
+                        Some(condition) if condition.contains("always()") => {
+                            entries.push(("when", Value::String("always".to_string())))
+                        }
+                        Some(condition) if condition.contains("failure()") => {
+                            entries.push(("when", Value::String("on_failure".to_string())))
+                        }
+                        _ => {}
+                    }
+                    if let Some(days) = with("retention-days") {
+                        entries.push(("expire_in", Value::String(format!("{} days", days))));
+                    }
+                    artifacts = Some(mapping(entries));
+                }
+                // Artifacts of the jobs in `needs` are downloaded before the script
+                "actions/download-artifact" => {}
+                action if action.starts_with("actions/setup-") => todos.push(format!(
+                    "step `{}` uses `{}`; use an image that provides the tool instead",
+                    label, uses
+                )),
+                _ => todos.push(format!(
+                    "step `{}` uses the action `{}`, which has no GitLab equivalent",
+                    label, uses
+                )),
+            }
+            if let Some(condition) = condition
+                .as_ref()
+                .filter(|_| action != "actions/upload-artifact")
+            {
+                todos.push(format!("step `{}` runs only `if: {}`", label, condition));
+            }
+            continue;
+        }
+
+        if let Some(run) = &step.run {
+            if let Some(condition) = &condition {
+                todos.push(format!("step `{}` runs only `if: {}`", label, condition));
+            }
+            let shell = step.shell.clone().or(defaults.shell.clone());
+            if let Some(shell) = shell.filter(|shell| shell != "bash" && shell != "sh") {
+                todos.push(format!(
+                    "step `{}` runs with `{}`, but GitLab scripts run in the image's shell",
+                    label, shell
+                ));
+            }
+            let working_directory = step
+                .working_directory
+                .clone()
+                .or(defaults.working_directory.clone());
+            if working_directory != directory {
+                script.push(match &working_directory {
+                    Some(dir) => format!("cd \"$CI_PROJECT_DIR/{}\"", dir),
+                    None => "cd \"$CI_PROJECT_DIR\"".to_string(),
+                });
+                directory = working_directory;
+            }
+            script.push(github_text(run.trim_end(), &mut todos));
+        }
+    }
+
+    if !variables.is_empty() {
+        entries.push((
+            "variables".to_string(),
+            mapping(
+                variables
+                    .into_iter()
+                    .map(|(key, value)| (key, Value::String(value))),
+            ),
+        ));
+    }
+    if !services.is_empty() {
+        entries.push(("services".to_string(), Value::Sequence(services)));
+    }
+    if let Some(parallel) = parallel {
+        entries.push(("parallel".to_string(), parallel));
+    }
+    if let Some(cache) = cache {
+        entries.push(("cache".to_string(), cache));
+    }
+    if script.is_empty() {
+        script.push("echo 'No commands to run'".to_string());
+    }
+    entries.push(("script".to_string(), strings(script)));
+    if let Some(artifacts) = artifacts {
+        entries.push(("artifacts".to_string(), artifacts));
+    }
+
+    if let Some(raw) = raw {
+        if let Some(minutes) = raw.get("timeout-minutes").and_then(scalar) {
+            entries.push((
+                "timeout".to_string(),
+                Value::String(format!("{} minutes", minutes)),
+            ));
+        }
+        if raw.get("continue-on-error").and_then(Value::as_bool) == Some(true) {
+            entries.push(("allow_failure".to_string(), Value::Bool(true)));
+        }
+        if let Some(environment) = raw.get("environment") {
+            let environment = match environment {
+                Value::Mapping(_) => {
+                    let mut entries = Vec::new();
+                    for key in ["name", "url"] {
+                        if let Some(value) = environment.get(key).and_then(scalar) {
+                            entries.push((key, Value::String(github_text(&value, &mut todos))));
+                        }
+                    }
+                    mapping(entries)
+                }
+                other => Value::String(github_text(&scalar(other).unwrap_or_default(), &mut todos)),
+            };
+            entries.push(("environment".to_string(), environment));
+        }
+    }
+    if job.outputs.is_some() {
+        todos.push(
+            "`outputs` aren't translated; pass values to later jobs with `artifacts: reports: dotenv`"
+                .to_string(),
+        );
+    }
+
+    (mapping(entries), todos)
+}
+
+// GitLab CI/CD to GitHub Actions
+
+/// Translate GitLab's predefined variables in a shell script into GitHub's
+fn gitlab_script(text: &str, todos: &mut Vec<String>) -> String {
+    SHELL_VARIABLE
+        .replace_all(text, |captures: &regex::Captures| {
+            let name = captures
+                .get(1)
+                .or_else(|| captures.get(2))
+                .map(|name| name.as_str())
+                .unwrap_or_default();
+            if let Some((_, github, _)) = PREDEFINED.iter().find(|(gitlab, _, _)| *gitlab == name) {
+                return format!("${}", github);
+            }
+            if name.starts_with("CI_") {
+                let todo = format!("`${}` has no GitHub equivalent", name);
+                if !todos.contains(&todo) {
+                    todos.push(todo);
+                }
+            }
+            captures[0].to_string()
+        })
+        .into_owned()
+}
+
+/// Translate variables in a value GitHub reads outside of a shell into expressions
+fn gitlab_value(text: &str, todos: &mut Vec<String>) -> String {
+    SHELL_VARIABLE
+        .replace_all(text, |captures: &regex::Captures| {
+            let name = captures
+                .get(1)
+                .or_else(|| captures.get(2))
+                .map(|name| name.as_str())
+                .unwrap_or_default();
+            match PREDEFINED.iter().find(|(gitlab, _, _)| *gitlab == name) {
+                Some((_, _, context)) => format!("${{{{ {} }}}}", context),
+                None if name.starts_with("CI_") => {
+                    todos.push(format!("`${}` has no GitHub equivalent", name));
+                    captures[0].to_string()
+                }
+                None => format!("${{{{ env.{} }}}}", name),
+            }
+        })
+        .into_owned()
+}
+
+/// Minutes in a GitLab duration such as `1h 30m` or `45 minutes`
+fn duration_minutes(duration: &str) -> Option<u64> {
+    let mut seconds = 0;
+    let mut matched = false;
+    for captures in DURATION_PART.captures_iter(duration) {
+        matched = true;
+        let amount: u64 = captures[1].parse().ok()?;
+        seconds += match &captures[2][..1] {
+            "h" => amount * 3600,
+            "m" => amount * 60,
+            _ => amount,
+        };
+    }
+    matched.then(|| seconds.div_ceil(60).max(1))
+}
+
+/// Days in a GitLab `expire_in` such as `1 week` or `30 days`
+fn expiry_days(expire_in: &str) -> Option<u64> {
+    let mut days = 0;
+    let mut matched = false;
+    for captures in EXPIRY_PART.captures_iter(expire_in) {
+        matched = true;
+        let amount: u64 = captures[1].parse().ok()?;
+        days += match &captures[2] {
+            unit if unit.starts_with("mo") => amount * 30,
+            unit if unit.starts_with('w') => amount * 7,
+            _ => amount,
+        };
+    }
+    matched.then_some(days)
+}
+
+/// Job ids in GitHub may only hold letters, digits, `-` and `_`
+fn github_job_id(name: &str) -> String {
+    let id: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    if id.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        id
+    } else {
+        format!("job-{}", id)
+    }
+}
+
+/// Values of a GitLab matrix entry, a single value meaning a list of one
+fn matrix_values(value: &Value) -> Vec<String> {
+    match value {
+        Value::Sequence(values) => values.iter().filter_map(scalar).collect(),
+        other => scalar(other).into_iter().collect(),
+    }
+}
+
+/// A job with the jobs it `extends` merged in, its own keys taking precedence and
+/// `variables` merged key by key
+fn resolve_extends(
+    name: &str,
+    pipeline: &Pipeline,
+    seen: &mut Vec<String>,
+    todos: &mut Vec<String>,
+) -> Option<GitlabJob> {
+    let job = pipeline.jobs.get(name)?;
+    if seen.iter().any(|seen| seen == name) {
+        todos.push(format!("`extends` of `{}` is circular", name));
+        return Some(job.clone());
+    }
+    seen.push(name.to_string());
+    let mut merged = Mapping::new();
+    for base in job.extends.iter().flatten() {
+        match resolve_extends(base, pipeline, seen, todos) {
+            Some(base) => {
+                if let Ok(Value::Mapping(base)) = serde_yaml::to_value(base) {
+                    merge(&mut merged, base);
+                }
+            }
+            None => todos.push(format!(
+                "extends `{}`, which isn't in this file; merge it in by hand",
+                base
+            )),
+        }
+    }
+    seen.pop();
+    if let Ok(Value::Mapping(own)) = serde_yaml::to_value(job) {
+        merge(&mut merged, own);
+    }
+    merged.remove("extends");
+    serde_yaml::from_value(Value::Mapping(merged)).ok()
+}
+
+fn merge(into: &mut Mapping, from: Mapping) {
+    for (key, value) in from {
+        match (into.get_mut(&key), value) {
+            (Some(Value::Mapping(existing)), Value::Mapping(value)) if key == "variables" => {
+                merge(existing, value)
+            }
+            (_, value) => {
+                into.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Convert a GitLab CI/CD pipeline into a GitHub Actions workflow
+pub fn gitlab_to_github(path: &Path) -> Result<Conversion, String> {
+    let pipeline = gitlab::parse_pipeline(path).map_err(|e| e.to_string())?;
+    let raw = read_raw(path)?;
+    let mut out = Output::default();
+    out.comment(&format!(
+        "Converted from the GitLab CI/CD pipeline {} by `wrkflw convert`",
+        path.display()
+    ));
+    out.yaml.push('\n');
+
+    out.entry(0, "name", Value::String("CI".to_string()), Vec::new());
+    let mut todos = Vec::new();
+    if raw.get("workflow").is_some() {
+        todos.push("`workflow: rules` aren't translated; narrow down `on` instead".to_string());
+    }
+    out.entry(0, "on", strings(["push", "workflow_dispatch"]), todos);
+
+    let mut todos: Vec<String> =
+        untranslated_keys(Some(&raw), &["stages", "variables", "workflow"])
+            .into_iter()
+            .filter(|key| gitlab::RESERVED_KEYS.contains(&key.as_str()) && key != "default")
+            .filter(|key| !matches!(key.as_str(), "image" | "before_script" | "after_script"))
+            .map(|key| match key.as_str() {
+                "include" => {
+                    "`include` isn't translated; convert the included files and merge them in"
+                        .to_string()
+                }
+                key => format!("top-level `{}` isn't translated", key),
+            })
+            .collect();
+    let mut env: Vec<(String, String)> = pipeline
+        .variables
+        .iter()
+        .flatten()
+        .map(|(key, value)| (key.clone(), gitlab_value(value, &mut todos)))
+        .collect();
+    env.sort();
+    if !env.is_empty() {
+        out.entry(
+            0,
+            "env",
+            mapping(
+                env.into_iter()
+                    .map(|(key, value)| (key, Value::String(value))),
+            ),
+            std::mem::take(&mut todos),
+        );
+    }
+
+    // Jobs in file order, with what they extend merged in
+    let mut order: Vec<String> = keys(Some(&raw))
+        .into_iter()
+        .filter(|name| !gitlab::RESERVED_KEYS.contains(&name.as_str()) && !name.starts_with('.'))
+        .filter(|name| {
+            pipeline
+                .jobs
+                .get(name)
+                .is_some_and(|job| job.template != Some(true))
+        })
+        .collect();
+    let mut jobs: HashMap<String, GitlabJob> = HashMap::new();
+    for name in &order {
+        if let Some(job) = resolve_extends(name, &pipeline, &mut Vec::new(), &mut todos) {
+            jobs.insert(name.clone(), job);
+        }
+    }
+    order.retain(|name| jobs.contains_key(name));
+    let defaults = pipeline.jobs.get("default");
+
+    // Stages, in order
+    let mut stages = vec![".pre".to_string()];
+    stages.extend(pipeline.stages.clone().unwrap_or_else(|| {
+        ["build", "test", "deploy"]
+            .iter()
+            .map(|stage| stage.to_string())
+            .collect()
+    }));
+    stages.push(".post".to_string());
+    let stage_of = |job: &GitlabJob| {
+        let stage = job.stage.as_deref().unwrap_or("test");
+        stages.iter().position(|s| s == stage).unwrap_or(0)
+    };
+
+    let needs_of = |name: &str| -> Vec<String> {
+        let job = &jobs[name];
+        if let Some(needs) = &job.needs {
+            return needs.iter().map(|need| need.job().to_string()).collect();
+        }
+        // Without `needs`, a job waits for the latest earlier stage that has jobs
+        let stage = stage_of(job);
+        let earlier = order
+            .iter()
+            .map(|other| stage_of(&jobs[other]))
+            .filter(|other| *other < stage)
+            .max();
+        order
+            .iter()
+            .filter(|other| Some(stage_of(&jobs[*other])) == earlier)
+            .cloned()
+            .collect()
+    };
+    // Jobs whose artifacts a job gets: its `dependencies`, else the jobs it needs,
+    // else all jobs of earlier stages
+    let artifacts_from = |name: &str| -> Vec<String> {
+        let job = &jobs[name];
+        let sources: Vec<String> = match (&job.dependencies, &job.needs) {
+            (Some(dependencies), _) => dependencies.clone(),
+            (None, Some(needs)) => needs
+                .iter()
+                .filter(|need| {
+                    !matches!(
+                        need,
+                        wrkflw_models::gitlab::Need::Detailed {
+                            artifacts: Some(false),
+                            ..
+                        }
+                    )
+                })
+                .map(|need| need.job().to_string())
+                .collect(),
+            (None, None) => order
+                .iter()
+                .filter(|other| stage_of(&jobs[*other]) < stage_of(job))
+                .cloned()
+                .collect(),
+        };
+        sources
+            .into_iter()
+            .filter(|source| {
+                jobs.get(source).is_some_and(|source| {
+                    source
+                        .artifacts
+                        .as_ref()
+                        .is_some_and(|artifacts| artifacts.paths.is_some())
+                })
+            })
+            .collect()
+    };
+
+    out.yaml.push('\n');
+    out.yaml.push_str("jobs:\n");
+    let ids: HashMap<&str, String> = order
+        .iter()
+        .map(|name| (name.as_str(), github_job_id(name)))
+        .collect();
+    for (index, name) in order.iter().enumerate() {
+        let job = &jobs[name];
+        let raw_job = raw.get(name.as_str());
+        let mut todos = std::mem::take(&mut todos);
+        let needs: Vec<String> = needs_of(name)
+            .iter()
+            .map(|need| {
+                ids.get(need.as_str())
+                    .cloned()
+                    .unwrap_or_else(|| need.clone())
+            })
+            .collect();
+        let context = GitlabContext {
+            pipeline: &pipeline,
+            defaults,
+            needs,
+            artifacts_from: artifacts_from(name)
+                .iter()
+                .map(|source| github_job_id(source))
+                .collect(),
+        };
+        let value = gitlab_job(name, job, raw_job, &context, &mut todos);
+        if index > 0 {
+            out.yaml.push('\n');
+        }
+        out.entry(2, &ids[name.as_str()], value, todos);
+    }
+
+    Ok(out.finish())
+}
+
+/// What converting a GitLab job needs from the rest of the pipeline
+struct GitlabContext<'a> {
+    pipeline: &'a Pipeline,
+    defaults: Option<&'a GitlabJob>,
+    /// Ids of the jobs the job waits for
+    needs: Vec<String>,
+    /// Ids of the jobs whose artifacts the job downloads
+    artifacts_from: Vec<String>,
+}
+
+fn gitlab_job(
+    name: &str,
+    job: &GitlabJob,
+    raw: Option<&Value>,
+    context: &GitlabContext,
+    todos: &mut Vec<String>,
+) -> Value {
+    let mut entries: Vec<(&str, Value)> = Vec::new();
+    if github_job_id(name) != name {
+        entries.push(("name", Value::String(name.to_string())));
+    }
+    entries.push(("runs-on", Value::String("ubuntu-latest".to_string())));
+
+    for key in untranslated_keys(
+        raw,
+        &[
+            "stage",
+            "image",
+            "script",
+            "before_script",
+            "after_script",
+            "when",
+            "allow_failure",
+            "services",
+            "variables",
+            "dependencies",
+            "needs",
+            "artifacts",
+            "cache",
+            "timeout",
+            "parallel",
+            "extends",
+            "environment",
+            "interruptible",
+        ],
+    ) {
+        todos.push(match key.as_str() {
+            "rules" | "only" | "except" => format!(
+                "`{}` isn't translated; express it with `if:` or the workflow's `on`",
+                key
+            ),
+            "tags" => "runner `tags` aren't translated; use self-hosted runner labels in `runs-on`"
+                .to_string(),
+            "retry" => "`retry` isn't translated; GitHub doesn't retry jobs".to_string(),
+            key => format!("`{}` isn't translated", key),
+        });
+    }
+
+    match job.when.as_deref() {
+        Some("on_failure") => entries.push(("if", Value::String("failure()".to_string()))),
+        Some("always") => entries.push(("if", Value::String("always()".to_string()))),
+        Some(when @ ("manual" | "delayed" | "never")) => todos.push(format!(
+            "`when: {}` isn't translated; consider an environment with required reviewers",
+            when
+        )),
+        _ => {}
+    }
+    if !context.needs.is_empty() {
+        entries.push(("needs", strings(context.needs.clone())));
+    }
+    if job.allow_failure == Some(true) {
+        entries.push(("continue-on-error", Value::Bool(true)));
+    }
+    if let Some(timeout) = &job.timeout {
+        match duration_minutes(timeout) {
+            Some(minutes) => entries.push(("timeout-minutes", Value::Number(minutes.into()))),
+            None => todos.push(format!("`timeout: {}` isn't translated", timeout)),
+        }
+    }
+
+    // Container, from the job, the defaults or the pipeline
+    let image = job
+        .image
+        .as_ref()
+        .or(context
+            .defaults
+            .and_then(|defaults| defaults.image.as_ref()))
+        .or(context.pipeline.image.as_ref());
+    match image {
+        Some(Image::Simple(image)) => entries.push(("container", Value::String(image.clone()))),
+        Some(Image::Detailed { name, entrypoint }) => {
+            entries.push(("container", Value::String(name.clone())));
+            if entrypoint.is_some() {
+                todos.push("the image's `entrypoint` isn't translated".to_string());
+            }
+        }
+        None => {}
+    }
+
+    let services = job.services.as_ref().or(context
+        .defaults
+        .and_then(|defaults| defaults.services.as_ref()));
+    if let Some(services) = services {
+        let mut converted = Vec::new();
+        for service in services {
+            let (image, extra) = match service {
+                Service::Simple(image) => (image, false),
+                Service::Detailed {
+                    name,
+                    command,
+                    entrypoint,
+                } => (name, command.is_some() || entrypoint.is_some()),
+            };
+            // postgres:16 is reachable as `postgres`, as in GitLab
+            let id = image
+                .rsplit('/')
+                .next()
+                .unwrap_or(image)
+                .split(':')
+                .next()
+                .unwrap_or(image)
+                .to_string();
+            if extra {
+                todos.push(format!(
+                    "`command` and `entrypoint` of service `{}` aren't translated",
+                    image
+                ));
+            }
+            converted.push((id, mapping([("image", Value::String(image.clone()))])));
+        }
+        entries.push(("services", mapping(converted)));
+    }
+
+    if let Some(environment) = raw.and_then(|raw| raw.get("environment")) {
+        let environment = match environment {
+            Value::Mapping(_) => {
+                let mut entries = Vec::new();
+                for key in ["name", "url"] {
+                    if let Some(value) = environment.get(key).and_then(scalar) {
+                        entries.push((key, Value::String(gitlab_value(&value, todos))));
+                    }
+                }
+                mapping(entries)
+            }
+            other => Value::String(gitlab_value(&scalar(other).unwrap_or_default(), todos)),
+        };
+        entries.push(("environment", environment));
+    }
+
+    // Matrix
+    let mut env: Vec<(String, String)> = job
+        .variables
+        .iter()
+        .flatten()
+        .map(|(key, value)| (key.clone(), gitlab_value(value, todos)))
+        .collect();
+    env.sort();
+    match &job.parallel {
+        Some(Parallel::Count(count)) => {
+            entries.push((
+                "strategy",
+                mapping([(
+                    "matrix",
+                    mapping([(
+                        "ci_node_index",
+                        Value::Sequence(
+                            (1..=*count as u64)
+                                .map(|i| Value::Number(i.into()))
+                                .collect(),
+                        ),
+                    )]),
+                )]),
+            ));
+            env.push((
+                "CI_NODE_INDEX".to_string(),
+                "${{ matrix.ci_node_index }}".to_string(),
+            ));
+            env.push(("CI_NODE_TOTAL".to_string(), count.to_string()));
+        }
+        Some(Parallel::Matrix { matrix }) => {
+            let mut variables: Vec<&String> =
+                matrix.iter().flat_map(|entry| entry.keys()).collect();
+            variables.sort();
+            variables.dedup();
+            let matrix = if let [entry] = matrix.as_slice() {
+                mapping(
+                    entry
+                        .iter()
+                        .map(|(key, value)| (key.clone(), strings(matrix_values(value)))),
+                )
+            } else {
+                // Each entry is a product of its own; list all combinations
+                let mut include = Vec::new();
+                for entry in matrix {
+                    let mut combinations: Vec<Vec<(String, String)>> = vec![Vec::new()];
+                    for (key, value) in entry {
+                        combinations = combinations
+                            .into_iter()
+                            .flat_map(|combination| {
+                                matrix_values(value).into_iter().map(move |value| {
+                                    let mut combination = combination.clone();
+                                    combination.push((key.clone(), value));
+                                    combination
+                                })
+                            })
+                            .collect();
+                    }
+                    include.extend(combinations.into_iter().map(|combination| {
+                        mapping(
+                            combination
+                                .into_iter()
+                                .map(|(key, value)| (key, Value::String(value))),
+                        )
+                    }));
+                }
+                mapping([("include", Value::Sequence(include))])
+            };
+            entries.push(("strategy", mapping([("matrix", matrix)])));
+            for variable in variables {
+                env.push((variable.clone(), format!("${{{{ matrix.{} }}}}", variable)));
+            }
+        }
+        None => {}
+    }
+    if !env.is_empty() {
+        entries.push((
+            "env",
+            mapping(
+                env.into_iter()
+                    .map(|(key, value)| (key, Value::String(value))),
+            ),
+        ));
+    }
+
+    // Steps
+    let mut steps = vec![mapping([(
+        "uses",
+        Value::String("actions/checkout@v4".to_string()),
+    )])];
+    for source in &context.artifacts_from {
+        steps.push(mapping([
+            (
+                "uses",
+                Value::String("actions/download-artifact@v4".to_string()),
+            ),
+            ("with", mapping([("name", Value::String(source.clone()))])),
+        ]));
+    }
+
+    let cache = job.cache.as_ref().or(context
+        .defaults
+        .and_then(|defaults| defaults.cache.as_ref()));
+    let mut save_cache = None;
+    if let Some(cache) = cache {
+        let key = match &cache.key {
+            Some(CacheKey::Key(key)) => gitlab_value(key, todos),
+            Some(CacheKey::Files { files, prefix }) => {
+                let files: Vec<String> = files.iter().map(|file| format!("'{}'", file)).collect();
+                let prefix = prefix
+                    .as_deref()
+                    .map(|prefix| format!("{}-", gitlab_value(prefix, todos)))
+                    .unwrap_or_default();
+                format!("{}${{{{ hashFiles({}) }}}}", prefix, files.join(", "))
+            }
+            None => "${{ github.job }}".to_string(),
+        };
+        let with = mapping([
+            (
+                "path",
+                Value::String(cache.paths.clone().unwrap_or_default().join("\n")),
+            ),
+            ("key", Value::String(key)),
+        ]);
+        match cache.policy.as_deref() {
+            Some("pull") => steps.push(mapping([
+                (
+                    "uses",
+                    Value::String("actions/cache/restore@v4".to_string()),
+                ),
+                ("with", with),
+            ])),
+            Some("push") => {
+                save_cache = Some(mapping([
+                    ("uses", Value::String("actions/cache/save@v4".to_string())),
+                    ("with", with),
+                ]))
+            }
+            _ => steps.push(mapping([
+                ("uses", Value::String("actions/cache@v4".to_string())),
+                ("with", with),
+            ])),
+        }
+        if cache.when.is_some() {
+            todos.push("`cache: when` isn't translated".to_string());
+        }
+    }
+
+    let script_of = |own: &Option<Vec<String>>, default: Option<&Vec<String>>| {
+        own.as_ref()
+            .or(default)
+            .map(|lines| lines.join("\n"))
+            .filter(|script| !script.is_empty())
+    };
+    let before = script_of(
+        &job.before_script,
+        context
+            .defaults
+            .and_then(|defaults| defaults.before_script.as_ref())
+            .or(context.pipeline.before_script.as_ref()),
+    );
+    let script = script_of(&job.script, None);
+    let run = [before, script]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join("\n");
+    if run.is_empty() {
+        todos.push("the job has no `script`".to_string());
+    } else {
+        steps.push(mapping([
+            ("name", Value::String("Script".to_string())),
+            ("run", Value::String(gitlab_script(&run, todos))),
+        ]));
+    }
+    if let Some(after) = script_of(
+        &job.after_script,
+        context
+            .defaults
+            .and_then(|defaults| defaults.after_script.as_ref())
+            .or(context.pipeline.after_script.as_ref()),
+    ) {
+        steps.push(mapping([
+            ("name", Value::String("After script".to_string())),
+            ("if", Value::String("always()".to_string())),
+            ("run", Value::String(gitlab_script(&after, todos))),
+        ]));
+    }
+    steps.extend(save_cache);
+
+    if let Some(artifacts) = &job.artifacts {
+        for key in untranslated_keys(
+            raw.and_then(|raw| raw.get("artifacts")),
+            &["paths", "expire_in", "when"],
+        ) {
+            todos.push(format!("`artifacts: {}` isn't translated", key));
+        }
+        if let Some(paths) = &artifacts.paths {
+            let mut with = vec![
+                ("name", Value::String(github_job_id(name))),
+                ("path", Value::String(paths.join("\n"))),
+            ];
+            if let Some(expire_in) = &artifacts.expire_in {
+                match expiry_days(expire_in) {
+                    Some(days) => with.push(("retention-days", Value::Number(days.into()))),
+                    None => todos.push(format!(
+                        "`artifacts: expire_in: {}` isn't translated",
+                        expire_in
+                    )),
+                }
+            }
+            let mut step = vec![(
+                "uses",
+                Value::String("actions/upload-artifact@v4".to_string()),
+            )];
+            match artifacts.when.as_deref() {
+                Some("always") => step.push(("if", Value::String("always()".to_string()))),
+                Some("on_failure") => step.push(("if", Value::String("failure()".to_string()))),
+                _ => {}
+            }
+            step.push(("with", mapping(with)));
+            steps.push(mapping(step));
+        }
+    }
+    entries.push(("steps", Value::Sequence(steps)));
+
+    mapping(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, name: &str, content: &str) -> std::path::PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_github_to_gitlab() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(
+            dir.path(),
+            "ci.yml",
+            r#"
+name: CI
+on:
+  push:
+    branches: [main]
+  pull_request:
+env:
+  CARGO_TERM_COLOR: always
+jobs:
+  build:
+    runs-on: ubuntu-22.04
+    strategy:
+      matrix:
+        rust: [stable, beta]
+    steps:
+      - uses: actions/checkout@v4
+        with:
+          fetch-depth: 0
+      - uses: actions/cache@v4
+        with:
+          path: |
+            ~/.cargo/registry
+            target
+          key: cargo-${{ github.sha }}
+      - uses: actions/setup-node@v4
+      - run: cargo +${{ matrix.rust }} build --release
+        working-directory: app
+      - uses: actions/upload-artifact@v4
+        if: always()
+        with:
+          name: binary
+          path: app/target/release/app
+  test:
+    needs: build
+    runs-on: ubuntu-latest
+    if: github.ref == 'refs/heads/main'
+    services:
+      db:
+        image: postgres:16
+        env:
+          POSTGRES_PASSWORD: secret
+    steps:
+      - uses: actions/download-artifact@v4
+      - run: ./app/target/release/app --token ${{ secrets.API_TOKEN }} --sha $GITHUB_SHA
+"#,
+        );
+        let conversion = github_to_gitlab(&path).unwrap();
+        let converted = write(dir.path(), ".gitlab-ci.yml", &conversion.yaml);
+        let pipeline = gitlab::parse_pipeline(&converted).unwrap();
+
+        assert_eq!(
+            pipeline.stages,
+            Some(vec!["stage-1".to_string(), "stage-2".to_string()])
+        );
+        let build = &pipeline.jobs["build"];
+        assert_eq!(build.stage.as_deref(), Some("stage-1"));
+        assert_eq!(
+            build.script.as_deref(),
+            Some(
+                &[
+                    "cd \"$CI_PROJECT_DIR/app\"".to_string(),
+                    "cargo +$rust build --release".to_string()
+                ][..]
+            )
+        );
+        assert_eq!(build.variables.as_ref().unwrap()["GIT_DEPTH"], "0");
+        assert_eq!(
+            build.cache.as_ref().unwrap().key,
+            Some(CacheKey::Key("cargo-$CI_COMMIT_SHA".to_string()))
+        );
+        assert_eq!(
+            build.artifacts.as_ref().unwrap().when.as_deref(),
+            Some("always")
+        );
+        assert!(matches!(build.parallel, Some(Parallel::Matrix { .. })));
+
+        let test = &pipeline.jobs["test"];
+        assert_eq!(test.needs.as_ref().unwrap()[0].job(), "build");
+        assert_eq!(
+            test.script.as_ref().unwrap()[0],
+            "./app/target/release/app --token $API_TOKEN --sha $CI_COMMIT_SHA"
+        );
+        assert_eq!(
+            test.variables.as_ref().unwrap()["POSTGRES_PASSWORD"],
+            "secret"
+        );
+
+        assert!(conversion
+            .yaml
+            .contains("$CI_PIPELINE_SOURCE == \"push\" && ($CI_COMMIT_BRANCH == \"main\")"));
+        assert_eq!(
+            conversion.todos,
+            vec![
+                "build: step `3` uses `actions/setup-node@v4`; use an image that provides the tool instead",
+                "test: runs only `if: github.ref == 'refs/heads/main'`; translate it into `rules`",
+            ]
+        );
+        assert!(conversion.yaml.contains(
+            "# TODO: runs only `if: github.ref == 'refs/heads/main'`; translate it into `rules`\ntest:"
+        ));
+    }
+
+    #[test]
+    fn test_gitlab_to_github() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(
+            dir.path(),
+            ".gitlab-ci.yml",
+            r#"
+stages: [build, test, deploy]
+variables:
+  RUST_LOG: info
+default:
+  image: rust:1.80
+  before_script:
+    - rustc --version
+.cached:
+  cache:
+    key: $CI_COMMIT_REF_SLUG
+    paths: [target/]
+build:
+  stage: build
+  extends: [.cached]
+  script:
+    - cargo build --release
+  artifacts:
+    paths: [target/release/app]
+    expire_in: 1 week
+unit tests:
+  stage: test
+  parallel:
+    matrix:
+      - SUITE: [unit, doc]
+  script:
+    - cargo test --$SUITE
+lint:
+  stage: test
+  needs: []
+  allow_failure: true
+  timeout: 1h 30m
+  script:
+    - cargo clippy
+deploy:
+  stage: deploy
+  image: alpine
+  services: [postgres:16]
+  rules:
+    - if: $CI_COMMIT_BRANCH == "main"
+  environment:
+    name: production
+  script:
+    - ./deploy.sh $CI_COMMIT_SHA
+"#,
+        );
+        let conversion = gitlab_to_github(&path).unwrap();
+        let converted = write(dir.path(), "ci.yml", &conversion.yaml);
+        let workflow = workflow::parse_workflow(&converted).unwrap();
+
+        assert_eq!(workflow.on, vec!["push", "workflow_dispatch"]);
+        assert_eq!(workflow.jobs["build"].needs, None);
+        assert_eq!(
+            workflow.jobs["unit-tests"].needs,
+            Some(vec!["build".to_string()])
+        );
+        assert_eq!(workflow.jobs["lint"].needs, None);
+        assert_eq!(
+            workflow.jobs["deploy"].needs,
+            Some(vec!["unit-tests".to_string(), "lint".to_string()])
+        );
+
+        let build = &workflow.jobs["build"];
+        let uses: Vec<_> = build.steps.iter().map(|step| step.uses.clone()).collect();
+        assert_eq!(
+            uses,
+            vec![
+                Some("actions/checkout@v4".to_string()),
+                Some("actions/cache@v4".to_string()),
+                None,
+                Some("actions/upload-artifact@v4".to_string()),
+            ]
+        );
+        assert_eq!(
+            build.steps[1].with.as_ref().unwrap()["key"],
+            "${{ github.ref_name }}"
+        );
+        assert_eq!(
+            build.steps[2].run.as_deref(),
+            Some("rustc --version\ncargo build --release")
+        );
+        assert_eq!(build.steps[3].with.as_ref().unwrap()["retention-days"], "7");
+
+        let tests = &workflow.jobs["unit-tests"];
+        assert_eq!(tests.env["SUITE"], "${{ matrix.SUITE }}");
+        assert_eq!(
+            tests.steps[1].uses.as_deref(),
+            Some("actions/download-artifact@v4")
+        );
+        assert!(workflow.jobs["lint"].steps.len() == 2);
+        assert!(conversion.yaml.contains("continue-on-error: true"));
+        assert!(conversion.yaml.contains("timeout-minutes: 90"));
+        assert!(conversion.yaml.contains("./deploy.sh $GITHUB_SHA"));
+        assert!(conversion.yaml.contains("container: alpine"));
+        assert_eq!(
+            conversion.todos,
+            vec!["deploy: `rules` isn't translated; express it with `if:` or the workflow's `on`"]
+        );
+    }
+
+    #[test]
+    fn test_durations() {
+        assert_eq!(duration_minutes("1h 30m"), Some(90));
+        assert_eq!(duration_minutes("45 minutes"), Some(45));
+        assert_eq!(duration_minutes("90s"), Some(2));
+        assert_eq!(duration_minutes("soon"), None);
+        assert_eq!(expiry_days("1 week"), Some(7));
+        assert_eq!(expiry_days("30 days"), Some(30));
+        assert_eq!(expiry_days("never"), None);
+    }
+}
@@ -0,0 +1,447 @@
+//! Cron expressions of `on.schedule`, in the five-field syntax GitHub accepts:
+//!
+//! ```text
+//! ┌───────────── minute (0 - 59)
+//! │ ┌─────────── hour (0 - 23)
+//! │ │ ┌───────── day of the month (1 - 31)
+//! │ │ │ ┌─────── month (1 - 12 or JAN - DEC)
+//! │ │ │ │ ┌───── day of the week (0 - 6 or SUN - SAT)
+//! * * * * *
+//! ```
+//!
+//! Fields take `*`, values, ranges (`1-5`), lists (`1,15`) and steps (`*/15`,
+//! `0-30/10`, `5/15`). Like in cron, when both day fields are restricted a day
+//! matching either of them runs. Schedules are in UTC.
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
+use std::fmt;
+use std::str::FromStr;
+
+/// GitHub runs scheduled workflows at most this often
+pub const SHORTEST_INTERVAL_MINUTES: u32 = 5;
+
+/// A day of the month and a day of the week line up again within this many years
+const SEARCH_YEARS: i64 = 28;
+
+struct Field {
+    name: &'static str,
+    min: u32,
+    max: u32,
+    /// Names of the values from `min` on
+    names: &'static [&'static str],
+}
+
+const MINUTE: usize = 0;
+const HOUR: usize = 1;
+const DAY_OF_MONTH: usize = 2;
+const MONTH: usize = 3;
+const DAY_OF_WEEK: usize = 4;
+
+const FIELDS: [Field; 5] = [
+    Field {
+        name: "minute",
+        min: 0,
+        max: 59,
+        names: &[],
+    },
+    Field {
+        name: "hour",
+        min: 0,
+        max: 23,
+        names: &[],
+    },
+    Field {
+        name: "day of the month",
+        min: 1,
+        max: 31,
+        names: &[],
+    },
+    Field {
+        name: "month",
+        min: 1,
+        max: 12,
+        names: &[
+            "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+        ],
+    },
+    // 7 is Sunday as well
+    Field {
+        name: "day of the week",
+        min: 0,
+        max: 7,
+        names: &["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"],
+    },
+];
+
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+impl Field {
+    /// Values the field allows, as bits
+    fn parse(&self, text: &str) -> Result<u64, String> {
+        let mut bits = 0;
+        for item in text.split(',') {
+            if item.is_empty() {
+                return Err(format!("{} '{}' has an empty list item", self.name, text));
+            }
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => (range, Some(self.step(step)?)),
+                None => (item, None),
+            };
+            let (start, end) = if range == "*" {
+                (self.min, self.max)
+            } else if let Some((start, end)) = range.split_once('-') {
+                let (start, end) = (self.value(start)?, self.value(end)?);
+                if start > end {
+                    return Err(format!("{} range '{}' goes backwards", self.name, range));
+                }
+                (start, end)
+            } else {
+                // `5/15` is `5-59/15`
+                let value = self.value(range)?;
+                (value, if step.is_some() { self.max } else { value })
+            };
+            for value in (start..=end).step_by(step.unwrap_or(1) as usize) {
+                bits |= 1 << value;
+            }
+        }
+        Ok(bits)
+    }
+
+    fn value(&self, text: &str) -> Result<u32, String> {
+        if let Some(idx) = self
+            .names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(text))
+        {
+            return Ok(self.min + idx as u32);
+        }
+        if text.is_empty() {
+            return Err(format!("{} is missing a value", self.name));
+        }
+        let value: u32 = text
+            .parse()
+            .ok()
+            .filter(|_| text.bytes().all(|b| b.is_ascii_digit()))
+            .ok_or_else(|| format!("'{}' is not a valid {}", text, self.name))?;
+        if value < self.min || value > self.max {
+            return Err(format!(
+                "{} {} is out of range {}-{}",
+                self.name, value, self.min, self.max
+            ));
+        }
+        Ok(value)
+    }
+
+    fn step(&self, text: &str) -> Result<u32, String> {
+        match text.parse::<u32>() {
+            Ok(0) => Err(format!("{} step can't be 0", self.name)),
+            Ok(step) if step <= self.max => Ok(step),
+            Ok(step) => Err(format!(
+                "{} step {} is larger than {}",
+                self.name, step, self.max
+            )),
+            Err(_) => Err(format!("'{}' is not a valid {} step", text, self.name)),
+        }
+    }
+}
+
+/// A parsed cron expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    expression: String,
+    /// Values each field allows, as bits
+    fields: [u64; 5],
+    /// Whether the day fields start with `*`; unless one of them does, a day
+    /// matching either field runs
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+impl CronSchedule {
+    /// Parse a five-field cron expression, with errors naming the field at fault
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = expression.split_whitespace().collect();
+        if let Some(shortcut) = parts.first().filter(|part| part.starts_with('@')) {
+            return Err(format!(
+                "'{}' is not supported; use five fields (minute hour day-of-month month day-of-week)",
+                shortcut
+            ));
+        }
+        if parts.len() != 5 {
+            return Err(format!(
+                "expected 5 fields (minute hour day-of-month month day-of-week), found {}",
+                parts.len()
+            ));
+        }
+
+        let mut fields = [0; 5];
+        for (bits, (part, field)) in fields.iter_mut().zip(parts.iter().zip(&FIELDS)) {
+            *bits = field.parse(part)?;
+        }
+        if fields[DAY_OF_WEEK] & 1 << 7 != 0 {
+            fields[DAY_OF_WEEK] = (fields[DAY_OF_WEEK] | 1) & !(1 << 7);
+        }
+
+        let schedule = CronSchedule {
+            expression: parts.join(" "),
+            fields,
+            any_day_of_month: parts[DAY_OF_MONTH].starts_with('*'),
+            any_day_of_week: parts[DAY_OF_WEEK].starts_with('*'),
+        };
+        schedule.check_reachable()?;
+        Ok(schedule)
+    }
+
+    /// Fail for days of the month that none of the months has, like `0 0 30 2 *`
+    fn check_reachable(&self) -> Result<(), String> {
+        if !self.any_day_of_week {
+            return Ok(());
+        }
+        let months: Vec<u32> = (1..=12).filter(|m| self.has(MONTH, *m)).collect();
+        let first_day = (1..=31).find(|d| self.has(DAY_OF_MONTH, *d)).unwrap_or(1);
+        if months.iter().any(|m| first_day <= days_in_month(*m)) {
+            return Ok(());
+        }
+        Err(match months.as_slice() {
+            [month] => format!(
+                "never runs: {} has no day {}",
+                MONTH_NAMES[*month as usize - 1],
+                first_day
+            ),
+            _ => format!("never runs: none of the months has day {}", first_day),
+        })
+    }
+
+    fn has(&self, field: usize, value: u32) -> bool {
+        self.fields[field] & 1 << value != 0
+    }
+
+    fn matches_day(&self, time: &DateTime<Utc>) -> bool {
+        let day_of_month = self.has(DAY_OF_MONTH, time.day());
+        let day_of_week = self.has(DAY_OF_WEEK, time.weekday().num_days_from_sunday());
+        if self.any_day_of_month || self.any_day_of_week {
+            day_of_month && day_of_week
+        } else {
+            day_of_month || day_of_week
+        }
+    }
+
+    /// Whether the schedule triggers in the minute of `time`
+    pub fn matches(&self, time: &DateTime<Utc>) -> bool {
+        self.has(MINUTE, time.minute())
+            && self.has(HOUR, time.hour())
+            && self.has(MONTH, time.month())
+            && self.matches_day(time)
+    }
+
+    /// The first time after `time` the schedule triggers
+    pub fn next_after(&self, time: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut next = time.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = next + Duration::days(366 * SEARCH_YEARS);
+        while next < limit {
+            next = if !self.has(MONTH, next.month()) {
+                let (year, month) = match next.month() {
+                    12 => (next.year() + 1, 1),
+                    month => (next.year(), month + 1),
+                };
+                Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?
+            } else if !self.matches_day(&next) {
+                (next.date_naive() + Duration::days(1))
+                    .and_hms_opt(0, 0, 0)?
+                    .and_utc()
+            } else if !self.has(HOUR, next.hour()) {
+                next.with_minute(0)? + Duration::hours(1)
+            } else if !self.has(MINUTE, next.minute()) {
+                next + Duration::minutes(1)
+            } else {
+                return Some(next);
+            };
+        }
+        None
+    }
+
+    /// The next `count` times after `time` the schedule triggers
+    pub fn upcoming(&self, time: &DateTime<Utc>, count: usize) -> Vec<DateTime<Utc>> {
+        std::iter::successors(self.next_after(time), |last| self.next_after(last))
+            .take(count)
+            .collect()
+    }
+
+    /// Whether, within an hour, the schedule triggers more often than GitHub runs
+    /// scheduled workflows
+    pub fn exceeds_shortest_interval(&self) -> bool {
+        let minutes: Vec<u32> = (0..60).filter(|m| self.has(MINUTE, *m)).collect();
+        minutes
+            .windows(2)
+            .any(|pair| pair[1] - pair[0] < SHORTEST_INTERVAL_MINUTES)
+    }
+}
+
+fn days_in_month(month: u32) -> u32 {
+    match month {
+        2 => 29,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn upcoming(expression: &str, after: &str, count: usize) -> Vec<String> {
+        CronSchedule::parse(expression)
+            .unwrap()
+            .upcoming(&utc(after), count)
+            .iter()
+            .map(|time| time.format("%Y-%m-%d %H:%M %a").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |expression| CronSchedule::parse(expression).unwrap_err();
+        assert_eq!(
+            error("0 0 * *"),
+            "expected 5 fields (minute hour day-of-month month day-of-week), found 4"
+        );
+        assert!(error("@daily").starts_with("'@daily' is not supported"));
+        assert_eq!(error("60 * * * *"), "minute 60 is out of range 0-59");
+        assert_eq!(error("0 24 * * *"), "hour 24 is out of range 0-23");
+        assert_eq!(
+            error("0 0 0 * *"),
+            "day of the month 0 is out of range 1-31"
+        );
+        assert_eq!(error("0 0 * FOO *"), "'FOO' is not a valid month");
+        assert_eq!(error("0 0 * * MON-"), "day of the week is missing a value");
+        assert_eq!(
+            error("0 0 * * 5-1"),
+            "day of the week range '5-1' goes backwards"
+        );
+        assert_eq!(error("*/0 * * * *"), "minute step can't be 0");
+        assert_eq!(error("*/x * * * *"), "'x' is not a valid minute step");
+        assert_eq!(
+            error("1,,2 * * * *"),
+            "minute '1,,2' has an empty list item"
+        );
+        assert_eq!(error("0 0 ? * *"), "'?' is not a valid day of the month");
+        assert_eq!(error("0 0 L * *"), "'L' is not a valid day of the month");
+        assert_eq!(error("0 0 30 2 *"), "never runs: February has no day 30");
+        assert_eq!(
+            error("0 0 31 4,6 *"),
+            "never runs: none of the months has day 31"
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        for expression in [
+            "* * * * *",
+            "*/15 9-17 * * MON-FRI",
+            "0 0 1,15 jan,jul *",
+            "5/20 0 * * 7",
+            "0 0 29 2 *",
+            "0 0 30 2 1",
+        ] {
+            assert!(CronSchedule::parse(expression).is_ok(), "{}", expression);
+        }
+        assert_eq!(
+            CronSchedule::parse("  0  12 * *   1 ").unwrap().to_string(),
+            "0 12 * * 1"
+        );
+    }
+
+    #[test]
+    fn test_upcoming() {
+        assert_eq!(
+            upcoming("*/15 9-17 * * MON-FRI", "2024-03-15T17:40:00Z", 3),
+            [
+                "2024-03-15 17:45 Fri",
+                "2024-03-18 09:00 Mon",
+                "2024-03-18 09:15 Mon"
+            ]
+        );
+        // On a trigger time, the next one comes after it
+        assert_eq!(
+            upcoming("30 5 * * *", "2024-03-15T05:30:00Z", 1),
+            ["2024-03-16 05:30 Sat"]
+        );
+        assert_eq!(
+            upcoming("0 0 29 2 *", "2024-03-01T00:00:00Z", 1),
+            ["2028-02-29 00:00 Tue"]
+        );
+        // Sunday, as 0 or 7, or the 1st of the month
+        assert_eq!(
+            upcoming("0 12 1 * 7", "2024-03-28T00:00:00Z", 3),
+            [
+                "2024-03-31 12:00 Sun",
+                "2024-04-01 12:00 Mon",
+                "2024-04-07 12:00 Sun"
+            ]
+        );
+        // ...but only Sundays that are odd days with a `*` day field
+        assert_eq!(
+            upcoming("0 12 */2 * SUN", "2024-03-28T00:00:00Z", 2),
+            ["2024-03-31 12:00 Sun", "2024-04-07 12:00 Sun"]
+        );
+        assert_eq!(
+            upcoming("5/20 0 * DEC *", "2024-12-31T23:59:30Z", 2),
+            ["2025-12-01 00:05 Mon", "2025-12-01 00:25 Mon"]
+        );
+    }
+
+    #[test]
+    fn test_matches() {
+        let schedule = CronSchedule::parse("0 6 * * 1-5").unwrap();
+        assert!(schedule.matches(&utc("2024-03-15T06:00:59Z")));
+        assert!(!schedule.matches(&utc("2024-03-16T06:00:00Z")));
+        assert!(!schedule.matches(&utc("2024-03-15T06:01:00Z")));
+    }
+
+    #[test]
+    fn test_exceeds_shortest_interval() {
+        let exceeds = |expression| {
+            CronSchedule::parse(expression)
+                .unwrap()
+                .exceeds_shortest_interval()
+        };
+        assert!(exceeds("* * * * *"));
+        assert!(exceeds("*/2 * * * *"));
+        assert!(exceeds("0,3 * * * *"));
+        assert!(!exceeds("*/5 * * * *"));
+        assert!(!exceeds("0 * * * *"));
+    }
+}
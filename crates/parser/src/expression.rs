@@ -0,0 +1,542 @@
+//! GitHub Actions expressions: what's inside `${{ }}` and in `if:` conditions.
+//!
+//! Expressions are parsed into an `Expr` tree following GitHub's grammar: literals
+//! (`null`, booleans, numbers, `'strings'`), contexts and property accesses
+//! (`github.event.inputs['name']`, `needs.*.result`), function calls, `!`,
+//! comparisons, `&&` and `||`, and parentheses.
+
+use std::fmt;
+
+/// A parsed expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    /// A context, such as `github`
+    Context(String),
+    /// `object.name` or `object['name']`
+    Property(Box<Expr>, String),
+    /// `object[index]` with a computed index
+    Index(Box<Expr>, Box<Expr>),
+    /// `object.*` or `object[*]`
+    Wildcard(Box<Expr>),
+    Call(String, Vec<Expr>),
+    Not(Box<Expr>),
+    Binary(Box<Expr>, BinaryOp, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    And,
+    Or,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl BinaryOp {
+    pub fn is_comparison(self) -> bool {
+        !matches!(self, BinaryOp::And | BinaryOp::Or)
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+            BinaryOp::Equal => "==",
+            BinaryOp::NotEqual => "!=",
+            BinaryOp::Less => "<",
+            BinaryOp::LessOrEqual => "<=",
+            BinaryOp::Greater => ">",
+            BinaryOp::GreaterOrEqual => ">=",
+        }
+    }
+
+    /// Binding strength; higher binds tighter
+    fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Or => 1,
+            BinaryOp::And => 2,
+            BinaryOp::Equal | BinaryOp::NotEqual => 3,
+            _ => 4,
+        }
+    }
+}
+
+impl Expr {
+    /// The path of a property access from a context, such as
+    /// `["needs", "build", "outputs", "version"]`; `*` for wildcards
+    pub fn reference(&self) -> Option<Vec<&str>> {
+        match self {
+            Expr::Context(name) => Some(vec![name.as_str()]),
+            Expr::Property(object, name) => {
+                let mut path = object.reference()?;
+                path.push(name.as_str());
+                Some(path)
+            }
+            Expr::Wildcard(object) => {
+                let mut path = object.reference()?;
+                path.push("*");
+                Some(path)
+            }
+            _ => None,
+        }
+    }
+
+    /// Call `visit` on this expression and everything in it, outermost first
+    pub fn walk<'a>(&'a self, visit: &mut impl FnMut(&'a Expr)) {
+        visit(self);
+        match self {
+            Expr::Property(object, _) | Expr::Wildcard(object) | Expr::Not(object) => {
+                object.walk(visit)
+            }
+            Expr::Index(object, index) => {
+                object.walk(visit);
+                index.walk(visit);
+            }
+            Expr::Call(_, args) => args.iter().for_each(|arg| arg.walk(visit)),
+            Expr::Binary(left, _, right) => {
+                left.walk(visit);
+                right.walk(visit);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Null => write!(f, "null"),
+            Expr::Bool(b) => write!(f, "{}", b),
+            Expr::Number(n) => write!(f, "{}", n),
+            Expr::String(s) => write!(f, "'{}'", s.replace('\'', "''")),
+            Expr::Context(name) => write!(f, "{}", name),
+            Expr::Property(object, name) => write!(f, "{}.{}", object, name),
+            Expr::Index(object, index) => write!(f, "{}[{}]", object, index),
+            Expr::Wildcard(object) => write!(f, "{}.*", object),
+            Expr::Call(name, args) => {
+                write!(f, "{}(", name)?;
+                for (idx, arg) in args.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+            Expr::Not(operand) => write!(f, "!{}", operand),
+            Expr::Binary(left, op, right) => write!(f, "{} {} {}", left, op.as_str(), right),
+        }
+    }
+}
+
+/// The expressions inside `${{ }}` in `text`, in order, or why they can't be found
+pub fn extract(text: &str) -> Result<Vec<&str>, String> {
+    let mut expressions = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${{") {
+        let body = &rest[start + 3..];
+        // `}}` inside a string literal doesn't end the expression
+        let mut in_string = false;
+        let mut end = None;
+        let bytes = body.as_bytes();
+        let mut idx = 0;
+        while idx < bytes.len() {
+            match bytes[idx] {
+                b'\'' => in_string = !in_string,
+                b'}' if !in_string && bytes.get(idx + 1) == Some(&b'}') => {
+                    end = Some(idx);
+                    break;
+                }
+                _ => {}
+            }
+            idx += 1;
+        }
+        let Some(end) = end else {
+            return Err(format!(
+                "Unterminated expression '${{{{{}'",
+                body.lines().next().unwrap_or_default()
+            ));
+        };
+        expressions.push(&body[..end]);
+        rest = &body[end + 2..];
+    }
+    Ok(expressions)
+}
+
+/// Parse an expression, without its `${{ }}`
+pub fn parse(expression: &str) -> Result<Expr, String> {
+    let tokens = tokenize(expression)?;
+    if tokens.is_empty() {
+        return Err("Empty expression".to_string());
+    }
+    let mut parser = Parser {
+        tokens: &tokens,
+        pos: 0,
+    };
+    let expr = parser.binary(0)?;
+    match parser.tokens.get(parser.pos) {
+        Some(token) => Err(format!("Unexpected {} in '{}'", token, expression.trim())),
+        None => Ok(expr),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Identifier(String),
+    String(String),
+    Number(f64),
+    Op(BinaryOp),
+    Not,
+    Dot,
+    Comma,
+    Star,
+    Open,
+    Close,
+    OpenBracket,
+    CloseBracket,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Identifier(name) => write!(f, "'{}'", name),
+            Token::String(s) => write!(f, "string '{}'", s),
+            Token::Number(n) => write!(f, "number {}", n),
+            Token::Op(op) => write!(f, "'{}'", op.as_str()),
+            Token::Not => write!(f, "'!'"),
+            Token::Dot => write!(f, "'.'"),
+            Token::Comma => write!(f, "','"),
+            Token::Star => write!(f, "'*'"),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+            Token::OpenBracket => write!(f, "'['"),
+            Token::CloseBracket => write!(f, "']'"),
+        }
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = expression.chars().collect();
+    let mut idx = 0;
+    while idx < chars.len() {
+        let c = chars[idx];
+        let next = chars.get(idx + 1).copied();
+        let (token, len) = match c {
+            c if c.is_whitespace() => {
+                idx += 1;
+                continue;
+            }
+            '\'' => {
+                let mut literal = String::new();
+                let mut end = idx + 1;
+                loop {
+                    match chars.get(end) {
+                        // '' is an escaped quote
+                        Some('\'') if chars.get(end + 1) == Some(&'\'') => {
+                            literal.push('\'');
+                            end += 2;
+                        }
+                        Some('\'') => break,
+                        Some(c) => {
+                            literal.push(*c);
+                            end += 1;
+                        }
+                        None => {
+                            return Err(format!("Unterminated string in '{}'", expression.trim()))
+                        }
+                    }
+                }
+                (Token::String(literal), end + 1 - idx)
+            }
+            '"' => {
+                return Err(format!(
+                    "Strings in expressions take single quotes, not double quotes: '{}'",
+                    expression.trim()
+                ))
+            }
+            '(' => (Token::Open, 1),
+            ')' => (Token::Close, 1),
+            '[' => (Token::OpenBracket, 1),
+            ']' => (Token::CloseBracket, 1),
+            ',' => (Token::Comma, 1),
+            '*' => (Token::Star, 1),
+            '.' if !next.is_some_and(|n| n.is_ascii_digit()) => (Token::Dot, 1),
+            '!' if next == Some('=') => (Token::Op(BinaryOp::NotEqual), 2),
+            '!' => (Token::Not, 1),
+            '=' if next == Some('=') => (Token::Op(BinaryOp::Equal), 2),
+            '<' if next == Some('=') => (Token::Op(BinaryOp::LessOrEqual), 2),
+            '<' => (Token::Op(BinaryOp::Less), 1),
+            '>' if next == Some('=') => (Token::Op(BinaryOp::GreaterOrEqual), 2),
+            '>' => (Token::Op(BinaryOp::Greater), 1),
+            '&' if next == Some('&') => (Token::Op(BinaryOp::And), 2),
+            '|' if next == Some('|') => (Token::Op(BinaryOp::Or), 2),
+            c if c.is_ascii_digit()
+                || c == '.'
+                || (c == '-' && next.is_some_and(|n| n.is_ascii_digit() || n == '.')) =>
+            {
+                let mut end = idx + 1;
+                while end < chars.len()
+                    && (chars[end].is_ascii_alphanumeric()
+                        || chars[end] == '.'
+                        || ((chars[end] == '-' || chars[end] == '+')
+                            && matches!(chars[end - 1], 'e' | 'E')))
+                {
+                    end += 1;
+                }
+                let literal: String = chars[idx..end].iter().collect();
+                (Token::Number(parse_number(&literal)?), end - idx)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = idx + 1;
+                while end < chars.len()
+                    && (chars[end].is_alphanumeric() || chars[end] == '_' || chars[end] == '-')
+                {
+                    end += 1;
+                }
+                (
+                    Token::Identifier(chars[idx..end].iter().collect()),
+                    end - idx,
+                )
+            }
+            c => return Err(format!("Unexpected '{}' in '{}'", c, expression.trim())),
+        };
+        tokens.push(token);
+        idx += len;
+    }
+    Ok(tokens)
+}
+
+fn parse_number(literal: &str) -> Result<f64, String> {
+    let (negative, digits) = match literal.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, literal),
+    };
+    let value = if let Some(hex) = digits.strip_prefix("0x") {
+        i64::from_str_radix(hex, 16).ok().map(|n| n as f64)
+    } else if let Some(octal) = digits.strip_prefix("0o") {
+        i64::from_str_radix(octal, 8).ok().map(|n| n as f64)
+    } else {
+        digits.parse::<f64>().ok()
+    };
+    let value = value.ok_or_else(|| format!("Invalid number '{}'", literal))?;
+    Ok(if negative { -value } else { value })
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<&Token, String> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .ok_or_else(|| "Unexpected end of expression".to_string())?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next()? {
+            token if *token == expected => Ok(()),
+            token => Err(format!("Expected {} but found {}", expected, token)),
+        }
+    }
+
+    /// Binary operators binding at least as tightly as `min_precedence`
+    fn binary(&mut self, min_precedence: u8) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        while let Some(Token::Op(op)) = self.peek() {
+            let op = *op;
+            if op.precedence() < min_precedence {
+                break;
+            }
+            self.pos += 1;
+            let right = self.binary(op.precedence() + 1)?;
+            left = Expr::Binary(Box::new(left), op, Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        let primary = self.primary()?;
+        self.accessors(primary)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next()?.clone() {
+            Token::Open => {
+                let expr = self.binary(0)?;
+                self.expect(Token::Close)?;
+                Ok(expr)
+            }
+            Token::String(s) => Ok(Expr::String(s)),
+            Token::Number(n) => Ok(Expr::Number(n)),
+            Token::Identifier(name) => {
+                if self.peek() == Some(&Token::Open) {
+                    self.pos += 1;
+                    return Ok(Expr::Call(name, self.arguments()?));
+                }
+                Ok(match name.as_str() {
+                    "null" => Expr::Null,
+                    "true" => Expr::Bool(true),
+                    "false" => Expr::Bool(false),
+                    _ => Expr::Context(name),
+                })
+            }
+            token => Err(format!("Unexpected {}", token)),
+        }
+    }
+
+    fn arguments(&mut self) -> Result<Vec<Expr>, String> {
+        let mut args = Vec::new();
+        if self.peek() == Some(&Token::Close) {
+            self.pos += 1;
+            return Ok(args);
+        }
+        loop {
+            args.push(self.binary(0)?);
+            match self.next()? {
+                Token::Comma => continue,
+                Token::Close => return Ok(args),
+                token => return Err(format!("Expected ',' or ')' but found {}", token)),
+            }
+        }
+    }
+
+    /// `.name`, `.*`, `[index]` and `[*]` after `object`
+    fn accessors(&mut self, mut object: Expr) -> Result<Expr, String> {
+        loop {
+            match self.peek() {
+                Some(Token::Dot) => {
+                    self.pos += 1;
+                    object = match self.next()?.clone() {
+                        Token::Identifier(name) => Expr::Property(Box::new(object), name),
+                        Token::Star => Expr::Wildcard(Box::new(object)),
+                        token => {
+                            return Err(format!("Expected a property name but found {}", token))
+                        }
+                    };
+                }
+                Some(Token::OpenBracket) => {
+                    self.pos += 1;
+                    if self.peek() == Some(&Token::Star) {
+                        self.pos += 1;
+                        object = Expr::Wildcard(Box::new(object));
+                    } else {
+                        object = match self.binary(0)? {
+                            Expr::String(name) => Expr::Property(Box::new(object), name),
+                            index => Expr::Index(Box::new(object), Box::new(index)),
+                        };
+                    }
+                    self.expect(Token::CloseBracket)?;
+                }
+                _ => return Ok(object),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract() {
+        assert_eq!(
+            extract("a ${{ x }} b ${{ format('{0}}}', y) }}"),
+            Ok(vec![" x ", " format('{0}}}', y) "])
+        );
+        assert_eq!(extract("no expressions"), Ok(vec![]));
+        assert!(extract("${{ x }").is_err());
+    }
+
+    #[test]
+    fn test_parse() {
+        let expr =
+            parse("github.event_name == 'push' && !contains(needs.*.result, 'failure')").unwrap();
+        assert_eq!(
+            expr.to_string(),
+            "github.event_name == 'push' && !contains(needs.*.result, 'failure')"
+        );
+        let Expr::Binary(left, BinaryOp::And, right) = &expr else {
+            panic!("{:?}", expr);
+        };
+        assert_eq!(
+            left.as_ref(),
+            &Expr::Binary(
+                Box::new(Expr::Property(
+                    Box::new(Expr::Context("github".to_string())),
+                    "event_name".to_string()
+                )),
+                BinaryOp::Equal,
+                Box::new(Expr::String("push".to_string()))
+            )
+        );
+        assert!(matches!(right.as_ref(), Expr::Not(_)));
+
+        // && binds tighter than ||, comparisons tighter than both
+        assert_eq!(
+            parse("a || b && c == 1").unwrap().to_string(),
+            "a || b && c == 1"
+        );
+        assert!(matches!(
+            parse("a || b && c").unwrap(),
+            Expr::Binary(_, BinaryOp::Or, _)
+        ));
+
+        let indexed = parse("steps.my-step.outputs['the value']").unwrap();
+        assert_eq!(
+            indexed.reference(),
+            Some(vec!["steps", "my-step", "outputs", "the value"])
+        );
+        assert_eq!(parse("-1.5e3").unwrap(), Expr::Number(-1500.0));
+        assert_eq!(parse("0xff").unwrap(), Expr::Number(255.0));
+        assert_eq!(parse("'it''s'").unwrap(), Expr::String("it's".to_string()));
+        assert_eq!(
+            parse("matrix[inputs.key]").unwrap(),
+            Expr::Index(
+                Box::new(Expr::Context("matrix".to_string())),
+                Box::new(Expr::Property(
+                    Box::new(Expr::Context("inputs".to_string())),
+                    "key".to_string()
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        for expression in [
+            "",
+            "github.",
+            "a ==",
+            "(a",
+            "a)",
+            "contains(a b)",
+            "\"double\"",
+            "'open",
+            "a = b",
+            "a &b",
+        ] {
+            assert!(parse(expression).is_err(), "{}", expression);
+        }
+    }
+}
@@ -7,6 +7,20 @@ use thiserror::Error;
 use wrkflw_models::gitlab::Pipeline;
 use wrkflw_models::ValidationResult;
 
+/// Top-level keys of a GitLab pipeline that aren't jobs
+pub const RESERVED_KEYS: &[&str] = &[
+    "default",
+    "include",
+    "stages",
+    "variables",
+    "workflow",
+    "image",
+    "services",
+    "cache",
+    "before_script",
+    "after_script",
+];
+
 #[derive(Error, Debug)]
 pub enum GitlabParserError {
     #[error("I/O error: {0}")]
@@ -34,8 +48,10 @@ pub fn parse_pipeline(pipeline_path: &Path) -> Result<Pipeline, GitlabParserErro
         .validate_with_specific_schema(&pipeline_content, SchemaType::GitLab)
         .map_err(GitlabParserError::SchemaValidationError)?;
 
-    // Parse the pipeline YAML
-    let pipeline: Pipeline = serde_yaml::from_str(&pipeline_content)?;
+    // Parse the pipeline YAML, with the mappings of `<<` keys merged
+    let mut yaml: serde_yaml::Value = serde_yaml::from_str(&pipeline_content)?;
+    yaml.apply_merge()?;
+    let pipeline: Pipeline = serde_yaml::from_value(yaml)?;
 
     // Return the parsed pipeline
     Ok(pipeline)
@@ -57,10 +73,10 @@ pub fn validate_pipeline_structure(pipeline: &Pipeline) -> ValidationResult {
             continue;
         }
 
-        // Check for script or extends
-        if job.script.is_none() && job.extends.is_none() {
+        // Check for script, extends or trigger
+        if job.script.is_none() && job.extends.is_none() && job.trigger.is_none() {
             result.add_issue(format!(
-                "Job '{}' must have a script section or extend another job",
+                "Job '{}' must have a script section, extend another job or trigger a pipeline",
                 job_name
             ));
         }
@@ -118,6 +134,9 @@ pub fn convert_to_workflow_format(pipeline: &Pipeline) -> workflow::WorkflowDefi
         name: "Converted GitLab CI Pipeline".to_string(),
         on: vec!["push".to_string()], // Default trigger
         on_raw: serde_yaml::Value::String("push".to_string()),
+        permissions: None,
+        defaults: None,
+        env: HashMap::new(),
         jobs: HashMap::new(),
     };
 
@@ -135,10 +154,13 @@ pub fn convert_to_workflow_format(pipeline: &Pipeline) -> workflow::WorkflowDefi
             steps: Vec::new(),
             env: HashMap::new(),
             matrix: None,
+            strategy: None,
             services: HashMap::new(),
             if_condition: None,
             outputs: None,
             permissions: None,
+            defaults: None,
+            environment: None,
             uses: None,
             with: None,
             secrets: None,
@@ -161,12 +183,16 @@ pub fn convert_to_workflow_format(pipeline: &Pipeline) -> workflow::WorkflowDefi
         if let Some(before_script) = &gitlab_job.before_script {
             for (i, cmd) in before_script.iter().enumerate() {
                 let step = workflow::Step {
+                    id: None,
                     name: Some(format!("Before script {}", i + 1)),
+                    if_condition: None,
                     uses: None,
                     run: Some(cmd.clone()),
                     with: None,
                     env: HashMap::new(),
                     continue_on_error: None,
+                    shell: None,
+                    working_directory: None,
                 };
                 job.steps.push(step);
             }
@@ -176,12 +202,16 @@ pub fn convert_to_workflow_format(pipeline: &Pipeline) -> workflow::WorkflowDefi
         if let Some(script) = &gitlab_job.script {
             for (i, cmd) in script.iter().enumerate() {
                 let step = workflow::Step {
+                    id: None,
                     name: Some(format!("Run script line {}", i + 1)),
+                    if_condition: None,
                     uses: None,
                     run: Some(cmd.clone()),
                     with: None,
                     env: HashMap::new(),
                     continue_on_error: None,
+                    shell: None,
+                    working_directory: None,
                 };
                 job.steps.push(step);
             }
@@ -191,12 +221,17 @@ pub fn convert_to_workflow_format(pipeline: &Pipeline) -> workflow::WorkflowDefi
         if let Some(after_script) = &gitlab_job.after_script {
             for (i, cmd) in after_script.iter().enumerate() {
                 let step = workflow::Step {
+                    id: None,
                     name: Some(format!("After script {}", i + 1)),
+                    if_condition: Some("always()".to_string()),
                     uses: None,
                     run: Some(cmd.clone()),
                     with: None,
                     env: HashMap::new(),
-                    continue_on_error: Some(true), // After script should continue even if previous steps fail
+                    // After script runs even if previous steps failed, and doesn't fail the job
+                    continue_on_error: Some(true),
+                    shell: None,
+                    working_directory: None,
                 };
                 job.steps.push(step);
             }
@@ -0,0 +1,486 @@
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+use wrkflw_models::jenkins::{Jenkinsfile, Node};
+
+#[derive(Error, Debug)]
+pub enum JenkinsParserError {
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Syntax error at line {line} column {column}: {message}")]
+    SyntaxError {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+/// Parse a Jenkinsfile into its tree of directives
+///
+/// Only as much Groovy is understood as declarative pipelines use: calls with or
+/// without parentheses, closures, assignments, strings and comments. Anything
+/// else, such as the code in `script { }` blocks, is kept as statements whose
+/// arguments are left unparsed.
+pub fn parse_pipeline(pipeline_path: &Path) -> Result<Jenkinsfile, JenkinsParserError> {
+    let content = fs::read_to_string(pipeline_path)?;
+    parse_content(&content)
+}
+
+/// Parse the text of a Jenkinsfile, see [`parse_pipeline`]
+pub fn parse_content(content: &str) -> Result<Jenkinsfile, JenkinsParserError> {
+    let tokens = tokenize(content)?;
+    let mut parser = Parser {
+        source: content,
+        tokens,
+        pos: 0,
+    };
+    Ok(Jenkinsfile {
+        nodes: parser.block(None)?,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Word,
+    Str,
+    Newline,
+    Symbol(char),
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: Kind,
+    start: usize,
+    end: usize,
+    line: usize,
+    column: usize,
+}
+
+fn syntax_error(line: usize, column: usize, message: impl Into<String>) -> JenkinsParserError {
+    JenkinsParserError::SyntaxError {
+        line,
+        column,
+        message: message.into(),
+    }
+}
+
+fn tokenize(content: &str) -> Result<Vec<Token>, JenkinsParserError> {
+    let chars: Vec<(usize, char)> = content.char_indices().collect();
+    let mut tokens = Vec::new();
+    let (mut line, mut line_start) = (1, 0);
+    let mut i = 0;
+    let at = |i: usize| chars.get(i).map(|(_, c)| *c);
+    let offset = |i: usize| chars.get(i).map(|(o, _)| *o).unwrap_or(content.len());
+
+    while let Some(c) = at(i) {
+        let column = i - line_start + 1;
+        let start_line = line;
+        let start = i;
+        match c {
+            '\n' => {
+                tokens.push(Token {
+                    kind: Kind::Newline,
+                    start: offset(i),
+                    end: offset(i + 1),
+                    line,
+                    column,
+                });
+                i += 1;
+                line += 1;
+                line_start = i;
+                continue;
+            }
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '/' if at(i + 1) == Some('/') => {
+                while at(i).is_some_and(|c| c != '\n') {
+                    i += 1;
+                }
+                continue;
+            }
+            '/' if at(i + 1) == Some('*') => {
+                i += 2;
+                loop {
+                    match at(i) {
+                        None => return Err(syntax_error(start_line, column, "Unclosed comment")),
+                        Some('*') if at(i + 1) == Some('/') => {
+                            i += 2;
+                            break;
+                        }
+                        Some('\n') => {
+                            i += 1;
+                            line += 1;
+                            line_start = i;
+                        }
+                        Some(_) => i += 1,
+                    }
+                }
+                continue;
+            }
+            '\'' | '"' => {
+                let triple = at(i + 1) == Some(c) && at(i + 2) == Some(c);
+                i += if triple { 3 } else { 1 };
+                // Depth of `${ }` in double-quoted strings, where quotes don't end it
+                let mut depth = 0;
+                loop {
+                    match at(i) {
+                        None => {
+                            return Err(syntax_error(start_line, column, "Unterminated string"))
+                        }
+                        Some('\\') => {
+                            if at(i + 1) == Some('\n') {
+                                line += 1;
+                                line_start = i + 2;
+                            }
+                            i += 2;
+                        }
+                        Some('\n') if !triple && depth == 0 => {
+                            return Err(syntax_error(start_line, column, "Unterminated string"))
+                        }
+                        Some('\n') => {
+                            i += 1;
+                            line += 1;
+                            line_start = i;
+                        }
+                        Some('$') if c == '"' && at(i + 1) == Some('{') => {
+                            depth += 1;
+                            i += 2;
+                        }
+                        Some('}') if depth > 0 => {
+                            depth -= 1;
+                            i += 1;
+                        }
+                        Some(q) if q == c && depth == 0 => {
+                            if !triple {
+                                i += 1;
+                                break;
+                            }
+                            if at(i + 1) == Some(c) && at(i + 2) == Some(c) {
+                                i += 3;
+                                break;
+                            }
+                            i += 1;
+                        }
+                        Some(_) => i += 1,
+                    }
+                }
+                tokens.push(Token {
+                    kind: Kind::Str,
+                    start: offset(start),
+                    end: offset(i),
+                    line: start_line,
+                    column,
+                });
+                continue;
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '$' => {
+                while at(i).is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '$' || c == '.')
+                {
+                    i += 1;
+                }
+                tokens.push(Token {
+                    kind: Kind::Word,
+                    start: offset(start),
+                    end: offset(i),
+                    line,
+                    column,
+                });
+                continue;
+            }
+            c => {
+                tokens.push(Token {
+                    kind: Kind::Symbol(c),
+                    start: offset(i),
+                    end: offset(i + 1),
+                    line,
+                    column,
+                });
+                i += 1;
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<Kind> {
+        self.tokens.get(self.pos).map(|token| token.kind)
+    }
+
+    fn at_statement_end(&self) -> bool {
+        matches!(
+            self.peek(),
+            None | Some(Kind::Newline) | Some(Kind::Symbol(';')) | Some(Kind::Symbol('}'))
+        )
+    }
+
+    /// Statements up to the `}` closing the brace at `opened`, or to the end of
+    /// the file at the top level
+    fn block(&mut self, opened: Option<&Token>) -> Result<Vec<Node>, JenkinsParserError> {
+        let mut nodes = Vec::new();
+        loop {
+            match self.peek() {
+                None => {
+                    return match opened {
+                        Some(brace) => Err(syntax_error(
+                            brace.line,
+                            brace.column,
+                            "'{' is never closed",
+                        )),
+                        None => Ok(nodes),
+                    }
+                }
+                Some(Kind::Symbol('}')) => {
+                    let token = &self.tokens[self.pos];
+                    if opened.is_none() {
+                        return Err(syntax_error(token.line, token.column, "Unexpected '}'"));
+                    }
+                    self.pos += 1;
+                    return Ok(nodes);
+                }
+                Some(Kind::Newline) | Some(Kind::Symbol(';')) => self.pos += 1,
+                Some(Kind::Word) => nodes.push(self.statement()?),
+                // Groovy that isn't a directive, e.g. `} else {` or `[a, b].each { }`
+                Some(_) => {
+                    self.arguments(false)?;
+                    if self.peek() == Some(Kind::Symbol('{')) {
+                        let brace = self.tokens[self.pos].clone();
+                        self.pos += 1;
+                        nodes.extend(self.block(Some(&brace))?);
+                    }
+                }
+            }
+        }
+    }
+
+    fn statement(&mut self) -> Result<Node, JenkinsParserError> {
+        let name = self.tokens[self.pos].clone();
+        self.pos += 1;
+        let mut node = Node {
+            name: self.source[name.start..name.end].to_string(),
+            line: name.line,
+            column: name.column,
+            ..Default::default()
+        };
+
+        if self.peek() == Some(Kind::Symbol('=')) {
+            self.pos += 1;
+            node.assigned = true;
+            node.args = self.arguments(false)?;
+            return Ok(node);
+        }
+        if self.peek() == Some(Kind::Symbol('(')) {
+            self.pos += 1;
+            node.args = self.arguments(true)?;
+        }
+        if !self.at_statement_end() && self.peek() != Some(Kind::Symbol('{')) {
+            // Arguments without parentheses, or what follows a call, e.g. `.trim()`
+            let rest = self.arguments(false)?;
+            if node.args.is_empty() {
+                node.args = rest;
+            }
+        }
+        if self.peek() == Some(Kind::Symbol('{')) {
+            let brace = self.tokens[self.pos].clone();
+            self.pos += 1;
+            node.body = Some(self.block(Some(&brace))?);
+        }
+        Ok(node)
+    }
+
+    /// Comma-separated arguments as source text: up to the closing `)` if
+    /// `parenthesized`, else to the end of the statement or an opening `{`
+    fn arguments(&mut self, parenthesized: bool) -> Result<Vec<String>, JenkinsParserError> {
+        let opened = self.tokens.get(self.pos.saturating_sub(1)).cloned();
+        let mut args = Vec::new();
+        let mut depth = 0;
+        let mut current: Option<(usize, usize)> = None;
+        loop {
+            let Some(token) = self.tokens.get(self.pos).cloned() else {
+                if parenthesized {
+                    let opened = opened.unwrap();
+                    return Err(syntax_error(
+                        opened.line,
+                        opened.column,
+                        "'(' is never closed",
+                    ));
+                }
+                break;
+            };
+            match token.kind {
+                Kind::Symbol(')') if parenthesized && depth == 0 => {
+                    self.pos += 1;
+                    break;
+                }
+                Kind::Symbol(',') if depth == 0 => {
+                    args.extend(current.take());
+                    self.pos += 1;
+                    continue;
+                }
+                Kind::Symbol('{') | Kind::Symbol(';') | Kind::Symbol('}') | Kind::Newline
+                    if !parenthesized && depth == 0 =>
+                {
+                    break
+                }
+                Kind::Symbol('(') | Kind::Symbol('[') | Kind::Symbol('{') => depth += 1,
+                Kind::Symbol(')') | Kind::Symbol(']') | Kind::Symbol('}') => {
+                    if depth == 0 {
+                        return Err(syntax_error(
+                            token.line,
+                            token.column,
+                            format!("Unexpected '{}'", &self.source[token.start..token.end]),
+                        ));
+                    }
+                    depth -= 1;
+                }
+                _ => {}
+            }
+            self.pos += 1;
+            if token.kind != Kind::Newline {
+                current = Some(match current {
+                    Some((start, _)) => (start, token.end),
+                    None => (token.start, token.end),
+                });
+            }
+        }
+        args.extend(current);
+        Ok(args
+            .into_iter()
+            .map(|(start, end)| self.source[start..end].to_string())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pipeline() {
+        let file = parse_content(
+            r#"
+// Build and deploy
+pipeline {
+    agent { docker { image 'rust:1.80' } }
+    environment {
+        CARGO_TERM_COLOR = 'always'
+        TOKEN = credentials('deploy-token')
+    }
+    options { timeout(time: 1, unit: 'HOURS') }
+    stages {
+        stage('Build') {
+            steps {
+                sh 'cargo build'
+                sh """
+                    echo "${env.BRANCH_NAME}"
+                """
+            }
+        }
+        /* Checks run side by side */
+        stage("Checks") {
+            parallel {
+                stage('Test') { steps { sh 'cargo test' } }
+                stage('Lint') {
+                    steps {
+                        script {
+                            if (env.BRANCH_NAME == 'main') {
+                                sh 'cargo clippy'
+                            } else {
+                                echo 'skipped'
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    post {
+        always { junit 'target/*.xml' }
+    }
+}
+"#,
+        )
+        .unwrap();
+
+        let pipeline = file.pipeline().unwrap();
+        assert_eq!((pipeline.line, pipeline.column), (3, 1));
+        let names: Vec<_> = pipeline
+            .body
+            .iter()
+            .flatten()
+            .map(|node| node.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["agent", "environment", "options", "stages", "post"]
+        );
+
+        let environment = pipeline.child("environment").unwrap();
+        let token = environment.child("TOKEN").unwrap();
+        assert!(token.assigned);
+        assert_eq!(token.args, vec!["credentials('deploy-token')"]);
+        assert_eq!(
+            pipeline
+                .child("options")
+                .unwrap()
+                .child("timeout")
+                .unwrap()
+                .args,
+            vec!["time: 1", "unit: 'HOURS'"]
+        );
+
+        let stages: Vec<_> = pipeline
+            .child("stages")
+            .unwrap()
+            .children("stage")
+            .collect();
+        assert_eq!(stages[0].string_arg(), Some("Build"));
+        assert_eq!(
+            stages[0]
+                .child("steps")
+                .unwrap()
+                .body
+                .as_ref()
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(stages[1].string_arg(), Some("Checks"));
+        let parallel: Vec<_> = stages[1]
+            .child("parallel")
+            .unwrap()
+            .children("stage")
+            .filter_map(Node::string_arg)
+            .collect();
+        assert_eq!(parallel, vec!["Test", "Lint"]);
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        let error =
+            parse_content("pipeline {\n    stages {\n        stage('x') {\n}\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Syntax error at line 2 column 12: '{' is never closed"
+        );
+        let error = parse_content("pipeline {\n    agent any\n}\n}\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Syntax error at line 4 column 1: Unexpected '}'"
+        );
+        let error = parse_content("pipeline {\n    agent { label 'x }\n}\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Syntax error at line 2 column 19: Unterminated string"
+        );
+    }
+}
@@ -1,5 +1,13 @@
 // parser crate
 
+pub mod audit;
+pub mod azure;
+pub mod convert;
+pub mod cron;
+pub mod expression;
 pub mod gitlab;
+pub mod jenkins;
+pub mod pin;
 pub mod schema;
 pub mod workflow;
+pub mod yaml;
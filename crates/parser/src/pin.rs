@@ -0,0 +1,195 @@
+//! `uses:` references to remote actions and reusable workflows, and pinning them
+//! to commit SHAs.
+//!
+//! Like audits, pins rewrite workflows line by line so that everything but the
+//! references stays as it was. A pinned reference keeps the version it was pinned
+//! from in a trailing comment: `uses: actions/checkout@<sha> # v4.2.2`.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashMap;
+
+lazy_static! {
+    static ref USES: Regex = Regex::new(
+        r##"^(\s*(?:-\s*)?uses:\s*)(['"]?)([\w.-]+/[\w.-]+(?:/[^@\s'"]*)?)@([^\s'"#]+)(['"]?)(?:\s+#\s*(.*?))?\s*$"##
+    )
+    .unwrap();
+}
+
+/// Owners whose actions are maintained by GitHub
+const FIRST_PARTY_OWNERS: &[&str] = &["actions", "github"];
+
+/// A `uses: owner/repo[/path]@ref` reference
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsesRef {
+    /// 1-based
+    pub line: usize,
+    /// `owner/repo[/path]`
+    pub action: String,
+    pub git_ref: String,
+    /// The trailing comment, the version of a pinned reference
+    pub comment: Option<String>,
+}
+
+impl UsesRef {
+    pub fn owner(&self) -> &str {
+        self.action.split('/').next().unwrap_or_default()
+    }
+
+    pub fn repo(&self) -> &str {
+        self.action.split('/').nth(1).unwrap_or_default()
+    }
+
+    /// Whether the reference is a full commit SHA, which can't be moved
+    pub fn is_pinned(&self) -> bool {
+        self.git_ref.len() == 40 && self.git_ref.chars().all(|c| c.is_ascii_hexdigit())
+    }
+
+    /// Whether the action comes from someone other than GitHub
+    pub fn is_third_party(&self) -> bool {
+        !FIRST_PARTY_OWNERS.contains(&self.owner())
+    }
+
+    /// The version a pinned reference was pinned from, if its comment names one
+    pub fn pinned_version(&self) -> Option<&str> {
+        let comment = self.comment.as_deref()?;
+        let version = comment.split_whitespace().next()?;
+        version
+            .trim_start_matches('v')
+            .starts_with(|c: char| c.is_ascii_digit())
+            .then_some(version)
+    }
+}
+
+/// The remote references in a workflow; local actions, `docker://` images and
+/// commented lines are left out
+pub fn uses_refs(content: &str) -> Vec<UsesRef> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(idx, line)| {
+            let caps = USES.captures(line)?;
+            // Mismatched quotes aren't valid YAML
+            if caps[2] != caps[5] {
+                return None;
+            }
+            Some(UsesRef {
+                line: idx + 1,
+                action: caps[3].to_string(),
+                git_ref: caps[4].to_string(),
+                comment: caps.get(6).map(|comment| comment.as_str().to_string()),
+            })
+        })
+        .collect()
+}
+
+/// `content` with the references on the lines of `pins` replaced by the commit
+/// and version comment they map to
+pub fn pin(content: &str, pins: &HashMap<usize, (String, String)>) -> String {
+    content
+        .split_inclusive('\n')
+        .enumerate()
+        .map(|(idx, line)| {
+            let Some((commit, version)) = pins.get(&(idx + 1)) else {
+                return line.to_string();
+            };
+            let (text, ending) = match line.strip_suffix('\n') {
+                Some(text) => (text, "\n"),
+                None => (line, ""),
+            };
+            let text = text.strip_suffix('\r').unwrap_or(text);
+            let ending = if line.ends_with("\r\n") {
+                "\r\n"
+            } else {
+                ending
+            };
+            match USES.captures(text) {
+                Some(caps) => format!(
+                    "{}{}{}@{}{} # {}{}",
+                    &caps[1], &caps[2], &caps[3], commit, &caps[5], version, ending
+                ),
+                None => line.to_string(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKFLOW: &str = r#"jobs:
+  build:
+    steps:
+      - uses: actions/checkout@v4
+      - uses: "docker/login-action@v3"
+      # - uses: someone/commented@v1
+      - uses: ./local-action
+      - uses: docker://alpine:3
+      - uses: someone/tool/sub@0123456789abcdef0123456789abcdef01234567 # v1.2.0
+  deploy:
+    uses: org/workflows/.github/workflows/deploy.yml@main
+"#;
+
+    #[test]
+    fn test_uses_refs() {
+        let refs = uses_refs(WORKFLOW);
+        let summary: Vec<(usize, &str, &str, bool, bool)> = refs
+            .iter()
+            .map(|r| {
+                (
+                    r.line,
+                    r.action.as_str(),
+                    r.git_ref.as_str(),
+                    r.is_pinned(),
+                    r.is_third_party(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (4, "actions/checkout", "v4", false, false),
+                (5, "docker/login-action", "v3", false, true),
+                (
+                    9,
+                    "someone/tool/sub",
+                    "0123456789abcdef0123456789abcdef01234567",
+                    true,
+                    true
+                ),
+                (
+                    11,
+                    "org/workflows/.github/workflows/deploy.yml",
+                    "main",
+                    false,
+                    true
+                ),
+            ]
+        );
+        assert_eq!(refs[2].pinned_version(), Some("v1.2.0"));
+        assert_eq!((refs[2].owner(), refs[2].repo()), ("someone", "tool"));
+        assert_eq!(refs[0].pinned_version(), None);
+    }
+
+    #[test]
+    fn test_pin() {
+        let sha = "a".repeat(40);
+        let pins = HashMap::from([
+            (4, (sha.clone(), "v4.2.2".to_string())),
+            (5, (sha.clone(), "v3.3.0".to_string())),
+        ]);
+        let pinned = pin(WORKFLOW, &pins);
+        let lines: Vec<&str> = pinned.lines().collect();
+        assert_eq!(
+            lines[3],
+            format!("      - uses: actions/checkout@{} # v4.2.2", sha)
+        );
+        assert_eq!(
+            lines[4],
+            format!("      - uses: \"docker/login-action@{}\" # v3.3.0", sha)
+        );
+        assert_eq!(&lines[5..], &WORKFLOW.lines().collect::<Vec<_>>()[5..]);
+        assert!(uses_refs(&pinned)[..2].iter().all(UsesRef::is_pinned));
+    }
+}
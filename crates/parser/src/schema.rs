@@ -1,3 +1,4 @@
+use crate::yaml::YamlError;
 use jsonschema::JSONSchema;
 use serde_json::Value;
 use std::fs;
@@ -83,9 +84,15 @@ impl SchemaValidator {
         content: &str,
         schema_type: SchemaType,
     ) -> Result<(), String> {
-        // Parse YAML to JSON Value
+        // Parse YAML to JSON Value; GitLab merges the mappings of `<<` keys first
+        let mut yaml: serde_yaml::Value = serde_yaml::from_str(content)
+            .map_err(|e| format!("Failed to parse YAML: {}", YamlError::new(content, &e)))?;
+        if let SchemaType::GitLab = schema_type {
+            yaml.apply_merge()
+                .map_err(|e| format!("Failed to merge YAML: {}", e))?;
+        }
         let workflow_json: Value =
-            serde_yaml::from_str(content).map_err(|e| format!("Failed to parse YAML: {}", e))?;
+            serde_json::to_value(&yaml).map_err(|e| format!("Failed to parse YAML: {}", e))?;
 
         // Validate against the appropriate schema
         let validation_result = match schema_type {
@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 use wrkflw_matrix::MatrixConfig;
+pub use wrkflw_models::PermissionLevel;
 
 use super::schema::SchemaValidator;
 
@@ -46,6 +47,73 @@ where
     }
 }
 
+// Custom deserializer for environment field that handles both a name and a `{name, url}` block
+fn deserialize_environment<'de, D>(deserializer: D) -> Result<Option<JobEnvironment>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum NameOrBlock {
+        Name(String),
+        Block(JobEnvironment),
+    }
+
+    let value = Option::<NameOrBlock>::deserialize(deserializer)?;
+    match value {
+        Some(NameOrBlock::Name(name)) => Ok(Some(JobEnvironment { name, url: None })),
+        Some(NameOrBlock::Block(environment)) => Ok(Some(environment)),
+        None => Ok(None),
+    }
+}
+
+/// The deployment environment a job runs in
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JobEnvironment {
+    pub name: String,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+/// A `permissions:` block, either a `read-all`/`write-all` shorthand or per-scope levels
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Permissions {
+    Shorthand(String),
+    Scopes(HashMap<String, String>),
+}
+
+impl Permissions {
+    /// Level granted for `scope`. Scopes left out of an explicit block get no access.
+    pub fn level(&self, scope: &str) -> PermissionLevel {
+        match self {
+            Permissions::Shorthand(s) if s == "write-all" => PermissionLevel::Write,
+            Permissions::Shorthand(s) if s == "read-all" => PermissionLevel::Read,
+            Permissions::Shorthand(_) => PermissionLevel::None,
+            Permissions::Scopes(scopes) => scopes
+                .get(scope)
+                .and_then(|level| PermissionLevel::parse(level))
+                .unwrap_or(PermissionLevel::None),
+        }
+    }
+}
+
+/// A `defaults:` block of a workflow or job
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Defaults {
+    #[serde(default)]
+    pub run: Option<RunDefaults>,
+}
+
+/// Settings applied to every `run:` step unless the step overrides them
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct RunDefaults {
+    #[serde(default)]
+    pub shell: Option<String>,
+    #[serde(default, rename = "working-directory")]
+    pub working_directory: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct WorkflowDefinition {
     pub name: String,
@@ -53,6 +121,13 @@ pub struct WorkflowDefinition {
     pub on: Vec<String>,
     #[serde(rename = "on")] // Raw access to the 'on' field for custom handling
     pub on_raw: serde_yaml::Value,
+    #[serde(default)]
+    pub permissions: Option<Permissions>,
+    #[serde(default)]
+    pub defaults: Option<Defaults>,
+    /// Variables set in every job
+    #[serde(default)]
+    pub env: HashMap<String, String>,
     pub jobs: HashMap<String, Job>,
 }
 
@@ -69,13 +144,19 @@ pub struct Job {
     #[serde(default)]
     pub matrix: Option<MatrixConfig>,
     #[serde(default)]
+    pub strategy: Option<Strategy>,
+    #[serde(default)]
     pub services: HashMap<String, Service>,
     #[serde(default, rename = "if")]
     pub if_condition: Option<String>,
     #[serde(default)]
     pub outputs: Option<HashMap<String, String>>,
     #[serde(default)]
-    pub permissions: Option<HashMap<String, String>>,
+    pub permissions: Option<Permissions>,
+    #[serde(default)]
+    pub defaults: Option<Defaults>,
+    #[serde(default, deserialize_with = "deserialize_environment")]
+    pub environment: Option<JobEnvironment>,
     // Reusable workflow (job-level 'uses') support
     #[serde(default)]
     pub uses: Option<String>,
@@ -85,6 +166,42 @@ pub struct Job {
     pub secrets: Option<serde_yaml::Value>,
 }
 
+/// A job's `strategy:` block
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Strategy {
+    /// Kept raw, as it can also be an expression such as `${{ fromJSON(...) }}`
+    #[serde(default)]
+    pub matrix: Option<serde_yaml::Value>,
+    #[serde(default, rename = "fail-fast")]
+    pub fail_fast: Option<bool>,
+    #[serde(default, rename = "max-parallel")]
+    pub max_parallel: Option<usize>,
+}
+
+impl Job {
+    /// The job's matrix, from `strategy.matrix` with the strategy's `fail-fast` and
+    /// `max-parallel`, or else a job-level `matrix:`
+    ///
+    /// Matrices computed by expressions can't be expanded locally, so the job then
+    /// runs once.
+    pub fn matrix_config(&self) -> Option<MatrixConfig> {
+        let strategy = self.strategy.as_ref();
+        let matrix = strategy
+            .and_then(|strategy| strategy.matrix.clone())
+            .filter(serde_yaml::Value::is_mapping)
+            .and_then(|matrix| serde_yaml::from_value::<MatrixConfig>(matrix).ok());
+        match matrix {
+            Some(mut matrix) => {
+                let strategy = strategy.cloned().unwrap_or_default();
+                matrix.fail_fast = strategy.fail_fast.or(matrix.fail_fast);
+                matrix.max_parallel = strategy.max_parallel.or(matrix.max_parallel);
+                Some(matrix)
+            }
+            None => self.matrix.clone(),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Service {
     pub image: String,
@@ -100,8 +217,12 @@ pub struct Service {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Step {
+    #[serde(default)]
+    pub id: Option<String>,
     #[serde(default)]
     pub name: Option<String>,
+    #[serde(default, rename = "if")]
+    pub if_condition: Option<String>,
     #[serde(default)]
     pub uses: Option<String>,
     #[serde(default)]
@@ -110,11 +231,85 @@ pub struct Step {
     pub with: Option<HashMap<String, String>>,
     #[serde(default)]
     pub env: HashMap<String, String>,
-    #[serde(default)]
+    #[serde(default, rename = "continue-on-error")]
     pub continue_on_error: Option<bool>,
+    #[serde(default)]
+    pub shell: Option<String>,
+    #[serde(default, rename = "working-directory")]
+    pub working_directory: Option<String>,
 }
 
 impl WorkflowDefinition {
+    /// `defaults.run` for a job, with job-level settings taking precedence
+    pub fn run_defaults(&self, job: &Job) -> RunDefaults {
+        let run = |defaults: &Option<Defaults>| {
+            defaults
+                .as_ref()
+                .and_then(|d| d.run.clone())
+                .unwrap_or_default()
+        };
+        let workflow = run(&self.defaults);
+        let job = run(&job.defaults);
+        RunDefaults {
+            shell: job.shell.or(workflow.shell),
+            working_directory: job.working_directory.or(workflow.working_directory),
+        }
+    }
+
+    /// Cron expressions of the `schedule` trigger, in the order they're declared
+    pub fn schedules(&self) -> Vec<String> {
+        self.on_raw
+            .get("schedule")
+            .and_then(serde_yaml::Value::as_sequence)
+            .into_iter()
+            .flatten()
+            .filter_map(|schedule| schedule.get("cron")?.as_str().map(str::to_string))
+            .collect()
+    }
+
+    /// Inputs of the `workflow_dispatch` trigger, in the order they're declared
+    pub fn dispatch_inputs(&self) -> Vec<DispatchInput> {
+        let Some(inputs) = self
+            .on_raw
+            .get("workflow_dispatch")
+            .and_then(|dispatch| dispatch.get("inputs"))
+            .and_then(serde_yaml::Value::as_mapping)
+        else {
+            return Vec::new();
+        };
+        let scalar = |value: &serde_yaml::Value| match value {
+            serde_yaml::Value::String(s) => Some(s.clone()),
+            serde_yaml::Value::Bool(b) => Some(b.to_string()),
+            serde_yaml::Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        };
+        inputs
+            .iter()
+            .filter_map(|(name, input)| {
+                let field = |key: &str| input.get(key).and_then(scalar);
+                let options = match field("type").as_deref() {
+                    Some("boolean") => vec!["true".to_string(), "false".to_string()],
+                    Some("choice") => input
+                        .get("options")
+                        .and_then(serde_yaml::Value::as_sequence)
+                        .map(|options| options.iter().filter_map(scalar).collect())
+                        .unwrap_or_default(),
+                    _ => Vec::new(),
+                };
+                Some(DispatchInput {
+                    name: name.as_str()?.to_string(),
+                    description: field("description"),
+                    default: field("default"),
+                    required: input
+                        .get("required")
+                        .and_then(serde_yaml::Value::as_bool)
+                        .unwrap_or(false),
+                    options,
+                })
+            })
+            .collect()
+    }
+
     pub fn resolve_action(&self, action_ref: &str) -> ActionInfo {
         // Parse GitHub action reference like "actions/checkout@v3"
         let parts: Vec<&str> = action_ref.split('@').collect();
@@ -133,6 +328,17 @@ impl WorkflowDefinition {
     }
 }
 
+/// An input of a `workflow_dispatch` trigger
+#[derive(Debug, Clone, PartialEq)]
+pub struct DispatchInput {
+    pub name: String,
+    pub description: Option<String>,
+    pub default: Option<String>,
+    pub required: bool,
+    /// Values a `choice` input can take, or `true` and `false` for a `boolean` one
+    pub options: Vec<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ActionInfo {
     pub repository: String,
@@ -190,3 +396,108 @@ fn normalize_triggers(on_value: &serde_yaml::Value) -> Result<Vec<String>, Strin
 
     Ok(triggers)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_dispatch_inputs_in_order() {
+        let workflow: WorkflowDefinition = serde_yaml::from_str(
+            r#"
+name: Deploy
+on:
+  workflow_dispatch:
+    inputs:
+      environment:
+        description: Where to deploy
+        type: choice
+        options: [staging, production]
+        default: staging
+        required: true
+      dry-run:
+        type: boolean
+        default: false
+      version:
+        description: Version to deploy
+jobs: {}
+"#,
+        )
+        .unwrap();
+
+        let inputs = workflow.dispatch_inputs();
+        assert_eq!(
+            inputs
+                .iter()
+                .map(|input| input.name.as_str())
+                .collect::<Vec<_>>(),
+            ["environment", "dry-run", "version"]
+        );
+        assert_eq!(inputs[0].options, ["staging", "production"]);
+        assert_eq!(inputs[0].default.as_deref(), Some("staging"));
+        assert!(inputs[0].required);
+        assert_eq!(inputs[1].options, ["true", "false"]);
+        assert_eq!(inputs[1].default.as_deref(), Some("false"));
+        assert_eq!(inputs[2].description.as_deref(), Some("Version to deploy"));
+        assert!(inputs[2].options.is_empty() && !inputs[2].required);
+    }
+
+    #[test]
+    fn reads_schedules() {
+        let workflow: WorkflowDefinition = serde_yaml::from_str(
+            r#"
+name: Nightly
+on:
+  schedule:
+    - cron: "0 3 * * *"
+    - cron: "30 12 * * MON"
+  workflow_dispatch:
+jobs: {}
+"#,
+        )
+        .unwrap();
+        assert_eq!(workflow.schedules(), ["0 3 * * *", "30 12 * * MON"]);
+
+        let workflow: WorkflowDefinition = serde_yaml::from_str(
+            "name: CI
+on: push
+jobs: {}
+",
+        )
+        .unwrap();
+        assert!(workflow.schedules().is_empty());
+    }
+
+    #[test]
+    fn reads_job_environments() {
+        let workflow: WorkflowDefinition = serde_yaml::from_str(
+            r#"
+name: Deploy
+on: push
+jobs:
+  staging:
+    runs-on: ubuntu-latest
+    environment: staging
+    steps: []
+  production:
+    runs-on: ubuntu-latest
+    environment:
+      name: production
+      url: https://example.com
+    steps: []
+  test:
+    runs-on: ubuntu-latest
+    steps: []
+"#,
+        )
+        .unwrap();
+
+        let staging = workflow.jobs["staging"].environment.as_ref().unwrap();
+        assert_eq!(staging.name, "staging");
+        assert!(staging.url.is_none());
+        let production = workflow.jobs["production"].environment.as_ref().unwrap();
+        assert_eq!(production.name, "production");
+        assert_eq!(production.url.as_deref(), Some("https://example.com"));
+        assert!(workflow.jobs["test"].environment.is_none());
+    }
+}
@@ -0,0 +1,340 @@
+//! YAML errors located in the file, with the lines around them, and parsing of
+//! broken files section by section: a file that doesn't parse as a whole still
+//! gives the top-level sections, and the entries of broken sections, that do, so
+//! the rest of it can be checked.
+
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use serde_yaml::{Mapping, Value};
+use std::fmt;
+use std::ops::Range;
+
+lazy_static! {
+    static ref POSITION: Regex = Regex::new(r"at line (\d+)(?: column (\d+))?").unwrap();
+    static ref QUOTED: Regex = Regex::new(r"'([^']+)'").unwrap();
+}
+
+/// A YAML error and where in the file it is
+#[derive(Debug, Clone, PartialEq)]
+pub struct YamlError {
+    /// The parser's message, its positions those in the file
+    pub message: String,
+    /// 1-based, as in the message
+    pub line: usize,
+    pub column: usize,
+    /// The error's line and the one before it, with a caret under the column
+    pub snippet: String,
+}
+
+impl YamlError {
+    /// The error `error` of parsing `content`
+    pub fn new(content: &str, error: &serde_yaml::Error) -> Self {
+        Self::at(content, error, 0..content.lines().count())
+    }
+
+    /// The error `error` of parsing the lines `part` of `content`. Errors at the end
+    /// of the part are put on its last line.
+    fn at(content: &str, error: &serde_yaml::Error, part: Range<usize>) -> Self {
+        let lines: Vec<&str> = content.lines().collect();
+        let last = part.end.max(part.start + 1);
+        // The line and column in `content` of a position in the part
+        let place = |line: usize, column: usize| {
+            if line + part.start > last {
+                let end = lines.get(last - 1).map_or(0, |line| line.chars().count());
+                (last, end + 1)
+            } else {
+                (line + part.start, column)
+            }
+        };
+        let message = POSITION
+            .replace_all(&error.to_string(), |captures: &Captures| {
+                let (line, column) = place(
+                    captures[1].parse().unwrap_or(1),
+                    captures
+                        .get(2)
+                        .map_or(1, |column| column.as_str().parse().unwrap_or(1)),
+                );
+                format!("at line {} column {}", line, column)
+            })
+            .into_owned();
+        let (line, column) = match error.location() {
+            Some(location) => place(location.line(), location.column()),
+            None => (part.start + 1, 1),
+        };
+        YamlError {
+            message,
+            line,
+            column,
+            snippet: snippet(content, line, column),
+        }
+    }
+}
+
+impl fmt::Display for YamlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if !self.snippet.is_empty() {
+            write!(f, "\n{}", self.snippet)?;
+        }
+        Ok(())
+    }
+}
+
+/// The line and column, 1-based, a message puts what it's about at, as in
+/// `at line 3 column 5` of YAML errors; column 1 when it gives only the line
+pub fn message_position(message: &str) -> Option<(usize, usize)> {
+    let captures = POSITION.captures(message)?;
+    let line = captures[1].parse().ok()?;
+    let column = captures
+        .get(2)
+        .and_then(|column| column.as_str().parse().ok())
+        .unwrap_or(1);
+    Some((line, column))
+}
+
+/// The names a message quotes, like `build` of "job 'build'"
+pub fn quoted_names(message: &str) -> impl Iterator<Item = &str> {
+    QUOTED
+        .captures_iter(message)
+        .filter_map(|captures| captures.get(1))
+        .map(|name| name.as_str())
+}
+
+/// Line `line` of `content` and the one before it, numbered, with a caret under
+/// `column`
+fn snippet(content: &str, line: usize, column: usize) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    if line == 0 || line > lines.len() {
+        return String::new();
+    }
+    let width = line.to_string().len();
+    let mut snippet = String::new();
+    for number in line.saturating_sub(1).max(1)..=line {
+        snippet.push_str(&format!(
+            "{:>width$} | {}\n",
+            number,
+            lines[number - 1],
+            width = width
+        ));
+    }
+    // Tabs keep their width so the caret lines up
+    let indent: String = lines[line - 1]
+        .chars()
+        .take(column.saturating_sub(1))
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    snippet.push_str(&format!("{:>width$} | {}^", "", indent, width = width));
+    snippet
+}
+
+/// What parsing a file that may be broken gave
+#[derive(Debug, Default)]
+pub struct Recovered {
+    /// The file, or the parts of it that parse
+    pub value: Value,
+    /// Errors of the parts that don't, none when the file parses
+    pub errors: Vec<YamlError>,
+    /// Paths of the parts left out for not parsing, like `jobs.build`
+    pub unparsed: Vec<String>,
+}
+
+/// Parse `content`, or as much of it as parses: each top-level section on its own,
+/// and of a section that doesn't parse, each of its entries
+pub fn parse_recovering(content: &str) -> Recovered {
+    let error = match serde_yaml::from_str::<Value>(content) {
+        Ok(value) => {
+            return Recovered {
+                value,
+                ..Default::default()
+            }
+        }
+        Err(e) => YamlError::new(content, &e),
+    };
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut recovered = Recovered {
+        value: Value::Mapping(Mapping::new()),
+        ..Default::default()
+    };
+    for (start, end) in sections(&lines, 0..lines.len(), 0) {
+        recover_section(&lines, start, end, &mut recovered);
+    }
+    // The sections parse on their own but not together, e.g. for a key given twice
+    if recovered.errors.is_empty() {
+        recovered.errors.push(error);
+    }
+    recovered
+}
+
+fn recover_section(lines: &[&str], start: usize, end: usize, recovered: &mut Recovered) {
+    let text = lines[start..end].join("\n");
+    let error = match serde_yaml::from_str::<Value>(&text) {
+        Ok(Value::Mapping(mapping)) => {
+            merge(&mut recovered.value, mapping);
+            return;
+        }
+        Ok(_) => return,
+        Err(e) => YamlError::at(&lines.join("\n"), &e, start..end),
+    };
+
+    // A `key:` whose entries are on the lines under it keeps those that parse
+    let key = block_key(lines[start]);
+    let entries = match &key {
+        Some(_) => sections(lines, start + 1..end, 1),
+        None => Vec::new(),
+    };
+    let (Some(key), false) = (key, entries.is_empty()) else {
+        recovered.errors.push(error);
+        recovered.unparsed.push(key_of(lines[start]));
+        return;
+    };
+    let mut kept = Mapping::new();
+    for (entry_start, entry_end) in entries {
+        let text = lines[entry_start..entry_end].join("\n");
+        match serde_yaml::from_str::<Value>(&text) {
+            Ok(Value::Mapping(mapping)) => kept.extend(mapping),
+            Ok(_) => {}
+            Err(e) => {
+                recovered
+                    .errors
+                    .push(YamlError::at(&lines.join("\n"), &e, entry_start..entry_end));
+                recovered
+                    .unparsed
+                    .push(format!("{}.{}", key, key_of(lines[entry_start])));
+            }
+        }
+    }
+    merge(
+        &mut recovered.value,
+        Mapping::from_iter([(Value::String(key), Value::Mapping(kept))]),
+    );
+}
+
+fn merge(value: &mut Value, mapping: Mapping) {
+    if let Value::Mapping(value) = value {
+        value.extend(mapping);
+    }
+}
+
+/// The line ranges of the sections of `range`: each starts at a line of content
+/// indented like the first one (at all for `min_indent` 1), and takes the lines
+/// under it
+fn sections(lines: &[&str], range: Range<usize>, min_indent: usize) -> Vec<(usize, usize)> {
+    let indent_of = |line: &str| line.len() - line.trim_start().len();
+    let is_content = |line: &str| {
+        let trimmed = line.trim();
+        !trimmed.is_empty() && !trimmed.starts_with('#') && trimmed != "---" && trimmed != "..."
+    };
+    let Some(indent) = range
+        .clone()
+        .map(|idx| lines[idx])
+        .find(|line| is_content(line))
+        .map(indent_of)
+        .filter(|indent| *indent >= min_indent)
+    else {
+        return Vec::new();
+    };
+    let mut starts: Vec<usize> = range
+        .clone()
+        .filter(|&idx| is_content(lines[idx]) && indent_of(lines[idx]) <= indent)
+        .collect();
+    // A line indented less than the sections ends them
+    if let Some(outside) = starts
+        .iter()
+        .position(|&idx| indent_of(lines[idx]) < indent)
+    {
+        starts.truncate(outside);
+    }
+    let mut sections = Vec::new();
+    for (i, &start) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).copied().unwrap_or(range.end);
+        sections.push((start, end));
+    }
+    sections
+}
+
+/// The key of a line opening a block, like `jobs:`
+fn block_key(line: &str) -> Option<String> {
+    let key = line.trim_end().strip_suffix(':')?;
+    let key = key.trim_matches(|c| c == '"' || c == '\'');
+    (!key.is_empty() && !key.starts_with(char::is_whitespace) && !key.contains(": "))
+        .then(|| key.to_string())
+}
+
+/// The key a line starts, or the line itself when it starts none
+fn key_of(line: &str) -> String {
+    let line = line.trim();
+    let key = line.split_once(':').map_or(line, |(key, _)| key);
+    key.trim_matches(|c| c == '"' || c == '\'').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_point_at_their_line_and_column() {
+        let content = "on: push\njobs:\n  build:\n    runs-on: [ubuntu\n";
+        let error = serde_yaml::from_str::<Value>(content).unwrap_err();
+        let error = YamlError::new(content, &error);
+        assert!(error.message.contains(&format!("at line {}", error.line)));
+        let caret = error.snippet.lines().last().unwrap();
+        assert_eq!(
+            caret.find('^').unwrap() - caret.find('|').unwrap() - 2,
+            error.column - 1
+        );
+    }
+
+    #[test]
+    fn messages_give_their_position_and_names() {
+        assert_eq!(
+            message_position("did not find expected node content at line 4 column 14"),
+            Some((4, 14))
+        );
+        assert_eq!(
+            message_position("Duplicate stage name 'Build' at line 9 (first defined at line 3)"),
+            Some((9, 1))
+        );
+        assert_eq!(message_position("Job 'build' has no steps"), None);
+        assert_eq!(
+            quoted_names("Job 'deploy' needs 'build', which doesn't exist").collect::<Vec<_>>(),
+            vec!["deploy", "build"]
+        );
+    }
+
+    #[test]
+    fn sections_that_parse_are_kept() {
+        let content = r#"name: CI
+on:
+  push:
+    branches: [main
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - run: make
+  test:
+    runs-on: ubuntu-latest
+    steps:
+      - run: "make test
+  lint:
+    runs-on: ubuntu-latest
+"#;
+        let recovered = parse_recovering(content);
+        assert_eq!(recovered.value["name"], Value::from("CI"));
+        assert!(recovered.value["jobs"]["build"].is_mapping());
+        assert!(recovered.value["jobs"]["lint"].is_mapping());
+        assert!(recovered.value["jobs"].get("test").is_none());
+        assert_eq!(recovered.unparsed, vec!["on.push", "jobs.test"]);
+        assert_eq!(recovered.errors.len(), 2);
+        assert!(recovered.errors[1].line >= 13, "{}", recovered.errors[1]);
+        assert!(recovered.errors[1].snippet.contains("make test"));
+    }
+
+    #[test]
+    fn files_that_parse_have_no_errors() {
+        let recovered = parse_recovering("on: push\njobs: {}\n");
+        assert!(recovered.errors.is_empty() && recovered.unparsed.is_empty());
+        assert!(recovered.value["jobs"].is_mapping());
+    }
+}
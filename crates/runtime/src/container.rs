@@ -1,4 +1,5 @@
 use async_trait::async_trait;
+use std::collections::BTreeMap;
 use std::path::Path;
 
 #[async_trait]
@@ -14,7 +15,12 @@ pub trait ContainerRuntime {
 
     async fn pull_image(&self, image: &str) -> Result<(), ContainerError>;
 
-    async fn build_image(&self, dockerfile: &Path, tag: &str) -> Result<(), ContainerError>;
+    async fn build_image(
+        &self,
+        dockerfile: &Path,
+        tag: &str,
+        options: &BuildOptions,
+    ) -> Result<(), ContainerError>;
 
     async fn prepare_language_environment(
         &self,
@@ -24,6 +30,40 @@ pub trait ContainerRuntime {
     ) -> Result<String, ContainerError>;
 }
 
+/// What an image is built with besides its Dockerfile, whose directory is the
+/// build context
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildOptions {
+    /// `ARG` values, as `--build-arg NAME=VALUE`
+    pub build_args: BTreeMap<String, String>,
+    /// Stage of a multi-stage Dockerfile to build up to
+    pub target: Option<String>,
+    /// Labels set on the image
+    pub labels: BTreeMap<String, String>,
+}
+
+/// Network mode for job containers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NetworkMode {
+    /// The runtime's default bridge network
+    #[default]
+    Bridge,
+    /// No networking at all
+    None,
+    /// Share the host's network stack
+    Host,
+}
+
+impl NetworkMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NetworkMode::Bridge => "bridge",
+            NetworkMode::None => "none",
+            NetworkMode::Host => "host",
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ContainerOutput {
     pub stdout: String,
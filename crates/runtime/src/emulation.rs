@@ -1,11 +1,12 @@
-use crate::container::{ContainerError, ContainerOutput, ContainerRuntime};
+use crate::container::{BuildOptions, ContainerError, ContainerOutput, ContainerRuntime};
 use async_trait::async_trait;
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
 use std::fs;
+use std::future::Future;
 use std::path::{Path, PathBuf};
-use std::process::Command;
-use std::sync::Mutex;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
 use tempfile::TempDir;
 use which;
 use wrkflw_logging;
@@ -16,6 +17,11 @@ use ignore::{gitignore::GitignoreBuilder, Match};
 static EMULATION_WORKSPACES: Lazy<Mutex<Vec<PathBuf>>> = Lazy::new(|| Mutex::new(Vec::new()));
 static EMULATION_PROCESSES: Lazy<Mutex<Vec<u32>>> = Lazy::new(|| Mutex::new(Vec::new()));
 
+tokio::task_local! {
+    // The processes of the run going on, apart from those of other runs
+    static RUN_PROCESSES: Arc<Mutex<Vec<u32>>>;
+}
+
 pub struct EmulationRuntime {
     #[allow(dead_code)]
     workspace: TempDir,
@@ -154,14 +160,8 @@ impl ContainerRuntime for EmulationRuntime {
         working_dir: &Path,
         _volumes: &[(&Path, &Path)],
     ) -> Result<ContainerOutput, ContainerError> {
-        // Build command string
-        let mut command_str = String::new();
-        for part in command {
-            if !command_str.is_empty() {
-                command_str.push(' ');
-            }
-            command_str.push_str(part);
-        }
+        // Build command string, quoting arguments so `sh -c` sees the same argv
+        let command_str = crate::shell::join(command);
 
         // Log more detailed debugging information
         wrkflw_logging::info(&format!("Executing command in container: {}", command_str));
@@ -198,6 +198,21 @@ impl ContainerRuntime for EmulationRuntime {
 
             // If found, use that as the working directory
             if let Some(path) = workspace_path {
+                // Subdirectories of the container workspace (`working-directory:`)
+                // map onto the same subdirectory of the real one
+                let path = match working_dir.strip_prefix("/github/workspace") {
+                    Ok(relative) if !relative.as_os_str().is_empty() => {
+                        let subdir = path.join(relative);
+                        if !subdir.is_dir() {
+                            return Err(ContainerError::ContainerExecution(format!(
+                                "Working directory does not exist: {}",
+                                subdir.display()
+                            )));
+                        }
+                        subdir
+                    }
+                    _ => path,
+                };
                 if path.exists() {
                     wrkflw_logging::info(&format!(
                         "Using environment-defined workspace: {}",
@@ -260,7 +275,7 @@ impl ContainerRuntime for EmulationRuntime {
                 cmd.env(key, value);
             }
 
-            match cmd.output() {
+            match tracked_output(&mut cmd) {
                 Ok(output_result) => {
                     let exit_code = output_result.status.code().unwrap_or(-1);
                     let output = String::from_utf8_lossy(&output_result.stdout).to_string();
@@ -346,7 +361,7 @@ impl ContainerRuntime for EmulationRuntime {
                 current_dir.display()
             ));
 
-            match cmd.output() {
+            match tracked_output(&mut cmd) {
                 Ok(output_result) => {
                     let exit_code = output_result.status.code().unwrap_or(-1);
                     let output = String::from_utf8_lossy(&output_result.stdout).to_string();
@@ -391,9 +406,20 @@ impl ContainerRuntime for EmulationRuntime {
         }
 
         // For other commands, use a shell as fallback
-        let mut cmd = Command::new("sh");
-        cmd.arg("-c");
-        cmd.arg(&command_str);
+        #[cfg(not(windows))]
+        let mut cmd = {
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c");
+            cmd.arg(&command_str);
+            cmd
+        };
+        // Windows has no `sh`; the argv already names the step's shell (pwsh, cmd, ...)
+        #[cfg(windows)]
+        let mut cmd = {
+            let mut cmd = Command::new(command[0]);
+            cmd.args(&command[1..]);
+            cmd
+        };
         cmd.current_dir(&actual_working_dir);
 
         // Add environment variables
@@ -401,7 +427,7 @@ impl ContainerRuntime for EmulationRuntime {
             cmd.env(key, value);
         }
 
-        match cmd.output() {
+        match tracked_output(&mut cmd) {
             Ok(output_result) => {
                 let exit_code = output_result.status.code().unwrap_or(-1);
                 let output = String::from_utf8_lossy(&output_result.stdout).to_string();
@@ -449,7 +475,12 @@ impl ContainerRuntime for EmulationRuntime {
         Ok(())
     }
 
-    async fn build_image(&self, dockerfile: &Path, tag: &str) -> Result<(), ContainerError> {
+    async fn build_image(
+        &self,
+        dockerfile: &Path,
+        tag: &str,
+        _options: &BuildOptions,
+    ) -> Result<(), ContainerError> {
         wrkflw_logging::info(&format!(
             "🔄 Emulation: Pretending to build image {} from {}",
             tag,
@@ -766,7 +797,7 @@ pub async fn cleanup_resources() {
 }
 
 // Clean up any tracked processes
-async fn cleanup_processes() {
+pub async fn cleanup_processes() {
     let processes_to_cleanup = {
         if let Ok(processes) = EMULATION_PROCESSES.lock() {
             processes.clone()
@@ -774,15 +805,22 @@ async fn cleanup_processes() {
             vec![]
         }
     };
+    kill_processes(processes_to_cleanup).await;
+}
 
-    for pid in processes_to_cleanup {
+// Kill the given processes, like those of a single run, with what they started
+pub async fn kill_processes(processes: Vec<u32>) {
+    for pid in processes {
         wrkflw_logging::info(&format!("Cleaning up emulated process: {}", pid));
 
         #[cfg(unix)]
         {
-            // On Unix-like systems, use kill command
+            // On Unix-like systems, use kill command, on the process group too so
+            // that what the command started goes with it
             let _ = Command::new("kill")
                 .arg("-TERM")
+                .arg("--")
+                .arg(format!("-{}", pid))
                 .arg(pid.to_string())
                 .output();
         }
@@ -798,9 +836,7 @@ async fn cleanup_processes() {
         }
 
         // Remove from tracking
-        if let Ok(mut processes) = EMULATION_PROCESSES.lock() {
-            processes.retain(|p| *p != pid);
-        }
+        untrack_process(pid);
     }
 }
 
@@ -835,20 +871,68 @@ async fn cleanup_workspaces() {
     }
 }
 
-// Add process to tracking
-#[allow(dead_code)]
+// Run a command to completion like `Command::output`, tracking it meanwhile so
+// that cleaning up kills it
+pub(crate) fn tracked_output(cmd: &mut Command) -> std::io::Result<std::process::Output> {
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(cmd, 0);
+    let mut child = cmd.spawn()?;
+    let pid = child.id();
+    track_process(pid);
+    // Read both pipes as the command writes, keeping only what the output limit allows
+    let capture = |pipe: Option<Box<dyn std::io::Read + Send>>| {
+        std::thread::spawn(move || {
+            let mut capture = crate::output::OutputCapture::new();
+            if let Some(pipe) = pipe {
+                if let Err(e) = capture.read_from(pipe) {
+                    wrkflw_logging::debug(&format!("Failed to read command output: {}", e));
+                }
+            }
+            capture.into_bytes()
+        })
+    };
+    let stdout = capture(child.stdout.take().map(|p| Box::new(p) as _));
+    let stderr = capture(child.stderr.take().map(|p| Box::new(p) as _));
+    let status = child.wait();
+    untrack_process(pid);
+    Ok(std::process::Output {
+        status: status?,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+// Run `run`, keeping the processes it starts in `processes` as well, so that they
+// can be killed apart from those of other runs
+pub async fn tracking_processes<F: Future>(processes: Arc<Mutex<Vec<u32>>>, run: F) -> F::Output {
+    RUN_PROCESSES.scope(processes, run).await
+}
+
+// Add process to tracking, also as one of the run going on
 pub fn track_process(pid: u32) {
     if let Ok(mut processes) = EMULATION_PROCESSES.lock() {
         processes.push(pid);
     }
+    let _ = RUN_PROCESSES.try_with(|run| {
+        if let Ok(mut processes) = run.lock() {
+            processes.push(pid);
+        }
+    });
 }
 
 // Remove process from tracking
-#[allow(dead_code)]
 pub fn untrack_process(pid: u32) {
     if let Ok(mut processes) = EMULATION_PROCESSES.lock() {
         processes.retain(|p| *p != pid);
     }
+    let _ = RUN_PROCESSES.try_with(|run| {
+        if let Ok(mut processes) = run.lock() {
+            processes.retain(|p| *p != pid);
+        }
+    });
 }
 
 // Track additional workspace paths if needed
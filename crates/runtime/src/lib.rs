@@ -2,5 +2,13 @@
 
 pub mod container;
 pub mod emulation;
+mod limits;
+pub mod output;
+pub mod policy;
 pub mod sandbox;
 pub mod secure_emulation;
+pub mod shell;
+pub mod timeouts;
+pub mod toolcache;
+#[cfg(feature = "wasm")]
+pub mod wasm;
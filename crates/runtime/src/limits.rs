@@ -0,0 +1,443 @@
+//! Memory, CPU and process limits for sandboxed commands.
+//!
+//! On Linux with a delegated cgroup v2 hierarchy each command runs in its own child
+//! cgroup with `memory.max`, `cpu.max` and `pids.max` set. Otherwise the limits fall
+//! back to rlimits on Unix: memory to `RLIMIT_DATA`, the CPU share to an
+//! `RLIMIT_CPU` budget of CPU seconds over the command's time limit, and processes
+//! to `RLIMIT_NPROC` on top of those the user already runs. Limits that can't be
+//! enforced are warned about once.
+
+use crate::sandbox::SandboxConfig;
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Whether the limits the fallback can't enforce were warned about already
+static WARNED_UNENFORCED: AtomicBool = AtomicBool::new(false);
+
+/// Limits applied to a single sandboxed command
+pub(crate) struct ResourceLimiter {
+    memory_bytes: u64,
+    max_processes: u32,
+    cgroup: Option<Cgroup>,
+    rlimits: Rlimits,
+}
+
+/// The rlimits standing in for a cgroup; `None` leaves a resource unlimited
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Rlimits {
+    data_bytes: Option<u64>,
+    cpu_seconds: Option<u64>,
+    processes: Option<u64>,
+}
+
+impl ResourceLimiter {
+    pub(crate) fn new(config: &SandboxConfig) -> Self {
+        let memory_bytes = config.max_memory_mb.saturating_mul(1024 * 1024);
+        let cgroup = Cgroup::create(memory_bytes, config.max_cpu_percent, config.max_processes);
+        let rlimits = if cgroup.is_some() {
+            Rlimits::default()
+        } else {
+            let (rlimits, unenforced) = fallback_rlimits(config, user_processes());
+            if !unenforced.is_empty() && !WARNED_UNENFORCED.swap(true, Ordering::Relaxed) {
+                wrkflw_logging::warning(&format!(
+                    "cgroup v2 is unavailable; the sandbox's {} limit is not enforced",
+                    unenforced.join(" and ")
+                ));
+            }
+            rlimits
+        };
+
+        Self {
+            memory_bytes,
+            max_processes: config.max_processes,
+            cgroup,
+            rlimits,
+        }
+    }
+
+    /// Arrange for the spawned process to run under the limits
+    pub(crate) fn apply(&self, cmd: &mut Command) {
+        #[cfg(unix)]
+        {
+            use std::os::fd::AsRawFd;
+            use std::os::unix::process::CommandExt;
+
+            let procs_fd = self.cgroup.as_ref().map(|cgroup| cgroup.procs.as_raw_fd());
+            let rlimits = self.rlimits;
+
+            // Only async-signal-safe calls are allowed between fork and exec
+            unsafe {
+                cmd.pre_exec(move || {
+                    if let Some(fd) = procs_fd {
+                        // Writing "0" moves the calling process into the cgroup
+                        if libc::write(fd, b"0".as_ptr().cast(), 1) < 0 {
+                            return Err(std::io::Error::last_os_error());
+                        }
+                        return Ok(());
+                    }
+                    if let Some(bytes) = rlimits.data_bytes {
+                        set_rlimit(libc::RLIMIT_DATA, bytes, bytes)?;
+                    }
+                    if let Some(seconds) = rlimits.cpu_seconds {
+                        // SIGXCPU at the budget, SIGKILL a second later if ignored
+                        set_rlimit(libc::RLIMIT_CPU, seconds, seconds + 1)?;
+                    }
+                    if let Some(processes) = rlimits.processes {
+                        set_rlimit(libc::RLIMIT_NPROC, processes, processes)?;
+                    }
+                    Ok(())
+                });
+            }
+        }
+    }
+
+    /// The resource that was exhausted, if the command hit one of the limits
+    pub(crate) fn exceeded(&self, status: &ExitStatus, stderr: &str) -> Option<String> {
+        if let Some(cgroup) = &self.cgroup {
+            if cgroup.event_count("memory.events", "oom_kill") > 0 {
+                return Some(format!("memory (max {} MB)", self.memory_bytes >> 20));
+            }
+            if cgroup.event_count("pids.events", "max") > 0 {
+                return Some(format!("processes (max {})", self.max_processes));
+            }
+            return None;
+        }
+
+        if status.success() {
+            return None;
+        }
+        if let Some(seconds) = self.rlimits.cpu_seconds {
+            #[cfg(unix)]
+            {
+                use std::os::unix::process::ExitStatusExt;
+                if status.signal() == Some(libc::SIGXCPU) {
+                    return Some(format!("CPU time (max {}s)", seconds));
+                }
+            }
+            #[cfg(not(unix))]
+            let _ = seconds;
+        }
+        // Allocation and fork failures under the rlimits surface as errors from the
+        // program itself
+        if self.rlimits.data_bytes.is_some() && mentions_any(stderr, OOM_MESSAGES) {
+            return Some(format!("memory (max {} MB)", self.memory_bytes >> 20));
+        }
+        if self.rlimits.processes.is_some() && mentions_any(stderr, FORK_MESSAGES) {
+            return Some(format!("processes (max {})", self.max_processes));
+        }
+        None
+    }
+}
+
+const OOM_MESSAGES: &[&str] = &[
+    "Cannot allocate memory",
+    "out of memory",
+    "Out of memory",
+    "MemoryError",
+    "memory allocation of",
+];
+
+const FORK_MESSAGES: &[&str] = &[
+    "Resource temporarily unavailable",
+    "fork: retry",
+    "Cannot fork",
+    "can't fork",
+];
+
+fn mentions_any(stderr: &str, messages: &[&str]) -> bool {
+    messages.iter().any(|msg| stderr.contains(msg))
+}
+
+/// The rlimits enforcing `config` without a cgroup, and the limits they can't
+/// enforce. `user_processes` is how many processes the user runs already, since
+/// `RLIMIT_NPROC` counts them all; unknown, processes aren't limited.
+fn fallback_rlimits(
+    config: &SandboxConfig,
+    user_processes: Option<u64>,
+) -> (Rlimits, Vec<&'static str>) {
+    let mut rlimits = Rlimits::default();
+    let mut unenforced = Vec::new();
+    if !cfg!(unix) {
+        for (limit, set) in [
+            ("memory", config.max_memory_mb > 0),
+            ("CPU", config.max_cpu_percent > 0),
+            ("process", config.max_processes > 0),
+        ] {
+            if set {
+                unenforced.push(limit);
+            }
+        }
+        return (rlimits, unenforced);
+    }
+
+    if config.max_memory_mb > 0 {
+        rlimits.data_bytes = Some(config.max_memory_mb.saturating_mul(1024 * 1024));
+    }
+    // A share of one CPU over the whole time limit; a command using more than that
+    // for part of its run still fits, as long as its total stays within the budget
+    if config.max_cpu_percent > 0 {
+        let budget = config
+            .max_execution_time
+            .as_secs()
+            .saturating_mul(config.max_cpu_percent)
+            .div_ceil(100);
+        rlimits.cpu_seconds = Some(budget.max(1));
+    }
+    if config.max_processes > 0 {
+        match user_processes {
+            Some(running) => {
+                rlimits.processes = Some(running + u64::from(config.max_processes));
+            }
+            None => unenforced.push("process"),
+        }
+    }
+    (rlimits, unenforced)
+}
+
+/// How many processes the user runs, which `RLIMIT_NPROC` counts; `None` where
+/// that's unknown, and for root, which the limit doesn't apply to
+#[cfg(target_os = "linux")]
+fn user_processes() -> Option<u64> {
+    let uid = unsafe { libc::getuid() };
+    if uid == 0 {
+        return None;
+    }
+    let uid = uid.to_string();
+    let entries = std::fs::read_dir("/proc").ok()?;
+    let count = entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().parse::<u32>().is_ok())
+        .filter_map(|entry| std::fs::read_to_string(entry.path().join("status")).ok())
+        .filter(|status| {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix("Uid:"))
+                .and_then(|ids| ids.split_whitespace().next())
+                == Some(uid.as_str())
+        })
+        .count();
+    Some(count as u64)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn user_processes() -> Option<u64> {
+    None
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type RlimitResource = libc::__rlimit_resource_t;
+#[cfg(all(unix, not(all(target_os = "linux", target_env = "gnu"))))]
+type RlimitResource = libc::c_int;
+
+#[cfg(unix)]
+fn set_rlimit(resource: RlimitResource, soft: u64, hard: u64) -> std::io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: soft as libc::rlim_t,
+        rlim_max: hard as libc::rlim_t,
+    };
+    if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// A child cgroup v2 group, removed on drop
+struct Cgroup {
+    path: PathBuf,
+    #[cfg(unix)]
+    procs: std::fs::File,
+}
+
+impl Cgroup {
+    #[cfg(target_os = "linux")]
+    fn create(memory_bytes: u64, cpu_percent: u64, max_processes: u32) -> Option<Self> {
+        let root = std::path::Path::new("/sys/fs/cgroup");
+        if !root.join("cgroup.controllers").exists() {
+            return None;
+        }
+
+        // Our own cgroup, from the "0::/path" line
+        let own = std::fs::read_to_string("/proc/self/cgroup").ok()?;
+        let relative = own.lines().find_map(|line| line.strip_prefix("0::"))?;
+        let parent = root.join(relative.trim_start_matches('/'));
+
+        let enabled = std::fs::read_to_string(parent.join("cgroup.subtree_control")).ok()?;
+        for controller in ["memory", "cpu", "pids"] {
+            if !enabled.split_whitespace().any(|c| c == controller) {
+                std::fs::write(
+                    parent.join("cgroup.subtree_control"),
+                    format!("+{}", controller),
+                )
+                .ok()?;
+            }
+        }
+
+        let path = parent.join(format!("wrkflw-sandbox-{}", uuid_suffix()));
+        std::fs::create_dir(&path).ok()?;
+
+        let configure = || -> std::io::Result<std::fs::File> {
+            if memory_bytes > 0 {
+                std::fs::write(path.join("memory.max"), memory_bytes.to_string())?;
+                // Not every kernel has swap accounting
+                let _ = std::fs::write(path.join("memory.swap.max"), "0");
+            }
+            if cpu_percent > 0 {
+                std::fs::write(path.join("cpu.max"), cpu_max(cpu_percent))?;
+            }
+            if max_processes > 0 {
+                std::fs::write(path.join("pids.max"), max_processes.to_string())?;
+            }
+            std::fs::OpenOptions::new()
+                .write(true)
+                .open(path.join("cgroup.procs"))
+        };
+
+        match configure() {
+            Ok(procs) => Some(Self { path, procs }),
+            Err(e) => {
+                wrkflw_logging::debug(&format!("Failed to configure sandbox cgroup: {}", e));
+                let _ = std::fs::remove_dir(&path);
+                None
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn create(_memory_bytes: u64, _cpu_percent: u64, _max_processes: u32) -> Option<Self> {
+        None
+    }
+
+    fn event_count(&self, file: &str, key: &str) -> u64 {
+        std::fs::read_to_string(self.path.join(file))
+            .map(|events| event_count(&events, key))
+            .unwrap_or(0)
+    }
+}
+
+impl Drop for Cgroup {
+    fn drop(&mut self) {
+        // Fails while processes remain, e.g. after a timeout; nothing else to do then
+        let _ = std::fs::remove_dir(&self.path);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn uuid_suffix() -> String {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    format!("{}-{}", std::process::id(), nanos)
+}
+
+/// Count of `key` in a cgroup events file such as `memory.events`
+fn event_count(events: &str, key: &str) -> u64 {
+    events
+        .lines()
+        .find_map(|line| {
+            let (name, count) = line.split_once(' ')?;
+            (name == key).then(|| count.trim().parse().ok()).flatten()
+        })
+        .unwrap_or(0)
+}
+
+/// `cpu.max` for a share of a single CPU: the quota, then the period
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn cpu_max(cpu_percent: u64) -> String {
+    let period = 100_000;
+    format!("{} {}", cpu_percent * period / 100, period)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn config(memory_mb: u64, cpu_percent: u64, processes: u32) -> SandboxConfig {
+        SandboxConfig {
+            max_execution_time: Duration::from_secs(30),
+            max_memory_mb: memory_mb,
+            max_cpu_percent: cpu_percent,
+            max_processes: processes,
+            ..SandboxConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_event_count() {
+        let events = "low 0\nhigh 3\nmax 12\noom 1\noom_kill 1\n";
+        assert_eq!(event_count(events, "oom_kill"), 1);
+        assert_eq!(event_count(events, "max"), 12);
+        assert_eq!(event_count(events, "oom_group_kill"), 0);
+        assert_eq!(event_count("max garbage\n", "max"), 0);
+        assert_eq!(event_count("", "max"), 0);
+    }
+
+    #[test]
+    fn test_cpu_max() {
+        assert_eq!(cpu_max(80), "80000 100000");
+        assert_eq!(cpu_max(250), "250000 100000");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fallback_rlimits() {
+        let (rlimits, unenforced) = fallback_rlimits(&config(512, 80, 10), Some(40));
+        assert_eq!(
+            rlimits,
+            Rlimits {
+                data_bytes: Some(512 * 1024 * 1024),
+                // 80% of one CPU over the 30s time limit
+                cpu_seconds: Some(24),
+                processes: Some(50),
+            }
+        );
+        assert!(unenforced.is_empty());
+
+        // Not knowing the user's processes, e.g. as root, leaves them unlimited
+        let (rlimits, unenforced) = fallback_rlimits(&config(512, 1, 10), None);
+        assert_eq!(rlimits.processes, None);
+        assert_eq!(rlimits.cpu_seconds, Some(1));
+        assert_eq!(unenforced, vec!["process"]);
+
+        // Limits set to 0 are off
+        let (rlimits, unenforced) = fallback_rlimits(&config(0, 0, 0), None);
+        assert_eq!(rlimits, Rlimits::default());
+        assert!(unenforced.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exceeded_without_cgroup() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let (rlimits, _) = fallback_rlimits(&config(64, 50, 5), Some(0));
+        let limiter = ResourceLimiter {
+            memory_bytes: 64 * 1024 * 1024,
+            max_processes: 5,
+            cgroup: None,
+            rlimits,
+        };
+        let failed = ExitStatus::from_raw(1 << 8);
+
+        assert_eq!(
+            limiter.exceeded(&failed, "python: MemoryError"),
+            Some("memory (max 64 MB)".to_string())
+        );
+        assert_eq!(
+            limiter.exceeded(&failed, "sh: fork: retry: Resource temporarily unavailable"),
+            Some("processes (max 5)".to_string())
+        );
+        assert_eq!(
+            limiter.exceeded(&ExitStatus::from_raw(libc::SIGXCPU), ""),
+            Some("CPU time (max 15s)".to_string())
+        );
+        assert_eq!(limiter.exceeded(&failed, "error: file not found"), None);
+        // Successful commands never exceeded anything
+        assert_eq!(
+            limiter.exceeded(&ExitStatus::from_raw(0), "out of memory"),
+            None
+        );
+    }
+}
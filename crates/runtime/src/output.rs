@@ -0,0 +1,233 @@
+//! Bounded capture of what steps print.
+//!
+//! Each output stream of a step keeps at most the output limit in memory: its first
+//! and last halves. Once a stream outgrows the limit, all of it is also written to a
+//! file under the temp directory, and the part left out is replaced by a marker
+//! naming that file.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Bytes of each output stream of a step kept in memory unless set otherwise
+pub const DEFAULT_OUTPUT_LIMIT: usize = 10 * 1024 * 1024;
+
+/// Start of the line put in place of the output left out
+pub const TRUNCATION_MARKER: &str = "[wrkflw: output truncated,";
+
+static OUTPUT_LIMIT: AtomicUsize = AtomicUsize::new(DEFAULT_OUTPUT_LIMIT);
+
+/// Keep at most `limit` bytes of each output stream of a step in memory; 0 keeps all
+pub fn set_output_limit(limit: usize) {
+    OUTPUT_LIMIT.store(limit, Ordering::Relaxed);
+}
+
+pub fn output_limit() -> usize {
+    OUTPUT_LIMIT.load(Ordering::Relaxed)
+}
+
+/// Whether part of a captured output was left out
+pub fn is_truncated(output: &str) -> bool {
+    output.contains(TRUNCATION_MARKER)
+}
+
+/// The output of one stream, as much of it as the limit allows
+pub struct OutputCapture {
+    limit: usize,
+    head: Vec<u8>,
+    tail: VecDeque<u8>,
+    dropped: usize,
+    spill: Option<(File, PathBuf)>,
+    spill_failed: bool,
+}
+
+impl Default for OutputCapture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OutputCapture {
+    /// A capture bounded by the current output limit
+    pub fn new() -> Self {
+        Self::with_limit(output_limit())
+    }
+
+    pub fn with_limit(limit: usize) -> Self {
+        OutputCapture {
+            limit,
+            head: Vec::new(),
+            tail: VecDeque::new(),
+            dropped: 0,
+            spill: None,
+            spill_failed: false,
+        }
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) {
+        if self.limit == 0 {
+            self.head.extend_from_slice(bytes);
+            return;
+        }
+        let held = self.head.len() + self.tail.len();
+        if self.spill.is_none() && !self.spill_failed && held + bytes.len() > self.limit {
+            self.start_spill();
+        }
+        if let Some((file, path)) = &mut self.spill {
+            if let Err(e) = file.write_all(bytes) {
+                wrkflw_logging::warning(&format!(
+                    "Failed to write step output to {}: {}",
+                    path.display(),
+                    e
+                ));
+                self.spill = None;
+                self.spill_failed = true;
+            }
+        }
+
+        let head_room = (self.limit / 2).saturating_sub(self.head.len());
+        let (head, rest) = bytes.split_at(head_room.min(bytes.len()));
+        self.head.extend_from_slice(head);
+        self.tail.extend(rest);
+        let tail_limit = self.limit - self.limit / 2;
+        if self.tail.len() > tail_limit {
+            let excess = self.tail.len() - tail_limit;
+            self.tail.drain(..excess);
+            self.dropped += excess;
+        }
+    }
+
+    /// Read `reader` to its end into the capture
+    pub fn read_from(&mut self, mut reader: impl Read) -> std::io::Result<()> {
+        let mut buffer = [0u8; 8192];
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => return Ok(()),
+                Ok(n) => self.push(&buffer[..n]),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    // Everything so far is still held, so the file gets the whole output
+    fn start_spill(&mut self) {
+        let dir = std::env::temp_dir().join("wrkflw-output");
+        let spill = std::fs::create_dir_all(&dir).and_then(|_| {
+            let (mut file, path) = tempfile::Builder::new()
+                .prefix("step-")
+                .suffix(".log")
+                .tempfile_in(&dir)?
+                .keep()
+                .map_err(|e| e.error)?;
+            file.write_all(&self.head)?;
+            let (front, back) = self.tail.as_slices();
+            file.write_all(front)?;
+            file.write_all(back)?;
+            Ok((file, path))
+        });
+        match spill {
+            Ok(spill) => self.spill = Some(spill),
+            Err(e) => {
+                wrkflw_logging::warning(&format!(
+                    "Failed to save the full step output in {}: {}",
+                    dir.display(),
+                    e
+                ));
+                self.spill_failed = true;
+            }
+        }
+    }
+
+    /// The captured bytes, with a marker where output was left out
+    pub fn into_bytes(self) -> Vec<u8> {
+        let mut bytes = self.head;
+        if self.dropped > 0 {
+            let saved = match &self.spill {
+                Some((_, path)) => format!("full output in {}", path.display()),
+                None => "full output not saved".to_string(),
+            };
+            if !bytes.is_empty() && !bytes.ends_with(b"\n") {
+                bytes.push(b'\n');
+            }
+            bytes.extend_from_slice(
+                format!(
+                    "{} {} left out; {}]\n",
+                    TRUNCATION_MARKER,
+                    format_size(self.dropped),
+                    saved
+                )
+                .as_bytes(),
+            );
+        }
+        bytes.extend(self.tail);
+        bytes
+    }
+
+    pub fn into_string(self) -> String {
+        String::from_utf8_lossy(&self.into_bytes()).into_owned()
+    }
+}
+
+/// A byte count for people, like `1.5 MiB`
+pub fn format_size(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["bytes", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} bytes", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_within_limit_is_kept() {
+        let mut capture = OutputCapture::with_limit(16);
+        capture.push(b"hello ");
+        capture.push(b"world\n");
+        assert_eq!(capture.into_string(), "hello world\n");
+    }
+
+    #[test]
+    fn test_truncated_output_keeps_head_and_tail() {
+        let mut capture = OutputCapture::with_limit(8);
+        for chunk in [&b"abcd"[..], b"efgh", b"ijkl", b"mnop"] {
+            capture.push(chunk);
+        }
+        let path = capture.spill.as_ref().unwrap().1.clone();
+        let output = capture.into_string();
+        assert!(is_truncated(&output));
+        assert!(output.starts_with("abcd\n"));
+        assert!(output.ends_with("]\nmnop"));
+        assert!(output.contains("8 bytes left out"));
+        assert!(output.contains(&path.display().to_string()));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "abcdefghijklmnop");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_zero_limit_keeps_everything() {
+        let mut capture = OutputCapture::with_limit(0);
+        capture.read_from(&vec![b'x'; 100_000][..]).unwrap();
+        let output = capture.into_string();
+        assert_eq!(output.len(), 100_000);
+        assert!(!is_truncated(&output));
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 bytes");
+        assert_eq!(format_size(3 * 1024 * 1024 / 2), "1.5 MiB");
+    }
+}
@@ -0,0 +1,181 @@
+//! Sandbox policy files.
+//!
+//! A policy tunes a [`SandboxConfig`] preset without recompiling. Files ending in
+//! `.toml` are read as TOML, anything else as YAML:
+//!
+//! ```yaml
+//! base: workflow            # workflow (default), strict or default
+//! timeout_seconds: 900
+//! max_memory_mb: 4096
+//! allow_network: false
+//! allowed_commands: [make, curl]   # also removes them from the blocked list
+//! blocked_commands: [docker]
+//! allowed_read_paths: [/opt/toolchains]
+//! allowed_write_paths: [./target]
+//! env_passthrough: [PATH, "CARGO_*"]
+//! blocked_env_vars: ["AWS_*"]
+//! ```
+
+use crate::sandbox::{
+    create_strict_sandbox_config, create_workflow_sandbox_config, SandboxConfig, SandboxError,
+};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Preset a policy starts from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyBase {
+    /// Permissive preset used for CI workflows
+    #[default]
+    Workflow,
+    /// Minimal command set for untrusted code
+    Strict,
+    /// `SandboxConfig::default()`
+    Default,
+}
+
+/// Overrides applied on top of a base sandbox configuration
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SandboxPolicy {
+    pub base: PolicyBase,
+    pub timeout_seconds: Option<u64>,
+    pub max_memory_mb: Option<u64>,
+    pub max_cpu_percent: Option<u64>,
+    pub max_processes: Option<u32>,
+    pub allow_network: Option<bool>,
+    pub strict_mode: Option<bool>,
+    pub allowed_commands: Vec<String>,
+    pub blocked_commands: Vec<String>,
+    pub allowed_read_paths: Vec<PathBuf>,
+    pub allowed_write_paths: Vec<PathBuf>,
+    pub env_passthrough: Vec<String>,
+    pub blocked_env_vars: Vec<String>,
+}
+
+impl SandboxPolicy {
+    /// Parse a policy file, picking the format from its extension
+    pub fn from_file(path: &Path) -> Result<Self, SandboxError> {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| SandboxError::SandboxSetupError {
+                reason: format!("Failed to read sandbox policy {}: {}", path.display(), e),
+            })?;
+
+        let is_toml = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+        let parsed = if is_toml {
+            toml::from_str(&content).map_err(|e| e.to_string())
+        } else {
+            serde_yaml::from_str(&content).map_err(|e| e.to_string())
+        };
+
+        parsed.map_err(|e| SandboxError::SandboxSetupError {
+            reason: format!("Invalid sandbox policy {}: {}", path.display(), e),
+        })
+    }
+
+    /// Build the sandbox configuration this policy describes
+    pub fn into_config(self) -> SandboxConfig {
+        let mut config = match self.base {
+            PolicyBase::Workflow => create_workflow_sandbox_config(),
+            PolicyBase::Strict => create_strict_sandbox_config(),
+            PolicyBase::Default => SandboxConfig::default(),
+        };
+
+        if let Some(seconds) = self.timeout_seconds {
+            config.max_execution_time = Duration::from_secs(seconds);
+        }
+        if let Some(memory) = self.max_memory_mb {
+            config.max_memory_mb = memory;
+        }
+        if let Some(cpu) = self.max_cpu_percent {
+            config.max_cpu_percent = cpu;
+        }
+        if let Some(processes) = self.max_processes {
+            config.max_processes = processes;
+        }
+        if let Some(allow_network) = self.allow_network {
+            config.allow_network = allow_network;
+        }
+        if let Some(strict_mode) = self.strict_mode {
+            config.strict_mode = strict_mode;
+        }
+
+        // An explicit allow in the policy wins over the preset's block list
+        for command in self.allowed_commands {
+            config.blocked_commands.remove(&command);
+            config.allowed_commands.insert(command);
+        }
+        for command in self.blocked_commands {
+            config.allowed_commands.remove(&command);
+            config.blocked_commands.insert(command);
+        }
+
+        config.allowed_read_paths.extend(self.allowed_read_paths);
+        config.allowed_write_paths.extend(self.allowed_write_paths);
+        config.env_passthrough.extend(self.env_passthrough);
+        config.blocked_env_vars.extend(self.blocked_env_vars);
+
+        config
+    }
+}
+
+/// Load a policy file into a sandbox configuration
+pub fn load_sandbox_policy(path: &Path) -> Result<SandboxConfig, SandboxError> {
+    SandboxPolicy::from_file(path).map(SandboxPolicy::into_config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn write_policy(suffix: &str, content: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::Builder::new().suffix(suffix).tempfile().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_yaml_policy_overrides_base() {
+        let file = write_policy(
+            ".yml",
+            "base: strict\ntimeout_seconds: 10\nallowed_commands: [curl]\nblocked_commands: [cat]\nenv_passthrough: [PATH]\n",
+        );
+        let config = load_sandbox_policy(file.path()).unwrap();
+
+        assert_eq!(config.max_execution_time, Duration::from_secs(10));
+        assert!(config.strict_mode);
+        assert!(config.allowed_commands.contains("curl"));
+        assert!(!config.blocked_commands.contains("curl"));
+        assert!(config.blocked_commands.contains("cat"));
+        assert!(!config.allowed_commands.contains("cat"));
+        assert!(config.env_passthrough.contains("PATH"));
+    }
+
+    #[test]
+    fn test_toml_policy() {
+        let file = write_policy(
+            ".toml",
+            "allow_network = false\nmax_memory_mb = 256\nblocked_env_vars = [\"AWS_*\"]\n",
+        );
+        let config = load_sandbox_policy(file.path()).unwrap();
+
+        assert!(!config.allow_network);
+        assert_eq!(config.max_memory_mb, 256);
+        assert!(config.blocked_env_vars.contains("AWS_*"));
+        assert!(config.blocked_env_vars.contains("LD_PRELOAD"));
+    }
+
+    #[test]
+    fn test_unknown_field_is_rejected() {
+        let file = write_policy(".yml", "allow_netwrk: true\n");
+        assert!(matches!(
+            load_sandbox_policy(file.path()),
+            Err(SandboxError::SandboxSetupError { .. })
+        ));
+    }
+}
@@ -1,8 +1,11 @@
+use crate::limits::ResourceLimiter;
+use crate::shell;
 use regex::Regex;
 use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::sync::OnceLock;
 use std::time::Duration;
 use tempfile::TempDir;
 use wrkflw_logging;
@@ -30,6 +33,10 @@ pub struct SandboxConfig {
     pub max_processes: u32,
     /// Whether to enable strict mode (more restrictive)
     pub strict_mode: bool,
+    /// Environment variables never passed to commands (a trailing `*` matches a prefix)
+    pub blocked_env_vars: HashSet<String>,
+    /// Environment variables passed through even if blocked (a trailing `*` matches a prefix)
+    pub env_passthrough: HashSet<String>,
 }
 
 impl Default for SandboxConfig {
@@ -137,6 +144,20 @@ impl Default for SandboxConfig {
         blocked_commands.insert("halt".to_string());
         blocked_commands.insert("poweroff".to_string());
 
+        // Variables that change how programs are loaded or resolved
+        let blocked_env_vars = [
+            "LD_PRELOAD",
+            "LD_LIBRARY_PATH",
+            "DYLD_INSERT_LIBRARIES",
+            "DYLD_LIBRARY_PATH",
+            "PATH",
+            "HOME",
+            "SHELL",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
         Self {
             max_execution_time: Duration::from_secs(300), // 5 minutes
             max_memory_mb: 512,
@@ -148,6 +169,8 @@ impl Default for SandboxConfig {
             allow_network: false,
             max_processes: 10,
             strict_mode: true,
+            blocked_env_vars,
+            env_passthrough: HashSet::new(),
         }
     }
 }
@@ -167,6 +190,9 @@ pub enum SandboxError {
     #[error("Resource limit exceeded: {resource}")]
     ResourceLimitExceeded { resource: String },
 
+    #[error("Command could not be parsed: {reason}")]
+    UnparsableCommand { reason: String },
+
     #[error("Execution timeout after {seconds} seconds")]
     ExecutionTimeout { seconds: u64 },
 
@@ -218,7 +244,8 @@ impl Sandbox {
             });
         }
 
-        let command_str = command.join(" ");
+        // Quote the arguments so validation sees exactly what `sh -c` will run
+        let command_str = shell::join(command);
 
         // Step 1: Validate command
         self.validate_command(&command_str)?;
@@ -246,24 +273,39 @@ impl Sandbox {
             }
         }
 
-        // Split command by shell operators to validate each part
-        let command_parts = self.split_shell_command(command_str);
+        // Find every command the shell would run, including substitutions and wrappers
+        let commands = shell::parse_commands(command_str).map_err(|e| {
+            wrkflw_logging::warning(&format!("🚫 Could not parse command: {}", e));
+            SandboxError::UnparsableCommand {
+                reason: e.to_string(),
+            }
+        })?;
 
-        for part in command_parts {
-            let part = part.trim();
-            if part.is_empty() {
-                continue;
+        for command in commands {
+            if command.dynamic {
+                // The real name is only known at runtime, e.g. `$CMD args`
+                if self.config.strict_mode {
+                    wrkflw_logging::warning(&format!(
+                        "🚫 Dynamic command name not allowed (strict mode): {}",
+                        command.name
+                    ));
+                    return Err(SandboxError::BlockedCommand {
+                        command: format!("{} (dynamic)", command.name),
+                    });
+                }
+                wrkflw_logging::warning(&format!(
+                    "⚠️ Cannot verify dynamic command name: {}",
+                    command.name
+                ));
             }
 
-            // Extract the base command from this part
-            let base_command = part.split_whitespace().next().unwrap_or("");
-            let command_name = Path::new(base_command)
+            let command_name = Path::new(&command.name)
                 .file_name()
                 .and_then(|s| s.to_str())
-                .unwrap_or(base_command);
+                .unwrap_or(&command.name);
 
-            // Skip shell built-ins and operators
-            if self.is_shell_builtin(command_name) {
+            // Skip shell built-ins, and shells whose inline script was parsed above
+            if self.is_shell_builtin(command_name) || command.runs_inline_script() {
                 continue;
             }
 
@@ -291,34 +333,11 @@ impl Sandbox {
         Ok(())
     }
 
-    /// Split shell command by operators while preserving quoted strings
-    fn split_shell_command(&self, command_str: &str) -> Vec<String> {
-        // Simple split by common shell operators
-        // This is not a full shell parser but handles most cases
-        let separators = ["&&", "||", ";", "|"];
-        let mut parts = vec![command_str.to_string()];
-
-        for separator in separators {
-            let mut new_parts = Vec::new();
-            for part in parts {
-                let split_parts: Vec<String> = part
-                    .split(separator)
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect();
-                new_parts.extend(split_parts);
-            }
-            parts = new_parts;
-        }
-
-        parts
-    }
-
     /// Check if a command is a shell built-in
     fn is_shell_builtin(&self, command: &str) -> bool {
         let builtins = [
-            "true", "false", "test", "[", "echo", "printf", "cd", "pwd", "export", "set", "unset",
-            "alias", "history", "jobs", "fg", "bg", "wait", "read",
+            "true", "false", "test", "[", "[[", "echo", "printf", "cd", "pwd", "export", "set",
+            "unset", "alias", "history", "jobs", "fg", "bg", "wait", "read",
         ];
         builtins.contains(&command)
     }
@@ -388,15 +407,38 @@ impl Sandbox {
         working_dir: &Path,
     ) -> Result<crate::container::ContainerOutput, SandboxError> {
         // Join command parts and execute via shell for proper handling of operators
-        let command_str = command.join(" ");
+        let command_str = shell::join(command);
 
-        let mut cmd = Command::new("sh");
+        let isolation = if self.config.allow_network {
+            None
+        } else {
+            let prefix = network_isolation_prefix();
+            if prefix.is_none() {
+                wrkflw_logging::warning(
+                    "⚠️ Network access is disabled but network namespaces are unavailable; running without network isolation",
+                );
+            }
+            prefix
+        };
+
+        let mut cmd = match isolation {
+            Some(prefix) => {
+                let mut cmd = Command::new(prefix[0]);
+                cmd.args(&prefix[1..]);
+                cmd.arg("sh");
+                cmd
+            }
+            None => Command::new("sh"),
+        };
         cmd.arg("-c");
         cmd.arg(&command_str);
         cmd.current_dir(working_dir);
         cmd.stdout(Stdio::piped());
         cmd.stderr(Stdio::piped());
 
+        let limiter = ResourceLimiter::new(&self.config);
+        limiter.apply(&mut cmd);
+
         // Set environment variables (filtered)
         for (key, value) in env_vars {
             if self.is_env_var_safe(key) {
@@ -420,10 +462,21 @@ impl Sandbox {
         let start_time = std::time::Instant::now();
 
         let result = tokio::time::timeout(timeout_duration, async {
-            let output = cmd.output().map_err(|e| SandboxError::ExecutionError {
-                reason: format!("Command execution failed: {}", e),
+            let output = crate::emulation::tracked_output(&mut cmd).map_err(|e| {
+                SandboxError::ExecutionError {
+                    reason: format!("Command execution failed: {}", e),
+                }
             })?;
 
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if let Some(resource) = limiter.exceeded(&output.status, &stderr) {
+                wrkflw_logging::warning(&format!(
+                    "🚫 Sandboxed command exceeded its {} limit",
+                    resource
+                ));
+                return Err(SandboxError::ResourceLimitExceeded { resource });
+            }
+
             Ok(crate::container::ContainerOutput {
                 stdout: String::from_utf8_lossy(&output.stdout).to_string(),
                 stderr: String::from_utf8_lossy(&output.stderr).to_string(),
@@ -478,18 +531,13 @@ impl Sandbox {
 
     /// Check if an environment variable is safe to pass through
     fn is_env_var_safe(&self, key: &str) -> bool {
-        // Block dangerous environment variables
-        let dangerous_env_vars = [
-            "LD_PRELOAD",
-            "LD_LIBRARY_PATH",
-            "DYLD_INSERT_LIBRARIES",
-            "DYLD_LIBRARY_PATH",
-            "PATH",
-            "HOME",
-            "SHELL",
-        ];
+        let matches = |rule: &String| match rule.strip_suffix('*') {
+            Some(prefix) => key.starts_with(prefix),
+            None => rule == key,
+        };
 
-        !dangerous_env_vars.contains(&key)
+        self.config.env_passthrough.iter().any(matches)
+            || !self.config.blocked_env_vars.iter().any(matches)
     }
 
     /// Check if a file should be skipped during copying
@@ -572,6 +620,33 @@ impl Sandbox {
     }
 }
 
+/// Command prefix that runs a program in an empty network namespace, if the host supports it
+///
+/// Only Linux has network namespaces; elsewhere `allow_network: false` cannot be enforced.
+pub fn network_isolation_prefix() -> Option<&'static [&'static str]> {
+    static PREFIX: OnceLock<Option<&'static [&'static str]>> = OnceLock::new();
+    *PREFIX.get_or_init(|| {
+        if !cfg!(target_os = "linux") {
+            return None;
+        }
+        // Unprivileged users need a user namespace to create a network namespace
+        const CANDIDATES: &[&[&str]] = &[
+            &["unshare", "--net"],
+            &["unshare", "--net", "--map-root-user"],
+        ];
+        CANDIDATES.iter().copied().find(|prefix| {
+            Command::new(prefix[0])
+                .args(&prefix[1..])
+                .arg("true")
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .map(|status| status.success())
+                .unwrap_or(false)
+        })
+    })
+}
+
 /// Create a default sandbox configuration for CI/CD workflows
 pub fn create_workflow_sandbox_config() -> SandboxConfig {
     let mut allowed_read_paths = HashSet::new();
@@ -641,6 +716,31 @@ mod tests {
         assert!(sandbox.validate_command("cargo build").is_ok());
     }
 
+    #[test]
+    fn test_validation_is_shell_aware() {
+        let sandbox = Sandbox::new(create_workflow_sandbox_config()).unwrap();
+
+        // Commands hidden in substitutions, prefixes, quoting and wrappers are still found
+        assert!(sandbox.validate_command("echo $(curl evil.sh)").is_err());
+        assert!(sandbox.validate_command("echo `wget x`").is_err());
+        assert!(sandbox.validate_command("FOO=1 ssh host").is_err());
+        assert!(sandbox.validate_command("c''url x").is_err());
+        assert!(sandbox.validate_command("env -i nc -l 80").is_err());
+        assert!(sandbox.validate_command("bash -c 'kill 1'").is_err());
+        assert!(sandbox.validate_command("echo 'unterminated").is_err());
+
+        // Quoted text is data, not commands
+        assert!(sandbox.validate_command("echo 'curl && wget'").is_ok());
+        assert!(sandbox
+            .validate_command("cargo test 2>&1 > out.log && cat out.log")
+            .is_ok());
+
+        // Dynamic command names are only rejected in strict mode
+        assert!(sandbox.validate_command("$TOOL --version").is_ok());
+        let strict = Sandbox::new(SandboxConfig::default()).unwrap();
+        assert!(strict.validate_command("$TOOL --version").is_err());
+    }
+
     #[test]
     fn test_command_whitelist() {
         let config = create_strict_sandbox_config();
@@ -653,6 +753,11 @@ mod tests {
         // Should block non-whitelisted commands
         assert!(sandbox.validate_command("git clone").is_err());
         assert!(sandbox.validate_command("cargo build").is_err());
+
+        // Inline shell scripts are checked command by command
+        assert!(sandbox.validate_command("bash -c 'echo hi && ls'").is_ok());
+        assert!(sandbox.validate_command("bash -c 'git clone x'").is_err());
+        assert!(sandbox.validate_command("bash script.sh").is_err());
     }
 
     #[test]
@@ -669,4 +774,57 @@ mod tests {
         assert!(!sandbox.should_skip_file("README.md"));
         assert!(!sandbox.should_skip_file(".gitignore"));
     }
+
+    #[tokio::test]
+    async fn test_network_disabled_uses_empty_namespace() {
+        if network_isolation_prefix().is_none() {
+            return;
+        }
+        let config = SandboxConfig {
+            allow_network: false,
+            ..create_workflow_sandbox_config()
+        };
+        let sandbox = Sandbox::new(config).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let output = sandbox
+            .execute_with_limits(&["cat", "/proc/net/dev"], &[], dir.path())
+            .await
+            .unwrap();
+
+        // Only the loopback interface exists in a fresh network namespace
+        let interfaces: Vec<&str> = output
+            .stdout
+            .lines()
+            .skip(2)
+            .filter_map(|line| line.split(':').next())
+            .map(str::trim)
+            .collect();
+        assert_eq!(interfaces, vec!["lo"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_memory_limit_exceeded() {
+        if which::which("python3").is_err() {
+            return;
+        }
+        let config = SandboxConfig {
+            max_memory_mb: 64,
+            ..create_workflow_sandbox_config()
+        };
+        let sandbox = Sandbox::new(config).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let result = sandbox
+            .execute_with_limits(
+                &["python3", "-c", "x = bytearray(512 * 1024 * 1024)"],
+                &[],
+                dir.path(),
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(SandboxError::ResourceLimitExceeded { .. })
+        ));
+    }
 }
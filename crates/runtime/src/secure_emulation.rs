@@ -1,4 +1,4 @@
-use crate::container::{ContainerError, ContainerOutput, ContainerRuntime};
+use crate::container::{BuildOptions, ContainerError, ContainerOutput, ContainerRuntime};
 use crate::sandbox::{create_workflow_sandbox_config, Sandbox, SandboxConfig, SandboxError};
 use async_trait::async_trait;
 use std::path::Path;
@@ -128,7 +128,12 @@ impl ContainerRuntime for SecureEmulationRuntime {
         Ok(())
     }
 
-    async fn build_image(&self, dockerfile: &Path, tag: &str) -> Result<(), ContainerError> {
+    async fn build_image(
+        &self,
+        dockerfile: &Path,
+        tag: &str,
+        _options: &BuildOptions,
+    ) -> Result<(), ContainerError> {
         wrkflw_logging::info(&format!(
             "🔒 Secure emulation: Pretending to build image {} from {}",
             tag,
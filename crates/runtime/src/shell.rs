@@ -0,0 +1,1013 @@
+//! A small POSIX shell lexer that finds every command a script would run.
+//!
+//! It is not a full shell grammar, but it understands what matters for validation:
+//! quoting and escapes, operators and subshells, command and process substitution,
+//! redirections, here-documents, `VAR=value` prefixes, `case` patterns, and wrappers
+//! that run another command (`env`, `sudo`, `xargs`, `sh -c`, `eval`, ...).
+
+/// A simple command found in a script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShellCommand {
+    /// Command name after quote removal
+    pub name: String,
+    /// Arguments after quote removal
+    pub args: Vec<String>,
+    /// The name comes from an expansion (`$CMD`, `$(...)`) and is only known at runtime
+    pub dynamic: bool,
+}
+
+impl ShellCommand {
+    /// A shell given an inline script (`bash -c '...'`); the script's own commands
+    /// are reported separately
+    pub fn runs_inline_script(&self) -> bool {
+        is_shell(&self.name) && inline_script(&self.args).is_some()
+    }
+}
+
+fn is_shell(name: &str) -> bool {
+    let base = name.rsplit('/').next().unwrap_or(name);
+    matches!(base, "sh" | "bash" | "dash" | "zsh" | "ksh" | "ash")
+}
+
+/// The script passed with -c, or combined flags such as -ec
+fn inline_script(args: &[String]) -> Option<&String> {
+    args.iter()
+        .position(|a| a.starts_with('-') && !a.starts_with("--") && a.contains('c'))
+        .and_then(|i| args.get(i + 1))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ShellParseError {
+    #[error("unterminated {0}")]
+    Unterminated(&'static str),
+}
+
+/// Every simple command in `script`, including those nested in substitutions
+pub fn parse_commands(script: &str) -> Result<Vec<ShellCommand>, ShellParseError> {
+    let mut parser = Parser::new(script);
+    parser.parse()?;
+    Ok(parser.commands)
+}
+
+/// The commands of a script made only of plain commands, one per line
+///
+/// Returns `None` when the script needs a shell: operators, subshells, expansions,
+/// redirections, control flow or `VAR=value` prefixes.
+pub fn parse_simple_commands(script: &str) -> Option<Vec<ShellCommand>> {
+    let mut parser = Parser::new(script);
+    parser.parse().ok()?;
+    (!parser.needs_shell).then_some(parser.top_level)
+}
+
+/// Join an argv into a script that `sh -c` runs with the same arguments
+///
+/// A single element is taken to already be a script.
+pub fn join(argv: &[&str]) -> String {
+    if argv.len() == 1 {
+        return argv[0].to_string();
+    }
+    argv.iter()
+        .map(|arg| quote(arg))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./=:,+@%^".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "!", "{", "}", "if", "then", "else", "elif", "fi", "do", "done", "while", "until", "time",
+];
+
+#[derive(Debug, Default)]
+struct Word {
+    text: String,
+    quoted: bool,
+    dynamic: bool,
+}
+
+impl Word {
+    fn is_keyword(&self) -> bool {
+        !self.quoted && KEYWORDS.contains(&self.text.as_str())
+    }
+
+    fn is_assignment(&self) -> bool {
+        match self.text.split_once('=') {
+            Some((name, _)) => {
+                !name.is_empty()
+                    && !name.starts_with(|c: char| c.is_ascii_digit())
+                    && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            }
+            None => false,
+        }
+    }
+}
+
+struct Heredoc {
+    delimiter: String,
+    strip_tabs: bool,
+    quoted: bool,
+}
+
+#[derive(Default)]
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    commands: Vec<ShellCommand>,
+    /// Commands as written, without the ones found inside them
+    top_level: Vec<ShellCommand>,
+    /// Anything beyond plain commands separated by newlines was seen
+    needs_shell: bool,
+    words: Vec<Word>,
+    word: Option<Word>,
+    /// The next word is a redirection target
+    redirect_target: bool,
+    /// The next word is a here-document delimiter (value: strip leading tabs)
+    heredoc_delimiter: Option<bool>,
+    pending_heredocs: Vec<Heredoc>,
+    paren_depth: usize,
+    /// Between `case ... in` and `esac`
+    case_depth: usize,
+    /// Reading `case WORD in` itself
+    case_header: bool,
+    /// Reading a case pattern, up to its `)`
+    case_pattern: bool,
+    /// Inside `[[ ... ]]`, where operators are just words
+    in_test: bool,
+}
+
+impl Parser {
+    fn new(script: &str) -> Self {
+        Self {
+            chars: script.chars().collect(),
+            ..Default::default()
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn word_mut(&mut self) -> &mut Word {
+        self.word.get_or_insert_with(Word::default)
+    }
+
+    fn at_command_start(&self) -> bool {
+        self.word.is_none() && self.words.iter().all(Word::is_keyword)
+    }
+
+    fn parse(&mut self) -> Result<(), ShellParseError> {
+        while let Some(c) = self.peek() {
+            match c {
+                ' ' | '\t' | '\r' => {
+                    self.finish_word();
+                    self.pos += 1;
+                }
+                '\n' => {
+                    self.finish_word();
+                    if !self.case_pattern {
+                        self.finish_command();
+                    }
+                    self.pos += 1;
+                    self.read_heredoc_bodies()?;
+                }
+                '&' if self.peek_at(1) == Some('>') => {
+                    // &> and &>> redirect both stdout and stderr
+                    self.finish_word();
+                    self.needs_shell = true;
+                    self.pos += 2;
+                    if self.peek() == Some('>') {
+                        self.pos += 1;
+                    }
+                    self.redirect_target = true;
+                }
+                ';' | '&' | '|' => {
+                    self.finish_word();
+                    self.needs_shell = true;
+                    self.pos += 1;
+                    if self.in_test && c != ';' {
+                        if self.peek() == Some(c) {
+                            self.pos += 1;
+                        }
+                        continue;
+                    }
+                    if self.case_pattern && c == '|' {
+                        // Alternatives within a case pattern
+                        continue;
+                    }
+                    self.finish_command();
+                    let doubled = self.peek() == Some(c) || (c == '|' && self.peek() == Some('&'));
+                    if doubled {
+                        self.pos += 1;
+                    }
+                    if c == ';' && self.case_depth > 0 && (doubled || self.peek() == Some('&')) {
+                        // ;; (or ;& / ;;&) ends a case branch, so a pattern follows
+                        if self.peek() == Some('&') {
+                            self.pos += 1;
+                        }
+                        self.case_pattern = true;
+                    }
+                }
+                '(' => self.open_paren()?,
+                ')' => {
+                    self.finish_word();
+                    self.needs_shell = true;
+                    self.pos += 1;
+                    if self.in_test {
+                        continue;
+                    }
+                    if self.case_pattern {
+                        self.words.clear();
+                        self.case_pattern = false;
+                    } else {
+                        self.paren_depth = self.paren_depth.saturating_sub(1);
+                        self.finish_command();
+                    }
+                }
+                '<' | '>' if self.peek_at(1) == Some('(') => {
+                    // Process substitution
+                    self.pos += 2;
+                    let inner = self.read_balanced_parens(1, "process substitution")?;
+                    self.parse_nested(&inner)?;
+                    self.word_mut().dynamic = true;
+                }
+                '<' | '>' => self.redirection(c),
+                '#' if self.word.is_none() => {
+                    while self.peek().is_some_and(|c| c != '\n') {
+                        self.pos += 1;
+                    }
+                }
+                '\'' => {
+                    self.pos += 1;
+                    let text = self.read_until('\'', "single quote")?;
+                    let word = self.word_mut();
+                    word.text.push_str(&text);
+                    word.quoted = true;
+                }
+                '"' => self.read_double_quoted()?,
+                '\\' => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some('\n') => self.pos += 1,
+                        Some(next) => {
+                            self.pos += 1;
+                            let word = self.word_mut();
+                            word.text.push(next);
+                            word.quoted = true;
+                        }
+                        None => {}
+                    }
+                }
+                '`' => {
+                    self.pos += 1;
+                    let inner = self.read_backticks()?;
+                    self.parse_nested(&inner)?;
+                    self.word_mut().dynamic = true;
+                }
+                '$' => self.read_dollar(false)?,
+                _ => {
+                    self.word_mut().text.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+
+        self.finish_word();
+        self.finish_command();
+        if self.paren_depth > 0 {
+            return Err(ShellParseError::Unterminated("subshell"));
+        }
+        Ok(())
+    }
+
+    /// Only look for substitutions, as in a double-quoted string or here-document
+    fn scan(&mut self) -> Result<(), ShellParseError> {
+        while let Some(c) = self.peek() {
+            match c {
+                '\\' => self.pos += 2,
+                '`' => {
+                    self.pos += 1;
+                    let inner = self.read_backticks()?;
+                    self.parse_nested(&inner)?;
+                }
+                '$' => self.read_dollar(true)?,
+                _ => self.pos += 1,
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_nested(&mut self, script: &str) -> Result<(), ShellParseError> {
+        let mut nested = Parser::new(script);
+        nested.parse()?;
+        self.commands.append(&mut nested.commands);
+        Ok(())
+    }
+
+    fn scan_nested(&mut self, text: &str) -> Result<(), ShellParseError> {
+        let mut nested = Parser::new(text);
+        nested.scan()?;
+        self.commands.append(&mut nested.commands);
+        Ok(())
+    }
+
+    fn open_paren(&mut self) -> Result<(), ShellParseError> {
+        self.finish_word();
+        self.needs_shell = true;
+        self.pos += 1;
+        if self.in_test {
+            return Ok(());
+        }
+        if self.case_pattern {
+            // Optional leading parenthesis of a case pattern
+            return Ok(());
+        }
+
+        // `name() { ... }` defines a function rather than running `name`
+        let mut lookahead = self.pos;
+        while matches!(self.chars.get(lookahead), Some(' ' | '\t')) {
+            lookahead += 1;
+        }
+        if self.chars.get(lookahead) == Some(&')') && !self.words.is_empty() {
+            self.words.pop();
+            self.pos = lookahead + 1;
+            return Ok(());
+        }
+
+        // (( arithmetic ))
+        if self.peek() == Some('(') && self.at_command_start() {
+            self.pos += 1;
+            let inner = self.read_balanced_parens(2, "arithmetic command")?;
+            return self.scan_nested(&inner);
+        }
+
+        self.finish_command();
+        self.paren_depth += 1;
+        Ok(())
+    }
+
+    fn redirection(&mut self, c: char) {
+        // A bare number right before the operator is a file descriptor, not a word
+        if self.word.as_ref().is_some_and(|w| {
+            !w.quoted && !w.text.is_empty() && w.text.chars().all(|c| c.is_ascii_digit())
+        }) {
+            self.word = None;
+        }
+        self.finish_word();
+        self.needs_shell = true;
+        self.pos += 1;
+
+        if self.in_test {
+            return;
+        }
+
+        if c == '<' && self.peek() == Some('<') {
+            self.pos += 1;
+            match self.peek() {
+                // <<< here-string
+                Some('<') => {
+                    self.pos += 1;
+                    self.redirect_target = true;
+                }
+                Some('-') => {
+                    self.pos += 1;
+                    self.heredoc_delimiter = Some(true);
+                }
+                _ => self.heredoc_delimiter = Some(false),
+            }
+            return;
+        }
+
+        if matches!(self.peek(), Some('>' | '&' | '|')) {
+            self.pos += 1;
+        }
+        self.redirect_target = true;
+    }
+
+    fn read_until(&mut self, end: char, what: &'static str) -> Result<String, ShellParseError> {
+        let mut text = String::new();
+        loop {
+            match self.peek() {
+                Some(c) if c == end => {
+                    self.pos += 1;
+                    return Ok(text);
+                }
+                Some(c) => {
+                    text.push(c);
+                    self.pos += 1;
+                }
+                None => return Err(ShellParseError::Unterminated(what)),
+            }
+        }
+    }
+
+    fn read_double_quoted(&mut self) -> Result<(), ShellParseError> {
+        self.pos += 1;
+        self.word_mut().quoted = true;
+        loop {
+            match self.peek() {
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some('\n') => self.pos += 1,
+                        Some(next @ ('$' | '`' | '"' | '\\')) => {
+                            self.pos += 1;
+                            self.word_mut().text.push(next);
+                        }
+                        _ => self.word_mut().text.push('\\'),
+                    }
+                }
+                Some('`') => {
+                    self.pos += 1;
+                    let inner = self.read_backticks()?;
+                    self.parse_nested(&inner)?;
+                    self.word_mut().dynamic = true;
+                }
+                Some('$') => self.read_dollar(true)?,
+                Some(c) => {
+                    self.word_mut().text.push(c);
+                    self.pos += 1;
+                }
+                None => return Err(ShellParseError::Unterminated("double quote")),
+            }
+        }
+    }
+
+    fn read_backticks(&mut self) -> Result<String, ShellParseError> {
+        let mut inner = String::new();
+        loop {
+            match self.peek() {
+                Some('`') => {
+                    self.pos += 1;
+                    return Ok(inner);
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some(next @ ('`' | '$' | '\\')) => {
+                            self.pos += 1;
+                            inner.push(next);
+                        }
+                        _ => inner.push('\\'),
+                    }
+                }
+                Some(c) => {
+                    inner.push(c);
+                    self.pos += 1;
+                }
+                None => return Err(ShellParseError::Unterminated("backtick substitution")),
+            }
+        }
+    }
+
+    /// Read up to the parenthesis closing `depth` open ones, skipping quoted text
+    fn read_balanced_parens(
+        &mut self,
+        mut depth: usize,
+        what: &'static str,
+    ) -> Result<String, ShellParseError> {
+        let arithmetic = depth == 2;
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '\\' => self.pos += 1,
+                '\'' => {
+                    self.read_until('\'', what)?;
+                }
+                '"' => {
+                    while let Some(q) = self.peek() {
+                        self.pos += 1;
+                        match q {
+                            '\\' => self.pos += 1,
+                            '"' => break,
+                            _ => {}
+                        }
+                    }
+                }
+                '`' => {
+                    self.read_backticks()?;
+                }
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        let mut inner: String = self.chars[start..self.pos - 1].iter().collect();
+                        // The closing parens of `(( ))` come as a pair
+                        if arithmetic && inner.ends_with(')') {
+                            inner.pop();
+                        }
+                        return Ok(inner);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err(ShellParseError::Unterminated(what))
+    }
+
+    fn read_dollar(&mut self, in_double_quotes: bool) -> Result<(), ShellParseError> {
+        match self.peek_at(1) {
+            Some('(') if self.peek_at(2) == Some('(') => {
+                self.pos += 3;
+                let inner = self.read_balanced_parens(2, "arithmetic expansion")?;
+                self.scan_nested(&inner)?;
+                self.word_mut().dynamic = true;
+            }
+            Some('(') => {
+                self.pos += 2;
+                let inner = self.read_balanced_parens(1, "command substitution")?;
+                self.parse_nested(&inner)?;
+                self.word_mut().dynamic = true;
+            }
+            Some('{') => {
+                self.pos += 2;
+                let mut depth = 1;
+                let start = self.pos;
+                loop {
+                    match self.peek() {
+                        Some('{') => depth += 1,
+                        Some('}') => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        Some(_) => {}
+                        None => return Err(ShellParseError::Unterminated("parameter expansion")),
+                    }
+                    self.pos += 1;
+                }
+                let inner: String = self.chars[start..self.pos].iter().collect();
+                self.pos += 1;
+                // Defaults such as ${X:-$(cmd)} may run commands
+                self.scan_nested(&inner)?;
+                self.word_mut().dynamic = true;
+            }
+            Some('\'') if !in_double_quotes => {
+                // $'...' ANSI-C quoting
+                self.pos += 2;
+                let mut text = String::new();
+                loop {
+                    match self.peek() {
+                        Some('\'') => {
+                            self.pos += 1;
+                            break;
+                        }
+                        Some('\\') => {
+                            self.pos += 1;
+                            let escaped = self.peek();
+                            self.pos += 1;
+                            match escaped {
+                                Some('n') => text.push('\n'),
+                                Some('t') => text.push('\t'),
+                                Some('x') => text.extend(self.read_code_point(16, 2)),
+                                Some('u') => text.extend(self.read_code_point(16, 4)),
+                                Some('0'..='7') => {
+                                    self.pos -= 1;
+                                    text.extend(self.read_code_point(8, 3));
+                                }
+                                Some(c) => text.push(c),
+                                None => {}
+                            }
+                        }
+                        Some(c) => {
+                            text.push(c);
+                            self.pos += 1;
+                        }
+                        None => return Err(ShellParseError::Unterminated("$'...' string")),
+                    }
+                }
+                let word = self.word_mut();
+                word.text.push_str(&text);
+                word.quoted = true;
+            }
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                self.pos += 1;
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+                {
+                    self.pos += 1;
+                }
+                self.word_mut().dynamic = true;
+            }
+            Some(c) if c.is_ascii_digit() || "@*#?$!-".contains(c) => {
+                self.pos += 2;
+                self.word_mut().dynamic = true;
+            }
+            _ => {
+                self.pos += 1;
+                self.word_mut().text.push('$');
+            }
+        }
+        Ok(())
+    }
+
+    /// Read up to `max_digits` digits in `radix` as a character, for `$'\x72'` and friends
+    fn read_code_point(&mut self, radix: u32, max_digits: usize) -> Option<char> {
+        let start = self.pos;
+        while self.pos - start < max_digits && self.peek().is_some_and(|c| c.is_digit(radix)) {
+            self.pos += 1;
+        }
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        u32::from_str_radix(&digits, radix)
+            .ok()
+            .and_then(char::from_u32)
+    }
+
+    fn read_heredoc_bodies(&mut self) -> Result<(), ShellParseError> {
+        for heredoc in std::mem::take(&mut self.pending_heredocs) {
+            let mut body = String::new();
+            while self.peek().is_some() {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.pos += 1;
+                }
+                let line: String = self.chars[start..self.pos].iter().collect();
+                if self.peek() == Some('\n') {
+                    self.pos += 1;
+                }
+                let compared = if heredoc.strip_tabs {
+                    line.trim_start_matches('\t')
+                } else {
+                    line.as_str()
+                };
+                if compared == heredoc.delimiter {
+                    break;
+                }
+                body.push_str(&line);
+                body.push('\n');
+            }
+            // Unquoted delimiters mean the body is expanded, substitutions included
+            if !heredoc.quoted {
+                self.scan_nested(&body)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish_word(&mut self) {
+        let Some(word) = self.word.take() else {
+            return;
+        };
+        if word.dynamic {
+            self.needs_shell = true;
+        }
+
+        if let Some(strip_tabs) = self.heredoc_delimiter.take() {
+            self.pending_heredocs.push(Heredoc {
+                delimiter: word.text,
+                strip_tabs,
+                quoted: word.quoted,
+            });
+            return;
+        }
+        if self.redirect_target {
+            self.redirect_target = false;
+            return;
+        }
+
+        if self.in_test {
+            if !word.quoted && word.text == "]]" {
+                self.in_test = false;
+            }
+            self.words.push(word);
+            return;
+        }
+
+        if self.case_header {
+            if !word.quoted && word.text == "in" {
+                self.case_header = false;
+                self.case_pattern = true;
+            }
+            return;
+        }
+
+        if self.at_command_start() && !word.quoted {
+            if matches!(word.text.as_str(), "case" | "esac" | "[[") {
+                self.needs_shell = true;
+            }
+            match word.text.as_str() {
+                "case" => {
+                    self.case_depth += 1;
+                    self.case_header = true;
+                    return;
+                }
+                "esac" if self.case_depth > 0 => {
+                    self.case_depth -= 1;
+                    self.case_pattern = false;
+                    return;
+                }
+                "[[" => self.in_test = true,
+                _ => {}
+            }
+        }
+
+        self.words.push(word);
+    }
+
+    fn finish_command(&mut self) {
+        let words = std::mem::take(&mut self.words);
+        let mut words = words.into_iter().peekable();
+
+        while let Some(word) = words.peek() {
+            if word.is_keyword()
+                || word.is_assignment()
+                || (!word.quoted && matches!(word.text.as_str(), "function" | "for" | "select"))
+            {
+                self.needs_shell = true;
+            }
+            if word.is_keyword() || word.is_assignment() {
+                words.next();
+                continue;
+            }
+            if !word.quoted && word.text == "function" {
+                // function NAME { ... }
+                words.next();
+                words.next();
+                continue;
+            }
+            if !word.quoted && (word.text == "for" || word.text == "select") {
+                // Loop headers only expand words; substitutions were already parsed
+                return;
+            }
+            break;
+        }
+
+        let Some(name) = words.next() else {
+            return;
+        };
+        let args: Vec<String> = words.map(|w| w.text).collect();
+        self.top_level.push(ShellCommand {
+            name: name.text.clone(),
+            args: args.clone(),
+            dynamic: name.dynamic,
+        });
+        self.emit(name.text, args, name.dynamic);
+    }
+
+    fn emit(&mut self, name: String, args: Vec<String>, dynamic: bool) {
+        let base = name.rsplit('/').next().unwrap_or(&name).to_string();
+        self.commands.push(ShellCommand {
+            name,
+            args: args.clone(),
+            dynamic,
+        });
+
+        match base.as_str() {
+            _ if is_shell(&base) => {
+                if let Some(script) = inline_script(&args) {
+                    let mut nested = Parser::new(script);
+                    if nested.parse().is_ok() {
+                        self.commands.append(&mut nested.commands);
+                    }
+                }
+            }
+            "eval" => {
+                let mut nested = Parser::new(&args.join(" "));
+                if nested.parse().is_ok() {
+                    self.commands.append(&mut nested.commands);
+                }
+            }
+            "find" => {
+                let mut iter = args.iter();
+                while let Some(arg) = iter.next() {
+                    if matches!(arg.as_str(), "-exec" | "-execdir" | "-ok" | "-okdir") {
+                        let inner: Vec<String> = iter
+                            .by_ref()
+                            .take_while(|a| *a != ";" && *a != "+")
+                            .cloned()
+                            .collect();
+                        self.emit_argv(inner);
+                    }
+                }
+            }
+            _ => {
+                if let Some(inner) = wrapped_command(&base, &args) {
+                    self.emit_argv(inner);
+                }
+            }
+        }
+    }
+
+    fn emit_argv(&mut self, mut argv: Vec<String>) {
+        if argv.is_empty() {
+            return;
+        }
+        let name = argv.remove(0);
+        let dynamic = name.contains('$');
+        self.emit(name, argv, dynamic);
+    }
+}
+
+/// The command run by a wrapper such as `env`, `sudo` or `xargs`
+fn wrapped_command(wrapper: &str, args: &[String]) -> Option<Vec<String>> {
+    // Options that take a separate value
+    let value_options: &[&str] = match wrapper {
+        "env" => &["-u", "-C", "--unset", "--chdir"],
+        "sudo" => &["-u", "-g", "-p", "-C", "-D", "-h", "-r", "-t", "-U"],
+        "doas" => &["-u", "-C"],
+        "nice" => &["-n", "--adjustment"],
+        "ionice" => &["-c", "-n", "-p"],
+        "timeout" => &["-s", "-k", "--signal", "--kill-after"],
+        "xargs" => &["-I", "-L", "-n", "-P", "-s", "-d", "-E", "-a"],
+        "exec" => &["-a"],
+        "command" | "builtin" | "nohup" | "stdbuf" | "setsid" | "time" | "unbuffer" | "strace" => {
+            &["-o", "-e", "-p"]
+        }
+        _ => return None,
+    };
+
+    let mut i = 0;
+    while i < args.len() {
+        let arg = &args[i];
+        if wrapper == "env" && (arg == "-S" || arg.starts_with("--split-string")) {
+            // env -S splits its argument into a command line
+            let split = match arg.split_once('=') {
+                Some((_, value)) => value.to_string(),
+                None => args.get(i + 1).cloned().unwrap_or_default(),
+            };
+            return Some(split.split_whitespace().map(str::to_string).collect());
+        }
+        if arg == "--" {
+            i += 1;
+            break;
+        }
+        if arg.starts_with('-') && arg.len() > 1 {
+            i += if value_options.contains(&arg.as_str()) {
+                2
+            } else {
+                1
+            };
+            continue;
+        }
+        if wrapper == "env" && arg.contains('=') {
+            i += 1;
+            continue;
+        }
+        break;
+    }
+
+    // timeout DURATION COMMAND
+    if wrapper == "timeout" {
+        i += 1;
+    }
+
+    (i < args.len()).then(|| args[i..].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(script: &str) -> Vec<String> {
+        parse_commands(script)
+            .unwrap()
+            .into_iter()
+            .map(|c| c.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_operators_and_subshells() {
+        assert_eq!(
+            names("echo a && (cd x; make) || { ls | wc -l; }"),
+            vec!["echo", "cd", "make", "ls", "wc"]
+        );
+    }
+
+    #[test]
+    fn test_substitutions_are_found() {
+        assert_eq!(names("echo $(rm -rf x)"), vec!["rm", "echo"]);
+        assert_eq!(names("echo \"`curl x`\""), vec!["curl", "echo"]);
+        assert_eq!(names("diff <(sort a) b"), vec!["sort", "diff"]);
+        assert_eq!(names("echo ${X:-$(wget y)}"), vec!["wget", "echo"]);
+        assert_eq!(names("echo '$(rm x)'"), vec!["echo"]);
+        assert_eq!(names("echo $(cd a && (ls))"), vec!["cd", "ls", "echo"]);
+        assert_eq!(names("echo $(( 1 + $(date +%s) ))"), vec!["date", "echo"]);
+    }
+
+    #[test]
+    fn test_env_prefixes_and_redirections() {
+        assert_eq!(names("FOO=1 BAR=2 rm -rf /"), vec!["rm"]);
+        assert_eq!(names("echo hi > out.txt 2>&1"), vec!["echo"]);
+        assert_eq!(names(">log cat <in"), vec!["cat"]);
+    }
+
+    #[test]
+    fn test_quoting_tricks() {
+        assert_eq!(names("r''m -rf /"), vec!["rm"]);
+        assert_eq!(names("\\rm x"), vec!["rm"]);
+        assert_eq!(names("\"rm\" x"), vec!["rm"]);
+        assert_eq!(names("$'\\x72m' x"), vec!["rm"]);
+        assert_eq!(names("$'\\162\\155' x"), vec!["rm"]);
+    }
+
+    #[test]
+    fn test_wrappers_and_nested_shells() {
+        assert_eq!(names("env -i FOO=1 rm x"), vec!["env", "rm"]);
+        assert_eq!(names("sudo -u root rm x"), vec!["sudo", "rm"]);
+        assert_eq!(names("xargs -n 1 rm"), vec!["xargs", "rm"]);
+        assert_eq!(names("timeout 5 curl x"), vec!["timeout", "curl"]);
+        assert_eq!(names("bash -ec 'curl x | sh'"), vec!["bash", "curl", "sh"]);
+        assert_eq!(names("eval \"wget y\""), vec!["eval", "wget"]);
+        assert_eq!(names("find . -exec rm {} ;"), vec!["find", "rm"]);
+    }
+
+    #[test]
+    fn test_compound_commands() {
+        assert_eq!(
+            names("case $x in a) ls; true\n rm -rf / ;; esac"),
+            vec!["ls", "true", "rm"]
+        );
+        let script = "if [ -f x ]; then\n  echo yes\nfi\nfor f in a b; do cat $f; done\ncase $x in\n  a|b) ls ;;\n  *) pwd ;;\nesac\nf() { date; }\n[[ -n $x && -f y ]] && echo ok";
+        assert_eq!(
+            names(script),
+            vec!["[", "echo", "cat", "ls", "pwd", "date", "[[", "echo"]
+        );
+    }
+
+    #[test]
+    fn test_heredocs() {
+        assert_eq!(
+            names("cat <<EOF\nrm -rf /\n$(curl x)\nEOF\nls"),
+            vec!["cat", "curl", "ls"]
+        );
+        assert_eq!(names("cat <<'EOF'\n$(curl x)\nEOF"), vec!["cat"]);
+    }
+
+    #[test]
+    fn test_dynamic_command_names() {
+        let commands = parse_commands("X=rm; $X -rf /").unwrap();
+        assert!(commands[0].dynamic);
+    }
+
+    #[test]
+    fn test_unterminated_quotes_are_errors() {
+        assert!(parse_commands("echo 'oops").is_err());
+        assert!(parse_commands("echo $(ls").is_err());
+    }
+
+    #[test]
+    fn test_simple_commands() {
+        let commands =
+            parse_simple_commands("# build\nhello 'big world'\n\nlint --fix src\n").unwrap();
+        let argv: Vec<_> = commands
+            .iter()
+            .map(|c| (c.name.as_str(), c.args.clone()))
+            .collect();
+        assert_eq!(
+            argv,
+            vec![
+                ("hello", vec!["big world".to_string()]),
+                ("lint", vec!["--fix".to_string(), "src".to_string()]),
+            ]
+        );
+
+        for script in [
+            "a | b",
+            "a && b",
+            "a; b",
+            "a > out",
+            "echo $HOME",
+            "echo $(date)",
+            "X=1 tool",
+            "if true; then a; fi",
+            "(a)",
+            "for i in 1; do a; done",
+        ] {
+            assert!(parse_simple_commands(script).is_none(), "{}", script);
+        }
+    }
+
+    #[test]
+    fn test_join_quotes_arguments() {
+        assert_eq!(join(&["echo hi && ls"]), "echo hi && ls");
+        assert_eq!(
+            join(&["bash", "-c", "echo 'a b'"]),
+            "bash -c 'echo '\\''a b'\\'''"
+        );
+        assert_eq!(
+            names(&join(&["bash", "-c", "rm x; ls"])),
+            vec!["bash", "rm", "ls"]
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! How long container operations may take before they're given up on.
+//!
+//! Each category has a default, which the `[timeouts]` section of `.wrkflw.toml`
+//! and then `wrkflw run --step-timeout/--pull-timeout` override, all in seconds.
+//! 0 means no timeout.
+
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::future::Future;
+use std::sync::RwLock;
+use std::time::Duration;
+
+const DEFAULT_STEP: u64 = 360;
+const DEFAULT_PULL: u64 = 30;
+const DEFAULT_BUILD: u64 = 120;
+const DEFAULT_AVAILABILITY: u64 = 3;
+
+static CURRENT: Lazy<RwLock<Timeouts>> = Lazy::new(|| RwLock::new(Timeouts::default()));
+
+/// Timeouts in seconds by category; those left unset keep their default
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Timeouts {
+    /// A step running in a Docker or Podman container, and each `podman` command
+    #[serde(default)]
+    pub step: Option<u64>,
+    /// Pulling an image; the image already present is used when it times out
+    #[serde(default)]
+    pub pull: Option<u64>,
+    /// Building an image
+    #[serde(default)]
+    pub build: Option<u64>,
+    /// Checking whether Docker or Podman is available
+    #[serde(default)]
+    pub availability: Option<u64>,
+}
+
+impl Timeouts {
+    /// These timeouts, with those set in `overrides` replacing them
+    pub fn overridden_by(self, overrides: Timeouts) -> Timeouts {
+        Timeouts {
+            step: overrides.step.or(self.step),
+            pull: overrides.pull.or(self.pull),
+            build: overrides.build.or(self.build),
+            availability: overrides.availability.or(self.availability),
+        }
+    }
+
+    pub fn step(&self) -> Option<Duration> {
+        limit(self.step, DEFAULT_STEP)
+    }
+
+    pub fn pull(&self) -> Option<Duration> {
+        limit(self.pull, DEFAULT_PULL)
+    }
+
+    pub fn build(&self) -> Option<Duration> {
+        limit(self.build, DEFAULT_BUILD)
+    }
+
+    pub fn availability(&self) -> Option<Duration> {
+        limit(self.availability, DEFAULT_AVAILABILITY)
+    }
+}
+
+fn limit(seconds: Option<u64>, default: u64) -> Option<Duration> {
+    match seconds.unwrap_or(default) {
+        0 => None,
+        seconds => Some(Duration::from_secs(seconds)),
+    }
+}
+
+/// Use `timeouts` from now on, for the run about to start
+pub fn set_timeouts(timeouts: Timeouts) {
+    if let Ok(mut current) = CURRENT.write() {
+        *current = timeouts;
+    }
+}
+
+/// The timeouts in use
+pub fn timeouts() -> Timeouts {
+    CURRENT.read().map(|current| *current).unwrap_or_default()
+}
+
+/// Await `future` for at most `limit`, or for as long as it takes without one
+pub async fn within<F: Future>(
+    limit: Option<Duration>,
+    future: F,
+) -> Result<F::Output, tokio::time::error::Elapsed> {
+    match limit {
+        Some(limit) => tokio::time::timeout(limit, future).await,
+        None => Ok(future.await),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_and_overrides() {
+        let defaults = Timeouts::default();
+        assert_eq!(defaults.step(), Some(Duration::from_secs(360)));
+        assert_eq!(defaults.pull(), Some(Duration::from_secs(30)));
+
+        let project = Timeouts {
+            step: Some(600),
+            pull: Some(90),
+            ..Default::default()
+        };
+        let cli = Timeouts {
+            pull: Some(0),
+            ..Default::default()
+        };
+        let timeouts = project.overridden_by(cli);
+        assert_eq!(timeouts.step(), Some(Duration::from_secs(600)));
+        assert_eq!(timeouts.pull(), None);
+        assert_eq!(timeouts.build(), Some(Duration::from_secs(120)));
+    }
+
+    #[tokio::test]
+    async fn test_within() {
+        let slow = tokio::time::sleep(Duration::from_millis(50));
+        assert!(within(Some(Duration::from_millis(1)), slow).await.is_err());
+        assert_eq!(within(None, async { 7 }).await.unwrap(), 7);
+    }
+}
@@ -0,0 +1,1016 @@
+//! Tool installation for the `actions/setup-*` actions in emulation mode.
+//!
+//! A requested version (or range) is looked up in the tool cache first, then in the
+//! upstream release index. Missing versions are downloaded into
+//! `<cache>/<tool>/<version>/<arch>`, the same layout as `RUNNER_TOOL_CACHE` on GitHub
+//! runners, and the caller puts the returned directories on PATH for later steps.
+
+use flate2::read::GzDecoder;
+use semver::{Version, VersionReq};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A toolchain installed by one of the setup actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Node,
+    Python,
+    Go,
+    Java,
+}
+
+impl Tool {
+    /// The tool an action installs, e.g. `actions/setup-node@v4`
+    pub fn from_action(uses: &str) -> Option<Self> {
+        let name = uses.split('@').next().unwrap_or(uses);
+        match name {
+            "actions/setup-node" => Some(Tool::Node),
+            "actions/setup-python" => Some(Tool::Python),
+            "actions/setup-go" => Some(Tool::Go),
+            "actions/setup-java" => Some(Tool::Java),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tool::Node => "Node.js",
+            Tool::Python => "Python",
+            Tool::Go => "Go",
+            Tool::Java => "Java",
+        }
+    }
+
+    /// Executable checked when no version is requested
+    pub fn command(&self) -> &'static str {
+        match self {
+            Tool::Node => "node",
+            Tool::Python => "python",
+            Tool::Go => "go",
+            Tool::Java => "java",
+        }
+    }
+
+    fn dir_name(&self) -> &'static str {
+        match self {
+            Tool::Node => "node",
+            Tool::Python => "python",
+            Tool::Go => "go",
+            Tool::Java => "java",
+        }
+    }
+
+    fn version_input(&self) -> &'static str {
+        match self {
+            Tool::Node => "node-version",
+            Tool::Python => "python-version",
+            Tool::Go => "go-version",
+            Tool::Java => "java-version",
+        }
+    }
+
+    /// Name of the tool in `.tool-versions` files
+    fn asdf_name(&self) -> &'static str {
+        match self {
+            Tool::Node => "nodejs",
+            Tool::Python => "python",
+            Tool::Go => "golang",
+            Tool::Java => "java",
+        }
+    }
+}
+
+/// A `*-version` input
+#[derive(Debug, Clone, PartialEq)]
+pub enum VersionSpec {
+    /// Releases matching any of these ranges (`||` alternatives)
+    Range(Vec<VersionReq>),
+    /// The newest stable release (`latest`, `stable`, `node`, `*`)
+    Latest,
+    /// Node.js LTS releases, optionally of one codename (`lts/*`, `lts/iron`)
+    Lts(Option<String>),
+    /// Go's `oldstable`, the newest release of the previous minor version
+    OldStable,
+}
+
+impl VersionSpec {
+    /// Parse a version input the way the setup actions do: partial versions such as
+    /// `20` or `3.12` mean the newest matching release, and npm-style ranges like
+    /// `>=18 <21`, `^20.1`, `~3.11`, `1.22.x` and `18 || 20` are accepted
+    pub fn parse(tool: Tool, input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        let lower = input.to_ascii_lowercase();
+        match lower.as_str() {
+            "" => return Err(format!("Empty {} version", tool.name())),
+            "latest" | "stable" | "*" | "x" => return Ok(VersionSpec::Latest),
+            "node" | "current" if tool == Tool::Node => return Ok(VersionSpec::Latest),
+            "oldstable" if tool == Tool::Go => return Ok(VersionSpec::OldStable),
+            _ => {}
+        }
+        if tool == Tool::Node {
+            if let Some(codename) = lower.strip_prefix("lts/") {
+                return Ok(VersionSpec::Lts(
+                    (codename != "*").then(|| codename.to_string()),
+                ));
+            }
+        }
+
+        input
+            .split("||")
+            .map(parse_range)
+            .collect::<Result<Vec<_>, _>>()
+            .map(VersionSpec::Range)
+            .map_err(|e| format!("Invalid {} version '{}': {}", tool.name(), input, e))
+    }
+
+    fn matches(&self, version: &Version) -> bool {
+        match self {
+            VersionSpec::Range(reqs) => reqs.iter().any(|req| req.matches(version)),
+            _ => version.pre.is_empty(),
+        }
+    }
+
+    /// The newest release satisfying the spec
+    fn pick<'a>(&self, releases: &'a [Release]) -> Option<&'a Release> {
+        let candidates = releases.iter().filter(|r| self.matches(&r.version));
+        match self {
+            VersionSpec::Lts(codename) => candidates
+                .filter(|r| match (&r.lts, codename) {
+                    (Some(lts), Some(codename)) => lts.eq_ignore_ascii_case(codename),
+                    (Some(_), None) => true,
+                    (None, _) => false,
+                })
+                .max_by(|a, b| a.version.cmp(&b.version)),
+            VersionSpec::OldStable => {
+                let latest = VersionSpec::Latest.pick(releases)?;
+                let (major, minor) = (latest.version.major, latest.version.minor);
+                candidates
+                    .filter(|r| r.version.major == major && r.version.minor + 1 == minor)
+                    .max_by(|a, b| a.version.cmp(&b.version))
+            }
+            _ => candidates.max_by(|a, b| a.version.cmp(&b.version)),
+        }
+    }
+}
+
+/// One `||` alternative of a version range, as a semver requirement
+fn parse_range(range: &str) -> Result<VersionReq, String> {
+    let mut comparators = Vec::new();
+    let mut pending_op = String::new();
+    for token in range.split_whitespace() {
+        // `>= 18` is written with a space at times
+        if token.chars().all(|c| "<>=~^".contains(c)) {
+            pending_op.push_str(token);
+            continue;
+        }
+        let token = format!("{}{}", std::mem::take(&mut pending_op), token);
+        let op_len = token.find(|c: char| !"<>=~^".contains(c)).unwrap_or(0);
+        let (op, version) = token.split_at(op_len);
+        let version = version.trim_start_matches('v').trim_start_matches("go");
+        // Build metadata (Java's `17.0.10+7`) plays no part in matching
+        let version = version.split('+').next().unwrap_or(version);
+        let version = version.replace(['x', 'X'], "*");
+
+        comparators.push(if version.contains('*') {
+            version
+        } else if op.is_empty() {
+            // A bare partial version means "the newest release starting with it"
+            format!("={}", version)
+        } else {
+            format!("{}{}", op, version)
+        });
+    }
+    if comparators.is_empty() {
+        return Err("no version given".to_string());
+    }
+    VersionReq::parse(&comparators.join(", ")).map_err(|e| e.to_string())
+}
+
+/// Parse a release version, padding partial versions such as Go's `1.21` or `go1.20`
+fn parse_version(version: &str) -> Option<Version> {
+    let version = version
+        .trim()
+        .trim_start_matches('v')
+        .trim_start_matches("go");
+    let core_len = version.find(['-', '+']).unwrap_or(version.len());
+    let (core, rest) = version.split_at(core_len);
+    let parts = core.split('.').count();
+    if parts == 0 || parts > 3 || !core.split('.').all(|p| p.parse::<u64>().is_ok()) {
+        return None;
+    }
+    let padding = ".0".repeat(3 - parts);
+    Version::parse(&format!("{}{}{}", core, padding, rest)).ok()
+}
+
+/// A downloadable release of a tool
+#[derive(Debug, Clone, PartialEq)]
+struct Release {
+    version: Version,
+    url: String,
+    sha256: Option<String>,
+    /// Node.js LTS codename
+    lts: Option<String>,
+}
+
+/// Host OS and CPU as named by the release indexes
+#[derive(Debug, Clone, Copy)]
+struct Platform {
+    darwin: bool,
+    arm64: bool,
+}
+
+impl Platform {
+    fn host() -> Option<Self> {
+        let darwin = match std::env::consts::OS {
+            "linux" => false,
+            "macos" => true,
+            _ => return None,
+        };
+        let arm64 = match std::env::consts::ARCH {
+            "x86_64" => false,
+            "aarch64" => true,
+            _ => return None,
+        };
+        Some(Self { darwin, arm64 })
+    }
+
+    /// Architecture directory in the tool cache
+    fn arch(&self) -> &'static str {
+        if self.arm64 {
+            "arm64"
+        } else {
+            "x64"
+        }
+    }
+
+    fn os(&self) -> &'static str {
+        if self.darwin {
+            "darwin"
+        } else {
+            "linux"
+        }
+    }
+}
+
+/// Whether tools can be installed on this host (Linux or macOS, x64 or arm64)
+pub fn supported_host() -> bool {
+    Platform::host().is_some()
+}
+
+const NODE_INDEX: &str = "https://nodejs.org/dist/index.json";
+const GO_INDEX: &str = "https://go.dev/dl/?mode=json&include=all";
+const PYTHON_INDEX: &str =
+    "https://raw.githubusercontent.com/actions/python-versions/main/versions-manifest.json";
+
+fn node_releases(index: &str, platform: Platform) -> Result<Vec<Release>, String> {
+    let index: Vec<Value> = serde_json::from_str(index).map_err(|e| e.to_string())?;
+    let os = if platform.darwin { "osx" } else { "linux" };
+    let file = format!("{}-{}", os, platform.arch());
+    let tar_file = format!("{}-tar", file);
+    Ok(index
+        .iter()
+        .filter(|release| {
+            release["files"]
+                .as_array()
+                .is_some_and(|files| files.iter().any(|f| f == &*file || f == &*tar_file))
+        })
+        .filter_map(|release| {
+            let name = release["version"].as_str()?;
+            let version = parse_version(name)?;
+            Some(Release {
+                url: format!(
+                    "https://nodejs.org/dist/v{0}/node-v{0}-{1}-{2}.tar.gz",
+                    version,
+                    platform.os(),
+                    platform.arch()
+                ),
+                version,
+                sha256: None,
+                lts: release["lts"].as_str().map(str::to_string),
+            })
+        })
+        .collect())
+}
+
+fn go_releases(index: &str, platform: Platform) -> Result<Vec<Release>, String> {
+    let index: Vec<Value> = serde_json::from_str(index).map_err(|e| e.to_string())?;
+    let arch = if platform.arm64 { "arm64" } else { "amd64" };
+    Ok(index
+        .iter()
+        .filter(|release| release["stable"].as_bool().unwrap_or(false))
+        .filter_map(|release| {
+            let version = parse_version(release["version"].as_str()?)?;
+            let file = release["files"].as_array()?.iter().find(|f| {
+                f["kind"] == "archive" && f["os"] == platform.os() && f["arch"] == arch
+            })?;
+            Some(Release {
+                version,
+                url: format!("https://dl.google.com/go/{}", file["filename"].as_str()?),
+                sha256: file["sha256"].as_str().map(str::to_string),
+                lts: None,
+            })
+        })
+        .collect())
+}
+
+/// Python builds from actions/python-versions; Linux builds are per Ubuntu release,
+/// so prefer the host's and otherwise the oldest, which needs the oldest glibc
+fn python_releases(
+    index: &str,
+    platform: Platform,
+    ubuntu: Option<&str>,
+) -> Result<Vec<Release>, String> {
+    let index: Vec<Value> = serde_json::from_str(index).map_err(|e| e.to_string())?;
+    Ok(index
+        .iter()
+        .filter_map(|release| {
+            let version = parse_version(release["version"].as_str()?)?;
+            let mut files: Vec<&Value> = release["files"]
+                .as_array()?
+                .iter()
+                .filter(|f| {
+                    f["platform"] == platform.os()
+                        && f["arch"] == platform.arch()
+                        && f["filename"]
+                            .as_str()
+                            .is_some_and(|n| n.ends_with(".tar.gz"))
+                })
+                .collect();
+            files.sort_by_key(|f| {
+                let platform_version = f["platform_version"].as_str().unwrap_or("");
+                (
+                    Some(platform_version) != ubuntu,
+                    platform_version.to_string(),
+                )
+            });
+            let file = files.first()?;
+            Some(Release {
+                version,
+                url: file["download_url"].as_str()?.to_string(),
+                sha256: None,
+                lts: None,
+            })
+        })
+        .collect())
+}
+
+/// Eclipse Temurin builds of one feature release from the Adoptium API
+fn java_releases(index: &str) -> Result<Vec<Release>, String> {
+    let index: Vec<Value> = serde_json::from_str(index).map_err(|e| e.to_string())?;
+    Ok(index
+        .iter()
+        .filter_map(|release| {
+            let version = parse_version(release["version_data"]["semver"].as_str()?)?;
+            let package = release["binaries"]
+                .as_array()?
+                .iter()
+                .map(|binary| &binary["package"])
+                .find(|package| {
+                    package["link"]
+                        .as_str()
+                        .is_some_and(|link| link.ends_with(".tar.gz"))
+                })?;
+            Some(Release {
+                version,
+                url: package["link"].as_str()?.to_string(),
+                sha256: package["checksum"].as_str().map(str::to_string),
+                lts: None,
+            })
+        })
+        .collect())
+}
+
+fn java_index_url(major: u64, platform: Platform) -> String {
+    format!(
+        "https://api.adoptium.net/v3/assets/feature_releases/{}/ga?architecture={}&image_type=jdk&jvm_impl=hotspot&os={}&page_size=50&project=jdk&sort_order=DESC&vendor=eclipse",
+        major,
+        if platform.arm64 { "aarch64" } else { "x64" },
+        if platform.darwin { "mac" } else { "linux" }
+    )
+}
+
+/// The version requested in a version file such as `.nvmrc`, `.python-version`,
+/// `go.mod`, `package.json` (`engines.node`) or `.tool-versions`
+fn version_from_file(tool: Tool, file_name: &str, content: &str) -> Option<String> {
+    let lines = || {
+        content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    };
+    match file_name {
+        "package.json" => {
+            let package: Value = serde_json::from_str(content).ok()?;
+            package["engines"]["node"].as_str().map(str::to_string)
+        }
+        "go.mod" | "go.work" => {
+            let directive = |prefix: &str| {
+                lines().find_map(|line| line.strip_prefix(prefix).map(|v| v.trim().to_string()))
+            };
+            directive("toolchain go").or_else(|| directive("go "))
+        }
+        ".tool-versions" => lines().find_map(|line| {
+            let mut fields = line.split_whitespace();
+            (fields.next() == Some(tool.asdf_name()))
+                .then(|| fields.next().map(str::to_string))
+                .flatten()
+        }),
+        _ => lines().next().map(str::to_string),
+    }
+}
+
+/// An installed tool and how to use it
+#[derive(Debug, Clone)]
+pub struct ToolSetup {
+    pub version: Version,
+    pub dir: PathBuf,
+    /// Directories to put on PATH, highest precedence first
+    pub path: Vec<PathBuf>,
+    /// Environment variables the setup action exports
+    pub env: Vec<(String, String)>,
+    /// Whether it had to be downloaded
+    pub downloaded: bool,
+}
+
+/// Tool installations under a cache directory
+pub struct ToolCache {
+    root: PathBuf,
+    offline: bool,
+}
+
+impl ToolCache {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            offline: false,
+        }
+    }
+
+    /// Only use installed versions, never downloading anything
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// `~/.wrkflw/toolcache`
+    pub fn default_root() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join(".wrkflw")
+            .join("toolcache")
+    }
+
+    fn install_dir(&self, tool: Tool, version: &Version, arch: &str) -> PathBuf {
+        self.root
+            .join(tool.dir_name())
+            .join(version.to_string())
+            .join(arch)
+    }
+
+    /// Completely installed versions; the `<arch>.complete` marker is written last
+    fn installed(&self, tool: Tool, arch: &str) -> Vec<Release> {
+        let Ok(entries) = fs::read_dir(self.root.join(tool.dir_name())) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .filter(|entry| entry.path().join(format!("{}.complete", arch)).exists())
+            .filter_map(|entry| {
+                Some(Release {
+                    version: parse_version(entry.file_name().to_str()?)?,
+                    url: String::new(),
+                    sha256: None,
+                    lts: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Install the version requested by a setup action's `with:` inputs
+    ///
+    /// Version files are read relative to `workspace`. Returns `None` when no version
+    /// is requested, in which case the action uses whatever is on PATH.
+    pub async fn setup(
+        &self,
+        tool: Tool,
+        inputs: &HashMap<String, String>,
+        workspace: &Path,
+    ) -> Result<Option<ToolSetup>, String> {
+        let Some(requested) = requested_version(tool, inputs, workspace)? else {
+            return Ok(None);
+        };
+        if tool == Tool::Java {
+            let distribution = inputs.get("distribution").map(|d| d.trim()).unwrap_or("");
+            if !matches!(distribution, "temurin" | "adopt" | "adopt-hotspot") {
+                return Err(format!(
+                    "Java distribution '{}' is not supported in emulation mode; use temurin",
+                    distribution
+                ));
+            }
+        }
+        let spec = VersionSpec::parse(tool, &requested)?;
+        let platform = Platform::host().ok_or_else(|| {
+            format!(
+                "Installing {} is only supported on Linux and macOS (x64 or arm64) hosts",
+                tool.name()
+            )
+        })?;
+        let arch = platform.arch();
+
+        // Like the setup actions, a cached match wins unless check-latest is set
+        let check_latest = inputs
+            .get("check-latest")
+            .is_some_and(|v| v.trim() == "true");
+        if matches!(spec, VersionSpec::Range(_)) && (!check_latest || self.offline) {
+            if let Some(cached) = spec.pick(&self.installed(tool, arch)) {
+                let dir = self.install_dir(tool, &cached.version, arch);
+                return Ok(Some(finish(tool, cached.version.clone(), dir, false)));
+            }
+        }
+        if self.offline {
+            return Err(format!(
+                "{} '{}' is not in the tool cache at {} and cannot be downloaded offline",
+                tool.name(),
+                requested,
+                self.root.display()
+            ));
+        }
+
+        let releases = fetch_releases(tool, &spec, platform).await?;
+        let release = spec
+            .pick(&releases)
+            .ok_or_else(|| format!("No {} release matches '{}'", tool.name(), requested))?;
+        let dir = self.install_dir(tool, &release.version, arch);
+        let complete = dir.with_extension("complete");
+        if complete.exists() {
+            return Ok(Some(finish(tool, release.version.clone(), dir, false)));
+        }
+
+        wrkflw_logging::info(&format!(
+            "Downloading {} {} from {}",
+            tool.name(),
+            release.version,
+            release.url
+        ));
+        let mut release = release.clone();
+        if tool == Tool::Node {
+            release.sha256 = node_checksum(&release.url).await;
+        }
+        self.install(tool, &release, &dir).await?;
+        fs::write(&complete, "").map_err(|e| e.to_string())?;
+        Ok(Some(finish(tool, release.version, dir, true)))
+    }
+
+    async fn install(&self, tool: Tool, release: &Release, dir: &Path) -> Result<(), String> {
+        let archive = fetch(&release.url)
+            .await?
+            .bytes()
+            .await
+            .map_err(|e| format!("Failed to download {}: {}", release.url, e))?;
+        if let Some(expected) = &release.sha256 {
+            let actual = format!("{:x}", Sha256::digest(&archive));
+            if !actual.eq_ignore_ascii_case(expected) {
+                return Err(format!(
+                    "Checksum mismatch for {}: expected {}, got {}",
+                    release.url, expected, actual
+                ));
+            }
+        }
+
+        // Unpack next to the destination so the final move is a rename
+        let tool_dir = self.root.join(tool.dir_name());
+        fs::create_dir_all(&tool_dir).map_err(|e| e.to_string())?;
+        let staging = tempfile::tempdir_in(&tool_dir).map_err(|e| e.to_string())?;
+        tar::Archive::new(GzDecoder::new(&archive[..]))
+            .unpack(staging.path())
+            .map_err(|e| format!("Failed to unpack {}: {}", release.url, e))?;
+
+        // Most archives hold a single top-level directory (node-v20.11.1-linux-x64/, go/)
+        let entries: Vec<PathBuf> = fs::read_dir(staging.path())
+            .map_err(|e| e.to_string())?
+            .flatten()
+            .map(|entry| entry.path())
+            .collect();
+        let content = match entries.as_slice() {
+            [only] if only.is_dir() => only.clone(),
+            _ => staging.path().to_path_buf(),
+        };
+
+        if tool == Tool::Python {
+            link_python(&content);
+        }
+
+        if dir.exists() {
+            fs::remove_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        if let Some(parent) = dir.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::rename(&content, dir).map_err(|e| {
+            format!(
+                "Failed to install {} into {}: {}",
+                tool.name(),
+                dir.display(),
+                e
+            )
+        })
+    }
+}
+
+/// The version from the `*-version` input, or else the `*-version-file` one
+pub fn requested_version(
+    tool: Tool,
+    inputs: &HashMap<String, String>,
+    workspace: &Path,
+) -> Result<Option<String>, String> {
+    let input = |name: &str| inputs.get(name).map(|v| v.trim()).filter(|v| !v.is_empty());
+    if let Some(version) = input(tool.version_input()) {
+        // Several versions may be listed one per line; the last is the default
+        return Ok(version
+            .lines()
+            .map(str::trim)
+            .rfind(|v| !v.is_empty())
+            .map(str::to_string));
+    }
+    let Some(file) = input(&format!("{}-file", tool.version_input())) else {
+        return Ok(None);
+    };
+    let path = workspace.join(file);
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    version_from_file(tool, file_name, &content)
+        .map(Some)
+        .ok_or_else(|| format!("No {} version found in {}", tool.name(), path.display()))
+}
+
+async fn fetch(url: &str) -> Result<reqwest::Response, String> {
+    reqwest::Client::new()
+        .get(url)
+        .header("User-Agent", "wrkflw")
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to fetch {}: {}", url, e))
+}
+
+async fn fetch_releases(
+    tool: Tool,
+    spec: &VersionSpec,
+    platform: Platform,
+) -> Result<Vec<Release>, String> {
+    let url = match tool {
+        Tool::Node => NODE_INDEX.to_string(),
+        Tool::Go => GO_INDEX.to_string(),
+        Tool::Python => PYTHON_INDEX.to_string(),
+        Tool::Java => {
+            // The Adoptium API lists one feature release (major version) at a time
+            let major = match spec {
+                VersionSpec::Range(reqs) => reqs
+                    .first()
+                    .and_then(|req| req.comparators.first())
+                    .map(|comparator| comparator.major),
+                _ => None,
+            }
+            .ok_or_else(|| "setup-java needs a java-version such as 17 or 21".to_string())?;
+            java_index_url(major, platform)
+        }
+    };
+    let index = fetch(&url)
+        .await?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", url, e))?;
+    match tool {
+        Tool::Node => node_releases(&index, platform),
+        Tool::Go => go_releases(&index, platform),
+        Tool::Python => python_releases(&index, platform, host_ubuntu_version().as_deref()),
+        Tool::Java => java_releases(&index),
+    }
+    .map_err(|e| format!("Invalid {} release index {}: {}", tool.name(), url, e))
+}
+
+/// Node.js publishes checksums next to each release
+async fn node_checksum(url: &str) -> Option<String> {
+    let (dir, file_name) = url.rsplit_once('/')?;
+    let sums = fetch(&format!("{}/SHASUMS256.txt", dir))
+        .await
+        .ok()?
+        .text()
+        .await
+        .ok()?;
+    sums.lines().find_map(|line| {
+        let (sum, name) = line.split_once(char::is_whitespace)?;
+        (name.trim() == file_name).then(|| sum.to_string())
+    })
+}
+
+fn host_ubuntu_version() -> Option<String> {
+    let os_release = fs::read_to_string("/etc/os-release").ok()?;
+    let field = |key: &str| {
+        os_release.lines().find_map(|line| {
+            line.strip_prefix(key)
+                .map(|value| value.trim_matches('"').to_string())
+        })
+    };
+    (field("ID=")? == "ubuntu")
+        .then(|| field("VERSION_ID="))
+        .flatten()
+}
+
+/// The python-versions builds only ship `python3`; their installer adds `python`
+fn link_python(dir: &Path) {
+    #[cfg(unix)]
+    {
+        let bin = dir.join("bin");
+        if bin.join("python3").exists() && !bin.join("python").exists() {
+            let _ = std::os::unix::fs::symlink("python3", bin.join("python"));
+        }
+    }
+}
+
+/// PATH entries and exported variables for an installed tool
+fn finish(tool: Tool, version: Version, dir: PathBuf, downloaded: bool) -> ToolSetup {
+    let display = |path: &Path| path.to_string_lossy().into_owned();
+    let (path, env) = match tool {
+        Tool::Node | Tool::Go => (vec![dir.join("bin")], Vec::new()),
+        Tool::Python => {
+            let mut env = vec![
+                ("pythonLocation".to_string(), display(&dir)),
+                ("Python_ROOT_DIR".to_string(), display(&dir)),
+                ("Python3_ROOT_DIR".to_string(), display(&dir)),
+                (
+                    "PKG_CONFIG_PATH".to_string(),
+                    display(&dir.join("lib").join("pkgconfig")),
+                ),
+            ];
+            if cfg!(target_os = "linux") {
+                let lib = display(&dir.join("lib"));
+                let value = match std::env::var("LD_LIBRARY_PATH") {
+                    Ok(existing) if !existing.is_empty() => format!("{}:{}", lib, existing),
+                    _ => lib,
+                };
+                env.push(("LD_LIBRARY_PATH".to_string(), value));
+            }
+            (vec![dir.join("bin"), dir.clone()], env)
+        }
+        Tool::Java => {
+            // macOS JDKs keep the actual home inside the bundle
+            let mac_home = dir.join("Contents").join("Home");
+            let home = if mac_home.is_dir() {
+                mac_home
+            } else {
+                dir.clone()
+            };
+            let arch = if cfg!(target_arch = "aarch64") {
+                "ARM64"
+            } else {
+                "X64"
+            };
+            let env = vec![
+                ("JAVA_HOME".to_string(), display(&home)),
+                (
+                    format!("JAVA_HOME_{}_{}", version.major, arch),
+                    display(&home),
+                ),
+            ];
+            (vec![home.join("bin")], env)
+        }
+    };
+    ToolSetup {
+        version,
+        dir,
+        path,
+        env,
+        downloaded,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(version: &str) -> Release {
+        Release {
+            version: parse_version(version).unwrap(),
+            url: String::new(),
+            sha256: None,
+            lts: None,
+        }
+    }
+
+    fn pick(tool: Tool, spec: &str, releases: &[Release]) -> Option<String> {
+        VersionSpec::parse(tool, spec)
+            .unwrap()
+            .pick(releases)
+            .map(|r| r.version.to_string())
+    }
+
+    #[test]
+    fn test_version_ranges() {
+        let releases: Vec<Release> = [
+            "18.19.0",
+            "20.10.0",
+            "20.11.1",
+            "21.6.1",
+            "22.0.0-rc.1",
+            "3.11.7",
+            "3.12.1",
+        ]
+        .iter()
+        .map(|v| release(v))
+        .collect();
+        let node = |spec| pick(Tool::Node, spec, &releases);
+
+        assert_eq!(node("20").as_deref(), Some("20.11.1"));
+        assert_eq!(node("v20.10").as_deref(), Some("20.10.0"));
+        assert_eq!(node("20.x").as_deref(), Some("20.11.1"));
+        assert_eq!(node("^20.1").as_deref(), Some("20.11.1"));
+        assert_eq!(node(">=18 <21").as_deref(), Some("20.11.1"));
+        assert_eq!(node(">= 18 < 20").as_deref(), Some("18.19.0"));
+        assert_eq!(node("18 || 21").as_deref(), Some("21.6.1"));
+        assert_eq!(node("latest").as_deref(), Some("21.6.1"));
+        assert_eq!(node("19"), None);
+
+        // Partial versions pin the minor, not just the major
+        assert_eq!(
+            pick(Tool::Python, "3.11", &releases).as_deref(),
+            Some("3.11.7")
+        );
+        assert_eq!(
+            pick(Tool::Python, "~3.12.0", &releases).as_deref(),
+            Some("3.12.1")
+        );
+
+        assert!(VersionSpec::parse(Tool::Node, "twenty").is_err());
+        assert_eq!(
+            VersionSpec::parse(Tool::Node, "lts/Iron").unwrap(),
+            VersionSpec::Lts(Some("iron".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_lts_and_oldstable() {
+        let mut iron = release("20.11.1");
+        iron.lts = Some("Iron".to_string());
+        let mut hydrogen = release("18.19.0");
+        hydrogen.lts = Some("Hydrogen".to_string());
+        let releases = vec![release("21.6.1"), iron, hydrogen];
+        assert_eq!(
+            pick(Tool::Node, "lts/*", &releases).as_deref(),
+            Some("20.11.1")
+        );
+        assert_eq!(
+            pick(Tool::Node, "lts/hydrogen", &releases).as_deref(),
+            Some("18.19.0")
+        );
+
+        let go: Vec<Release> = ["go1.21.6", "go1.22.0", "go1.22.1", "go1.20"]
+            .iter()
+            .map(|v| release(v))
+            .collect();
+        assert_eq!(pick(Tool::Go, "stable", &go).as_deref(), Some("1.22.1"));
+        assert_eq!(pick(Tool::Go, "oldstable", &go).as_deref(), Some("1.21.6"));
+        assert_eq!(pick(Tool::Go, "1.20", &go).as_deref(), Some("1.20.0"));
+    }
+
+    #[test]
+    fn test_release_indexes() {
+        let linux_x64 = Platform {
+            darwin: false,
+            arm64: false,
+        };
+
+        let node = node_releases(
+            r#"[{"version":"v20.11.1","files":["linux-x64","osx-arm64-tar"],"lts":"Iron"},
+                {"version":"v21.6.1","files":["win-x64-zip"],"lts":false}]"#,
+            linux_x64,
+        )
+        .unwrap();
+        assert_eq!(node.len(), 1);
+        assert_eq!(node[0].lts.as_deref(), Some("Iron"));
+        assert_eq!(
+            node[0].url,
+            "https://nodejs.org/dist/v20.11.1/node-v20.11.1-linux-x64.tar.gz"
+        );
+
+        let go = go_releases(
+            r#"[{"version":"go1.22.1","stable":true,"files":[
+                  {"filename":"go1.22.1.src.tar.gz","os":"","arch":"","kind":"source"},
+                  {"filename":"go1.22.1.linux-amd64.tar.gz","os":"linux","arch":"amd64","kind":"archive","sha256":"abc"}]},
+                {"version":"go1.23rc1","stable":false,"files":[]}]"#,
+            linux_x64,
+        )
+        .unwrap();
+        assert_eq!(go.len(), 1);
+        assert_eq!(
+            go[0].url,
+            "https://dl.google.com/go/go1.22.1.linux-amd64.tar.gz"
+        );
+        assert_eq!(go[0].sha256.as_deref(), Some("abc"));
+
+        let python = python_releases(
+            r#"[{"version":"3.12.1","files":[
+                  {"filename":"python-3.12.1-linux-22.04-x64.tar.gz","arch":"x64","platform":"linux","platform_version":"22.04","download_url":"u2204"},
+                  {"filename":"python-3.12.1-linux-20.04-x64.tar.gz","arch":"x64","platform":"linux","platform_version":"20.04","download_url":"u2004"}]}]"#,
+            linux_x64,
+            Some("22.04"),
+        )
+        .unwrap();
+        assert_eq!(python[0].url, "u2204");
+
+        let java = java_releases(
+            r#"[{"version_data":{"semver":"17.0.10+7"},"binaries":[{"package":{"link":"https://x/jdk.tar.gz","checksum":"def"}}]}]"#,
+        )
+        .unwrap();
+        assert_eq!(java[0].version.major, 17);
+        assert_eq!(java[0].sha256.as_deref(), Some("def"));
+        assert_eq!(pick(Tool::Java, "17", &java).as_deref(), Some("17.0.10+7"));
+    }
+
+    #[test]
+    fn test_version_files() {
+        assert_eq!(
+            version_from_file(Tool::Node, ".nvmrc", "# pinned\nv20.11.1\n").as_deref(),
+            Some("v20.11.1")
+        );
+        assert_eq!(
+            version_from_file(Tool::Node, "package.json", r#"{"engines":{"node":">=18"}}"#)
+                .as_deref(),
+            Some(">=18")
+        );
+        assert_eq!(
+            version_from_file(Tool::Go, "go.mod", "module x\n\ngo 1.21\n").as_deref(),
+            Some("1.21")
+        );
+        assert_eq!(
+            version_from_file(Tool::Go, "go.mod", "go 1.21\ntoolchain go1.22.1\n").as_deref(),
+            Some("1.22.1")
+        );
+        assert_eq!(
+            version_from_file(Tool::Python, ".tool-versions", "nodejs 20\npython 3.12.1\n")
+                .as_deref(),
+            Some("3.12.1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_setup_uses_cached_version() {
+        let Some(platform) = Platform::host() else {
+            return;
+        };
+        let root = tempfile::tempdir().unwrap();
+        let cache = ToolCache::new(root.path());
+        for version in ["20.10.0", "20.11.1", "21.6.1"] {
+            let dir = cache.install_dir(
+                Tool::Node,
+                &parse_version(version).unwrap(),
+                platform.arch(),
+            );
+            fs::create_dir_all(dir.join("bin")).unwrap();
+            fs::write(dir.with_extension("complete"), "").unwrap();
+        }
+        // Installed but never completed
+        fs::create_dir_all(
+            root.path()
+                .join("node")
+                .join("20.12.0")
+                .join(platform.arch()),
+        )
+        .unwrap();
+
+        let inputs = HashMap::from([("node-version".to_string(), "20".to_string())]);
+        let setup = cache
+            .setup(Tool::Node, &inputs, root.path())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(setup.version.to_string(), "20.11.1");
+        assert!(!setup.downloaded);
+        assert_eq!(setup.path, vec![setup.dir.join("bin")]);
+
+        assert!(cache
+            .setup(Tool::Node, &HashMap::new(), root.path())
+            .await
+            .unwrap()
+            .is_none());
+
+        // Offline, check-latest settles for the cache and anything else fails
+        let cache = cache.offline(true);
+        let inputs = HashMap::from([
+            ("node-version".to_string(), "21".to_string()),
+            ("check-latest".to_string(), "true".to_string()),
+        ]);
+        let setup = cache.setup(Tool::Node, &inputs, root.path()).await;
+        assert_eq!(setup.unwrap().unwrap().version.to_string(), "21.6.1");
+        let inputs = HashMap::from([("node-version".to_string(), "22".to_string())]);
+        let error = cache
+            .setup(Tool::Node, &inputs, root.path())
+            .await
+            .unwrap_err();
+        assert!(error.contains("offline"), "{}", error);
+    }
+}
@@ -0,0 +1,457 @@
+//! Experimental WebAssembly runtime.
+//!
+//! Runs `run:` steps made of plain commands (one per line, no pipes, expansions or
+//! redirections) where every command is a WASI preview 1 module. A command `name`
+//! resolves to `name.wasm` in one of the module paths (by default the project's
+//! `.wrkflw/wasm` directory); a command containing `/` or ending in `.wasm` is a path
+//! relative to the working directory.
+//!
+//! Modules only get the capabilities granted to them: the step's arguments and
+//! environment, the job's mounts and any extra read-only directories. WASI preview 1
+//! has no sockets, so network access is impossible.
+
+use crate::container::{BuildOptions, ContainerError, ContainerOutput, ContainerRuntime};
+use crate::shell::{parse_simple_commands, ShellCommand};
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use wasmtime::{Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder, Trap};
+use wasmtime_wasi::p1::{self, WasiP1Ctx};
+use wasmtime_wasi::p2::pipe::MemoryOutputPipe;
+use wasmtime_wasi::{FsPerms, I32Exit, WasiCtxBuilder};
+
+/// Interval between epoch ticks, the granularity of the execution timeout
+const EPOCH_TICK: Duration = Duration::from_millis(100);
+
+/// Cap on captured stdout/stderr per command
+const OUTPUT_CAPACITY: usize = 16 * 1024 * 1024;
+
+/// Capabilities granted to WebAssembly steps
+#[derive(Debug, Clone)]
+pub struct WasmConfig {
+    /// Directories searched for `<command>.wasm`
+    pub module_paths: Vec<PathBuf>,
+    /// Host directories exposed read-only at the same path
+    pub read_only_dirs: Vec<PathBuf>,
+    /// Whether the job's mounts (the workspace) are writable
+    pub writable_mounts: bool,
+    /// Linear memory limit per module instance
+    pub max_memory_mb: u64,
+    /// Wall-clock limit per command
+    pub max_execution_time: Duration,
+}
+
+impl Default for WasmConfig {
+    fn default() -> Self {
+        Self {
+            module_paths: vec![PathBuf::from(".wrkflw/wasm")],
+            read_only_dirs: Vec::new(),
+            writable_mounts: true,
+            max_memory_mb: 1024,
+            max_execution_time: Duration::from_secs(300),
+        }
+    }
+}
+
+struct StepState {
+    wasi: WasiP1Ctx,
+    limits: StoreLimits,
+}
+
+/// Runtime executing steps as WASI modules under wasmtime
+pub struct WasmRuntime {
+    engine: Engine,
+    config: WasmConfig,
+}
+
+impl WasmRuntime {
+    pub fn new(config: WasmConfig) -> Result<Self, ContainerError> {
+        let mut engine_config = wasmtime::Config::new();
+        engine_config.epoch_interruption(true);
+        let engine = Engine::new(&engine_config).map_err(|e| {
+            ContainerError::ContainerStart(format!("Failed to create WebAssembly engine: {}", e))
+        })?;
+
+        // Drive the epoch so running modules can be interrupted; stops with the engine
+        let weak = engine.weak();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(EPOCH_TICK);
+            match weak.upgrade() {
+                Some(engine) => engine.increment_epoch(),
+                None => break,
+            }
+        });
+
+        wrkflw_logging::info("🧩 Initialized experimental WebAssembly runtime");
+        Ok(Self { engine, config })
+    }
+
+    /// Find the module for a command
+    fn resolve_module(
+        &self,
+        name: &str,
+        working_dir: &Path,
+        volumes: &[(&Path, &Path)],
+    ) -> Result<PathBuf, ContainerError> {
+        if name.contains('/') || name.ends_with(".wasm") {
+            let guest = working_dir.join(name);
+            let host = to_host_path(&guest, volumes).unwrap_or(guest);
+            return if host.is_file() {
+                Ok(host)
+            } else {
+                Err(ContainerError::ContainerExecution(format!(
+                    "WebAssembly module not found: {}",
+                    name
+                )))
+            };
+        }
+
+        let file = format!("{}.wasm", name);
+        self.config
+            .module_paths
+            .iter()
+            .map(|dir| dir.join(&file))
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                let searched: Vec<_> = self
+                    .config
+                    .module_paths
+                    .iter()
+                    .map(|dir| dir.display().to_string())
+                    .collect();
+                ContainerError::ContainerExecution(format!(
+                    "No WebAssembly module for '{}': {} not found in {}",
+                    name,
+                    file,
+                    searched.join(", ")
+                ))
+            })
+    }
+
+    /// Run one command to completion, returning its exit code and output
+    fn run_module(
+        &self,
+        module_path: &Path,
+        command: &ShellCommand,
+        env_vars: &[(String, String)],
+        working_dir: &Path,
+        volumes: &[(PathBuf, PathBuf)],
+    ) -> Result<ContainerOutput, ContainerError> {
+        let start_error = |e: wasmtime::Error| {
+            ContainerError::ContainerStart(format!("{}: {:#}", module_path.display(), e))
+        };
+
+        let module = Module::from_file(&self.engine, module_path).map_err(start_error)?;
+
+        let stdout = MemoryOutputPipe::new(OUTPUT_CAPACITY);
+        let stderr = MemoryOutputPipe::new(OUTPUT_CAPACITY);
+        let mut builder = WasiCtxBuilder::new();
+        let args: Vec<&str> = std::iter::once(command.name.as_str())
+            .chain(command.args.iter().map(String::as_str))
+            .collect();
+        builder
+            .args(&args)
+            .envs(env_vars)
+            .env("PWD", working_dir.to_string_lossy())
+            .stdout(stdout.clone())
+            .stderr(stderr.clone());
+
+        let mount_perms = if self.config.writable_mounts {
+            FsPerms::ReadWrite
+        } else {
+            FsPerms::ReadOnly
+        };
+        for (host, guest) in volumes {
+            builder
+                .preopened_dir(host, guest.to_string_lossy(), mount_perms)
+                .map_err(start_error)?;
+        }
+        for dir in &self.config.read_only_dirs {
+            builder
+                .preopened_dir(dir, dir.to_string_lossy(), FsPerms::ReadOnly)
+                .map_err(start_error)?;
+        }
+
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.config.max_memory_mb.saturating_mul(1024 * 1024) as usize)
+            .build();
+        let mut store = Store::new(
+            &self.engine,
+            StepState {
+                wasi: builder.build_p1(),
+                limits,
+            },
+        );
+        store.limiter(|state| &mut state.limits);
+        let ticks = self.config.max_execution_time.as_millis() / EPOCH_TICK.as_millis();
+        store.set_epoch_deadline(ticks.max(1) as u64);
+
+        let mut linker: Linker<StepState> = Linker::new(&self.engine);
+        p1::add_to_linker_sync(&mut linker, |state| &mut state.wasi).map_err(start_error)?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .map_err(start_error)?;
+        let start = instance
+            .get_typed_func::<(), ()>(&mut store, "_start")
+            .map_err(start_error)?;
+
+        let mut extra_stderr = String::new();
+        let exit_code = match start.call(&mut store, ()) {
+            Ok(()) => 0,
+            Err(e) => {
+                if let Some(exit) = e.downcast_ref::<I32Exit>() {
+                    exit.0
+                } else if e.downcast_ref::<Trap>() == Some(&Trap::Interrupt) {
+                    return Err(ContainerError::ContainerExecution(format!(
+                        "'{}' timed out after {} seconds",
+                        command.name,
+                        self.config.max_execution_time.as_secs()
+                    )));
+                } else {
+                    extra_stderr = format!("\n{}: {:#}", command.name, e);
+                    1
+                }
+            }
+        };
+
+        let mut stderr = String::from_utf8_lossy(&stderr.contents()).into_owned();
+        stderr.push_str(&extra_stderr);
+        Ok(ContainerOutput {
+            stdout: String::from_utf8_lossy(&stdout.contents()).into_owned(),
+            stderr,
+            exit_code,
+        })
+    }
+}
+
+/// Map a path inside the job's mounts back to the host
+fn to_host_path(guest: &Path, volumes: &[(&Path, &Path)]) -> Option<PathBuf> {
+    volumes.iter().find_map(|(host, mount)| {
+        guest
+            .strip_prefix(mount)
+            .ok()
+            .map(|relative| host.join(relative))
+    })
+}
+
+/// The script of a `bash -c` style invocation, or the argv itself joined
+fn step_script(cmd: &[&str]) -> String {
+    let is_shell = cmd
+        .first()
+        .is_some_and(|name| matches!(*name, "bash" | "sh"));
+    match cmd.iter().position(|arg| *arg == "-c") {
+        Some(i) if is_shell && i + 1 < cmd.len() => cmd[i + 1].to_string(),
+        _ => crate::shell::join(cmd),
+    }
+}
+
+#[async_trait]
+impl ContainerRuntime for WasmRuntime {
+    async fn run_container(
+        &self,
+        _image: &str,
+        cmd: &[&str],
+        env_vars: &[(&str, &str)],
+        working_dir: &Path,
+        volumes: &[(&Path, &Path)],
+    ) -> Result<ContainerOutput, ContainerError> {
+        let script = step_script(cmd);
+        let commands = parse_simple_commands(&script).ok_or_else(|| {
+            ContainerError::ContainerExecution(
+                "The WebAssembly runtime only runs plain commands, one per line; \
+                 pipes, redirections, expansions and control flow need a shell runtime"
+                    .to_string(),
+            )
+        })?;
+
+        let modules = commands
+            .iter()
+            .map(|command| self.resolve_module(&command.name, working_dir, volumes))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let env_vars: Vec<(String, String)> = env_vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let working_dir = working_dir.to_path_buf();
+        let volumes: Vec<(PathBuf, PathBuf)> = volumes
+            .iter()
+            .map(|(host, guest)| (host.to_path_buf(), guest.to_path_buf()))
+            .collect();
+        let runtime = WasmRuntime {
+            engine: self.engine.clone(),
+            config: self.config.clone(),
+        };
+
+        tokio::task::spawn_blocking(move || {
+            let mut combined = ContainerOutput {
+                stdout: String::new(),
+                stderr: String::new(),
+                exit_code: 0,
+            };
+            // Like `bash -e`, stop at the first failing command
+            for (command, module) in commands.iter().zip(&modules) {
+                wrkflw_logging::debug(&format!(
+                    "🧩 Running {} as {}",
+                    command.name,
+                    module.display()
+                ));
+                let output =
+                    runtime.run_module(module, command, &env_vars, &working_dir, &volumes)?;
+                combined.stdout.push_str(&output.stdout);
+                combined.stderr.push_str(&output.stderr);
+                combined.exit_code = output.exit_code;
+                if output.exit_code != 0 {
+                    break;
+                }
+            }
+            Ok(combined)
+        })
+        .await
+        .map_err(|e| {
+            ContainerError::ContainerExecution(format!("WebAssembly task failed: {}", e))
+        })?
+    }
+
+    async fn pull_image(&self, image: &str) -> Result<(), ContainerError> {
+        wrkflw_logging::info(&format!("🧩 WebAssembly runtime: ignoring image {}", image));
+        Ok(())
+    }
+
+    async fn build_image(
+        &self,
+        dockerfile: &Path,
+        tag: &str,
+        _options: &BuildOptions,
+    ) -> Result<(), ContainerError> {
+        Err(ContainerError::ImageBuild(format!(
+            "Cannot build {} from {}: the WebAssembly runtime does not support images",
+            tag,
+            dockerfile.display()
+        )))
+    }
+
+    async fn prepare_language_environment(
+        &self,
+        language: &str,
+        _version: Option<&str>,
+        _additional_packages: Option<Vec<String>>,
+    ) -> Result<String, ContainerError> {
+        Err(ContainerError::ContainerStart(format!(
+            "The WebAssembly runtime has no {} environment",
+            language
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Prints its first argument and exits with status 3
+    const ECHO_AND_EXIT: &str = r#"
+        (module
+          (import "wasi_snapshot_preview1" "args_sizes_get" (func $sizes (param i32 i32) (result i32)))
+          (import "wasi_snapshot_preview1" "args_get" (func $args (param i32 i32) (result i32)))
+          (import "wasi_snapshot_preview1" "fd_write" (func $write (param i32 i32 i32 i32) (result i32)))
+          (import "wasi_snapshot_preview1" "proc_exit" (func $exit (param i32)))
+          (memory (export "memory") 1)
+          (func (export "_start")
+            (local $arg i32) (local $len i32)
+            (drop (call $sizes (i32.const 0) (i32.const 4)))
+            (drop (call $args (i32.const 16) (i32.const 256)))
+            ;; argv[1] and its length up to the NUL
+            (local.set $arg (i32.load (i32.const 20)))
+            (block $done
+              (loop $scan
+                (br_if $done (i32.eqz (i32.load8_u (i32.add (local.get $arg) (local.get $len)))))
+                (local.set $len (i32.add (local.get $len) (i32.const 1)))
+                (br $scan)))
+            (i32.store (i32.const 8) (local.get $arg))
+            (i32.store (i32.const 12) (local.get $len))
+            (drop (call $write (i32.const 1) (i32.const 8) (i32.const 1) (i32.const 0)))
+            (call $exit (i32.const 3))))
+    "#;
+
+    const SPIN: &str = r#"(module (func (export "_start") (loop $l (br $l))))"#;
+
+    /// A workspace and a runtime finding `modules` in it
+    fn runtime_with(
+        modules: &[(&str, &str)],
+        config: WasmConfig,
+    ) -> (tempfile::TempDir, WasmRuntime) {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, wat) in modules {
+            std::fs::write(dir.path().join(format!("{}.wasm", name)), wat).unwrap();
+        }
+        let runtime = WasmRuntime::new(WasmConfig {
+            module_paths: vec![dir.path().to_path_buf()],
+            ..config
+        })
+        .unwrap();
+        (dir, runtime)
+    }
+
+    #[tokio::test]
+    async fn test_runs_modules_with_arguments() {
+        let (workspace, runtime) = runtime_with(&[("echo3", ECHO_AND_EXIT)], WasmConfig::default());
+        let guest = Path::new("/github/workspace");
+
+        let output = runtime
+            .run_container(
+                "ubuntu-latest",
+                &["bash", "-e", "-c", "echo3 'hello wasm'\necho3 never"],
+                &[],
+                guest,
+                &[(workspace.path(), guest)],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(output.stdout, "hello wasm");
+        assert_eq!(output.exit_code, 3);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_shell_syntax_and_unknown_commands() {
+        let (workspace, runtime) = runtime_with(&[], WasmConfig::default());
+        let guest = Path::new("/github/workspace");
+        let volumes = [(workspace.path(), guest)];
+
+        for script in ["a | b", "missing-tool"] {
+            let result = runtime
+                .run_container("img", &["bash", "-c", script], &[], guest, &volumes)
+                .await;
+            assert!(
+                matches!(result, Err(ContainerError::ContainerExecution(_))),
+                "{}",
+                script
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeout_interrupts_module() {
+        let (workspace, runtime) = runtime_with(
+            &[("spin", SPIN)],
+            WasmConfig {
+                max_execution_time: Duration::from_millis(200),
+                ..WasmConfig::default()
+            },
+        );
+        let guest = Path::new("/github/workspace");
+
+        let result = runtime
+            .run_container(
+                "img",
+                &["bash", "-c", "spin"],
+                &[],
+                guest,
+                &[(workspace.path(), guest)],
+            )
+            .await;
+        assert!(
+            matches!(result, Err(ContainerError::ContainerExecution(msg)) if msg.contains("timed out"))
+        );
+    }
+}
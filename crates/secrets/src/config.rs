@@ -26,6 +26,68 @@ pub struct SecretConfig {
     /// Rate limiting configuration
     #[serde(skip)]
     pub rate_limit: RateLimitConfig,
+
+    /// Ordered providers tried by `get_secret`; empty means only `default_provider`
+    #[serde(default)]
+    pub provider_chain: Vec<ProviderChainEntry>,
+}
+
+/// One link in a provider fallback chain
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProviderChainEntry {
+    /// Name of a configured provider
+    pub provider: String,
+
+    /// Secret name patterns this provider may serve (`*` and `?` wildcards); empty matches all
+    #[serde(default)]
+    pub include: Vec<String>,
+
+    /// Secret name patterns this provider must never serve
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl ProviderChainEntry {
+    /// Create an entry that serves every secret name
+    pub fn new(provider: impl Into<String>) -> Self {
+        Self {
+            provider: provider.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Whether this provider should be consulted for the given secret name
+    pub fn accepts(&self, name: &str) -> bool {
+        let included =
+            self.include.is_empty() || self.include.iter().any(|p| wildcard_match(p, name));
+        included && !self.exclude.iter().any(|p| wildcard_match(p, name))
+    }
+}
+
+/// Match `name` against a pattern where `*` matches any run of characters and `?` one character
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = backtrack {
+            p = star_p + 1;
+            n = star_n + 1;
+            backtrack = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
 }
 
 impl Default for SecretConfig {
@@ -46,6 +108,16 @@ impl Default for SecretConfig {
             },
         );
 
+        // Add default encrypted store provider
+        providers.insert(
+            "encrypted".to_string(),
+            SecretProviderConfig::Encrypted {
+                path: "~/.wrkflw/secrets.enc".to_string(),
+                key_file: None,
+                password_env: None,
+            },
+        );
+
         Self {
             default_provider: "env".to_string(),
             providers,
@@ -54,6 +126,7 @@ impl Default for SecretConfig {
             enable_caching: true,
             cache_ttl_seconds: 300, // 5 minutes
             rate_limit: RateLimitConfig::default(),
+            provider_chain: Vec::new(),
         }
     }
 }
@@ -73,6 +146,18 @@ pub enum SecretProviderConfig {
         /// Path to the secrets file or directory
         path: String,
     },
+
+    /// AES-256-GCM encrypted secret store on disk
+    Encrypted {
+        /// Path to the encrypted store file
+        path: String,
+        /// Optional file holding the 32-byte key (raw or base64); takes precedence over a password
+        #[serde(default)]
+        key_file: Option<String>,
+        /// Environment variable holding the store password (defaults to `WRKFLW_SECRETS_PASSWORD`)
+        #[serde(default)]
+        password_env: Option<String>,
+    },
     // Cloud providers are planned for future implementation
     // /// HashiCorp Vault provider
     // #[cfg(feature = "vault-provider")]
@@ -201,3 +286,54 @@ impl SecretConfig {
         config
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*", "ANYTHING"));
+        assert!(wildcard_match("AWS_*", "AWS_SECRET_KEY"));
+        assert!(wildcard_match("*_TOKEN", "GITHUB_TOKEN"));
+        assert!(wildcard_match("DB_?", "DB_1"));
+        assert!(!wildcard_match("DB_?", "DB_12"));
+        assert!(!wildcard_match("AWS_*", "GCP_KEY"));
+    }
+
+    #[test]
+    fn test_chain_entry_include_exclude() {
+        let entry = ProviderChainEntry {
+            provider: "vault".to_string(),
+            include: vec!["PROD_*".to_string()],
+            exclude: vec!["PROD_LOCAL_*".to_string()],
+        };
+        assert!(entry.accepts("PROD_DB_PASSWORD"));
+        assert!(!entry.accepts("PROD_LOCAL_TOKEN"));
+        assert!(!entry.accepts("DEV_DB_PASSWORD"));
+        assert!(ProviderChainEntry::new("env").accepts("ANY_NAME"));
+    }
+
+    #[test]
+    fn test_provider_chain_from_yaml() {
+        let yaml = r#"
+default_provider: env
+providers:
+  env:
+    type: environment
+    prefix: null
+enable_masking: true
+timeout_seconds: 30
+enable_caching: false
+cache_ttl_seconds: 0
+provider_chain:
+  - provider: env
+    exclude: ["VAULT_*"]
+  - provider: file
+"#;
+        let config: SecretConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.provider_chain.len(), 2);
+        assert_eq!(config.provider_chain[0].exclude, vec!["VAULT_*"]);
+        assert!(config.provider_chain[1].include.is_empty());
+    }
+}
@@ -87,6 +87,7 @@
 //!         enable_caching: true,
 //!         cache_ttl_seconds: 300,
 //!         rate_limit: Default::default(),
+//!         provider_chain: Vec::new(),
 //!     };
 //!     
 //!     let manager = SecretManager::new(config).await?;
@@ -130,6 +131,21 @@
 //! };
 //! ```
 //!
+//! ### Encrypted Store
+//!
+//! Secrets encrypted at rest with AES-256-GCM. The key comes from a key file or is
+//! derived from a password (read from `WRKFLW_SECRETS_PASSWORD` by default):
+//!
+//! ```rust
+//! use wrkflw_secrets::SecretProviderConfig;
+//!
+//! let provider = SecretProviderConfig::Encrypted {
+//!     path: "~/.wrkflw/secrets.enc".to_string(),
+//!     key_file: None,
+//!     password_env: Some("MYAPP_SECRETS_PASSWORD".to_string()),
+//! };
+//! ```
+//!
 //! ### File-based Storage
 //!
 //! Supports JSON, YAML, and environment file formats:
@@ -162,7 +178,7 @@ pub mod storage;
 pub mod substitution;
 pub mod validation;
 
-pub use config::{SecretConfig, SecretProviderConfig};
+pub use config::{ProviderChainEntry, SecretConfig, SecretProviderConfig};
 pub use error::{SecretError, SecretResult};
 pub use manager::SecretManager;
 pub use masking::SecretMasker;
@@ -180,7 +196,6 @@ pub mod prelude {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use uuid;
 
     #[tokio::test]
     async fn test_basic_secret_management() {
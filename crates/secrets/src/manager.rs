@@ -1,6 +1,9 @@
 use crate::{
     config::{SecretConfig, SecretProviderConfig},
-    providers::{env::EnvironmentProvider, file::FileProvider, SecretProvider, SecretValue},
+    providers::{
+        encrypted::EncryptedProvider, env::EnvironmentProvider, file::FileProvider, SecretProvider,
+        SecretValue,
+    },
     rate_limit::RateLimiter,
     validation::{validate_provider_name, validate_secret_name},
     SecretError, SecretResult,
@@ -39,6 +42,15 @@ impl SecretManager {
                     Box::new(EnvironmentProvider::new(prefix.clone()))
                 }
                 SecretProviderConfig::File { path } => Box::new(FileProvider::new(path.clone())),
+                SecretProviderConfig::Encrypted {
+                    path,
+                    key_file,
+                    password_env,
+                } => Box::new(EncryptedProvider::from_config(
+                    path,
+                    key_file.as_deref(),
+                    password_env.as_deref(),
+                )),
                 // Cloud providers are planned for future implementation
                 // #[cfg(feature = "vault-provider")]
                 // SecretProviderConfig::Vault { url, auth, mount_path } => {
@@ -68,11 +80,44 @@ impl SecretManager {
         Self::new(SecretConfig::default()).await
     }
 
-    /// Get a secret by name using the default provider
+    /// Get a secret by name using the provider chain, or the default provider
+    /// when no chain is configured.
+    ///
+    /// Chain entries are tried in order, skipping those whose patterns reject
+    /// the name. The first provider that has the secret serves it; its name is
+    /// recorded in the `provider` metadata entry.
     pub async fn get_secret(&self, name: &str) -> SecretResult<SecretValue> {
         validate_secret_name(name)?;
-        self.get_secret_from_provider(&self.config.default_provider, name)
-            .await
+
+        if self.config.provider_chain.is_empty() {
+            return self
+                .get_secret_from_provider(&self.config.default_provider, name)
+                .await;
+        }
+
+        let mut first_error = None;
+        for entry in &self.config.provider_chain {
+            if !entry.accepts(name) {
+                continue;
+            }
+
+            match self.get_secret_from_provider(&entry.provider, name).await {
+                Ok(secret) => return Ok(secret),
+                Err(SecretError::NotFound { .. }) => {}
+                Err(e) => {
+                    tracing::debug!(
+                        "Provider '{}' failed for secret '{}', trying next: {}",
+                        entry.provider,
+                        name,
+                        e
+                    );
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        // Surface a real provider failure over a plain miss
+        Err(first_error.unwrap_or_else(|| SecretError::not_found(name)))
     }
 
     /// Get a secret from a specific provider
@@ -92,13 +137,20 @@ impl SecretManager {
         if self.config.enable_caching {
             let cache_key = format!("{}:{}", provider_name, name);
 
-            {
+            let expired = {
                 let cache = self.cache.read().await;
-                if let Some(cached) = cache.get(&cache_key) {
-                    if chrono::Utc::now() < cached.expires_at {
+                match cache.get(&cache_key) {
+                    Some(cached) if chrono::Utc::now() < cached.expires_at => {
                         return Ok(cached.value.clone());
                     }
+                    Some(_) => true,
+                    None => false,
                 }
+            };
+
+            // Drop the stale entry so its value is wiped now rather than on overwrite
+            if expired {
+                self.cache.write().await.remove(&cache_key);
             }
         }
 
@@ -108,8 +160,11 @@ impl SecretManager {
             .get(provider_name)
             .ok_or_else(|| SecretError::provider_not_found(provider_name))?;
 
-        // Get secret from provider
-        let secret = provider.get_secret(name).await?;
+        // Get secret from provider, recording which one served it
+        let mut secret = provider.get_secret(name).await?;
+        secret
+            .metadata
+            .insert("provider".to_string(), provider_name.to_string());
 
         // Cache the result if caching is enabled
         if self.config.enable_caching {
@@ -129,6 +184,50 @@ impl SecretManager {
         Ok(secret)
     }
 
+    /// Store a secret in a writable provider, invalidating any cached value
+    pub async fn set_secret_in_provider(
+        &self,
+        provider_name: &str,
+        name: &str,
+        value: &str,
+    ) -> SecretResult<()> {
+        validate_provider_name(provider_name)?;
+        validate_secret_name(name)?;
+
+        let provider = self
+            .providers
+            .get(provider_name)
+            .ok_or_else(|| SecretError::provider_not_found(provider_name))?;
+
+        provider.set_secret(name, value).await?;
+        self.invalidate_cached(provider_name, name).await;
+        Ok(())
+    }
+
+    /// Delete a secret from a writable provider, returning whether it existed
+    pub async fn delete_secret_from_provider(
+        &self,
+        provider_name: &str,
+        name: &str,
+    ) -> SecretResult<bool> {
+        validate_provider_name(provider_name)?;
+        validate_secret_name(name)?;
+
+        let provider = self
+            .providers
+            .get(provider_name)
+            .ok_or_else(|| SecretError::provider_not_found(provider_name))?;
+
+        let removed = provider.delete_secret(name).await?;
+        self.invalidate_cached(provider_name, name).await;
+        Ok(removed)
+    }
+
+    async fn invalidate_cached(&self, provider_name: &str, name: &str) {
+        let mut cache = self.cache.write().await;
+        cache.remove(&format!("{}:{}", provider_name, name));
+    }
+
     /// List all available secrets from all providers
     pub async fn list_all_secrets(&self) -> SecretResult<HashMap<String, Vec<String>>> {
         let mut all_secrets = HashMap::new();
@@ -160,6 +259,13 @@ impl SecretManager {
         results
     }
 
+    /// Remove expired entries from the cache, wiping their values
+    pub async fn purge_expired(&self) {
+        let now = chrono::Utc::now();
+        let mut cache = self.cache.write().await;
+        cache.retain(|_, cached| now < cached.expires_at);
+    }
+
     /// Clear the cache
     pub async fn clear_cache(&self) {
         let mut cache = self.cache.write().await;
@@ -195,6 +301,7 @@ mod tests {
         let manager = manager.unwrap();
         assert!(manager.has_provider("env"));
         assert!(manager.has_provider("file"));
+        assert!(manager.has_provider("encrypted"));
     }
 
     #[tokio::test]
@@ -253,6 +360,145 @@ mod tests {
         assert!(result3.is_err());
     }
 
+    #[tokio::test]
+    async fn test_secret_manager_encrypted_provider_write() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let store_path = temp_dir.path().join("store.enc");
+        let store_path = store_path.to_string_lossy().to_string();
+        let key_file = temp_dir.path().join("store.key");
+        let key_file = key_file.to_string_lossy().to_string();
+        crate::providers::encrypted::generate_key_file(&key_file).unwrap();
+
+        let mut config = SecretConfig::default();
+        config.providers.insert(
+            "vault".to_string(),
+            SecretProviderConfig::Encrypted {
+                path: store_path.clone(),
+                key_file: Some(key_file.clone()),
+                password_env: None,
+            },
+        );
+        let manager = SecretManager::new(config).await.unwrap();
+
+        // Writes need an initialized store
+        assert!(manager
+            .set_secret_in_provider("vault", "DEPLOY_KEY", "v1")
+            .await
+            .is_err());
+
+        EncryptedProvider::from_config(&store_path, Some(&key_file), None)
+            .init(false)
+            .await
+            .unwrap();
+
+        manager
+            .set_secret_in_provider("vault", "DEPLOY_KEY", "v1")
+            .await
+            .unwrap();
+        let first = manager
+            .get_secret_from_provider("vault", "DEPLOY_KEY")
+            .await
+            .unwrap();
+        assert_eq!(first.value(), "v1");
+
+        // Overwriting must not serve the stale cached value
+        manager
+            .set_secret_in_provider("vault", "DEPLOY_KEY", "v2")
+            .await
+            .unwrap();
+        let second = manager
+            .get_secret_from_provider("vault", "DEPLOY_KEY")
+            .await
+            .unwrap();
+        assert_eq!(second.value(), "v2");
+
+        // Read-only providers reject writes
+        assert!(manager
+            .set_secret_in_provider("env", "DEPLOY_KEY", "v1")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_secret_manager_provider_chain() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let secrets_file = temp_dir.path().join("secrets.json");
+        std::fs::write(
+            &secrets_file,
+            r#"{"CHAIN_FILE_ONLY": "from_file", "CHAIN_SHARED": "file_value"}"#,
+        )
+        .unwrap();
+
+        let shared = format!("CHAIN_SHARED_{}", std::process::id());
+        std::env::set_var("CHAIN_SHARED", "env_value");
+        std::env::set_var(&shared, "unused");
+
+        let mut config = SecretConfig {
+            enable_caching: false,
+            ..Default::default()
+        };
+        config.providers.insert(
+            "file".to_string(),
+            SecretProviderConfig::File {
+                path: secrets_file.to_string_lossy().to_string(),
+            },
+        );
+        config.provider_chain = vec![
+            crate::config::ProviderChainEntry {
+                provider: "env".to_string(),
+                include: vec![],
+                exclude: vec!["CHAIN_SHARED".to_string()],
+            },
+            crate::config::ProviderChainEntry::new("file"),
+        ];
+        let manager = SecretManager::new(config).await.unwrap();
+
+        // Falls through to the file provider when env has nothing
+        let secret = manager.get_secret("CHAIN_FILE_ONLY").await.unwrap();
+        assert_eq!(secret.value(), "from_file");
+        assert_eq!(secret.metadata.get("provider"), Some(&"file".to_string()));
+
+        // Excluded names skip env even though it has the value
+        let secret = manager.get_secret("CHAIN_SHARED").await.unwrap();
+        assert_eq!(secret.value(), "file_value");
+
+        // Env serves names it is allowed to
+        let secret = manager.get_secret(&shared).await.unwrap();
+        assert_eq!(secret.metadata.get("provider"), Some(&"env".to_string()));
+
+        assert!(matches!(
+            manager.get_secret("CHAIN_MISSING_EVERYWHERE").await,
+            Err(SecretError::NotFound { .. })
+        ));
+
+        std::env::remove_var("CHAIN_SHARED");
+        std::env::remove_var(&shared);
+    }
+
+    #[tokio::test]
+    async fn test_secret_manager_drops_expired_entries() {
+        let test_secret_name = format!("EXPIRY_TEST_SECRET_{}", std::process::id());
+        std::env::set_var(&test_secret_name, "short_lived");
+
+        let config = SecretConfig {
+            enable_caching: true,
+            cache_ttl_seconds: 0,
+            ..Default::default()
+        };
+        let manager = SecretManager::new(config).await.unwrap();
+
+        manager
+            .get_secret_from_provider("env", &test_secret_name)
+            .await
+            .unwrap();
+        assert_eq!(manager.cache.read().await.len(), 1);
+
+        manager.purge_expired().await;
+        assert!(manager.cache.read().await.is_empty());
+
+        std::env::remove_var(&test_secret_name);
+    }
+
     #[tokio::test]
     async fn test_secret_manager_health_check() {
         let manager = SecretManager::default().await.unwrap();
@@ -0,0 +1,521 @@
+use crate::{
+    storage::{EncryptedSecretStore, KeyDerivation},
+    validation::{validate_secret_name, validate_secret_value},
+    SecretError, SecretProvider, SecretResult, SecretValue,
+};
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use zeroize::Zeroize;
+
+/// Environment variable consulted for the store password when none is configured
+pub const DEFAULT_PASSWORD_ENV: &str = "WRKFLW_SECRETS_PASSWORD";
+
+/// Where the encryption key for an encrypted store comes from
+#[derive(Debug, Clone)]
+pub enum KeySource {
+    /// A file containing the raw 32-byte key, or its base64 encoding
+    KeyFile(String),
+    /// A password that is stretched into a key with PBKDF2 and the store salt
+    Password(String),
+    /// An environment variable holding the password
+    PasswordEnv(String),
+}
+
+/// Provider backed by an AES-256-GCM encrypted secret store on disk
+pub struct EncryptedProvider {
+    path: String,
+    key_source: KeySource,
+    /// Derived key cached per store salt, so PBKDF2 only runs once
+    derived_key: Mutex<Option<(String, [u8; 32])>>,
+}
+
+impl EncryptedProvider {
+    /// Create a new encrypted provider
+    pub fn new(path: impl Into<String>, key_source: KeySource) -> Self {
+        Self {
+            path: path.into(),
+            key_source,
+            derived_key: Mutex::new(None),
+        }
+    }
+
+    /// Create a provider from the `Encrypted` provider configuration.
+    /// A key file takes precedence over the password environment variable.
+    pub fn from_config(path: &str, key_file: Option<&str>, password_env: Option<&str>) -> Self {
+        let key_source = match key_file {
+            Some(key_file) => KeySource::KeyFile(key_file.to_string()),
+            None => {
+                KeySource::PasswordEnv(password_env.unwrap_or(DEFAULT_PASSWORD_ENV).to_string())
+            }
+        };
+        Self::new(path, key_source)
+    }
+
+    /// Expand tilde in path
+    pub fn expand_path(&self) -> String {
+        expand_tilde(&self.path)
+    }
+
+    /// Check whether the store file exists
+    pub fn exists(&self) -> bool {
+        Path::new(&self.expand_path()).exists()
+    }
+
+    /// Create an empty store on disk. Fails if one already exists unless `force` is set.
+    pub async fn init(&self, force: bool) -> SecretResult<()> {
+        if self.exists() && !force {
+            return Err(SecretError::invalid_config(format!(
+                "Encrypted secret store already exists at {}",
+                self.expand_path()
+            )));
+        }
+
+        let (mut store, _) = EncryptedSecretStore::new()?;
+        // Make sure the key material is usable before writing anything
+        let key = self.resolve_key(&store)?;
+        store.set_key_check(&key)?;
+        self.save_store(&store).await
+    }
+
+    /// Add or replace a secret in the store
+    pub async fn set(&self, name: &str, value: &str) -> SecretResult<()> {
+        validate_secret_name(name)?;
+        validate_secret_value(value)?;
+
+        let mut store = self
+            .load_store()
+            .await?
+            .ok_or_else(|| self.missing_store())?;
+        let key = self.resolve_key(&store)?;
+
+        // Refuse to mix keys: the key must be the one the store was created with
+        store.verify_key(&key)?;
+
+        if store.needs_migration() {
+            store.migrate(&key)?;
+        }
+        // Stores written before key checks take the key they're first written with
+        if !store.has_key_check() {
+            store.set_key_check(&key)?;
+        }
+
+        store.add_secret(&key, name, value)?;
+        self.save_store(&store).await
+    }
+
+    /// Remove a secret from the store, returning whether it existed
+    pub async fn remove(&self, name: &str) -> SecretResult<bool> {
+        let mut store = self
+            .load_store()
+            .await?
+            .ok_or_else(|| self.missing_store())?;
+        let removed = store.remove_secret(name);
+        if removed {
+            self.save_store(&store).await?;
+        }
+        Ok(removed)
+    }
+
+    /// Load the store from disk, or `None` if it has not been initialized
+    async fn load_store(&self) -> SecretResult<Option<EncryptedSecretStore>> {
+        let expanded_path = self.expand_path();
+        if !Path::new(&expanded_path).exists() {
+            return Ok(None);
+        }
+        EncryptedSecretStore::load_from_file(&expanded_path)
+            .await
+            .map(Some)
+    }
+
+    /// Write the store to disk with owner-only permissions
+    async fn save_store(&self, store: &EncryptedSecretStore) -> SecretResult<()> {
+        let expanded_path = self.expand_path();
+        if let Some(parent) = Path::new(&expanded_path).parent() {
+            if !parent.as_os_str().is_empty() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+        }
+
+        let json = store.to_json()?;
+        tokio::task::spawn_blocking(move || write_owner_only(Path::new(&expanded_path), &json))
+            .await
+            .map_err(|e| SecretError::internal(format!("Failed to write store: {}", e)))??;
+
+        Ok(())
+    }
+
+    /// Resolve the encryption key for the given store
+    fn resolve_key(&self, store: &EncryptedSecretStore) -> SecretResult<[u8; 32]> {
+        match &self.key_source {
+            KeySource::KeyFile(key_file) => read_key_file(&expand_tilde(key_file)),
+            KeySource::Password(password) => self.derive_cached(store, password),
+            KeySource::PasswordEnv(var) => {
+                let password = std::env::var(var).map_err(|_| {
+                    SecretError::auth_failed(
+                        "encrypted",
+                        format!("no key file configured and {} is not set", var),
+                    )
+                })?;
+                self.derive_cached(store, &password)
+            }
+        }
+    }
+
+    fn derive_cached(
+        &self,
+        store: &EncryptedSecretStore,
+        password: &str,
+    ) -> SecretResult<[u8; 32]> {
+        let salt = store.salt().to_string();
+        let mut cached = self
+            .derived_key
+            .lock()
+            .map_err(|_| SecretError::internal("Encrypted provider key cache poisoned"))?;
+
+        if let Some((cached_salt, key)) = cached.as_ref() {
+            if *cached_salt == salt {
+                return Ok(*key);
+            }
+        }
+
+        let key = KeyDerivation::derive_key_from_password(
+            password,
+            &store.salt_bytes()?,
+            KeyDerivation::DEFAULT_ITERATIONS,
+        );
+        *cached = Some((salt, key));
+        Ok(key)
+    }
+
+    fn missing_store(&self) -> SecretError {
+        SecretError::invalid_config(format!(
+            "Encrypted secret store not found at {}. Run 'wrkflw secrets init' first",
+            self.expand_path()
+        ))
+    }
+}
+
+impl Drop for EncryptedProvider {
+    fn drop(&mut self) {
+        if let Ok(cached) = self.derived_key.get_mut() {
+            if let Some((_, key)) = cached.as_mut() {
+                key.zeroize();
+            }
+        }
+        if let KeySource::Password(password) = &mut self.key_source {
+            password.zeroize();
+        }
+    }
+}
+
+#[async_trait]
+impl SecretProvider for EncryptedProvider {
+    async fn get_secret(&self, name: &str) -> SecretResult<SecretValue> {
+        let mut store = match self.load_store().await? {
+            Some(store) if store.has_secret(name) => store,
+            _ => return Err(SecretError::not_found(name)),
+        };
+
+        let key = self.resolve_key(&store)?;
+        store.verify_key(&key)?;
+        let value = store.get_secret(&key, name)?;
+        validate_secret_value(&value)?;
+
+        // Upgrade stores written with the old shared-nonce layout on first read
+        if store.needs_migration() {
+            match store.migrate(&key) {
+                Ok(()) => {
+                    if let Err(e) = self.save_store(&store).await {
+                        tracing::warn!("Failed to save migrated secret store: {}", e);
+                    }
+                }
+                Err(e) => tracing::warn!("Failed to migrate secret store: {}", e),
+            }
+        }
+
+        let mut metadata = HashMap::new();
+        metadata.insert("source".to_string(), "encrypted".to_string());
+        metadata.insert("file_path".to_string(), self.expand_path());
+
+        Ok(SecretValue::with_metadata(value, metadata))
+    }
+
+    async fn set_secret(&self, name: &str, value: &str) -> SecretResult<()> {
+        self.set(name, value).await
+    }
+
+    async fn delete_secret(&self, name: &str) -> SecretResult<bool> {
+        self.remove(name).await
+    }
+
+    async fn list_secrets(&self) -> SecretResult<Vec<String>> {
+        Ok(self
+            .load_store()
+            .await?
+            .map(|store| store.list_secrets())
+            .unwrap_or_default())
+    }
+
+    fn name(&self) -> &str {
+        "encrypted"
+    }
+}
+
+/// Read a key file containing either 32 raw bytes or a base64-encoded key
+fn read_key_file(path: &str) -> SecretResult<[u8; 32]> {
+    let content = std::fs::read(path)?;
+
+    let bytes = if content.len() == 32 {
+        content
+    } else {
+        let text = String::from_utf8_lossy(&content);
+        general_purpose::STANDARD
+            .decode(text.trim())
+            .map_err(|e| SecretError::EncryptionError(format!("Invalid key file: {}", e)))?
+    };
+
+    bytes.try_into().map_err(|_| {
+        SecretError::EncryptionError("Key file must contain a 32-byte key".to_string())
+    })
+}
+
+/// Generate a new random key and write it base64-encoded to `path`
+pub fn generate_key_file(path: &str) -> SecretResult<()> {
+    let path = expand_tilde(path);
+    if let Some(parent) = Path::new(&path).parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+
+    let key = KeyDerivation::generate_random_key();
+    write_owner_only(Path::new(&path), &general_purpose::STANDARD.encode(key))?;
+
+    Ok(())
+}
+
+/// Write `contents` to `path` without it ever being readable by other users.
+///
+/// The data goes to a temporary file created with mode 0600 next to `path`,
+/// which is then renamed over it, so readers see either the old file or the
+/// complete new one.
+fn write_owner_only(path: &Path, contents: &str) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let tmp_path = path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()));
+
+    // A leftover from an interrupted write may have looser permissions
+    let _ = std::fs::remove_file(&tmp_path);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let result = options.open(&tmp_path).and_then(|mut file| {
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()
+    });
+    if let Err(e) = result.and_then(|_| std::fs::rename(&tmp_path, path)) {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+fn expand_tilde(path: &str) -> String {
+    if let Some(rest) = path.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest).to_string_lossy().to_string();
+        }
+    }
+    path.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_encrypted_provider_password_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("secrets.enc");
+        let path = path.to_string_lossy().to_string();
+
+        let provider = EncryptedProvider::new(&path, KeySource::Password("hunter2".to_string()));
+        provider.init(false).await.unwrap();
+        provider.set("API_KEY", "encrypted_value").await.unwrap();
+
+        // A fresh provider with the same password can read the value back
+        let reader = EncryptedProvider::new(&path, KeySource::Password("hunter2".to_string()));
+        let secret = reader.get_secret("API_KEY").await.unwrap();
+        assert_eq!(secret.value(), "encrypted_value");
+        assert_eq!(
+            secret.metadata.get("source"),
+            Some(&"encrypted".to_string())
+        );
+
+        // The wrong password fails to decrypt
+        let wrong = EncryptedProvider::new(&path, KeySource::Password("wrong".to_string()));
+        assert!(matches!(
+            wrong.get_secret("API_KEY").await,
+            Err(SecretError::EncryptionError(_))
+        ));
+
+        // Plaintext never touches the disk
+        let on_disk = std::fs::read_to_string(&path).unwrap();
+        assert!(!on_disk.contains("encrypted_value"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_encrypted_provider_writes_owner_only_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("secrets.enc");
+        let key_path = temp_dir.path().join("secrets.key");
+
+        generate_key_file(&key_path.to_string_lossy()).unwrap();
+        let provider = EncryptedProvider::from_config(
+            &path.to_string_lossy(),
+            Some(&key_path.to_string_lossy()),
+            None,
+        );
+        provider.init(false).await.unwrap();
+        provider.set("TOKEN", "private_value").await.unwrap();
+
+        for file in [&path, &key_path] {
+            let mode = std::fs::metadata(file).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600, "{} is not owner-only", file.display());
+        }
+
+        // No temporary files are left next to the store
+        let entries: Vec<_> = std::fs::read_dir(temp_dir.path()).unwrap().collect();
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_provider_key_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("secrets.enc");
+        let key_path = temp_dir.path().join("secrets.key");
+        let key_path = key_path.to_string_lossy().to_string();
+
+        generate_key_file(&key_path).unwrap();
+        let provider =
+            EncryptedProvider::from_config(&path.to_string_lossy(), Some(key_path.as_str()), None);
+        provider.init(false).await.unwrap();
+        assert!(provider.init(false).await.is_err());
+
+        provider.set("TOKEN", "value_from_key_file").await.unwrap();
+        assert_eq!(
+            provider.get_secret("TOKEN").await.unwrap().value(),
+            "value_from_key_file"
+        );
+        assert_eq!(provider.list_secrets().await.unwrap(), vec!["TOKEN"]);
+
+        assert!(provider.remove("TOKEN").await.unwrap());
+        assert!(matches!(
+            provider.get_secret("TOKEN").await,
+            Err(SecretError::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_provider_migrates_legacy_store() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("legacy.enc");
+        let key_path = temp_dir.path().join("legacy.key");
+        let key_path = key_path.to_string_lossy().to_string();
+        generate_key_file(&key_path).unwrap();
+        let key = read_key_file(&key_path).unwrap();
+
+        // Write a version 1 store that used one nonce for every value
+        let nonce = [3u8; 12];
+        let cipher = {
+            use aes_gcm::{aead::Aead, Aes256Gcm, Key, KeyInit, Nonce};
+            Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+                .encrypt(Nonce::from_slice(&nonce), b"old_secret".as_ref())
+                .unwrap()
+        };
+        let mut legacy = HashMap::new();
+        legacy.insert("OLD".to_string(), general_purpose::STANDARD.encode(cipher));
+        EncryptedSecretStore::from_data(
+            legacy,
+            general_purpose::STANDARD.encode([1u8; 32]),
+            general_purpose::STANDARD.encode(nonce),
+        )
+        .save_to_file(&path.to_string_lossy())
+        .await
+        .unwrap();
+
+        let provider =
+            EncryptedProvider::from_config(&path.to_string_lossy(), Some(&key_path), None);
+        assert_eq!(
+            provider.get_secret("OLD").await.unwrap().value(),
+            "old_secret"
+        );
+
+        // The read rewrote the file in the current format
+        let reloaded = EncryptedSecretStore::load_from_file(&path.to_string_lossy())
+            .await
+            .unwrap();
+        assert!(!reloaded.needs_migration());
+        assert_eq!(reloaded.get_secret(&key, "OLD").unwrap(), "old_secret");
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_provider_rejects_other_keys() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("secrets.enc");
+        let path = path.to_string_lossy().to_string();
+
+        let provider = EncryptedProvider::new(&path, KeySource::Password("hunter2".to_string()));
+        provider.init(false).await.unwrap();
+
+        // Even an empty store only takes the key it was created with
+        let wrong = EncryptedProvider::new(&path, KeySource::Password("wrong".to_string()));
+        assert!(matches!(
+            wrong.set("API_KEY", "value").await,
+            Err(SecretError::EncryptionError(_))
+        ));
+        provider.set("API_KEY", "value").await.unwrap();
+        assert!(matches!(
+            wrong.set("OTHER", "value").await,
+            Err(SecretError::EncryptionError(_))
+        ));
+        assert!(matches!(
+            wrong.get_secret("API_KEY").await,
+            Err(SecretError::EncryptionError(_))
+        ));
+        assert_eq!(provider.list_secrets().await.unwrap(), vec!["API_KEY"]);
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_provider_missing_store() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("missing.enc");
+        let provider = EncryptedProvider::new(
+            path.to_string_lossy(),
+            KeySource::Password("unused".to_string()),
+        );
+
+        assert!(matches!(
+            provider.get_secret("ANY").await,
+            Err(SecretError::NotFound { .. })
+        ));
+        assert!(provider.set("ANY", "value").await.is_err());
+    }
+}
@@ -1,8 +1,11 @@
 use crate::{SecretError, SecretResult};
 use async_trait::async_trait;
-use serde::{Deserialize, Serialize};
+use serde::{Serialize, Serializer};
 use std::collections::HashMap;
+use std::fmt;
+use zeroize::Zeroizing;
 
+pub mod encrypted;
 pub mod env;
 pub mod file;
 
@@ -19,11 +22,22 @@ pub mod file;
 // #[cfg(feature = "gcp-provider")]
 // pub mod gcp;
 
-/// A secret value with metadata
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Placeholder written in place of the raw value by `Debug` and `Serialize`
+pub const REDACTED: &str = "***";
+
+/// A secret value with metadata.
+///
+/// The value is wiped from memory when dropped, and is never exposed through
+/// `Debug` or `Serialize`; use [`SecretValue::value`] to read it.
+///
+/// Serialization is write-only: it produces a redacted view for reports and
+/// APIs, so the type deliberately does not implement `Deserialize`. Rebuild a
+/// secret from its source with [`SecretValue::new`] instead.
+#[derive(Clone, Serialize)]
 pub struct SecretValue {
     /// The actual secret value
-    value: String,
+    #[serde(serialize_with = "serialize_redacted")]
+    value: Zeroizing<String>,
     /// Optional metadata about the secret
     pub metadata: HashMap<String, String>,
     /// When this secret was retrieved (for caching)
@@ -34,7 +48,7 @@ impl SecretValue {
     /// Create a new secret value
     pub fn new(value: impl Into<String>) -> Self {
         Self {
-            value: value.into(),
+            value: Zeroizing::new(value.into()),
             metadata: HashMap::new(),
             retrieved_at: chrono::Utc::now(),
         }
@@ -43,7 +57,7 @@ impl SecretValue {
     /// Create a new secret value with metadata
     pub fn with_metadata(value: impl Into<String>, metadata: HashMap<String, String>) -> Self {
         Self {
-            value: value.into(),
+            value: Zeroizing::new(value.into()),
             metadata,
             retrieved_at: chrono::Utc::now(),
         }
@@ -62,6 +76,23 @@ impl SecretValue {
     }
 }
 
+impl fmt::Debug for SecretValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretValue")
+            .field("value", &REDACTED)
+            .field("metadata", &self.metadata)
+            .field("retrieved_at", &self.retrieved_at)
+            .finish()
+    }
+}
+
+fn serialize_redacted<S: Serializer>(
+    _value: &Zeroizing<String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(REDACTED)
+}
+
 /// Trait for secret providers
 #[async_trait]
 pub trait SecretProvider: Send + Sync {
@@ -75,6 +106,20 @@ pub trait SecretProvider: Send + Sync {
         ))
     }
 
+    /// Store a secret (optional, for writable providers)
+    async fn set_secret(&self, _name: &str, _value: &str) -> SecretResult<()> {
+        Err(SecretError::internal(
+            "set_secret not supported by this provider",
+        ))
+    }
+
+    /// Delete a secret, returning whether it existed (optional, for writable providers)
+    async fn delete_secret(&self, _name: &str) -> SecretResult<bool> {
+        Err(SecretError::internal(
+            "delete_secret not supported by this provider",
+        ))
+    }
+
     /// Check if the provider is healthy/accessible
     async fn health_check(&self) -> SecretResult<()> {
         // Default implementation tries to get a non-existent secret
@@ -89,3 +134,35 @@ pub trait SecretProvider: Send + Sync {
     /// Get the provider name
     fn name(&self) -> &str;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_value_does_not_leak() {
+        let secret = SecretValue::new("super_secret_value");
+        assert_eq!(secret.value(), "super_secret_value");
+
+        let debug = format!("{:?}", secret);
+        assert!(!debug.contains("super_secret_value"));
+        assert!(debug.contains(REDACTED));
+
+        let json = serde_json::to_string(&secret).unwrap();
+        assert!(!json.contains("super_secret_value"));
+    }
+
+    #[test]
+    fn test_secret_value_serializes_redacted_view() {
+        let mut metadata = HashMap::new();
+        metadata.insert("source".to_string(), "env".to_string());
+        let secret = SecretValue::with_metadata("super_secret_value", metadata);
+
+        // The serialized form is a redacted view that keeps the metadata, and
+        // cannot be read back as a secret since `SecretValue` has no `Deserialize`
+        let json: serde_json::Value = serde_json::to_value(&secret).unwrap();
+        assert_eq!(json["value"], REDACTED);
+        assert_eq!(json["metadata"]["source"], "env");
+        assert!(json["retrieved_at"].is_string());
+    }
+}
@@ -7,15 +7,44 @@ use base64::{engine::general_purpose, Engine as _};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Current on-disk format version of [`EncryptedSecretStore`]
+pub const STORE_FORMAT_VERSION: u32 = 2;
+
+/// Known plaintext sealed as a store's key check
+const KEY_CHECK_PLAINTEXT: &str = "wrkflw-secrets-key-check";
+
+/// Format version assumed for stores written before versioning existed
+fn legacy_format_version() -> u32 {
+    1
+}
+
+/// A single encrypted value as stored on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum StoredSecret {
+    /// Version 2: ciphertext with its own random nonce (both base64 encoded)
+    Sealed { nonce: String, ciphertext: String },
+    /// Version 1: ciphertext encrypted with the store-wide nonce
+    Legacy(String),
+}
+
 /// Encrypted secret storage for sensitive data at rest
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EncryptedSecretStore {
-    /// Encrypted secrets map (base64 encoded)
-    secrets: HashMap<String, String>,
+    /// Storage format version
+    #[serde(default = "legacy_format_version")]
+    version: u32,
+    /// Encrypted secrets map
+    secrets: HashMap<String, StoredSecret>,
     /// Salt for key derivation (base64 encoded)
     salt: String,
-    /// Nonce for encryption (base64 encoded)
-    nonce: String,
+    /// Store-wide nonce used by the version 1 format (base64 encoded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    nonce: Option<String>,
+    /// A known plaintext sealed with the store's key, telling other keys apart
+    /// even while the store is empty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key_check: Option<StoredSecret>,
 }
 
 impl EncryptedSecretStore {
@@ -23,41 +52,130 @@ impl EncryptedSecretStore {
     pub fn new() -> SecretResult<(Self, [u8; 32])> {
         let key = Aes256Gcm::generate_key(&mut OsRng);
         let salt = Self::generate_salt();
-        let nonce = Self::generate_nonce();
 
         let store = Self {
+            version: STORE_FORMAT_VERSION,
             secrets: HashMap::new(),
             salt: general_purpose::STANDARD.encode(salt),
-            nonce: general_purpose::STANDARD.encode(nonce),
+            nonce: None,
+            key_check: None,
         };
 
         Ok((store, key.into()))
     }
 
-    /// Create an encrypted secret store from existing data
+    /// Create an encrypted secret store from data in the version 1 layout,
+    /// where every value was encrypted with the same store-wide nonce
     pub fn from_data(secrets: HashMap<String, String>, salt: String, nonce: String) -> Self {
         Self {
-            secrets,
+            version: legacy_format_version(),
+            secrets: secrets
+                .into_iter()
+                .map(|(name, ciphertext)| (name, StoredSecret::Legacy(ciphertext)))
+                .collect(),
             salt,
-            nonce,
+            nonce: Some(nonce),
+            key_check: None,
         }
     }
 
-    /// Add an encrypted secret
+    /// Add an encrypted secret, sealed with a fresh random nonce
     pub fn add_secret(&mut self, key: &[u8; 32], name: &str, value: &str) -> SecretResult<()> {
-        let encrypted = self.encrypt_value(key, value)?;
-        self.secrets.insert(name.to_string(), encrypted);
+        let sealed = Self::seal(key, value)?;
+        self.secrets.insert(name.to_string(), sealed);
         Ok(())
     }
 
     /// Get and decrypt a secret
     pub fn get_secret(&self, key: &[u8; 32], name: &str) -> SecretResult<String> {
-        let encrypted = self
+        let stored = self
             .secrets
             .get(name)
             .ok_or_else(|| SecretError::not_found(name))?;
 
-        self.decrypt_value(key, encrypted)
+        match stored {
+            StoredSecret::Sealed { nonce, ciphertext } => Self::open(key, nonce, ciphertext),
+            StoredSecret::Legacy(ciphertext) => {
+                let nonce = self.nonce.as_deref().ok_or_else(|| {
+                    SecretError::EncryptionError(
+                        "Legacy secret entry without a store nonce".to_string(),
+                    )
+                })?;
+                Self::open(key, nonce, ciphertext)
+            }
+        }
+    }
+
+    /// Seal the key check with `key`, making it the only key the store accepts
+    pub fn set_key_check(&mut self, key: &[u8; 32]) -> SecretResult<()> {
+        self.key_check = Some(Self::seal(key, KEY_CHECK_PLAINTEXT)?);
+        Ok(())
+    }
+
+    /// Whether the store has a key check; stores written before it had none
+    pub fn has_key_check(&self) -> bool {
+        self.key_check.is_some()
+    }
+
+    /// Check that `key` is the store's key, by its key check or else by the
+    /// first entry it has
+    pub fn verify_key(&self, key: &[u8; 32]) -> SecretResult<()> {
+        let wrong_key = || {
+            SecretError::EncryptionError(
+                "The key does not match the one the store was created with".to_string(),
+            )
+        };
+        match &self.key_check {
+            Some(StoredSecret::Sealed { nonce, ciphertext }) => {
+                match Self::open(key, nonce, ciphertext) {
+                    Ok(plaintext) if plaintext == KEY_CHECK_PLAINTEXT => Ok(()),
+                    _ => Err(wrong_key()),
+                }
+            }
+            Some(StoredSecret::Legacy(_)) => Err(SecretError::EncryptionError(
+                "Invalid key check in the store".to_string(),
+            )),
+            None => match self.secrets.keys().next() {
+                Some(name) => self.get_secret(key, name).map(|_| ()),
+                None => Ok(()),
+            },
+        }
+    }
+
+    /// Storage format version this store was loaded with
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Whether the store still uses the version 1 shared-nonce layout
+    pub fn needs_migration(&self) -> bool {
+        self.version < STORE_FORMAT_VERSION
+            || self
+                .secrets
+                .values()
+                .any(|s| matches!(s, StoredSecret::Legacy(_)))
+    }
+
+    /// Re-encrypt every legacy entry with its own nonce and upgrade to the current format.
+    /// Fails without modifying the store if any entry cannot be decrypted with `key`.
+    pub fn migrate(&mut self, key: &[u8; 32]) -> SecretResult<()> {
+        let mut migrated = HashMap::with_capacity(self.secrets.len());
+        for (name, stored) in &self.secrets {
+            let entry = match stored {
+                StoredSecret::Legacy(_) => Self::seal(key, &self.get_secret(key, name)?)?,
+                sealed => sealed.clone(),
+            };
+            migrated.insert(name.clone(), entry);
+        }
+
+        self.secrets = migrated;
+        self.nonce = None;
+        self.version = STORE_FORMAT_VERSION;
+        // Every entry decrypted with `key`, so it's the store's
+        if self.key_check.is_none() {
+            self.set_key_check(key)?;
+        }
+        Ok(())
     }
 
     /// Remove a secret
@@ -75,6 +193,18 @@ impl EncryptedSecretStore {
         self.secrets.contains_key(name)
     }
 
+    /// Get the base64-encoded key derivation salt
+    pub fn salt(&self) -> &str {
+        &self.salt
+    }
+
+    /// Get the decoded key derivation salt
+    pub fn salt_bytes(&self) -> SecretResult<Vec<u8>> {
+        general_purpose::STANDARD
+            .decode(&self.salt)
+            .map_err(|e| SecretError::EncryptionError(format!("Invalid salt: {}", e)))
+    }
+
     /// Get the number of stored secrets
     pub fn secret_count(&self) -> usize {
         self.secrets.len()
@@ -85,32 +215,27 @@ impl EncryptedSecretStore {
         self.secrets.clear();
     }
 
-    /// Encrypt a value
-    fn encrypt_value(&self, key: &[u8; 32], value: &str) -> SecretResult<String> {
+    /// Encrypt a value under a newly generated nonce
+    fn seal(key: &[u8; 32], value: &str) -> SecretResult<StoredSecret> {
         let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
-        let nonce_bytes = general_purpose::STANDARD
-            .decode(&self.nonce)
-            .map_err(|e| SecretError::EncryptionError(format!("Invalid nonce: {}", e)))?;
-
-        if nonce_bytes.len() != 12 {
-            return Err(SecretError::EncryptionError(
-                "Invalid nonce length".to_string(),
-            ));
-        }
-
+        let nonce_bytes = Self::generate_nonce();
         let nonce = Nonce::from_slice(&nonce_bytes);
+
         let ciphertext = cipher
             .encrypt(nonce, value.as_bytes())
             .map_err(|e| SecretError::EncryptionError(format!("Encryption failed: {}", e)))?;
 
-        Ok(general_purpose::STANDARD.encode(&ciphertext))
+        Ok(StoredSecret::Sealed {
+            nonce: general_purpose::STANDARD.encode(nonce_bytes),
+            ciphertext: general_purpose::STANDARD.encode(&ciphertext),
+        })
     }
 
-    /// Decrypt a value
-    fn decrypt_value(&self, key: &[u8; 32], encrypted: &str) -> SecretResult<String> {
+    /// Decrypt a base64 ciphertext with the given base64 nonce
+    fn open(key: &[u8; 32], nonce: &str, encrypted: &str) -> SecretResult<String> {
         let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
         let nonce_bytes = general_purpose::STANDARD
-            .decode(&self.nonce)
+            .decode(nonce)
             .map_err(|e| SecretError::EncryptionError(format!("Invalid nonce: {}", e)))?;
 
         if nonce_bytes.len() != 12 {
@@ -186,6 +311,9 @@ impl Default for EncryptedSecretStore {
 pub struct KeyDerivation;
 
 impl KeyDerivation {
+    /// PBKDF2 iteration count used for password-protected stores
+    pub const DEFAULT_ITERATIONS: u32 = 100_000;
+
     /// Derive a key from a password using PBKDF2
     pub fn derive_key_from_password(password: &str, salt: &[u8], iterations: u32) -> [u8; 32] {
         let mut key = [0u8; 32];
@@ -318,6 +446,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_encrypted_secret_store_unique_nonces() {
+        let (mut store, key) = EncryptedSecretStore::new().unwrap();
+
+        // Identical plaintexts must not produce identical ciphertexts
+        store.add_secret(&key, "a", "same_value").unwrap();
+        store.add_secret(&key, "b", "same_value").unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&store.to_json().unwrap()).unwrap();
+        assert_eq!(json["version"], STORE_FORMAT_VERSION);
+        assert!(json.get("nonce").is_none());
+        assert_ne!(json["secrets"]["a"]["nonce"], json["secrets"]["b"]["nonce"]);
+        assert_ne!(
+            json["secrets"]["a"]["ciphertext"],
+            json["secrets"]["b"]["ciphertext"]
+        );
+    }
+
+    #[test]
+    fn test_encrypted_secret_store_legacy_migration() {
+        let key = KeyDerivation::generate_random_key();
+        let nonce_bytes = EncryptedSecretStore::generate_nonce();
+        let nonce = general_purpose::STANDARD.encode(nonce_bytes);
+
+        // Build a version 1 document by hand: one shared nonce, bare ciphertext strings
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce_bytes), b"legacy_value".as_ref())
+            .unwrap();
+        let legacy_json = serde_json::json!({
+            "secrets": { "OLD": general_purpose::STANDARD.encode(ciphertext) },
+            "salt": general_purpose::STANDARD.encode([7u8; 32]),
+            "nonce": nonce,
+        })
+        .to_string();
+
+        let mut store = EncryptedSecretStore::from_json(&legacy_json).unwrap();
+        assert_eq!(store.version(), 1);
+        assert!(store.needs_migration());
+        assert_eq!(store.get_secret(&key, "OLD").unwrap(), "legacy_value");
+
+        // Migration with the wrong key leaves the store untouched
+        let wrong_key = KeyDerivation::generate_random_key();
+        assert!(store.migrate(&wrong_key).is_err());
+        assert!(store.needs_migration());
+
+        store.migrate(&key).unwrap();
+        assert!(!store.needs_migration());
+        assert_eq!(store.version(), STORE_FORMAT_VERSION);
+
+        let restored = EncryptedSecretStore::from_json(&store.to_json().unwrap()).unwrap();
+        assert_eq!(restored.get_secret(&key, "OLD").unwrap(), "legacy_value");
+    }
+
     #[test]
     fn test_key_derivation() {
         let password = "test_password";
@@ -1,6 +1,7 @@
 use crate::{SecretManager, SecretResult};
 use regex::Regex;
 use std::collections::HashMap;
+use zeroize::Zeroizing;
 
 lazy_static::lazy_static! {
     /// Regex to match GitHub-style secret references: ${{ secrets.SECRET_NAME }}
@@ -14,10 +15,11 @@ lazy_static::lazy_static! {
     ).unwrap();
 }
 
-/// Secret substitution engine for replacing secret references in text
+/// Secret substitution engine for replacing secret references in text.
+/// Resolved values are wiped from memory when the engine is dropped.
 pub struct SecretSubstitution<'a> {
     manager: &'a SecretManager,
-    resolved_secrets: HashMap<String, String>,
+    resolved_secrets: HashMap<String, Zeroizing<String>>,
 }
 
 impl<'a> SecretSubstitution<'a> {
@@ -60,7 +62,7 @@ impl<'a> SecretSubstitution<'a> {
                     .manager
                     .get_secret_from_provider(provider, secret_name)
                     .await?;
-                let value = secret.value().to_string();
+                let value = Zeroizing::new(secret.value().to_string());
                 self.resolved_secrets.insert(cache_key, value.clone());
                 value
             };
@@ -83,7 +85,7 @@ impl<'a> SecretSubstitution<'a> {
                 cached.clone()
             } else {
                 let secret = self.manager.get_secret(secret_name).await?;
-                let value = secret.value().to_string();
+                let value = Zeroizing::new(secret.value().to_string());
                 self.resolved_secrets
                     .insert(secret_name.to_string(), value.clone());
                 value
@@ -96,10 +98,15 @@ impl<'a> SecretSubstitution<'a> {
     }
 
     /// Get all resolved secrets (for masking purposes)
-    pub fn resolved_secrets(&self) -> &HashMap<String, String> {
+    pub fn resolved_secrets(&self) -> &HashMap<String, Zeroizing<String>> {
         &self.resolved_secrets
     }
 
+    /// Forget all resolved secrets, wiping their values
+    pub fn clear(&mut self) {
+        self.resolved_secrets.clear();
+    }
+
     /// Check if text contains secret references
     pub fn contains_secrets(text: &str) -> bool {
         SECRET_PATTERN.is_match(text) || PROVIDER_SECRET_PATTERN.is_match(text)
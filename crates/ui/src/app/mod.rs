@@ -1,8 +1,10 @@
 // App module for UI state and main TUI entry point
+mod mouse;
 mod state;
 
 use crate::handlers::workflow::start_next_workflow_execution;
-use crate::models::{ExecutionResultMsg, Workflow, WorkflowStatus};
+use crate::models::{ExecutionResultMsg, RunParams, Workflow, WorkflowStatus};
+use crate::theme::{set_theme, Theme};
 use crate::utils::load_workflows;
 use crate::views::render_ui;
 use chrono::Local;
@@ -12,14 +14,32 @@ use crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
+use std::collections::HashMap;
 use std::io::{self, stdout};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc;
 use std::time::{Duration, Instant};
-use wrkflw_executor::RuntimeType;
+use wrkflw_executor::{RuntimeType, SandboxConfig};
 
 pub use state::App;
 
+static TUI_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Give the terminal back if the TUI has it, before exiting from elsewhere, like
+/// a signal handler
+pub fn restore_terminal() {
+    if TUI_ACTIVE.swap(false, Ordering::SeqCst) {
+        let _ = disable_raw_mode();
+        let _ = execute!(
+            stdout(),
+            LeaveAlternateScreen,
+            DisableMouseCapture,
+            crossterm::cursor::Show
+        );
+    }
+}
+
 // Main entry point for the TUI interface
 #[allow(clippy::ptr_arg)]
 pub async fn run_wrkflw_tui(
@@ -27,11 +47,26 @@ pub async fn run_wrkflw_tui(
     runtime_type: RuntimeType,
     verbose: bool,
     preserve_containers_on_failure: bool,
+    sandbox_config: Option<SandboxConfig>,
+    env: HashMap<String, String>,
 ) -> io::Result<()> {
+    // Colors from .wrkflw.toml, checked before the terminal is taken over
+    let theme_error = match std::env::current_dir()
+        .map_err(|e| e.to_string())
+        .and_then(|dir| Theme::load(&dir))
+    {
+        Ok(theme) => {
+            set_theme(theme);
+            None
+        }
+        Err(e) => Some(e),
+    };
+
     // Terminal setup
     enable_raw_mode()?;
     let mut stdout = stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    TUI_ACTIVE.store(true, Ordering::SeqCst);
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
@@ -47,6 +82,11 @@ pub async fn run_wrkflw_tui(
         tx.clone(),
         preserve_containers_on_failure,
     );
+    app.sandbox_config = sandbox_config;
+    app.env = env.clone();
+    if let Some(e) = theme_error {
+        app.set_status_message(format!("Using the dark theme: {}", e));
+    }
 
     if app.validation_mode {
         app.logs.push("Starting in validation mode".to_string());
@@ -70,6 +110,7 @@ pub async fn run_wrkflw_tui(
                 selected: true,
                 status: WorkflowStatus::NotStarted,
                 execution_details: None,
+                run_params: RunParams::default(),
             }];
 
             // Queue the single workflow for execution
@@ -96,6 +137,7 @@ pub async fn run_wrkflw_tui(
     let result = run_tui_event_loop(&mut terminal, &mut app, &tx_clone, &rx, verbose);
 
     // Clean up terminal
+    TUI_ACTIVE.store(false, Ordering::SeqCst);
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
@@ -115,8 +157,13 @@ pub async fn run_wrkflw_tui(
             if let Some(path) = path {
                 if path.is_file() {
                     wrkflw_logging::error("Falling back to CLI mode...");
-                    crate::handlers::workflow::execute_workflow_cli(path, runtime_type, verbose)
-                        .await
+                    crate::handlers::workflow::execute_workflow_cli(
+                        path,
+                        runtime_type,
+                        verbose,
+                        env,
+                    )
+                    .await
                 } else if path.is_dir() {
                     crate::handlers::workflow::validate_workflow(path, verbose)
                 } else {
@@ -130,6 +177,7 @@ pub async fn run_wrkflw_tui(
 }
 
 // Helper function to run the main event loop
+#[allow(clippy::collapsible_match)]
 fn run_tui_event_loop(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut App,
@@ -154,6 +202,7 @@ fn run_tui_event_loop(
         if last_tick.elapsed() >= tick_rate {
             app.tick();
             app.update_running_workflow_progress();
+            app.update_running_steps();
 
             // Check for log processing updates (includes system log change detection)
             app.check_log_processing_updates();
@@ -180,15 +229,70 @@ fn run_tui_event_loop(
             start_next_workflow_execution(app, tx_clone, verbose);
         }
 
-        // Handle key events with a short timeout
+        // Handle key and mouse events with a short timeout
         if event::poll(event_poll_timeout)? {
-            if let Event::Key(key) = event::read()? {
+            let event = event::read()?;
+            // Dialogs and search take the keyboard; the mouse stays out of their way
+            if let Event::Mouse(mouse) = event {
+                if app.run_dialog.is_none()
+                    && app.trigger_dialog.is_none()
+                    && app.palette.is_none()
+                    && !app.log_search_active
+                {
+                    app.handle_mouse(mouse);
+                }
+                continue;
+            }
+            if let Event::Key(key) = event {
+                // The find palette takes all keys while open
+                if app.palette.is_some() {
+                    app.handle_palette_input(key.code);
+                    continue;
+                }
+
+                // The terminal sends no SIGINT in raw mode: Ctrl+C cancels the
+                // running workflow, or quits when none is
+                if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                    if app.run_cancel.is_running() {
+                        app.cancel_run();
+                        continue;
+                    }
+                    break Ok(());
+                }
+
+                // Ctrl+P opens it from anywhere but the run and trigger dialogs
+                if key.code == KeyCode::Char('p')
+                    && key.modifiers.contains(KeyModifiers::CONTROL)
+                    && app.run_dialog.is_none()
+                    && app.trigger_dialog.is_none()
+                {
+                    app.open_palette();
+                    continue;
+                }
+
                 // Handle search input first if we're in search mode and logs tab
                 if app.selected_tab == 2 && app.log_search_active {
                     app.handle_log_search_input(key.code);
                     continue;
                 }
 
+                // The run dialog takes all keys while open
+                if app.run_dialog.is_some() {
+                    app.handle_run_dialog_input(key.code);
+                    continue;
+                }
+
+                // So does the trigger form
+                if app.trigger_dialog.is_some() {
+                    app.handle_trigger_dialog_input(key.code);
+                    continue;
+                }
+
+                // The file viewer takes the keys it uses while open over the Workflows tab
+                if app.selected_tab == 0 && !app.show_help && app.handle_file_view_input(key.code) {
+                    continue;
+                }
+
                 // Handle help overlay scrolling
                 if app.show_help {
                     match key.code {
@@ -214,7 +318,11 @@ fn run_tui_event_loop(
                         break Ok(());
                     }
                     KeyCode::Esc => {
-                        if app.detailed_view {
+                        if app.selected_tab == 3
+                            && app.graph_view.as_mut().is_some_and(|view| view.leave())
+                        {
+                            // Back out of a called workflow in the graph
+                        } else if app.detailed_view {
                             app.detailed_view = false;
                         } else if app.show_help {
                             app.show_help = false;
@@ -225,16 +333,19 @@ fn run_tui_event_loop(
                     }
                     KeyCode::Tab => {
                         // Cycle through tabs
-                        app.switch_tab((app.selected_tab + 1) % 4);
+                        app.switch_tab((app.selected_tab + 1) % 7);
                     }
                     KeyCode::BackTab => {
                         // Cycle through tabs backwards
-                        app.switch_tab((app.selected_tab + 3) % 4);
+                        app.switch_tab((app.selected_tab + 6) % 7);
                     }
                     KeyCode::Char('1') | KeyCode::Char('w') => app.switch_tab(0),
                     KeyCode::Char('2') | KeyCode::Char('x') => app.switch_tab(1),
                     KeyCode::Char('3') | KeyCode::Char('l') => app.switch_tab(2),
-                    KeyCode::Char('4') | KeyCode::Char('h') => app.switch_tab(3),
+                    KeyCode::Char('4') | KeyCode::Char('g') => app.switch_tab(3),
+                    KeyCode::Char('5') | KeyCode::Char('i') => app.switch_tab(4),
+                    KeyCode::Char('6') | KeyCode::Char('V') => app.switch_tab(5),
+                    KeyCode::Char('7') | KeyCode::Char('h') => app.switch_tab(6),
                     KeyCode::Up | KeyCode::Char('k') => {
                         if app.selected_tab == 2 {
                             if !app.log_search_matches.is_empty() {
@@ -242,8 +353,20 @@ fn run_tui_event_loop(
                             } else {
                                 app.scroll_logs_up();
                             }
-                        } else if app.selected_tab == 3 {
+                        } else if app.selected_tab == 6 {
                             app.scroll_help_up();
+                        } else if app.selected_tab == 5 {
+                            if let Some(view) = &mut app.validation_view {
+                                view.move_selection(-1);
+                            }
+                        } else if app.selected_tab == 4 {
+                            if let Some(view) = &mut app.history_view {
+                                view.move_selection(-1);
+                            }
+                        } else if app.selected_tab == 3 {
+                            if let Some(view) = &mut app.graph_view {
+                                view.move_selection(0, -1);
+                            }
                         } else if app.selected_tab == 0 {
                             app.previous_workflow();
                         } else if app.selected_tab == 1 {
@@ -261,8 +384,20 @@ fn run_tui_event_loop(
                             } else {
                                 app.scroll_logs_down();
                             }
-                        } else if app.selected_tab == 3 {
+                        } else if app.selected_tab == 6 {
                             app.scroll_help_down();
+                        } else if app.selected_tab == 5 {
+                            if let Some(view) = &mut app.validation_view {
+                                view.move_selection(1);
+                            }
+                        } else if app.selected_tab == 4 {
+                            if let Some(view) = &mut app.history_view {
+                                view.move_selection(1);
+                            }
+                        } else if app.selected_tab == 3 {
+                            if let Some(view) = &mut app.graph_view {
+                                view.move_selection(0, 1);
+                            }
                         } else if app.selected_tab == 0 {
                             app.next_workflow();
                         } else if app.selected_tab == 1 {
@@ -273,6 +408,27 @@ fn run_tui_event_loop(
                             }
                         }
                     }
+                    KeyCode::PageUp | KeyCode::PageDown => {
+                        if app.selected_tab == 1 && app.detailed_view {
+                            let dy = if key.code == KeyCode::PageUp { -10 } else { 10 };
+                            app.scroll_step_output(dy);
+                        }
+                    }
+                    KeyCode::Left | KeyCode::Right => {
+                        if app.selected_tab == 3 {
+                            if let Some(view) = &mut app.graph_view {
+                                let dx = if key.code == KeyCode::Left { -1 } else { 1 };
+                                view.move_selection(dx, 0);
+                            }
+                        }
+                    }
+                    KeyCode::Backspace => {
+                        if app.selected_tab == 3 {
+                            if let Some(view) = &mut app.graph_view {
+                                view.leave();
+                            }
+                        }
+                    }
                     KeyCode::Char(' ') => {
                         if app.selected_tab == 0 && !app.running {
                             app.toggle_selected();
@@ -281,12 +437,17 @@ fn run_tui_event_loop(
                     KeyCode::Enter => {
                         match app.selected_tab {
                             0 => {
-                                // In workflows tab, Enter runs the selected workflow
+                                // In workflows tab, Enter runs the selected workflow, asking
+                                // what to run it with unless only validating
                                 if !app.running {
-                                    if let Some(idx) = app.workflow_list_state.selected() {
-                                        app.workflows[idx].selected = true;
-                                        app.queue_selected_for_execution();
-                                        app.start_execution();
+                                    if app.validation_mode {
+                                        if let Some(idx) = app.workflow_list_state.selected() {
+                                            app.workflows[idx].selected = true;
+                                            app.queue_selected_for_execution();
+                                            app.start_execution();
+                                        }
+                                    } else {
+                                        app.open_run_dialog();
                                     }
                                 }
                             }
@@ -294,9 +455,27 @@ fn run_tui_event_loop(
                                 // In execution tab, Enter shows job details
                                 app.toggle_detailed_view();
                             }
+                            3 => {
+                                // In graph tab, Enter opens the workflow a job calls
+                                if let Some(view) = &mut app.graph_view {
+                                    view.enter();
+                                }
+                            }
+                            4 => {
+                                // In history tab, Enter marks the run to compare others with
+                                if let Some(view) = &mut app.history_view {
+                                    view.toggle_mark();
+                                }
+                            }
+                            5 => {
+                                // In validation tab, Enter opens the file at the selected issue
+                                app.open_validation_location();
+                            }
                             _ => {}
                         }
                     }
+                    KeyCode::Char('r') if app.selected_tab == 5 => app.validate_workflows(),
+                    KeyCode::Char('r') if app.selected_tab == 4 => app.rerun_from_failed(),
                     KeyCode::Char('r') => {
                         // Check if shift is pressed - this might be receiving the reset command
                         if key.modifiers.contains(KeyModifiers::SHIFT) {
@@ -345,6 +524,11 @@ fn run_tui_event_loop(
                             app.toggle_validation_mode();
                         }
                     }
+                    KeyCode::Char('d') => {
+                        if !app.running {
+                            app.toggle_dry_run_mode();
+                        }
+                    }
                     KeyCode::Char('n') => {
                         if app.selected_tab == 2 && !app.log_search_query.is_empty() {
                             app.next_search_match();
@@ -382,10 +566,16 @@ fn run_tui_event_loop(
                             ));
                         }
                     }
+                    KeyCode::Char('p') => app.open_palette(),
                     KeyCode::Char('?') => {
                         // Toggle help overlay
                         app.show_help = !app.show_help;
                     }
+                    KeyCode::Char('o') => {
+                        if app.selected_tab == 0 {
+                            app.open_file_view();
+                        }
+                    }
                     KeyCode::Char('t') => {
                         // Only trigger workflow if not already running and we're in the workflows tab
                         if !app.running && app.selected_tab == 0 {
@@ -393,7 +583,7 @@ fn run_tui_event_loop(
                                 if selected_idx < app.workflows.len() {
                                     let workflow = &app.workflows[selected_idx];
                                     if workflow.status == WorkflowStatus::NotStarted {
-                                        app.trigger_selected_workflow();
+                                        app.open_trigger_dialog();
                                     } else if workflow.status == WorkflowStatus::Running {
                                         app.logs.push(format!(
                                             "Workflow '{}' is already running",
@@ -479,10 +669,16 @@ fn run_tui_event_loop(
                         }
                     }
                     KeyCode::Char('c') => {
-                        if app.selected_tab == 2 {
+                        if app.selected_tab == 1 {
+                            app.cancel_selected_job();
+                        } else if app.selected_tab == 2 {
                             app.clear_log_search_and_filter();
                         }
                     }
+                    KeyCode::Char('C') => app.cancel_run(),
+                    KeyCode::Char('m') if app.selected_tab == 2 => app.toggle_log_mark(),
+                    KeyCode::Char('y') if app.selected_tab == 2 => app.copy_selected_logs(),
+                    KeyCode::Char('S') if app.selected_tab == 2 => app.export_logs(),
                     KeyCode::Char(c) => {
                         if app.selected_tab == 2 && app.log_search_active {
                             app.handle_log_search_input(KeyCode::Char(c));
@@ -0,0 +1,192 @@
+// Mouse handling: clicking tabs and list rows, scrolling and dragging splits
+use super::App;
+use crate::models::{JobExecution, ListKind};
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Direction;
+
+// Lines a turn of the wheel scrolls through logs, help and files
+const WHEEL_LINES: usize = 3;
+
+impl App {
+    // Act on a mouse event, against what the last frame drew
+    pub fn handle_mouse(&mut self, event: MouseEvent) {
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => self.click(event.column, event.row),
+            MouseEventKind::Drag(MouseButton::Left) => {
+                if let Some(target) = self.dragging {
+                    let percent = target.percent_at(event.column, event.row);
+                    self.pane_sizes.set(target.split, percent);
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) if self.dragging.take().is_some() => {
+                if let Err(e) = self.pane_sizes.save() {
+                    self.set_status_message(format!("Failed to save the layout: {}", e));
+                }
+            }
+            MouseEventKind::ScrollUp => self.scroll_wheel(-1),
+            MouseEventKind::ScrollDown => self.scroll_wheel(1),
+            _ => {}
+        }
+    }
+
+    fn click(&mut self, column: u16, row: u16) {
+        // Nothing under the help overlay can be clicked
+        if self.show_help {
+            return;
+        }
+        if let Some((tabs_row, spans)) = &self.mouse_targets.tabs {
+            if row == *tabs_row {
+                if let Some(tab) = spans
+                    .iter()
+                    .position(|&(start, end)| column >= start && column < end)
+                {
+                    self.switch_tab(tab);
+                }
+                return;
+            }
+        }
+
+        // A click on or right next to a split boundary starts dragging it
+        let pane_sizes = &self.pane_sizes;
+        self.dragging = self.mouse_targets.splits.iter().copied().find(|target| {
+            let boundary = target.boundary(pane_sizes.get(target.split));
+            let (pos, within) = match target.direction {
+                Direction::Horizontal => (
+                    column,
+                    row >= target.area.y && row < target.area.y + target.area.height,
+                ),
+                Direction::Vertical => (
+                    row,
+                    column >= target.area.x && column < target.area.x + target.area.width,
+                ),
+            };
+            within && pos + 1 >= boundary && pos <= boundary
+        });
+        if self.dragging.is_some() {
+            return;
+        }
+
+        let Some(target) = self.mouse_targets.list else {
+            return;
+        };
+        let Some(idx) = target.item_at(column, row) else {
+            return;
+        };
+        self.select_item(target.kind, idx);
+    }
+
+    // Select the `idx`th item of a list, as the arrow keys would
+    fn select_item(&mut self, kind: ListKind, idx: usize) {
+        match kind {
+            ListKind::Workflows => {
+                if idx < self.workflows.len() {
+                    self.workflow_list_state.select(Some(idx));
+                }
+            }
+            ListKind::Jobs => {
+                if idx < self.current_jobs().map_or(0, |jobs| jobs.len()) {
+                    self.job_list_state.select(Some(idx));
+                    // Reset step selection when changing jobs
+                    self.step_list_state.select(Some(0));
+                }
+            }
+            ListKind::Steps => {
+                let steps = self.current_jobs().and_then(|jobs| {
+                    self.job_list_state
+                        .selected()
+                        .and_then(|job| jobs.get(job))
+                        .map(|job| job.steps.len())
+                });
+                if idx < steps.unwrap_or(0) {
+                    self.step_list_state.select(Some(idx));
+                    self.step_table_state.select(Some(idx));
+                    self.step_output_scroll = 0;
+                }
+            }
+            ListKind::Logs => {
+                if idx < self.processed_logs.len() {
+                    self.log_scroll = idx;
+                }
+            }
+            ListKind::Runs => {
+                if let Some(view) = &mut self.history_view {
+                    if idx < view.runs.len() {
+                        view.selected = idx;
+                    }
+                }
+            }
+            ListKind::Validation => {
+                if let Some(view) = &mut self.validation_view {
+                    if idx < view.rows().len() {
+                        view.selected = idx;
+                    }
+                }
+            }
+        }
+    }
+
+    // Jobs of the workflow shown in the Execution tab
+    fn current_jobs(&self) -> Option<&[JobExecution]> {
+        self.current_execution
+            .or_else(|| self.workflow_list_state.selected())
+            .and_then(|idx| self.workflows.get(idx))
+            .and_then(|workflow| workflow.execution_details.as_ref())
+            .map(|execution| execution.jobs.as_slice())
+    }
+
+    // Scroll whatever is under the wheel, like the arrow keys
+    fn scroll_wheel(&mut self, dy: isize) {
+        if self.show_help || self.selected_tab == 6 {
+            for _ in 0..WHEEL_LINES {
+                if dy < 0 {
+                    self.scroll_help_up();
+                } else {
+                    self.scroll_help_down();
+                }
+            }
+            return;
+        }
+        match self.selected_tab {
+            0 => {
+                if let Some(view) = self.file_view.as_mut() {
+                    view.scroll_by(dy * WHEEL_LINES as isize);
+                } else if dy < 0 {
+                    self.previous_workflow();
+                } else {
+                    self.next_workflow();
+                }
+            }
+            1 => match (self.detailed_view, dy < 0) {
+                (true, true) => self.previous_step(),
+                (true, false) => self.next_step(),
+                (false, true) => self.previous_job(),
+                (false, false) => self.next_job(),
+            },
+            2 => {
+                for _ in 0..WHEEL_LINES {
+                    if dy < 0 {
+                        self.scroll_logs_up();
+                    } else {
+                        self.scroll_logs_down();
+                    }
+                }
+            }
+            3 => {
+                if let Some(view) = &mut self.graph_view {
+                    view.move_selection(0, dy);
+                }
+            }
+            4 => {
+                if let Some(view) = &mut self.history_view {
+                    view.move_selection(dy);
+                }
+            }
+            5 => {
+                if let Some(view) = &mut self.validation_view {
+                    view.move_selection(dy);
+                }
+            }
+            _ => {}
+        }
+    }
+}
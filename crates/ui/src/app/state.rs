@@ -1,15 +1,22 @@
 // App state for the UI
 use crate::log_processor::{LogProcessingRequest, LogProcessor, ProcessedLogEntry};
 use crate::models::{
-    ExecutionResultMsg, JobExecution, LogFilterLevel, StepExecution, Workflow, WorkflowExecution,
-    WorkflowStatus,
+    runtime_name, ExecutionResultMsg, FileView, GraphView, HistoryView, IssueSeverity,
+    JobExecution, LogFilterLevel, MouseTargets, Palette, PaletteTarget, PaneSizes, PipelineView,
+    RunDialog, RunField, SplitTarget, StepExecution, TriggerDialog, TriggerField, TriggerParams,
+    ValidationView, Workflow, WorkflowExecution, WorkflowStatus,
 };
 use chrono::Local;
 use crossterm::event::KeyCode;
 use ratatui::widgets::{ListState, TableState};
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::mpsc;
 use std::time::{Duration, Instant};
-use wrkflw_executor::{JobStatus, RuntimeType, StepStatus};
+use wrkflw_executor::cancel::RunCancel;
+use wrkflw_executor::progress::ProgressEvent;
+use wrkflw_executor::runs::RunStore;
+use wrkflw_executor::{JobStatus, RuntimeType, SandboxConfig, StepStatus};
 
 /// Application state
 pub struct App {
@@ -20,19 +27,24 @@ pub struct App {
     pub show_help: bool,
     pub runtime_type: RuntimeType,
     pub validation_mode: bool,
+    pub dry_run_mode: bool, // Plan workflows instead of running them
     pub preserve_containers_on_failure: bool,
-    pub execution_queue: Vec<usize>, // Indices of workflows to execute
+    pub sandbox_config: Option<SandboxConfig>, // Sandbox used by secure emulation
+    pub env: HashMap<String, String>,          // Variables from --env/--env-file
+    pub execution_queue: Vec<usize>,           // Indices of workflows to execute
     pub current_execution: Option<usize>,
-    pub logs: Vec<String>,                    // Overall execution logs
-    pub log_scroll: usize,                    // Scrolling position for logs
-    pub job_list_state: ListState,            // For viewing job details
-    pub detailed_view: bool,                  // Whether we're in detailed view mode
-    pub step_list_state: ListState,           // For selecting steps in detailed view
-    pub step_table_state: TableState,         // For the steps table in detailed view
-    pub last_tick: Instant,                   // For UI animations and updates
-    pub tick_rate: Duration,                  // How often to update the UI
+    pub run_cancel: RunCancel,        // Cancels the workflow running
+    pub logs: Vec<String>,            // Overall execution logs
+    pub log_scroll: usize,            // Scrolling position for logs
+    pub job_list_state: ListState,    // For viewing job details
+    pub detailed_view: bool,          // Whether we're in detailed view mode
+    pub step_list_state: ListState,   // For selecting steps in detailed view
+    pub step_table_state: TableState, // For the steps table in detailed view
+    pub step_output_scroll: usize,    // Scrolling position for the selected step's output
+    pub last_tick: Instant,           // For UI animations and updates
+    pub tick_rate: Duration,          // How often to update the UI
     pub tx: mpsc::Sender<ExecutionResultMsg>, // Channel for async communication
-    pub status_message: Option<String>,       // Temporary status message to display
+    pub status_message: Option<String>, // Temporary status message to display
     pub status_message_time: Option<Instant>, // When the message was set
 
     // Search and filter functionality
@@ -41,10 +53,24 @@ pub struct App {
     pub log_filter_level: Option<LogFilterLevel>, // Current log level filter
     pub log_search_matches: Vec<usize>, // Indices of logs that match the search
     pub log_search_match_idx: usize, // Current match index for navigation
+    pub log_mark: Option<usize>,  // Other end of the log lines selected for copying
+    pub clipboard: Option<arboard::Clipboard>, // Kept open, as on X11 copied text lives as long as it
 
     // Help tab scrolling
     pub help_scroll: usize, // Scrolling position for help content
 
+    pub graph_view: Option<GraphView>, // Graph of the selected workflow, loaded by the Graph tab
+    pub history_view: Option<HistoryView>, // Recorded runs, loaded by the History tab
+    pub validation_view: Option<ValidationView>, // Findings of the loaded workflows, for the Validation tab
+    pub pipeline_view: Option<PipelineView>, // Stages of the GitLab pipeline selected in the Workflows tab
+    pub file_view: Option<FileView>,         // Workflow file opened read-only in the Workflows tab
+    pub run_dialog: Option<RunDialog>, // What to run the selected workflow with, before running it
+    pub palette: Option<Palette>,      // Find palette, while open
+    pub trigger_dialog: Option<TriggerDialog>, // Branch and inputs to trigger a workflow remotely with
+    pub pane_sizes: PaneSizes, // Sizes of the resizable panes, kept between sessions
+    pub mouse_targets: MouseTargets, // What the last frame drew that the mouse can act on
+    pub dragging: Option<SplitTarget>, // Split being dragged with the mouse
+
     // Background log processing
     pub log_processor: LogProcessor,
     pub processed_logs: Vec<ProcessedLogEntry>,
@@ -179,6 +205,7 @@ impl App {
             }
             RuntimeType::Emulation => RuntimeType::Emulation,
             RuntimeType::SecureEmulation => RuntimeType::SecureEmulation,
+            RuntimeType::Wasm => RuntimeType::Wasm,
         };
 
         App {
@@ -189,15 +216,20 @@ impl App {
             show_help: false,
             runtime_type,
             validation_mode: false,
+            dry_run_mode: false,
             preserve_containers_on_failure,
+            sandbox_config: None,
+            env: HashMap::new(),
             execution_queue: Vec::new(),
             current_execution: None,
+            run_cancel: RunCancel::new(),
             logs: initial_logs,
             log_scroll: 0,
             job_list_state,
             detailed_view: false,
             step_list_state,
             step_table_state,
+            step_output_scroll: 0,
             last_tick: Instant::now(),
             tick_rate: Duration::from_millis(250), // Update 4 times per second
             tx,
@@ -210,7 +242,20 @@ impl App {
             log_filter_level: Some(LogFilterLevel::All),
             log_search_matches: Vec::new(),
             log_search_match_idx: 0,
+            log_mark: None,
+            clipboard: None,
             help_scroll: 0,
+            graph_view: None,
+            history_view: None,
+            pipeline_view: None,
+            validation_view: None,
+            file_view: None,
+            run_dialog: None,
+            palette: None,
+            trigger_dialog: None,
+            pane_sizes: PaneSizes::load(),
+            mouse_targets: MouseTargets::default(),
+            dragging: None,
 
             // Background log processing
             log_processor: LogProcessor::new(),
@@ -234,7 +279,7 @@ impl App {
             RuntimeType::Docker => RuntimeType::Podman,
             RuntimeType::Podman => RuntimeType::SecureEmulation,
             RuntimeType::SecureEmulation => RuntimeType::Emulation,
-            RuntimeType::Emulation => RuntimeType::Docker,
+            RuntimeType::Emulation | RuntimeType::Wasm => RuntimeType::Docker,
         };
         self.logs
             .push(format!("Switched to {} mode", self.runtime_type_name()));
@@ -242,6 +287,9 @@ impl App {
 
     pub fn toggle_validation_mode(&mut self) {
         self.validation_mode = !self.validation_mode;
+        if self.validation_mode {
+            self.dry_run_mode = false;
+        }
         let mode = if self.validation_mode {
             "validation"
         } else {
@@ -253,13 +301,24 @@ impl App {
         wrkflw_logging::info(&format!("Switched to {} mode", mode));
     }
 
-    pub fn runtime_type_name(&self) -> &str {
-        match self.runtime_type {
-            RuntimeType::Docker => "Docker",
-            RuntimeType::Podman => "Podman",
-            RuntimeType::SecureEmulation => "Secure Emulation",
-            RuntimeType::Emulation => "Emulation (Unsafe)",
+    pub fn toggle_dry_run_mode(&mut self) {
+        self.dry_run_mode = !self.dry_run_mode;
+        if self.dry_run_mode {
+            self.validation_mode = false;
         }
+        let mode = if self.dry_run_mode {
+            "dry run"
+        } else {
+            "normal"
+        };
+        let timestamp = Local::now().format("%H:%M:%S").to_string();
+        self.logs
+            .push(format!("[{}] Switched to {} mode", timestamp, mode));
+        wrkflw_logging::info(&format!("Switched to {} mode", mode));
+    }
+
+    pub fn runtime_type_name(&self) -> &str {
+        runtime_name(&self.runtime_type)
     }
 
     // Move cursor up in the workflow list
@@ -398,6 +457,7 @@ impl App {
                         self.step_list_state.select(Some(i));
                         // Update the table state to match
                         self.step_table_state.select(Some(i));
+                        self.step_output_scroll = 0;
                     }
                 }
             }
@@ -433,6 +493,7 @@ impl App {
                         self.step_list_state.select(Some(i));
                         // Update the table state to match
                         self.step_table_state.select(Some(i));
+                        self.step_output_scroll = 0;
                     }
                 }
             }
@@ -442,6 +503,350 @@ impl App {
     // Change the tab
     pub fn switch_tab(&mut self, tab: usize) {
         self.selected_tab = tab;
+        if tab == 3 {
+            self.load_graph();
+        } else if tab == 4 {
+            self.load_history();
+        } else if tab == 5 && self.validation_view.is_none() {
+            self.validate_workflows();
+        }
+    }
+
+    // Validate the loaded workflows for the Validation tab, keeping the selected row
+    pub fn validate_workflows(&mut self) {
+        let selected = self
+            .validation_view
+            .as_ref()
+            .map_or(0, |view| view.selected);
+        let mut view =
+            ValidationView::validate(self.workflows.iter().map(|workflow| workflow.path.clone()));
+        view.selected = selected;
+        view.move_selection(0);
+        self.set_status_message(format!(
+            "Validated {} file(s): {} error(s), {} warning(s)",
+            view.files.len(),
+            view.count(IssueSeverity::Error),
+            view.count(IssueSeverity::Warning)
+        ));
+        self.validation_view = Some(view);
+    }
+
+    // Open the file of the selected validation row in the viewer, at its issue's line
+    pub fn open_validation_location(&mut self) {
+        let Some((path, line)) = self
+            .validation_view
+            .as_ref()
+            .and_then(|view| view.selected_location())
+            .map(|(path, line)| (path.to_path_buf(), line))
+        else {
+            return;
+        };
+        if let Some(idx) = self.workflows.iter().position(|w| w.path == path) {
+            self.workflow_list_state.select(Some(idx));
+        }
+        let mut view = FileView::open(path);
+        if let Some(line) = line {
+            view.reveal(line);
+        }
+        self.file_view = Some(view);
+        self.selected_tab = 0;
+    }
+
+    // Read the run history of the project for the History tab
+    pub fn load_history(&mut self) {
+        let Ok(project_dir) = std::env::current_dir() else {
+            return;
+        };
+        let store = RunStore::for_project(&project_dir);
+        match &mut self.history_view {
+            Some(view) => view.reload(&store),
+            None => self.history_view = Some(HistoryView::load(&store)),
+        }
+    }
+
+    // Rerun the failed run selected in the History tab, skipping the jobs and steps
+    // that succeeded in it
+    pub fn rerun_from_failed(&mut self) {
+        if self.running {
+            return;
+        }
+        let Some(run) = self
+            .history_view
+            .as_ref()
+            .and_then(|view| view.selected_run())
+            .cloned()
+        else {
+            return;
+        };
+        let same_file = |path: &std::path::Path| {
+            path == run.path
+                || path.canonicalize().ok().is_some_and(|path| {
+                    run.path.canonicalize().ok().as_deref() == Some(path.as_path())
+                })
+        };
+        let Some(idx) = self.workflows.iter().position(|w| same_file(&w.path)) else {
+            self.set_status_message(format!("{} is not loaded", run.path.display()));
+            return;
+        };
+        let resume = std::env::current_dir()
+            .map_err(|e| e.to_string())
+            .and_then(|dir| RunStore::for_project(&dir).resume(&run));
+        match resume {
+            Ok(resume) => {
+                self.logs.push(format!(
+                    "Rerunning {} from the failures of run {}",
+                    run.workflow, run.id
+                ));
+                self.workflows[idx].run_params.resume = Some(resume);
+                self.workflows[idx].selected = true;
+                self.queue_selected_for_execution();
+                self.start_execution();
+                self.selected_tab = 1;
+            }
+            Err(e) => self.set_status_message(e),
+        }
+    }
+
+    // Build the Graph tab's graph for the selected workflow, unless already shown
+    pub fn load_graph(&mut self) {
+        let Some(workflow) = self
+            .workflow_list_state
+            .selected()
+            .and_then(|idx| self.workflows.get(idx))
+        else {
+            self.graph_view = None;
+            return;
+        };
+        if self
+            .graph_view
+            .as_ref()
+            .is_some_and(|view| view.path == workflow.path)
+        {
+            return;
+        }
+        self.graph_view = Some(GraphView::new(workflow.path.clone()));
+    }
+
+    // Load the stages of the selected workflow if it's a GitLab pipeline
+    pub fn load_pipeline_view(&mut self) {
+        let Some(workflow) = self
+            .workflow_list_state
+            .selected()
+            .and_then(|idx| self.workflows.get(idx))
+            .filter(|workflow| wrkflw_utils::is_gitlab_pipeline_file(&workflow.path))
+        else {
+            self.pipeline_view = None;
+            return;
+        };
+        if self
+            .pipeline_view
+            .as_ref()
+            .is_some_and(|view| view.path == workflow.path)
+        {
+            return;
+        }
+        self.pipeline_view = Some(PipelineView::new(workflow.path.clone()));
+    }
+
+    // Open the selected workflow in the read-only file viewer
+    pub fn open_file_view(&mut self) {
+        let Some(workflow) = self
+            .workflow_list_state
+            .selected()
+            .and_then(|idx| self.workflows.get(idx))
+        else {
+            return;
+        };
+        let view = FileView::open(workflow.path.clone());
+        let issues = view.issue_count();
+        self.file_view = Some(view);
+        self.set_status_message(if issues == 0 {
+            "No validation issues".to_string()
+        } else {
+            format!("{} validation issue(s) annotated", issues)
+        });
+    }
+
+    // Ask what to run the selected workflow with
+    pub fn open_run_dialog(&mut self) {
+        let Some(idx) = self
+            .workflow_list_state
+            .selected()
+            .filter(|&idx| idx < self.workflows.len())
+        else {
+            return;
+        };
+        self.run_dialog = Some(RunDialog::new(
+            idx,
+            &self.workflows[idx],
+            self.runtime_type.clone(),
+            self.preserve_containers_on_failure,
+        ));
+    }
+
+    // Handle a key in the run dialog, running the workflow once confirmed
+    pub fn handle_run_dialog_input(&mut self, key: KeyCode) {
+        let Some(dialog) = self.run_dialog.as_mut() else {
+            return;
+        };
+        dialog.error = None;
+        match key {
+            KeyCode::Esc => self.run_dialog = None,
+            KeyCode::Up | KeyCode::BackTab => dialog.move_focus(-1),
+            KeyCode::Down | KeyCode::Tab => dialog.move_focus(1),
+            KeyCode::Left => dialog.cycle(false),
+            KeyCode::Right => dialog.cycle(true),
+            KeyCode::Backspace => {
+                if let Some(value) = dialog.text_input() {
+                    value.pop();
+                }
+            }
+            KeyCode::Char(c) => match dialog.text_input() {
+                Some(value) => value.push(c),
+                None if c == ' ' => dialog.cycle(true),
+                None => {}
+            },
+            KeyCode::Enter if dialog.focus != RunField::Run => dialog.move_focus(1),
+            KeyCode::Enter => match dialog.params() {
+                Ok(params) => {
+                    let idx = dialog.workflow;
+                    let runtime = dialog.runtime.clone();
+                    self.preserve_containers_on_failure = dialog.preserve_containers;
+                    self.run_dialog = None;
+                    if self.runtime_type != runtime {
+                        self.runtime_type = runtime;
+                        self.logs
+                            .push(format!("Switched to {} mode", self.runtime_type_name()));
+                    }
+                    self.workflows[idx].run_params = params;
+                    self.workflows[idx].selected = true;
+                    self.queue_selected_for_execution();
+                    self.start_execution();
+                }
+                Err(e) => dialog.error = Some(e),
+            },
+            _ => {}
+        }
+    }
+
+    // Ask what to trigger the selected workflow on GitHub or GitLab with
+    pub fn open_trigger_dialog(&mut self) {
+        let Some(idx) = self
+            .workflow_list_state
+            .selected()
+            .filter(|&idx| idx < self.workflows.len())
+        else {
+            return;
+        };
+        self.trigger_dialog = Some(TriggerDialog::new(idx, &self.workflows[idx]));
+    }
+
+    // Handle a key in the trigger form, triggering the workflow once confirmed
+    pub fn handle_trigger_dialog_input(&mut self, key: KeyCode) {
+        let Some(dialog) = self.trigger_dialog.as_mut() else {
+            return;
+        };
+        dialog.error = None;
+        match key {
+            KeyCode::Esc => self.trigger_dialog = None,
+            KeyCode::Up | KeyCode::BackTab => dialog.move_focus(-1),
+            KeyCode::Down | KeyCode::Tab => dialog.move_focus(1),
+            KeyCode::Left => dialog.cycle(false, &self.workflows),
+            KeyCode::Right => dialog.cycle(true, &self.workflows),
+            KeyCode::Backspace => {
+                if let Some(value) = dialog.text_input() {
+                    value.pop();
+                }
+            }
+            KeyCode::Char(c) => match dialog.text_input() {
+                Some(value) => value.push(c),
+                None if c == ' ' => dialog.cycle(true, &self.workflows),
+                None => {}
+            },
+            KeyCode::Enter if dialog.focus != TriggerField::Trigger => dialog.move_focus(1),
+            KeyCode::Enter => match dialog.params() {
+                Ok(params) => {
+                    let idx = dialog.workflow;
+                    self.trigger_dialog = None;
+                    self.trigger_workflow(idx, params);
+                }
+                Err(e) => dialog.error = Some(e),
+            },
+            _ => {}
+        }
+    }
+
+    // Open the find palette over the loaded workflows, their jobs and the recorded runs
+    pub fn open_palette(&mut self) {
+        let runs = std::env::current_dir()
+            .map(|dir| RunStore::for_project(&dir).list())
+            .unwrap_or_default();
+        self.palette = Some(Palette::new(&self.workflows, &runs));
+    }
+
+    // Handle a key in the find palette, jumping to the selected entry on Enter
+    pub fn handle_palette_input(&mut self, key: KeyCode) {
+        let Some(palette) = self.palette.as_mut() else {
+            return;
+        };
+        match key {
+            KeyCode::Esc => self.palette = None,
+            KeyCode::Up => palette.move_selection(-1),
+            KeyCode::Down => palette.move_selection(1),
+            KeyCode::Backspace => {
+                palette.query.pop();
+                palette.update_matches();
+            }
+            KeyCode::Char(c) => {
+                palette.query.push(c);
+                palette.update_matches();
+            }
+            KeyCode::Enter => {
+                let target = palette.selected_target().cloned();
+                self.palette = None;
+                if let Some(target) = target {
+                    self.jump_to(target);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Show what a palette entry points at: a workflow in the Workflows tab, a job
+    // in the Graph tab or a run in the History tab
+    fn jump_to(&mut self, target: PaletteTarget) {
+        self.show_help = false;
+        match target {
+            PaletteTarget::Workflow(idx) => {
+                self.file_view = None;
+                self.workflow_list_state.select(Some(idx));
+                self.switch_tab(0);
+            }
+            PaletteTarget::Job { workflow, job } => {
+                self.workflow_list_state.select(Some(workflow));
+                self.switch_tab(3);
+                if !self
+                    .graph_view
+                    .as_mut()
+                    .is_some_and(|view| view.select_job(&job))
+                {
+                    self.set_status_message(format!("Job '{}' is not in the graph", job));
+                }
+            }
+            PaletteTarget::Run(id) => {
+                self.switch_tab(4);
+                let found = self.history_view.as_mut().is_some_and(|view| {
+                    view.runs
+                        .iter()
+                        .position(|run| run.id == id)
+                        .map(|idx| view.selected = idx)
+                        .is_some()
+                });
+                if !found {
+                    self.set_status_message(format!("Run {} is no longer recorded", id));
+                }
+            }
+        }
     }
 
     // Queue selected workflows for execution
@@ -472,6 +877,54 @@ impl App {
         }
     }
 
+    // Cancel the running workflow, killing its containers and processes
+    pub fn cancel_run(&mut self) {
+        let Some(idx) = self.current_execution else {
+            self.set_status_message("No workflow is running".to_string());
+            return;
+        };
+        if self.workflows[idx].status != WorkflowStatus::Running {
+            self.set_status_message("No workflow is running".to_string());
+            return;
+        }
+
+        let timestamp = Local::now().format("%H:%M:%S").to_string();
+        self.logs.push(format!(
+            "[{}] Cancelling workflow '{}'",
+            timestamp, self.workflows[idx].name
+        ));
+        self.set_status_message(format!("Cancelling '{}'...", self.workflows[idx].name));
+
+        // The cleanup is async and the run's thread is busy with the run itself
+        let cancel = self.run_cancel.clone();
+        std::thread::spawn(move || match tokio::runtime::Runtime::new() {
+            Ok(rt) => rt.block_on(cancel.cancel_run()),
+            Err(e) => wrkflw_logging::error(&format!(
+                "Failed to create Tokio runtime to cancel the run: {}",
+                e
+            )),
+        });
+    }
+
+    // Cancel the job selected on the Execution tab once its current step finishes
+    pub fn cancel_selected_job(&mut self) {
+        let job = self.current_execution.and_then(|idx| {
+            let execution = self.workflows[idx].execution_details.as_ref()?;
+            let job = execution.jobs.get(self.job_list_state.selected()?)?;
+            job.running.then(|| job.name.clone())
+        });
+        match job {
+            Some(job) => {
+                self.run_cancel.cancel_job(&job);
+                let timestamp = Local::now().format("%H:%M:%S").to_string();
+                self.logs
+                    .push(format!("[{}] Cancelling job '{}'", timestamp, job));
+                self.set_status_message(format!("Cancelling '{}' after its current step...", job));
+            }
+            None => self.set_status_message("The selected job isn't running".to_string()),
+        }
+    }
+
     // Process execution results and update UI
     pub fn process_execution_result(
         &mut self,
@@ -488,6 +941,11 @@ impl App {
             return;
         }
 
+        // The run may have just been recorded
+        if self.history_view.is_some() {
+            self.load_history();
+        }
+
         let workflow = &mut self.workflows[workflow_idx];
 
         // Ensure execution details exist
@@ -498,6 +956,7 @@ impl App {
                 end_time: Some(Local::now()),
                 logs: Vec::new(),
                 progress: 1.0,
+                stage: None,
             });
         }
 
@@ -522,6 +981,7 @@ impl App {
                                 wrkflw_executor::JobStatus::Success => JobStatus::Success,
                                 wrkflw_executor::JobStatus::Failure => JobStatus::Failure,
                                 wrkflw_executor::JobStatus::Skipped => JobStatus::Skipped,
+                                wrkflw_executor::JobStatus::Cancelled => JobStatus::Cancelled,
                             },
                             steps: job_result
                                 .steps
@@ -534,9 +994,12 @@ impl App {
                                         wrkflw_executor::StepStatus::Skipped => StepStatus::Skipped,
                                     },
                                     output: step_result.output.clone(),
+                                    duration: step_result.duration,
+                                    running_since: None,
                                 })
                                 .collect::<Vec<StepExecution>>(),
                             logs: vec![job_result.logs.clone()],
+                            running: false,
                         })
                         .collect::<Vec<JobExecution>>();
                 }
@@ -555,14 +1018,33 @@ impl App {
                             name: "Execution Error".to_string(),
                             status: StepStatus::Failure,
                             output: format!("Error: {}\n\nThis error prevented the workflow from executing properly.", e),
+                            duration: Duration::ZERO,
+                            running_since: None,
                         }],
                         logs: vec![format!("Workflow execution error: {}", e)],
+                        running: false,
                     }];
                 }
             }
         }
 
         match result {
+            Ok((jobs, _))
+                if jobs
+                    .iter()
+                    .any(|job| job.status == wrkflw_executor::JobStatus::Cancelled) =>
+            {
+                workflow.status = WorkflowStatus::Cancelled;
+                let timestamp = Local::now().format("%H:%M:%S").to_string();
+                self.logs.push(format!(
+                    "[{}] Workflow '{}' was cancelled",
+                    timestamp, workflow.name
+                ));
+                wrkflw_logging::warning(&format!(
+                    "[{}] Workflow '{}' was cancelled",
+                    timestamp, workflow.name
+                ));
+            }
             Ok(_) => {
                 workflow.status = WorkflowStatus::Success;
                 let timestamp = Local::now().format("%H:%M:%S").to_string();
@@ -620,6 +1102,7 @@ impl App {
             end_time: None,
             logs: vec!["Execution started".to_string()],
             progress: 0.0, // Just started
+            stage: None,
         });
 
         Some(next)
@@ -628,6 +1111,7 @@ impl App {
     // Toggle detailed view mode
     pub fn toggle_detailed_view(&mut self) {
         self.detailed_view = !self.detailed_view;
+        self.step_output_scroll = 0;
 
         // When entering detailed view, make sure step selection is initialized
         if self.detailed_view {
@@ -672,6 +1156,48 @@ impl App {
         }
     }
 
+    // Handle a key while the file viewer is open; false if the key isn't the viewer's
+    pub fn handle_file_view_input(&mut self, key: KeyCode) -> bool {
+        let Some(view) = self.file_view.as_mut() else {
+            return false;
+        };
+        if view.search_active {
+            match key {
+                KeyCode::Esc => {
+                    view.search_active = false;
+                    view.search_query.clear();
+                    view.update_search();
+                }
+                KeyCode::Backspace => {
+                    view.search_query.pop();
+                    view.update_search();
+                }
+                KeyCode::Enter => view.search_active = false,
+                KeyCode::Char(c) => {
+                    view.search_query.push(c);
+                    view.update_search();
+                }
+                _ => {}
+            }
+            return true;
+        }
+        let page = view.page.max(1) as isize;
+        match key {
+            KeyCode::Up | KeyCode::Char('k') => view.scroll_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => view.scroll_by(1),
+            KeyCode::PageUp => view.scroll_by(-page),
+            KeyCode::PageDown => view.scroll_by(page),
+            KeyCode::Home => view.scroll = 0,
+            KeyCode::End => view.scroll_to_end(),
+            KeyCode::Char('s') | KeyCode::Char('/') => view.search_active = true,
+            KeyCode::Char('n') => view.cycle_match(true),
+            KeyCode::Char('N') => view.cycle_match(false),
+            KeyCode::Esc | KeyCode::Char('o') => self.file_view = None,
+            _ => return false,
+        }
+        true
+    }
+
     // Toggle log search mode
     pub fn toggle_log_search(&mut self) {
         self.log_search_active = !self.log_search_active;
@@ -811,6 +1337,92 @@ impl App {
         }
     }
 
+    // The log lines selected for copying: from the mark to the cursor, else the cursor's
+    pub fn selected_log_range(&self) -> Option<std::ops::RangeInclusive<usize>> {
+        let last = self.processed_logs.len().checked_sub(1)?;
+        let cursor = self.log_scroll.min(last);
+        let mark = self.log_mark.unwrap_or(cursor).min(last);
+        Some(mark.min(cursor)..=mark.max(cursor))
+    }
+
+    // Start or drop a selection of log lines at the cursor
+    pub fn toggle_log_mark(&mut self) {
+        self.log_mark = match self.log_mark {
+            Some(_) => None,
+            None => Some(self.log_scroll),
+        };
+    }
+
+    // Copy the selected log lines to the system clipboard
+    pub fn copy_selected_logs(&mut self) {
+        let Some(range) = self.selected_log_range() else {
+            self.set_status_message("No logs to copy".to_string());
+            return;
+        };
+        let count = range.end() - range.start() + 1;
+        let text = self.processed_logs[range]
+            .iter()
+            .map(|entry| entry.text())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if self.clipboard.is_none() {
+            match arboard::Clipboard::new() {
+                Ok(clipboard) => self.clipboard = Some(clipboard),
+                Err(e) => {
+                    self.set_status_message(format!("Clipboard unavailable: {}", e));
+                    return;
+                }
+            }
+        }
+        let result = match self.clipboard.as_mut() {
+            Some(clipboard) => clipboard.set_text(text),
+            None => return,
+        };
+        match result {
+            Ok(()) => {
+                self.log_mark = None;
+                self.set_status_message(format!("Copied {} log line(s) to the clipboard", count));
+            }
+            Err(e) => self.set_status_message(format!("Failed to copy logs: {}", e)),
+        }
+    }
+
+    // Save the logs shown, with the search and filter applied, to a file in the
+    // current directory
+    pub fn export_logs(&mut self) {
+        if self.processed_logs.is_empty() {
+            self.set_status_message("No logs to save".to_string());
+            return;
+        }
+        let path = PathBuf::from(format!(
+            "wrkflw-logs-{}.log",
+            Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        let mut content: String = self
+            .processed_logs
+            .iter()
+            .map(|entry| entry.text())
+            .collect::<Vec<_>>()
+            .join("\n");
+        content.push('\n');
+        match std::fs::write(&path, content) {
+            Ok(()) => self.set_status_message(format!(
+                "Saved {} log line(s) to {}",
+                self.processed_logs.len(),
+                path.display()
+            )),
+            Err(e) => {
+                self.set_status_message(format!("Failed to save logs to {}: {}", path.display(), e))
+            }
+        }
+    }
+
+    // Scroll the selected step's output in the detailed view
+    pub fn scroll_step_output(&mut self, dy: isize) {
+        self.step_output_scroll = self.step_output_scroll.saturating_add_signed(dy);
+    }
+
     // Scroll help content up
     pub fn scroll_help_up(&mut self) {
         self.help_scroll = self.help_scroll.saturating_sub(1);
@@ -819,7 +1431,7 @@ impl App {
     // Scroll help content down
     pub fn scroll_help_down(&mut self) {
         // The help content has a fixed number of lines, so we set a reasonable max
-        const MAX_HELP_SCROLL: usize = 30; // Adjust based on help content length
+        const MAX_HELP_SCROLL: usize = 50; // Adjust based on help content length
         self.help_scroll = (self.help_scroll + 1).min(MAX_HELP_SCROLL);
     }
 
@@ -835,6 +1447,85 @@ impl App {
         }
     }
 
+    // Show the steps of the running workflow as they start and finish
+    pub fn update_running_steps(&mut self) {
+        let events = wrkflw_executor::progress::take();
+        let Some(execution) = self
+            .current_execution
+            .and_then(|idx| self.workflows.get_mut(idx))
+            .and_then(|workflow| workflow.execution_details.as_mut())
+        else {
+            return;
+        };
+        if execution.end_time.is_some() {
+            return;
+        }
+
+        for event in events {
+            let (job_name, step) = match &event {
+                ProgressEvent::StepStarted { job, step, .. }
+                | ProgressEvent::StepFinished { job, step, .. } => (job, *step),
+                ProgressEvent::StageStarted {
+                    stage,
+                    index,
+                    total,
+                    ..
+                } => {
+                    execution.stage = Some(format!("{} ({}/{})", stage, index + 1, total));
+                    execution.progress = execution.progress.max(*index as f64 / *total as f64);
+                    continue;
+                }
+                ProgressEvent::StageFinished { .. } => continue,
+            };
+            let job = match execution.jobs.iter().position(|job| &job.name == job_name) {
+                Some(idx) => &mut execution.jobs[idx],
+                None => {
+                    execution.jobs.push(JobExecution {
+                        name: job_name.clone(),
+                        status: JobStatus::Skipped,
+                        steps: Vec::new(),
+                        logs: Vec::new(),
+                        running: true,
+                    });
+                    execution.jobs.last_mut().unwrap()
+                }
+            };
+            if job.steps.len() <= step {
+                job.steps.resize_with(step + 1, || StepExecution {
+                    name: String::new(),
+                    status: StepStatus::Skipped,
+                    output: String::new(),
+                    duration: Duration::ZERO,
+                    running_since: None,
+                });
+            }
+            match event {
+                ProgressEvent::StepStarted { name, .. } => {
+                    job.steps[step].name = name;
+                    job.steps[step].running_since = Some(Instant::now());
+                }
+                ProgressEvent::StepFinished { result, .. } => {
+                    if result.status == StepStatus::Failure {
+                        job.status = JobStatus::Failure;
+                    }
+                    job.steps[step] = StepExecution {
+                        name: result.name,
+                        status: result.status,
+                        output: result.output,
+                        duration: result.duration,
+                        running_since: None,
+                    };
+                }
+                ProgressEvent::StageStarted { .. } | ProgressEvent::StageFinished { .. } => {}
+            }
+        }
+
+        // Jobs stay running until the run's result gives their final status
+        if self.job_list_state.selected().is_none() && !execution.jobs.is_empty() {
+            self.job_list_state.select(Some(0));
+        }
+    }
+
     // Set a temporary status message to be displayed in the UI
     pub fn set_status_message(&mut self, message: String) {
         self.status_message = Some(message);
@@ -861,75 +1552,79 @@ impl App {
         }
     }
 
-    // Trigger the selected workflow
-    pub fn trigger_selected_workflow(&mut self) {
-        if let Some(selected_idx) = self.workflow_list_state.selected() {
-            if selected_idx < self.workflows.len() {
-                let workflow = &self.workflows[selected_idx];
+    // Trigger a workflow on GitHub, or its pipeline on GitLab, and follow the run
+    pub fn trigger_workflow(&mut self, idx: usize, params: TriggerParams) {
+        let Some(workflow) = self.workflows.get(idx) else {
+            self.logs
+                .push("No workflow selected to trigger".to_string());
+            wrkflw_logging::warning("No workflow selected to trigger");
+            return;
+        };
+        if workflow.name.is_empty() {
+            let timestamp = Local::now().format("%H:%M:%S").to_string();
+            self.logs
+                .push(format!("[{}] Error: Invalid workflow selection", timestamp));
+            wrkflw_logging::error("Invalid workflow selection in trigger_workflow");
+            return;
+        }
 
-                if workflow.name.is_empty() {
-                    let timestamp = Local::now().format("%H:%M:%S").to_string();
-                    self.logs
-                        .push(format!("[{}] Error: Invalid workflow selection", timestamp));
-                    wrkflw_logging::error(
-                        "Invalid workflow selection in trigger_selected_workflow",
-                    );
-                    return;
-                }
+        let gitlab = wrkflw_utils::is_gitlab_pipeline_file(&workflow.path);
+        let timestamp = Local::now().format("%H:%M:%S").to_string();
+        let branch = params.branch.as_deref().unwrap_or("the default branch");
+        self.logs.push(format!(
+            "[{}] Triggering workflow: {} on {}",
+            timestamp, workflow.name, branch
+        ));
+        wrkflw_logging::info(&format!(
+            "Triggering workflow: {} on {}",
+            workflow.name, branch
+        ));
 
-                // Set up background task to execute the workflow via GitHub Actions REST API
-                let timestamp = Local::now().format("%H:%M:%S").to_string();
-                self.logs.push(format!(
-                    "[{}] Triggering workflow: {}",
-                    timestamp, workflow.name
-                ));
-                wrkflw_logging::info(&format!("Triggering workflow: {}", workflow.name));
+        // Clone necessary values for the async task
+        let workflow_name = workflow.name.clone();
+        let tx_clone = self.tx.clone();
 
-                // Clone necessary values for the async task
-                let workflow_name = workflow.name.clone();
-                let tx_clone = self.tx.clone();
-
-                // Set this tab as the current execution to ensure it shows in the Execution tab
-                self.current_execution = Some(selected_idx);
-
-                // Switch to execution tab for better user feedback
-                self.selected_tab = 1; // Switch to Execution tab manually to avoid the borrowing issue
-
-                // Create a thread instead of using tokio runtime directly since send() is not async
-                std::thread::spawn(move || {
-                    // Create a runtime for the thread
-                    let rt = match tokio::runtime::Runtime::new() {
-                        Ok(runtime) => runtime,
-                        Err(e) => {
-                            let _ = tx_clone.send((
-                                selected_idx,
-                                Err(format!("Failed to create Tokio runtime: {}", e)),
-                            ));
-                            return;
-                        }
-                    };
+        // Set this tab as the current execution to ensure it shows in the Execution tab
+        self.current_execution = Some(idx);
 
-                    // Execute the GitHub Actions trigger API call
-                    let result = rt.block_on(async {
-                        crate::handlers::workflow::execute_curl_trigger(&workflow_name, None).await
-                    });
+        // Switch to execution tab for better user feedback
+        self.selected_tab = 1; // Switch to Execution tab manually to avoid the borrowing issue
 
-                    // Send the result back to the main thread
-                    if let Err(e) = tx_clone.send((selected_idx, result)) {
-                        wrkflw_logging::error(&format!("Error sending trigger result: {}", e));
-                    }
-                });
-            } else {
-                let timestamp = Local::now().format("%H:%M:%S").to_string();
-                self.logs
-                    .push(format!("[{}] No workflow selected to trigger", timestamp));
-                wrkflw_logging::warning("No workflow selected to trigger");
+        // Create a thread instead of using tokio runtime directly since send() is not async
+        std::thread::spawn(move || {
+            // Create a runtime for the thread
+            let rt = match tokio::runtime::Runtime::new() {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    let _ =
+                        tx_clone.send((idx, Err(format!("Failed to create Tokio runtime: {}", e))));
+                    return;
+                }
+            };
+
+            // Dispatch through the GitHub Actions or GitLab REST API and follow the run
+            let result = rt.block_on(async {
+                if gitlab {
+                    crate::handlers::workflow::execute_gitlab_trigger(
+                        params.branch.as_deref(),
+                        &params.inputs,
+                    )
+                    .await
+                } else {
+                    crate::handlers::workflow::execute_curl_trigger(
+                        &workflow_name,
+                        params.branch.as_deref(),
+                        &params.inputs,
+                    )
+                    .await
+                }
+            });
+
+            // Send the result back to the main thread
+            if let Err(e) = tx_clone.send((idx, result)) {
+                wrkflw_logging::error(&format!("Error sending trigger result: {}", e));
             }
-        } else {
-            self.logs
-                .push("No workflow selected to trigger".to_string());
-            wrkflw_logging::warning("No workflow selected to trigger");
-        }
+        });
     }
 
     // Reset a workflow's status to NotStarted
@@ -961,6 +1656,7 @@ impl App {
                     WorkflowStatus::Success => "Success",
                     WorkflowStatus::Failed => "Failed",
                     WorkflowStatus::Skipped => "Skipped",
+                    WorkflowStatus::Cancelled => "Cancelled",
                     WorkflowStatus::NotStarted => "NotStarted",
                     WorkflowStatus::Running => "Running",
                 };
@@ -1,6 +1,7 @@
 // Checkbox component
+use crate::theme::theme;
 use ratatui::{
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::Paragraph,
 };
@@ -45,10 +46,10 @@ impl Checkbox {
 
         let style = if self.is_selected {
             Style::default()
-                .fg(Color::Yellow)
+                .fg(theme().title)
                 .add_modifier(Modifier::BOLD)
         } else {
-            Style::default().fg(Color::White)
+            Style::default().fg(theme().text)
         };
 
         Paragraph::new(Line::from(vec![
@@ -1,13 +1,16 @@
 // Workflow handlers
 use crate::app::App;
-use crate::models::{ExecutionResultMsg, WorkflowExecution, WorkflowStatus};
+use crate::models::{evaluate_file, ExecutionResultMsg, WorkflowExecution, WorkflowStatus};
 use chrono::Local;
+use std::collections::{HashMap, HashSet};
 use std::io;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 use std::thread;
-use wrkflw_evaluator::evaluate_workflow_file;
+use wrkflw_executor::cancel::RunCancel;
 use wrkflw_executor::{self, JobStatus, RuntimeType, StepStatus};
+use wrkflw_github::runs::{read_step_log, RunsClient};
+use wrkflw_gitlab::pipelines::{PipelineUpdate, PipelinesClient};
 
 // Validate a workflow or directory containing workflows
 pub fn validate_workflow(path: &Path, verbose: bool) -> io::Result<()> {
@@ -39,7 +42,7 @@ pub fn validate_workflow(path: &Path, verbose: bool) -> io::Result<()> {
     println!("Validating {} workflow file(s)...", workflows.len());
 
     for workflow_path in workflows {
-        match evaluate_workflow_file(&workflow_path, verbose) {
+        match evaluate_file(&workflow_path, verbose) {
             Ok(result) => {
                 if result.is_valid {
                     println!("✅ Valid: {}", workflow_path.display());
@@ -51,6 +54,9 @@ pub fn validate_workflow(path: &Path, verbose: bool) -> io::Result<()> {
                     }
                     invalid_count += 1;
                 }
+                for warning in &result.warnings {
+                    println!("   ⚠️  {}", warning);
+                }
             }
             Err(e) => {
                 println!("❌ Error processing {}: {}", workflow_path.display(), e);
@@ -67,11 +73,26 @@ pub fn validate_workflow(path: &Path, verbose: bool) -> io::Result<()> {
     Ok(())
 }
 
+// Configuration variables from `.wrkflw/vars.toml` of the current directory
+fn project_vars() -> HashMap<String, String> {
+    let vars = std::env::current_dir()
+        .map_err(|e| e.to_string())
+        .and_then(|dir| wrkflw_executor::vars::VarsFile::for_project(&dir).load());
+    match vars {
+        Ok(vars) => vars.into_iter().collect(),
+        Err(e) => {
+            wrkflw_logging::warning(&format!("Running without vars: {}", e));
+            HashMap::new()
+        }
+    }
+}
+
 // Execute a workflow through the CLI
 pub async fn execute_workflow_cli(
     path: &Path,
     runtime_type: RuntimeType,
     verbose: bool,
+    env: HashMap<String, String>,
 ) -> io::Result<()> {
     if !path.exists() {
         return Err(io::Error::new(
@@ -81,7 +102,7 @@ pub async fn execute_workflow_cli(
     }
 
     println!("Validating workflow...");
-    match evaluate_workflow_file(path, false) {
+    match evaluate_file(path, false) {
         Ok(result) => {
             if !result.is_valid {
                 println!("❌ Cannot execute invalid workflow: {}", path.display());
@@ -124,6 +145,7 @@ pub async fn execute_workflow_cli(
         }
         RuntimeType::SecureEmulation => RuntimeType::SecureEmulation,
         RuntimeType::Emulation => RuntimeType::Emulation,
+        RuntimeType::Wasm => RuntimeType::Wasm,
     };
 
     println!("Executing workflow: {}", path.display());
@@ -142,6 +164,10 @@ pub async fn execute_workflow_cli(
         verbose,
         preserve_containers_on_failure: false, // Default for this path
         secrets_config: None,                  // Use default secrets configuration
+        log_files: true,
+        env,
+        vars: project_vars(),
+        ..Default::default()
     };
 
     match wrkflw_executor::execute_workflow(path, config).await {
@@ -163,6 +189,10 @@ pub async fn execute_workflow_cli(
                     JobStatus::Skipped => {
                         println!("\n⏭️ Job skipped: {}", job.name);
                     }
+                    JobStatus::Cancelled => {
+                        println!("\n🚫 Job cancelled: {}", job.name);
+                        any_job_failed = true;
+                    }
                 }
 
                 println!("-------------------------");
@@ -288,17 +318,13 @@ pub async fn execute_workflow_cli(
 pub async fn execute_curl_trigger(
     workflow_name: &str,
     branch: Option<&str>,
+    inputs: &HashMap<String, String>,
 ) -> Result<(Vec<wrkflw_executor::JobResult>, ()), String> {
-    // Get GitHub token
-    let token = std::env::var("GITHUB_TOKEN").map_err(|_| {
-        "GitHub token not found. Please set GITHUB_TOKEN environment variable".to_string()
-    })?;
-
-    // Debug log to check if GITHUB_TOKEN is set
-    match std::env::var("GITHUB_TOKEN") {
-        Ok(token) => wrkflw_logging::info(&format!("GITHUB_TOKEN is set: {}", &token[..5])), // Log first 5 characters for security
-        Err(_) => wrkflw_logging::error("GITHUB_TOKEN is not set"),
-    }
+    // Get GitHub token from the environment or the gh CLI
+    let credential = wrkflw_utils::auth::github_token()
+        .ok_or_else(|| wrkflw_github::GithubError::TokenNotFound.to_string())?;
+    wrkflw_logging::info(&format!("Using GitHub token from {}", credential.source));
+    let token = credential.token;
 
     // Get repository information
     let repo_info = wrkflw_github::get_repo_info()
@@ -319,10 +345,24 @@ pub async fn execute_curl_trigger(
 
     wrkflw_logging::info(&format!("Using workflow name: {}", workflow_name));
 
+    // Runs from before the dispatch, to find the run it starts and follow it
+    let runs_client = RunsClient::from_env(repo_info.clone()).ok();
+    let known_runs = match &runs_client {
+        Some(client) => client
+            .workflow_runs(workflow_name)
+            .await
+            .ok()
+            .map(|runs| runs.into_iter().map(|run| run.id).collect::<HashSet<u64>>()),
+        None => None,
+    };
+
     // Construct JSON payload
-    let payload = serde_json::json!({
+    let mut payload = serde_json::json!({
         "ref": branch_ref
     });
+    if !inputs.is_empty() {
+        payload["inputs"] = serde_json::json!(inputs);
+    }
 
     // Construct API URL
     let url = format!(
@@ -357,6 +397,15 @@ pub async fn execute_curl_trigger(
         return Err(format!("API error: {} - {}", status, error_message));
     }
 
+    if let (Some(client), Some(known_runs)) = (&runs_client, known_runs) {
+        match follow_remote_run(client, workflow_name, &known_runs).await {
+            Ok(jobs) => return Ok((jobs, ())),
+            Err(e) => {
+                wrkflw_logging::warning(&format!("Could not follow the triggered run: {}", e))
+            }
+        }
+    }
+
     // Success message with URL to view the workflow
     let success_msg = format!(
         "Workflow triggered successfully. View it at: https://github.com/{}/{}/actions/workflows/{}.yml",
@@ -371,13 +420,163 @@ pub async fn execute_curl_trigger(
             name: "Remote Trigger".to_string(),
             status: wrkflw_executor::StepStatus::Success,
             output: success_msg,
+            duration: std::time::Duration::ZERO,
         }],
         logs: "Workflow triggered remotely on GitHub".to_string(),
+        environment: None,
+        timing: wrkflw_executor::JobTiming::default(),
     };
 
     Ok((vec![job_result], ()))
 }
 
+// Start a pipeline on GitLab and follow it until it finishes, logging its progress,
+// and turn its jobs into results with their traces as step output
+pub async fn execute_gitlab_trigger(
+    branch: Option<&str>,
+    variables: &HashMap<String, String>,
+) -> Result<(Vec<wrkflw_executor::JobResult>, ()), String> {
+    let repo_info = wrkflw_gitlab::get_repo_info()
+        .map_err(|e| format!("Failed to get repository info: {}", e))?;
+    let branch_ref = branch.unwrap_or(&repo_info.default_branch).to_string();
+    let client = PipelinesClient::from_env(repo_info).map_err(|e| e.to_string())?;
+
+    wrkflw_logging::info(&format!(
+        "Triggering pipeline for {}/{} on {}",
+        client.repo().namespace,
+        client.repo().project,
+        branch_ref
+    ));
+    let pipeline = client
+        .trigger(&branch_ref, variables)
+        .await
+        .map_err(|e| format!("Error triggering GitLab pipeline: {}", e))?;
+    wrkflw_logging::info(&format!(
+        "Following pipeline {}: {}",
+        pipeline.id, pipeline.web_url
+    ));
+
+    let mut traces = HashMap::new();
+    let pipeline = client
+        .follow(pipeline.id, |update| match update {
+            PipelineUpdate::Job(_) => {
+                wrkflw_logging::info(&format!("Pipeline {}: {}", pipeline.id, update))
+            }
+            PipelineUpdate::Trace { job, trace } => {
+                traces.insert(job.clone(), trace.clone());
+            }
+        })
+        .await
+        .map_err(|e| format!("Failed to follow pipeline {}: {}", pipeline.id, e))?;
+    wrkflw_logging::info(&format!(
+        "Pipeline {} finished: {}",
+        pipeline.id, pipeline.status
+    ));
+
+    let mut jobs = client
+        .pipeline_jobs(pipeline.id)
+        .await
+        .map_err(|e| e.to_string())?;
+    jobs.sort_by_key(|job| job.id);
+    Ok((
+        jobs.into_iter()
+            .map(|job| {
+                let (job_status, step_status) = match job.status.as_str() {
+                    "success" => (JobStatus::Success, StepStatus::Success),
+                    "skipped" | "manual" => (JobStatus::Skipped, StepStatus::Skipped),
+                    "canceled" => (JobStatus::Cancelled, StepStatus::Skipped),
+                    _ => (JobStatus::Failure, StepStatus::Failure),
+                };
+                let duration = std::time::Duration::from_secs_f64(job.duration.unwrap_or(0.0));
+                wrkflw_executor::JobResult {
+                    status: job_status,
+                    steps: vec![wrkflw_executor::StepResult {
+                        name: format!("{} › {}", job.stage, job.name),
+                        status: step_status,
+                        output: traces.remove(&job.name).unwrap_or_default(),
+                        duration,
+                    }],
+                    logs: format!("Pipeline {} on GitLab: {}", pipeline.id, pipeline.web_url),
+                    environment: None,
+                    timing: wrkflw_executor::JobTiming::default(),
+                    name: job.name,
+                }
+            })
+            .collect(),
+        (),
+    ))
+}
+
+// Follow the run a dispatch started until it completes, logging its progress, and
+// turn its jobs into results with the step logs of the downloaded log archive
+async fn follow_remote_run(
+    client: &RunsClient,
+    workflow_name: &str,
+    known_runs: &HashSet<u64>,
+) -> Result<Vec<wrkflw_executor::JobResult>, String> {
+    let run = client
+        .wait_for_dispatched_run(workflow_name, known_runs)
+        .await
+        .map_err(|e| e.to_string())?;
+    wrkflw_logging::info(&format!(
+        "Following run #{}: {}",
+        run.run_number, run.html_url
+    ));
+    let (run, jobs) = client
+        .follow(run.id, |update| {
+            wrkflw_logging::info(&format!("Run #{}: {}", run.run_number, update))
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+    wrkflw_logging::info(&format!(
+        "Run #{} finished: {}",
+        run.run_number,
+        run.state()
+    ));
+
+    let project_dir = std::env::current_dir().map_err(|e| e.to_string())?;
+    let archive = wrkflw_github::runs::logs_file(&project_dir, run.id);
+    let archive = match client.download_logs(run.id, &archive).await {
+        Ok(()) => Some(archive),
+        Err(e) => {
+            wrkflw_logging::warning(&format!("Could not download the run's logs: {}", e));
+            None
+        }
+    };
+
+    Ok(jobs
+        .into_iter()
+        .map(|job| wrkflw_executor::JobResult {
+            status: match job.state() {
+                "success" => JobStatus::Success,
+                "skipped" | "neutral" => JobStatus::Skipped,
+                _ => JobStatus::Failure,
+            },
+            steps: job
+                .steps
+                .iter()
+                .map(|step| wrkflw_executor::StepResult {
+                    name: step.name.clone(),
+                    status: match step.state() {
+                        "success" => StepStatus::Success,
+                        "skipped" | "neutral" => StepStatus::Skipped,
+                        _ => StepStatus::Failure,
+                    },
+                    output: archive
+                        .as_deref()
+                        .and_then(|archive| read_step_log(archive, &job.name, step.number))
+                        .unwrap_or_default(),
+                    duration: std::time::Duration::ZERO,
+                })
+                .collect(),
+            logs: format!("Run #{} on GitHub: {}", run.run_number, run.html_url),
+            environment: None,
+            timing: wrkflw_executor::JobTiming::default(),
+            name: job.name,
+        })
+        .collect())
+}
+
 // Extract common workflow execution logic to avoid duplication
 pub fn start_next_workflow_execution(
     app: &mut App,
@@ -458,10 +657,16 @@ pub fn start_next_workflow_execution(
             }
             RuntimeType::SecureEmulation => RuntimeType::SecureEmulation,
             RuntimeType::Emulation => RuntimeType::Emulation,
+            RuntimeType::Wasm => RuntimeType::Wasm,
         };
 
         let validation_mode = app.validation_mode;
+        let dry_run_mode = app.dry_run_mode;
         let preserve_containers_on_failure = app.preserve_containers_on_failure;
+        let sandbox_config = app.sandbox_config.clone();
+        let env = app.env.clone();
+        let run_params = app.workflows[next_idx].run_params.clone();
+        app.workflows[next_idx].run_params.resume = None;
 
         // Update workflow status and add execution details
         app.workflows[next_idx].status = WorkflowStatus::Running;
@@ -474,9 +679,15 @@ pub fn start_next_workflow_execution(
                 end_time: None,
                 logs: Vec::new(),
                 progress: 0.0,
+                stage: None,
             });
         }
 
+        // Show the steps of this run as they start and finish
+        wrkflw_executor::progress::listen();
+        app.run_cancel = RunCancel::new();
+        let cancel = app.run_cancel.clone();
+
         thread::spawn(move || {
             let rt = match tokio::runtime::Runtime::new() {
                 Ok(runtime) => runtime,
@@ -492,7 +703,7 @@ pub fn start_next_workflow_execution(
             let result = rt.block_on(async {
                 if validation_mode {
                     // Perform validation instead of execution
-                    match evaluate_workflow_file(&workflow_path, verbose) {
+                    match evaluate_file(&workflow_path, verbose) {
                         Ok(validation_result) => {
                             // Create execution result based on validation
                             let status = if validation_result.is_valid {
@@ -513,6 +724,7 @@ pub fn start_next_workflow_execution(
                                         wrkflw_executor::StepStatus::Failure
                                     },
                                     output: validation_result.issues.join("\n"),
+                                    duration: std::time::Duration::ZERO,
                                 }],
                                 logs: format!(
                                     "Validation result: {}",
@@ -522,6 +734,8 @@ pub fn start_next_workflow_execution(
                                         "FAILED"
                                     }
                                 ),
+                                environment: None,
+                                timing: wrkflw_executor::JobTiming::default(),
                             }];
 
                             Ok((jobs, ()))
@@ -529,14 +743,31 @@ pub fn start_next_workflow_execution(
                         Err(e) => Err(e.to_string()),
                     }
                 } else {
+                    let runtime_mode = runtime_type.mode();
                     // Use safe FD redirection for execution
                     let config = wrkflw_executor::ExecutionConfig {
                         runtime_type,
                         verbose,
                         preserve_containers_on_failure,
                         secrets_config: None, // Use default secrets configuration
+                        sandbox_config,
+                        event: run_params.event,
+                        inputs: run_params.inputs,
+                        log_files: true,
+                        env,
+                        vars: project_vars(),
+                        resume: run_params.resume,
+                        cancel,
+                        ..Default::default()
                     };
 
+                    if dry_run_mode {
+                        return wrkflw_executor::plan_workflow(&workflow_path, &config)
+                            .await
+                            .map(|plan| (plan_job_results(&plan), ()))
+                            .map_err(|e| e.to_string());
+                    }
+
                     let execution_result = wrkflw_utils::fd::with_stderr_to_null(|| {
                         futures::executor::block_on(async {
                             wrkflw_executor::execute_workflow(&workflow_path, config).await
@@ -546,6 +777,22 @@ pub fn start_next_workflow_execution(
 
                     match execution_result {
                         Ok(execution_result) => {
+                            if let Err(e) = std::env::current_dir()
+                                .map_err(|e| e.to_string())
+                                .and_then(|dir| {
+                                    wrkflw_executor::runs::RunStore::for_project(&dir).save(
+                                        &workflow_path,
+                                        &execution_result,
+                                        runtime_mode,
+                                        false,
+                                    )
+                                })
+                            {
+                                wrkflw_logging::warning(&format!(
+                                    "Failed to record the run: {}",
+                                    e
+                                ));
+                            }
                             // Send back the job results in a wrapped result
                             Ok((execution_result.jobs, ()))
                         }
@@ -567,3 +814,49 @@ pub fn start_next_workflow_execution(
         wrkflw_logging::info("All workflows completed execution");
     }
 }
+
+/// Show a dry-run plan as job results: nothing has run, so planned jobs and steps are
+/// skipped, and jobs that could not run at all are failed
+fn plan_job_results(plan: &wrkflw_executor::ExecutionPlan) -> Vec<wrkflw_executor::JobResult> {
+    use wrkflw_executor::plan::JobDisposition;
+
+    plan.stages
+        .iter()
+        .flatten()
+        .map(|job| {
+            let (status, logs) = match &job.disposition {
+                JobDisposition::Run => (
+                    JobStatus::Skipped,
+                    format!("Dry run: would run {}", job.runner)
+                        .trim_end()
+                        .to_string(),
+                ),
+                JobDisposition::Skip(reason) => (JobStatus::Skipped, reason.clone()),
+                JobDisposition::Fail(reason) => {
+                    (JobStatus::Failure, format!("Would fail: {}", reason))
+                }
+            };
+            wrkflw_executor::JobResult {
+                name: job.name.clone(),
+                status,
+                steps: job
+                    .steps
+                    .iter()
+                    .map(|step| wrkflw_executor::StepResult {
+                        name: step.name.clone(),
+                        status: if step.would_fail() {
+                            StepStatus::Failure
+                        } else {
+                            StepStatus::Skipped
+                        },
+                        output: format!("[{}]\n{}", step.kind, step.detail),
+                        duration: std::time::Duration::ZERO,
+                    })
+                    .collect(),
+                logs,
+                environment: None,
+                timing: wrkflw_executor::JobTiming::default(),
+            }
+        })
+        .collect()
+}
@@ -5,6 +5,7 @@
 // - models: Contains the data structures for the UI
 // - components: Contains reusable UI elements
 // - handlers: Contains workflow handling logic
+// - theme: Contains the color palettes the views draw with
 // - utils: Contains utility functions
 // - views: Contains UI rendering code
 
@@ -14,10 +15,11 @@ pub mod components;
 pub mod handlers;
 pub mod log_processor;
 pub mod models;
+pub mod theme;
 pub mod utils;
 pub mod views;
 
 // Re-export main entry points
-pub use app::run_wrkflw_tui;
+pub use app::{restore_terminal, run_wrkflw_tui};
 pub use handlers::workflow::execute_workflow_cli;
 pub use handlers::workflow::validate_workflow;
@@ -1,7 +1,8 @@
 // Background log processor for asynchronous log filtering and formatting
 use crate::models::LogFilterLevel;
+use crate::theme::theme;
 use ratatui::{
-    style::{Color, Style},
+    style::Style,
     text::{Line, Span},
     widgets::{Cell, Row},
 };
@@ -19,6 +20,18 @@ pub struct ProcessedLogEntry {
 }
 
 impl ProcessedLogEntry {
+    /// The entry as plain text, for saving or copying
+    pub fn text(&self) -> String {
+        let content: String = self
+            .content_spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect();
+        format!("{} {} {}", self.timestamp, self.log_type, content)
+            .trim()
+            .to_string()
+    }
+
     /// Convert to a table row for rendering
     pub fn to_row(&self) -> Row<'static> {
         Row::new(vec![
@@ -215,26 +228,26 @@ impl LogProcessor {
         let (log_type, log_style) =
             if log_line.contains("Error") || log_line.contains("error") || log_line.contains("❌")
             {
-                ("ERROR", Style::default().fg(Color::Red))
+                ("ERROR", Style::default().fg(theme().failure))
             } else if log_line.contains("Warning")
                 || log_line.contains("warning")
                 || log_line.contains("⚠️")
             {
-                ("WARN", Style::default().fg(Color::Yellow))
+                ("WARN", Style::default().fg(theme().warning))
             } else if log_line.contains("Success")
                 || log_line.contains("success")
                 || log_line.contains("✅")
             {
-                ("SUCCESS", Style::default().fg(Color::Green))
+                ("SUCCESS", Style::default().fg(theme().success))
             } else if log_line.contains("Running")
                 || log_line.contains("running")
                 || log_line.contains("⟳")
             {
-                ("INFO", Style::default().fg(Color::Cyan))
+                ("INFO", Style::default().fg(theme().running))
             } else if log_line.contains("Triggering") || log_line.contains("triggered") {
-                ("TRIG", Style::default().fg(Color::Magenta))
+                ("TRIG", Style::default().fg(theme().special))
             } else {
-                ("INFO", Style::default().fg(Color::Gray))
+                ("INFO", Style::default().fg(theme().dim))
             };
 
         // Extract content after timestamp
@@ -280,7 +293,7 @@ impl LogProcessor {
                 let match_end = real_idx + search_query.len();
                 spans.push(Span::styled(
                     content[real_idx..match_end].to_string(),
-                    Style::default().bg(Color::Yellow).fg(Color::Black),
+                    Style::default().bg(theme().title).fg(theme().background),
                 ));
 
                 last_idx = match_end;
@@ -1,7 +1,18 @@
 // UI Models for wrkflw
 use chrono::Local;
-use std::path::PathBuf;
-use wrkflw_executor::{JobStatus, StepStatus};
+use ratatui::layout::{Direction, Rect};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use wrkflw_executor::graph::GraphJob;
+use wrkflw_executor::resume::Resume;
+use wrkflw_executor::runs::{RunRecord, RunStore};
+use wrkflw_executor::{JobStatus, RuntimeType, StepStatus, WorkflowGraph};
+use wrkflw_models::ValidationResult;
+use wrkflw_parser::workflow::DispatchInput;
+use wrkflw_parser::yaml;
+use wrkflw_utils::is_gitlab_pipeline_file;
 
 /// Type alias for the complex execution result type
 pub type ExecutionResultMsg = (usize, Result<(Vec<wrkflw_executor::JobResult>, ()), String>);
@@ -13,6 +24,17 @@ pub struct Workflow {
     pub selected: bool,
     pub status: WorkflowStatus,
     pub execution_details: Option<WorkflowExecution>,
+    /// Event and inputs last chosen in the run dialog
+    pub run_params: RunParams,
+}
+
+/// Event a workflow run simulates and its `workflow_dispatch` inputs
+#[derive(Debug, Clone, Default)]
+pub struct RunParams {
+    pub event: Option<String>,
+    pub inputs: HashMap<String, String>,
+    /// Failed run the next run reruns from its failures
+    pub resume: Option<Resume>,
 }
 
 /// Status of a workflow
@@ -23,6 +45,7 @@ pub enum WorkflowStatus {
     Success,
     Failed,
     Skipped,
+    Cancelled,
 }
 
 /// Detailed execution information
@@ -32,6 +55,8 @@ pub struct WorkflowExecution {
     pub end_time: Option<chrono::DateTime<Local>>,
     pub logs: Vec<String>,
     pub progress: f64, // 0.0 - 1.0 for progress bar
+    /// Stage of a GitLab pipeline running now, e.g. `test (2/3)`
+    pub stage: Option<String>,
 }
 
 /// Job execution details
@@ -40,6 +65,8 @@ pub struct JobExecution {
     pub status: JobStatus,
     pub steps: Vec<StepExecution>,
     pub logs: Vec<String>,
+    /// Whether the job is still running; `status` is meaningless until it finishes
+    pub running: bool,
 }
 
 /// Step execution details
@@ -47,6 +74,1142 @@ pub struct StepExecution {
     pub name: String,
     pub status: StepStatus,
     pub output: String,
+    pub duration: Duration,
+    /// When the step started, while it runs; `status` is meaningless until it finishes
+    pub running_since: Option<Instant>,
+}
+
+/// The Graph tab's state for one workflow
+pub struct GraphView {
+    pub path: PathBuf,
+    pub graph: Result<WorkflowGraph, String>,
+    /// Stage and job index of each calling job entered, outermost first
+    pub trail: Vec<(usize, usize)>,
+    pub stage: usize,
+    pub job: usize,
+}
+
+impl GraphView {
+    pub fn new(path: PathBuf) -> Self {
+        let graph = wrkflw_executor::build_graph(&path);
+        GraphView {
+            path,
+            graph,
+            trail: Vec::new(),
+            stage: 0,
+            job: 0,
+        }
+    }
+
+    /// The graph shown: the workflow's own, or that of the called workflow entered last
+    pub fn current(&self) -> Option<&WorkflowGraph> {
+        let mut graph = self.graph.as_ref().ok()?;
+        for &(stage, job) in &self.trail {
+            let call = graph.stages.get(stage)?.get(job)?.call.as_ref()?;
+            graph = call.graph.as_ref().ok()?;
+        }
+        Some(graph)
+    }
+
+    pub fn selected(&self) -> Option<&GraphJob> {
+        self.current()?.stages.get(self.stage)?.get(self.job)
+    }
+
+    /// Move the selection by stage (`dx`) or within a stage (`dy`)
+    pub fn move_selection(&mut self, dx: isize, dy: isize) {
+        let Some(graph) = self.current() else {
+            return;
+        };
+        if graph.stages.is_empty() {
+            return;
+        }
+        let stage = self
+            .stage
+            .saturating_add_signed(dx)
+            .min(graph.stages.len() - 1);
+        let jobs = graph.stages[stage].len();
+        let job = if stage != self.stage {
+            self.job.min(jobs.saturating_sub(1))
+        } else {
+            self.job
+                .saturating_add_signed(dy)
+                .min(jobs.saturating_sub(1))
+        };
+        self.stage = stage;
+        self.job = job;
+    }
+
+    /// Select the job with id `id` in the workflow's own graph; false if it has none
+    pub fn select_job(&mut self, id: &str) -> bool {
+        let Ok(graph) = &self.graph else {
+            return false;
+        };
+        let found = graph.stages.iter().enumerate().find_map(|(stage, jobs)| {
+            jobs.iter()
+                .position(|job| job.id == id)
+                .map(|job| (stage, job))
+        });
+        let Some((stage, job)) = found else {
+            return false;
+        };
+        self.trail.clear();
+        self.stage = stage;
+        self.job = job;
+        true
+    }
+
+    /// Show the workflow the selected job calls; false if it calls none that loaded
+    pub fn enter(&mut self) -> bool {
+        let loaded = self
+            .selected()
+            .and_then(|job| job.call.as_ref())
+            .is_some_and(|call| call.graph.is_ok());
+        if loaded {
+            self.trail.push((self.stage, self.job));
+            self.stage = 0;
+            self.job = 0;
+        }
+        loaded
+    }
+
+    /// Go back to the calling workflow; false if already at the top
+    pub fn leave(&mut self) -> bool {
+        match self.trail.pop() {
+            Some((stage, job)) => {
+                self.stage = stage;
+                self.job = job;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Stages of the GitLab pipeline selected in the Workflows tab, with their jobs
+pub struct PipelineView {
+    pub path: PathBuf,
+    /// Stage names in running order with their jobs, or why the pipeline didn't parse
+    pub stages: Result<Vec<(String, Vec<String>)>, String>,
+}
+
+impl PipelineView {
+    pub fn new(path: PathBuf) -> Self {
+        let stages = wrkflw_parser::gitlab::parse_pipeline(&path)
+            .map(|pipeline| {
+                // GitLab's default stages, and its default stage for jobs without one
+                let mut stages: Vec<(String, Vec<String>)> = pipeline
+                    .stages
+                    .clone()
+                    .unwrap_or_else(|| vec!["build".into(), "test".into(), "deploy".into()])
+                    .into_iter()
+                    .map(|stage| (stage, Vec::new()))
+                    .collect();
+                let declared = stages.len();
+                let mut jobs: Vec<_> = pipeline
+                    .jobs
+                    .iter()
+                    .filter(|(_, job)| job.template != Some(true))
+                    .collect();
+                jobs.sort_by(|a, b| a.0.cmp(b.0));
+                for (name, job) in jobs {
+                    let stage = job.stage.as_deref().unwrap_or("test");
+                    match stages.iter_mut().find(|(s, _)| s == stage) {
+                        Some((_, stage_jobs)) => stage_jobs.push(name.clone()),
+                        None => stages.push((stage.to_string(), vec![name.clone()])),
+                    }
+                }
+                // Jobs of stages `stages:` doesn't list don't run
+                for (stage, _) in &mut stages[declared..] {
+                    stage.push_str(" (not in stages)");
+                }
+                stages.retain(|(_, jobs)| !jobs.is_empty());
+                stages
+            })
+            .map_err(|e| e.to_string());
+        PipelineView { path, stages }
+    }
+}
+
+/// The History tab's state: the runs recorded in the project, newest first
+pub struct HistoryView {
+    pub runs: Vec<RunRecord>,
+    pub selected: usize,
+    /// Id of the run marked to compare the selected one with, instead of the run of
+    /// the same workflow before it
+    pub marked: Option<String>,
+}
+
+impl HistoryView {
+    pub fn load(store: &RunStore) -> Self {
+        HistoryView {
+            runs: store.list(),
+            selected: 0,
+            marked: None,
+        }
+    }
+
+    /// Reload the runs, keeping the selected and marked ones
+    pub fn reload(&mut self, store: &RunStore) {
+        let selected = self.selected_run().map(|run| run.id.clone());
+        self.runs = store.list();
+        self.selected = selected
+            .and_then(|id| self.runs.iter().position(|run| run.id == id))
+            .unwrap_or(0);
+        let runs = &self.runs;
+        self.marked = self
+            .marked
+            .take()
+            .filter(|id| runs.iter().any(|run| &run.id == id));
+    }
+
+    pub fn selected_run(&self) -> Option<&RunRecord> {
+        self.runs.get(self.selected)
+    }
+
+    /// The run the selected one is compared with
+    pub fn baseline(&self) -> Option<&RunRecord> {
+        let selected = self.selected_run()?;
+        match &self.marked {
+            Some(id) if *id != selected.id => self.runs.iter().find(|run| &run.id == id),
+            _ => self.runs[self.selected + 1..]
+                .iter()
+                .find(|run| run.path == selected.path),
+        }
+    }
+
+    pub fn move_selection(&mut self, dy: isize) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(dy)
+            .min(self.runs.len().saturating_sub(1));
+    }
+
+    /// Mark the selected run to compare others with, or unmark it
+    pub fn toggle_mark(&mut self) {
+        let Some(id) = self.selected_run().map(|run| run.id.clone()) else {
+            return;
+        };
+        self.marked = if self.marked.as_ref() == Some(&id) {
+            None
+        } else {
+            Some(id)
+        };
+    }
+}
+
+/// Name of a runtime as the TUI shows it
+pub fn runtime_name(runtime: &RuntimeType) -> &'static str {
+    match runtime {
+        RuntimeType::Docker => "Docker",
+        RuntimeType::Podman => "Podman",
+        RuntimeType::SecureEmulation => "Secure Emulation",
+        RuntimeType::Emulation => "Emulation (Unsafe)",
+        RuntimeType::Wasm => "WebAssembly (Experimental)",
+    }
+}
+
+/// Runtimes the run dialog offers, in the order `e` cycles through them
+const RUNTIMES: [RuntimeType; 4] = [
+    RuntimeType::Docker,
+    RuntimeType::Podman,
+    RuntimeType::SecureEmulation,
+    RuntimeType::Emulation,
+];
+
+/// A row of the run dialog
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RunField {
+    Runtime,
+    Event,
+    /// The `workflow_dispatch` input at this index
+    Input(usize),
+    PreserveContainers,
+    Run,
+}
+
+/// The dialog setting what a workflow run from the Workflows tab runs with
+pub struct RunDialog {
+    /// Index of the workflow to run
+    pub workflow: usize,
+    pub runtime: RuntimeType,
+    /// Events the workflow is triggered by; empty for GitLab pipelines
+    pub events: Vec<String>,
+    pub event: usize,
+    /// Declared inputs with the values entered
+    pub inputs: Vec<(DispatchInput, String)>,
+    pub preserve_containers: bool,
+    pub focus: RunField,
+    /// Why the run can't start yet
+    pub error: Option<String>,
+}
+
+impl RunDialog {
+    pub fn new(
+        idx: usize,
+        workflow: &Workflow,
+        runtime: RuntimeType,
+        preserve_containers: bool,
+    ) -> Self {
+        let definition = if is_gitlab_pipeline_file(&workflow.path) {
+            None
+        } else {
+            wrkflw_parser::workflow::parse_workflow(&workflow.path).ok()
+        };
+        let events = definition
+            .as_ref()
+            .map(|definition| definition.on.clone())
+            .unwrap_or_default();
+        let event = workflow
+            .run_params
+            .event
+            .as_ref()
+            .and_then(|event| events.iter().position(|e| e == event))
+            .unwrap_or(0);
+        let inputs = definition
+            .map(|definition| definition.dispatch_inputs())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|input| {
+                let value = workflow
+                    .run_params
+                    .inputs
+                    .get(&input.name)
+                    .or(input.default.as_ref())
+                    .cloned()
+                    .unwrap_or_default();
+                (input, value)
+            })
+            .collect();
+        RunDialog {
+            workflow: idx,
+            runtime,
+            events,
+            event,
+            inputs,
+            preserve_containers,
+            focus: RunField::Run,
+            error: None,
+        }
+    }
+
+    /// The rows, top to bottom
+    pub fn fields(&self) -> Vec<RunField> {
+        let mut fields = vec![RunField::Runtime];
+        if !self.events.is_empty() {
+            fields.push(RunField::Event);
+        }
+        fields.extend((0..self.inputs.len()).map(RunField::Input));
+        fields.push(RunField::PreserveContainers);
+        fields.push(RunField::Run);
+        fields
+    }
+
+    pub fn move_focus(&mut self, dy: isize) {
+        let fields = self.fields();
+        let current = fields.iter().position(|f| *f == self.focus).unwrap_or(0);
+        let next = (current as isize + dy).rem_euclid(fields.len() as isize);
+        self.focus = fields[next as usize];
+    }
+
+    /// Pick the next (`forward`) or previous value of the focused row, when it has
+    /// a set of values
+    pub fn cycle(&mut self, forward: bool) {
+        let step = |idx: usize, len: usize| {
+            if forward {
+                (idx + 1) % len
+            } else {
+                (idx + len - 1) % len
+            }
+        };
+        match self.focus {
+            RunField::Runtime => {
+                let idx = RUNTIMES
+                    .iter()
+                    .position(|runtime| *runtime == self.runtime)
+                    .unwrap_or(0);
+                self.runtime = RUNTIMES[step(idx, RUNTIMES.len())].clone();
+            }
+            RunField::Event if !self.events.is_empty() => {
+                self.event = step(self.event, self.events.len());
+            }
+            RunField::Input(idx) => {
+                let (input, value) = &mut self.inputs[idx];
+                if !input.options.is_empty() {
+                    let current = input.options.iter().position(|o| o == value);
+                    let next = match current {
+                        Some(current) => step(current, input.options.len()),
+                        None => 0,
+                    };
+                    *value = input.options[next].clone();
+                }
+            }
+            RunField::PreserveContainers => self.preserve_containers = !self.preserve_containers,
+            _ => {}
+        }
+    }
+
+    /// The value typed into, if a free-text input is focused
+    pub fn text_input(&mut self) -> Option<&mut String> {
+        match self.focus {
+            RunField::Input(idx) if self.inputs[idx].0.options.is_empty() => {
+                Some(&mut self.inputs[idx].1)
+            }
+            _ => None,
+        }
+    }
+
+    /// The event and inputs to run with; an error if a required input is empty
+    pub fn params(&self) -> Result<RunParams, String> {
+        if let Some((input, _)) = self
+            .inputs
+            .iter()
+            .find(|(input, value)| input.required && value.is_empty())
+        {
+            return Err(format!("Input '{}' is required", input.name));
+        }
+        Ok(RunParams {
+            event: self.events.get(self.event).cloned(),
+            inputs: self
+                .inputs
+                .iter()
+                .map(|(input, value)| (input.name.clone(), value.clone()))
+                .collect(),
+            resume: None,
+        })
+    }
+}
+
+/// A row of the remote trigger form
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriggerField {
+    Workflow,
+    Branch,
+    /// The input, or GitLab variable, at this index
+    Input(usize),
+    Trigger,
+}
+
+/// What to start a workflow on GitHub, or a pipeline on GitLab, with
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TriggerParams {
+    /// Branch to run on; the repository's default branch if not given
+    pub branch: Option<String>,
+    /// `workflow_dispatch` inputs, or pipeline variables for GitLab
+    pub inputs: HashMap<String, String>,
+}
+
+/// The form starting a workflow remotely from the Workflows tab
+pub struct TriggerDialog {
+    /// Index of the workflow to trigger
+    pub workflow: usize,
+    /// Whether it's a GitLab pipeline, whose inputs are its variables
+    pub gitlab: bool,
+    /// Typed branch name; empty for the default branch
+    pub branch: String,
+    /// Declared inputs with the values entered
+    pub inputs: Vec<(DispatchInput, String)>,
+    pub focus: TriggerField,
+    /// Why the workflow can't be triggered yet
+    pub error: Option<String>,
+}
+
+impl TriggerDialog {
+    pub fn new(idx: usize, workflow: &Workflow) -> Self {
+        let gitlab = is_gitlab_pipeline_file(&workflow.path);
+        let inputs = if gitlab {
+            // Variables of the pipeline, which the trigger can override
+            let mut variables: Vec<(DispatchInput, String)> =
+                wrkflw_parser::gitlab::parse_pipeline(&workflow.path)
+                    .ok()
+                    .and_then(|pipeline| pipeline.variables)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(name, value)| {
+                        let input = DispatchInput {
+                            name,
+                            description: None,
+                            default: Some(value.clone()),
+                            required: false,
+                            options: Vec::new(),
+                        };
+                        (input, value)
+                    })
+                    .collect();
+            variables.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name));
+            variables
+        } else {
+            wrkflw_parser::workflow::parse_workflow(&workflow.path)
+                .map(|definition| definition.dispatch_inputs())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|input| {
+                    let value = input.default.clone().unwrap_or_default();
+                    (input, value)
+                })
+                .collect()
+        };
+        TriggerDialog {
+            workflow: idx,
+            gitlab,
+            branch: String::new(),
+            inputs,
+            focus: TriggerField::Trigger,
+            error: None,
+        }
+    }
+
+    /// The rows, top to bottom
+    pub fn fields(&self) -> Vec<TriggerField> {
+        let mut fields = vec![TriggerField::Workflow, TriggerField::Branch];
+        fields.extend((0..self.inputs.len()).map(TriggerField::Input));
+        fields.push(TriggerField::Trigger);
+        fields
+    }
+
+    pub fn move_focus(&mut self, dy: isize) {
+        let fields = self.fields();
+        let current = fields.iter().position(|f| *f == self.focus).unwrap_or(0);
+        let next = (current as isize + dy).rem_euclid(fields.len() as isize);
+        self.focus = fields[next as usize];
+    }
+
+    /// Pick the next (`forward`) or previous value of the focused row, when it has
+    /// a set of values; the workflow row switches to another of `workflows`, keeping
+    /// the branch
+    pub fn cycle(&mut self, forward: bool, workflows: &[Workflow]) {
+        let step = |idx: usize, len: usize| {
+            if forward {
+                (idx + 1) % len
+            } else {
+                (idx + len - 1) % len
+            }
+        };
+        match self.focus {
+            TriggerField::Workflow if !workflows.is_empty() => {
+                let idx = step(self.workflow.min(workflows.len() - 1), workflows.len());
+                let branch = std::mem::take(&mut self.branch);
+                *self = TriggerDialog::new(idx, &workflows[idx]);
+                self.branch = branch;
+                self.focus = TriggerField::Workflow;
+            }
+            TriggerField::Input(idx) => {
+                let (input, value) = &mut self.inputs[idx];
+                if !input.options.is_empty() {
+                    let current = input.options.iter().position(|o| o == value);
+                    let next = match current {
+                        Some(current) => step(current, input.options.len()),
+                        None => 0,
+                    };
+                    *value = input.options[next].clone();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The value typed into, if a free-text row is focused
+    pub fn text_input(&mut self) -> Option<&mut String> {
+        match self.focus {
+            TriggerField::Branch => Some(&mut self.branch),
+            TriggerField::Input(idx) if self.inputs[idx].0.options.is_empty() => {
+                Some(&mut self.inputs[idx].1)
+            }
+            _ => None,
+        }
+    }
+
+    /// The branch and inputs to trigger with; an error if a required input is empty.
+    /// GitLab variables are only sent when changed, so the pipeline's own values and
+    /// those set on the project still apply to the others
+    pub fn params(&self) -> Result<TriggerParams, String> {
+        if let Some((input, _)) = self
+            .inputs
+            .iter()
+            .find(|(input, value)| input.required && value.is_empty())
+        {
+            return Err(format!("Input '{}' is required", input.name));
+        }
+        let branch = self.branch.trim();
+        Ok(TriggerParams {
+            branch: (!branch.is_empty()).then(|| branch.to_string()),
+            inputs: self
+                .inputs
+                .iter()
+                .filter(|(input, value)| !self.gitlab || input.default.as_ref() != Some(value))
+                .map(|(input, value)| (input.name.clone(), value.clone()))
+                .collect(),
+        })
+    }
+}
+
+/// Where an entry of the find palette jumps to
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaletteTarget {
+    /// Index of a loaded workflow
+    Workflow(usize),
+    /// A job of a loaded workflow, by id
+    Job { workflow: usize, job: String },
+    /// Id of a recorded run
+    Run(String),
+}
+
+impl PaletteTarget {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PaletteTarget::Workflow(_) => "workflow",
+            PaletteTarget::Job { .. } => "job",
+            PaletteTarget::Run(_) => "run",
+        }
+    }
+}
+
+pub struct PaletteEntry {
+    /// What the query is matched against
+    pub label: String,
+    /// Shown after the label: the file, workflow or result the entry belongs to
+    pub detail: String,
+    pub target: PaletteTarget,
+}
+
+/// The find palette: workflows, their jobs and recorded runs, narrowed down by a
+/// fuzzy query
+pub struct Palette {
+    pub query: String,
+    pub entries: Vec<PaletteEntry>,
+    /// Indices of the entries matching the query, best match first
+    pub matches: Vec<usize>,
+    /// Index into `matches`
+    pub selected: usize,
+}
+
+impl Palette {
+    pub fn new(workflows: &[Workflow], runs: &[RunRecord]) -> Self {
+        let mut entries: Vec<PaletteEntry> = workflows
+            .iter()
+            .enumerate()
+            .map(|(idx, workflow)| PaletteEntry {
+                label: workflow.name.clone(),
+                detail: workflow.path.display().to_string(),
+                target: PaletteTarget::Workflow(idx),
+            })
+            .collect();
+        for (idx, workflow) in workflows.iter().enumerate() {
+            entries.extend(job_ids(&workflow.path).into_iter().map(|job| PaletteEntry {
+                label: job.clone(),
+                detail: workflow.name.clone(),
+                target: PaletteTarget::Job { workflow: idx, job },
+            }));
+        }
+        entries.extend(runs.iter().map(|run| PaletteEntry {
+            label: format!("{} {}", run.workflow, run.started.format("%m-%d %H:%M:%S")),
+            detail: if run.success { "success" } else { "failure" }.to_string(),
+            target: PaletteTarget::Run(run.id.clone()),
+        }));
+
+        let mut palette = Palette {
+            query: String::new(),
+            entries,
+            matches: Vec::new(),
+            selected: 0,
+        };
+        palette.update_matches();
+        palette
+    }
+
+    /// Match the entries against the query again, after it changed
+    pub fn update_matches(&mut self) {
+        let mut scored: Vec<(i64, usize)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, entry)| {
+                wrkflw_utils::fuzzy_score(&self.query, &entry.label).map(|score| (score, idx))
+            })
+            .collect();
+        // Best first; ties keep the order workflows, jobs, runs
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        self.matches = scored.into_iter().map(|(_, idx)| idx).collect();
+        self.selected = 0;
+    }
+
+    pub fn move_selection(&mut self, dy: isize) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(dy)
+            .min(self.matches.len().saturating_sub(1));
+    }
+
+    pub fn selected_target(&self) -> Option<&PaletteTarget> {
+        let idx = *self.matches.get(self.selected)?;
+        Some(&self.entries[idx].target)
+    }
+}
+
+// Ids of the jobs a workflow or GitLab pipeline defines, sorted; none if it
+// doesn't parse
+fn job_ids(path: &Path) -> Vec<String> {
+    let gitlab = is_gitlab_pipeline_file(path).to_string();
+    wrkflw_utils::cache::cached_for_file("job-ids", path, &[&gitlab], || parse_job_ids(path))
+}
+
+fn parse_job_ids(path: &Path) -> Vec<String> {
+    let mut ids: Vec<String> = if is_gitlab_pipeline_file(path) {
+        wrkflw_parser::gitlab::parse_pipeline(path)
+            .map(|pipeline| {
+                pipeline
+                    .jobs
+                    .into_iter()
+                    .filter(|(_, job)| job.template != Some(true))
+                    .map(|(id, _)| id)
+                    .collect()
+            })
+            .unwrap_or_default()
+    } else {
+        wrkflw_parser::workflow::parse_workflow(path)
+            .map(|workflow| workflow.jobs.into_keys().collect())
+            .unwrap_or_default()
+    };
+    ids.sort();
+    ids
+}
+
+/// A workflow file opened read-only from the Workflows tab, with its validation
+/// issues next to the lines they concern
+pub struct FileView {
+    pub path: PathBuf,
+    pub lines: Vec<String>,
+    /// Issues by the index of the line they concern
+    pub annotations: BTreeMap<usize, Vec<String>>,
+    /// Issues that couldn't be tied to a line
+    pub unplaced: Vec<String>,
+    /// Index of the first line shown
+    pub scroll: usize,
+    /// Number of lines the pane showed when last drawn
+    pub page: usize,
+    pub search_query: String,
+    pub search_active: bool,
+    /// Indices of the lines containing the search query
+    pub search_matches: Vec<usize>,
+    pub search_match_idx: usize,
+}
+
+impl FileView {
+    pub fn open(path: PathBuf) -> Self {
+        let (lines, issues) = match std::fs::read_to_string(&path) {
+            Ok(content) => (
+                content.lines().map(str::to_string).collect::<Vec<_>>(),
+                file_issues(&path),
+            ),
+            Err(e) => (Vec::new(), vec![format!("Failed to read file: {}", e)]),
+        };
+        let mut annotations: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+        let mut unplaced = Vec::new();
+        for issue in issues {
+            match issue_line(&lines, &issue) {
+                Some(line) => annotations.entry(line).or_default().push(issue),
+                None => unplaced.push(issue),
+            }
+        }
+        FileView {
+            path,
+            lines,
+            annotations,
+            unplaced,
+            scroll: 0,
+            page: 20,
+            search_query: String::new(),
+            search_active: false,
+            search_matches: Vec::new(),
+            search_match_idx: 0,
+        }
+    }
+
+    pub fn issue_count(&self) -> usize {
+        self.annotations.values().map(Vec::len).sum::<usize>() + self.unplaced.len()
+    }
+
+    pub fn scroll_by(&mut self, dy: isize) {
+        self.scroll = self
+            .scroll
+            .saturating_add_signed(dy)
+            .min(self.lines.len().saturating_sub(1));
+    }
+
+    pub fn scroll_to_end(&mut self) {
+        self.scroll = self.lines.len().saturating_sub(self.page);
+    }
+
+    /// Scroll so that line `idx` shows a third of the way down the pane
+    pub fn reveal(&mut self, idx: usize) {
+        self.scroll = idx.saturating_sub(self.page / 3);
+    }
+
+    /// Find the lines containing the search query, ignoring case, and show the first
+    pub fn update_search(&mut self) {
+        let query = self.search_query.to_lowercase();
+        self.search_matches = if query.is_empty() {
+            Vec::new()
+        } else {
+            self.lines
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| line.to_lowercase().contains(&query))
+                .map(|(idx, _)| idx)
+                .collect()
+        };
+        self.search_match_idx = 0;
+        if let Some(&idx) = self.search_matches.first() {
+            self.reveal(idx);
+        }
+    }
+
+    /// Show the next (`forward`) or previous match, wrapping around
+    pub fn cycle_match(&mut self, forward: bool) {
+        let count = self.search_matches.len();
+        if count == 0 {
+            return;
+        }
+        self.search_match_idx = if forward {
+            (self.search_match_idx + 1) % count
+        } else {
+            (self.search_match_idx + count - 1) % count
+        };
+        self.reveal(self.search_matches[self.search_match_idx]);
+    }
+
+    /// The line of the current match, if searching
+    pub fn current_match(&self) -> Option<usize> {
+        self.search_matches.get(self.search_match_idx).copied()
+    }
+}
+
+/// Validate a GitHub workflow, or a GitLab pipeline with the GitLab validators,
+/// from the cache when neither it nor what it depends on changed
+pub fn evaluate_file(path: &Path, verbose: bool) -> Result<ValidationResult, String> {
+    // Verbose validation prints as it goes
+    if verbose {
+        return evaluate_uncached(path, verbose);
+    }
+    let mut inputs = wrkflw_utils::cache::workflow_inputs(path);
+    inputs.push(is_gitlab_pipeline_file(path).to_string());
+    let inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+    wrkflw_utils::cache::cached_for_file("tui-validation", path, &inputs, || {
+        evaluate_uncached(path, verbose).map(|result| result.findings)
+    })
+    .map(ValidationResult::from_findings)
+}
+
+fn evaluate_uncached(path: &Path, verbose: bool) -> Result<ValidationResult, String> {
+    if is_gitlab_pipeline_file(path) {
+        wrkflw_parser::gitlab::parse_pipeline(path)
+            .map(|pipeline| wrkflw_validators::validate_gitlab_pipeline(&pipeline))
+            .map_err(|e| e.to_string())
+    } else {
+        let mut result = wrkflw_evaluator::evaluate_workflow_file(path, verbose)?;
+        for issue in wrkflw_executor::policy::workflow_file_issues(path) {
+            result.add_issue(issue);
+        }
+        Ok(result)
+    }
+}
+
+/// Validation issues of a GitHub workflow or GitLab pipeline
+fn file_issues(path: &Path) -> Vec<String> {
+    match evaluate_file(path, false) {
+        Ok(result) => result.issues,
+        Err(e) => vec![e],
+    }
+}
+
+/// How bad a validation finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueSeverity {
+    /// Makes the file invalid
+    Error,
+    /// Worth reporting without making the file invalid
+    Warning,
+}
+
+/// A validation finding, with the index of the line it concerns when known
+pub struct FileIssue {
+    pub severity: IssueSeverity,
+    pub message: String,
+    pub line: Option<usize>,
+}
+
+/// The validation findings of one file
+pub struct FileValidation {
+    pub path: PathBuf,
+    pub issues: Vec<FileIssue>,
+}
+
+impl FileValidation {
+    pub fn validate(path: PathBuf) -> Self {
+        let lines: Vec<String> = std::fs::read_to_string(&path)
+            .map(|content| content.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+        let (errors, warnings) = match evaluate_file(&path, false) {
+            Ok(result) => (result.issues, result.warnings),
+            Err(e) => (vec![e], Vec::new()),
+        };
+        let issues = errors
+            .into_iter()
+            .map(|message| (IssueSeverity::Error, message))
+            .chain(
+                warnings
+                    .into_iter()
+                    .map(|message| (IssueSeverity::Warning, message)),
+            )
+            .map(|(severity, message)| FileIssue {
+                severity,
+                line: issue_line(&lines, &message),
+                message,
+            })
+            .collect();
+        FileValidation { path, issues }
+    }
+
+    pub fn count(&self, severity: IssueSeverity) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == severity)
+            .count()
+    }
+}
+
+/// The Validation tab's state: the findings of the loaded workflows, one row per file
+/// followed by one row per finding of that file
+pub struct ValidationView {
+    pub files: Vec<FileValidation>,
+    pub selected: usize,
+    pub validated_at: chrono::DateTime<Local>,
+}
+
+impl ValidationView {
+    pub fn validate(paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        ValidationView {
+            files: paths.into_iter().map(FileValidation::validate).collect(),
+            selected: 0,
+            validated_at: Local::now(),
+        }
+    }
+
+    /// File and finding index of each row; the file's own row has no finding
+    pub fn rows(&self) -> Vec<(usize, Option<usize>)> {
+        self.files
+            .iter()
+            .enumerate()
+            .flat_map(|(file, validation)| {
+                std::iter::once((file, None))
+                    .chain((0..validation.issues.len()).map(move |issue| (file, Some(issue))))
+            })
+            .collect()
+    }
+
+    pub fn count(&self, severity: IssueSeverity) -> usize {
+        self.files.iter().map(|file| file.count(severity)).sum()
+    }
+
+    pub fn move_selection(&mut self, dy: isize) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(dy)
+            .min(self.rows().len().saturating_sub(1));
+    }
+
+    /// The selected file and the line its selected finding concerns
+    pub fn selected_location(&self) -> Option<(&Path, Option<usize>)> {
+        let (file, issue) = *self.rows().get(self.selected)?;
+        let validation = &self.files[file];
+        let line = issue.and_then(|issue| validation.issues[issue].line);
+        Some((&validation.path, line))
+    }
+}
+
+/// Index of the line an issue concerns: the position a YAML error gives, else the
+/// first key named by a quoted word of the issue, e.g. `build:` for job 'build'
+fn issue_line(lines: &[String], issue: &str) -> Option<usize> {
+    if let Some((line, _)) = yaml::message_position(issue) {
+        return Some(line.saturating_sub(1).min(lines.len().saturating_sub(1)));
+    }
+    yaml::quoted_names(issue).find_map(|name| {
+        lines.iter().position(|line| {
+            let trimmed = line.trim_start();
+            let item = trimmed.strip_prefix("- ").unwrap_or(trimmed);
+            item.trim_start_matches(['"', '\''])
+                .strip_prefix(name)
+                .is_some_and(|rest| rest.trim_start_matches(['"', '\'']).starts_with(':'))
+        })
+    })
+}
+
+/// A boundary between two panes that can be dragged with the mouse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Split {
+    /// Workflow list | GitLab pipeline stages
+    Workflows,
+    /// Steps table / step output, in the job details
+    JobDetail,
+    /// Stages | selected job
+    Graph,
+    /// Runs | run details
+    History,
+}
+
+/// Share of its tab the first pane of each split takes, in percent, kept between
+/// sessions in `~/.wrkflw/tui.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PaneSizes {
+    pub workflows: u16,
+    pub job_detail: u16,
+    pub graph: u16,
+    pub history: u16,
+}
+
+impl Default for PaneSizes {
+    fn default() -> Self {
+        PaneSizes {
+            workflows: 60,
+            job_detail: 40,
+            graph: 65,
+            history: 50,
+        }
+    }
+}
+
+impl PaneSizes {
+    /// How far a split can be dragged towards either side
+    pub const MIN: u16 = 15;
+    pub const MAX: u16 = 85;
+
+    fn path() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join(".wrkflw")
+            .join("tui.json")
+    }
+
+    /// The sizes saved last, else the defaults
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, content).map_err(|e| e.to_string())
+    }
+
+    pub fn get(&self, split: Split) -> u16 {
+        match split {
+            Split::Workflows => self.workflows,
+            Split::JobDetail => self.job_detail,
+            Split::Graph => self.graph,
+            Split::History => self.history,
+        }
+        .clamp(Self::MIN, Self::MAX)
+    }
+
+    pub fn set(&mut self, split: Split, percent: u16) {
+        let percent = percent.clamp(Self::MIN, Self::MAX);
+        match split {
+            Split::Workflows => self.workflows = percent,
+            Split::JobDetail => self.job_detail = percent,
+            Split::Graph => self.graph = percent,
+            Split::History => self.history = percent,
+        }
+    }
+}
+
+/// A list whose rows can be clicked to select them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListKind {
+    Workflows,
+    Jobs,
+    Steps,
+    Logs,
+    Runs,
+    Validation,
+}
+
+/// Where a clickable list was drawn: the area of its rows and the index of the
+/// first row shown
+#[derive(Debug, Clone, Copy)]
+pub struct ListTarget {
+    pub kind: ListKind,
+    pub rows: Rect,
+    pub offset: usize,
+}
+
+impl ListTarget {
+    /// A list drawn in a bordered block over `area`, below `header` rows of
+    /// column titles
+    pub fn new(kind: ListKind, area: Rect, header: u16, offset: usize) -> Self {
+        let inner = Rect {
+            x: area.x.saturating_add(1),
+            y: area.y.saturating_add(1 + header),
+            width: area.width.saturating_sub(2),
+            height: area.height.saturating_sub(2 + header),
+        };
+        ListTarget {
+            kind,
+            rows: inner,
+            offset,
+        }
+    }
+
+    /// Index of the item drawn at `column`/`row`, if any
+    pub fn item_at(&self, column: u16, row: u16) -> Option<usize> {
+        let inside = column >= self.rows.x
+            && column < self.rows.x + self.rows.width
+            && row >= self.rows.y
+            && row < self.rows.y + self.rows.height;
+        inside.then(|| self.offset + (row - self.rows.y) as usize)
+    }
+}
+
+/// Where a split was drawn: the area it divides and in which direction
+#[derive(Debug, Clone, Copy)]
+pub struct SplitTarget {
+    pub split: Split,
+    pub area: Rect,
+    pub direction: Direction,
+}
+
+impl SplitTarget {
+    /// Position of the boundary along the split's direction, for `percent`
+    pub fn boundary(&self, percent: u16) -> u16 {
+        let (start, length) = match self.direction {
+            Direction::Horizontal => (self.area.x, self.area.width),
+            Direction::Vertical => (self.area.y, self.area.height),
+        };
+        start + (length as u32 * percent as u32 / 100) as u16
+    }
+
+    /// The percentage putting the boundary at `column`/`row`
+    pub fn percent_at(&self, column: u16, row: u16) -> u16 {
+        let (start, length, pos) = match self.direction {
+            Direction::Horizontal => (self.area.x, self.area.width, column),
+            Direction::Vertical => (self.area.y, self.area.height, row),
+        };
+        (pos.saturating_sub(start) as u32 * 100 / length.max(1) as u32) as u16
+    }
+}
+
+/// What the last frame drew that the mouse can act on
+#[derive(Debug, Default)]
+pub struct MouseTargets {
+    /// Row of the tabs and the columns each tab spans
+    pub tabs: Option<(u16, Vec<(u16, u16)>)>,
+    pub list: Option<ListTarget>,
+    pub splits: Vec<SplitTarget>,
 }
 
 /// Log filter levels
@@ -0,0 +1,197 @@
+// Color themes for the TUI
+//
+// Every color the views use comes from the palette of the current theme, picked
+// with `theme = "..."` in the `[tui]` section of `.wrkflw.toml`. Themes defined
+// under `[tui.themes.<name>]` start from a built-in one (`base`, dark if not
+// given) and override the roles they list.
+use ratatui::style::Color;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+use wrkflw_executor::config::{ProjectConfig, TuiConfig};
+
+/// Names of the built-in themes
+pub const BUILTIN_THEMES: &[&str] = &["dark", "light", "solarized"];
+
+/// The colors the TUI draws with, by what they are used for
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    /// Regular text
+    pub text: Color,
+    /// Secondary text: paths, durations, hints
+    pub muted: Color,
+    /// Pending or skipped items
+    pub dim: Color,
+    /// Headings, pane titles and the letters that switch tabs
+    pub title: Color,
+    /// Key names in instructions, and labels
+    pub key: Color,
+    /// Informational labels and progress
+    pub accent: Color,
+    /// Background of the selected row and of the status bar
+    pub highlight: Color,
+    /// Background of lines marked for copying
+    pub mark: Color,
+    pub success: Color,
+    pub failure: Color,
+    pub warning: Color,
+    pub running: Color,
+    pub cancelled: Color,
+    /// Triggered runs, marked runs and other things that stand apart
+    pub special: Color,
+    /// Background of overlays, and text drawn over a colored background
+    pub background: Color,
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Theme {
+            name: "dark".to_string(),
+            text: Color::White,
+            muted: Color::DarkGray,
+            dim: Color::Gray,
+            title: Color::Yellow,
+            key: Color::Cyan,
+            accent: Color::Blue,
+            highlight: Color::DarkGray,
+            mark: Color::Blue,
+            success: Color::Green,
+            failure: Color::Red,
+            warning: Color::Yellow,
+            running: Color::Cyan,
+            cancelled: Color::Magenta,
+            special: Color::Magenta,
+            background: Color::Black,
+        }
+    }
+
+    pub fn light() -> Self {
+        Theme {
+            name: "light".to_string(),
+            text: Color::Black,
+            muted: Color::DarkGray,
+            dim: Color::Gray,
+            title: Color::Blue,
+            key: Color::Magenta,
+            accent: Color::Blue,
+            highlight: Color::Rgb(208, 208, 208),
+            mark: Color::LightCyan,
+            success: Color::Rgb(0, 135, 0),
+            failure: Color::Rgb(175, 0, 0),
+            warning: Color::Rgb(175, 95, 0),
+            running: Color::Rgb(0, 95, 175),
+            cancelled: Color::Magenta,
+            special: Color::Rgb(135, 0, 175),
+            background: Color::White,
+        }
+    }
+
+    pub fn solarized() -> Self {
+        Theme {
+            name: "solarized".to_string(),
+            text: Color::Rgb(147, 161, 161),
+            muted: Color::Rgb(88, 110, 117),
+            dim: Color::Rgb(101, 123, 131),
+            title: Color::Rgb(181, 137, 0),
+            key: Color::Rgb(42, 161, 152),
+            accent: Color::Rgb(38, 139, 210),
+            highlight: Color::Rgb(7, 54, 66),
+            mark: Color::Rgb(108, 113, 196),
+            success: Color::Rgb(133, 153, 0),
+            failure: Color::Rgb(220, 50, 47),
+            warning: Color::Rgb(203, 75, 22),
+            running: Color::Rgb(42, 161, 152),
+            cancelled: Color::Rgb(211, 54, 130),
+            special: Color::Rgb(108, 113, 196),
+            background: Color::Rgb(0, 43, 54),
+        }
+    }
+
+    fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "solarized" => Some(Self::solarized()),
+            _ => None,
+        }
+    }
+
+    fn role_mut(&mut self, role: &str) -> Option<&mut Color> {
+        Some(match role {
+            "text" => &mut self.text,
+            "muted" => &mut self.muted,
+            "dim" => &mut self.dim,
+            "title" => &mut self.title,
+            "key" => &mut self.key,
+            "accent" => &mut self.accent,
+            "highlight" => &mut self.highlight,
+            "mark" => &mut self.mark,
+            "success" => &mut self.success,
+            "failure" => &mut self.failure,
+            "warning" => &mut self.warning,
+            "running" => &mut self.running,
+            "cancelled" => &mut self.cancelled,
+            "special" => &mut self.special,
+            "background" => &mut self.background,
+            _ => return None,
+        })
+    }
+
+    // A user-defined theme: its base with the listed roles overridden
+    fn custom(name: &str, colors: &BTreeMap<String, String>) -> Result<Self, String> {
+        let base = colors.get("base").map_or("dark", String::as_str);
+        let mut theme = Self::builtin(base).ok_or_else(|| {
+            format!(
+                "theme '{}' is based on unknown theme '{}' (built-in: {})",
+                name,
+                base,
+                BUILTIN_THEMES.join(", ")
+            )
+        })?;
+        theme.name = name.to_string();
+        for (role, value) in colors.iter().filter(|(role, _)| *role != "base") {
+            let color = value
+                .parse::<Color>()
+                .map_err(|_| format!("theme '{}': invalid color '{}' for {}", name, value, role))?;
+            *theme
+                .role_mut(role)
+                .ok_or_else(|| format!("theme '{}': unknown role '{}'", name, role))? = color;
+        }
+        Ok(theme)
+    }
+
+    /// The theme selected in the `[tui]` settings, dark by default
+    pub fn from_config(config: &TuiConfig) -> Result<Self, String> {
+        let Some(name) = config.theme.as_deref() else {
+            return Ok(Self::dark());
+        };
+        if let Some(colors) = config.themes.get(name) {
+            return Self::custom(name, colors);
+        }
+        Self::builtin(name).ok_or_else(|| {
+            format!(
+                "unknown theme '{}' (built-in: {})",
+                name,
+                BUILTIN_THEMES.join(", ")
+            )
+        })
+    }
+
+    /// The theme selected in `.wrkflw.toml` in `dir`
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        Self::from_config(&ProjectConfig::load(dir)?.tui)
+    }
+}
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Use `theme` for the rest of the session; only the first call has an effect
+pub fn set_theme(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+/// The palette to draw with
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(Theme::dark)
+}
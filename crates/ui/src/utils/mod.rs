@@ -1,7 +1,7 @@
 // UI utilities
-use crate::models::{Workflow, WorkflowStatus};
+use crate::models::{RunParams, Workflow, WorkflowStatus};
 use std::path::{Path, PathBuf};
-use wrkflw_utils::is_workflow_file;
+use wrkflw_utils::{is_gitlab_pipeline_file, is_workflow_file};
 
 /// Find and load all workflow files in a directory
 pub fn load_workflows(dir_path: &Path) -> Vec<Workflow> {
@@ -27,6 +27,7 @@ pub fn load_workflows(dir_path: &Path) -> Vec<Workflow> {
                     selected: false,
                     status: WorkflowStatus::NotStarted,
                     execution_details: None,
+                    run_params: RunParams::default(),
                 });
             }
         }
@@ -43,8 +44,30 @@ pub fn load_workflows(dir_path: &Path) -> Vec<Workflow> {
                 selected: false,
                 status: WorkflowStatus::NotStarted,
                 execution_details: None,
+                run_params: RunParams::default(),
             });
         }
+
+        // And for the pipeline files split into .gitlab/ci
+        if let Ok(entries) = std::fs::read_dir(Path::new(".gitlab").join("ci")) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_file() && is_gitlab_pipeline_file(&path) {
+                    let name = format!(
+                        "gitlab-ci/{}",
+                        path.file_stem().unwrap_or_default().to_string_lossy()
+                    );
+                    workflows.push(Workflow {
+                        name,
+                        path,
+                        selected: false,
+                        status: WorkflowStatus::NotStarted,
+                        execution_details: None,
+                        run_params: RunParams::default(),
+                    });
+                }
+            }
+        }
     }
 
     // Sort workflows by name
@@ -1,10 +1,11 @@
 // Execution tab rendering
 use crate::app::App;
-use crate::models::WorkflowStatus;
+use crate::models::{ListKind, ListTarget, MouseTargets, WorkflowStatus};
+use crate::theme::theme;
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, Gauge, List, ListItem, Paragraph},
     Frame,
@@ -16,6 +17,7 @@ pub fn render_execution_tab(
     f: &mut Frame<CrosstermBackend<io::Stdout>>,
     app: &mut App,
     area: Rect,
+    targets: &mut MouseTargets,
 ) {
     // Get the workflow index either from current_execution or selected workflow
     let current_workflow_idx = app
@@ -47,28 +49,30 @@ pub fn render_execution_tab(
             WorkflowStatus::Success => "Success",
             WorkflowStatus::Failed => "Failed",
             WorkflowStatus::Skipped => "Skipped",
+            WorkflowStatus::Cancelled => "Cancelled",
         };
 
         let status_style = match workflow.status {
-            WorkflowStatus::NotStarted => Style::default().fg(Color::Gray),
-            WorkflowStatus::Running => Style::default().fg(Color::Cyan),
-            WorkflowStatus::Success => Style::default().fg(Color::Green),
-            WorkflowStatus::Failed => Style::default().fg(Color::Red),
-            WorkflowStatus::Skipped => Style::default().fg(Color::Yellow),
+            WorkflowStatus::NotStarted => Style::default().fg(theme().dim),
+            WorkflowStatus::Running => Style::default().fg(theme().running),
+            WorkflowStatus::Success => Style::default().fg(theme().success),
+            WorkflowStatus::Failed => Style::default().fg(theme().failure),
+            WorkflowStatus::Skipped => Style::default().fg(theme().dim),
+            WorkflowStatus::Cancelled => Style::default().fg(theme().cancelled),
         };
 
         let mut workflow_info = vec![
             Line::from(vec![
-                Span::styled("Workflow: ", Style::default().fg(Color::Blue)),
+                Span::styled("Workflow: ", Style::default().fg(theme().accent)),
                 Span::styled(
                     workflow.name.clone(),
                     Style::default()
-                        .fg(Color::White)
+                        .fg(theme().text)
                         .add_modifier(Modifier::BOLD),
                 ),
             ]),
             Line::from(vec![
-                Span::styled("Status: ", Style::default().fg(Color::Blue)),
+                Span::styled("Status: ", Style::default().fg(theme().accent)),
                 Span::styled(status_text, status_style),
             ]),
         ];
@@ -80,16 +84,21 @@ pub fn render_execution_tab(
 
             // Add progress bar
             let gauge_color = match workflow.status {
-                WorkflowStatus::Running => Color::Cyan,
-                WorkflowStatus::Success => Color::Green,
-                WorkflowStatus::Failed => Color::Red,
-                _ => Color::Gray,
+                WorkflowStatus::Running => theme().running,
+                WorkflowStatus::Success => theme().success,
+                WorkflowStatus::Failed => theme().failure,
+                WorkflowStatus::Cancelled => theme().cancelled,
+                _ => theme().dim,
             };
 
             let progress_text = match workflow.status {
-                WorkflowStatus::Running => format!("{:.0}%", progress * 100.0),
+                WorkflowStatus::Running => match &execution.stage {
+                    Some(stage) => format!("{:.0}% · stage {}", progress * 100.0, stage),
+                    None => format!("{:.0}%", progress * 100.0),
+                },
                 WorkflowStatus::Success => "Completed".to_string(),
                 WorkflowStatus::Failed => "Failed".to_string(),
+                WorkflowStatus::Cancelled => "Cancelled".to_string(),
                 _ => "Not started".to_string(),
             };
 
@@ -99,12 +108,12 @@ pub fn render_execution_tab(
             // Add the gauge widget to the paragraph data
             workflow_info.push(Line::from(vec![Span::styled(
                 format!("Progress: {}", progress_text),
-                Style::default().fg(Color::Blue),
+                Style::default().fg(theme().accent),
             )]));
 
             let gauge = Gauge::default()
                 .block(Block::default())
-                .gauge_style(Style::default().fg(gauge_color).bg(Color::Black))
+                .gauge_style(Style::default().fg(gauge_color).bg(theme().background))
                 .percent((progress * 100.0) as u16);
 
             // Render gauge separately after the paragraph
@@ -114,7 +123,7 @@ pub fn render_execution_tab(
                     .border_type(BorderType::Rounded)
                     .title(Span::styled(
                         " Workflow Information ",
-                        Style::default().fg(Color::Yellow),
+                        Style::default().fg(theme().title),
                     )),
             );
 
@@ -135,7 +144,7 @@ pub fn render_execution_tab(
                         Block::default()
                             .borders(Borders::ALL)
                             .border_type(BorderType::Rounded)
-                            .title(Span::styled(" Jobs ", Style::default().fg(Color::Yellow))),
+                            .title(Span::styled(" Jobs ", Style::default().fg(theme().title))),
                     )
                     .alignment(Alignment::Center);
                 f.render_widget(placeholder, chunks[1]);
@@ -145,17 +154,25 @@ pub fn render_execution_tab(
                     .iter()
                     .map(|job| {
                         let status_symbol = match job.status {
+                            _ if job.running => "⏳",
                             wrkflw_executor::JobStatus::Success => "✅",
                             wrkflw_executor::JobStatus::Failure => "❌",
                             wrkflw_executor::JobStatus::Skipped => "⏭",
+                            wrkflw_executor::JobStatus::Cancelled => "🚫",
                         };
 
                         let status_style = match job.status {
+                            _ if job.running => Style::default().fg(theme().running),
                             wrkflw_executor::JobStatus::Success => {
-                                Style::default().fg(Color::Green)
+                                Style::default().fg(theme().success)
+                            }
+                            wrkflw_executor::JobStatus::Failure => {
+                                Style::default().fg(theme().failure)
+                            }
+                            wrkflw_executor::JobStatus::Skipped => Style::default().fg(theme().dim),
+                            wrkflw_executor::JobStatus::Cancelled => {
+                                Style::default().fg(theme().cancelled)
                             }
-                            wrkflw_executor::JobStatus::Failure => Style::default().fg(Color::Red),
-                            wrkflw_executor::JobStatus::Skipped => Style::default().fg(Color::Gray),
                         };
 
                         // Count completed and total steps
@@ -164,8 +181,9 @@ pub fn render_execution_tab(
                             .steps
                             .iter()
                             .filter(|s| {
-                                s.status == wrkflw_executor::StepStatus::Success
-                                    || s.status == wrkflw_executor::StepStatus::Failure
+                                s.running_since.is_none()
+                                    && (s.status == wrkflw_executor::StepStatus::Success
+                                        || s.status == wrkflw_executor::StepStatus::Failure)
                             })
                             .count();
 
@@ -174,9 +192,9 @@ pub fn render_execution_tab(
                         ListItem::new(Line::from(vec![
                             Span::styled(status_symbol, status_style),
                             Span::raw(" "),
-                            Span::styled(&job.name, Style::default().fg(Color::White)),
+                            Span::styled(&job.name, Style::default().fg(theme().text)),
                             Span::raw(" "),
-                            Span::styled(steps_info, Style::default().fg(Color::DarkGray)),
+                            Span::styled(steps_info, Style::default().fg(theme().muted)),
                         ]))
                     })
                     .collect();
@@ -186,49 +204,55 @@ pub fn render_execution_tab(
                         Block::default()
                             .borders(Borders::ALL)
                             .border_type(BorderType::Rounded)
-                            .title(Span::styled(" Jobs ", Style::default().fg(Color::Yellow))),
+                            .title(Span::styled(" Jobs ", Style::default().fg(theme().title))),
                     )
                     .highlight_style(
                         Style::default()
-                            .bg(Color::DarkGray)
+                            .bg(theme().highlight)
                             .add_modifier(Modifier::BOLD),
                     )
                     .highlight_symbol("» ");
 
                 f.render_stateful_widget(jobs_list, chunks[1], &mut app.job_list_state);
+                targets.list = Some(ListTarget::new(
+                    ListKind::Jobs,
+                    chunks[1],
+                    0,
+                    app.job_list_state.offset(),
+                ));
             }
 
             // Execution info section
             let mut execution_info = Vec::new();
 
             execution_info.push(Line::from(vec![
-                Span::styled("Started: ", Style::default().fg(Color::Blue)),
+                Span::styled("Started: ", Style::default().fg(theme().accent)),
                 Span::styled(
                     execution.start_time.format("%Y-%m-%d %H:%M:%S").to_string(),
-                    Style::default().fg(Color::White),
+                    Style::default().fg(theme().text),
                 ),
             ]));
 
             if let Some(end_time) = execution.end_time {
                 execution_info.push(Line::from(vec![
-                    Span::styled("Finished: ", Style::default().fg(Color::Blue)),
+                    Span::styled("Finished: ", Style::default().fg(theme().accent)),
                     Span::styled(
                         end_time.format("%Y-%m-%d %H:%M:%S").to_string(),
-                        Style::default().fg(Color::White),
+                        Style::default().fg(theme().text),
                     ),
                 ]));
 
                 // Calculate duration
                 let duration = end_time.signed_duration_since(execution.start_time);
                 execution_info.push(Line::from(vec![
-                    Span::styled("Duration: ", Style::default().fg(Color::Blue)),
+                    Span::styled("Duration: ", Style::default().fg(theme().accent)),
                     Span::styled(
                         format!(
                             "{}m {}s",
                             duration.num_minutes(),
                             duration.num_seconds() % 60
                         ),
-                        Style::default().fg(Color::White),
+                        Style::default().fg(theme().text),
                     ),
                 ]));
             } else {
@@ -236,14 +260,14 @@ pub fn render_execution_tab(
                 let current_time = chrono::Local::now();
                 let running_time = current_time.signed_duration_since(execution.start_time);
                 execution_info.push(Line::from(vec![
-                    Span::styled("Running for: ", Style::default().fg(Color::Blue)),
+                    Span::styled("Running for: ", Style::default().fg(theme().accent)),
                     Span::styled(
                         format!(
                             "{}m {}s",
                             running_time.num_minutes(),
                             running_time.num_seconds() % 60
                         ),
-                        Style::default().fg(Color::White),
+                        Style::default().fg(theme().text),
                     ),
                 ]));
             }
@@ -251,9 +275,9 @@ pub fn render_execution_tab(
             // Add hint for Enter key to see details
             execution_info.push(Line::from(""));
             execution_info.push(Line::from(vec![
-                Span::styled("Press ", Style::default().fg(Color::DarkGray)),
-                Span::styled("Enter", Style::default().fg(Color::Yellow)),
-                Span::styled(" to view job details", Style::default().fg(Color::DarkGray)),
+                Span::styled("Press ", Style::default().fg(theme().muted)),
+                Span::styled("Enter", Style::default().fg(theme().title)),
+                Span::styled(" to view job details", Style::default().fg(theme().muted)),
             ]));
 
             let info_widget = Paragraph::new(execution_info).block(
@@ -262,7 +286,7 @@ pub fn render_execution_tab(
                     .border_type(BorderType::Rounded)
                     .title(Span::styled(
                         " Execution Information ",
-                        Style::default().fg(Color::Yellow),
+                        Style::default().fg(theme().title),
                     )),
             );
 
@@ -275,7 +299,7 @@ pub fn render_execution_tab(
                     .border_type(BorderType::Rounded)
                     .title(Span::styled(
                         " Workflow Information ",
-                        Style::default().fg(Color::Yellow),
+                        Style::default().fg(theme().title),
                     )),
             );
 
@@ -287,7 +311,7 @@ pub fn render_execution_tab(
                 Line::from(vec![Span::styled(
                     "No execution data available.",
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(theme().title)
                         .add_modifier(Modifier::BOLD),
                 )]),
                 Line::from(""),
@@ -298,7 +322,7 @@ pub fn render_execution_tab(
                 Block::default()
                     .borders(Borders::ALL)
                     .border_type(BorderType::Rounded)
-                    .title(Span::styled(" Jobs ", Style::default().fg(Color::Yellow))),
+                    .title(Span::styled(" Jobs ", Style::default().fg(theme().title))),
             )
             .alignment(Alignment::Center);
 
@@ -309,11 +333,11 @@ pub fn render_execution_tab(
                 Line::from(""),
                 Line::from(vec![Span::styled(
                     "No execution has been started.",
-                    Style::default().fg(Color::Yellow),
+                    Style::default().fg(theme().title),
                 )]),
                 Line::from(""),
                 Line::from("Press 'Enter' in the Workflows tab to run,"),
-                Line::from("or 't' to trigger on GitHub."),
+                Line::from("or 't' to trigger on GitHub or GitLab."),
             ])
             .block(
                 Block::default()
@@ -321,7 +345,7 @@ pub fn render_execution_tab(
                     .border_type(BorderType::Rounded)
                     .title(Span::styled(
                         " Execution Information ",
-                        Style::default().fg(Color::Yellow),
+                        Style::default().fg(theme().title),
                     )),
             )
             .alignment(Alignment::Center);
@@ -335,7 +359,7 @@ pub fn render_execution_tab(
             Line::from(vec![Span::styled(
                 "No workflow execution data available.",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             )]),
             Line::from(""),
@@ -343,7 +367,9 @@ pub fn render_execution_tab(
             Line::from(""),
             Line::from("Or press Enter on a selected workflow to run it directly."),
             Line::from(""),
-            Line::from("You can also press 't' to trigger a workflow on GitHub remotely."),
+            Line::from(
+                "You can also press 't' to trigger a workflow on GitHub or GitLab remotely.",
+            ),
         ])
         .block(
             Block::default()
@@ -351,7 +377,7 @@ pub fn render_execution_tab(
                 .border_type(BorderType::Rounded)
                 .title(Span::styled(
                     " Execution ",
-                    Style::default().fg(Color::Yellow),
+                    Style::default().fg(theme().title),
                 )),
         )
         .alignment(Alignment::Center);
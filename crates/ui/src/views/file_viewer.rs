@@ -0,0 +1,257 @@
+// Workflow file viewer rendering
+use crate::models::FileView;
+use crate::theme::theme;
+use lazy_static::lazy_static;
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Paragraph, Wrap},
+    Frame,
+};
+use regex::Regex;
+use std::io;
+
+lazy_static! {
+    static ref KEY: Regex = Regex::new(r#"^("[^"]*"|'[^']*'|[^\s#'"{\[][^:#]*?):(\s|$)"#).unwrap();
+    static ref EXPRESSION: Regex = Regex::new(r"\$\{\{.*?\}\}").unwrap();
+    static ref SCALAR: Regex =
+        Regex::new(r"^(true|false|null|~|yes|no|on|off|-?\d+(\.\d+)?)$").unwrap();
+}
+
+// Render a workflow file read-only, with its validation issues under their lines
+pub fn render_file_viewer(
+    f: &mut Frame<CrosstermBackend<io::Stdout>>,
+    view: &mut FileView,
+    area: Rect,
+) {
+    let unplaced_height = if view.unplaced.is_empty() {
+        0
+    } else {
+        view.unplaced.len().min(4) as u16 + 2
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(3),               // Header with instructions or search
+                Constraint::Length(unplaced_height), // Issues without a line
+                Constraint::Min(5),                  // File content
+            ]
+            .as_ref(),
+        )
+        .margin(1)
+        .split(area);
+
+    let header_line = if view.search_active || !view.search_query.is_empty() {
+        let cursor = if view.search_active { "█" } else { "" };
+        let matches = if view.search_matches.is_empty() {
+            if view.search_query.is_empty() {
+                String::new()
+            } else {
+                "   No matches".to_string()
+            }
+        } else {
+            format!(
+                "   Match {}/{}",
+                view.search_match_idx + 1,
+                view.search_matches.len()
+            )
+        };
+        Line::from(vec![
+            Span::styled("Search: ", Style::default().fg(theme().key)),
+            Span::raw(format!("{}{}", view.search_query, cursor)),
+            Span::styled(matches, Style::default().fg(theme().title)),
+        ])
+    } else {
+        Line::from(vec![
+            Span::styled("↑/↓", Style::default().fg(theme().key)),
+            Span::raw(": Scroll   "),
+            Span::styled("PgUp/PgDn", Style::default().fg(theme().key)),
+            Span::raw(": Page   "),
+            Span::styled("s", Style::default().fg(theme().key)),
+            Span::raw(": Search   "),
+            Span::styled("n/N", Style::default().fg(theme().key)),
+            Span::raw(": Next/previous match   "),
+            Span::styled("Esc", Style::default().fg(theme().key)),
+            Span::raw(": Close"),
+        ])
+    };
+    let header = Paragraph::new(header_line)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded),
+        )
+        .alignment(Alignment::Center);
+    f.render_widget(header, chunks[0]);
+
+    if !view.unplaced.is_empty() {
+        let issues: Vec<Line> = view
+            .unplaced
+            .iter()
+            .map(|issue| {
+                Line::from(Span::styled(
+                    format!("⚠ {}", issue),
+                    Style::default().fg(theme().failure),
+                ))
+            })
+            .collect();
+        let paragraph = Paragraph::new(issues)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .title(Span::styled(
+                        " File issues ",
+                        Style::default().fg(theme().failure),
+                    )),
+            )
+            .wrap(Wrap { trim: false });
+        f.render_widget(paragraph, chunks[1]);
+    }
+
+    let issue_count = view.issue_count();
+    let title = format!(
+        " {} — {} ",
+        view.path.display(),
+        match issue_count {
+            0 => "no issues".to_string(),
+            1 => "1 issue".to_string(),
+            n => format!("{} issues", n),
+        }
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .title(Span::styled(
+            title,
+            Style::default().fg(if issue_count == 0 {
+                theme().success
+            } else {
+                theme().failure
+            }),
+        ));
+    let inner = block.inner(chunks[2]);
+    view.page = inner.height as usize;
+
+    let gutter = view.lines.len().max(1).to_string().len();
+    let current_match = view.current_match();
+    let mut rows = Vec::new();
+    for (idx, text) in view.lines.iter().enumerate().skip(view.scroll) {
+        if rows.len() >= view.page {
+            break;
+        }
+        let annotated = view.annotations.get(&idx);
+        let mut spans = vec![
+            Span::styled(
+                format!("{:>width$} ", idx + 1, width = gutter),
+                Style::default().fg(theme().muted),
+            ),
+            if annotated.is_some() {
+                Span::styled("● ", Style::default().fg(theme().failure))
+            } else {
+                Span::raw("  ")
+            },
+        ];
+        let mut code = highlight(text);
+        if view.search_matches.binary_search(&idx).is_ok() {
+            let background = if current_match == Some(idx) {
+                theme().title
+            } else {
+                theme().highlight
+            };
+            for span in &mut code {
+                span.style = span.style.bg(background);
+                if current_match == Some(idx) {
+                    span.style = span.style.fg(theme().background);
+                }
+            }
+        }
+        spans.extend(code);
+        rows.push(Line::from(spans));
+
+        for issue in annotated.into_iter().flatten() {
+            rows.push(Line::from(vec![
+                Span::raw(" ".repeat(gutter + 3)),
+                Span::styled(
+                    format!("⚠ {}", issue),
+                    Style::default()
+                        .fg(theme().failure)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ]));
+        }
+    }
+
+    f.render_widget(Paragraph::new(rows).block(block), chunks[2]);
+}
+
+// Split a YAML line into spans colored by what they are: comments, keys, list
+// markers, scalars and `${{ }}` expressions
+fn highlight(line: &str) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let trimmed = line.trim_start();
+    spans.push(Span::raw(line[..line.len() - trimmed.len()].to_string()));
+    if trimmed.starts_with('#') {
+        spans.push(Span::styled(
+            trimmed.to_string(),
+            Style::default().fg(theme().muted),
+        ));
+        return spans;
+    }
+
+    let mut rest = trimmed;
+    while let Some(item) = rest.strip_prefix("- ") {
+        spans.push(Span::styled("- ", Style::default().fg(theme().title)));
+        rest = item;
+    }
+    if rest == "-" {
+        spans.push(Span::styled("-", Style::default().fg(theme().title)));
+        return spans;
+    }
+    if let Some(captures) = KEY.captures(rest) {
+        let key = captures.get(1).unwrap();
+        spans.push(Span::styled(
+            key.as_str().to_string(),
+            Style::default().fg(theme().key),
+        ));
+        spans.push(Span::raw(":"));
+        rest = &rest[key.end() + 1..];
+    }
+
+    // A comment starts at a `#` after whitespace
+    let (value, comment) = match rest.find(" #") {
+        Some(pos) => rest.split_at(pos),
+        None => (rest, ""),
+    };
+    let scalar = value.trim();
+    let style = if scalar.starts_with('"') || scalar.starts_with('\'') {
+        Style::default().fg(theme().success)
+    } else if SCALAR.is_match(scalar) {
+        Style::default().fg(theme().accent)
+    } else {
+        Style::default()
+    };
+    let mut last = 0;
+    for expression in EXPRESSION.find_iter(value) {
+        spans.push(Span::styled(
+            value[last..expression.start()].to_string(),
+            style,
+        ));
+        spans.push(Span::styled(
+            expression.as_str().to_string(),
+            Style::default().fg(theme().special),
+        ));
+        last = expression.end();
+    }
+    spans.push(Span::styled(value[last..].to_string(), style));
+    if !comment.is_empty() {
+        spans.push(Span::styled(
+            comment.to_string(),
+            Style::default().fg(theme().muted),
+        ));
+    }
+    spans
+}
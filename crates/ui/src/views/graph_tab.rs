@@ -0,0 +1,254 @@
+// Graph tab rendering
+use crate::app::App;
+use crate::models::{GraphView, MouseTargets, Split, SplitTarget};
+use crate::theme::theme;
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Paragraph, Wrap},
+    Frame,
+};
+use std::io;
+use wrkflw_executor::WorkflowGraph;
+
+// Render the job dependency graph of the selected workflow
+pub fn render_graph_tab(
+    f: &mut Frame<CrosstermBackend<io::Stdout>>,
+    app: &App,
+    area: Rect,
+    targets: &mut MouseTargets,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(3), // Header with instructions
+                Constraint::Min(5),    // Graph
+            ]
+            .as_ref(),
+        )
+        .margin(1)
+        .split(area);
+
+    let header_text = vec![
+        Line::from(vec![Span::styled(
+            "Job Dependency Graph",
+            Style::default()
+                .fg(theme().title)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(vec![
+            Span::styled("←/→", Style::default().fg(theme().key)),
+            Span::raw(": Stages   "),
+            Span::styled("↑/↓", Style::default().fg(theme().key)),
+            Span::raw(": Jobs   "),
+            Span::styled("Enter", Style::default().fg(theme().key)),
+            Span::raw(": Open called workflow   "),
+            Span::styled("Esc", Style::default().fg(theme().key)),
+            Span::raw(": Back"),
+        ]),
+    ];
+    let header = Paragraph::new(header_text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded),
+        )
+        .alignment(Alignment::Center);
+    f.render_widget(header, chunks[0]);
+
+    let view = match &app.graph_view {
+        Some(view) if view.graph.is_ok() => view,
+        other => {
+            let message = match other {
+                Some(GraphView { graph: Err(e), .. }) => format!("Can't build the graph: {}", e),
+                _ => "No workflow selected".to_string(),
+            };
+            let paragraph = Paragraph::new(message)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded),
+                )
+                .wrap(Wrap { trim: false });
+            f.render_widget(paragraph, chunks[1]);
+            return;
+        }
+    };
+    let Some(graph) = view.current() else {
+        return;
+    };
+
+    let percent = app.pane_sizes.get(Split::Graph);
+    let body = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage(percent),
+                Constraint::Percentage(100 - percent),
+            ]
+            .as_ref(),
+        )
+        .split(chunks[1]);
+    targets.splits.push(SplitTarget {
+        split: Split::Graph,
+        area: chunks[1],
+        direction: Direction::Horizontal,
+    });
+    render_stages(f, view, graph, body[0]);
+    render_job_details(f, view, graph, body[1]);
+}
+
+// One column per stage, with the selected job, what it needs and what needs it highlighted
+fn render_stages(
+    f: &mut Frame<CrosstermBackend<io::Stdout>>,
+    view: &GraphView,
+    graph: &WorkflowGraph,
+    area: Rect,
+) {
+    let mut title = format!(" {} ", graph.name);
+    if !view.trail.is_empty() {
+        title = format!(" {} (called, Esc to go back) ", graph.name);
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .title(Span::styled(title, Style::default().fg(theme().title)));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    if graph.stages.is_empty() {
+        return;
+    }
+
+    let selected = view.selected();
+    let dependents = selected
+        .map(|job| graph.dependents(&job.id))
+        .unwrap_or_default();
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            graph
+                .stages
+                .iter()
+                .map(|_| Constraint::Ratio(1, graph.stages.len() as u32))
+                .collect::<Vec<_>>(),
+        )
+        .split(inner);
+
+    for (stage_idx, stage) in graph.stages.iter().enumerate() {
+        let lines: Vec<Line> = stage
+            .iter()
+            .enumerate()
+            .map(|(job_idx, job)| {
+                let marker = if job.call.is_some() {
+                    "↳"
+                } else if !job.instances.is_empty() {
+                    "▣"
+                } else {
+                    "●"
+                };
+                let mut label = format!("{} {}", marker, job.id);
+                if !job.instances.is_empty() {
+                    label.push_str(&format!(" ×{}", job.instances.len()));
+                }
+                let style = if stage_idx == view.stage && job_idx == view.job {
+                    Style::default()
+                        .bg(theme().highlight)
+                        .fg(theme().title)
+                        .add_modifier(Modifier::BOLD)
+                } else if selected.is_some_and(|s| s.needs.contains(&job.id)) {
+                    Style::default().fg(theme().key)
+                } else if dependents.contains(&job.id.as_str()) {
+                    Style::default().fg(theme().special)
+                } else {
+                    Style::default().fg(theme().text)
+                };
+                Line::from(Span::styled(label, style))
+            })
+            .collect();
+        let column = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Plain)
+                .title(format!(" Stage {} ", stage_idx + 1)),
+        );
+        f.render_widget(column, columns[stage_idx]);
+    }
+}
+
+fn render_job_details(
+    f: &mut Frame<CrosstermBackend<io::Stdout>>,
+    view: &GraphView,
+    graph: &WorkflowGraph,
+    area: Rect,
+) {
+    let mut lines = Vec::new();
+    if let Some(job) = view.selected() {
+        let label = |text: &str| Span::styled(text.to_string(), Style::default().fg(theme().key));
+        lines.push(Line::from(Span::styled(
+            job.id.clone(),
+            Style::default()
+                .fg(theme().title)
+                .add_modifier(Modifier::BOLD),
+        )));
+        lines.push(Line::from(""));
+        let needs = if job.needs.is_empty() {
+            "-".to_string()
+        } else {
+            job.needs.join(", ")
+        };
+        lines.push(Line::from(vec![label("Needs: "), Span::raw(needs)]));
+        let dependents = graph.dependents(&job.id);
+        let dependents = if dependents.is_empty() {
+            "-".to_string()
+        } else {
+            dependents.join(", ")
+        };
+        lines.push(Line::from(vec![
+            Span::styled("Needed by: ", Style::default().fg(theme().special)),
+            Span::raw(dependents),
+        ]));
+        if let Some(note) = &job.note {
+            lines.push(Line::from(Span::styled(
+                format!("⚠️  {}", note),
+                Style::default().fg(theme().title),
+            )));
+        }
+        if !job.instances.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(label("Matrix:")));
+            for instance in &job.instances {
+                lines.push(Line::from(format!("  ◦ {}", instance)));
+            }
+        }
+        if let Some(call) = &job.call {
+            lines.push(Line::from(""));
+            lines.push(Line::from(vec![
+                label("Calls: "),
+                Span::raw(call.uses.clone()),
+            ]));
+            match &call.graph {
+                Ok(called) => lines.push(Line::from(Span::styled(
+                    format!("Enter to open '{}'", called.name),
+                    Style::default().fg(theme().muted),
+                ))),
+                Err(e) => lines.push(Line::from(Span::styled(
+                    e.clone(),
+                    Style::default().fg(theme().failure),
+                ))),
+            }
+        }
+    }
+
+    let details = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .title(Span::styled(" Job ", Style::default().fg(theme().title))),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(details, area);
+}
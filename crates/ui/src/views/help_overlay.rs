@@ -1,8 +1,9 @@
 // Help overlay rendering
+use crate::theme::theme;
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, Paragraph, Wrap},
     Frame,
@@ -26,7 +27,7 @@ pub fn render_help_content(
         Line::from(Span::styled(
             "🗂  NAVIGATION",
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme().key)
                 .add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
@@ -34,16 +35,16 @@ pub fn render_help_content(
             Span::styled(
                 "Tab / Shift+Tab",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Switch between tabs"),
         ]),
         Line::from(vec![
             Span::styled(
-                "1-4 / w,x,l,h",
+                "1-7 / w,x,l,g,i,V,h",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Jump to specific tab"),
@@ -52,7 +53,7 @@ pub fn render_help_content(
             Span::styled(
                 "↑/↓ or k/j",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Navigate lists"),
@@ -61,16 +62,34 @@ pub fn render_help_content(
             Span::styled(
                 "Enter",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Select/View details"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "Ctrl+P / p",
+                Style::default()
+                    .fg(theme().title)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" - Find a workflow, job or run"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "Mouse",
+                Style::default()
+                    .fg(theme().title)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" - Click tabs/rows, wheel to scroll, drag pane borders"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "Esc",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Back/Exit help"),
@@ -79,7 +98,7 @@ pub fn render_help_content(
         Line::from(Span::styled(
             "🚀 WORKFLOW MANAGEMENT",
             Style::default()
-                .fg(Color::Green)
+                .fg(theme().success)
                 .add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
@@ -87,16 +106,25 @@ pub fn render_help_content(
             Span::styled(
                 "Space",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Toggle workflow selection"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "Enter",
+                Style::default()
+                    .fg(theme().title)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" - Run with chosen runtime, event & inputs"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "r",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Run selected workflows"),
@@ -105,7 +133,7 @@ pub fn render_help_content(
             Span::styled(
                 "a",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Select all workflows"),
@@ -114,7 +142,7 @@ pub fn render_help_content(
             Span::styled(
                 "n",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Deselect all workflows"),
@@ -123,7 +151,7 @@ pub fn render_help_content(
             Span::styled(
                 "Shift+R",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Reset workflow status"),
@@ -132,16 +160,25 @@ pub fn render_help_content(
             Span::styled(
                 "t",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
-            Span::raw(" - Trigger remote workflow"),
+            Span::raw(" - Trigger remotely (branch, inputs)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "o",
+                Style::default()
+                    .fg(theme().title)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" - View workflow file with its issues"),
         ]),
         Line::from(""),
         Line::from(Span::styled(
             "🔧 EXECUTION MODES",
             Style::default()
-                .fg(Color::Magenta)
+                .fg(theme().special)
                 .add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
@@ -149,7 +186,7 @@ pub fn render_help_content(
             Span::styled(
                 "e",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Toggle emulation mode"),
@@ -158,36 +195,45 @@ pub fn render_help_content(
             Span::styled(
                 "v",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Toggle validation mode"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "d",
+                Style::default()
+                    .fg(theme().title)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" - Toggle dry run (show the plan without running)"),
+        ]),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Runtime Modes:",
             Style::default()
-                .fg(Color::White)
+                .fg(theme().text)
                 .add_modifier(Modifier::BOLD),
         )]),
         Line::from(vec![
             Span::raw("  • "),
-            Span::styled("Docker", Style::default().fg(Color::Blue)),
+            Span::styled("Docker", Style::default().fg(theme().accent)),
             Span::raw(" - Container isolation (default)"),
         ]),
         Line::from(vec![
             Span::raw("  • "),
-            Span::styled("Podman", Style::default().fg(Color::Blue)),
+            Span::styled("Podman", Style::default().fg(theme().accent)),
             Span::raw(" - Rootless containers"),
         ]),
         Line::from(vec![
             Span::raw("  • "),
-            Span::styled("Emulation", Style::default().fg(Color::Red)),
+            Span::styled("Emulation", Style::default().fg(theme().failure)),
             Span::raw(" - Process mode (UNSAFE)"),
         ]),
         Line::from(vec![
             Span::raw("  • "),
-            Span::styled("Secure Emulation", Style::default().fg(Color::Yellow)),
+            Span::styled("Secure Emulation", Style::default().fg(theme().title)),
             Span::raw(" - Sandboxed processes"),
         ]),
     ];
@@ -197,7 +243,7 @@ pub fn render_help_content(
         Line::from(Span::styled(
             "📄 LOGS & SEARCH",
             Style::default()
-                .fg(Color::Blue)
+                .fg(theme().accent)
                 .add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
@@ -205,7 +251,7 @@ pub fn render_help_content(
             Span::styled(
                 "s",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Toggle log search"),
@@ -214,7 +260,7 @@ pub fn render_help_content(
             Span::styled(
                 "f",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Toggle log filter"),
@@ -223,16 +269,43 @@ pub fn render_help_content(
             Span::styled(
                 "c",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Clear search & filter"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "m",
+                Style::default()
+                    .fg(theme().title)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" - Mark lines to copy from here"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "y",
+                Style::default()
+                    .fg(theme().title)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" - Copy the line or marked lines"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "S",
+                Style::default()
+                    .fg(theme().title)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" - Save the shown logs to a file"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "n",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Next search match"),
@@ -241,7 +314,7 @@ pub fn render_help_content(
             Span::styled(
                 "↑/↓",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Scroll logs/Navigate"),
@@ -250,7 +323,7 @@ pub fn render_help_content(
         Line::from(Span::styled(
             "ℹ️  TAB OVERVIEW",
             Style::default()
-                .fg(Color::White)
+                .fg(theme().text)
                 .add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
@@ -258,12 +331,13 @@ pub fn render_help_content(
             Span::styled(
                 "1. Workflows",
                 Style::default()
-                    .fg(Color::Cyan)
+                    .fg(theme().key)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Browse & select workflows"),
         ]),
         Line::from(vec![Span::raw("   • View workflow files")]),
+        Line::from(vec![Span::raw("   • See the stages of GitLab pipelines")]),
         Line::from(vec![Span::raw("   • Select multiple for batch execution")]),
         Line::from(vec![Span::raw("   • Trigger remote workflows")]),
         Line::from(""),
@@ -271,7 +345,7 @@ pub fn render_help_content(
             Span::styled(
                 "2. Execution",
                 Style::default()
-                    .fg(Color::Green)
+                    .fg(theme().success)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Monitor job progress"),
@@ -279,12 +353,17 @@ pub fn render_help_content(
         Line::from(vec![Span::raw("   • View job status and details")]),
         Line::from(vec![Span::raw("   • Enter job details with Enter")]),
         Line::from(vec![Span::raw("   • Navigate step execution")]),
+        Line::from(vec![Span::raw(
+            "   • Scroll a step's output with PgUp/PgDn",
+        )]),
+        Line::from(vec![Span::raw("   • Cancel the selected job with c")]),
+        Line::from(vec![Span::raw("   • Cancel the whole run with Shift+C")]),
         Line::from(""),
         Line::from(vec![
             Span::styled(
                 "3. Logs",
                 Style::default()
-                    .fg(Color::Blue)
+                    .fg(theme().accent)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - View execution logs"),
@@ -295,9 +374,54 @@ pub fn render_help_content(
         Line::from(""),
         Line::from(vec![
             Span::styled(
-                "4. Help",
+                "4. Graph",
+                Style::default()
+                    .fg(theme().special)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" - Job dependency graph"),
+        ]),
+        Line::from(vec![Span::raw("   • Matrix jobs and called workflows")]),
+        Line::from(vec![Span::raw(
+            "   • ←/→ stages, Enter opens a called workflow",
+        )]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                "5. History",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().key)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" - Past runs of this project"),
+        ]),
+        Line::from(vec![Span::raw("   • Job and step results of each run")]),
+        Line::from(vec![Span::raw(
+            "   • Changes since the previous run, or a run marked with Enter",
+        )]),
+        Line::from(vec![Span::raw(
+            "   • r reruns a failed run, skipping what succeeded in it",
+        )]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                "6. Validation",
+                Style::default()
+                    .fg(theme().failure)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" - Issues of the loaded workflows"),
+        ]),
+        Line::from(vec![Span::raw("   • Errors and warnings grouped by file")]),
+        Line::from(vec![Span::raw(
+            "   • Enter opens the file at the issue, r re-validates",
+        )]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                "7. Help",
+                Style::default()
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - This comprehensive guide"),
@@ -305,14 +429,16 @@ pub fn render_help_content(
         Line::from(""),
         Line::from(Span::styled(
             "🎯 QUICK ACTIONS",
-            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            Style::default()
+                .fg(theme().failure)
+                .add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
         Line::from(vec![
             Span::styled(
                 "?",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Toggle help overlay"),
@@ -321,7 +447,7 @@ pub fn render_help_content(
             Span::styled(
                 "q",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme().title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" - Quit application"),
@@ -330,34 +456,34 @@ pub fn render_help_content(
         Line::from(Span::styled(
             "💡 TIPS",
             Style::default()
-                .fg(Color::Yellow)
+                .fg(theme().title)
                 .add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
         Line::from(vec![
             Span::raw("• Use "),
-            Span::styled("emulation mode", Style::default().fg(Color::Red)),
+            Span::styled("emulation mode", Style::default().fg(theme().failure)),
             Span::raw(" when containers"),
         ]),
         Line::from(vec![Span::raw("  are unavailable or for quick testing")]),
         Line::from(""),
         Line::from(vec![
             Span::raw("• "),
-            Span::styled("Secure emulation", Style::default().fg(Color::Yellow)),
+            Span::styled("Secure emulation", Style::default().fg(theme().title)),
             Span::raw(" provides sandboxing"),
         ]),
         Line::from(vec![Span::raw("  for untrusted workflows")]),
         Line::from(""),
         Line::from(vec![
             Span::raw("• Use "),
-            Span::styled("validation mode", Style::default().fg(Color::Green)),
+            Span::styled("validation mode", Style::default().fg(theme().success)),
             Span::raw(" to check"),
         ]),
         Line::from(vec![Span::raw("  workflows without execution")]),
         Line::from(""),
         Line::from(vec![
             Span::raw("• "),
-            Span::styled("Preserve containers", Style::default().fg(Color::Blue)),
+            Span::styled("Preserve containers", Style::default().fg(theme().accent)),
             Span::raw(" on failure"),
         ]),
         Line::from(vec![Span::raw("  for debugging (Docker/Podman only)")]),
@@ -385,7 +511,7 @@ pub fn render_help_content(
                 .title(Span::styled(
                     " WRKFLW Help - Controls & Features ",
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(theme().title)
                         .add_modifier(Modifier::BOLD),
                 )),
         )
@@ -400,7 +526,7 @@ pub fn render_help_content(
                 .title(Span::styled(
                     " Interface Guide & Tips ",
                     Style::default()
-                        .fg(Color::Cyan)
+                        .fg(theme().key)
                         .add_modifier(Modifier::BOLD),
                 )),
         )
@@ -428,18 +554,18 @@ pub fn render_help_overlay(f: &mut Frame<CrosstermBackend<io::Stdout>>, scroll_o
     };
 
     // Create a semi-transparent dark background for better visibility
-    let clear = Block::default().style(Style::default().bg(Color::Black));
+    let clear = Block::default().style(Style::default().bg(theme().background));
     f.render_widget(clear, size);
 
     // Add a border around the entire overlay for better visual separation
     let overlay_block = Block::default()
         .borders(Borders::ALL)
         .border_type(BorderType::Double)
-        .style(Style::default().bg(Color::Black).fg(Color::White))
+        .style(Style::default().bg(theme().background).fg(theme().text))
         .title(Span::styled(
             " Press ? or Esc to close help ",
             Style::default()
-                .fg(Color::Gray)
+                .fg(theme().dim)
                 .add_modifier(Modifier::ITALIC),
         ));
 
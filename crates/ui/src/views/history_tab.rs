@@ -0,0 +1,268 @@
+// History tab rendering
+use crate::app::App;
+use crate::models::{HistoryView, ListKind, ListTarget, MouseTargets, Split, SplitTarget};
+use crate::theme::theme;
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+use std::io;
+use wrkflw_executor::runs::{diff_runs, RunChange, RunRecord};
+
+fn status_style(status: &str) -> (&'static str, Style) {
+    match status {
+        "success" => ("✅", Style::default().fg(theme().success)),
+        "failure" => ("❌", Style::default().fg(theme().failure)),
+        _ => ("⏭", Style::default().fg(theme().dim)),
+    }
+}
+
+fn short_commit(run: &RunRecord) -> &str {
+    run.commit
+        .as_deref()
+        .map(|sha| &sha[..sha.len().min(8)])
+        .unwrap_or("-")
+}
+
+// Render the recorded runs with the results of the selected one and what changed
+pub fn render_history_tab(
+    f: &mut Frame<CrosstermBackend<io::Stdout>>,
+    app: &App,
+    area: Rect,
+    targets: &mut MouseTargets,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(3), // Header with instructions
+                Constraint::Min(5),    // Runs and details
+            ]
+            .as_ref(),
+        )
+        .margin(1)
+        .split(area);
+
+    let header_text = vec![
+        Line::from(vec![Span::styled(
+            "Run History",
+            Style::default()
+                .fg(theme().title)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(vec![
+            Span::styled("↑/↓", Style::default().fg(theme().key)),
+            Span::raw(": Runs   "),
+            Span::styled("Enter", Style::default().fg(theme().key)),
+            Span::raw(": Mark run to compare with   "),
+            Span::styled("r", Style::default().fg(theme().key)),
+            Span::raw(": Rerun from failures"),
+        ]),
+    ];
+    let header = Paragraph::new(header_text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded),
+        )
+        .alignment(Alignment::Center);
+    f.render_widget(header, chunks[0]);
+
+    let view = match &app.history_view {
+        Some(view) if !view.runs.is_empty() => view,
+        _ => {
+            let paragraph = Paragraph::new(
+                "No runs recorded yet; runs started here or with `wrkflw run` show up here",
+            )
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            )
+            .wrap(Wrap { trim: false });
+            f.render_widget(paragraph, chunks[1]);
+            return;
+        }
+    };
+
+    let percent = app.pane_sizes.get(Split::History);
+    let body = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage(percent),
+                Constraint::Percentage(100 - percent),
+            ]
+            .as_ref(),
+        )
+        .split(chunks[1]);
+    targets.splits.push(SplitTarget {
+        split: Split::History,
+        area: chunks[1],
+        direction: Direction::Horizontal,
+    });
+    let offset = render_runs(f, view, body[0]);
+    targets.list = Some(ListTarget::new(ListKind::Runs, body[0], 0, offset));
+
+    let details = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+        .split(body[1]);
+    render_run_details(f, view, details[0]);
+    render_changes(f, view, details[1]);
+}
+
+// Returns the index of the first run shown
+fn render_runs(
+    f: &mut Frame<CrosstermBackend<io::Stdout>>,
+    view: &HistoryView,
+    area: Rect,
+) -> usize {
+    let items: Vec<ListItem> = view
+        .runs
+        .iter()
+        .map(|run| {
+            let (icon, style) = status_style(if run.success { "success" } else { "failure" });
+            let mut spans = vec![
+                Span::styled(format!("{} ", icon), style),
+                Span::raw(format!(
+                    "{}  {:<20} {:>7.1}s  {}",
+                    run.started.format("%m-%d %H:%M:%S"),
+                    run.workflow,
+                    run.duration_secs,
+                    short_commit(run)
+                )),
+            ];
+            if view.marked.as_ref() == Some(&run.id) {
+                spans.push(Span::styled(" ◆", Style::default().fg(theme().special)));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .title(Span::styled(" Runs ", Style::default().fg(theme().title))),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(theme().highlight)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("» ");
+    let mut state = ListState::default();
+    state.select(Some(view.selected));
+    f.render_stateful_widget(list, area, &mut state);
+    state.offset()
+}
+
+fn render_run_details(f: &mut Frame<CrosstermBackend<io::Stdout>>, view: &HistoryView, area: Rect) {
+    let mut lines = Vec::new();
+    if let Some(run) = view.selected_run() {
+        let label = |text: &str| Span::styled(text.to_string(), Style::default().fg(theme().key));
+        lines.push(Line::from(vec![label("Run: "), Span::raw(run.id.clone())]));
+        lines.push(Line::from(vec![
+            label("Workflow: "),
+            Span::raw(run.path.display().to_string()),
+        ]));
+        lines.push(Line::from(vec![
+            label("Commit: "),
+            Span::raw(short_commit(run).to_string()),
+            label("   Runtime: "),
+            Span::raw(if run.runtime.is_empty() {
+                "-".to_string()
+            } else {
+                run.runtime.clone()
+            }),
+        ]));
+        lines.push(Line::from(""));
+        for job in &run.jobs {
+            let (icon, style) = status_style(&job.status);
+            lines.push(Line::from(vec![
+                Span::styled(format!("{} {}", icon, job.name), style),
+                Span::styled(
+                    format!(" ({:.1}s)", job.duration_secs),
+                    Style::default().fg(theme().muted),
+                ),
+            ]));
+            for step in &job.steps {
+                let (icon, style) = status_style(&step.status);
+                lines.push(Line::from(vec![
+                    Span::styled(format!("   {} {}", icon, step.name), style),
+                    Span::styled(
+                        format!(" ({:.1}s)", step.duration_secs),
+                        Style::default().fg(theme().muted),
+                    ),
+                ]));
+            }
+        }
+    }
+
+    let details = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .title(Span::styled(
+                    " Results ",
+                    Style::default().fg(theme().title),
+                )),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(details, area);
+}
+
+fn render_changes(f: &mut Frame<CrosstermBackend<io::Stdout>>, view: &HistoryView, area: Rect) {
+    let (title, lines) = match (view.selected_run(), view.baseline()) {
+        (Some(run), Some(baseline)) => {
+            let mut lines: Vec<Line> = diff_runs(baseline, run)
+                .into_iter()
+                .map(|change| {
+                    let color = match &change {
+                        RunChange::JobAdded(_) | RunChange::StepAdded { .. } => theme().success,
+                        RunChange::JobRemoved(_) | RunChange::StepRemoved { .. } => theme().failure,
+                        RunChange::Status { to, .. } if to == "failure" => theme().failure,
+                        RunChange::Status { .. } => theme().success,
+                        RunChange::Duration { .. } => theme().warning,
+                    };
+                    Line::from(Span::styled(change.to_string(), Style::default().fg(color)))
+                })
+                .collect();
+            if lines.is_empty() {
+                lines.push(Line::from("No changes in job or step results"));
+            }
+            lines.push(Line::from(Span::styled(
+                format!(
+                    "Total: {:.1}s → {:.1}s",
+                    baseline.duration_secs, run.duration_secs
+                ),
+                Style::default().fg(theme().muted),
+            )));
+            (format!(" Changes since {} ", baseline.id), lines)
+        }
+        _ => (
+            " Changes ".to_string(),
+            vec![Line::from(Span::styled(
+                "No earlier run of this workflow",
+                Style::default().fg(theme().muted),
+            ))],
+        ),
+    };
+
+    let changes = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .title(Span::styled(title, Style::default().fg(theme().title))),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(changes, area);
+}
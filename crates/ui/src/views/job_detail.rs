@@ -1,20 +1,24 @@
 // Job detail view rendering
 use crate::app::App;
+use crate::models::{ListKind, ListTarget, MouseTargets, Split, SplitTarget, StepExecution};
+use crate::theme::theme;
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, Paragraph, Row, Table},
     Frame,
 };
 use std::io;
+use std::time::Duration;
 
 // Render the job detail view
 pub fn render_job_detail_view(
     f: &mut Frame<CrosstermBackend<io::Stdout>>,
     app: &mut App,
     area: Rect,
+    targets: &mut MouseTargets,
 ) {
     // Get the workflow index either from current_execution or selected workflow
     let current_workflow_idx = app
@@ -31,68 +35,94 @@ pub fn render_job_detail_view(
                     let job = &execution.jobs[job_idx];
 
                     // Split the area into sections
-                    let chunks = Layout::default()
+                    let outer = Layout::default()
                         .direction(Direction::Vertical)
                         .constraints(
                             [
                                 Constraint::Length(3), // Job title
-                                Constraint::Min(5),    // Steps table
-                                Constraint::Length(8), // Step details
+                                Constraint::Min(0),    // Steps table and step output
                             ]
                             .as_ref(),
                         )
                         .margin(1)
                         .split(area);
+                    let percent = app.pane_sizes.get(Split::JobDetail);
+                    let body = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints(
+                            [
+                                Constraint::Percentage(percent),       // Steps table
+                                Constraint::Percentage(100 - percent), // Step output
+                            ]
+                            .as_ref(),
+                        )
+                        .split(outer[1]);
+                    targets.splits.push(SplitTarget {
+                        split: Split::JobDetail,
+                        area: outer[1],
+                        direction: Direction::Vertical,
+                    });
+                    let chunks = [outer[0], body[0], body[1]];
 
                     // Job title section
-                    let status_text = match job.status {
-                        wrkflw_executor::JobStatus::Success => "Success",
-                        wrkflw_executor::JobStatus::Failure => "Failed",
-                        wrkflw_executor::JobStatus::Skipped => "Skipped",
-                    };
-
-                    let status_style = match job.status {
-                        wrkflw_executor::JobStatus::Success => Style::default().fg(Color::Green),
-                        wrkflw_executor::JobStatus::Failure => Style::default().fg(Color::Red),
-                        wrkflw_executor::JobStatus::Skipped => Style::default().fg(Color::Yellow),
+                    let (status_text, status_style) = if job.running {
+                        ("Running", Style::default().fg(theme().running))
+                    } else {
+                        match job.status {
+                            wrkflw_executor::JobStatus::Success => {
+                                ("Success", Style::default().fg(theme().success))
+                            }
+                            wrkflw_executor::JobStatus::Failure => {
+                                ("Failed", Style::default().fg(theme().failure))
+                            }
+                            wrkflw_executor::JobStatus::Skipped => {
+                                ("Skipped", Style::default().fg(theme().dim))
+                            }
+                            wrkflw_executor::JobStatus::Cancelled => {
+                                ("Cancelled", Style::default().fg(theme().cancelled))
+                            }
+                        }
                     };
+                    let total: Duration = job.steps.iter().map(step_duration).sum();
 
-                    let job_title = Paragraph::new(vec![
-                        Line::from(vec![
-                            Span::styled("Job: ", Style::default().fg(Color::Blue)),
-                            Span::styled(
-                                job.name.clone(),
-                                Style::default()
-                                    .fg(Color::White)
-                                    .add_modifier(Modifier::BOLD),
-                            ),
-                            Span::raw(" ("),
-                            Span::styled(status_text, status_style),
-                            Span::raw(")"),
-                        ]),
-                        Line::from(vec![
-                            Span::styled("Steps: ", Style::default().fg(Color::Blue)),
-                            Span::styled(
-                                format!("{}", job.steps.len()),
-                                Style::default().fg(Color::White),
-                            ),
-                        ]),
-                    ])
+                    let job_title = Paragraph::new(vec![Line::from(vec![
+                        Span::styled("Job: ", Style::default().fg(theme().accent)),
+                        Span::styled(
+                            job.name.clone(),
+                            Style::default()
+                                .fg(theme().text)
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                        Span::raw(" ("),
+                        Span::styled(status_text, status_style),
+                        Span::raw(")   "),
+                        Span::styled("Steps: ", Style::default().fg(theme().accent)),
+                        Span::styled(
+                            format!("{}", job.steps.len()),
+                            Style::default().fg(theme().text),
+                        ),
+                        Span::raw("   "),
+                        Span::styled("Duration: ", Style::default().fg(theme().accent)),
+                        Span::styled(
+                            format!("{:.1}s", total.as_secs_f64()),
+                            Style::default().fg(theme().text),
+                        ),
+                    ])])
                     .block(
                         Block::default()
                             .borders(Borders::ALL)
                             .border_type(BorderType::Rounded)
                             .title(Span::styled(
                                 " Job Details ",
-                                Style::default().fg(Color::Yellow),
+                                Style::default().fg(theme().title),
                             )),
                     );
 
                     f.render_widget(job_title, chunks[0]);
 
                     // Steps section
-                    let header_cells = ["Status", "Step Name"].iter().map(|h| {
-                        ratatui::widgets::Cell::from(*h).style(Style::default().fg(Color::Yellow))
+                    let header_cells = ["Status", "Step Name", "Duration"].iter().map(|h| {
+                        ratatui::widgets::Cell::from(*h).style(Style::default().fg(theme().title))
                     });
 
                     let header = Row::new(header_cells)
@@ -100,25 +130,15 @@ pub fn render_job_detail_view(
                         .height(1);
 
                     let rows = job.steps.iter().map(|step| {
-                        let status_symbol = match step.status {
-                            wrkflw_executor::StepStatus::Success => "✅",
-                            wrkflw_executor::StepStatus::Failure => "❌",
-                            wrkflw_executor::StepStatus::Skipped => "⏭",
-                        };
-
-                        let status_style = match step.status {
-                            wrkflw_executor::StepStatus::Success => {
-                                Style::default().fg(Color::Green)
-                            }
-                            wrkflw_executor::StepStatus::Failure => Style::default().fg(Color::Red),
-                            wrkflw_executor::StepStatus::Skipped => {
-                                Style::default().fg(Color::Gray)
-                            }
-                        };
-
+                        let (status_symbol, status_style) = step_status(step);
                         Row::new(vec![
                             ratatui::widgets::Cell::from(status_symbol).style(status_style),
                             ratatui::widgets::Cell::from(step.name.clone()),
+                            ratatui::widgets::Cell::from(format!(
+                                "{:.1}s",
+                                step_duration(step).as_secs_f64()
+                            ))
+                            .style(Style::default().fg(theme().muted)),
                         ])
                     });
 
@@ -128,84 +148,117 @@ pub fn render_job_detail_view(
                             Block::default()
                                 .borders(Borders::ALL)
                                 .border_type(BorderType::Rounded)
-                                .title(Span::styled(" Steps ", Style::default().fg(Color::Yellow))),
+                                .title(Span::styled(" Steps ", Style::default().fg(theme().title))),
                         )
                         .highlight_style(
                             Style::default()
-                                .bg(Color::DarkGray)
+                                .bg(theme().highlight)
                                 .add_modifier(Modifier::BOLD),
                         )
                         .highlight_symbol("» ")
                         .widths(&[
                             Constraint::Length(8),      // Status icon column
-                            Constraint::Percentage(92), // Name column
+                            Constraint::Percentage(75), // Name column
+                            Constraint::Length(10),     // Duration column
                         ]);
 
                     // We need to use the table state from the app
                     f.render_stateful_widget(steps_table, chunks[1], &mut app.step_table_state);
+                    targets.list = Some(ListTarget::new(
+                        ListKind::Steps,
+                        chunks[1],
+                        1,
+                        app.step_table_state.offset(),
+                    ));
 
-                    // Step detail section
-                    if let Some(step_idx) = app.step_table_state.selected() {
-                        if step_idx < job.steps.len() {
-                            let step = &job.steps[step_idx];
-
-                            // Show step output with proper styling
-                            let status_text = match step.status {
-                                wrkflw_executor::StepStatus::Success => "Success",
-                                wrkflw_executor::StepStatus::Failure => "Failed",
-                                wrkflw_executor::StepStatus::Skipped => "Skipped",
-                            };
-
-                            let status_style = match step.status {
-                                wrkflw_executor::StepStatus::Success => {
-                                    Style::default().fg(Color::Green)
-                                }
-                                wrkflw_executor::StepStatus::Failure => {
-                                    Style::default().fg(Color::Red)
-                                }
-                                wrkflw_executor::StepStatus::Skipped => {
-                                    Style::default().fg(Color::Yellow)
-                                }
-                            };
-
-                            let mut output_text = step.output.clone();
-                            // Truncate if too long
-                            if output_text.len() > 1000 {
-                                output_text = format!("{}... [truncated]", &output_text[..1000]);
-                            }
+                    // Step output section
+                    if let Some(step) = app
+                        .step_table_state
+                        .selected()
+                        .and_then(|step_idx| job.steps.get(step_idx))
+                    {
+                        let (status_symbol, status_style) = step_status(step);
+                        let mut lines = vec![
+                            Line::from(vec![
+                                Span::styled("Step: ", Style::default().fg(theme().accent)),
+                                Span::styled(
+                                    step.name.clone(),
+                                    Style::default()
+                                        .fg(theme().text)
+                                        .add_modifier(Modifier::BOLD),
+                                ),
+                                Span::raw(" "),
+                                Span::styled(status_symbol, status_style),
+                            ]),
+                            Line::from(""),
+                        ];
+                        if let Some(started) = step.running_since {
+                            lines.push(Line::from(Span::styled(
+                                format!(
+                                    "Running for {}s, the output shows when the step finishes...",
+                                    started.elapsed().as_secs()
+                                ),
+                                Style::default().fg(theme().title),
+                            )));
+                        } else if step.output.is_empty() {
+                            lines.push(Line::from(Span::styled(
+                                "No output",
+                                Style::default().fg(theme().muted),
+                            )));
+                        } else {
+                            lines.extend(
+                                step.output.lines().map(|line| Line::from(line.to_string())),
+                            );
+                        }
 
-                            let step_detail = Paragraph::new(vec![
-                                Line::from(vec![
-                                    Span::styled("Step: ", Style::default().fg(Color::Blue)),
-                                    Span::styled(
-                                        step.name.clone(),
-                                        Style::default()
-                                            .fg(Color::White)
-                                            .add_modifier(Modifier::BOLD),
-                                    ),
-                                    Span::raw(" ("),
-                                    Span::styled(status_text, status_style),
-                                    Span::raw(")"),
-                                ]),
-                                Line::from(""),
-                                Line::from(output_text),
-                            ])
+                        // Keep the last lines in view when scrolled past the end
+                        let visible = chunks[2].height.saturating_sub(2) as usize;
+                        app.step_output_scroll = app
+                            .step_output_scroll
+                            .min(lines.len().saturating_sub(visible));
+                        let title = if lines.len() > visible {
+                            format!(
+                                " Step Output [{}-{}/{}] ",
+                                app.step_output_scroll + 1,
+                                (app.step_output_scroll + visible).min(lines.len()),
+                                lines.len()
+                            )
+                        } else {
+                            " Step Output ".to_string()
+                        };
+
+                        let step_detail = Paragraph::new(lines)
                             .block(
                                 Block::default()
                                     .borders(Borders::ALL)
                                     .border_type(BorderType::Rounded)
-                                    .title(Span::styled(
-                                        " Step Output ",
-                                        Style::default().fg(Color::Yellow),
-                                    )),
+                                    .title(Span::styled(title, Style::default().fg(theme().title))),
                             )
-                            .wrap(ratatui::widgets::Wrap { trim: false });
+                            .scroll((app.step_output_scroll as u16, 0));
 
-                            f.render_widget(step_detail, chunks[2]);
-                        }
+                        f.render_widget(step_detail, chunks[2]);
                     }
                 }
             }
         }
     }
 }
+
+// Status symbol and style of a step, which may still be running
+fn step_status(step: &StepExecution) -> (&'static str, Style) {
+    if step.running_since.is_some() {
+        return ("⏳", Style::default().fg(theme().running));
+    }
+    match step.status {
+        wrkflw_executor::StepStatus::Success => ("✅", Style::default().fg(theme().success)),
+        wrkflw_executor::StepStatus::Failure => ("❌", Style::default().fg(theme().failure)),
+        wrkflw_executor::StepStatus::Skipped => ("⏭", Style::default().fg(theme().dim)),
+    }
+}
+
+// How long a step took, or has been running
+fn step_duration(step: &StepExecution) -> Duration {
+    step.running_since
+        .map(|started| started.elapsed())
+        .unwrap_or(step.duration)
+}
@@ -1,9 +1,11 @@
 // Logs tab rendering
 use crate::app::App;
+use crate::models::{ListKind, ListTarget, MouseTargets};
+use crate::theme::theme;
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, Cell, Paragraph, Row, Table, TableState},
     Frame,
@@ -11,7 +13,12 @@ use ratatui::{
 use std::io;
 
 // Render the logs tab
-pub fn render_logs_tab(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App, area: Rect) {
+pub fn render_logs_tab(
+    f: &mut Frame<CrosstermBackend<io::Stdout>>,
+    app: &App,
+    area: Rect,
+    targets: &mut MouseTargets,
+) {
     // Split the area into header, search bar (optionally shown), and log content
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -44,30 +51,34 @@ pub fn render_logs_tab(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App, a
         Line::from(vec![Span::styled(
             "Execution and System Logs",
             Style::default()
-                .fg(Color::Yellow)
+                .fg(theme().title)
                 .add_modifier(Modifier::BOLD),
         )]),
         Line::from(vec![
-            Span::styled("↑/↓", Style::default().fg(Color::Cyan)),
+            Span::styled("↑/↓", Style::default().fg(theme().key)),
             Span::raw(" or "),
-            Span::styled("j/k", Style::default().fg(Color::Cyan)),
+            Span::styled("j/k", Style::default().fg(theme().key)),
             Span::raw(": Navigate logs/matches   "),
-            Span::styled("s", Style::default().fg(Color::Cyan)),
+            Span::styled("s", Style::default().fg(theme().key)),
             Span::raw(": Search   "),
-            Span::styled("f", Style::default().fg(Color::Cyan)),
+            Span::styled("f", Style::default().fg(theme().key)),
             Span::raw(": Filter   "),
-            Span::styled("Tab", Style::default().fg(Color::Cyan)),
-            Span::raw(": Switch tabs"),
+            Span::styled("m", Style::default().fg(theme().key)),
+            Span::raw(": Mark   "),
+            Span::styled("y", Style::default().fg(theme().key)),
+            Span::raw(": Copy   "),
+            Span::styled("S", Style::default().fg(theme().key)),
+            Span::raw(": Save"),
         ]),
     ];
 
     if show_search_bar {
         header_text.push(Line::from(vec![
-            Span::styled("Enter", Style::default().fg(Color::Cyan)),
+            Span::styled("Enter", Style::default().fg(theme().key)),
             Span::raw(": Apply search   "),
-            Span::styled("Esc", Style::default().fg(Color::Cyan)),
+            Span::styled("Esc", Style::default().fg(theme().key)),
             Span::raw(": Clear search   "),
-            Span::styled("c", Style::default().fg(Color::Cyan)),
+            Span::styled("c", Style::default().fg(theme().key)),
             Span::raw(": Clear all filters"),
         ]));
     }
@@ -113,16 +124,16 @@ pub fn render_logs_tab(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App, a
             Span::styled(
                 filter_text,
                 Style::default().fg(match &app.log_filter_level {
-                    Some(crate::models::LogFilterLevel::Error) => Color::Red,
-                    Some(crate::models::LogFilterLevel::Warning) => Color::Yellow,
-                    Some(crate::models::LogFilterLevel::Info) => Color::Cyan,
-                    Some(crate::models::LogFilterLevel::Success) => Color::Green,
-                    Some(crate::models::LogFilterLevel::Trigger) => Color::Magenta,
-                    Some(crate::models::LogFilterLevel::All) | None => Color::Gray,
+                    Some(crate::models::LogFilterLevel::Error) => theme().failure,
+                    Some(crate::models::LogFilterLevel::Warning) => theme().warning,
+                    Some(crate::models::LogFilterLevel::Info) => theme().running,
+                    Some(crate::models::LogFilterLevel::Success) => theme().success,
+                    Some(crate::models::LogFilterLevel::Trigger) => theme().special,
+                    Some(crate::models::LogFilterLevel::All) | None => theme().dim,
                 }),
             ),
             Span::raw("   "),
-            Span::styled(match_info, Style::default().fg(Color::Magenta)),
+            Span::styled(match_info, Style::default().fg(theme().special)),
         ]);
 
         let search_block = Paragraph::new(search_info)
@@ -132,7 +143,7 @@ pub fn render_logs_tab(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App, a
                     .border_type(BorderType::Rounded)
                     .title(Span::styled(
                         " Search & Filter ",
-                        Style::default().fg(Color::Yellow),
+                        Style::default().fg(theme().title),
                     )),
             )
             .alignment(Alignment::Left);
@@ -146,16 +157,25 @@ pub fn render_logs_tab(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App, a
     // Create a table for logs for better organization
     let header_cells = ["Time", "Type", "Message"]
         .iter()
-        .map(|h| Cell::from(*h).style(Style::default().fg(Color::Yellow)));
+        .map(|h| Cell::from(*h).style(Style::default().fg(theme().title)));
 
     let header = Row::new(header_cells)
         .style(Style::default().add_modifier(Modifier::BOLD))
         .height(1);
 
     // Convert processed logs to table rows - this is now very fast since logs are pre-processed
+    let marked = app.log_mark.and(app.selected_log_range());
     let rows = filtered_logs
         .iter()
-        .map(|processed_log| processed_log.to_row());
+        .enumerate()
+        .map(|(idx, processed_log)| {
+            let row = processed_log.to_row();
+            if marked.as_ref().is_some_and(|range| range.contains(&idx)) {
+                row.style(Style::default().bg(theme().mark))
+            } else {
+                row
+            }
+        });
 
     let content_idx = if show_search_bar { 2 } else { 1 };
 
@@ -175,10 +195,10 @@ pub fn render_logs_tab(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App, a
                         },
                         filtered_logs.len()
                     ),
-                    Style::default().fg(Color::Yellow),
+                    Style::default().fg(theme().title),
                 )),
         )
-        .highlight_style(Style::default().bg(Color::DarkGray))
+        .highlight_style(Style::default().bg(theme().highlight))
         .widths(&[
             Constraint::Length(10),     // Timestamp column
             Constraint::Length(7),      // Log type column
@@ -206,4 +226,10 @@ pub fn render_logs_tab(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App, a
     }
 
     f.render_stateful_widget(log_table, chunks[content_idx], &mut log_table_state);
+    targets.list = Some(ListTarget::new(
+        ListKind::Logs,
+        chunks[content_idx],
+        1,
+        log_table_state.offset(),
+    ));
 }
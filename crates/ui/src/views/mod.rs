@@ -1,13 +1,21 @@
 // UI Views module
 mod execution_tab;
+mod file_viewer;
+mod graph_tab;
 mod help_overlay;
+mod history_tab;
 mod job_detail;
 mod logs_tab;
+mod palette;
+mod run_dialog;
 mod status_bar;
 mod title_bar;
+mod trigger_dialog;
+mod validation_tab;
 mod workflows_tab;
 
 use crate::app::App;
+use crate::models::MouseTargets;
 use ratatui::{backend::CrosstermBackend, Frame};
 use std::io;
 
@@ -16,10 +24,14 @@ pub fn render_ui(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &mut App) {
     // Check if help should be shown as an overlay
     if app.show_help {
         help_overlay::render_help_overlay(f, app.help_scroll);
+        if let Some(palette) = &app.palette {
+            palette::render_palette(f, palette, f.size());
+        }
         return;
     }
 
     let size = f.size();
+    let mut targets = MouseTargets::default();
 
     // Create main layout
     let main_chunks = ratatui::layout::Layout::default()
@@ -35,23 +47,54 @@ pub fn render_ui(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &mut App) {
         .split(size);
 
     // Render title bar with tabs
-    title_bar::render_title_bar(f, app, main_chunks[0]);
+    title_bar::render_title_bar(f, app, main_chunks[0], &mut targets);
 
     // Render main content based on selected tab
     match app.selected_tab {
-        0 => workflows_tab::render_workflows_tab(f, app, main_chunks[1]),
+        0 => {
+            if let Some(view) = app.file_view.as_mut() {
+                file_viewer::render_file_viewer(f, view, main_chunks[1])
+            } else {
+                workflows_tab::render_workflows_tab(f, app, main_chunks[1], &mut targets)
+            }
+        }
         1 => {
             if app.detailed_view {
-                job_detail::render_job_detail_view(f, app, main_chunks[1])
+                job_detail::render_job_detail_view(f, app, main_chunks[1], &mut targets)
             } else {
-                execution_tab::render_execution_tab(f, app, main_chunks[1])
+                execution_tab::render_execution_tab(f, app, main_chunks[1], &mut targets)
             }
         }
-        2 => logs_tab::render_logs_tab(f, app, main_chunks[1]),
-        3 => help_overlay::render_help_content(f, main_chunks[1], app.help_scroll),
+        2 => logs_tab::render_logs_tab(f, app, main_chunks[1], &mut targets),
+        3 => graph_tab::render_graph_tab(f, app, main_chunks[1], &mut targets),
+        4 => history_tab::render_history_tab(f, app, main_chunks[1], &mut targets),
+        5 => validation_tab::render_validation_tab(f, app, main_chunks[1], &mut targets),
+        6 => help_overlay::render_help_content(f, main_chunks[1], app.help_scroll),
         _ => {}
     }
 
     // Render status bar
     status_bar::render_status_bar(f, app, main_chunks[2]);
+
+    if let Some(dialog) = &app.run_dialog {
+        let name = app
+            .workflows
+            .get(dialog.workflow)
+            .map_or("", |workflow| workflow.name.as_str());
+        run_dialog::render_run_dialog(f, dialog, name, size);
+    }
+
+    if let Some(dialog) = &app.trigger_dialog {
+        let name = app
+            .workflows
+            .get(dialog.workflow)
+            .map_or("", |workflow| workflow.name.as_str());
+        trigger_dialog::render_trigger_dialog(f, dialog, name, size);
+    }
+
+    if let Some(palette) = &app.palette {
+        palette::render_palette(f, palette, size);
+    }
+
+    app.mouse_targets = targets;
 }
@@ -0,0 +1,103 @@
+// Find palette rendering
+use crate::models::{Palette, PaletteTarget};
+use crate::theme::theme;
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+use std::io;
+
+// Render the find palette over the top middle of `area`
+pub fn render_palette(f: &mut Frame<CrosstermBackend<io::Stdout>>, palette: &Palette, area: Rect) {
+    let width = area.width.min(80);
+    let height = area.height.saturating_sub(4).min(20);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + 2.min(area.height),
+        width,
+        height,
+    };
+    f.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .title(Span::styled(
+            format!(
+                " Find ({}/{}) ",
+                palette.matches.len(),
+                palette.entries.len()
+            ),
+            Style::default().fg(theme().title),
+        ));
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(1), // Query
+                Constraint::Length(1), // Instructions
+                Constraint::Min(0),    // Matches
+            ]
+            .as_ref(),
+        )
+        .split(inner);
+
+    let query = Paragraph::new(Line::from(vec![
+        Span::styled("> ", Style::default().fg(theme().key)),
+        Span::styled(
+            format!("{}█", palette.query),
+            Style::default().fg(theme().text),
+        ),
+    ]));
+    f.render_widget(query, chunks[0]);
+    f.render_widget(
+        Paragraph::new(Span::styled(
+            "↑/↓: Select   Enter: Go to   Esc: Close",
+            Style::default().fg(theme().muted),
+        )),
+        chunks[1],
+    );
+
+    let items: Vec<ListItem> = palette
+        .matches
+        .iter()
+        .map(|&idx| {
+            let entry = &palette.entries[idx];
+            let kind_color = match entry.target {
+                PaletteTarget::Workflow(_) => theme().accent,
+                PaletteTarget::Job { .. } => theme().key,
+                PaletteTarget::Run(_) => theme().special,
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:<9}", entry.target.kind()),
+                    Style::default().fg(kind_color),
+                ),
+                Span::styled(entry.label.clone(), Style::default().fg(theme().text)),
+                Span::styled(
+                    format!("  {}", entry.detail),
+                    Style::default().fg(theme().muted),
+                ),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .highlight_style(
+            Style::default()
+                .bg(theme().highlight)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("» ");
+    let mut state = ListState::default();
+    if !palette.matches.is_empty() {
+        state.select(Some(palette.selected));
+    }
+    f.render_stateful_widget(list, chunks[2], &mut state);
+}
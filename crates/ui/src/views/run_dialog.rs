@@ -0,0 +1,137 @@
+// Run dialog rendering
+use crate::models::{runtime_name, RunDialog, RunField};
+use crate::theme::theme;
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+use std::io;
+
+// Render the run dialog over the middle of `area`
+pub fn render_run_dialog(
+    f: &mut Frame<CrosstermBackend<io::Stdout>>,
+    dialog: &RunDialog,
+    workflow_name: &str,
+    area: Rect,
+) {
+    let label = |text: &str, field: RunField| {
+        let style = if dialog.focus == field {
+            Style::default()
+                .fg(theme().title)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme().accent)
+        };
+        let marker = if dialog.focus == field { "» " } else { "  " };
+        Span::styled(format!("{}{:<20}", marker, text), style)
+    };
+    let choice = |text: String, field: RunField| {
+        if dialog.focus == field {
+            Span::styled(format!("◀ {} ▶", text), Style::default().fg(theme().text))
+        } else {
+            Span::styled(text, Style::default().fg(theme().text))
+        }
+    };
+
+    let mut lines = vec![Line::from(vec![
+        label("Runtime", RunField::Runtime),
+        choice(runtime_name(&dialog.runtime).to_string(), RunField::Runtime),
+    ])];
+    if !dialog.events.is_empty() {
+        lines.push(Line::from(vec![
+            label("Event", RunField::Event),
+            choice(dialog.events[dialog.event].clone(), RunField::Event),
+        ]));
+    }
+    if !dialog.inputs.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "  Inputs",
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+    }
+    for (idx, (input, value)) in dialog.inputs.iter().enumerate() {
+        let field = RunField::Input(idx);
+        let name = if input.required {
+            format!("{} *", input.name)
+        } else {
+            input.name.clone()
+        };
+        let value = if !input.options.is_empty() {
+            choice(value.clone(), field)
+        } else if dialog.focus == field {
+            Span::styled(format!("{}█", value), Style::default().fg(theme().text))
+        } else {
+            Span::styled(value.clone(), Style::default().fg(theme().text))
+        };
+        lines.push(Line::from(vec![label(&name, field), value]));
+        if let Some(description) = &input.description {
+            lines.push(Line::from(Span::styled(
+                format!("{:22}{}", "", description),
+                Style::default().fg(theme().muted),
+            )));
+        }
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        label("Preserve containers", RunField::PreserveContainers),
+        choice(
+            if dialog.preserve_containers {
+                "on failure".to_string()
+            } else {
+                "no".to_string()
+            },
+            RunField::PreserveContainers,
+        ),
+    ]));
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "  [ Run ]",
+        if dialog.focus == RunField::Run {
+            Style::default()
+                .fg(theme().background)
+                .bg(theme().success)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme().success)
+        },
+    )));
+    if let Some(error) = &dialog.error {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("  {}", error),
+            Style::default().fg(theme().failure),
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "  ↑/↓: Field   ←/→: Change   Enter: Run   Esc: Cancel",
+        Style::default().fg(theme().muted),
+    )));
+
+    let width = area.width.min(72);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .title(Span::styled(
+                    format!(" Run {} ", workflow_name),
+                    Style::default().fg(theme().title),
+                )),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(Clear, popup);
+    f.render_widget(paragraph, popup);
+}
@@ -1,9 +1,10 @@
 // Status bar rendering
 use crate::app::App;
+use crate::theme::theme;
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Alignment, Rect},
-    style::{Color, Style},
+    style::Style,
     text::{Line, Span},
     widgets::Paragraph,
     Frame,
@@ -21,8 +22,12 @@ pub fn render_status_bar(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App,
         let status_message = Paragraph::new(Line::from(vec![Span::styled(
             format!(" {} ", message),
             Style::default()
-                .bg(if is_success { Color::Green } else { Color::Red })
-                .fg(Color::White)
+                .bg(if is_success {
+                    theme().success
+                } else {
+                    theme().failure
+                })
+                .fg(theme().text)
                 .add_modifier(ratatui::style::Modifier::BOLD),
         )]))
         .alignment(Alignment::Center);
@@ -39,12 +44,13 @@ pub fn render_status_bar(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App,
         format!(" {} ", app.runtime_type_name()),
         Style::default()
             .bg(match app.runtime_type {
-                RuntimeType::Docker => Color::Blue,
-                RuntimeType::Podman => Color::Cyan,
-                RuntimeType::SecureEmulation => Color::Green,
-                RuntimeType::Emulation => Color::Red,
+                RuntimeType::Docker => theme().accent,
+                RuntimeType::Podman => theme().key,
+                RuntimeType::SecureEmulation => theme().success,
+                RuntimeType::Emulation => theme().failure,
+                RuntimeType::Wasm => theme().special,
             })
-            .fg(Color::White),
+            .fg(theme().text),
     ));
 
     // Add container runtime status if relevant
@@ -72,11 +78,11 @@ pub fn render_status_bar(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App,
                 },
                 Style::default()
                     .bg(if is_docker_available {
-                        Color::Green
+                        theme().success
                     } else {
-                        Color::Red
+                        theme().failure
                     })
-                    .fg(Color::White),
+                    .fg(theme().text),
             ));
         }
         RuntimeType::Podman => {
@@ -102,20 +108,20 @@ pub fn render_status_bar(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App,
                 },
                 Style::default()
                     .bg(if is_podman_available {
-                        Color::Green
+                        theme().success
                     } else {
-                        Color::Red
+                        theme().failure
                     })
-                    .fg(Color::White),
+                    .fg(theme().text),
             ));
         }
         RuntimeType::SecureEmulation => {
             status_items.push(Span::styled(
                 " 🔒SECURE ",
-                Style::default().bg(Color::Green).fg(Color::White),
+                Style::default().bg(theme().success).fg(theme().text),
             ));
         }
-        RuntimeType::Emulation => {
+        RuntimeType::Emulation | RuntimeType::Wasm => {
             // No need to check anything for emulation mode
         }
     }
@@ -123,34 +129,38 @@ pub fn render_status_bar(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App,
     // Add validation/execution mode
     status_items.push(Span::raw(" "));
     status_items.push(Span::styled(
-        format!(
-            " {} ",
-            if app.validation_mode {
-                "Validation"
-            } else {
-                "Execution"
-            }
-        ),
+        if app.validation_mode {
+            " Validation "
+        } else if app.dry_run_mode {
+            " Dry Run "
+        } else {
+            " Execution "
+        },
         Style::default()
             .bg(if app.validation_mode {
-                Color::Yellow
+                theme().title
+            } else if app.dry_run_mode {
+                theme().key
             } else {
-                Color::Green
+                theme().success
             })
-            .fg(Color::Black),
+            .fg(theme().background),
     ));
 
     // Add context-specific help based on current tab
     status_items.push(Span::raw(" "));
     let help_text = match app.selected_tab {
+        0 if app.file_view.is_some() => {
+            "[↑/↓] Scroll   [s] Search   [n/N] Next/previous match   [Esc] Close viewer"
+        }
         0 => {
             if let Some(idx) = app.workflow_list_state.selected() {
                 if idx < app.workflows.len() {
                     let workflow = &app.workflows[idx];
                     match workflow.status {
-                        crate::models::WorkflowStatus::NotStarted => "[Space] Toggle selection   [Enter] Run selected   [r] Run all selected   [t] Trigger Workflow  [Shift+R] Reset workflow",
-                        crate::models::WorkflowStatus::Running => "[Space] Toggle selection   [Enter] Run selected   [r] Run all selected   (Workflow running...)",
-                        crate::models::WorkflowStatus::Success | crate::models::WorkflowStatus::Failed | crate::models::WorkflowStatus::Skipped => "[Space] Toggle selection   [Enter] Run selected   [r] Run all selected   [Shift+R] Reset workflow",
+                        crate::models::WorkflowStatus::NotStarted => "[Space] Toggle selection   [Enter] Run selected   [r] Run all selected   [t] Trigger remotely  [o] View file  [Shift+R] Reset workflow",
+                        crate::models::WorkflowStatus::Running => "[Space] Toggle selection   [Enter] Run selected   [r] Run all selected   [Shift+C] Cancel run   (Workflow running...)",
+                        crate::models::WorkflowStatus::Success | crate::models::WorkflowStatus::Failed | crate::models::WorkflowStatus::Skipped | crate::models::WorkflowStatus::Cancelled => "[Space] Toggle selection   [Enter] Run selected   [r] Run all selected   [o] View file   [Shift+R] Reset workflow",
                     }
                 } else {
                     "[Space] Toggle selection   [Enter] Run selected   [r] Run all selected"
@@ -161,9 +171,9 @@ pub fn render_status_bar(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App,
         }
         1 => {
             if app.detailed_view {
-                "[Esc] Back to jobs   [↑/↓] Navigate steps"
+                "[Esc] Back to jobs   [↑/↓] Navigate steps   [PgUp/PgDn] Scroll output"
             } else {
-                "[Enter] View details   [↑/↓] Navigate jobs"
+                "[Enter] View details   [↑/↓] Navigate jobs   [c] Cancel job   [Shift+C] Cancel run"
             }
         }
         2 => {
@@ -172,7 +182,7 @@ pub fn render_status_bar(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App,
             if log_count > 0 {
                 // Convert to a static string for consistent return type
                 let scroll_text = format!(
-                    "[↑/↓] Scroll logs ({}/{}) [s] Search [f] Filter",
+                    "[↑/↓] Scroll logs ({}/{}) [s] Search [f] Filter [m] Mark [y] Copy [S] Save",
                     app.log_scroll + 1,
                     log_count
                 );
@@ -181,31 +191,38 @@ pub fn render_status_bar(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App,
                 "[No logs to display]"
             }
         }
-        3 => "[↑/↓] Scroll help   [?] Toggle help overlay",
+        3 => "[←/→] Stages   [↑/↓] Jobs   [Enter] Open called workflow   [Esc] Back",
+        4 => "[↑/↓] Runs   [Enter] Mark run to compare with   [r] Rerun from failures",
+        5 => "[↑/↓] Issues   [Enter] Open file at issue   [r] Re-validate",
+        6 => "[↑/↓] Scroll help   [?] Toggle help overlay",
         _ => "",
     };
     status_items.push(Span::styled(
         format!(" {} ", help_text),
-        Style::default().fg(Color::White),
+        Style::default().fg(theme().text),
     ));
 
     // Show keybindings for common actions
     status_items.push(Span::raw(" "));
     status_items.push(Span::styled(
         " [Tab] Switch tabs ",
-        Style::default().fg(Color::White),
+        Style::default().fg(theme().text),
+    ));
+    status_items.push(Span::styled(
+        " [Ctrl+P] Find ",
+        Style::default().fg(theme().text),
     ));
     status_items.push(Span::styled(
         " [?] Help ",
-        Style::default().fg(Color::White),
+        Style::default().fg(theme().text),
     ));
     status_items.push(Span::styled(
         " [q] Quit ",
-        Style::default().fg(Color::White),
+        Style::default().fg(theme().text),
     ));
 
     let status_bar = Paragraph::new(Line::from(status_items))
-        .style(Style::default().bg(Color::DarkGray))
+        .style(Style::default().bg(theme().highlight))
         .alignment(Alignment::Left);
 
     f.render_widget(status_bar, area);
@@ -1,9 +1,11 @@
 // Title bar rendering
 use crate::app::App;
+use crate::models::MouseTargets;
+use crate::theme::theme;
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Alignment, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, Tabs},
     Frame,
@@ -11,41 +13,39 @@ use ratatui::{
 use std::io;
 
 // Render the title bar with tabs
-pub fn render_title_bar(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App, area: Rect) {
-    let titles = ["Workflows", "Execution", "Logs", "Help"];
+pub fn render_title_bar(
+    f: &mut Frame<CrosstermBackend<io::Stdout>>,
+    app: &App,
+    area: Rect,
+    targets: &mut MouseTargets,
+) {
+    // Tab names with the position of the letter that switches to them
+    let titles = [
+        ("Workflows", 0),
+        ("Execution", 1),
+        ("Logs", 0),
+        ("Graph", 0),
+        ("History", 1),
+        ("Validation", 0),
+        ("Help", 0),
+    ];
     let tabs = Tabs::new(
         titles
             .iter()
-            .enumerate()
-            .map(|(i, t)| {
-                if i == 1 {
-                    // Special case for "Execution"
-                    let e_part = &t[0..1]; // "E"
-                    let x_part = &t[1..2]; // "x"
-                    let rest = &t[2..]; // "ecution"
-                    Line::from(vec![
-                        Span::styled(e_part, Style::default().fg(Color::White)),
-                        Span::styled(
-                            x_part,
-                            Style::default()
-                                .fg(Color::Yellow)
-                                .add_modifier(Modifier::UNDERLINED),
-                        ),
-                        Span::styled(rest, Style::default().fg(Color::White)),
-                    ])
-                } else {
-                    // Original styling for other tabs
-                    let (first, rest) = t.split_at(1);
-                    Line::from(vec![
-                        Span::styled(
-                            first,
-                            Style::default()
-                                .fg(Color::Yellow)
-                                .add_modifier(Modifier::UNDERLINED),
-                        ),
-                        Span::styled(rest, Style::default().fg(Color::White)),
-                    ])
-                }
+            .map(|(t, key)| {
+                // Underline the key letter
+                let (before, rest) = t.split_at(*key);
+                let (letter, after) = rest.split_at(1);
+                Line::from(vec![
+                    Span::styled(before, Style::default().fg(theme().text)),
+                    Span::styled(
+                        letter,
+                        Style::default()
+                            .fg(theme().title)
+                            .add_modifier(Modifier::UNDERLINED),
+                    ),
+                    Span::styled(after, Style::default().fg(theme().text)),
+                ])
             })
             .collect(),
     )
@@ -56,19 +56,33 @@ pub fn render_title_bar(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App,
             .title(Span::styled(
                 " wrkflw ",
                 Style::default()
-                    .fg(Color::Cyan)
+                    .fg(theme().key)
                     .add_modifier(Modifier::BOLD),
             ))
             .title_alignment(Alignment::Center),
     )
     .highlight_style(
         Style::default()
-            .bg(Color::DarkGray)
-            .fg(Color::Yellow)
+            .bg(theme().highlight)
+            .fg(theme().title)
             .add_modifier(Modifier::BOLD),
     )
     .select(app.selected_tab)
     .divider(Span::raw("|"));
 
     f.render_widget(tabs, area);
+
+    // Columns each tab spans: a space of padding on both sides of the title and
+    // a one column divider between tabs
+    let mut start = area.x + 1;
+    let spans = titles
+        .iter()
+        .map(|(t, _)| {
+            let width = t.chars().count() as u16 + 2;
+            let span = (start, start + width);
+            start += width + 1;
+            span
+        })
+        .collect();
+    targets.tabs = Some((area.y + 1, spans));
 }
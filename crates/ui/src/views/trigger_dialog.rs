@@ -0,0 +1,145 @@
+// Remote trigger form rendering
+use crate::models::{TriggerDialog, TriggerField};
+use crate::theme::theme;
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+use std::io;
+
+// Render the trigger form over the middle of `area`
+pub fn render_trigger_dialog(
+    f: &mut Frame<CrosstermBackend<io::Stdout>>,
+    dialog: &TriggerDialog,
+    workflow_name: &str,
+    area: Rect,
+) {
+    let label = |text: &str, field: TriggerField| {
+        let style = if dialog.focus == field {
+            Style::default()
+                .fg(theme().title)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme().accent)
+        };
+        let marker = if dialog.focus == field { "» " } else { "  " };
+        Span::styled(format!("{}{:<20}", marker, text), style)
+    };
+    let choice = |text: String, field: TriggerField| {
+        if dialog.focus == field {
+            Span::styled(format!("◀ {} ▶", text), Style::default().fg(theme().text))
+        } else {
+            Span::styled(text, Style::default().fg(theme().text))
+        }
+    };
+    let text = |value: &str, placeholder: &str, field: TriggerField| {
+        let cursor = if dialog.focus == field { "█" } else { "" };
+        if value.is_empty() {
+            Span::styled(
+                format!("{}{}", cursor, placeholder),
+                Style::default().fg(theme().muted),
+            )
+        } else {
+            Span::styled(
+                format!("{}{}", value, cursor),
+                Style::default().fg(theme().text),
+            )
+        }
+    };
+
+    let mut lines = vec![
+        Line::from(vec![
+            label("Workflow", TriggerField::Workflow),
+            choice(workflow_name.to_string(), TriggerField::Workflow),
+        ]),
+        Line::from(vec![
+            label("Branch", TriggerField::Branch),
+            text(&dialog.branch, "default branch", TriggerField::Branch),
+        ]),
+    ];
+    if !dialog.inputs.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            if dialog.gitlab {
+                "  Variables"
+            } else {
+                "  Inputs"
+            },
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+    }
+    for (idx, (input, value)) in dialog.inputs.iter().enumerate() {
+        let field = TriggerField::Input(idx);
+        let name = if input.required {
+            format!("{} *", input.name)
+        } else {
+            input.name.clone()
+        };
+        let value = if input.options.is_empty() {
+            text(value, "", field)
+        } else {
+            choice(value.clone(), field)
+        };
+        lines.push(Line::from(vec![label(&name, field), value]));
+        if let Some(description) = &input.description {
+            lines.push(Line::from(Span::styled(
+                format!("{:22}{}", "", description),
+                Style::default().fg(theme().muted),
+            )));
+        }
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        if dialog.gitlab {
+            "  [ Start pipeline on GitLab ]"
+        } else {
+            "  [ Trigger on GitHub ]"
+        },
+        if dialog.focus == TriggerField::Trigger {
+            Style::default()
+                .fg(theme().background)
+                .bg(theme().special)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme().special)
+        },
+    )));
+    if let Some(error) = &dialog.error {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("  {}", error),
+            Style::default().fg(theme().failure),
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "  ↑/↓: Field   ←/→: Change   Enter: Trigger   Esc: Cancel",
+        Style::default().fg(theme().muted),
+    )));
+
+    let width = area.width.min(72);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .title(Span::styled(
+                    " Trigger remotely ",
+                    Style::default().fg(theme().title),
+                )),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(Clear, popup);
+    f.render_widget(paragraph, popup);
+}
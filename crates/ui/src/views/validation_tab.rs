@@ -0,0 +1,177 @@
+// Validation tab rendering
+use crate::app::App;
+use crate::models::{FileValidation, IssueSeverity, ListKind, ListTarget, MouseTargets};
+use crate::theme::theme;
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+use std::io;
+
+fn severity_style(severity: IssueSeverity) -> (&'static str, Style) {
+    match severity {
+        IssueSeverity::Error => ("✖", Style::default().fg(theme().failure)),
+        IssueSeverity::Warning => ("⚠", Style::default().fg(theme().warning)),
+    }
+}
+
+// The row of a file: its path and how many findings of each severity it has
+fn file_line(validation: &FileValidation) -> Line<'static> {
+    let errors = validation.count(IssueSeverity::Error);
+    let warnings = validation.count(IssueSeverity::Warning);
+    let (symbol, style) = if errors > 0 {
+        ("❌", Style::default().fg(theme().failure))
+    } else if warnings > 0 {
+        ("⚠️", Style::default().fg(theme().warning))
+    } else {
+        ("✅", Style::default().fg(theme().success))
+    };
+    let mut counts = Vec::new();
+    if errors > 0 {
+        counts.push(format!("{} error(s)", errors));
+    }
+    if warnings > 0 {
+        counts.push(format!("{} warning(s)", warnings));
+    }
+    Line::from(vec![
+        Span::styled(format!("{} ", symbol), style),
+        Span::styled(
+            validation.path.display().to_string(),
+            style.add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            if counts.is_empty() {
+                String::new()
+            } else {
+                format!("  ({})", counts.join(", "))
+            },
+            Style::default().fg(theme().muted),
+        ),
+    ])
+}
+
+// Render the validation findings of the loaded workflows, grouped by file
+pub fn render_validation_tab(
+    f: &mut Frame<CrosstermBackend<io::Stdout>>,
+    app: &App,
+    area: Rect,
+    targets: &mut MouseTargets,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(3), // Header with instructions
+                Constraint::Min(5),    // Findings
+            ]
+            .as_ref(),
+        )
+        .margin(1)
+        .split(area);
+
+    let header_text = vec![
+        Line::from(vec![Span::styled(
+            "Validation",
+            Style::default()
+                .fg(theme().title)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(vec![
+            Span::styled("↑/↓", Style::default().fg(theme().key)),
+            Span::raw(": Issues   "),
+            Span::styled("Enter", Style::default().fg(theme().key)),
+            Span::raw(": Open file at issue   "),
+            Span::styled("r", Style::default().fg(theme().key)),
+            Span::raw(": Re-validate"),
+        ]),
+    ];
+    let header = Paragraph::new(header_text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded),
+        )
+        .alignment(Alignment::Center);
+    f.render_widget(header, chunks[0]);
+
+    let view = match &app.validation_view {
+        Some(view) if !view.files.is_empty() => view,
+        _ => {
+            let paragraph = Paragraph::new("No workflows loaded to validate")
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded),
+                )
+                .wrap(Wrap { trim: false });
+            f.render_widget(paragraph, chunks[1]);
+            return;
+        }
+    };
+
+    let items: Vec<ListItem> = view
+        .rows()
+        .into_iter()
+        .map(|(file, issue)| {
+            let validation = &view.files[file];
+            let Some(issue) = issue.map(|issue| &validation.issues[issue]) else {
+                return ListItem::new(file_line(validation));
+            };
+            let (symbol, style) = severity_style(issue.severity);
+            let location = issue
+                .line
+                .map_or_else(String::new, |line| format!("L{} ", line + 1));
+            ListItem::new(Line::from(vec![
+                Span::raw("    "),
+                Span::styled(format!("{} ", symbol), style),
+                Span::styled(location, Style::default().fg(theme().muted)),
+                Span::styled(issue.message.clone(), style),
+            ]))
+        })
+        .collect();
+
+    let errors = view.count(IssueSeverity::Error);
+    let warnings = view.count(IssueSeverity::Warning);
+    let title = format!(
+        " {} file(s), {} error(s), {} warning(s) — validated at {} ",
+        view.files.len(),
+        errors,
+        warnings,
+        view.validated_at.format("%H:%M:%S")
+    );
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .title(Span::styled(
+                    title,
+                    Style::default().fg(if errors > 0 {
+                        theme().failure
+                    } else if warnings > 0 {
+                        theme().warning
+                    } else {
+                        theme().success
+                    }),
+                )),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(theme().highlight)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("» ");
+    let mut state = ListState::default();
+    state.select(Some(view.selected));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+    targets.list = Some(ListTarget::new(
+        ListKind::Validation,
+        chunks[1],
+        0,
+        state.offset(),
+    ));
+}
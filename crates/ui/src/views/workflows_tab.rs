@@ -1,12 +1,13 @@
 // Workflows tab rendering
 use crate::app::App;
-use crate::models::WorkflowStatus;
+use crate::models::{ListKind, ListTarget, MouseTargets, Split, SplitTarget, WorkflowStatus};
+use crate::theme::theme;
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Cell, Paragraph, Row, Table, TableState},
+    widgets::{Block, BorderType, Borders, Cell, Paragraph, Row, Table, TableState, Wrap},
     Frame,
 };
 use std::io;
@@ -16,6 +17,7 @@ pub fn render_workflows_tab(
     f: &mut Frame<CrosstermBackend<io::Stdout>>,
     app: &mut App,
     area: Rect,
+    targets: &mut MouseTargets,
 ) {
     // Create a more structured layout for the workflow tab
     let chunks = Layout::default()
@@ -35,15 +37,15 @@ pub fn render_workflows_tab(
         Line::from(vec![Span::styled(
             "Available Workflows",
             Style::default()
-                .fg(Color::Yellow)
+                .fg(theme().title)
                 .add_modifier(Modifier::BOLD),
         )]),
         Line::from(vec![
-            Span::styled("Space", Style::default().fg(Color::Cyan)),
+            Span::styled("Space", Style::default().fg(theme().key)),
             Span::raw(": Toggle selection   "),
-            Span::styled("Enter", Style::default().fg(Color::Cyan)),
+            Span::styled("Enter", Style::default().fg(theme().key)),
             Span::raw(": Run   "),
-            Span::styled("t", Style::default().fg(Color::Cyan)),
+            Span::styled("t", Style::default().fg(theme().key)),
             Span::raw(": Trigger remotely"),
         ]),
     ];
@@ -60,14 +62,14 @@ pub fn render_workflows_tab(
 
     // Create a table for workflows instead of a list for better organization
     let selected_style = Style::default()
-        .bg(Color::DarkGray)
+        .bg(theme().highlight)
         .add_modifier(Modifier::BOLD);
 
     // Normal style definition removed as it was unused
 
     let header_cells = ["", "Status", "Workflow Name", "Path"]
         .iter()
-        .map(|h| Cell::from(*h).style(Style::default().fg(Color::Yellow)));
+        .map(|h| Cell::from(*h).style(Style::default().fg(theme().title)));
 
     let header = Row::new(header_cells)
         .style(Style::default().add_modifier(Modifier::BOLD))
@@ -78,11 +80,12 @@ pub fn render_workflows_tab(
         let checkbox = if workflow.selected { "✓" } else { " " };
 
         let (status_symbol, status_style) = match workflow.status {
-            WorkflowStatus::NotStarted => ("○", Style::default().fg(Color::Gray)),
-            WorkflowStatus::Running => ("⟳", Style::default().fg(Color::Cyan)),
-            WorkflowStatus::Success => ("✅", Style::default().fg(Color::Green)),
-            WorkflowStatus::Failed => ("❌", Style::default().fg(Color::Red)),
-            WorkflowStatus::Skipped => ("⏭", Style::default().fg(Color::Yellow)),
+            WorkflowStatus::NotStarted => ("○", Style::default().fg(theme().dim)),
+            WorkflowStatus::Running => ("⟳", Style::default().fg(theme().running)),
+            WorkflowStatus::Success => ("✅", Style::default().fg(theme().success)),
+            WorkflowStatus::Failed => ("❌", Style::default().fg(theme().failure)),
+            WorkflowStatus::Skipped => ("⏭", Style::default().fg(theme().dim)),
+            WorkflowStatus::Cancelled => ("🚫", Style::default().fg(theme().cancelled)),
         };
 
         let path_display = workflow.path.to_string_lossy();
@@ -93,10 +96,10 @@ pub fn render_workflows_tab(
         };
 
         Row::new(vec![
-            Cell::from(checkbox).style(Style::default().fg(Color::Green)),
+            Cell::from(checkbox).style(Style::default().fg(theme().success)),
             Cell::from(status_symbol).style(status_style),
             Cell::from(workflow.name.clone()),
-            Cell::from(path_shortened).style(Style::default().fg(Color::DarkGray)),
+            Cell::from(path_shortened).style(Style::default().fg(theme().muted)),
         ])
     });
 
@@ -108,7 +111,7 @@ pub fn render_workflows_tab(
                 .border_type(BorderType::Rounded)
                 .title(Span::styled(
                     " Workflows ",
-                    Style::default().fg(Color::Yellow),
+                    Style::default().fg(theme().title),
                 )),
         )
         .highlight_style(selected_style)
@@ -120,12 +123,81 @@ pub fn render_workflows_tab(
             Constraint::Percentage(45), // Path column
         ]);
 
+    // A GitLab pipeline's stages show beside the list
+    app.load_pipeline_view();
+    let (table_area, pipeline_area) = if app.pipeline_view.is_some() {
+        let percent = app.pane_sizes.get(Split::Workflows);
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                [
+                    Constraint::Percentage(percent),
+                    Constraint::Percentage(100 - percent),
+                ]
+                .as_ref(),
+            )
+            .split(chunks[1]);
+        targets.splits.push(SplitTarget {
+            split: Split::Workflows,
+            area: chunks[1],
+            direction: Direction::Horizontal,
+        });
+        (columns[0], Some(columns[1]))
+    } else {
+        (chunks[1], None)
+    };
+
     // We need to convert ListState to TableState
     let mut table_state = TableState::default();
     table_state.select(app.workflow_list_state.selected());
 
-    f.render_stateful_widget(workflows_table, chunks[1], &mut table_state);
+    f.render_stateful_widget(workflows_table, table_area, &mut table_state);
+    targets.list = Some(ListTarget::new(
+        ListKind::Workflows,
+        table_area,
+        1,
+        table_state.offset(),
+    ));
 
     // Update the app list state to match the table state
     app.workflow_list_state.select(table_state.selected());
+
+    if let (Some(view), Some(area)) = (&app.pipeline_view, pipeline_area) {
+        let lines: Vec<Line> = match &view.stages {
+            Ok(stages) if stages.is_empty() => vec![Line::from(Span::styled(
+                "No jobs",
+                Style::default().fg(theme().muted),
+            ))],
+            Ok(stages) => stages
+                .iter()
+                .enumerate()
+                .flat_map(|(idx, (stage, jobs))| {
+                    let mut lines = vec![Line::from(Span::styled(
+                        format!("{}. {}", idx + 1, stage),
+                        Style::default()
+                            .fg(theme().title)
+                            .add_modifier(Modifier::BOLD),
+                    ))];
+                    lines.extend(jobs.iter().map(|job| Line::from(format!("   • {}", job))));
+                    lines
+                })
+                .collect(),
+            Err(e) => vec![Line::from(Span::styled(
+                format!("⚠ {}", e),
+                Style::default().fg(theme().failure),
+            ))],
+        };
+        let pipeline = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .title(Span::styled(
+                        " Pipeline stages ",
+                        Style::default().fg(theme().title),
+                    )),
+            )
+            .wrap(Wrap { trim: false });
+        f.render_widget(pipeline, area);
+    }
 }
@@ -0,0 +1,222 @@
+//! Tokens for the GitHub and GitLab APIs: from environment variables first, then
+//! from the `gh` and `glab` CLIs, then from the config files those CLIs keep.
+//!
+//! These are for the commands that call the APIs themselves (`trigger`, `status`,
+//! `pin` and the like). Workflow runs never get them unasked: `wrkflw run` only
+//! exposes the GitHub token to steps with `--github-token-from-env`.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// A place a token can come from
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenSource {
+    /// An environment variable, by name
+    Env(&'static str),
+    /// `gh auth token`
+    GhCli,
+    /// The `hosts.yml` of the gh CLI
+    GhConfig(PathBuf),
+    /// `glab auth status --show-token`
+    GlabCli,
+    /// The `config.yml` of the glab CLI
+    GlabConfig(PathBuf),
+}
+
+impl fmt::Display for TokenSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenSource::Env(name) => write!(f, "{} environment variable", name),
+            TokenSource::GhCli => write!(f, "`gh auth token`"),
+            TokenSource::GhConfig(path) => write!(f, "gh config {}", path.display()),
+            TokenSource::GlabCli => write!(f, "`glab auth status`"),
+            TokenSource::GlabConfig(path) => write!(f, "glab config {}", path.display()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Credential {
+    pub token: String,
+    pub source: TokenSource,
+}
+
+/// `token` with all but its first and last few characters hidden, for display
+pub fn mask(token: &str) -> String {
+    let chars: Vec<char> = token.chars().collect();
+    if chars.len() <= 12 {
+        return "*".repeat(chars.len());
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}…{}", head, tail)
+}
+
+/// Where GitHub tokens are looked for, in order of precedence
+pub fn github_sources() -> Vec<TokenSource> {
+    let mut sources = vec![
+        TokenSource::Env("GITHUB_TOKEN"),
+        TokenSource::Env("GH_TOKEN"),
+        TokenSource::GhCli,
+    ];
+    if let Some(dir) = config_dir("GH_CONFIG_DIR", "gh") {
+        sources.push(TokenSource::GhConfig(dir.join("hosts.yml")));
+    }
+    sources
+}
+
+/// Where GitLab tokens are looked for, in order of precedence
+pub fn gitlab_sources() -> Vec<TokenSource> {
+    let mut sources = vec![
+        TokenSource::Env("GITLAB_TOKEN"),
+        TokenSource::Env("GITLAB_ACCESS_TOKEN"),
+        TokenSource::GlabCli,
+    ];
+    if let Some(dir) = config_dir("GLAB_CONFIG_DIR", "glab-cli") {
+        sources.push(TokenSource::GlabConfig(dir.join("config.yml")));
+    }
+    sources
+}
+
+/// The token of the first of `github_sources` that has one, for calling the API
+pub fn github_token() -> Option<Credential> {
+    first_token(github_sources())
+}
+
+/// The token of the first of `gitlab_sources` that has one, for calling the API
+pub fn gitlab_token() -> Option<Credential> {
+    first_token(gitlab_sources())
+}
+
+fn first_token(sources: Vec<TokenSource>) -> Option<Credential> {
+    sources.into_iter().find_map(|source| {
+        lookup(&source).map(|token| Credential {
+            token,
+            source: source.clone(),
+        })
+    })
+}
+
+/// The token `source` holds, if any
+pub fn lookup(source: &TokenSource) -> Option<String> {
+    let token = match source {
+        TokenSource::Env(name) => std::env::var(name).ok(),
+        TokenSource::GhCli => {
+            let output = Command::new("gh").args(["auth", "token"]).output().ok()?;
+            output
+                .status
+                .success()
+                .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+        }
+        TokenSource::GhConfig(path) => {
+            let hosts = read_yaml(path)?;
+            hosts
+                .get("github.com")?
+                .get("oauth_token")?
+                .as_str()
+                .map(str::to_string)
+        }
+        TokenSource::GlabCli => {
+            let output = Command::new("glab")
+                .args(["auth", "status", "--show-token", "--hostname", "gitlab.com"])
+                .output()
+                .ok()?;
+            // Reported on stderr, along with the rest of the status
+            let report = format!(
+                "{}{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            );
+            glab_status_token(&report)
+        }
+        TokenSource::GlabConfig(path) => {
+            let config = read_yaml(path)?;
+            config
+                .get("hosts")?
+                .get("gitlab.com")?
+                .get("token")?
+                .as_str()
+                .map(str::to_string)
+        }
+    }?;
+    let token = token.trim().to_string();
+    (!token.is_empty()).then_some(token)
+}
+
+/// The token in the output of `glab auth status --show-token`
+fn glab_status_token(report: &str) -> Option<String> {
+    report.lines().find_map(|line| {
+        let (_, token) = line.split_once("Token: ")?;
+        let token = token.trim();
+        // Without a token glab prints a placeholder of asterisks
+        (!token.is_empty() && !token.starts_with('*')).then(|| token.to_string())
+    })
+}
+
+fn read_yaml(path: &std::path::Path) -> Option<serde_yaml::Value> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_yaml::from_str(&content).ok()
+}
+
+/// Config directory of a CLI: `$override_var`, else `$XDG_CONFIG_HOME/<name>`, else
+/// `~/.config/<name>`, which the CLIs also use on macOS
+fn config_dir(override_var: &str, name: &str) -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(override_var).filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    if let Some(dir) = std::env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(dir).join(name));
+    }
+    dirs::home_dir().map(|home| home.join(".config").join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glab_status_token() {
+        let report = "gitlab.com\n  ✓ Logged in to gitlab.com as alice (/home/alice/.config/glab-cli/config.yml)\n  ✓ REST API Endpoint: https://gitlab.com/api/v4/\n  ✓ Token: glpat-abcdef123456\n";
+        assert_eq!(
+            glab_status_token(report).as_deref(),
+            Some("glpat-abcdef123456")
+        );
+        assert!(glab_status_token("  ✓ Token: **************").is_none());
+        assert!(glab_status_token("x Not logged in").is_none());
+    }
+
+    #[test]
+    fn test_config_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let hosts = dir.path().join("hosts.yml");
+        std::fs::write(
+            &hosts,
+            "github.com:\n    oauth_token: gho_abc123\n    user: alice\n",
+        )
+        .unwrap();
+        assert_eq!(
+            lookup(&TokenSource::GhConfig(hosts)).as_deref(),
+            Some("gho_abc123")
+        );
+
+        let config = dir.path().join("config.yml");
+        std::fs::write(
+            &config,
+            "git_protocol: ssh\nhosts:\n    gitlab.com:\n        token: glpat-xyz\n",
+        )
+        .unwrap();
+        assert_eq!(
+            lookup(&TokenSource::GlabConfig(config)).as_deref(),
+            Some("glpat-xyz")
+        );
+
+        assert!(lookup(&TokenSource::GhConfig(dir.path().join("missing.yml"))).is_none());
+    }
+
+    #[test]
+    fn test_mask() {
+        assert_eq!(mask("ghp_1234567890abcdef"), "ghp_…cdef");
+        assert_eq!(mask("short"), "*****");
+    }
+}
@@ -0,0 +1,165 @@
+//! A cache of what's parsed and validated from workflow files, under
+//! `.wrkflw/cache` of the current directory. Entries are keyed by a hash of the
+//! file's content and of everything else the result depends on, so a file that
+//! changes simply gets a new entry.
+//!
+//! The cache is off until [`set_enabled`] turns it on, as the CLI does, so that
+//! programs using wrkflw as a library don't get files written where they run.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Directory of the cache, relative to the current directory
+pub const CACHE_DIR: &str = ".wrkflw/cache";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// `compute`'s result for the file at `path`, from the cache if it has one for
+/// the same content and `inputs`, which are what else the result depends on;
+/// `kind` tells apart what is cached for the same file
+pub fn cached_for_file<T: Serialize + DeserializeOwned>(
+    kind: &str,
+    path: &Path,
+    inputs: &[&str],
+    compute: impl FnOnce() -> T,
+) -> T {
+    if !is_enabled() {
+        return compute();
+    }
+    match std::fs::read(path) {
+        Ok(content) => ContentCache::new(CACHE_DIR).get_or_compute(kind, &content, inputs, compute),
+        Err(_) => compute(),
+    }
+}
+
+/// What validating the workflow at `path` depends on besides its content: the
+/// project's `.wrkflw.toml`, with its action policy and rule severities, and
+/// whether the local actions the workflow uses exist
+pub fn workflow_inputs(path: &Path) -> Vec<String> {
+    let mut inputs = vec![std::fs::read_to_string(".wrkflw.toml").unwrap_or_default()];
+    let content = std::fs::read_to_string(path).unwrap_or_default();
+    for line in content.lines() {
+        let line = line.trim_start();
+        let line = line.strip_prefix("- ").unwrap_or(line).trim_start();
+        let Some(uses) = line.strip_prefix("uses:") else {
+            continue;
+        };
+        let uses = uses.trim().trim_matches(['"', '\'']);
+        if uses.starts_with("./") {
+            inputs.push(format!("{}={}", uses, Path::new(uses).exists()));
+        }
+    }
+    inputs
+}
+
+/// Remove the cache of the current directory
+pub fn clear() -> std::io::Result<()> {
+    match std::fs::remove_dir_all(CACHE_DIR) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Results stored as JSON files named by their key
+pub struct ContentCache {
+    dir: PathBuf,
+}
+
+impl ContentCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        ContentCache { dir: dir.into() }
+    }
+
+    /// The cached result for `content` and `inputs`, or `compute`'s, which is
+    /// cached; a cache that can't be read or written is only slower
+    pub fn get_or_compute<T: Serialize + DeserializeOwned>(
+        &self,
+        kind: &str,
+        content: &[u8],
+        inputs: &[&str],
+        compute: impl FnOnce() -> T,
+    ) -> T {
+        let entry = self
+            .dir
+            .join(kind)
+            .join(format!("{}.json", key(content, inputs)));
+        if let Some(cached) = std::fs::read(&entry)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+        {
+            return cached;
+        }
+        let result = compute();
+        if let Ok(json) = serde_json::to_vec(&result) {
+            // Written aside then renamed, as files are validated concurrently
+            let partial = entry.with_extension(format!("{}.tmp", std::process::id()));
+            let written = entry
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(&partial, json))
+                .and_then(|_| std::fs::rename(&partial, &entry));
+            if written.is_err() {
+                let _ = std::fs::remove_file(&partial);
+            }
+        }
+        result
+    }
+}
+
+/// Hash of the content and inputs, and of the version of wrkflw, whose
+/// validators may find other things
+fn key(content: &[u8], inputs: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION"));
+    for input in inputs {
+        hasher.update([0]);
+        hasher.update(input);
+    }
+    hasher.update([0]);
+    hasher.update(content);
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ContentCache::new(dir.path());
+        let mut computed = 0;
+        let mut get = |content: &str, inputs: &[&str]| {
+            cache.get_or_compute("lengths", content.as_bytes(), inputs, || {
+                computed += 1;
+                content.len()
+            })
+        };
+        assert_eq!(get("on: push", &[]), 8);
+        assert_eq!(get("on: push", &[]), 8);
+        assert_eq!(get("on: pull_request", &[]), 16);
+        assert_eq!(get("on: push", &["github"]), 8);
+        assert_eq!(computed, 3);
+        assert_eq!(
+            std::fs::read_dir(dir.path().join("lengths"))
+                .unwrap()
+                .count(),
+            3
+        );
+    }
+}
@@ -2,13 +2,13 @@
 
 use std::path::Path;
 
+pub mod auth;
+pub mod cache;
+
 pub fn is_workflow_file(path: &Path) -> bool {
-    // First, check for GitLab CI files by name
-    if let Some(file_name) = path.file_name() {
-        let file_name_str = file_name.to_string_lossy().to_lowercase();
-        if file_name_str == ".gitlab-ci.yml" || file_name_str.ends_with("gitlab-ci.yml") {
-            return true;
-        }
+    // First, check for GitLab CI files
+    if is_gitlab_pipeline_file(path) {
+        return true;
     }
 
     // Then check for GitHub Actions workflows
@@ -34,6 +34,62 @@ pub fn is_workflow_file(path: &Path) -> bool {
     false
 }
 
+/// Whether `path` is a GitLab CI pipeline by its location: a `.gitlab-ci.yml`, or a
+/// YAML file in `.gitlab/ci`, where projects split their pipeline into
+pub fn is_gitlab_pipeline_file(path: &Path) -> bool {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if file_name.ends_with("gitlab-ci.yml") {
+        return true;
+    }
+    (file_name.ends_with(".yml") || file_name.ends_with(".yaml"))
+        && path
+            .parent()
+            .is_some_and(|parent| parent.ends_with(Path::new(".gitlab").join("ci")))
+}
+
+/// How well `query` fuzzily matches `candidate`, or `None` if its characters don't
+/// all appear in order. Matches case-insensitively; consecutive characters and
+/// characters starting a word (after `/`, `-`, `_`, `.`, `:` or a space) score
+/// higher, and gaps between matched characters lower the score.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let candidate: Vec<char> = candidate.chars().flat_map(char::to_lowercase).collect();
+    let Some(&first) = query.first() else {
+        return Some(0);
+    };
+    // Each place the first character appears starts a match; the rest follow as
+    // early as they can
+    (0..candidate.len())
+        .filter(|&start| candidate[start] == first)
+        .filter_map(|start| {
+            let mut score = 0;
+            let mut last: Option<usize> = None;
+            for &wanted in &query {
+                let from = last.map_or(start, |last| last + 1);
+                let pos = from + candidate[from..].iter().position(|&c| c == wanted)?;
+                score += 1;
+                if pos == 0 || matches!(candidate[pos - 1], '/' | '-' | '_' | '.' | ':' | ' ') {
+                    score += 8;
+                }
+                match last {
+                    Some(last) if pos == last + 1 => score += 5,
+                    Some(last) => score -= (pos - last - 1).min(10) as i64,
+                    None => score -= pos.min(10) as i64,
+                }
+                last = Some(pos);
+            }
+            Some(score)
+        })
+        .max()
+}
+
 /// Module for safely handling file descriptor redirection
 ///
 /// On Unix systems (Linux, macOS), this module provides true file descriptor
@@ -196,4 +252,41 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), 42);
     }
+
+    #[test]
+    fn test_gitlab_pipeline_file() {
+        assert!(is_gitlab_pipeline_file(Path::new(".gitlab-ci.yml")));
+        assert!(is_gitlab_pipeline_file(Path::new(
+            "ci/deploy.gitlab-ci.yml"
+        )));
+        assert!(is_gitlab_pipeline_file(Path::new(".gitlab/ci/build.yml")));
+        assert!(is_gitlab_pipeline_file(Path::new(
+            "repo/.gitlab/ci/test.yaml"
+        )));
+        assert!(!is_gitlab_pipeline_file(Path::new(".gitlab/ci/README.md")));
+        assert!(!is_gitlab_pipeline_file(Path::new(
+            ".github/workflows/ci.yml"
+        )));
+        assert!(is_workflow_file(Path::new(".gitlab/ci/build.yml")));
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert_eq!(fuzzy_score("xyz", "build"), None);
+        assert_eq!(fuzzy_score("dlib", "build"), None);
+        assert!(fuzzy_score("BLD", "build").is_some());
+
+        // Consecutive characters beat scattered ones
+        assert!(
+            fuzzy_score("test", "unit-test").unwrap() > fuzzy_score("test", "the_east").unwrap()
+        );
+        // Word starts beat the middle of words
+        assert!(
+            fuzzy_score("ci", "release/ci.yml").unwrap()
+                > fuzzy_score("ci", "special.yml").unwrap()
+        );
+        // An earlier match beats a later one
+        assert!(fuzzy_score("b", "build").unwrap() > fuzzy_score("b", "abc").unwrap());
+    }
 }
@@ -0,0 +1,249 @@
+use serde_yaml::Value;
+use wrkflw_models::ValidationResult;
+
+/// Keys an action's metadata file can have at the top level
+const TOP_LEVEL_KEYS: &[&str] = &[
+    "name",
+    "author",
+    "description",
+    "inputs",
+    "outputs",
+    "runs",
+    "branding",
+];
+
+/// Keys an input of an action can have
+const INPUT_KEYS: &[&str] = &["description", "required", "default", "deprecationMessage"];
+
+/// Runtimes JavaScript actions can run with
+const NODE_VERSIONS: &[&str] = &["node12", "node16", "node20", "node24"];
+
+/// Validate an action's metadata file (`action.yml`): the required keys, its inputs
+/// and outputs, and what `runs` needs for composite, JavaScript and Docker actions
+pub fn validate_action_metadata(action: &Value) -> ValidationResult {
+    let mut result = ValidationResult::new();
+    result.set_rule("action-metadata");
+    let Value::Mapping(map) = action else {
+        result.add_issue("Action metadata must be a mapping".to_string());
+        return result;
+    };
+
+    for key in map.keys() {
+        match key.as_str() {
+            Some(key) if TOP_LEVEL_KEYS.contains(&key) => {}
+            Some(key) => result.add_issue(format!("Unknown key '{}'", key)),
+            None => result.add_issue("Keys must be strings".to_string()),
+        }
+    }
+    for required in ["name", "description", "runs"] {
+        if action.get(required).is_none() {
+            result.add_issue(format!("Missing required '{}'", required));
+        }
+    }
+
+    let using = action
+        .get("runs")
+        .and_then(|runs| runs.get("using"))
+        .and_then(Value::as_str);
+    validate_inputs(action.get("inputs"), &mut result);
+    validate_outputs(
+        action.get("outputs"),
+        using == Some("composite"),
+        &mut result,
+    );
+    if let Some(runs) = action.get("runs") {
+        validate_runs(runs, &mut result);
+    }
+    result
+}
+
+fn validate_inputs(inputs: Option<&Value>, result: &mut ValidationResult) {
+    let Some(inputs) = inputs else {
+        return;
+    };
+    let Value::Mapping(inputs) = inputs else {
+        result.add_issue("'inputs' must be a mapping".to_string());
+        return;
+    };
+    for (name, input) in inputs {
+        let name = name.as_str().unwrap_or_default();
+        let Value::Mapping(input) = input else {
+            result.add_issue(format!("Input '{}' must be a mapping", name));
+            continue;
+        };
+        for key in input.keys().filter_map(Value::as_str) {
+            if !INPUT_KEYS.contains(&key) {
+                result.add_issue(format!("Input '{}': Unknown key '{}'", name, key));
+            }
+        }
+        if !input.contains_key("description") {
+            result.add_warning(format!("Input '{}' has no description", name));
+        }
+    }
+}
+
+fn validate_outputs(outputs: Option<&Value>, composite: bool, result: &mut ValidationResult) {
+    let Some(outputs) = outputs else {
+        return;
+    };
+    let Value::Mapping(outputs) = outputs else {
+        result.add_issue("'outputs' must be a mapping".to_string());
+        return;
+    };
+    for (name, output) in outputs {
+        let name = name.as_str().unwrap_or_default();
+        // Composite actions map their outputs to those of their steps
+        if composite && output.get("value").is_none() {
+            result.add_issue(format!(
+                "Output '{}' needs a 'value' in a composite action",
+                name
+            ));
+        }
+    }
+}
+
+fn validate_runs(runs: &Value, result: &mut ValidationResult) {
+    let Some(using) = runs.get("using").and_then(Value::as_str) else {
+        result.add_issue("'runs' is missing 'using'".to_string());
+        return;
+    };
+    match using {
+        "composite" => {
+            let Some(Value::Sequence(steps)) = runs.get("steps") else {
+                result.add_issue("Composite action is missing 'runs.steps'".to_string());
+                return;
+            };
+            for (i, step) in steps.iter().enumerate() {
+                validate_composite_step(step, i, result);
+            }
+        }
+        "docker" => {
+            if runs.get("image").is_none() {
+                result.add_issue("Docker action is missing 'runs.image'".to_string());
+            }
+        }
+        using if NODE_VERSIONS.contains(&using) => {
+            if runs.get("main").is_none() {
+                result.add_issue("JavaScript action is missing 'runs.main'".to_string());
+            }
+        }
+        using => result.add_issue(format!(
+            "Unknown 'runs.using' value '{}'. Valid values are: composite, docker, {}",
+            using,
+            NODE_VERSIONS.join(", ")
+        )),
+    }
+}
+
+fn validate_composite_step(step: &Value, idx: usize, result: &mut ValidationResult) {
+    let Value::Mapping(map) = step else {
+        result.add_issue(format!("Step {}: Not a valid mapping", idx + 1));
+        return;
+    };
+    match (map.get("run"), map.get("uses")) {
+        (Some(_), Some(_)) => result.add_issue(format!(
+            "Step {}: Contains both 'uses' and 'run' (should only use one)",
+            idx + 1
+        )),
+        (None, None) => result.add_issue(format!("Step {}: Missing 'uses' or 'run'", idx + 1)),
+        // Unlike in workflows, run steps of composite actions have no default shell
+        (Some(_), None) if !map.contains_key("shell") => result.add_issue(format!(
+            "Step {}: 'run' steps of composite actions need a 'shell'",
+            idx + 1
+        )),
+        (None, Some(uses)) => {
+            let uses = uses.as_str().unwrap_or_default();
+            let local = uses.starts_with("./") || uses.starts_with("docker://");
+            if !local && !uses.contains('@') {
+                result.add_issue(format!(
+                    "Step {}: Action '{}' is missing a version (@ref)",
+                    idx + 1,
+                    uses
+                ));
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(action: &str) -> ValidationResult {
+        validate_action_metadata(&serde_yaml::from_str(action).unwrap())
+    }
+
+    #[test]
+    fn test_valid_actions() {
+        let composite = validate(
+            r#"
+name: Setup
+description: Set things up
+inputs:
+  version:
+    description: Version to install
+    default: latest
+outputs:
+  path:
+    description: Where it went
+    value: ${{ steps.install.outputs.path }}
+runs:
+  using: composite
+  steps:
+    - uses: actions/cache@v4
+      with:
+        path: ~/.tool
+    - id: install
+      run: ./install.sh ${{ inputs.version }}
+      shell: bash
+"#,
+        );
+        assert!(composite.is_valid, "{:?}", composite.issues);
+        assert!(composite.warnings.is_empty());
+
+        let node =
+            validate("name: n\ndescription: d\nruns:\n  using: node20\n  main: dist/index.js\n");
+        assert!(node.is_valid, "{:?}", node.issues);
+    }
+
+    #[test]
+    fn test_invalid_actions() {
+        let result = validate(
+            r#"
+name: Broken
+on: push
+inputs:
+  token:
+    requried: true
+outputs:
+  path:
+    description: Missing its value
+runs:
+  using: composite
+  steps:
+    - run: make
+    - uses: someone/tool
+    - name: Nothing
+"#,
+        );
+        assert_eq!(
+            result.issues,
+            vec![
+                "Unknown key 'on'",
+                "Missing required 'description'",
+                "Input 'token': Unknown key 'requried'",
+                "Output 'path' needs a 'value' in a composite action",
+                "Step 1: 'run' steps of composite actions need a 'shell'",
+                "Step 2: Action 'someone/tool' is missing a version (@ref)",
+                "Step 3: Missing 'uses' or 'run'",
+            ]
+        );
+        assert_eq!(result.warnings, vec!["Input 'token' has no description"]);
+
+        let docker = validate("name: d\ndescription: d\nruns:\n  using: docker\n");
+        assert_eq!(docker.issues, vec!["Docker action is missing 'runs.image'"]);
+        let unknown = validate("name: d\ndescription: d\nruns:\n  using: python\n");
+        assert!(unknown.issues[0].starts_with("Unknown 'runs.using' value 'python'"));
+    }
+}
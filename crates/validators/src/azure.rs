@@ -0,0 +1,578 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use wrkflw_models::azure::{is_template_expression, DependsOn, Job, Pipeline, Stage, Step};
+use wrkflw_models::ValidationResult;
+
+/// Top-level keys besides those of `Pipeline`; a pipeline of jobs or steps may
+/// also carry the keys of its single implicit job
+const PIPELINE_KEYS: &[&str] = &[
+    "appendCommitMessageToRunName",
+    "lockBehavior",
+    "container",
+    "services",
+    "workspace",
+    "strategy",
+    "timeoutInMinutes",
+    "cancelTimeoutInMinutes",
+    "continueOnError",
+];
+
+const STAGE_KEYS: &[&str] = &[
+    "variables",
+    "pool",
+    "lockBehavior",
+    "templateContext",
+    "trigger",
+    "isSkippable",
+];
+
+const JOB_KEYS: &[&str] = &[
+    "pool",
+    "container",
+    "services",
+    "workspace",
+    "timeoutInMinutes",
+    "cancelTimeoutInMinutes",
+    "continueOnError",
+    "variables",
+    "uses",
+    "templateContext",
+];
+
+/// Keys other than the step kind: the common ones, then those of particular kinds
+const STEP_KEYS: &[&str] = &[
+    "continueOnError",
+    "enabled",
+    "env",
+    "timeoutInMinutes",
+    "retryCountOnTaskFailure",
+    "target",
+    "workingDirectory",
+    "failOnStderr",
+    "errorActionPreference",
+    "ignoreLASTEXITCODE",
+    "warningPreference",
+    "parameters",
+    "clean",
+    "fetchDepth",
+    "fetchFilter",
+    "fetchTags",
+    "lfs",
+    "persistCredentials",
+    "submodules",
+    "path",
+    "sparseCheckoutDirectories",
+    "sparseCheckoutPatterns",
+    "workspaceRepo",
+    "artifact",
+    "patterns",
+];
+
+/// Step kinds that have no field of their own in `Step`
+const OTHER_STEP_KINDS: &[&str] = &["downloadBuild", "getPackage", "reviewApp"];
+
+/// Validate an Azure Pipelines configuration
+pub fn validate_azure_pipeline(pipeline: &Pipeline) -> ValidationResult {
+    let mut result = ValidationResult::new();
+    result.set_rule("azure");
+
+    unknown_keys("Pipeline", &pipeline.other, PIPELINE_KEYS, &mut result);
+
+    let levels: Vec<&str> = [
+        ("stages", pipeline.stages.is_some()),
+        ("jobs", pipeline.jobs.is_some()),
+        ("steps", pipeline.steps.is_some()),
+    ]
+    .into_iter()
+    .filter_map(|(level, present)| present.then_some(level))
+    .collect();
+    if levels.len() > 1 {
+        result.add_issue(format!(
+            "Pipeline can only define one of stages, jobs and steps, but defines {}",
+            levels.join(" and ")
+        ));
+    } else if levels.is_empty() && pipeline.extends.is_none() {
+        result.add_issue(
+            "Pipeline must define stages, jobs or steps, or extend a template".to_string(),
+        );
+    }
+
+    if let Some(stages) = &pipeline.stages {
+        validate_stages(stages, &mut result);
+    }
+    if let Some(jobs) = &pipeline.jobs {
+        validate_jobs(jobs, None, &mut result);
+    }
+    if let Some(steps) = &pipeline.steps {
+        validate_steps(steps, "the pipeline", &mut result);
+    }
+
+    result
+}
+
+fn validate_stages(stages: &[Stage], result: &mut ValidationResult) {
+    if stages.is_empty() {
+        result.add_issue("Pipeline must contain at least one stage".to_string());
+    }
+
+    let mut dependencies = Vec::new();
+    let mut has_templates = false;
+    for (index, stage) in stages.iter().enumerate() {
+        if stage.is_expression() {
+            continue;
+        }
+        let name = match (&stage.stage, &stage.template) {
+            (Some(_), Some(_)) => {
+                result.add_issue(format!(
+                    "Stage {} can't both be a stage and reference a template",
+                    index + 1
+                ));
+                continue;
+            }
+            (None, Some(template)) => {
+                has_templates = true;
+                template_item(
+                    &format!("Stage {}", index + 1),
+                    template,
+                    &stage.other,
+                    result,
+                );
+                continue;
+            }
+            (None, None) => {
+                result.add_issue(format!(
+                    "Stage {} must have a 'stage' name or reference a template",
+                    index + 1
+                ));
+                continue;
+            }
+            (Some(name), None) => name,
+        };
+
+        let label = format!("Stage '{}'", name);
+        check_name(&label, name, result);
+        unknown_keys(&label, &stage.other, STAGE_KEYS, result);
+        match &stage.jobs {
+            Some(jobs) if !jobs.is_empty() => validate_jobs(jobs, Some(name), result),
+            _ => result.add_issue(format!("{} must contain at least one job", label)),
+        }
+        dependencies.push((name.as_str(), stage.depends_on.as_ref()));
+    }
+
+    validate_dependencies("Stage", &dependencies, has_templates, "", result);
+}
+
+fn validate_jobs(jobs: &[Job], stage: Option<&str>, result: &mut ValidationResult) {
+    let scope = stage
+        .map(|stage| format!(" in stage '{}'", stage))
+        .unwrap_or_default();
+    if jobs.is_empty() {
+        result.add_issue(format!("There must be at least one job{}", scope));
+    }
+
+    let mut dependencies = Vec::new();
+    let mut has_templates = false;
+    for (index, job) in jobs.iter().enumerate() {
+        if job.is_expression() {
+            continue;
+        }
+        let kinds = [&job.job, &job.deployment, &job.template]
+            .iter()
+            .filter(|kind| kind.is_some())
+            .count();
+        if kinds != 1 {
+            result.add_issue(format!(
+                "Job {}{} must have exactly one of 'job', 'deployment' and 'template'",
+                index + 1,
+                scope
+            ));
+            continue;
+        }
+        if let Some(template) = &job.template {
+            has_templates = true;
+            template_item(
+                &format!("Job {}{}", index + 1, scope),
+                template,
+                &job.other,
+                result,
+            );
+            continue;
+        }
+
+        let name = job.name().unwrap_or_default();
+        let label = if job.deployment.is_some() {
+            format!("Deployment job '{}'{}", name, scope)
+        } else {
+            format!("Job '{}'{}", name, scope)
+        };
+        check_name(&label, name, result);
+        unknown_keys(&label, &job.other, JOB_KEYS, result);
+
+        if job.deployment.is_some() {
+            if job.steps.is_some() {
+                result.add_issue(format!(
+                    "{} can't have steps; they belong in its strategy",
+                    label
+                ));
+            }
+            let strategy = job
+                .strategy
+                .as_ref()
+                .and_then(|strategy| strategy.as_mapping());
+            let known_strategy = strategy.is_some_and(|strategy| {
+                ["runOnce", "rolling", "canary"]
+                    .iter()
+                    .any(|kind| strategy.contains_key(*kind))
+            });
+            if !known_strategy {
+                result.add_issue(format!(
+                    "{} must have a runOnce, rolling or canary strategy",
+                    label
+                ));
+            }
+        } else {
+            match &job.steps {
+                Some(steps) if !steps.is_empty() => {
+                    validate_steps(steps, &format!("job '{}'", name), result)
+                }
+                _ => result.add_issue(format!("{} must contain at least one step", label)),
+            }
+        }
+        dependencies.push((name, job.depends_on.as_ref()));
+    }
+
+    validate_dependencies("Job", &dependencies, has_templates, &scope, result);
+}
+
+fn validate_steps(steps: &[Step], owner: &str, result: &mut ValidationResult) {
+    let mut names = HashSet::new();
+    for (index, step) in steps.iter().enumerate() {
+        if step.is_expression() {
+            continue;
+        }
+        let label = format!("Step {} of {}", index + 1, owner);
+
+        let mut kinds: Vec<&str> = [
+            ("script", &step.script),
+            ("bash", &step.bash),
+            ("pwsh", &step.pwsh),
+            ("powershell", &step.powershell),
+            ("task", &step.task),
+            ("checkout", &step.checkout),
+            ("download", &step.download),
+            ("publish", &step.publish),
+            ("template", &step.template),
+        ]
+        .into_iter()
+        .filter_map(|(kind, value)| value.is_some().then_some(kind))
+        .collect();
+        kinds.extend(
+            OTHER_STEP_KINDS
+                .iter()
+                .filter(|kind| step.other.contains_key(**kind)),
+        );
+        match kinds.len() {
+            0 => result.add_issue(format!(
+                "{} must be one of script, bash, pwsh, powershell, task, checkout, download, publish or template",
+                label
+            )),
+            1 => {}
+            _ => result.add_issue(format!(
+                "{} can only be one kind of step, but is {}",
+                label,
+                kinds.join(" and ")
+            )),
+        }
+
+        if let Some(task) = &step.task {
+            let versioned = task.rsplit_once('@').is_some_and(|(name, version)| {
+                !name.is_empty()
+                    && !version.is_empty()
+                    && version.chars().all(|c| c.is_ascii_digit())
+            });
+            if !versioned && !task.contains("$(") {
+                result.add_issue(format!(
+                    "{} uses task '{}' without a major version, e.g. '{}@1'",
+                    label, task, task
+                ));
+            }
+        }
+        if let Some(name) = &step.name {
+            check_name(&label, name, result);
+            if !names.insert(name.as_str()) {
+                result.add_issue(format!(
+                    "{} has the name '{}' of an earlier step of {}",
+                    label, name, owner
+                ));
+            }
+        }
+
+        let unknown: BTreeMap<_, _> = step
+            .other
+            .iter()
+            .filter(|(key, _)| !OTHER_STEP_KINDS.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        unknown_keys(&label, &unknown, STEP_KEYS, result);
+    }
+}
+
+/// A stage or job that's a template reference can only pass parameters
+fn template_item(
+    label: &str,
+    template: &str,
+    other: &BTreeMap<String, serde_yaml::Value>,
+    result: &mut ValidationResult,
+) {
+    if template.trim().is_empty() {
+        result.add_issue(format!("{} references an empty template path", label));
+    }
+    unknown_keys(label, other, &["parameters"], result);
+}
+
+/// Check `dependsOn` references within one scope and look for cycles. Names may
+/// come from templates, so references are only checked when there are none.
+fn validate_dependencies(
+    kind: &str,
+    items: &[(&str, Option<&DependsOn>)],
+    has_templates: bool,
+    scope: &str,
+    result: &mut ValidationResult,
+) {
+    let mut seen = HashSet::new();
+    for (name, _) in items {
+        if !seen.insert(*name) {
+            result.add_issue(format!(
+                "{} name '{}'{} is used more than once",
+                kind, name, scope
+            ));
+        }
+    }
+
+    let graph: HashMap<&str, Vec<&str>> = items
+        .iter()
+        .map(|(name, depends_on)| {
+            (
+                *name,
+                depends_on
+                    .map(|depends_on| depends_on.names())
+                    .unwrap_or_default(),
+            )
+        })
+        .collect();
+    for (name, depends_on) in items {
+        for dependency in depends_on.map(|d| d.names()).unwrap_or_default() {
+            if dependency == *name {
+                result.add_issue(format!("{} '{}'{} depends on itself", kind, name, scope));
+            } else if !has_templates
+                && !graph.contains_key(dependency)
+                && !dependency.contains("${{")
+            {
+                result.add_issue(format!(
+                    "{} '{}'{} depends on non-existent {} '{}'",
+                    kind,
+                    name,
+                    scope,
+                    kind.to_lowercase(),
+                    dependency
+                ));
+            }
+        }
+    }
+
+    let mut finished = HashSet::new();
+    for (name, _) in items {
+        let mut path = Vec::new();
+        if let Some(cycle) = find_cycle(name, &graph, &mut path, &mut finished) {
+            result.add_issue(format!(
+                "{} dependencies{} form a cycle: {}",
+                kind,
+                scope,
+                cycle.join(" -> ")
+            ));
+            return;
+        }
+    }
+}
+
+fn find_cycle<'a>(
+    name: &'a str,
+    graph: &HashMap<&'a str, Vec<&'a str>>,
+    path: &mut Vec<&'a str>,
+    finished: &mut HashSet<&'a str>,
+) -> Option<Vec<&'a str>> {
+    if let Some(start) = path.iter().position(|step| *step == name) {
+        let mut cycle = path[start..].to_vec();
+        cycle.push(name);
+        // A stage depending on itself is reported on its own
+        return (cycle.len() > 2).then_some(cycle);
+    }
+    if finished.contains(name) {
+        return None;
+    }
+    path.push(name);
+    for dependency in graph.get(name).into_iter().flatten() {
+        if graph.contains_key(dependency) {
+            if let Some(cycle) = find_cycle(dependency, graph, path, finished) {
+                return Some(cycle);
+            }
+        }
+    }
+    path.pop();
+    finished.insert(name);
+    None
+}
+
+/// Stage, job and step names may only contain letters, digits and underscores,
+/// and may not start with a digit
+fn check_name(label: &str, name: &str, result: &mut ValidationResult) {
+    if name.contains("${{") || name.contains("$(") {
+        return;
+    }
+    let valid = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        result.add_issue(format!(
+            "{} has invalid name '{}'; use only letters, digits and underscores, not starting with a digit",
+            label, name
+        ));
+    }
+}
+
+fn unknown_keys(
+    label: &str,
+    other: &BTreeMap<String, serde_yaml::Value>,
+    allowed: &[&str],
+    result: &mut ValidationResult,
+) {
+    for key in other.keys() {
+        if !allowed.contains(&key.as_str()) && !is_template_expression(key) {
+            result.add_issue(format!("{} has unknown key '{}'", label, key));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issues(yaml: &str) -> Vec<String> {
+        let pipeline: Pipeline = serde_yaml::from_str(yaml).unwrap();
+        validate_azure_pipeline(&pipeline).issues
+    }
+
+    #[test]
+    fn test_valid_pipeline() {
+        let yaml = r#"
+trigger: [main]
+pool:
+  vmImage: ubuntu-latest
+stages:
+  - stage: Build
+    jobs:
+      - job: Compile
+        steps:
+          - checkout: self
+            fetchDepth: 1
+          - script: cargo build
+            displayName: Build
+            name: build
+          - task: PublishPipelineArtifact@1
+            inputs:
+              targetPath: target
+      - template: jobs/lint.yml
+        parameters:
+          strict: true
+  - stage: Deploy
+    dependsOn: [Build]
+    condition: succeeded()
+    jobs:
+      - deployment: Production
+        environment: production
+        strategy:
+          runOnce:
+            deploy:
+              steps:
+                - script: ./deploy.sh
+  - ${{ if eq(variables['Build.SourceBranch'], 'refs/heads/main') }}:
+    - stage: Release
+      jobs:
+        - job: Tag
+          steps:
+            - bash: git tag v1
+"#;
+        assert_eq!(issues(yaml), Vec::<String>::new());
+        assert!(issues("steps:\n  - script: echo hi\n").is_empty());
+        assert!(issues("extends:\n  template: pipeline.yml\n").is_empty());
+    }
+
+    #[test]
+    fn test_structure_issues() {
+        assert_eq!(
+            issues("trigger: none\n"),
+            vec!["Pipeline must define stages, jobs or steps, or extend a template"]
+        );
+        assert_eq!(
+            issues("jobs:\n  - job: A\n    steps: [{script: a}]\nsteps:\n  - script: b\n"),
+            vec!["Pipeline can only define one of stages, jobs and steps, but defines jobs and steps"]
+        );
+
+        let yaml = r#"
+stages:
+  - stage: Build
+    jobs:
+      - job: Compile
+        dependsOn: Lint
+        steps:
+          - task: DotNetCoreCLI
+          - script: a
+            bash: b
+          - scirpt: typo
+      - job: Compile
+        steps: []
+      - deployment: Ship
+        steps:
+          - script: ./ship.sh
+  - stage: Test-Stage
+    dependsOn: Build
+"#;
+        assert_eq!(
+            issues(yaml),
+            vec![
+                "Step 1 of job 'Compile' uses task 'DotNetCoreCLI' without a major version, e.g. 'DotNetCoreCLI@1'",
+                "Step 2 of job 'Compile' can only be one kind of step, but is script and bash",
+                "Step 3 of job 'Compile' must be one of script, bash, pwsh, powershell, task, checkout, download, publish or template",
+                "Step 3 of job 'Compile' has unknown key 'scirpt'",
+                "Job 'Compile' in stage 'Build' must contain at least one step",
+                "Deployment job 'Ship' in stage 'Build' can't have steps; they belong in its strategy",
+                "Deployment job 'Ship' in stage 'Build' must have a runOnce, rolling or canary strategy",
+                "Job name 'Compile' in stage 'Build' is used more than once",
+                "Job 'Compile' in stage 'Build' depends on non-existent job 'Lint'",
+                "Stage 'Test-Stage' has invalid name 'Test-Stage'; use only letters, digits and underscores, not starting with a digit",
+                "Stage 'Test-Stage' must contain at least one job",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dependency_cycle() {
+        let yaml = r#"
+jobs:
+  - job: A
+    dependsOn: C
+    steps: [{script: a}]
+  - job: B
+    dependsOn: A
+    steps: [{script: b}]
+  - job: C
+    dependsOn: B
+    steps: [{script: c}]
+"#;
+        assert_eq!(
+            issues(yaml),
+            vec!["Job dependencies form a cycle: A -> C -> B -> A"]
+        );
+    }
+}
@@ -0,0 +1,469 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_yaml::Value;
+use std::collections::HashSet;
+use wrkflw_models::ValidationResult;
+use wrkflw_parser::expression::{self, BinaryOp, Expr};
+
+lazy_static! {
+    /// `secrets.provider:NAME`, which wrkflw substitutes before expressions are evaluated
+    static ref PROVIDER_SECRET: Regex = Regex::new(r"^\s*secrets\.[\w-]+:\S+\s*$").unwrap();
+}
+
+/// Contexts expressions can reference
+const CONTEXTS: &[&str] = &[
+    "github", "env", "vars", "job", "jobs", "steps", "runner", "secrets", "strategy", "matrix",
+    "needs", "inputs",
+];
+
+/// Functions expressions can call: name, minimum and maximum number of arguments,
+/// and what they return
+const FUNCTIONS: &[(&str, usize, usize, Type)] = &[
+    ("contains", 2, 2, Type::Bool),
+    ("startsWith", 2, 2, Type::Bool),
+    ("endsWith", 2, 2, Type::Bool),
+    ("format", 1, usize::MAX, Type::String),
+    ("join", 1, 2, Type::String),
+    ("toJSON", 1, 1, Type::String),
+    ("fromJSON", 1, 1, Type::Any),
+    ("hashFiles", 1, usize::MAX, Type::String),
+    ("success", 0, 0, Type::Bool),
+    ("always", 0, 0, Type::Bool),
+    ("cancelled", 0, 0, Type::Bool),
+    ("failure", 0, 0, Type::Bool),
+];
+
+/// What an expression evaluates to, as far as can be told without running it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Type {
+    Null,
+    Bool,
+    Number,
+    String,
+    Object,
+    Array,
+    Any,
+}
+
+impl Type {
+    fn name(self) -> &'static str {
+        match self {
+            Type::Null => "null",
+            Type::Bool => "a boolean",
+            Type::Number => "a number",
+            Type::String => "a string",
+            Type::Object => "an object",
+            Type::Array => "an array",
+            Type::Any => "anything",
+        }
+    }
+
+    fn is_primitive(self) -> bool {
+        matches!(self, Type::Null | Type::Bool | Type::Number | Type::String)
+    }
+
+    fn is_structured(self) -> bool {
+        matches!(self, Type::Object | Type::Array)
+    }
+}
+
+/// What a job's expressions can refer to
+struct Scope<'a> {
+    context: String,
+    /// The jobs in `needs`, or `None` outside of jobs
+    needs: Option<Vec<&'a str>>,
+    /// The keys of the job's matrix, `None` when they can't be known statically
+    matrix: Option<HashSet<&'a str>>,
+}
+
+/// Parse every `${{ }}` expression and `if:` condition in a workflow, reporting
+/// syntax errors, unknown functions and contexts, references to jobs not in
+/// `needs` and to matrix keys the job doesn't define, and comparisons of objects
+/// with plain values, which are never equal
+pub fn validate_expressions(workflow: &Value, result: &mut ValidationResult) {
+    let Value::Mapping(map) = workflow else {
+        return;
+    };
+    let top_level = Scope {
+        context: "Workflow".to_string(),
+        needs: None,
+        matrix: None,
+    };
+    for (key, value) in map {
+        if key.as_str() != Some("jobs") {
+            check_value(value, false, &top_level, result);
+        }
+    }
+
+    let Some(Value::Mapping(jobs)) = workflow.get("jobs") else {
+        return;
+    };
+    for (job_name, job) in jobs {
+        let (Some(job_name), Value::Mapping(job_map)) = (job_name.as_str(), job) else {
+            continue;
+        };
+        let needs = match job.get("needs") {
+            Some(Value::String(need)) => vec![need.as_str()],
+            Some(Value::Sequence(needs)) => needs.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        let mut scope = Scope {
+            context: format!("Job '{}'", job_name),
+            needs: Some(needs),
+            matrix: matrix_keys(job),
+        };
+
+        for (key, value) in job_map {
+            match key.as_str() {
+                Some("steps") => {}
+                Some("if") => check_value(value, true, &scope, result),
+                _ => check_value(value, false, &scope, result),
+            }
+        }
+
+        let Some(Value::Sequence(steps)) = job.get("steps") else {
+            continue;
+        };
+        let job_context = std::mem::take(&mut scope.context);
+        for (i, step) in steps.iter().enumerate() {
+            let Value::Mapping(step_map) = step else {
+                continue;
+            };
+            scope.context = format!("{}, step {}", job_context, i + 1);
+            for (key, value) in step_map {
+                check_value(value, key.as_str() == Some("if"), &scope, result);
+            }
+        }
+    }
+}
+
+/// The keys `matrix.<key>` can use in `job`: an empty set without a matrix, and
+/// `None` for matrices built by expressions
+fn matrix_keys(job: &Value) -> Option<HashSet<&str>> {
+    let matrix = job
+        .get("strategy")
+        .and_then(|strategy| strategy.get("matrix"))
+        .or_else(|| job.get("matrix"));
+    let Some(matrix) = matrix else {
+        return Some(HashSet::new());
+    };
+    let Value::Mapping(matrix) = matrix else {
+        return None;
+    };
+    let mut keys = HashSet::new();
+    for (key, value) in matrix {
+        match key.as_str() {
+            Some("exclude") => {}
+            Some("include") => match value {
+                Value::Sequence(entries) => {
+                    for entry in entries {
+                        match entry {
+                            Value::Mapping(entry) => {
+                                keys.extend(entry.keys().filter_map(Value::as_str))
+                            }
+                            _ => return None,
+                        }
+                    }
+                }
+                _ => return None,
+            },
+            Some(key) => {
+                keys.insert(key);
+            }
+            None => {}
+        }
+    }
+    Some(keys)
+}
+
+/// Check the expressions in `value`; `condition` for `if:` values, which are
+/// expressions even without `${{ }}`
+fn check_value(value: &Value, condition: bool, scope: &Scope, result: &mut ValidationResult) {
+    match value {
+        Value::String(text) => {
+            if condition && !text.contains("${{") {
+                check_expression(text, scope, result);
+                return;
+            }
+            match expression::extract(text) {
+                Ok(expressions) => {
+                    for expression in expressions {
+                        check_expression(expression, scope, result);
+                    }
+                }
+                Err(e) => result.add_issue(format!("{}: {}", scope.context, e)),
+            }
+        }
+        Value::Mapping(map) => {
+            for value in map.values() {
+                check_value(value, false, scope, result);
+            }
+        }
+        Value::Sequence(values) => {
+            for value in values {
+                check_value(value, false, scope, result);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_expression(text: &str, scope: &Scope, result: &mut ValidationResult) {
+    if PROVIDER_SECRET.is_match(text) {
+        return;
+    }
+    let expr = match expression::parse(text) {
+        Ok(expr) => expr,
+        Err(e) => {
+            result.add_issue(format!("{}: Invalid expression: {}", scope.context, e));
+            return;
+        }
+    };
+    let mut issues = Vec::new();
+    expr.walk(&mut |node| match node {
+        Expr::Context(name)
+            if !CONTEXTS
+                .iter()
+                .any(|context| context.eq_ignore_ascii_case(name)) =>
+        {
+            issues.push(format!("Unrecognized named-value '{}'", name));
+        }
+        Expr::Call(name, args) => match function(name) {
+            None => issues.push(format!("Unrecognized function '{}'", name)),
+            Some((name, min, max, _)) if args.len() < min || args.len() > max => {
+                let expected = match (min, max) {
+                    (min, max) if min == max => format!("{}", min),
+                    (min, usize::MAX) => format!("at least {}", min),
+                    (min, max) => format!("{} to {}", min, max),
+                };
+                issues.push(format!(
+                    "{}() takes {} argument(s) but is given {}",
+                    name,
+                    expected,
+                    args.len()
+                ));
+            }
+            Some(_) => {}
+        },
+        Expr::Binary(left, op, right) if op.is_comparison() => {
+            let (left_type, right_type) = (type_of(left), type_of(right));
+            let mismatch = (left_type.is_structured() && right_type.is_primitive())
+                || (left_type.is_primitive() && right_type.is_structured());
+            if mismatch {
+                issues.push(format!(
+                    "'{} {} {}' compares {} with {}, which is never {}",
+                    left,
+                    match op {
+                        BinaryOp::Equal => "==",
+                        BinaryOp::NotEqual => "!=",
+                        BinaryOp::Less => "<",
+                        BinaryOp::LessOrEqual => "<=",
+                        BinaryOp::Greater => ">",
+                        _ => ">=",
+                    },
+                    right,
+                    left_type.name(),
+                    right_type.name(),
+                    if *op == BinaryOp::NotEqual {
+                        "false"
+                    } else {
+                        "true"
+                    }
+                ));
+            }
+        }
+        _ => {}
+    });
+
+    // References are checked once, at their outermost property access
+    let mut references = Vec::new();
+    collect_references(&expr, &mut references);
+    for path in references {
+        let context = path[0].to_lowercase();
+        match (context.as_str(), path.get(1)) {
+            ("needs", Some(&job)) if job != "*" => {
+                if let Some(needs) = &scope.needs {
+                    if !needs.iter().any(|need| need.eq_ignore_ascii_case(job)) {
+                        issues.push(format!(
+                            "needs.{} refers to a job that isn't in this job's 'needs'",
+                            job
+                        ));
+                    }
+                }
+            }
+            ("matrix", Some(&key)) if key != "*" => match &scope.matrix {
+                Some(keys) if keys.is_empty() && scope.needs.is_some() => {
+                    issues.push(format!("matrix.{} is used but the job has no matrix", key))
+                }
+                Some(keys) if !keys.is_empty() && !keys.contains(key) => {
+                    let mut defined: Vec<&str> = keys.iter().copied().collect();
+                    defined.sort();
+                    issues.push(format!(
+                        "matrix.{} is not defined in the job's matrix (which has {})",
+                        key,
+                        defined.join(", ")
+                    ))
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    for issue in issues {
+        result.add_issue(format!("{}: {}", scope.context, issue));
+    }
+}
+
+/// The name, arity and return type of a function, matched case-insensitively
+fn function(name: &str) -> Option<(&'static str, usize, usize, Type)> {
+    FUNCTIONS
+        .iter()
+        .find(|(function, ..)| function.eq_ignore_ascii_case(name))
+        .copied()
+}
+
+/// The outermost context references in `expr`
+fn collect_references<'a>(expr: &'a Expr, references: &mut Vec<Vec<&'a str>>) {
+    if let Some(path) = expr.reference() {
+        references.push(path);
+        return;
+    }
+    match expr {
+        Expr::Property(object, _) | Expr::Wildcard(object) | Expr::Not(object) => {
+            collect_references(object, references)
+        }
+        Expr::Index(object, index) => {
+            collect_references(object, references);
+            collect_references(index, references);
+        }
+        Expr::Call(_, args) => args
+            .iter()
+            .for_each(|arg| collect_references(arg, references)),
+        Expr::Binary(left, _, right) => {
+            collect_references(left, references);
+            collect_references(right, references);
+        }
+        _ => {}
+    }
+}
+
+fn type_of(expr: &Expr) -> Type {
+    match expr {
+        Expr::Null => Type::Null,
+        Expr::Bool(_) => Type::Bool,
+        Expr::Number(_) => Type::Number,
+        Expr::String(_) => Type::String,
+        Expr::Not(_) => Type::Bool,
+        Expr::Binary(_, op, _) if op.is_comparison() => Type::Bool,
+        Expr::Binary(..) | Expr::Index(..) => Type::Any,
+        Expr::Call(name, _) => function(name).map_or(Type::Any, |(.., returns)| returns),
+        Expr::Wildcard(_) => Type::Array,
+        Expr::Context(_) | Expr::Property(..) => match expr.reference() {
+            Some(path) => reference_type(&path),
+            None => Type::Any,
+        },
+    }
+}
+
+/// The type of a context reference, for the contexts whose shape is known
+fn reference_type(path: &[&str]) -> Type {
+    if path.contains(&"*") {
+        return Type::Array;
+    }
+    let path: Vec<String> = path.iter().map(|part| part.to_lowercase()).collect();
+    let path: Vec<&str> = path.iter().map(String::as_str).collect();
+    match path.as_slice() {
+        [_] => Type::Object,
+        ["github", "event"] => Type::Object,
+        ["env" | "vars" | "secrets" | "runner", _] => Type::String,
+        ["needs" | "steps", _] | ["needs" | "steps", _, "outputs"] => Type::Object,
+        ["needs", _, "result"] | ["steps", _, "outcome" | "conclusion"] => Type::String,
+        ["needs" | "steps", _, "outputs", _] => Type::String,
+        _ => Type::Any,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issues(workflow: &str) -> Vec<String> {
+        let workflow: Value = serde_yaml::from_str(workflow).unwrap();
+        let mut result = ValidationResult::new();
+        validate_expressions(&workflow, &mut result);
+        result.issues
+    }
+
+    #[test]
+    fn test_valid_expressions() {
+        let found = issues(
+            r#"
+on: push
+env:
+  REF: ${{ github.ref }}
+jobs:
+  build:
+    runs-on: ${{ matrix.os }}
+    strategy:
+      matrix:
+        os: [ubuntu-latest, windows-latest]
+        include:
+          - os: ubuntu-latest
+            experimental: true
+    steps:
+      - id: version
+        run: echo "v=${{ format('{0}-{1}', matrix.os, hashFiles('**/Cargo.lock')) }}"
+      - if: matrix.experimental && steps.version.outputs.v != ''
+        run: echo ${{ toJSON(github.event) }} ${{ secrets.vault:api-key }}
+  deploy:
+    needs: [build]
+    if: ${{ always() && !contains(needs.*.result, 'failure') && needs.build.result == 'success' }}
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo ${{ fromJSON(needs.build.outputs.list)[0] }}
+"#,
+        );
+        assert!(found.is_empty(), "{:?}", found);
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        let found = issues(
+            r#"
+on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    strategy:
+      matrix:
+        os: [ubuntu-latest]
+    steps:
+      - run: echo ${{ matrix.arch }}
+      - if: github.event == 'push'
+        run: echo ${{ github.ref ==  }}
+      - run: echo "${{ toJson(github) }} ${{ contain(github.ref, 'main') }} ${{ startsWith('a') }}"
+      - run: echo ${{ foo.bar }} ${{ "double" }}
+  deploy:
+    needs: build
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo ${{ needs.test.outputs.x }} ${{ matrix.os }}
+"#,
+        );
+        assert_eq!(
+            found,
+            vec![
+                "Job 'build', step 1: matrix.arch is not defined in the job's matrix (which has os)",
+                "Job 'build', step 2: 'github.event == 'push'' compares an object with a string, which is never true",
+                "Job 'build', step 2: Invalid expression: Unexpected end of expression",
+                "Job 'build', step 3: Unrecognized function 'contain'",
+                "Job 'build', step 3: startsWith() takes 2 argument(s) but is given 1",
+                "Job 'build', step 4: Unrecognized named-value 'foo'",
+                "Job 'build', step 4: Invalid expression: Strings in expressions take single quotes, not double quotes: '\"double\"'",
+                "Job 'deploy', step 1: needs.test refers to a job that isn't in this job's 'needs'",
+                "Job 'deploy', step 1: matrix.os is used but the job has no matrix",
+            ]
+        );
+    }
+}
@@ -4,10 +4,22 @@ use wrkflw_models::ValidationResult;
 
 /// Validate a GitLab CI/CD pipeline
 pub fn validate_gitlab_pipeline(pipeline: &Pipeline) -> ValidationResult {
+    validate(pipeline, true)
+}
+
+/// Validate a fragment of a GitLab CI/CD pipeline, a file the pipeline includes:
+/// the jobs it depends on and extends may be defined in other files
+pub fn validate_gitlab_fragment(pipeline: &Pipeline) -> ValidationResult {
+    validate(pipeline, false)
+}
+
+/// `complete` when `pipeline` has every job, so references can be resolved
+fn validate(pipeline: &Pipeline, complete: bool) -> ValidationResult {
     let mut result = ValidationResult::new();
+    result.set_rule("gitlab");
 
     // Basic structure validation
-    if pipeline.jobs.is_empty() {
+    if complete && pipeline.jobs.is_empty() {
         result.add_issue("Pipeline must contain at least one job".to_string());
     }
 
@@ -20,29 +32,36 @@ pub fn validate_gitlab_pipeline(pipeline: &Pipeline) -> ValidationResult {
     }
 
     // Validate dependencies
-    validate_dependencies(&pipeline.jobs, &mut result);
+    validate_dependencies(&pipeline.jobs, complete, &mut result);
 
     // Validate extends
-    validate_extends(&pipeline.jobs, &mut result);
+    validate_extends(&pipeline.jobs, complete, &mut result);
 
     // Validate artifacts
     validate_artifacts(&pipeline.jobs, &mut result);
 
+    // Validate environments
+    validate_environments(&pipeline.jobs, complete, &mut result);
+
     result
 }
 
 /// Validate GitLab CI/CD jobs
 fn validate_jobs(jobs: &HashMap<String, Job>, result: &mut ValidationResult) {
     for (job_name, job) in jobs {
-        // Skip template jobs
+        // Skip template jobs, hidden ones included
         if let Some(true) = job.template {
             continue;
         }
 
-        // Check for script or extends
-        if job.script.is_none() && job.extends.is_none() {
+        // Check for script, extends or trigger
+        if job.script.is_none()
+            && job.extends.is_none()
+            && !job_name.starts_with('.')
+            && job.trigger.is_none()
+        {
             result.add_issue(format!(
-                "Job '{}' must have a script section or extend another job",
+                "Job '{}' must have a script section, extend another job or trigger a pipeline",
                 job_name
             ));
         }
@@ -122,11 +141,18 @@ fn validate_stages(stages: &[String], jobs: &HashMap<String, Job>, result: &mut
 }
 
 /// Validate GitLab CI/CD job dependencies
-fn validate_dependencies(jobs: &HashMap<String, Job>, result: &mut ValidationResult) {
+fn validate_dependencies(
+    jobs: &HashMap<String, Job>,
+    complete: bool,
+    result: &mut ValidationResult,
+) {
     for (job_name, job) in jobs {
         if let Some(dependencies) = &job.dependencies {
             for dependency in dependencies {
                 if !jobs.contains_key(dependency) {
+                    if !complete {
+                        continue;
+                    }
                     result.add_issue(format!(
                         "Job '{}' depends on undefined job '{}'",
                         job_name, dependency
@@ -139,14 +165,61 @@ fn validate_dependencies(jobs: &HashMap<String, Job>, result: &mut ValidationRes
     }
 }
 
+/// Validate the environments of GitLab CI/CD jobs
+fn validate_environments(
+    jobs: &HashMap<String, Job>,
+    complete: bool,
+    result: &mut ValidationResult,
+) {
+    for (job_name, job) in jobs {
+        let Some(environment) = &job.environment else {
+            continue;
+        };
+        if environment.name().trim().is_empty() {
+            result.add_issue(format!(
+                "Job '{}' has an environment without a name",
+                job_name
+            ));
+        }
+        match environment.action() {
+            "start" | "prepare" | "stop" | "verify" | "access" => {}
+            action => result.add_issue(format!(
+                "Job '{}' has invalid environment action: '{}'. Valid actions are: start, prepare, stop, verify, access",
+                job_name, action
+            )),
+        }
+        let Some(on_stop) = environment.on_stop() else {
+            continue;
+        };
+        match jobs.get(on_stop) {
+            None if complete => result.add_issue(format!(
+                "Job '{}' stops its environment with undefined job '{}'",
+                job_name, on_stop
+            )),
+            Some(stop_job)
+                if stop_job.environment.as_ref().map(|stop| stop.action()) != Some("stop") =>
+            {
+                result.add_issue(format!(
+                    "Job '{}' stops the environment of job '{}' but doesn't have `environment: action: stop`",
+                    on_stop, job_name
+                ))
+            }
+            _ => {}
+        }
+    }
+}
+
 /// Validate GitLab CI/CD job extends
-fn validate_extends(jobs: &HashMap<String, Job>, result: &mut ValidationResult) {
+fn validate_extends(jobs: &HashMap<String, Job>, complete: bool, result: &mut ValidationResult) {
     // Check for circular extends
     for (job_name, job) in jobs {
         if let Some(extends) = &job.extends {
             // Check that all extended jobs exist
             for extend in extends {
                 if !jobs.contains_key(extend) {
+                    if !complete {
+                        continue;
+                    }
                     result.add_issue(format!(
                         "Job '{}' extends undefined job '{}'",
                         job_name, extend
@@ -0,0 +1,618 @@
+use std::collections::HashMap;
+use wrkflw_models::jenkins::{Jenkinsfile, Node};
+use wrkflw_models::ValidationResult;
+
+const PIPELINE_SECTIONS: &[&str] = &[
+    "agent",
+    "environment",
+    "options",
+    "parameters",
+    "triggers",
+    "tools",
+    "libraries",
+    "stages",
+    "post",
+];
+
+const STAGE_SECTIONS: &[&str] = &[
+    "agent",
+    "environment",
+    "input",
+    "options",
+    "when",
+    "tools",
+    "failFast",
+    "steps",
+    "stages",
+    "parallel",
+    "matrix",
+    "post",
+];
+
+/// Sections of which a stage has exactly one
+const STAGE_CONTENTS: &[&str] = &["steps", "stages", "parallel", "matrix"];
+
+const AGENT_TYPES: &[&str] = &[
+    "any",
+    "none",
+    "label",
+    "node",
+    "docker",
+    "dockerfile",
+    "kubernetes",
+];
+
+const POST_CONDITIONS: &[&str] = &[
+    "always",
+    "changed",
+    "fixed",
+    "regression",
+    "aborted",
+    "failure",
+    "success",
+    "unstable",
+    "unsuccessful",
+    "cleanup",
+];
+
+const WHEN_CONDITIONS: &[&str] = &[
+    "branch",
+    "buildingTag",
+    "changelog",
+    "changeset",
+    "changeRequest",
+    "environment",
+    "equals",
+    "expression",
+    "tag",
+    "triggeredBy",
+    "not",
+    "allOf",
+    "anyOf",
+    "beforeAgent",
+    "beforeInput",
+    "beforeOptions",
+];
+
+/// Groovy that declarative pipelines only allow inside `script { }`
+const SCRIPT_ONLY: &[&str] = &[
+    "def", "if", "else", "for", "while", "try", "catch", "return",
+];
+
+/// Validate the structure of a declarative Jenkinsfile
+pub fn validate_jenkins_pipeline(file: &Jenkinsfile) -> ValidationResult {
+    let mut result = ValidationResult::new();
+    result.set_rule("jenkins");
+
+    let pipelines: Vec<&Node> = file
+        .nodes
+        .iter()
+        .filter(|node| node.name == "pipeline")
+        .collect();
+    let pipeline = match pipelines.as_slice() {
+        [] if file.nodes.iter().any(|node| node.name == "node") => {
+            result.add_issue(
+                "Scripted pipelines ('node' blocks) aren't supported; only declarative pipelines can be validated"
+                    .to_string(),
+            );
+            return result;
+        }
+        [] => {
+            result.add_issue("Jenkinsfile must contain a 'pipeline' block".to_string());
+            return result;
+        }
+        [pipeline, rest @ ..] => {
+            for extra in rest {
+                result.add_issue(format!(
+                    "Only one 'pipeline' block is allowed, another is {}",
+                    extra.position()
+                ));
+            }
+            *pipeline
+        }
+    };
+    let Some(sections) = &pipeline.body else {
+        result.add_issue(format!(
+            "'pipeline' {} must be a block",
+            pipeline.position()
+        ));
+        return result;
+    };
+
+    sections_of(
+        "Pipeline",
+        pipeline,
+        sections,
+        PIPELINE_SECTIONS,
+        &mut result,
+    );
+    if pipeline.child("agent").is_none() {
+        result.add_issue("Pipeline must have an 'agent' section".to_string());
+    }
+    let agent_none = pipeline.child("agent").is_some_and(is_agent_none);
+
+    let mut names = HashMap::new();
+    match pipeline.child("stages") {
+        Some(stages) => validate_stages(stages, agent_none, false, &mut names, &mut result),
+        None => result.add_issue("Pipeline must have a 'stages' section".to_string()),
+    }
+
+    for section in sections {
+        match section.name.as_str() {
+            "agent" => validate_agent(section, "Pipeline", &mut result),
+            "environment" => validate_environment(section, &mut result),
+            "post" => validate_post(section, "Pipeline", &mut result),
+            _ => {}
+        }
+    }
+
+    result
+}
+
+/// Check the sections of a block against those allowed, and that none repeats
+fn sections_of(
+    label: &str,
+    node: &Node,
+    sections: &[Node],
+    allowed: &[&str],
+    result: &mut ValidationResult,
+) {
+    let mut seen: Vec<&str> = Vec::new();
+    for section in sections {
+        if !allowed.contains(&section.name.as_str()) {
+            result.add_issue(format!(
+                "{} has unknown section '{}' {}",
+                label,
+                section.name,
+                section.position()
+            ));
+        } else if seen.contains(&section.name.as_str()) {
+            result.add_issue(format!(
+                "{} has more than one '{}' section, the second {}",
+                label,
+                section.name,
+                section.position()
+            ));
+        }
+        seen.push(&section.name);
+    }
+    if sections.is_empty() {
+        result.add_issue(format!("{} {} is empty", label, node.position()));
+    }
+}
+
+fn is_agent_none(agent: &Node) -> bool {
+    agent.args.first().map(String::as_str) == Some("none")
+}
+
+fn validate_agent(agent: &Node, owner: &str, result: &mut ValidationResult) {
+    match (&agent.body, agent.args.first()) {
+        (Some(types), _) => {
+            if types.len() != 1 {
+                result.add_issue(format!(
+                    "{} 'agent' {} must have exactly one agent type",
+                    owner,
+                    agent.position()
+                ));
+            }
+            for kind in types {
+                if !AGENT_TYPES.contains(&kind.name.as_str()) {
+                    result.add_issue(format!(
+                        "{} has unknown agent type '{}' {}",
+                        owner,
+                        kind.name,
+                        kind.position()
+                    ));
+                }
+            }
+        }
+        (None, Some(arg)) if arg == "any" || arg == "none" => {}
+        (None, Some(arg)) if arg.starts_with("label") => {}
+        (None, Some(arg)) => result.add_issue(format!(
+            "{} has invalid agent '{}' {}; use any, none, label or an agent block",
+            owner,
+            arg,
+            agent.position()
+        )),
+        (None, None) => result.add_issue(format!(
+            "{} 'agent' {} needs an agent type",
+            owner,
+            agent.position()
+        )),
+    }
+}
+
+fn validate_environment(environment: &Node, result: &mut ValidationResult) {
+    for variable in environment.body.iter().flatten() {
+        if !variable.assigned {
+            result.add_issue(format!(
+                "Environment entry '{}' {} must be an assignment, e.g. NAME = 'value'",
+                variable.name,
+                variable.position()
+            ));
+        } else if !variable
+            .name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+            || variable.name.starts_with(|c: char| c.is_ascii_digit())
+        {
+            result.add_issue(format!(
+                "Environment variable '{}' {} isn't a valid name",
+                variable.name,
+                variable.position()
+            ));
+        }
+    }
+}
+
+fn validate_post(post: &Node, owner: &str, result: &mut ValidationResult) {
+    let mut seen: Vec<&str> = Vec::new();
+    for condition in post.body.iter().flatten() {
+        if !POST_CONDITIONS.contains(&condition.name.as_str()) {
+            result.add_issue(format!(
+                "{} has unknown post condition '{}' {}",
+                owner,
+                condition.name,
+                condition.position()
+            ));
+        } else if seen.contains(&condition.name.as_str()) {
+            result.add_issue(format!(
+                "{} has more than one post condition '{}'",
+                owner, condition.name
+            ));
+        } else if condition.body.is_none() {
+            result.add_issue(format!(
+                "Post condition '{}' {} must be a block of steps",
+                condition.name,
+                condition.position()
+            ));
+        } else {
+            validate_steps(
+                condition,
+                &format!("Post condition '{}'", condition.name),
+                result,
+            );
+        }
+        seen.push(&condition.name);
+    }
+}
+
+fn validate_when(conditions: &[Node], stage: &str, result: &mut ValidationResult) {
+    for condition in conditions {
+        if !WHEN_CONDITIONS.contains(&condition.name.as_str()) {
+            result.add_issue(format!(
+                "Stage '{}' has unknown 'when' condition '{}' {}",
+                stage,
+                condition.name,
+                condition.position()
+            ));
+        } else if matches!(condition.name.as_str(), "not" | "allOf" | "anyOf") {
+            let nested = condition.body.as_deref().unwrap_or_default();
+            if nested.is_empty() || (condition.name == "not" && nested.len() != 1) {
+                result.add_issue(format!(
+                    "'{}' {} in stage '{}' needs {} condition",
+                    condition.name,
+                    condition.position(),
+                    stage,
+                    if condition.name == "not" {
+                        "exactly one"
+                    } else {
+                        "at least one"
+                    }
+                ));
+            }
+            validate_when(nested, stage, result);
+        }
+    }
+}
+
+/// Steps of a `steps` or post condition block: at least one, with Groovy
+/// statements only inside `script { }`
+fn validate_steps(block: &Node, label: &str, result: &mut ValidationResult) {
+    let steps = block.body.as_deref().unwrap_or_default();
+    if steps.is_empty() {
+        result.add_issue(format!(
+            "{} {} must contain at least one step",
+            label,
+            block.position()
+        ));
+    }
+    for step in steps {
+        if step.assigned || SCRIPT_ONLY.contains(&step.name.as_str()) {
+            result.add_issue(format!(
+                "'{}' {} must be inside a 'script' block",
+                step.name,
+                step.position()
+            ));
+        }
+    }
+}
+
+/// Validate a `stages` block; `agent_none` is whether no enclosing agent applies
+fn validate_stages(
+    stages: &Node,
+    agent_none: bool,
+    in_parallel: bool,
+    names: &mut HashMap<String, usize>,
+    result: &mut ValidationResult,
+) {
+    let children = stages.body.as_deref().unwrap_or_default();
+    if children.is_empty() {
+        result.add_issue(format!(
+            "'{}' {} must contain at least one stage",
+            stages.name,
+            stages.position()
+        ));
+    }
+    for child in children {
+        if child.name == "stage" {
+            validate_stage(child, agent_none, in_parallel, names, result);
+        } else {
+            result.add_issue(format!(
+                "'{}' {} may only contain stages, not '{}'",
+                stages.name,
+                child.position(),
+                child.name
+            ));
+        }
+    }
+}
+
+fn validate_stage(
+    stage: &Node,
+    agent_none: bool,
+    in_parallel: bool,
+    names: &mut HashMap<String, usize>,
+    result: &mut ValidationResult,
+) {
+    let Some(name) = stage.string_arg() else {
+        result.add_issue(format!(
+            "Stage {} must have a name, e.g. stage('Build')",
+            stage.position()
+        ));
+        return;
+    };
+    // Stage names are unique across the pipeline, nested ones included
+    if let Some(first) = names.insert(name.to_string(), stage.line) {
+        result.add_issue(format!(
+            "Duplicate stage name '{}' at line {} (first defined at line {})",
+            name, stage.line, first
+        ));
+        names.insert(name.to_string(), first);
+    }
+    let label = format!("Stage '{}'", name);
+    let Some(sections) = &stage.body else {
+        result.add_issue(format!("{} {} must be a block", label, stage.position()));
+        return;
+    };
+    sections_of(&label, stage, sections, STAGE_SECTIONS, result);
+
+    let contents: Vec<&Node> = sections
+        .iter()
+        .filter(|section| STAGE_CONTENTS.contains(&section.name.as_str()))
+        .collect();
+    match contents.as_slice() {
+        [] => result.add_issue(format!(
+            "{} must have one of steps, stages, parallel or matrix",
+            label
+        )),
+        [_] => {}
+        [..] => result.add_issue(format!(
+            "{} can only have one of steps, stages, parallel and matrix, but has {}",
+            label,
+            contents
+                .iter()
+                .map(|section| section.name.as_str())
+                .collect::<Vec<_>>()
+                .join(" and ")
+        )),
+    }
+
+    let agent = stage.child("agent");
+    let agent_none = match agent {
+        Some(agent) => is_agent_none(agent),
+        None => agent_none,
+    };
+    for section in sections {
+        match section.name.as_str() {
+            "agent" => validate_agent(section, &label, result),
+            "environment" => validate_environment(section, result),
+            "post" => validate_post(section, &label, result),
+            "when" => validate_when(section.body.as_deref().unwrap_or_default(), name, result),
+            "steps" => {
+                validate_steps(section, &format!("Steps of stage '{}'", name), result);
+                if agent_none {
+                    result.add_warning(format!(
+                        "{} has steps but no agent, as the pipeline's agent is none",
+                        label
+                    ));
+                }
+            }
+            "stages" => validate_stages(section, agent_none, false, names, result),
+            "parallel" | "matrix" if in_parallel => result.add_issue(format!(
+                "{} is run in parallel, so it can't have '{}' itself; nest it in 'stages'",
+                label, section.name
+            )),
+            "parallel" => validate_stages(section, agent_none, true, names, result),
+            "matrix" => validate_matrix(section, &label, agent_none, names, result),
+            _ => {}
+        }
+    }
+}
+
+fn validate_matrix(
+    matrix: &Node,
+    label: &str,
+    agent_none: bool,
+    names: &mut HashMap<String, usize>,
+    result: &mut ValidationResult,
+) {
+    let axes: Vec<&Node> = matrix
+        .child("axes")
+        .map(|axes| axes.children("axis").collect())
+        .unwrap_or_default();
+    if axes.is_empty() {
+        result.add_issue(format!("Matrix of {} must have at least one axis", label));
+    }
+    let mut axis_names: Vec<&str> = Vec::new();
+    for axis in axes {
+        match axis.child("name").and_then(Node::string_arg) {
+            Some(name) if axis_names.contains(&name) => result.add_issue(format!(
+                "Matrix of {} has more than one axis '{}'",
+                label, name
+            )),
+            Some(name) => axis_names.push(name),
+            None => result.add_issue(format!("Axis {} must have a name", axis.position())),
+        }
+        if axis
+            .child("values")
+            .is_none_or(|values| values.args.is_empty())
+        {
+            result.add_issue(format!("Axis {} must have values", axis.position()));
+        }
+    }
+    match matrix.child("stages") {
+        Some(stages) => validate_stages(stages, agent_none, true, names, result),
+        None => result.add_issue(format!("Matrix of {} must have 'stages'", label)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(jenkinsfile: &str) -> ValidationResult {
+        let file = wrkflw_parser::jenkins::parse_content(jenkinsfile).unwrap();
+        validate_jenkins_pipeline(&file)
+    }
+
+    #[test]
+    fn test_valid_pipeline() {
+        let result = validate(
+            r#"
+pipeline {
+    agent any
+    environment {
+        CARGO_TERM_COLOR = 'always'
+    }
+    stages {
+        stage('Build') {
+            steps {
+                sh 'cargo build'
+            }
+        }
+        stage('Checks') {
+            failFast true
+            parallel {
+                stage('Test') { steps { sh 'cargo test' } }
+                stage('Lint') {
+                    when { not { branch 'release/*' } }
+                    steps {
+                        script {
+                            def args = '--all-targets'
+                            sh "cargo clippy ${args}"
+                        }
+                    }
+                }
+            }
+        }
+        stage('Platforms') {
+            matrix {
+                axes {
+                    axis {
+                        name 'OS'
+                        values 'linux', 'windows'
+                    }
+                }
+                stages {
+                    stage('Package') { steps { echo "Packaging for ${OS}" } }
+                }
+            }
+        }
+    }
+    post {
+        always { junit 'target/*.xml' }
+        failure { mail to: 'team@example.com', subject: 'Build failed' }
+    }
+}
+"#,
+        );
+        assert!(result.is_valid, "{:?}", result.issues);
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_pipeline() {
+        let result = validate(
+            r#"
+pipeline {
+    agent none
+    environment {
+        FOO 'bar'
+    }
+    stages {
+        stage('Build') {
+            steps {
+                def out = sh(script: 'make', returnStdout: true)
+            }
+            stages {
+                stage('Inner') { steps { echo 'x' } }
+            }
+        }
+        stage('Build') {
+            agent { docker 'rust' }
+            step { sh 'make' }
+        }
+        stage {
+            steps { echo 'unnamed' }
+        }
+        stage('Checks') {
+            parallel {
+                stage('Nested') {
+                    parallel {
+                        stage('Deep') { steps { echo 'deep' } }
+                    }
+                }
+            }
+        }
+    }
+    post {
+        sometimes { echo 'maybe' }
+    }
+}
+"#,
+        );
+        assert_eq!(
+            result.issues,
+            vec![
+                "Stage 'Build' can only have one of steps, stages, parallel and matrix, but has steps and stages",
+                "'def' at line 10 column 17 must be inside a 'script' block",
+                "Duplicate stage name 'Build' at line 16 (first defined at line 8)",
+                "Stage 'Build' has unknown section 'step' at line 18 column 13",
+                "Stage 'Build' must have one of steps, stages, parallel or matrix",
+                "Stage at line 20 column 9 must have a name, e.g. stage('Build')",
+                "Stage 'Nested' is run in parallel, so it can't have 'parallel' itself; nest it in 'stages'",
+                "Environment entry 'FOO' at line 5 column 9 must be an assignment, e.g. NAME = 'value'",
+                "Pipeline has unknown post condition 'sometimes' at line 34 column 9",
+            ]
+        );
+        assert_eq!(
+            result.warnings,
+            vec![
+                "Stage 'Build' has steps but no agent, as the pipeline's agent is none",
+                "Stage 'Inner' has steps but no agent, as the pipeline's agent is none",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_not_declarative() {
+        let result = validate("node {\n    sh 'make'\n}\n");
+        assert_eq!(
+            result.issues,
+            vec!["Scripted pipelines ('node' blocks) aren't supported; only declarative pipelines can be validated"]
+        );
+        let result = validate("pipeline {\n    stages { stage('x') { steps { echo 'x' } } }\n}\n");
+        assert_eq!(result.issues, vec!["Pipeline must have an 'agent' section"]);
+    }
+}
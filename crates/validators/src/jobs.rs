@@ -33,7 +33,9 @@ pub fn validate_jobs(jobs: &Value, result: &mut ValidationResult) {
                                         job_name
                                     ));
                                 } else {
-                                    validate_steps(steps, job_name, result);
+                                    result.in_rule("steps", |result| {
+                                        validate_steps(steps, job_name, result)
+                                    });
                                 }
                             }
                             Some(_) => {
@@ -91,7 +93,7 @@ pub fn validate_jobs(jobs: &Value, result: &mut ValidationResult) {
 
                     // Validate matrix configuration if present
                     if let Some(matrix) = job_config.get(Value::String("matrix".to_string())) {
-                        validate_matrix(matrix, result);
+                        result.in_rule("matrix", |result| validate_matrix(matrix, result));
                     }
                 } else {
                     result.add_issue(format!("Job '{}' configuration is not a mapping", job_name));
@@ -1,15 +1,31 @@
 // validators crate
 
+mod action_metadata;
 mod actions;
+mod azure;
+mod expressions;
 mod gitlab;
+mod jenkins;
 mod jobs;
 mod matrix;
+mod permissions;
+mod secrets;
 mod steps;
+mod suppressions;
 mod triggers;
+mod yaml;
 
+pub use action_metadata::validate_action_metadata;
 pub use actions::validate_action_reference;
-pub use gitlab::validate_gitlab_pipeline;
+pub use azure::validate_azure_pipeline;
+pub use expressions::validate_expressions;
+pub use gitlab::{validate_gitlab_fragment, validate_gitlab_pipeline};
+pub use jenkins::validate_jenkins_pipeline;
 pub use jobs::validate_jobs;
 pub use matrix::validate_matrix;
+pub use permissions::validate_permissions;
+pub use secrets::validate_secret_usage;
 pub use steps::validate_steps;
+pub use suppressions::Suppressions;
 pub use triggers::validate_triggers;
+pub use yaml::validate_yaml;
@@ -0,0 +1,311 @@
+use serde_yaml::Value;
+use wrkflw_models::{PermissionLevel, ValidationResult};
+
+const VALID_SCOPES: &[&str] = &[
+    "actions",
+    "attestations",
+    "checks",
+    "contents",
+    "deployments",
+    "discussions",
+    "id-token",
+    "issues",
+    "models",
+    "packages",
+    "pages",
+    "pull-requests",
+    "repository-projects",
+    "security-events",
+    "statuses",
+];
+
+/// Validate workflow- and job-level `permissions:` blocks, and warn when a step
+/// uses an API the effective permissions would not allow
+pub fn validate_permissions(workflow: &Value, result: &mut ValidationResult) {
+    let workflow_permissions = workflow.get("permissions");
+    if let Some(permissions) = workflow_permissions {
+        validate_permissions_block(permissions, "Workflow", result);
+    }
+
+    let Some(Value::Mapping(jobs)) = workflow.get("jobs") else {
+        return;
+    };
+
+    for (job_name, job_config) in jobs {
+        let Some(job_name) = job_name.as_str() else {
+            continue;
+        };
+
+        let job_permissions = job_config.get("permissions");
+        if let Some(permissions) = job_permissions {
+            validate_permissions_block(permissions, &format!("Job '{}'", job_name), result);
+        }
+
+        // Without any permissions block the token gets the repository defaults,
+        // which we cannot know locally
+        let Some(effective) = job_permissions.or(workflow_permissions) else {
+            continue;
+        };
+
+        if let Some(Value::Sequence(steps)) = job_config.get("steps") {
+            for (i, step) in steps.iter().enumerate() {
+                for (scope, needed, reason) in required_permissions(step) {
+                    let granted = granted_level(effective, scope);
+                    if granted < needed {
+                        result.add_warning(format!(
+                            "Job '{}', step {}: {} needs '{}: {}' but the token only has '{}: {}'",
+                            job_name,
+                            i + 1,
+                            reason,
+                            scope,
+                            needed.as_str(),
+                            scope,
+                            granted.as_str()
+                        ));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn validate_permissions_block(permissions: &Value, context: &str, result: &mut ValidationResult) {
+    match permissions {
+        Value::String(shorthand) => {
+            if shorthand != "read-all" && shorthand != "write-all" {
+                result.add_issue(format!(
+                    "{}: Invalid permissions value '{}' (expected 'read-all', 'write-all' or a mapping)",
+                    context, shorthand
+                ));
+            }
+        }
+        Value::Mapping(scopes) => {
+            for (scope, level) in scopes {
+                let Some(scope) = scope.as_str() else {
+                    continue;
+                };
+                if !VALID_SCOPES.contains(&scope) {
+                    result.add_issue(format!("{}: Unknown permission scope '{}'", context, scope));
+                    continue;
+                }
+
+                let level = level.as_str().unwrap_or_default();
+                let allowed: &[&str] = match scope {
+                    "id-token" => &["write", "none"],
+                    "models" => &["read", "none"],
+                    _ => &["read", "write", "none"],
+                };
+                if !allowed.contains(&level) {
+                    result.add_issue(format!(
+                        "{}: Invalid level '{}' for permission '{}' (expected one of: {})",
+                        context,
+                        level,
+                        scope,
+                        allowed.join(", ")
+                    ));
+                }
+            }
+        }
+        Value::Null => {}
+        _ => {
+            result.add_issue(format!(
+                "{}: 'permissions' must be 'read-all', 'write-all' or a mapping",
+                context
+            ));
+        }
+    }
+}
+
+fn granted_level(permissions: &Value, scope: &str) -> PermissionLevel {
+    match permissions {
+        Value::String(s) if s == "write-all" => PermissionLevel::Write,
+        Value::String(s) if s == "read-all" => PermissionLevel::Read,
+        Value::Mapping(scopes) => scopes
+            .get(Value::String(scope.to_string()))
+            .and_then(Value::as_str)
+            .and_then(PermissionLevel::parse)
+            .unwrap_or(PermissionLevel::None),
+        _ => PermissionLevel::None,
+    }
+}
+
+const READ: PermissionLevel = PermissionLevel::Read;
+const WRITE: PermissionLevel = PermissionLevel::Write;
+
+/// Permissions a step is known to need, as (scope, level, description)
+fn required_permissions(step: &Value) -> Vec<(&'static str, PermissionLevel, String)> {
+    let mut required = Vec::new();
+
+    if let Some(uses) = step.get("uses").and_then(Value::as_str) {
+        let action = uses.split('@').next().unwrap_or(uses);
+        let with = |key: &str| step.get("with").and_then(|w| w.get(key)).is_some();
+        let needs: &[(&'static str, PermissionLevel)] = match action {
+            "actions/checkout" => &[("contents", READ)],
+            "actions/create-release"
+            | "softprops/action-gh-release"
+            | "ncipollo/release-action" => &[("contents", WRITE)],
+            "peter-evans/create-pull-request" => &[("contents", WRITE), ("pull-requests", WRITE)],
+            "actions/deploy-pages" => &[("pages", WRITE), ("id-token", WRITE)],
+            "actions/labeler" => &[("pull-requests", WRITE)],
+            "actions/stale" => &[("issues", WRITE), ("pull-requests", WRITE)],
+            "marocchino/sticky-pull-request-comment" => &[("pull-requests", WRITE)],
+            "github/codeql-action/analyze" | "github/codeql-action/upload-sarif" => {
+                &[("security-events", WRITE)]
+            }
+            "aws-actions/configure-aws-credentials" if with("role-to-assume") => {
+                &[("id-token", WRITE)]
+            }
+            "google-github-actions/auth" if with("workload_identity_provider") => {
+                &[("id-token", WRITE)]
+            }
+            _ => &[],
+        };
+        for (scope, level) in needs {
+            required.push((*scope, *level, format!("'{}'", action)));
+        }
+    }
+
+    if let Some(run) = step.get("run").and_then(Value::as_str) {
+        const COMMANDS: &[(&str, &str, PermissionLevel)] = &[
+            ("gh release create", "contents", WRITE),
+            ("gh release upload", "contents", WRITE),
+            ("gh release edit", "contents", WRITE),
+            ("gh release delete", "contents", WRITE),
+            ("git push", "contents", WRITE),
+            ("gh pr create", "pull-requests", WRITE),
+            ("gh pr merge", "pull-requests", WRITE),
+            ("gh pr comment", "pull-requests", WRITE),
+            ("gh pr edit", "pull-requests", WRITE),
+            ("gh pr review", "pull-requests", WRITE),
+            ("gh pr close", "pull-requests", WRITE),
+            ("gh issue create", "issues", WRITE),
+            ("gh issue comment", "issues", WRITE),
+            ("gh issue edit", "issues", WRITE),
+            ("gh issue close", "issues", WRITE),
+            ("gh workflow run", "actions", WRITE),
+            ("gh run rerun", "actions", WRITE),
+            ("gh run cancel", "actions", WRITE),
+        ];
+        for (command, scope, level) in COMMANDS {
+            if run.contains(command) {
+                required.push((*scope, *level, format!("'{}'", command)));
+            }
+        }
+    }
+
+    required
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(workflow: &str) -> ValidationResult {
+        let workflow: Value = serde_yaml::from_str(workflow).unwrap();
+        let mut result = ValidationResult::new();
+        validate_permissions(&workflow, &mut result);
+        result
+    }
+
+    #[test]
+    fn test_valid_scopes() {
+        let result = validate(
+            r#"
+permissions:
+  contents: read
+  pull-requests: write
+  id-token: write
+  models: read
+  issues: none
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    permissions:
+      contents: write
+      security-events: write
+    steps:
+      - uses: actions/checkout@v4
+      - run: git push
+"#,
+        );
+        assert!(result.is_valid, "{:?}", result.issues);
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+    }
+
+    #[test]
+    fn test_invalid_scope() {
+        let result = validate(
+            r#"
+permissions:
+  contents: read
+  repo: write
+jobs: {}
+"#,
+        );
+        assert!(!result.is_valid);
+        assert_eq!(
+            result.issues,
+            vec!["Workflow: Unknown permission scope 'repo'".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_invalid_level() {
+        let result = validate(
+            r#"
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    permissions:
+      contents: admin
+      id-token: read
+    steps: []
+"#,
+        );
+        assert!(!result.is_valid);
+        assert_eq!(result.issues.len(), 2, "{:?}", result.issues);
+        assert_eq!(
+            result.issues[0],
+            "Job 'build': Invalid level 'admin' for permission 'contents' (expected one of: read, write, none)"
+        );
+        assert_eq!(
+            result.issues[1],
+            "Job 'build': Invalid level 'read' for permission 'id-token' (expected one of: write, none)"
+        );
+    }
+
+    #[test]
+    fn test_shorthands() {
+        let result = validate(
+            r#"
+permissions: read-all
+jobs:
+  release:
+    runs-on: ubuntu-latest
+    steps:
+      - run: gh release create v1.0.0
+  publish:
+    runs-on: ubuntu-latest
+    permissions: write-all
+    steps:
+      - run: gh release create v1.0.0
+"#,
+        );
+        assert!(result.is_valid, "{:?}", result.issues);
+        // read-all grants reads only; write-all covers the release
+        assert_eq!(result.warnings.len(), 1, "{:?}", result.warnings);
+        assert_eq!(
+            result.warnings[0],
+            "Job 'release', step 1: 'gh release create' needs 'contents: write' but the token only has 'contents: read'"
+        );
+
+        let result = validate("permissions: admin-all\njobs: {}\n");
+        assert_eq!(
+            result.issues,
+            vec![
+                "Workflow: Invalid permissions value 'admin-all' (expected 'read-all', 'write-all' or a mapping)"
+                    .to_string()
+            ]
+        );
+    }
+}
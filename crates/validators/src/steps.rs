@@ -44,7 +44,9 @@ pub fn validate_steps(steps: &[Value], job_name: &str, result: &mut ValidationRe
 
             // Validate action reference if 'uses' is present
             if let Some(Value::String(uses)) = step_map.get(Value::String("uses".to_string())) {
-                validate_action_reference(uses, job_name, i, result);
+                result.in_rule("action-references", |result| {
+                    validate_action_reference(uses, job_name, i, result)
+                });
             }
         } else {
             result.add_issue(format!(
@@ -0,0 +1,304 @@
+//! Comments in workflow YAML suppressing findings: `# wrkflw-disable-next-line
+//! <rule-id>` for the node starting on the next line, everything nested under it
+//! included, and `# wrkflw-disable <rule-id>` for the whole file. Rule ids are
+//! separated by commas or spaces, none meaning every rule, and anything after
+//! ` -- ` is the reason.
+//!
+//! Findings don't know where in the file they are, so they are placed by what
+//! their messages name: a YAML error's position, a path like
+//! `jobs.build.steps[1].run`, `Job 'build', step 2`, or a quoted key.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::ops::Range;
+use wrkflw_models::Finding;
+use wrkflw_parser::yaml;
+
+lazy_static! {
+    static ref DIRECTIVE: Regex =
+        Regex::new(r"^\s*#\s*wrkflw-(disable-next-line|disable)(?:\s+(.*))?$").unwrap();
+    static ref YAML_PATH: Regex =
+        Regex::new(r"^([A-Za-z0-9_-]+(?:\.[A-Za-z0-9_-]+|\[\d+\])+): ").unwrap();
+    static ref PATH_SEGMENT: Regex = Regex::new(r"([A-Za-z0-9_-]+)|\[(\d+)\]").unwrap();
+    static ref JOB_STEP: Regex = Regex::new(r"\b[Jj]ob '([^']+)'(?:, step (\d+))?").unwrap();
+}
+
+/// The suppression comments of a file
+#[derive(Debug, Default)]
+pub struct Suppressions {
+    lines: Vec<String>,
+    /// Rules disabled in the whole file
+    file: Vec<Vec<String>>,
+    /// Rules disabled for ranges of lines
+    blocks: Vec<(Range<usize>, Vec<String>)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Item(usize),
+}
+
+impl Suppressions {
+    pub fn parse(text: &str) -> Self {
+        let lines: Vec<String> = text.lines().map(str::to_string).collect();
+        let mut suppressions = Suppressions::default();
+        for (idx, line) in lines.iter().enumerate() {
+            let Some(captures) = DIRECTIVE.captures(line) else {
+                continue;
+            };
+            let rules = captures.get(2).map_or("", |rules| rules.as_str());
+            let rules = rules.split(" -- ").next().unwrap_or_default();
+            let rules: Vec<String> = rules
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|rule| !rule.is_empty())
+                .map(str::to_string)
+                .collect();
+            if &captures[1] == "disable" {
+                suppressions.file.push(rules);
+            } else if let Some(next) = (idx + 1..lines.len()).find(|&i| is_content(&lines[i])) {
+                suppressions
+                    .blocks
+                    .push((next..block_end(&lines, next), rules));
+            }
+        }
+        suppressions.lines = lines;
+        suppressions
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.file.is_empty() && self.blocks.is_empty()
+    }
+
+    /// Whether a comment suppresses `finding`
+    pub fn suppresses(&self, finding: &Finding) -> bool {
+        let disables = |rules: &Vec<String>| rules.is_empty() || rules.contains(&finding.rule);
+        if self.file.iter().any(disables) {
+            return true;
+        }
+        if self.blocks.is_empty() {
+            return false;
+        }
+        let Some(line) = locate(&self.lines, &finding.message) else {
+            return false;
+        };
+        self.blocks
+            .iter()
+            .any(|(lines, rules)| lines.contains(&line) && disables(rules))
+    }
+}
+
+/// The line a finding's message is about, if it can be told
+fn locate(lines: &[String], message: &str) -> Option<usize> {
+    if let Some((line, _)) = yaml::message_position(message) {
+        return Some(line.saturating_sub(1));
+    }
+    if let Some(captures) = YAML_PATH.captures(message) {
+        let path = PATH_SEGMENT
+            .captures_iter(&captures[1])
+            .map(|segment| match (segment.get(1), segment.get(2)) {
+                (_, Some(idx)) => Segment::Item(idx.as_str().parse().unwrap_or_default()),
+                (key, _) => Segment::Key(key.map_or("", |key| key.as_str()).to_string()),
+            })
+            .collect::<Vec<_>>();
+        if let Some(line) = find_path(lines, &path) {
+            return Some(line);
+        }
+    }
+    if let Some(captures) = JOB_STEP.captures(message) {
+        let mut path = vec![Segment::Key(captures[1].to_string())];
+        if let Some(step) = captures.get(2) {
+            let step: usize = step.as_str().parse().unwrap_or(1);
+            path.push(Segment::Key("steps".to_string()));
+            path.push(Segment::Item(step.saturating_sub(1)));
+        }
+        // GitLab jobs are at the top level
+        let github = [vec![Segment::Key("jobs".to_string())], path.clone()].concat();
+        if let Some(line) = find_path(lines, &github).or_else(|| find_path(lines, &path)) {
+            return Some(line);
+        }
+    }
+    let quoted: Vec<&str> = yaml::quoted_names(message).collect();
+    quoted
+        .iter()
+        .find_map(|name| {
+            lines
+                .iter()
+                .position(|line| is_content(line) && key_of(item_text(line)) == Some(name))
+        })
+        .or_else(|| {
+            quoted.iter().find_map(|name| {
+                lines
+                    .iter()
+                    .position(|line| is_content(line) && contains_word(line, name))
+            })
+        })
+}
+
+/// The line of the deepest node of `path` in the file
+fn find_path(lines: &[String], path: &[Segment]) -> Option<usize> {
+    let mut found: Option<usize> = None;
+    for segment in path {
+        let range = match found {
+            Some(line) => line + 1..block_end(lines, line),
+            None => 0..lines.len(),
+        };
+        let Some(line) = find_child(lines, range, found, segment) else {
+            break;
+        };
+        found = Some(line);
+    }
+    found
+}
+
+/// The line of the child `segment` of the node on `parent` whose block is `range`
+fn find_child(
+    lines: &[String],
+    range: Range<usize>,
+    parent: Option<usize>,
+    segment: &Segment,
+) -> Option<usize> {
+    // The first key of a list item is on the item's line
+    let mut children: Vec<(usize, usize, &str)> = Vec::new();
+    if let Some(parent) = parent.filter(|&parent| is_item(&lines[parent])) {
+        let line = &lines[parent];
+        children.push((parent, indent(line) + 2, item_text(line)));
+    }
+    children.extend(
+        range
+            .filter(|&idx| is_content(&lines[idx]))
+            .map(|idx| (idx, indent(&lines[idx]), lines[idx].trim_start())),
+    );
+    let depth = children.iter().map(|(_, indent, _)| *indent).min()?;
+    let mut children = children
+        .into_iter()
+        .filter(|(_, indent, _)| *indent == depth);
+    match segment {
+        Segment::Key(name) => children
+            .find(|(_, _, text)| key_of(text) == Some(name))
+            .map(|(idx, _, _)| idx),
+        Segment::Item(n) => children
+            .filter(|(idx, _, _)| Some(*idx) != parent && is_item(&lines[*idx]))
+            .nth(*n)
+            .map(|(idx, _, _)| idx),
+    }
+}
+
+/// The end of the block of the node on line `start`: the lines nested under it
+fn block_end(lines: &[String], start: usize) -> usize {
+    let line = &lines[start];
+    let depth = indent(line);
+    // A key's list items may be at its own indentation
+    let items_may_follow = !is_item(line) && line.trim_end().ends_with(':');
+    (start + 1..lines.len())
+        .filter(|&idx| is_content(&lines[idx]))
+        .find(|&idx| {
+            let line = &lines[idx];
+            indent(line) < depth || (indent(line) == depth && !(items_may_follow && is_item(line)))
+        })
+        .unwrap_or(lines.len())
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+fn is_content(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    !trimmed.is_empty() && !trimmed.starts_with('#')
+}
+
+fn is_item(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed == "-" || trimmed.starts_with("- ")
+}
+
+/// The text of a line after the dash of a list item
+fn item_text(line: &str) -> &str {
+    let trimmed = line.trim_start();
+    trimmed.strip_prefix("- ").unwrap_or(trimmed).trim_start()
+}
+
+fn key_of(text: &str) -> Option<&str> {
+    let (key, _) = text.split_once(':')?;
+    let key = key.trim().trim_matches(['"', '\'']);
+    (!key.is_empty() && !key.starts_with('-')).then_some(key)
+}
+
+fn contains_word(line: &str, word: &str) -> bool {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+    line.match_indices(word).any(|(idx, _)| {
+        !line[..idx].ends_with(is_word_char) && !line[idx + word.len()..].starts_with(is_word_char)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wrkflw_models::Severity;
+
+    const WORKFLOW: &str = r#"# wrkflw-disable triggers -- we run on a custom event
+on: [push, deployment_review]
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+      # wrkflw-disable-next-line secret-leaks, expressions -- the token is a dummy
+      - run: echo "${{ secrets.TOKEN }}"
+      - run: echo "${{ secrets.OTHER }}"
+  # wrkflw-disable-next-line
+  test:
+    steps:
+    - run: make
+"#;
+
+    fn finding(rule: &str, message: &str) -> Finding {
+        Finding {
+            rule: rule.to_string(),
+            severity: Severity::Warning,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_suppressions() {
+        let suppressions = Suppressions::parse(WORKFLOW);
+        assert!(suppressions.suppresses(&finding(
+            "triggers",
+            "Unknown trigger event: 'deployment_review'"
+        )));
+        assert!(suppressions.suppresses(&finding(
+            "secret-leaks",
+            "Job 'build', step 2: prints secrets.TOKEN to the log"
+        )));
+        assert!(!suppressions.suppresses(&finding(
+            "secret-leaks",
+            "Job 'build', step 3: prints secrets.OTHER to the log"
+        )));
+        assert!(!suppressions.suppresses(&finding(
+            "steps",
+            "Job 'build', step 2: Missing 'name', 'uses', or 'run' field"
+        )));
+        assert!(suppressions.suppresses(&finding("jobs", "Job 'test' is missing 'runs-on' field")));
+        assert!(
+            suppressions.suppresses(&finding("steps", "Job 'test', step 1: Something about it"))
+        );
+        assert!(!suppressions.suppresses(&finding(
+            "jobs",
+            "Job 'build' depends on non-existent job 'x'"
+        )));
+    }
+
+    #[test]
+    fn test_locate() {
+        let lines: Vec<String> = WORKFLOW.lines().map(str::to_string).collect();
+        assert_eq!(
+            locate(&lines, "jobs.build.steps[1].run: looks like a credential"),
+            Some(8)
+        );
+        assert_eq!(locate(&lines, "Job 'test', step 1: Oops"), Some(13));
+        assert_eq!(locate(&lines, "while parsing at line 5 column 3"), Some(4));
+        assert_eq!(locate(&lines, "Something odd"), None);
+    }
+}
@@ -1,5 +1,6 @@
 use serde_yaml::Value;
 use wrkflw_models::ValidationResult;
+use wrkflw_parser::cron::{CronSchedule, SHORTEST_INTERVAL_MINUTES};
 
 pub fn validate_triggers(on: &Value, result: &mut ValidationResult) {
     let valid_events = vec![
@@ -62,13 +63,11 @@ pub fn validate_triggers(on: &Value, result: &mut ValidationResult) {
             }
 
             // Check schedule syntax if present
-            if let Some(Value::Sequence(schedules)) =
-                event_map.get(Value::String("schedule".to_string()))
-            {
-                for schedule in schedules {
-                    if let Some(schedule_map) = schedule.as_mapping() {
+            match event_map.get(Value::String("schedule".to_string())) {
+                Some(Value::Sequence(schedules)) => {
+                    for schedule in schedules {
                         if let Some(Value::String(cron)) =
-                            schedule_map.get(Value::String("cron".to_string()))
+                            schedule.get(Value::String("cron".to_string()))
                         {
                             validate_cron_syntax(cron, result);
                         } else {
@@ -76,6 +75,10 @@ pub fn validate_triggers(on: &Value, result: &mut ValidationResult) {
                         }
                     }
                 }
+                Some(_) => {
+                    result.add_issue("'schedule' should be a list of 'cron' entries".to_string())
+                }
+                None => {}
             }
         }
         _ => {
@@ -85,12 +88,12 @@ pub fn validate_triggers(on: &Value, result: &mut ValidationResult) {
 }
 
 fn validate_cron_syntax(cron: &str, result: &mut ValidationResult) {
-    // Basic validation of cron syntax
-    let parts: Vec<&str> = cron.split_whitespace().collect();
-    if parts.len() != 5 {
-        result.add_issue(format!(
-            "Invalid cron syntax '{}': should have 5 components",
-            cron
-        ));
+    match CronSchedule::parse(cron) {
+        Ok(schedule) if schedule.exceeds_shortest_interval() => result.add_warning(format!(
+            "Schedule '{}' runs more often than every {} minutes; GitHub runs scheduled workflows at most that often",
+            cron, SHORTEST_INTERVAL_MINUTES
+        )),
+        Ok(_) => {}
+        Err(e) => result.add_issue(format!("Invalid cron syntax '{}': {}", cron, e)),
     }
 }
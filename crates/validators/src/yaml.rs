@@ -0,0 +1,431 @@
+//! Checks of the YAML of a file as written, before it's parsed: keys given twice in
+//! a mapping, merge keys (`<<`) where they aren't supported or merge something
+//! other than mappings, and aliases of anchors not defined before them. Block
+//! mappings and sequences are followed line by line; flow collections are only
+//! searched for anchors and aliases.
+
+use std::collections::{HashMap, HashSet};
+use wrkflw_models::ValidationResult;
+
+/// The keys of a block mapping that starts at `column`, with their lines
+struct BlockMapping {
+    column: usize,
+    keys: HashMap<String, usize>,
+}
+
+/// Where the scan is in the lines of a node that spans several
+enum Continuation {
+    /// A `|` or `>` scalar, whose lines are indented more than `column`
+    BlockScalar { column: usize },
+    /// A quoted scalar not closed yet
+    Quoted(char),
+    /// A flow collection `depth` brackets deep
+    Flow { depth: usize },
+}
+
+/// Report duplicate keys, aliases of undefined anchors and, unless `merge_keys`
+/// are supported, merge keys in `content`; merge keys that are supported must
+/// merge aliases or mappings
+pub fn validate_yaml(content: &str, merge_keys: bool, result: &mut ValidationResult) {
+    let mut scan = Scan {
+        merge_keys,
+        mappings: Vec::new(),
+        anchors: HashSet::new(),
+        continuation: None,
+        result,
+    };
+    for (idx, line) in content.lines().enumerate() {
+        scan.line(idx + 1, line);
+    }
+}
+
+struct Scan<'a> {
+    merge_keys: bool,
+    mappings: Vec<BlockMapping>,
+    anchors: HashSet<String>,
+    continuation: Option<Continuation>,
+    result: &'a mut ValidationResult,
+}
+
+impl Scan<'_> {
+    fn line(&mut self, number: usize, line: &str) {
+        let indent = line.len() - line.trim_start().len();
+        match self.continuation.take() {
+            Some(Continuation::BlockScalar { column })
+                if line.trim().is_empty() || indent > column =>
+            {
+                self.continuation = Some(Continuation::BlockScalar { column });
+                return;
+            }
+            Some(Continuation::Quoted(quote)) => {
+                if closing_quote(line, quote).is_none() {
+                    self.continuation = Some(Continuation::Quoted(quote));
+                }
+                return;
+            }
+            Some(Continuation::Flow { depth }) => {
+                let line = strip_comment(line);
+                self.flow(number, 0, line);
+                self.continue_flow(depth, line);
+                return;
+            }
+            _ => {}
+        }
+
+        let trimmed = line.trim();
+        if trimmed == "---" || trimmed.starts_with("--- ") {
+            // Every document has its own keys and anchors
+            self.mappings.clear();
+            self.anchors.clear();
+            return;
+        }
+        let line = strip_comment(line);
+        if line.trim().is_empty() || trimmed == "..." {
+            return;
+        }
+
+        // Sequence items, and the anchors of the nodes they start
+        let mut column = indent;
+        let mut rest = &line[indent..];
+        let mut item = false;
+        loop {
+            if let Some(after) = rest
+                .strip_prefix('-')
+                .filter(|after| after.is_empty() || after.starts_with(char::is_whitespace))
+            {
+                item = true;
+                let trimmed = after.trim_start();
+                column += rest.len() - trimmed.len();
+                rest = trimmed;
+            } else if let Some((name, after)) = property(rest, '&') {
+                self.anchors.insert(name.to_string());
+                let trimmed = after.trim_start();
+                column += rest.len() - trimmed.len();
+                rest = trimmed;
+            } else {
+                break;
+            }
+        }
+
+        let Some((key, value_at)) = mapping_key(rest) else {
+            // A scalar, alias or collection of its own, e.g. a sequence item
+            self.mappings.retain(|mapping| mapping.column <= indent);
+            if item || rest.starts_with(['*', '[', '{', '|', '>', '"', '\'']) {
+                self.value(number, column, indent, rest);
+            }
+            return;
+        };
+
+        // A key ends the mappings nested deeper, and an item starts a new one
+        self.mappings
+            .retain(|mapping| mapping.column < column || (mapping.column == column && !item));
+        if self.mappings.last().map(|mapping| mapping.column) != Some(column) {
+            self.mappings.push(BlockMapping {
+                column,
+                keys: HashMap::new(),
+            });
+        }
+        let mapping = self.mappings.last_mut().unwrap();
+        if let Some(first) = mapping.keys.get(&key) {
+            self.result.add_issue(format!(
+                "Duplicate key '{}' at line {} column {}, already given at line {}",
+                key,
+                number,
+                column + 1,
+                first
+            ));
+        } else {
+            mapping.keys.insert(key.clone(), number);
+        }
+
+        let value = rest[value_at..].trim_start();
+        let value_column = column + rest.len() - value.len();
+        if key == "<<" {
+            self.merge_key(number, column, value);
+        }
+        self.value(number, value_column, column, value);
+    }
+
+    /// The value of a node starting at `column` of line `number`, its parent at
+    /// `parent_column`
+    fn value(&mut self, number: usize, column: usize, parent_column: usize, value: &str) {
+        let (column, value) = match property(value, '&') {
+            Some((name, after)) => {
+                self.anchors.insert(name.to_string());
+                let trimmed = after.trim_start();
+                (column + value.len() - trimmed.len(), trimmed)
+            }
+            None => (column, value),
+        };
+        let value = match property(value, '!') {
+            Some((_, after)) => after.trim_start(),
+            None => value,
+        };
+        match value.chars().next() {
+            Some('*') => self.alias(number, column, value),
+            Some('|') | Some('>') => {
+                self.continuation = Some(Continuation::BlockScalar {
+                    column: parent_column,
+                })
+            }
+            Some(quote @ ('"' | '\'')) if closing_quote(&value[1..], quote).is_none() => {
+                self.continuation = Some(Continuation::Quoted(quote));
+            }
+            Some('[') | Some('{') => {
+                self.flow(number, column, value);
+                self.continue_flow(0, value);
+            }
+            _ => {}
+        }
+    }
+
+    /// Check the alias `value` starts with
+    fn alias(&mut self, number: usize, column: usize, value: &str) {
+        let Some((name, _)) = property(value, '*') else {
+            return;
+        };
+        if !self.anchors.contains(name) {
+            self.result.add_issue(format!(
+                "Alias '*{}' at line {} column {} refers to no anchor defined before it",
+                name,
+                number,
+                column + 1
+            ));
+        }
+    }
+
+    /// Check the merge key at `column` of line `number`, whose value is `value`
+    fn merge_key(&mut self, number: usize, column: usize, value: &str) {
+        if !self.merge_keys {
+            self.result.add_issue(format!(
+                "Merge key '<<' at line {} column {}: GitHub doesn't support YAML merge keys, the keys have to be repeated",
+                number,
+                column + 1
+            ));
+            return;
+        }
+        // An alias, a list of them, or a mapping, in the lines under it or in braces
+        let merges_mappings = value.is_empty()
+            || value.starts_with(['*', '{'])
+            || value
+                .strip_prefix('[')
+                .and_then(|items| items.trim_end().strip_suffix(']'))
+                .is_some_and(|items| {
+                    items
+                        .split(',')
+                        .all(|item| item.trim().starts_with('*') || item.trim().is_empty())
+                });
+        if !merges_mappings {
+            self.result.add_issue(format!(
+                "Merge key '<<' at line {} column {} merges '{}', which isn't an alias or a mapping",
+                number,
+                column + 1,
+                value
+            ));
+        }
+    }
+
+    /// Check the anchors and aliases of the nodes of a flow collection in `text`,
+    /// at `column` of line `number`
+    fn flow(&mut self, number: usize, column: usize, text: &str) {
+        let mut quote = None;
+        let mut node_start = true;
+        for (offset, c) in text.char_indices() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if c == '"' || c == '\'' => {
+                    quote = Some(c);
+                    node_start = false;
+                }
+                None if matches!(c, '[' | '{' | ',') => node_start = true,
+                None if c == ':' => node_start = true,
+                None if c.is_whitespace() => {}
+                None if node_start && c == '&' => {
+                    if let Some((name, _)) = property(&text[offset..], '&') {
+                        self.anchors.insert(name.to_string());
+                    }
+                    node_start = false;
+                }
+                None if node_start && c == '*' => {
+                    self.alias(number, column + offset, &text[offset..]);
+                    node_start = false;
+                }
+                None => node_start = false,
+            }
+        }
+    }
+
+    /// Carry on a flow collection `depth` brackets deep through `text`
+    fn continue_flow(&mut self, depth: usize, text: &str) {
+        let mut depth = depth as isize;
+        let mut quote = None;
+        for c in text.chars() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if c == '"' || c == '\'' => quote = Some(c),
+                None if c == '[' || c == '{' => depth += 1,
+                None if c == ']' || c == '}' => depth -= 1,
+                None => {}
+            }
+        }
+        if depth > 0 {
+            self.continuation = Some(Continuation::Flow {
+                depth: depth as usize,
+            });
+        }
+    }
+}
+
+/// The key `rest` starts with, if it's a `key: value` line, and where its value
+/// starts
+fn mapping_key(rest: &str) -> Option<(String, usize)> {
+    if let Some(quote @ ('"' | '\'')) = rest.chars().next() {
+        let end = closing_quote(&rest[1..], quote)? + 1;
+        let after = &rest[end + 1..];
+        let colon = after.len() - after.trim_start().len();
+        let value = after[colon..].strip_prefix(':')?;
+        if !value.is_empty() && !value.starts_with(char::is_whitespace) {
+            return None;
+        }
+        return Some((rest[1..end].to_string(), end + 1 + colon + 1));
+    }
+    if rest.starts_with(['[', '{', '*', '|', '>', '!', '%', '@', '`', '?', '#']) {
+        return None;
+    }
+    let colon = rest.char_indices().find_map(|(offset, c)| {
+        let after = &rest[offset + 1..];
+        (c == ':' && (after.is_empty() || after.starts_with(char::is_whitespace))).then_some(offset)
+    })?;
+    let key = rest[..colon].trim_end();
+    (!key.is_empty()).then(|| (key.to_string(), colon + 1))
+}
+
+/// The name of the anchor (`&`), alias (`*`) or tag (`!`) `text` starts with, and
+/// what follows it
+fn property(text: &str, indicator: char) -> Option<(&str, &str)> {
+    let rest = text.strip_prefix(indicator)?;
+    let end = rest
+        .find(|c: char| c.is_whitespace() || matches!(c, ',' | '[' | ']' | '{' | '}'))
+        .unwrap_or(rest.len());
+    (end > 0 || indicator == '!').then(|| (&rest[..end], &rest[end..]))
+}
+
+/// Offset in `text` of the quote closing a scalar opened before it
+fn closing_quote(text: &str, quote: char) -> Option<usize> {
+    let mut chars = text.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        if quote == '"' && c == '\\' {
+            chars.next();
+        } else if c == quote {
+            // '' is a quote in single-quoted scalars
+            if quote == '\'' && chars.peek().is_some_and(|(_, next)| *next == '\'') {
+                chars.next();
+                continue;
+            }
+            return Some(offset);
+        }
+    }
+    None
+}
+
+/// `line` without its comment
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (offset, c) in line.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            // Quotes only start scalars, not in the middle of one
+            None if (c == '"' || c == '\'')
+                && (previous.is_whitespace()
+                    || matches!(previous, ':' | '-' | '[' | '{' | ',')) =>
+            {
+                quote = Some(c)
+            }
+            None if c == '#' && previous.is_whitespace() => return &line[..offset],
+            None => {}
+        }
+        previous = c;
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issues(content: &str, merge_keys: bool) -> Vec<String> {
+        let mut result = ValidationResult::new();
+        validate_yaml(content, merge_keys, &mut result);
+        result.issues
+    }
+
+    #[test]
+    fn duplicate_keys_are_reported_where_given_again() {
+        let content = r#"on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - name: Build
+        run: |
+          name: not a key
+          name: not a key
+        name: Again
+      - name: Test
+        run: make test
+  test:
+    runs-on: ubuntu-latest
+    runs-on: windows-latest # comment
+"#;
+        assert_eq!(
+            issues(content, false),
+            vec![
+                "Duplicate key 'name' at line 10 column 9, already given at line 6",
+                "Duplicate key 'runs-on' at line 15 column 5, already given at line 14",
+            ]
+        );
+    }
+
+    #[test]
+    fn aliases_need_anchors_defined_before_them() {
+        let content = r#"env: &env
+  CI: "true"
+jobs:
+  build:
+    env: *env
+    steps: [*setup, {run: make}]
+    services: *services
+"#;
+        let issues = issues(content, false);
+        assert_eq!(issues.len(), 2, "{:?}", issues);
+        assert!(issues[0].starts_with("Alias '*setup' at line 6 column 13"));
+        assert!(issues[1].starts_with("Alias '*services' at line 7 column 15"));
+
+        // `*` inside a scalar isn't an alias
+        assert!(self::issues("run: ls *.txt\nglob: '*.md'\n", false).is_empty());
+    }
+
+    #[test]
+    fn merge_keys_merge_aliases_or_mappings() {
+        let content = r#".defaults: &defaults
+  image: alpine
+build:
+  <<: *defaults
+  script: [make]
+test:
+  <<: defaults
+  script: [make test]
+"#;
+        let issues_with_merge = issues(content, true);
+        assert_eq!(issues_with_merge.len(), 1, "{:?}", issues_with_merge);
+        assert!(issues_with_merge[0].contains("line 7 column 3 merges 'defaults'"));
+
+        let without = issues(content, false);
+        assert_eq!(without.len(), 2);
+        assert!(without[0].contains("GitHub doesn't support YAML merge keys"));
+    }
+}
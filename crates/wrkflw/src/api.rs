@@ -0,0 +1,330 @@
+//! The library API: load, validate and run workflows from another Rust program
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+use wrkflw_executor::engine::{ExecutionError, ExecutionResult};
+use wrkflw_executor::{ExecutionConfig, RuntimeType};
+use wrkflw_logging::Record;
+use wrkflw_models::gitlab::Pipeline;
+use wrkflw_models::ValidationResult;
+use wrkflw_parser::workflow::WorkflowDefinition;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Failed to load {path}: {message}")]
+    Load { path: PathBuf, message: String },
+
+    #[error(transparent)]
+    Execution(#[from] ExecutionError),
+}
+
+/// CI system a workflow is written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkflowKind {
+    /// A GitHub Actions workflow
+    GitHub,
+    /// A GitLab CI/CD pipeline
+    GitLab,
+}
+
+#[derive(Debug)]
+enum Definition {
+    GitHub(WorkflowDefinition),
+    GitLab(Pipeline),
+}
+
+/// A parsed GitHub Actions workflow or GitLab CI/CD pipeline
+#[derive(Debug)]
+pub struct Workflow {
+    path: PathBuf,
+    definition: Definition,
+}
+
+impl Workflow {
+    /// Parse the workflow at `path`, a GitLab pipeline if its name says so
+    /// (`.gitlab-ci.yml`, `*.gitlab-ci.yml`, `.gitlab/ci/*.yml`)
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let load_error = |message: String| Error::Load {
+            path: path.to_path_buf(),
+            message,
+        };
+        let definition = if wrkflw_utils::is_gitlab_pipeline_file(path) {
+            wrkflw_parser::gitlab::parse_pipeline(path)
+                .map(Definition::GitLab)
+                .map_err(|e| load_error(e.to_string()))?
+        } else {
+            wrkflw_parser::workflow::parse_workflow(path)
+                .map(Definition::GitHub)
+                .map_err(load_error)?
+        };
+        Ok(Workflow {
+            path: path.to_path_buf(),
+            definition,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn kind(&self) -> WorkflowKind {
+        match self.definition {
+            Definition::GitHub(_) => WorkflowKind::GitHub,
+            Definition::GitLab(_) => WorkflowKind::GitLab,
+        }
+    }
+
+    /// The workflow's `name:`, or its file name for GitLab pipelines, which have none
+    pub fn name(&self) -> String {
+        match &self.definition {
+            Definition::GitHub(workflow) => workflow.name.clone(),
+            Definition::GitLab(_) => self
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Names of the jobs, sorted; hidden GitLab jobs (`.name`) and templates left out
+    pub fn jobs(&self) -> Vec<String> {
+        let jobs: BTreeSet<&String> = match &self.definition {
+            Definition::GitHub(workflow) => workflow.jobs.keys().collect(),
+            Definition::GitLab(pipeline) => pipeline
+                .jobs
+                .iter()
+                .filter(|(name, job)| !name.starts_with('.') && job.template != Some(true))
+                .map(|(name, _)| name)
+                .collect(),
+        };
+        jobs.into_iter().cloned().collect()
+    }
+
+    /// The parsed GitHub Actions workflow, if it's one
+    pub fn github(&self) -> Option<&WorkflowDefinition> {
+        match &self.definition {
+            Definition::GitHub(workflow) => Some(workflow),
+            Definition::GitLab(_) => None,
+        }
+    }
+
+    /// The parsed GitLab pipeline, if it's one
+    pub fn gitlab(&self) -> Option<&Pipeline> {
+        match &self.definition {
+            Definition::GitLab(pipeline) => Some(pipeline),
+            Definition::GitHub(_) => None,
+        }
+    }
+
+    /// Check the workflow as `wrkflw validate` does
+    pub fn validate(&self) -> ValidationResult {
+        match &self.definition {
+            Definition::GitLab(pipeline) => wrkflw_validators::validate_gitlab_pipeline(pipeline),
+            Definition::GitHub(_) => wrkflw_evaluator::evaluate_workflow_file(&self.path, false)
+                .unwrap_or_else(|e| {
+                    let mut result = ValidationResult::new();
+                    result.add_issue(e);
+                    result
+                }),
+        }
+    }
+}
+
+/// Runs workflows, set up like `wrkflw run`: in Docker unless told otherwise, as a
+/// push unless given another event
+#[derive(Clone)]
+pub struct Runner {
+    config: ExecutionConfig,
+    on_log: Option<wrkflw_logging::LogCallback>,
+}
+
+impl Default for Runner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Runner {
+    pub fn new() -> Self {
+        Runner {
+            config: ExecutionConfig::default(),
+            on_log: None,
+        }
+    }
+
+    /// Runtime of the jobs; Docker and Podman fall back to emulation when missing
+    pub fn runtime(mut self, runtime_type: RuntimeType) -> Self {
+        self.config.runtime_type = runtime_type;
+        self
+    }
+
+    /// Event the run simulates (`github.event_name`), like `wrkflw run --event`
+    pub fn event(mut self, event: impl Into<String>) -> Self {
+        self.config.event = Some(event.into());
+        self
+    }
+
+    /// Set a `workflow_dispatch` input
+    pub fn input(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.inputs.insert(name.into(), value.into());
+        self
+    }
+
+    /// Set an environment variable in every job, over the env the workflow defines
+    pub fn env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.env.insert(name.into(), value.into());
+        self
+    }
+
+    /// Set a configuration variable (`${{ vars.NAME }}`)
+    pub fn var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.vars.insert(name.into(), value.into());
+        self
+    }
+
+    /// Token for steps calling the GitHub API (`GITHUB_TOKEN`); a placeholder by default
+    pub fn github_token(mut self, token: impl Into<String>) -> Self {
+        self.config.github_token = Some(token.into());
+        self
+    }
+
+    /// Without `github_token`, give runs the token of the environment or the gh CLI
+    /// instead of a placeholder; off by default
+    pub fn github_token_from_env(mut self, from_env: bool) -> Self {
+        self.config.github_token_from_env = from_env;
+        self
+    }
+
+    /// Only use actions already in the action cache
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.config.offline = offline;
+        self
+    }
+
+    /// Write the logs of each run under `.wrkflw/logs` of the current directory,
+    /// for `wrkflw logs`; off by default
+    pub fn log_files(mut self, log_files: bool) -> Self {
+        self.config.log_files = log_files;
+        self
+    }
+
+    /// Bytes of each output stream of a step kept in memory (10 MiB by default);
+    /// longer output keeps its start and end, with the whole saved to a file
+    pub fn output_limit(mut self, bytes: usize) -> Self {
+        self.config.output_limit = bytes;
+        self
+    }
+
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.config.verbose = verbose;
+        self
+    }
+
+    /// Receive what the run logs instead of it being printed. Logging is shared by
+    /// the whole process, so with runs at the same time each callback also gets what
+    /// the others log; a run ending only removes its own callback.
+    pub fn on_log(mut self, callback: impl Fn(&Record) + Send + Sync + 'static) -> Self {
+        self.on_log = Some(Arc::new(callback));
+        self
+    }
+
+    /// The configuration runs use, for what the setters above don't cover
+    pub fn config_mut(&mut self) -> &mut ExecutionConfig {
+        &mut self.config
+    }
+
+    /// Run `workflow`; failing jobs are reported in the result, not as an error
+    pub async fn execute(&self, workflow: &Workflow) -> Result<ExecutionResult, Error> {
+        let _callback = self
+            .on_log
+            .clone()
+            .map(|callback| CallbackGuard(wrkflw_logging::add_log_callback(callback)));
+        Ok(wrkflw_executor::execute_workflow(&workflow.path, self.config.clone()).await?)
+    }
+}
+
+// Stops handing logs to a runner's callback once its run is over, or dropped
+struct CallbackGuard(wrkflw_logging::CallbackId);
+
+impl Drop for CallbackGuard {
+    fn drop(&mut self) {
+        wrkflw_logging::remove_log_callback(self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn write(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_load_and_validate() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(
+            dir.path(),
+            "ci.yml",
+            "name: CI\non: push\njobs:\n  test:\n    runs-on: ubuntu-latest\n    steps:\n      - run: cargo test\n  build:\n    runs-on: ubuntu-latest\n    steps:\n      - run: cargo build\n",
+        );
+        let workflow = Workflow::load(&path).unwrap();
+        assert_eq!(workflow.kind(), WorkflowKind::GitHub);
+        assert_eq!(workflow.name(), "CI");
+        assert_eq!(workflow.jobs(), vec!["build", "test"]);
+        assert!(workflow.github().is_some());
+        assert!(workflow.validate().is_valid);
+
+        let broken = write(dir.path(), "broken.yml", "jobs: [\n");
+        assert!(matches!(
+            Workflow::load(&broken),
+            Err(Error::Load { path, .. }) if path == broken
+        ));
+    }
+
+    #[test]
+    fn test_load_gitlab_pipeline() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(
+            dir.path(),
+            ".gitlab-ci.yml",
+            "stages: [test]\n.base:\n  image: rust\ntest:\n  stage: test\n  script: [cargo test]\n",
+        );
+        let workflow = Workflow::load(&path).unwrap();
+        assert_eq!(workflow.kind(), WorkflowKind::GitLab);
+        assert_eq!(workflow.name(), ".gitlab-ci.yml");
+        assert_eq!(workflow.jobs(), vec!["test"]);
+        assert!(workflow.gitlab().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_log_callback() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(
+            dir.path(),
+            "hello.yml",
+            "name: Hello\non: push\njobs:\n  greet:\n    runs-on: ubuntu-latest\n    steps:\n      - run: echo \"hello $WHO\"\n",
+        );
+        let workflow = Workflow::load(&path).unwrap();
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let seen = records.clone();
+        let runner = Runner::new()
+            .runtime(RuntimeType::Emulation)
+            .env("WHO", "embedder")
+            .on_log(move |record| seen.lock().unwrap().push(record.message.clone()));
+
+        let result = runner.execute(&workflow).await.unwrap();
+        assert!(result.failure_details.is_none());
+        assert_eq!(result.jobs.len(), 1);
+        assert_eq!(result.jobs[0].name, "greet");
+        assert!(result.jobs[0].steps[0].output.contains("hello embedder"));
+        assert!(!records.lock().unwrap().is_empty());
+    }
+}
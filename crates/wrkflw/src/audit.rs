@@ -0,0 +1,110 @@
+//! `wrkflw audit`: deprecated runner images, action versions and workflow commands
+//! in workflows and the local actions they use.
+
+use crate::validation::{relative, workflow_files};
+use std::fs;
+use std::path::{Path, PathBuf};
+use wrkflw_parser::audit;
+
+/// Audit `paths` (or `.github/workflows`), rewriting fixable findings with `fix`.
+/// Fails when findings are left unfixed.
+pub fn handle_audit_command(paths: &[PathBuf], fix: bool) -> Result<(), String> {
+    let paths = if paths.is_empty() {
+        vec![PathBuf::from(".github/workflows")]
+    } else {
+        paths.to_vec()
+    };
+
+    let mut files = Vec::new();
+    for path in &paths {
+        if !path.exists() {
+            return Err(format!("{} does not exist", path.display()));
+        }
+        for file in workflow_files(path) {
+            push_unique(&mut files, file);
+        }
+    }
+    // The local actions the workflows use may run on deprecated runtimes too
+    for file in files.clone() {
+        for action in local_actions(&file) {
+            push_unique(&mut files, action);
+        }
+    }
+
+    let (mut fixed, mut left) = (0, 0);
+    for file in &files {
+        let content = fs::read_to_string(file)
+            .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+        let audit = audit::audit(&content);
+        if audit.findings.is_empty() {
+            continue;
+        }
+
+        println!("{}", relative(file).display());
+        for finding in &audit.findings {
+            let fixable = if finding.fixable && !fix {
+                " (fixable)"
+            } else {
+                ""
+            };
+            println!(
+                "  {}: {}{}\n     → {}",
+                finding.line, finding.message, fixable, finding.suggestion
+            );
+        }
+        if fix && audit.fixable() > 0 {
+            fs::write(file, &audit.fixed)
+                .map_err(|e| format!("Failed to write {}: {}", file.display(), e))?;
+            fixed += audit.fixable();
+            left += audit.findings.len() - audit.fixable();
+        } else {
+            left += audit.findings.len();
+        }
+        println!();
+    }
+
+    if fixed > 0 {
+        println!("🔧 Fixed {} finding(s)", fixed);
+    }
+    if left > 0 {
+        return Err(if fix {
+            format!("{} finding(s) need fixing by hand", left)
+        } else {
+            format!(
+                "{} finding(s) found; `wrkflw audit --fix` rewrites the fixable ones",
+                left
+            )
+        });
+    }
+    if fixed == 0 {
+        println!("✅ No deprecations found in {} file(s)", files.len());
+    }
+    Ok(())
+}
+
+fn push_unique(files: &mut Vec<PathBuf>, file: PathBuf) {
+    if !files.contains(&file) {
+        files.push(file);
+    }
+}
+
+/// The `action.yml` of each local action `uses: ./...` in `workflow` refers to
+fn local_actions(workflow: &Path) -> Vec<PathBuf> {
+    let Ok(content) = fs::read_to_string(workflow) else {
+        return Vec::new();
+    };
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start().trim_start_matches("- ").trim_start();
+            let uses = line.strip_prefix("uses:")?.trim().trim_matches(['"', '\'']);
+            uses.starts_with("./").then(|| PathBuf::from(uses))
+        })
+        .filter_map(|dir| {
+            ["action.yml", "action.yaml"]
+                .iter()
+                .map(|file| dir.join(file))
+                .find(|file| file.is_file())
+        })
+        .collect()
+}
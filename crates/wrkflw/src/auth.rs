@@ -0,0 +1,80 @@
+use clap::Subcommand;
+use wrkflw_utils::auth::{self, TokenSource};
+
+#[derive(Debug, Subcommand)]
+pub enum AuthCommand {
+    /// Show where GitHub and GitLab tokens are looked for and which ones will be used
+    Status,
+}
+
+/// Status lines of one service: each source in order of precedence, the one whose
+/// token is used marked with an arrow
+fn status_lines(found: &[(TokenSource, Option<String>)]) -> Vec<String> {
+    let mut used = false;
+    found
+        .iter()
+        .map(|(source, token)| match token {
+            Some(token) if !used => {
+                used = true;
+                format!("  ✓ {} ({})  ← used", source, auth::mask(token))
+            }
+            Some(token) => format!("  ✓ {} ({})", source, auth::mask(token)),
+            None => format!("  ✗ {}", source),
+        })
+        .collect()
+}
+
+fn print_service(name: &str, sources: Vec<TokenSource>, login: &str) {
+    let found: Vec<(TokenSource, Option<String>)> = sources
+        .into_iter()
+        .map(|source| {
+            let token = auth::lookup(&source);
+            (source, token)
+        })
+        .collect();
+    println!("{}:", name);
+    for line in status_lines(&found) {
+        println!("{}", line);
+    }
+    if found.iter().all(|(_, token)| token.is_none()) {
+        println!(
+            "  No token found; run `{}` or set one of the variables above",
+            login
+        );
+    }
+}
+
+/// Execute a `wrkflw auth` subcommand
+pub fn handle_auth_command(command: &AuthCommand) {
+    let AuthCommand::Status = command;
+    print_service("GitHub", auth::github_sources(), "gh auth login");
+    println!();
+    print_service("GitLab", auth::gitlab_sources(), "glab auth login");
+    println!();
+    println!("These tokens are used to call the APIs; `wrkflw run` only exposes the GitHub token to steps with --github-token-from-env");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_lines() {
+        let found = vec![
+            (TokenSource::Env("GITHUB_TOKEN"), None),
+            (TokenSource::GhCli, Some("gho_1234567890abcdef".to_string())),
+            (
+                TokenSource::GhConfig("/home/a/.config/gh/hosts.yml".into()),
+                Some("gho_1234567890abcdef".to_string()),
+            ),
+        ];
+        assert_eq!(
+            status_lines(&found),
+            vec![
+                "  ✗ GITHUB_TOKEN environment variable",
+                "  ✓ `gh auth token` (gho_…cdef)  ← used",
+                "  ✓ gh config /home/a/.config/gh/hosts.yml (gho_…cdef)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! `wrkflw validate --baseline`: a file recording the findings a repository
+//! already has, so that validation only fails on new ones while the old ones are
+//! fixed over time.
+
+use crate::validation::{relative, ValidationReport};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path};
+use wrkflw_models::{Finding, Severity};
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub findings: Vec<BaselineEntry>,
+}
+
+/// A finding of a file; findings are matched on everything but their severity,
+/// which rules can be given in the project config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub path: String,
+    pub rule: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Baseline {
+    /// The baseline stored at `path`, or none if there's no file there yet
+    pub fn load(path: &Path) -> Result<Option<Self>, String> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| format!("Invalid baseline {}: {}", path.display(), e))
+    }
+
+    /// The findings of `report`, which suppressed findings aren't
+    pub fn of(report: &ValidationReport) -> Self {
+        let findings = report
+            .files
+            .iter()
+            .flat_map(|file| {
+                let path = baseline_path(&file.path);
+                file.findings.iter().map(move |finding| BaselineEntry {
+                    path: path.clone(),
+                    rule: finding.rule.clone(),
+                    severity: finding.severity,
+                    message: finding.message.clone(),
+                })
+            })
+            .collect();
+        Baseline { findings }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize baseline: {}", e))?;
+        std::fs::write(path, json + "\n")
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Move the findings of `report` that are in the baseline to the
+    /// `baselined` of their files; a finding the baseline has once is moved
+    /// once, so that another like it is still new. Returns how many findings of
+    /// the baseline weren't found anymore.
+    pub fn apply(&self, report: &mut ValidationReport) -> usize {
+        let mut remaining: HashMap<(String, String, String), usize> = HashMap::new();
+        for entry in &self.findings {
+            let key = (
+                entry.path.clone(),
+                entry.rule.clone(),
+                entry.message.clone(),
+            );
+            *remaining.entry(key).or_default() += 1;
+        }
+        report.move_to_baseline(|path, finding: &Finding| {
+            let key = (
+                baseline_path(path),
+                finding.rule.clone(),
+                finding.message.clone(),
+            );
+            match remaining.get_mut(&key) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    true
+                }
+                _ => false,
+            }
+        });
+        remaining.values().sum()
+    }
+}
+
+/// A path as stored in baselines: relative to the current directory, with `/`
+/// separators whatever the platform
+fn baseline_path(path: &Path) -> String {
+    relative(path)
+        .components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::FileReport;
+    use std::path::PathBuf;
+
+    fn report(messages: &[&str]) -> ValidationReport {
+        let mut file = FileReport {
+            path: PathBuf::from("./.github/workflows/ci.yml"),
+            ..FileReport::default()
+        };
+        for message in messages {
+            file.add(Finding {
+                rule: "jobs".to_string(),
+                severity: Severity::Error,
+                message: message.to_string(),
+            });
+        }
+        ValidationReport::new(vec![file])
+    }
+
+    #[test]
+    fn test_baseline_only_lets_new_findings_through() {
+        let baseline = Baseline::of(&report(&["Job 'a' is missing 'runs-on' field", "Fixed"]));
+        assert_eq!(baseline.findings[0].path, ".github/workflows/ci.yml");
+
+        let mut current = report(&[
+            "Job 'a' is missing 'runs-on' field",
+            "Job 'a' is missing 'runs-on' field",
+            "Job 'b' is missing 'runs-on' field",
+        ]);
+        assert_eq!(baseline.apply(&mut current), 1);
+        let file = &current.files[0];
+        assert_eq!(
+            file.issues,
+            vec![
+                "Job 'a' is missing 'runs-on' field",
+                "Job 'b' is missing 'runs-on' field",
+            ]
+        );
+        assert_eq!(file.baselined.len(), 1);
+        assert!(!current.valid);
+
+        let mut fixed = report(&["Job 'a' is missing 'runs-on' field"]);
+        baseline.apply(&mut fixed);
+        assert!(fixed.valid);
+        assert!(!fixed.fails(Severity::Info));
+    }
+}
@@ -0,0 +1,80 @@
+use clap::Subcommand;
+use std::time::Duration;
+use wrkflw_executor::action_cache::ActionCache;
+use wrkflw_utils::cache;
+
+#[derive(Debug, Subcommand)]
+pub enum CacheCommand {
+    /// Manage downloaded reusable workflows
+    Actions {
+        #[command(subcommand)]
+        command: ActionsCommand,
+    },
+
+    /// Remove the parsed and validated workflows cached in .wrkflw/cache
+    Clear,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ActionsCommand {
+    /// List cached refs and the commits they point to
+    List,
+
+    /// Remove cached files no ref points to anymore
+    Prune {
+        /// Also remove refs not used in this many days
+        #[arg(long, value_name = "DAYS", conflicts_with = "all")]
+        older_than: Option<u64>,
+
+        /// Remove everything
+        #[arg(long)]
+        all: bool,
+    },
+}
+
+/// Execute a `wrkflw cache` subcommand
+pub fn handle_cache_command(command: &CacheCommand) -> Result<(), String> {
+    let command = match command {
+        CacheCommand::Actions { command } => command,
+        CacheCommand::Clear => {
+            wrkflw_utils::cache::clear()
+                .map_err(|e| format!("Failed to remove {}: {}", cache::CACHE_DIR, e))?;
+            println!("🧹 Removed {}", cache::CACHE_DIR);
+            return Ok(());
+        }
+    };
+    let cache = ActionCache::new(ActionCache::default_root());
+    match command {
+        ActionsCommand::List => {
+            let cached = cache.list();
+            if cached.is_empty() {
+                println!("No actions cached in {}", cache.root().display());
+            }
+            for cached in cached {
+                let last_used = chrono::DateTime::<chrono::Local>::from(cached.last_used);
+                println!(
+                    "  {}  {}  last used {}",
+                    cached.repo_ref,
+                    &cached.commit[..cached.commit.len().min(12)],
+                    last_used.format("%Y-%m-%d %H:%M")
+                );
+            }
+            Ok(())
+        }
+        ActionsCommand::Prune { older_than, all } => {
+            let older_than = if *all {
+                Some(Duration::ZERO)
+            } else {
+                older_than.map(|days| Duration::from_secs(days * 24 * 60 * 60))
+            };
+            let summary = cache.prune(older_than)?;
+            println!(
+                "🧹 Removed {} ref(s) and {} cached commit(s) from {}",
+                summary.refs,
+                summary.objects,
+                cache.root().display()
+            );
+            Ok(())
+        }
+    }
+}
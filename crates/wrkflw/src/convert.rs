@@ -0,0 +1,69 @@
+use crate::validation::Platform;
+use clap::ValueEnum;
+use std::fs;
+use std::path::Path;
+use wrkflw_parser::convert;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ConvertFormat {
+    /// GitHub Actions workflow
+    Github,
+    /// GitLab CI/CD pipeline
+    Gitlab,
+}
+
+/// Convert `path` into the format `to`, writing the result to `output` or else
+/// stdout, and the constructs that weren't translated to stderr
+pub fn handle_convert_command(
+    from: Option<ConvertFormat>,
+    to: ConvertFormat,
+    path: &Path,
+    output: Option<&Path>,
+) -> Result<(), String> {
+    let from = match from {
+        Some(from) => from,
+        None => match Platform::of(path, None) {
+            Platform::GitHub => ConvertFormat::Github,
+            Platform::GitLab => ConvertFormat::Gitlab,
+            Platform::Azure | Platform::Jenkins | Platform::Action => {
+                return Err(format!(
+                "{} is neither a GitHub workflow nor a GitLab pipeline, so it can't be converted",
+                path.display()
+            ))
+            }
+        },
+    };
+    let conversion = match (from, to) {
+        (ConvertFormat::Github, ConvertFormat::Gitlab) => convert::github_to_gitlab(path)?,
+        (ConvertFormat::Gitlab, ConvertFormat::Github) => convert::gitlab_to_github(path)?,
+        _ => {
+            return Err(format!(
+                "{} is already a {} file; pick another --to",
+                path.display(),
+                match from {
+                    ConvertFormat::Github => "GitHub Actions",
+                    ConvertFormat::Gitlab => "GitLab CI/CD",
+                }
+            ))
+        }
+    };
+
+    match output {
+        Some(output) => {
+            fs::write(output, &conversion.yaml)
+                .map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+            eprintln!("Wrote {}", output.display());
+        }
+        None => print!("{}", conversion.yaml),
+    }
+    if !conversion.todos.is_empty() {
+        eprintln!(
+            "{} construct(s) need attention, marked with TODO comments:",
+            conversion.todos.len()
+        );
+        for todo in &conversion.todos {
+            eprintln!("  - {}", todo);
+        }
+    }
+    Ok(())
+}
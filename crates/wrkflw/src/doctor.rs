@@ -0,0 +1,342 @@
+//! `wrkflw doctor`: checks of what runs need from the host, each problem printed
+//! with how to fix it.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Command;
+use wrkflw_secrets::SecretManager;
+
+/// How long a single check may take
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Free space below which runs are likely to fail
+const LOW_DISK_SPACE: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Hosts runs fetch actions, workflows and images from
+const ENDPOINTS: &[(&str, &str)] = &[
+    ("github.com", "https://github.com"),
+    ("GitHub API", "https://api.github.com"),
+    ("Docker Hub", "https://registry-1.docker.io/v2/"),
+    ("GitHub Container Registry", "https://ghcr.io/v2/"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// The result of one check
+struct Check {
+    level: Level,
+    message: String,
+    /// What to do about a warning or error
+    fix: Option<String>,
+}
+
+impl Check {
+    fn ok(message: impl Into<String>) -> Self {
+        Check {
+            level: Level::Ok,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn warning(message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Check {
+            level: Level::Warning,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn error(message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Check {
+            level: Level::Error,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn print(&self) {
+        let icon = match self.level {
+            Level::Ok => "✅",
+            Level::Warning => "⚠️ ",
+            Level::Error => "❌",
+        };
+        println!("  {} {}", icon, self.message);
+        if let Some(fix) = &self.fix {
+            println!("     → {}", fix);
+        }
+    }
+}
+
+/// Run `wrkflw doctor`, failing when a check found an error
+pub async fn handle_doctor_command() -> Result<(), String> {
+    let sections = [
+        ("Container runtimes", container_runtimes().await),
+        ("Disk space", disk_space()),
+        ("Network", network().await),
+        ("Tools", tools().await),
+        ("Secret providers", secret_providers().await),
+    ];
+
+    let (mut warnings, mut errors) = (0, 0);
+    for (title, checks) in &sections {
+        println!("{}", title);
+        for check in checks {
+            check.print();
+            match check.level {
+                Level::Ok => {}
+                Level::Warning => warnings += 1,
+                Level::Error => errors += 1,
+            }
+        }
+        println!();
+    }
+
+    if errors > 0 {
+        return Err(format!(
+            "{} problem(s) and {} warning(s) found",
+            errors, warnings
+        ));
+    }
+    if warnings > 0 {
+        println!("🩺 No problems found, {} warning(s)", warnings);
+    } else {
+        println!("🩺 No problems found");
+    }
+    Ok(())
+}
+
+/// The output of a command, or why it couldn't be run or failed
+async fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = tokio::time::timeout(
+        CHECK_TIMEOUT,
+        Command::new(program).args(args).kill_on_drop(true).output(),
+    )
+    .await
+    .map_err(|_| format!("`{} {}` timed out", program, args.join(" ")))?
+    .map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => "not installed".to_string(),
+        _ => e.to_string(),
+    })?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if output.status.success() {
+        // Some tools, like java, print their version to stderr
+        Ok(if stdout.is_empty() { stderr } else { stdout })
+    } else {
+        Err(if stderr.is_empty() { stdout } else { stderr })
+    }
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default().trim()
+}
+
+async fn container_runtimes() -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let docker = match run("docker", &["version", "--format", "{{.Server.Version}}"]).await {
+        Ok(version) => Check::ok(format!("Docker {}", first_line(&version))),
+        Err(e) if e == "not installed" => Check::warning(
+            "Docker is not installed",
+            "Install Docker (https://docs.docker.com/get-docker/) to run jobs with --runtime docker",
+        ),
+        Err(e) if e.contains("permission denied") => Check::error(
+            format!("Docker: {}", first_line(&e)),
+            "Add yourself to the docker group (`sudo usermod -aG docker $USER`) and log in again",
+        ),
+        Err(e) => Check::error(
+            format!("Docker daemon is not reachable: {}", first_line(&e)),
+            "Start Docker Desktop, or the daemon with `sudo systemctl start docker`",
+        ),
+    };
+    let docker_ok = docker.level == Level::Ok;
+    checks.push(docker);
+    if docker_ok {
+        checks.push(match run("docker", &["buildx", "version"]).await {
+            Ok(version) => Check::ok(format!("docker buildx {}", first_line(&version))),
+            Err(_) => Check::warning(
+                "docker buildx is not installed; images are built with the legacy builder",
+                "Install the buildx plugin (https://github.com/docker/buildx#installing)",
+            ),
+        });
+    }
+
+    let podman = match run("podman", &["version", "--format", "{{.Version}}"]).await {
+        Ok(version) => Check::ok(format!("Podman {}", first_line(&version))),
+        Err(e) if e == "not installed" => Check::warning(
+            "Podman is not installed",
+            "Install Podman (https://podman.io/getting-started/installation) to run jobs with --runtime podman",
+        ),
+        Err(e) if e.contains("machine") => Check::error(
+            format!("Podman machine is not running: {}", first_line(&e)),
+            "Start it with `podman machine init && podman machine start`",
+        ),
+        Err(e) => Check::error(
+            format!("Podman doesn't work: {}", first_line(&e)),
+            "Check `podman info` for what's wrong",
+        ),
+    };
+    let podman_ok = podman.level == Level::Ok;
+    checks.push(podman);
+
+    if !docker_ok && !podman_ok {
+        checks.push(Check::warning(
+            "No container runtime is usable; only emulation runs will work",
+            "Fix Docker or Podman as above, or run with --runtime emulation",
+        ));
+    }
+    checks
+}
+
+fn disk_space() -> Vec<Check> {
+    let wrkflw_dir = dirs::home_dir().unwrap_or_default().join(".wrkflw");
+    let dirs = [
+        ("job workspaces", std::env::temp_dir()),
+        ("caches", existing_ancestor(&wrkflw_dir)),
+    ];
+    dirs.iter()
+        .map(|(name, dir)| match free_space(dir) {
+            Some(free) if free < LOW_DISK_SPACE => Check::warning(
+                format!(
+                    "Only {} free for {} ({})",
+                    human_size(free),
+                    name,
+                    dir.display()
+                ),
+                "Free up space, e.g. with `wrkflw images prune`, `wrkflw cache actions prune` or `docker system prune`",
+            ),
+            Some(free) => Check::ok(format!(
+                "{} free for {} ({})",
+                human_size(free),
+                name,
+                dir.display()
+            )),
+            None => Check::warning(
+                format!("Can't tell the free space for {} ({})", name, dir.display()),
+                format!("Check it with `df -h {}`", dir.display()),
+            ),
+        })
+        .collect()
+}
+
+/// `path`, or the closest of its parents that exists
+fn existing_ancestor(path: &Path) -> PathBuf {
+    path.ancestors()
+        .find(|dir| dir.exists())
+        .unwrap_or(path)
+        .to_path_buf()
+}
+
+#[cfg(unix)]
+fn free_space(dir: &Path) -> Option<u64> {
+    let stats = nix::sys::statvfs::statvfs(dir).ok()?;
+    Some(stats.blocks_available() as u64 * stats.fragment_size() as u64)
+}
+
+#[cfg(not(unix))]
+fn free_space(_dir: &Path) -> Option<u64> {
+    None
+}
+
+fn human_size(bytes: u64) -> String {
+    const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+    format!("{:.1} GiB", bytes as f64 / GIB)
+}
+
+async fn network() -> Vec<Check> {
+    let client = match reqwest::Client::builder().timeout(CHECK_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            return vec![Check::error(
+                format!("Can't create an HTTP client: {}", e),
+                "Check your TLS setup",
+            )]
+        }
+    };
+    let mut checks = Vec::new();
+    for (name, url) in ENDPOINTS {
+        // Any response will do: registries answer 401 without credentials
+        checks.push(match client.head(*url).send().await {
+            Ok(_) => Check::ok(format!("{} is reachable", name)),
+            Err(e) => Check::warning(
+                format!("{} is not reachable: {}", name, e),
+                "Check your connection and HTTPS_PROXY; runs can use cached actions with --offline",
+            ),
+        });
+    }
+    checks
+}
+
+async fn tools() -> Vec<Check> {
+    let mut checks = vec![
+        match run("git", &["--version"]).await {
+            Ok(version) => Check::ok(first_line(&version).to_string()),
+            Err(e) => Check::error(
+                format!("git: {}", first_line(&e)),
+                "Install git (https://git-scm.com/downloads); checkouts and remote actions need it",
+            ),
+        },
+        match run("bash", &["--version"]).await {
+            Ok(version) => Check::ok(first_line(&version).to_string()),
+            Err(e) => Check::error(
+                format!("bash: {}", first_line(&e)),
+                "Install bash; emulated `run:` steps use it",
+            ),
+        },
+    ];
+
+    // Emulated jobs use the host's toolchains, unless `actions/setup-*` installs one
+    let setup = if wrkflw_runtime::toolcache::supported_host() {
+        "or let `actions/setup-*` steps install it into the tool cache"
+    } else {
+        "`actions/setup-*` steps can't install it on this host"
+    };
+    for (name, program, args) in [
+        ("Node.js", "node", &["--version"][..]),
+        ("Python", "python3", &["--version"][..]),
+        ("Go", "go", &["version"][..]),
+        ("Java", "java", &["-version"][..]),
+    ] {
+        checks.push(match run(program, args).await {
+            Ok(version) if first_line(&version).contains(name) => {
+                Check::ok(first_line(&version).to_string())
+            }
+            Ok(version) => Check::ok(format!("{} {}", name, first_line(&version))),
+            Err(_) => Check::warning(
+                format!("{} is not installed; emulated jobs can't use it", name),
+                format!("Install {} {}", name, setup),
+            ),
+        });
+    }
+    checks
+}
+
+async fn secret_providers() -> Vec<Check> {
+    let manager = match SecretManager::default().await {
+        Ok(manager) => manager,
+        Err(e) => {
+            return vec![Check::error(
+                format!("Can't set up the secret providers: {}", e),
+                "Check the secrets configuration",
+            )]
+        }
+    };
+    let mut results: Vec<_> = manager.health_check().await.into_iter().collect();
+    results.sort_by(|(a, _), (b, _)| a.cmp(b));
+    results
+        .into_iter()
+        .map(|(provider, result)| match result {
+            Ok(()) => Check::ok(format!("{} provider is healthy", provider)),
+            Err(e) => Check::error(
+                format!("{} provider: {}", provider, e),
+                format!("Check the settings of the {} provider", provider),
+            ),
+        })
+        .collect()
+}
@@ -0,0 +1,622 @@
+//! `wrkflw estimate`: what a GitHub workflow costs on GitHub-hosted runners. Each
+//! job takes as long as in the runs recorded for it, or an assumed time, on the
+//! runner its `runs-on` labels ask for; a run is billed by the minute, per job and
+//! matrix instance, at GitHub's rate for the runner. How often the triggers fire
+//! in a month comes from the `schedule` crons, and from what's given for other
+//! events.
+
+use crate::explain;
+use crate::validation::{relative, Platform};
+use chrono::{Duration, Utc};
+use clap::ValueEnum;
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use serde::Serialize;
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use wrkflw_parser::cron::CronSchedule;
+use wrkflw_parser::workflow::{parse_workflow, Job};
+
+lazy_static! {
+    static ref MATRIX_REFERENCE: Regex =
+        Regex::new(r"\$\{\{\s*matrix\.([A-Za-z0-9_-]+)\s*\}\}").unwrap();
+}
+
+/// GitHub's per-minute rates in USD of its hosted runners, by the kind of runner
+/// `runner_kind` gives labels
+const PRICES: &[(&str, f64)] = &[
+    ("linux", 0.008),
+    ("linux-arm", 0.005),
+    ("windows", 0.016),
+    ("windows-arm", 0.010),
+    ("macos", 0.08),
+    ("macos-large", 0.12),
+    ("macos-xlarge", 0.16),
+    ("self-hosted", 0.0),
+];
+
+/// Days in the month runs are counted for
+const DAYS_PER_MONTH: i64 = 30;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum EstimateFormat {
+    /// Tables of the jobs and the events
+    Table,
+    /// The estimate as JSON
+    Json,
+}
+
+/// What `wrkflw estimate` was given
+pub struct EstimateOptions {
+    pub path: PathBuf,
+    /// Runs a month of events, over what their crons give
+    pub per_month: Vec<(String, u64)>,
+    /// Minutes of the jobs no run is recorded for
+    pub assumed_minutes: f64,
+    /// Per-minute rates, over GitHub's, by kind of runner
+    pub prices: Vec<(String, f64)>,
+    pub format: EstimateFormat,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Estimate {
+    pub workflow: String,
+    /// The jobs, one entry per kind of runner their instances run on
+    pub jobs: Vec<JobEstimate>,
+    pub per_run: Total,
+    pub events: Vec<EventEstimate>,
+    /// The runs of the events whose runs a month are known
+    pub per_month: Option<Total>,
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JobEstimate {
+    pub job: String,
+    pub runner: String,
+    pub instances: usize,
+    /// Minutes each instance takes
+    pub minutes: f64,
+    /// Whether the minutes are those of recorded runs
+    pub recorded: bool,
+    pub billable_minutes: u64,
+    pub cost: f64,
+    /// Events the job doesn't run on, by its `if`
+    #[serde(skip)]
+    skipped_on: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EventEstimate {
+    pub event: String,
+    pub runs_per_month: Option<u64>,
+    /// `given`, or `cron` for runs counted from the schedule
+    pub source: Option<String>,
+    pub billable_minutes: u64,
+    pub cost: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Total {
+    pub runs: u64,
+    pub billable_minutes: u64,
+    pub cost: f64,
+}
+
+/// `EVENT=N` of `--per-month`
+pub fn parse_per_month(s: &str) -> Result<(String, u64), String> {
+    let (event, runs) = s
+        .split_once('=')
+        .ok_or_else(|| format!("invalid EVENT=N: no `=` found in `{}`", s))?;
+    let runs = runs
+        .parse()
+        .map_err(|_| format!("invalid number of runs `{}`", runs))?;
+    Ok((event.to_string(), runs))
+}
+
+/// `RUNNER=USD` of `--price`
+pub fn parse_price(s: &str) -> Result<(String, f64), String> {
+    let (runner, price) = s
+        .split_once('=')
+        .ok_or_else(|| format!("invalid RUNNER=USD: no `=` found in `{}`", s))?;
+    if !PRICES.iter().any(|(kind, _)| *kind == runner) {
+        let kinds: Vec<&str> = PRICES.iter().map(|(kind, _)| *kind).collect();
+        return Err(format!(
+            "unknown runner `{}`; expected one of {}",
+            runner,
+            kinds.join(", ")
+        ));
+    }
+    let price = price
+        .parse::<f64>()
+        .ok()
+        .filter(|price| *price >= 0.0)
+        .ok_or_else(|| format!("invalid price `{}`", price))?;
+    Ok((runner.to_string(), price))
+}
+
+/// Print the estimate of the workflow at `options.path`
+pub fn handle_estimate_command(options: &EstimateOptions) -> Result<(), String> {
+    let estimate = estimate(options)?;
+    match options.format {
+        EstimateFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&estimate)
+                .map_err(|e| format!("Failed to serialize estimate: {}", e))?
+        ),
+        EstimateFormat::Table => print!("{}", render(&estimate)),
+    }
+    Ok(())
+}
+
+fn estimate(options: &EstimateOptions) -> Result<Estimate, String> {
+    let path = &options.path;
+    let platform = Platform::of(path, None);
+    if platform != Platform::GitHub {
+        return Err(format!(
+            "{} is a {} file; only GitHub workflows can be estimated",
+            path.display(),
+            platform.name()
+        ));
+    }
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let raw: Value = serde_yaml::from_str(&content).map_err(|e| format!("Invalid YAML: {}", e))?;
+    let workflow = parse_workflow(path)?;
+    let runs = explain::recorded_runs(path);
+    let prices: BTreeMap<&str, f64> = PRICES
+        .iter()
+        .copied()
+        .chain(
+            options
+                .prices
+                .iter()
+                .map(|(kind, price)| (kind.as_str(), *price)),
+        )
+        .collect();
+    let events: Vec<String> = explain::triggers(&raw)
+        .into_iter()
+        .map(|trigger| trigger.event)
+        .collect();
+
+    let mut notes = Vec::new();
+    let mut jobs = Vec::new();
+    let mut ids: Vec<&String> = workflow.jobs.keys().collect();
+    ids.sort();
+    for id in ids {
+        let job = &workflow.jobs[id];
+        let raw_job = &raw["jobs"][id.as_str()];
+        if let Some(uses) = &job.uses {
+            notes.push(format!(
+                "Job '{}' calls {}, whose jobs aren't estimated",
+                id, uses
+            ));
+            continue;
+        }
+        let (minutes, recorded) = match explain::job_duration(&runs, id, raw_job) {
+            Some(secs) => (secs / 60.0, true),
+            None => (options.assumed_minutes, false),
+        };
+        let skipped_on: Vec<String> = events
+            .iter()
+            .filter(|event| !explain::runs_on_event(raw_job, event))
+            .cloned()
+            .collect();
+        let mut by_runner: BTreeMap<&str, usize> = BTreeMap::new();
+        for labels in instance_labels(job) {
+            let (kind, known) = runner_kind(&labels);
+            if !known {
+                let note = format!(
+                    "Job '{}' runs on '{}', which isn't a GitHub-hosted runner; it's priced as {}",
+                    id,
+                    labels.join(", "),
+                    kind
+                );
+                if !notes.contains(&note) {
+                    notes.push(note);
+                }
+            }
+            *by_runner.entry(kind).or_default() += 1;
+        }
+        for (kind, instances) in by_runner {
+            // Each instance is billed by the minute, rounded up
+            let billable_minutes = if kind == "self-hosted" {
+                0
+            } else {
+                instances as u64 * minutes.ceil().max(1.0) as u64
+            };
+            jobs.push(JobEstimate {
+                job: id.clone(),
+                runner: kind.to_string(),
+                instances,
+                minutes,
+                recorded,
+                billable_minutes,
+                cost: billable_minutes as f64 * prices[kind],
+                skipped_on: skipped_on.clone(),
+            });
+        }
+    }
+    let per_run = Total {
+        runs: 1,
+        billable_minutes: jobs.iter().map(|job| job.billable_minutes).sum(),
+        cost: jobs.iter().map(|job| job.cost).sum(),
+    };
+
+    let given: BTreeMap<&str, u64> = options
+        .per_month
+        .iter()
+        .map(|(event, runs)| (event.as_str(), *runs))
+        .collect();
+    for event in given.keys() {
+        if !events.iter().any(|e| e == event) {
+            notes.push(format!(
+                "The workflow isn't triggered by {}; its --per-month runs are left out",
+                event
+            ));
+        }
+    }
+    let mut per_month: Option<Total> = None;
+    let mut event_estimates = Vec::new();
+    for event in &events {
+        let (runs_per_month, source) = match given.get(event.as_str()) {
+            Some(runs) => (Some(*runs), Some("given".to_string())),
+            None if event == "schedule" => (Some(scheduled_runs(&raw)), Some("cron".to_string())),
+            None => (None, None),
+        };
+        let (billable_minutes, cost) = jobs
+            .iter()
+            .filter(|job| !job.skipped_on.contains(event))
+            .fold((0, 0.0), |(minutes, cost), job| {
+                (minutes + job.billable_minutes, cost + job.cost)
+            });
+        let runs = runs_per_month.unwrap_or_default();
+        if runs_per_month.is_some() {
+            let total = per_month.get_or_insert_with(Total::default);
+            total.runs += runs;
+            total.billable_minutes += runs * billable_minutes;
+            total.cost += runs as f64 * cost;
+        }
+        event_estimates.push(EventEstimate {
+            event: event.clone(),
+            runs_per_month,
+            source,
+            billable_minutes: runs * billable_minutes,
+            cost: runs as f64 * cost,
+        });
+    }
+    let unknown: Vec<&str> = event_estimates
+        .iter()
+        .filter(|event| event.runs_per_month.is_none())
+        .map(|event| event.event.as_str())
+        .collect();
+    if !unknown.is_empty() {
+        notes.push(format!(
+            "Runs a month of {} aren't known; give them with --per-month {}=N",
+            unknown.join(", "),
+            unknown[0]
+        ));
+    }
+
+    Ok(Estimate {
+        workflow: relative(path).display().to_string(),
+        jobs,
+        per_run,
+        events: event_estimates,
+        per_month,
+        notes,
+    })
+}
+
+/// The `runs-on` labels of each instance of a job, with its matrix values put in
+fn instance_labels(job: &Job) -> Vec<Vec<String>> {
+    let runs_on = job.runs_on.clone().unwrap_or_default();
+    let combinations = job
+        .matrix_config()
+        .and_then(|matrix| wrkflw_matrix::expand_matrix(&matrix).ok())
+        .filter(|combinations| !combinations.is_empty());
+    let Some(combinations) = combinations else {
+        return vec![runs_on];
+    };
+    combinations
+        .iter()
+        .map(|combination| {
+            runs_on
+                .iter()
+                .map(|label| {
+                    MATRIX_REFERENCE
+                        .replace_all(label, |captures: &Captures| {
+                            match combination.values.get(&captures[1]) {
+                                Some(Value::String(value)) => value.clone(),
+                                Some(value) => serde_yaml::to_string(value)
+                                    .unwrap_or_default()
+                                    .trim()
+                                    .to_string(),
+                                None => captures[0].to_string(),
+                            }
+                        })
+                        .into_owned()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The kind of runner in `PRICES` that `labels` ask for, and whether it's one of
+/// GitHub's standard runners; other labels are taken for a Linux runner
+fn runner_kind(labels: &[String]) -> (&'static str, bool) {
+    if labels.iter().any(|label| label == "self-hosted") {
+        return ("self-hosted", true);
+    }
+    for label in labels {
+        let label = label.to_ascii_lowercase();
+        let kind = if label.starts_with("macos-") {
+            if label.ends_with("-xlarge") {
+                "macos-xlarge"
+            } else if label.ends_with("-large") {
+                "macos-large"
+            } else {
+                "macos"
+            }
+        } else if label.starts_with("windows-") {
+            if label.ends_with("-arm") {
+                "windows-arm"
+            } else {
+                "windows"
+            }
+        } else if label.starts_with("ubuntu-") {
+            if label.ends_with("-arm") {
+                "linux-arm"
+            } else {
+                "linux"
+            }
+        } else {
+            continue;
+        };
+        return (kind, true);
+    }
+    ("linux", false)
+}
+
+/// How often the `schedule` crons of a workflow fire in a month
+fn scheduled_runs(workflow: &Value) -> u64 {
+    let now = Utc::now();
+    let end = now + Duration::days(DAYS_PER_MONTH);
+    workflow["on"]["schedule"]
+        .as_sequence()
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.get("cron").and_then(Value::as_str))
+        .filter_map(|cron| CronSchedule::parse(cron).ok())
+        .map(|schedule| {
+            std::iter::successors(schedule.next_after(&now), |last| schedule.next_after(last))
+                .take_while(|time| *time <= end)
+                .count() as u64
+        })
+        .sum()
+}
+
+fn render(estimate: &Estimate) -> String {
+    let mut out = format!("{}\n\n", estimate.workflow);
+    let mut rows = vec![vec![
+        "Job".to_string(),
+        "Runner".to_string(),
+        "Instances".to_string(),
+        "Time each".to_string(),
+        "Billable minutes".to_string(),
+        "Cost".to_string(),
+    ]];
+    for job in &estimate.jobs {
+        let source = if job.recorded { "recorded" } else { "assumed" };
+        rows.push(vec![
+            job.job.clone(),
+            job.runner.clone(),
+            job.instances.to_string(),
+            format!(
+                "{} ({})",
+                explain::format_duration(job.minutes * 60.0),
+                source
+            ),
+            job.billable_minutes.to_string(),
+            format_cost(job.cost),
+        ]);
+    }
+    rows.push(vec![
+        "Per run".to_string(),
+        String::new(),
+        String::new(),
+        String::new(),
+        estimate.per_run.billable_minutes.to_string(),
+        format_cost(estimate.per_run.cost),
+    ]);
+    out.push_str(&table(&rows));
+
+    if !estimate.events.is_empty() {
+        let mut rows = vec![vec![
+            "Event".to_string(),
+            "Runs a month".to_string(),
+            "Billable minutes".to_string(),
+            "Cost".to_string(),
+        ]];
+        for event in &estimate.events {
+            let known = |text: String| {
+                if event.runs_per_month.is_some() {
+                    text
+                } else {
+                    "-".to_string()
+                }
+            };
+            rows.push(vec![
+                event.event.clone(),
+                match (&event.runs_per_month, &event.source) {
+                    (Some(runs), Some(source)) => format!("{} ({})", runs, source),
+                    _ => "?".to_string(),
+                },
+                known(event.billable_minutes.to_string()),
+                known(format_cost(event.cost)),
+            ]);
+        }
+        if let Some(total) = &estimate.per_month {
+            rows.push(vec![
+                "Per month".to_string(),
+                total.runs.to_string(),
+                total.billable_minutes.to_string(),
+                format_cost(total.cost),
+            ]);
+        }
+        out.push('\n');
+        out.push_str(&table(&rows));
+    }
+
+    if !estimate.notes.is_empty() {
+        out.push('\n');
+    }
+    for note in &estimate.notes {
+        out.push_str(&format!("ℹ️  {}\n", note));
+    }
+    out.push_str(
+        "\nJobs are billed per started minute at GitHub's rates for hosted runners, before the minutes plans include.\n",
+    );
+    out
+}
+
+/// Rows with their columns padded to the widest cell
+fn table(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|column| {
+            rows.iter()
+                .filter_map(|row| row.get(column))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    rows.iter()
+        .map(|row| {
+            let line: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect();
+            format!("{}\n", line.join("  ").trim_end())
+        })
+        .collect()
+}
+
+fn format_cost(cost: f64) -> String {
+    if cost > 0.0 && cost < 1.0 {
+        format!("${:.3}", cost)
+    } else {
+        format!("${:.2}", cost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(labels: &[&str]) -> Vec<String> {
+        labels.iter().map(|label| label.to_string()).collect()
+    }
+
+    #[test]
+    fn test_runner_kind() {
+        assert_eq!(runner_kind(&labels(&["ubuntu-latest"])), ("linux", true));
+        assert_eq!(
+            runner_kind(&labels(&["ubuntu-24.04-arm"])),
+            ("linux-arm", true)
+        );
+        assert_eq!(runner_kind(&labels(&["windows-2022"])), ("windows", true));
+        assert_eq!(
+            runner_kind(&labels(&["macos-14-xlarge"])),
+            ("macos-xlarge", true)
+        );
+        assert_eq!(
+            runner_kind(&labels(&["macos-latest-large"])),
+            ("macos-large", true)
+        );
+        assert_eq!(
+            runner_kind(&labels(&["self-hosted", "linux"])),
+            ("self-hosted", true)
+        );
+        assert_eq!(runner_kind(&labels(&["big-runner"])), ("linux", false));
+    }
+
+    #[test]
+    fn test_estimate() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ci.yml");
+        std::fs::write(
+            &path,
+            r#"name: CI
+on:
+  push:
+  pull_request:
+  schedule:
+    - cron: "0 0 * * *"
+jobs:
+  build:
+    runs-on: ${{ matrix.os }}
+    strategy:
+      matrix:
+        os: [ubuntu-latest, macos-latest]
+        node: [18, 20]
+    steps:
+      - run: npm test
+  deploy:
+    if: github.event_name == 'push'
+    runs-on: ubuntu-latest
+    steps:
+      - run: ./deploy.sh
+"#,
+        )
+        .unwrap();
+        let estimate = estimate(&EstimateOptions {
+            path,
+            per_month: vec![("push".to_string(), 100)],
+            assumed_minutes: 2.5,
+            prices: vec![("linux".to_string(), 0.01)],
+            format: EstimateFormat::Json,
+        })
+        .unwrap();
+        let jobs: Vec<(&str, &str, usize, u64)> = estimate
+            .jobs
+            .iter()
+            .map(|job| {
+                (
+                    job.job.as_str(),
+                    job.runner.as_str(),
+                    job.instances,
+                    job.billable_minutes,
+                )
+            })
+            .collect();
+        assert_eq!(
+            jobs,
+            vec![
+                ("build", "linux", 2, 6),
+                ("build", "macos", 2, 6),
+                ("deploy", "linux", 1, 3),
+            ]
+        );
+        assert_eq!(estimate.per_run.billable_minutes, 15);
+        assert!((estimate.per_run.cost - (9.0 * 0.01 + 6.0 * 0.08)).abs() < 1e-9);
+
+        let push = &estimate.events[0];
+        assert_eq!(
+            (push.runs_per_month, push.billable_minutes),
+            (Some(100), 1500)
+        );
+        // The deploy job only runs on pushes
+        let schedule = &estimate.events[2];
+        assert_eq!(schedule.source.as_deref(), Some("cron"));
+        let nightly = schedule.runs_per_month.unwrap();
+        assert!((29..=30).contains(&nightly));
+        assert_eq!(schedule.billable_minutes, nightly * 12);
+        assert_eq!(estimate.events[1].runs_per_month, None);
+        assert_eq!(estimate.per_month.as_ref().unwrap().runs, 100 + nightly);
+        assert!(estimate.notes[0].contains("--per-month pull_request=N"));
+    }
+}
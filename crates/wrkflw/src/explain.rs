@@ -0,0 +1,628 @@
+//! `wrkflw explain`: what a GitHub workflow does, in words: what triggers it,
+//! which jobs run when and in what order, the actions it uses, the secrets and
+//! variables it reads, and how long it took in the runs recorded for it.
+
+use crate::validation::{relative, Platform};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use wrkflw_executor::runs::{RunRecord, RunStore};
+use wrkflw_parser::expression::{self, BinaryOp, Expr};
+use wrkflw_parser::pin::UsesRef;
+
+lazy_static! {
+    static ref CONTEXT_REFERENCE: Regex =
+        Regex::new(r"\b(secrets|vars)\.([A-Za-z_][A-Za-z0-9_]*)").unwrap();
+}
+
+/// Filters of a trigger, in the order they're shown
+const TRIGGER_FILTERS: &[&str] = &[
+    "types",
+    "branches",
+    "branches-ignore",
+    "tags",
+    "tags-ignore",
+    "paths",
+    "paths-ignore",
+    "workflows",
+];
+
+/// Recorded runs the estimated runtime is taken from, newest first
+const RUNS_FOR_ESTIMATE: usize = 10;
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Trigger {
+    pub(crate) event: String,
+    /// `branches: main, release/**` and the like
+    pub(crate) filters: Vec<String>,
+}
+
+/// When a job's `if` lets it run, as far as its comparisons tell
+#[derive(Debug, Default, PartialEq)]
+struct Condition {
+    events: Vec<String>,
+    excluded_events: Vec<String>,
+    branches: Vec<String>,
+    tags: Vec<String>,
+}
+
+/// Print what the workflow at `path` does
+pub fn handle_explain_command(path: &Path) -> Result<(), String> {
+    let platform = Platform::of(path, None);
+    if platform != Platform::GitHub {
+        return Err(format!(
+            "{} is a {} file; only GitHub workflows can be explained",
+            path.display(),
+            platform.name()
+        ));
+    }
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let workflow: Value =
+        serde_yaml::from_str(&content).map_err(|e| format!("Invalid YAML: {}", e))?;
+    let jobs = workflow
+        .get("jobs")
+        .and_then(Value::as_mapping)
+        .ok_or_else(|| format!("{} has no jobs", path.display()))?;
+    let graph = wrkflw_executor::build_graph(path)?;
+    let runs = recorded_runs(path);
+
+    match workflow.get("name").and_then(Value::as_str) {
+        Some(name) => println!("{} ({})", name, relative(path).display()),
+        None => println!("{}", relative(path).display()),
+    }
+
+    println!("\nTriggered by");
+    let triggers = triggers(&workflow);
+    if triggers.is_empty() {
+        println!("  nothing: the workflow has no `on` events");
+    }
+    let width = triggers.iter().map(|t| t.event.len()).max().unwrap_or(0);
+    for trigger in &triggers {
+        let line = format!(
+            "  {:<width$}  {}",
+            trigger.event,
+            trigger.filters.join("; "),
+            width = width
+        );
+        println!("{}", line.trim_end());
+    }
+
+    println!("\nJobs, in the order they run");
+    for (idx, stage) in graph.stages.iter().enumerate() {
+        println!("  {}.", idx + 1);
+        for graph_job in stage {
+            let Some(job) = jobs.get(graph_job.id.as_str()) else {
+                continue;
+            };
+            let mut details = vec![run_when(job)];
+            if !graph_job.needs.is_empty() {
+                details.push(format!("after {}", graph_job.needs.join(", ")));
+            }
+            if graph_job.instances.len() > 1 {
+                details.push(format!("{} matrix instances", graph_job.instances.len()));
+            }
+            if let Some(environment) = environment(job) {
+                details.push(format!("deploys to {}", environment));
+            }
+            if let Some(duration) = job_duration(&runs, &graph_job.id, job) {
+                details.push(format!("~{}", format_duration(duration)));
+            }
+            let runner = match (job.get("uses"), job.get("runs-on")) {
+                (Some(uses), _) => format!(" (calls {})", value_text(uses)),
+                (None, Some(runs_on)) => format!(" ({})", value_text(runs_on)),
+                (None, None) => String::new(),
+            };
+            println!("     {}{}: {}", graph_job.id, runner, details.join("; "));
+        }
+    }
+
+    println!("\nActions");
+    let actions = actions(&content, &workflow);
+    if actions.is_empty() {
+        println!("  none");
+    }
+    let width = actions.keys().map(String::len).max().unwrap_or(0);
+    for (uses, job_ids) in &actions {
+        println!("  {:<width$}  {}", uses, job_ids.join(", "), width = width);
+    }
+
+    let (secrets, vars, inherited) = references(&workflow);
+    println!("\nSecrets");
+    if secrets.is_empty() && inherited.is_empty() {
+        println!("  none");
+    }
+    let width = secrets.keys().map(String::len).max().unwrap_or(0);
+    for (secret, job_ids) in &secrets {
+        let automatic = if secret == "GITHUB_TOKEN" {
+            " (provided by GitHub)"
+        } else {
+            ""
+        };
+        println!(
+            "  {:<width$}  {}{}",
+            secret,
+            job_ids.join(", "),
+            automatic,
+            width = width
+        );
+    }
+    if !inherited.is_empty() {
+        println!(
+            "  all of the caller's, passed on by {}",
+            inherited.join(", ")
+        );
+    }
+    println!("\nVariables");
+    if vars.is_empty() {
+        println!("  none");
+    }
+    let width = vars.keys().map(String::len).max().unwrap_or(0);
+    for (var, job_ids) in &vars {
+        println!("  {:<width$}  {}", var, job_ids.join(", "), width = width);
+    }
+
+    println!();
+    match estimate(&runs) {
+        Some((duration, count)) => println!(
+            "Estimated runtime: {}, the median of the last {} successful run(s)",
+            format_duration(duration),
+            count
+        ),
+        None => println!("Estimated runtime: unknown, as no successful run is recorded"),
+    }
+    Ok(())
+}
+
+pub(crate) fn triggers(workflow: &Value) -> Vec<Trigger> {
+    let event = |event: &str| Trigger {
+        event: event.to_string(),
+        filters: Vec::new(),
+    };
+    match workflow.get("on") {
+        Some(Value::String(name)) => vec![event(name)],
+        Some(Value::Sequence(names)) => names.iter().filter_map(Value::as_str).map(event).collect(),
+        Some(Value::Mapping(events)) => events
+            .iter()
+            .filter_map(|(name, config)| {
+                let name = name.as_str()?;
+                Some(Trigger {
+                    event: name.to_string(),
+                    filters: trigger_filters(config),
+                })
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn trigger_filters(config: &Value) -> Vec<String> {
+    // `schedule` is a list of crons
+    if let Some(entries) = config.as_sequence() {
+        let crons: Vec<String> = entries
+            .iter()
+            .filter_map(|entry| entry.get("cron").and_then(Value::as_str))
+            .map(str::to_string)
+            .collect();
+        return if crons.is_empty() {
+            Vec::new()
+        } else {
+            vec![format!("cron: {}", crons.join(", "))]
+        };
+    }
+    let mut filters: Vec<String> = TRIGGER_FILTERS
+        .iter()
+        .filter_map(|filter| {
+            let value = config.get(*filter)?;
+            Some(format!("{}: {}", filter, value_text(value)))
+        })
+        .collect();
+    if let Some(inputs) = config.get("inputs").and_then(Value::as_mapping) {
+        let inputs: Vec<String> = inputs
+            .iter()
+            .filter_map(|(name, input)| {
+                let name = name.as_str()?;
+                let required = input.get("required").and_then(Value::as_bool) == Some(true);
+                Some(if required {
+                    format!("{} (required)", name)
+                } else {
+                    name.to_string()
+                })
+            })
+            .collect();
+        filters.push(format!("inputs: {}", inputs.join(", ")));
+    }
+    filters
+}
+
+/// When a job runs, in words
+fn run_when(job: &Value) -> String {
+    let Some(condition) = job.get("if").map(value_text) else {
+        return "runs on every trigger".to_string();
+    };
+    let parsed = condition_of(&condition);
+    let mut when = String::new();
+    if !parsed.events.is_empty() {
+        when = parsed.events.join(" or ");
+    } else if !parsed.excluded_events.is_empty() {
+        when = format!("every trigger but {}", parsed.excluded_events.join(" or "));
+    }
+    if !parsed.branches.is_empty() {
+        when = format!("{} to {}", or_anything(when), parsed.branches.join(" or "));
+    }
+    if !parsed.tags.is_empty() {
+        when = format!("{} of tags {}", or_anything(when), parsed.tags.join(" or "));
+    }
+    if when.is_empty() {
+        format!("runs if `{}`", condition)
+    } else {
+        format!("runs on {}", when)
+    }
+}
+
+/// Whether a job's `if` lets it run on `event`, as far as its comparisons tell
+pub(crate) fn runs_on_event(job: &Value, event: &str) -> bool {
+    let Some(condition) = job.get("if").map(value_text) else {
+        return true;
+    };
+    let parsed = condition_of(&condition);
+    if !parsed.events.is_empty() {
+        parsed.events.iter().any(|e| e == event)
+    } else {
+        !parsed.excluded_events.iter().any(|e| e == event)
+    }
+}
+
+fn or_anything(when: String) -> String {
+    if when.is_empty() {
+        "anything".to_string()
+    } else {
+        when
+    }
+}
+
+/// The events and refs the comparisons of a job's `if` name
+fn condition_of(condition: &str) -> Condition {
+    let text = condition.trim();
+    let text = text
+        .strip_prefix("${{")
+        .and_then(|text| text.strip_suffix("}}"))
+        .unwrap_or(text);
+    let mut parsed = Condition::default();
+    let Ok(expr) = expression::parse(text) else {
+        return parsed;
+    };
+    let add = |list: &mut Vec<String>, value: &str| {
+        if !list.iter().any(|item| item == value) {
+            list.push(value.to_string());
+        }
+    };
+    expr.walk(&mut |node| match node {
+        Expr::Binary(left, op @ (BinaryOp::Equal | BinaryOp::NotEqual), right) => {
+            let (reference, value) = match (left.reference(), &**right, &**left) {
+                (Some(reference), Expr::String(value), _) => (reference, value),
+                (None, _, Expr::String(value)) => match right.reference() {
+                    Some(reference) => (reference, value),
+                    None => return,
+                },
+                _ => return,
+            };
+            match (reference.as_slice(), op) {
+                (["github", "event_name"], BinaryOp::Equal) => add(&mut parsed.events, value),
+                (["github", "event_name"], _) => add(&mut parsed.excluded_events, value),
+                (["github", "ref"], BinaryOp::Equal) => {
+                    if let Some(branch) = value.strip_prefix("refs/heads/") {
+                        add(&mut parsed.branches, branch);
+                    } else if let Some(tag) = value.strip_prefix("refs/tags/") {
+                        add(&mut parsed.tags, tag);
+                    }
+                }
+                (["github", "ref_name"], BinaryOp::Equal) => add(&mut parsed.branches, value),
+                _ => {}
+            }
+        }
+        Expr::Call(name, args) if name.eq_ignore_ascii_case("startsWith") => {
+            if let [subject, Expr::String(prefix)] = args.as_slice() {
+                if subject.reference().as_deref() == Some(&["github", "ref"][..]) {
+                    if let Some(tag) = prefix.strip_prefix("refs/tags/") {
+                        add(&mut parsed.tags, &format!("{}*", tag));
+                    } else if let Some(branch) = prefix.strip_prefix("refs/heads/") {
+                        add(&mut parsed.branches, &format!("{}*", branch));
+                    }
+                }
+            }
+        }
+        _ => {}
+    });
+    parsed
+}
+
+fn environment(job: &Value) -> Option<String> {
+    match job.get("environment")? {
+        Value::Mapping(environment) => environment
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        value => Some(value_text(value)),
+    }
+}
+
+/// The `uses` of the workflow's steps and jobs, with the jobs using them;
+/// pinned references are shown with the version their comment names
+fn actions(content: &str, workflow: &Value) -> BTreeMap<String, Vec<String>> {
+    let comments: BTreeMap<(String, String), String> = wrkflw_parser::pin::uses_refs(content)
+        .into_iter()
+        .filter_map(|uses| {
+            let version = uses.pinned_version()?.to_string();
+            Some(((uses.action, uses.git_ref), version))
+        })
+        .collect();
+    let mut actions: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let jobs = workflow.get("jobs").and_then(Value::as_mapping);
+    for (id, job) in jobs.into_iter().flatten() {
+        let Some(id) = id.as_str() else {
+            continue;
+        };
+        let steps = job.get("steps").and_then(Value::as_sequence);
+        let uses = job
+            .get("uses")
+            .into_iter()
+            .chain(
+                steps
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|step| step.get("uses")),
+            )
+            .filter_map(Value::as_str);
+        for uses in uses {
+            let shown = describe_uses(uses, &comments);
+            let job_ids = actions.entry(shown).or_default();
+            if !job_ids.iter().any(|job_id| job_id == id) {
+                job_ids.push(id.to_string());
+            }
+        }
+    }
+    actions
+}
+
+fn describe_uses(uses: &str, comments: &BTreeMap<(String, String), String>) -> String {
+    if uses.starts_with("./") {
+        return format!("{} (local)", uses);
+    }
+    if uses.starts_with("docker://") {
+        return uses.to_string();
+    }
+    let Some((action, git_ref)) = uses.split_once('@') else {
+        return format!("{} (no version)", uses);
+    };
+    let uses_ref = UsesRef {
+        line: 0,
+        action: action.to_string(),
+        git_ref: git_ref.to_string(),
+        comment: None,
+    };
+    if !uses_ref.is_pinned() {
+        return uses.to_string();
+    }
+    match comments.get(&(action.to_string(), git_ref.to_string())) {
+        Some(version) => format!("{}@{} (pinned to {})", action, &git_ref[..12], version),
+        None => format!("{}@{} (pinned)", action, &git_ref[..12]),
+    }
+}
+
+/// The secrets and variables each job reads, and the jobs passing on all
+/// secrets to the workflows they call; what's read at the workflow level is
+/// read by every job
+#[allow(clippy::type_complexity)]
+fn references(
+    workflow: &Value,
+) -> (
+    BTreeMap<String, Vec<String>>,
+    BTreeMap<String, Vec<String>>,
+    Vec<String>,
+) {
+    let mut secrets: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut vars: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut inherited = Vec::new();
+    let mut scan = |value: &Value, reader: &str| {
+        let text = serde_yaml::to_string(value).unwrap_or_default();
+        for captures in CONTEXT_REFERENCE.captures_iter(&text) {
+            let found = if &captures[1] == "secrets" {
+                &mut secrets
+            } else {
+                &mut vars
+            };
+            let readers = found.entry(captures[2].to_string()).or_default();
+            if !readers.iter().any(|existing| existing == reader) {
+                readers.push(reader.to_string());
+            }
+        }
+    };
+    for key in ["env", "defaults", "run-name", "concurrency"] {
+        if let Some(value) = workflow.get(key) {
+            scan(value, "all jobs");
+        }
+    }
+    let jobs = workflow.get("jobs").and_then(Value::as_mapping);
+    for (id, job) in jobs.into_iter().flatten() {
+        let Some(id) = id.as_str() else {
+            continue;
+        };
+        scan(job, id);
+        if job.get("secrets").and_then(Value::as_str) == Some("inherit") {
+            inherited.push(id.to_string());
+        }
+    }
+    (secrets, vars, inherited)
+}
+
+/// The recorded runs of the workflow at `path`, newest first
+pub(crate) fn recorded_runs(path: &Path) -> Vec<RunRecord> {
+    let Ok(project_dir) = std::env::current_dir() else {
+        return Vec::new();
+    };
+    let canonical = path.canonicalize().ok();
+    RunStore::for_project(&project_dir)
+        .list()
+        .into_iter()
+        .filter(|run| {
+            run.path == path || (canonical.is_some() && run.path.canonicalize().ok() == canonical)
+        })
+        .collect()
+}
+
+/// The median duration of the latest successful runs, and how many there were
+fn estimate(runs: &[RunRecord]) -> Option<(f64, usize)> {
+    let durations: Vec<f64> = runs
+        .iter()
+        .filter(|run| run.success)
+        .take(RUNS_FOR_ESTIMATE)
+        .map(|run| run.duration_secs)
+        .collect();
+    median(durations.clone()).map(|median| (median, durations.len()))
+}
+
+/// The median duration of a job, or of an instance of its matrix, in the latest
+/// successful runs, which record jobs by their name, or their id if they have none
+pub(crate) fn job_duration(runs: &[RunRecord], id: &str, job: &Value) -> Option<f64> {
+    let name = job.get("name").and_then(Value::as_str).unwrap_or(id);
+    let instance = format!("{} (", id);
+    let durations = runs
+        .iter()
+        .filter(|run| run.success)
+        .take(RUNS_FOR_ESTIMATE)
+        .flat_map(|run| &run.jobs)
+        .filter(|job| job.name == name || job.name == id || job.name.starts_with(&instance))
+        .map(|job| job.duration_secs)
+        .collect();
+    median(durations)
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let middle = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    })
+}
+
+pub(crate) fn format_duration(secs: f64) -> String {
+    if secs < 60.0 {
+        format!("{:.1}s", secs)
+    } else {
+        let secs = secs.round() as u64;
+        format!("{}m {}s", secs / 60, secs % 60)
+    }
+}
+
+/// A scalar as written, or a list joined with commas
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::Sequence(items) => items.iter().map(value_text).collect::<Vec<_>>().join(", "),
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conditions() {
+        let condition = condition_of(
+            "${{ github.event_name == 'push' && (github.ref == 'refs/heads/main' || startsWith(github.ref, 'refs/tags/v')) }}",
+        );
+        assert_eq!(
+            condition,
+            Condition {
+                events: vec!["push".to_string()],
+                branches: vec!["main".to_string()],
+                tags: vec!["v*".to_string()],
+                ..Condition::default()
+            }
+        );
+
+        let job = |condition: &str| {
+            serde_yaml::from_str::<Value>(&format!("if: \"{}\"", condition)).unwrap()
+        };
+        assert_eq!(
+            run_when(&job("github.event_name != 'pull_request'")),
+            "runs on every trigger but pull_request"
+        );
+        assert_eq!(
+            run_when(&job("github.ref_name == 'main'")),
+            "runs on anything to main"
+        );
+        assert_eq!(run_when(&job("success()")), "runs if `success()`");
+        assert_eq!(
+            run_when(&serde_yaml::from_str("runs-on: x").unwrap()),
+            "runs on every trigger"
+        );
+    }
+
+    #[test]
+    fn test_triggers_and_references() {
+        let workflow: Value = serde_yaml::from_str(
+            r#"
+on:
+  push:
+    branches: [main]
+    paths-ignore: ["docs/**"]
+  schedule:
+    - cron: "0 3 * * *"
+  workflow_dispatch:
+    inputs:
+      version:
+        required: true
+env:
+  REGION: ${{ vars.REGION }}
+jobs:
+  deploy:
+    runs-on: ubuntu-latest
+    steps:
+      - run: deploy --token ${{ secrets.DEPLOY_TOKEN }}
+  call:
+    uses: ./.github/workflows/reusable.yml
+    secrets: inherit
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            triggers(&workflow),
+            vec![
+                Trigger {
+                    event: "push".to_string(),
+                    filters: vec![
+                        "branches: main".to_string(),
+                        "paths-ignore: docs/**".to_string()
+                    ],
+                },
+                Trigger {
+                    event: "schedule".to_string(),
+                    filters: vec!["cron: 0 3 * * *".to_string()],
+                },
+                Trigger {
+                    event: "workflow_dispatch".to_string(),
+                    filters: vec!["inputs: version (required)".to_string()],
+                },
+            ]
+        );
+        let (secrets, vars, inherited) = references(&workflow);
+        assert_eq!(secrets["DEPLOY_TOKEN"], vec!["deploy"]);
+        assert_eq!(vars["REGION"], vec!["all jobs"]);
+        assert_eq!(inherited, vec!["call"]);
+    }
+}
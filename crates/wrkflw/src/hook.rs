@@ -0,0 +1,216 @@
+use crate::validation::ValidationReport;
+use clap::{Subcommand, ValueEnum};
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// First line after the shebang of hooks wrkflw writes, so it knows which ones it owns
+const HOOK_MARKER: &str = "# Installed by `wrkflw hook install`";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum HookKind {
+    /// Validate workflow files staged for the commit
+    PreCommit,
+    /// Validate every workflow file before pushing
+    PrePush,
+}
+
+impl HookKind {
+    fn file_name(self) -> &'static str {
+        match self {
+            HookKind::PreCommit => "pre-commit",
+            HookKind::PrePush => "pre-push",
+        }
+    }
+
+    fn validate_args(self) -> &'static str {
+        match self {
+            HookKind::PreCommit => "validate --staged-only --format json",
+            HookKind::PrePush => "validate --format json",
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum HookCommand {
+    /// Install a git hook that validates workflow files
+    Install {
+        /// Hook to install
+        #[arg(long, value_enum, default_value = "pre-commit")]
+        hook: HookKind,
+
+        /// Replace a hook that wasn't installed by wrkflw
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Remove a git hook installed by `wrkflw hook install`
+    Uninstall {
+        /// Hook to remove
+        #[arg(long, value_enum, default_value = "pre-commit")]
+        hook: HookKind,
+    },
+
+    /// Print `wrkflw validate --format json` output read from stdin as one line per
+    /// issue, failing if there are any
+    Annotate,
+}
+
+/// Execute a `wrkflw hook` subcommand
+pub fn handle_hook_command(command: &HookCommand) -> Result<bool, String> {
+    match command {
+        HookCommand::Install { hook, force } => {
+            let path = hook_path(*hook)?;
+            if path.exists() && !is_ours(&path) && !force {
+                return Err(format!(
+                    "{} already exists; use --force to replace it",
+                    path.display()
+                ));
+            }
+            let parent = path.parent().unwrap_or(&path);
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            std::fs::write(&path, hook_script(*hook)?)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            make_executable(&path)?;
+            println!("🪝 Installed {}", path.display());
+            Ok(true)
+        }
+        HookCommand::Uninstall { hook } => {
+            let path = hook_path(*hook)?;
+            if !path.exists() {
+                println!("No {} hook installed", hook.file_name());
+                return Ok(true);
+            }
+            if !is_ours(&path) {
+                return Err(format!(
+                    "{} wasn't installed by wrkflw; leaving it alone",
+                    path.display()
+                ));
+            }
+            std::fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+            println!("🗑  Removed {}", path.display());
+            Ok(true)
+        }
+        HookCommand::Annotate => {
+            let mut input = String::new();
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|e| format!("Failed to read stdin: {}", e))?;
+            let report: ValidationReport = serde_json::from_str(&input)
+                .map_err(|e| format!("Failed to parse validation output: {}", e))?;
+            for line in annotations(&report) {
+                eprintln!("{}", line);
+            }
+            Ok(report.valid)
+        }
+    }
+}
+
+/// One `path: issue` line per issue, then a summary
+fn annotations(report: &ValidationReport) -> Vec<String> {
+    let mut lines: Vec<String> = report
+        .files
+        .iter()
+        .flat_map(|file| {
+            file.issues
+                .iter()
+                .map(move |issue| format!("{}: {}", file.path.display(), issue))
+        })
+        .collect();
+    if !report.valid {
+        let failing = report
+            .files
+            .iter()
+            .filter(|file| !file.issues.is_empty())
+            .count();
+        lines.push(format!(
+            "wrkflw: {} issue(s) in {} workflow file(s); fix them or skip the check with --no-verify",
+            report.issue_count(),
+            failing
+        ));
+    }
+    lines
+}
+
+/// Where git looks for the hook, respecting `core.hooksPath`
+fn hook_path(hook: HookKind) -> Result<PathBuf, String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--git-path", "hooks"])
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Not in a git repository: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let hooks = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(PathBuf::from(hooks).join(hook.file_name()))
+}
+
+fn is_ours(path: &std::path::Path) -> bool {
+    std::fs::read_to_string(path).is_ok_and(|content| content.contains(HOOK_MARKER))
+}
+
+/// The hook runs the wrkflw on PATH, falling back to the binary that installed it
+fn hook_script(hook: HookKind) -> Result<String, String> {
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("Failed to locate the wrkflw binary: {}", e))?;
+    Ok(format!(
+        "#!/bin/sh\n{marker}\nWRKFLW=$(command -v wrkflw || echo '{exe}')\n\"$WRKFLW\" {args} | \"$WRKFLW\" hook annotate\n",
+        marker = HOOK_MARKER,
+        exe = exe.display(),
+        args = hook.validate_args()
+    ))
+}
+
+#[cfg(unix)]
+fn make_executable(path: &std::path::Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+        .map_err(|e| format!("Failed to make {} executable: {}", path.display(), e))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &std::path::Path) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::FileReport;
+
+    #[test]
+    fn test_annotations() {
+        let report = ValidationReport {
+            valid: false,
+            files: vec![
+                FileReport {
+                    path: PathBuf::from(".github/workflows/ci.yml"),
+                    issues: vec!["Job 'build' is missing 'runs-on' field".to_string()],
+                    ..FileReport::default()
+                },
+                FileReport {
+                    path: PathBuf::from(".gitlab-ci.yml"),
+                    ..FileReport::default()
+                },
+            ],
+        };
+        assert_eq!(
+            annotations(&report),
+            vec![
+                ".github/workflows/ci.yml: Job 'build' is missing 'runs-on' field".to_string(),
+                "wrkflw: 1 issue(s) in 1 workflow file(s); fix them or skip the check with --no-verify"
+                    .to_string(),
+            ]
+        );
+        let clean = ValidationReport {
+            valid: true,
+            files: vec![],
+        };
+        assert!(annotations(&clean).is_empty());
+    }
+}
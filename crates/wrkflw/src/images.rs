@@ -0,0 +1,65 @@
+use clap::Subcommand;
+use std::time::Duration;
+use wrkflw_executor::images::ImageStore;
+
+#[derive(Debug, Subcommand)]
+pub enum ImagesCommand {
+    /// List the language environment images built by earlier runs
+    List,
+
+    /// Remove language environment images from Docker and Podman
+    Prune {
+        /// Only remove images not used in this many days
+        #[arg(long, value_name = "DAYS")]
+        older_than: Option<u64>,
+    },
+}
+
+/// Execute a `wrkflw images` subcommand
+pub async fn handle_images_command(command: &ImagesCommand) -> Result<(), String> {
+    let store = ImageStore::new(ImageStore::default_file());
+    match command {
+        ImagesCommand::List => {
+            let images = store.list();
+            if images.is_empty() {
+                println!("No images recorded in {}", store.file().display());
+            }
+            for image in images {
+                let last_used = chrono::DateTime::<chrono::Local>::from(image.last_used);
+                println!(
+                    "  {:<7} {}  {}  last used {}",
+                    image.runtime,
+                    image.tag,
+                    image.environment(),
+                    last_used.format("%Y-%m-%d %H:%M")
+                );
+            }
+            Ok(())
+        }
+        ImagesCommand::Prune { older_than } => {
+            let older_than = older_than.map(|days| Duration::from_secs(days * 24 * 60 * 60));
+            let (mut removed, mut failed) = (0, 0);
+            for image in store.unused(older_than) {
+                let result = match image.runtime.as_str() {
+                    "podman" => wrkflw_executor::podman::remove_image(&image.tag).await,
+                    _ => wrkflw_executor::docker::remove_image(&image.tag).await,
+                };
+                match result {
+                    Ok(()) => {
+                        store.forget(&image)?;
+                        removed += 1;
+                    }
+                    Err(e) => {
+                        eprintln!("⚠️  {}", e);
+                        failed += 1;
+                    }
+                }
+            }
+            println!("🧹 Removed {} image(s)", removed);
+            if failed > 0 {
+                return Err(format!("{} image(s) could not be removed", failed));
+            }
+            Ok(())
+        }
+    }
+}
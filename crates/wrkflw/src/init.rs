@@ -0,0 +1,576 @@
+use crate::validation;
+use clap::ValueEnum;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+/// Directory workflows are written to
+const WORKFLOWS_DIR: &str = ".github/workflows";
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Language {
+    Rust,
+    Node,
+    Python,
+    Go,
+    Java,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum BuildTool {
+    Cargo,
+    Npm,
+    Yarn,
+    Pnpm,
+    Pip,
+    Poetry,
+    Go,
+    Maven,
+    Gradle,
+}
+
+/// Events a scaffolded workflow runs on
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Event {
+    /// Pushes to the default branch
+    Push,
+    #[value(name = "pull_request")]
+    PullRequest,
+    /// Every Monday morning
+    Schedule,
+    /// By hand, from GitHub or `wrkflw trigger`
+    #[value(name = "workflow_dispatch")]
+    WorkflowDispatch,
+}
+
+impl Language {
+    fn build_tools(self) -> &'static [BuildTool] {
+        match self {
+            Language::Rust => &[BuildTool::Cargo],
+            Language::Node => &[BuildTool::Npm, BuildTool::Yarn, BuildTool::Pnpm],
+            Language::Python => &[BuildTool::Pip, BuildTool::Poetry],
+            Language::Go => &[BuildTool::Go],
+            Language::Java => &[BuildTool::Maven, BuildTool::Gradle],
+        }
+    }
+
+    /// Versions a new workflow tests with
+    fn default_versions(self) -> &'static [&'static str] {
+        match self {
+            Language::Rust => &["stable"],
+            Language::Node => &["20", "22"],
+            Language::Python => &["3.12", "3.13"],
+            Language::Go => &["1.23", "1.24"],
+            Language::Java => &["17", "21"],
+        }
+    }
+
+    /// What the versions are of, in the matrix and in prompts
+    fn version_key(self) -> &'static str {
+        match self {
+            Language::Rust => "toolchain",
+            Language::Node => "node-version",
+            Language::Python => "python-version",
+            Language::Go => "go-version",
+            Language::Java => "java-version",
+        }
+    }
+
+    /// The language of the project in `dir`, going by its manifest
+    fn detect(dir: &Path) -> Option<Self> {
+        let has = |file: &str| dir.join(file).exists();
+        if has("Cargo.toml") {
+            Some(Language::Rust)
+        } else if has("package.json") {
+            Some(Language::Node)
+        } else if has("pyproject.toml") || has("requirements.txt") || has("setup.py") {
+            Some(Language::Python)
+        } else if has("go.mod") {
+            Some(Language::Go)
+        } else if has("pom.xml") || has("build.gradle") || has("build.gradle.kts") {
+            Some(Language::Java)
+        } else {
+            None
+        }
+    }
+}
+
+impl BuildTool {
+    /// The build tool of a `language` project in `dir`, going by its lock and
+    /// build files
+    fn detect(language: Language, dir: &Path) -> Self {
+        let has = |file: &str| dir.join(file).exists();
+        match language {
+            Language::Node if has("pnpm-lock.yaml") => BuildTool::Pnpm,
+            Language::Node if has("yarn.lock") => BuildTool::Yarn,
+            Language::Python
+                if has("poetry.lock")
+                    || fs::read_to_string(dir.join("pyproject.toml"))
+                        .is_ok_and(|pyproject| pyproject.contains("[tool.poetry]")) =>
+            {
+                BuildTool::Poetry
+            }
+            Language::Java if has("build.gradle") || has("build.gradle.kts") => BuildTool::Gradle,
+            _ => language.build_tools()[0],
+        }
+    }
+}
+
+/// A workflow to scaffold
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub language: Language,
+    pub build_tool: BuildTool,
+    pub events: Vec<Event>,
+    /// Runners to run on
+    pub os: Vec<String>,
+    /// Versions of the language to run with
+    pub versions: Vec<String>,
+    /// Branch pushes run on
+    pub branch: String,
+}
+
+impl Template {
+    /// The workflow's YAML
+    pub fn render(&self) -> String {
+        let mut yaml = String::from("name: CI\n\non:\n");
+        for event in &self.events {
+            yaml.push_str(match event {
+                Event::Push => "  push:\n",
+                Event::PullRequest => "  pull_request:\n",
+                Event::Schedule => "  schedule:\n    - cron: \"0 6 * * 1\"\n",
+                Event::WorkflowDispatch => "  workflow_dispatch:\n",
+            });
+            if *event == Event::Push {
+                let _ = writeln!(yaml, "    branches: [{}]", self.branch);
+            }
+        }
+
+        let key = self.language.version_key();
+        let _ = write!(
+            yaml,
+            "
+jobs:
+  build:
+    name: Build (${{{{ matrix.os }}}}, {language} ${{{{ matrix.{key} }}}})
+    runs-on: ${{{{ matrix.os }}}}
+    strategy:
+      fail-fast: false
+      matrix:
+        os: [{os}]
+        {key}: [{versions}]
+    steps:
+      - uses: actions/checkout@v4
+",
+            language = name(self.language),
+            key = key,
+            os = self.os.join(", "),
+            versions = self
+                .versions
+                .iter()
+                .map(|version| format!("\"{}\"", version))
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+        for step in self.steps() {
+            yaml.push_str(&step);
+        }
+        yaml
+    }
+
+    /// The steps after the checkout, each as a YAML list item
+    fn steps(&self) -> Vec<String> {
+        let version = format!("${{{{ matrix.{} }}}}", self.language.version_key());
+        let setup = |action: &str, with: &[(&str, &str)]| {
+            let mut step = format!("      - uses: {}\n", action);
+            if !with.is_empty() {
+                step.push_str("        with:\n");
+                for (name, value) in with {
+                    let _ = writeln!(step, "          {}: {}", name, value);
+                }
+            }
+            step
+        };
+        let run = |name: &str, command: &str| {
+            format!("      - name: {}\n        run: {}\n", name, command)
+        };
+        match self.build_tool {
+            BuildTool::Cargo => vec![
+                setup("dtolnay/rust-toolchain@master", &[("toolchain", &version)]),
+                run("Build", "cargo build --verbose"),
+                run("Test", "cargo test --verbose"),
+            ],
+            BuildTool::Npm | BuildTool::Yarn | BuildTool::Pnpm => {
+                let tool = name(self.build_tool);
+                let mut steps = Vec::new();
+                if self.build_tool == BuildTool::Pnpm {
+                    steps.push(setup("pnpm/action-setup@v4", &[("version", "9")]));
+                }
+                steps.push(setup(
+                    "actions/setup-node@v4",
+                    &[("node-version", &version), ("cache", &tool)],
+                ));
+                let install = match self.build_tool {
+                    BuildTool::Npm => "npm ci",
+                    BuildTool::Yarn => "yarn install --frozen-lockfile",
+                    _ => "pnpm install --frozen-lockfile",
+                };
+                steps.push(run("Install dependencies", install));
+                steps.push(run("Build", &format!("{} run build --if-present", tool)));
+                steps.push(run("Test", &format!("{} test", tool)));
+                steps
+            }
+            BuildTool::Pip => vec![
+                setup(
+                    "actions/setup-python@v5",
+                    &[("python-version", &version), ("cache", "pip")],
+                ),
+                run(
+                    "Install dependencies",
+                    "|\n          python -m pip install --upgrade pip\n          if [ -f requirements.txt ]; then pip install -r requirements.txt; fi\n          pip install pytest",
+                ),
+                run("Test", "pytest"),
+            ],
+            BuildTool::Poetry => vec![
+                run("Install Poetry", "pipx install poetry"),
+                setup(
+                    "actions/setup-python@v5",
+                    &[("python-version", &version), ("cache", "poetry")],
+                ),
+                run("Install dependencies", "poetry install"),
+                run("Test", "poetry run pytest"),
+            ],
+            BuildTool::Go => vec![
+                setup("actions/setup-go@v5", &[("go-version", &version)]),
+                run("Build", "go build ./..."),
+                run("Test", "go test ./..."),
+            ],
+            BuildTool::Maven | BuildTool::Gradle => {
+                let tool = name(self.build_tool);
+                let mut steps = vec![setup(
+                    "actions/setup-java@v4",
+                    &[
+                        ("distribution", "temurin"),
+                        ("java-version", &version),
+                        ("cache", &tool),
+                    ],
+                )];
+                if self.build_tool == BuildTool::Maven {
+                    steps.push(run("Build and test", "mvn --batch-mode verify"));
+                } else {
+                    steps.push(setup("gradle/actions/setup-gradle@v4", &[]));
+                    steps.push(run("Build and test", "./gradlew build"));
+                }
+                steps
+            }
+        }
+    }
+}
+
+/// What `wrkflw init` was given; what's missing is asked for, or detected when
+/// there's no one to ask
+pub struct InitOptions {
+    pub language: Option<Language>,
+    pub build_tool: Option<BuildTool>,
+    pub events: Vec<Event>,
+    pub os: Vec<String>,
+    pub versions: Vec<String>,
+    pub name: String,
+    pub force: bool,
+    /// Take the defaults instead of asking
+    pub yes: bool,
+}
+
+/// Scaffold a workflow in `.github/workflows` and validate it; whether it's valid
+pub fn handle_init_command(options: InitOptions) -> Result<bool, String> {
+    let dir =
+        std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?;
+    let path = PathBuf::from(WORKFLOWS_DIR).join(workflow_file_name(&options.name)?);
+    if path.exists() && !options.force {
+        return Err(format!(
+            "{} already exists; pass --force to overwrite it",
+            path.display()
+        ));
+    }
+
+    let stdin = std::io::stdin();
+    let mut prompt = Prompt {
+        input: (!options.yes && stdin.is_terminal()).then(|| stdin.lock()),
+    };
+    let template = prompt.template(&options, &dir)?;
+
+    fs::create_dir_all(WORKFLOWS_DIR)
+        .map_err(|e| format!("Failed to create {}: {}", WORKFLOWS_DIR, e))?;
+    fs::write(&path, template.render())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    println!("Wrote {}", path.display());
+
+    let result = validation::file_result(&path, None);
+    validation::print_status(&path, &result.issues);
+    for issue in &result.issues {
+        println!("   - {}", issue);
+    }
+    for warning in &result.warnings {
+        println!("   ⚠️  {}", warning);
+    }
+    if result.is_valid {
+        println!(
+            "Run it locally with `wrkflw run {}`",
+            validation::relative(&path).display()
+        );
+    }
+    Ok(result.is_valid)
+}
+
+/// Asks for what the options leave out, on a terminal; without one, the
+/// defaults are taken
+struct Prompt<R> {
+    input: Option<R>,
+}
+
+impl<R: BufRead> Prompt<R> {
+    fn template(&mut self, options: &InitOptions, dir: &Path) -> Result<Template, String> {
+        let language = match options.language {
+            Some(language) => language,
+            None => {
+                let detected = Language::detect(dir).unwrap_or(Language::Rust);
+                self.choose("Language", Language::value_variants(), detected)?
+            }
+        };
+        let build_tool = match options.build_tool {
+            Some(tool) if !language.build_tools().contains(&tool) => {
+                return Err(format!(
+                    "{} isn't a build tool for {}; pick one of {}",
+                    name(tool),
+                    name(language),
+                    language
+                        .build_tools()
+                        .iter()
+                        .map(|tool| name(*tool))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+            }
+            Some(tool) => tool,
+            None if language.build_tools().len() == 1 => language.build_tools()[0],
+            None => self.choose(
+                "Build tool",
+                language.build_tools(),
+                BuildTool::detect(language, dir),
+            )?,
+        };
+        let events = match options.events.is_empty() {
+            false => options.events.clone(),
+            true => self
+                .list("Run on", "push, pull_request")?
+                .iter()
+                .map(|event| Event::from_str(event, true))
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("Unknown event: {}", e))?,
+        };
+        let os = match options.os.is_empty() {
+            false => options.os.clone(),
+            true => self.list("Runners", "ubuntu-latest")?,
+        };
+        let versions = match options.versions.is_empty() {
+            false => options.versions.clone(),
+            true => self.list(
+                &format!("{} versions", name(language)),
+                &language.default_versions().join(", "),
+            )?,
+        };
+        Ok(Template {
+            language,
+            build_tool,
+            events,
+            os,
+            versions,
+            branch: default_branch(),
+        })
+    }
+
+    /// The answer to `question`, or `default`
+    fn ask(&mut self, question: &str, default: &str) -> Result<String, String> {
+        let Some(input) = &mut self.input else {
+            return Ok(default.to_string());
+        };
+        print!("{} [{}]: ", question, default);
+        std::io::stdout().flush().map_err(|e| e.to_string())?;
+        let mut answer = String::new();
+        input
+            .read_line(&mut answer)
+            .map_err(|e| format!("Failed to read the answer: {}", e))?;
+        let answer = answer.trim();
+        Ok(if answer.is_empty() { default } else { answer }.to_string())
+    }
+
+    /// One of `options`, `default` if none is picked
+    fn choose<T: ValueEnum + Copy>(
+        &mut self,
+        question: &str,
+        options: &[T],
+        default: T,
+    ) -> Result<T, String> {
+        let names: Vec<String> = options.iter().map(|option| name(*option)).collect();
+        loop {
+            let answer = self.ask(
+                &format!("{} ({})", question, names.join("/")),
+                &name(default),
+            )?;
+            match T::from_str(&answer, true) {
+                Ok(option) if options.iter().any(|o| name(*o) == name(option)) => {
+                    return Ok(option)
+                }
+                _ => println!("Pick one of {}", names.join(", ")),
+            }
+        }
+    }
+
+    /// A comma-separated list
+    fn list(&mut self, question: &str, default: &str) -> Result<Vec<String>, String> {
+        let answer = self.ask(question, default)?;
+        Ok(answer
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+}
+
+/// The file in `.github/workflows` a workflow named `name` goes to, with `.yml`
+/// added unless it ends in `.yml` or `.yaml`; names of other directories are refused
+fn workflow_file_name(name: &str) -> Result<String, String> {
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        return Err(format!(
+            "Invalid workflow name '{}'; give a file name, like ci.yml, without directories",
+            name
+        ));
+    }
+    let has_extension = Path::new(name)
+        .extension()
+        .is_some_and(|extension| extension == "yml" || extension == "yaml");
+    Ok(if has_extension {
+        name.to_string()
+    } else {
+        format!("{}.yml", name)
+    })
+}
+
+/// The name `option` is given by on the command line
+fn name<T: ValueEnum>(option: T) -> String {
+    option
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+/// The branch `origin` defaults to, else `main`
+fn default_branch() -> String {
+    std::process::Command::new("git")
+        .args(["symbolic-ref", "--short", "refs/remotes/origin/HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| {
+            let head = String::from_utf8_lossy(&output.stdout);
+            head.trim().strip_prefix("origin/").map(str::to_string)
+        })
+        .unwrap_or_else(|| "main".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_template_validates() {
+        let dir = tempfile::tempdir().unwrap();
+        for language in Language::value_variants() {
+            for build_tool in language.build_tools() {
+                let template = Template {
+                    language: *language,
+                    build_tool: *build_tool,
+                    events: Event::value_variants().to_vec(),
+                    os: vec!["ubuntu-latest".to_string(), "windows-latest".to_string()],
+                    versions: language
+                        .default_versions()
+                        .iter()
+                        .map(|version| version.to_string())
+                        .collect(),
+                    branch: "main".to_string(),
+                };
+                let path = dir.path().join(format!("{}.yml", name(*build_tool)));
+                fs::write(&path, template.render()).unwrap();
+                let result = wrkflw_evaluator::evaluate_workflow_file(&path, false).unwrap();
+                assert!(
+                    result.is_valid,
+                    "{}:\n{}\n{:?}",
+                    name(*build_tool),
+                    template.render(),
+                    result.issues
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn answers_fill_in_what_options_leave_out() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("package.json"), "{}").unwrap();
+        std::fs::write(dir.path().join("yarn.lock"), "").unwrap();
+        let options = InitOptions {
+            language: None,
+            build_tool: None,
+            events: Vec::new(),
+            os: vec!["macos-latest".to_string()],
+            versions: Vec::new(),
+            name: "ci.yml".to_string(),
+            force: false,
+            yes: false,
+        };
+        // The detected language, a build tool of another language and then the
+        // detected one, the default events, and versions
+        let mut prompt = Prompt {
+            input: Some("\nmaven\n\n\n18, 20\n".as_bytes()),
+        };
+        let template = prompt.template(&options, dir.path()).unwrap();
+        assert_eq!(template.language, Language::Node);
+        assert_eq!(template.build_tool, BuildTool::Yarn);
+        assert_eq!(template.events, vec![Event::Push, Event::PullRequest]);
+        assert_eq!(template.os, vec!["macos-latest"]);
+        assert_eq!(template.versions, vec!["18", "20"]);
+
+        let err = Prompt::<&[u8]> { input: None }
+            .template(
+                &InitOptions {
+                    language: Some(Language::Go),
+                    build_tool: Some(BuildTool::Npm),
+                    ..options
+                },
+                dir.path(),
+            )
+            .unwrap_err();
+        assert!(err.contains("isn't a build tool for go"), "{}", err);
+    }
+
+    #[test]
+    fn workflow_names_are_file_names() {
+        assert_eq!(workflow_file_name("ci.yml").unwrap(), "ci.yml");
+        assert_eq!(workflow_file_name("ci.yaml").unwrap(), "ci.yaml");
+        assert_eq!(workflow_file_name("ci").unwrap(), "ci.yml");
+        assert_eq!(workflow_file_name("release.v2").unwrap(), "release.v2.yml");
+
+        for name in [
+            "",
+            "..",
+            "../ci.yml",
+            "nested/ci.yml",
+            "nested\\ci.yml",
+            "/etc/ci.yml",
+        ] {
+            assert!(workflow_file_name(name).is_err(), "{}", name);
+        }
+    }
+}
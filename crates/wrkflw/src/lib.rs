@@ -1,3 +1,41 @@
+//! Validate and run GitHub Actions workflows and GitLab CI/CD pipelines locally.
+//!
+//! [`Workflow`] and [`Runner`] let another Rust program do what `wrkflw validate`
+//! and `wrkflw run` do without shelling out to the CLI:
+//!
+//! ```no_run
+//! use wrkflw_lib::{RuntimeType, Runner, Workflow};
+//!
+//! # async fn example() -> Result<(), wrkflw_lib::Error> {
+//! let workflow = Workflow::load(".github/workflows/ci.yml")?;
+//! let validation = workflow.validate();
+//! for issue in &validation.issues {
+//!     eprintln!("{}", issue);
+//! }
+//!
+//! let result = Runner::new()
+//!     .runtime(RuntimeType::Emulation)
+//!     .event("pull_request")
+//!     .on_log(|record| println!("{}", record.message))
+//!     .execute(&workflow)
+//!     .await?;
+//! for job in &result.jobs {
+//!     println!("{}: {:?}", job.name, job.status);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The crates wrkflw is made of are re-exported below for everything else.
+
+mod api;
+
+pub use api::{Error, Runner, Workflow, WorkflowKind};
+pub use wrkflw_executor::engine::ExecutionResult;
+pub use wrkflw_executor::{JobResult, JobStatus, RuntimeType, StepResult, StepStatus};
+pub use wrkflw_logging::{LogLevel, Record};
+pub use wrkflw_models::ValidationResult;
+
 pub use wrkflw_evaluator as evaluator;
 pub use wrkflw_executor as executor;
 pub use wrkflw_github as github;
@@ -7,6 +45,7 @@ pub use wrkflw_matrix as matrix;
 pub use wrkflw_models as models;
 pub use wrkflw_parser as parser;
 pub use wrkflw_runtime as runtime;
+pub use wrkflw_secrets as secrets;
 pub use wrkflw_ui as ui;
 pub use wrkflw_utils as utils;
 pub use wrkflw_validators as validators;
@@ -0,0 +1,334 @@
+//! `wrkflw list`: the workflows and pipelines under directories, with their
+//! names, triggers and number of jobs
+
+use crate::validation::{relative, workflow_files_recursive, Platform};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::path::{Path, PathBuf};
+use wrkflw_parser::gitlab::RESERVED_KEYS;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListEntry {
+    pub path: PathBuf,
+    pub platform: String,
+    pub name: Option<String>,
+    /// Events that start it
+    pub triggers: Vec<String>,
+    pub jobs: usize,
+    /// Why the file couldn't be read, if it couldn't
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// What `ListEntry` has of a file besides its path, which is what's cached
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct Summary {
+    name: Option<String>,
+    triggers: Vec<String>,
+    jobs: usize,
+    error: Option<String>,
+}
+
+/// List the workflows and pipelines under `paths`, the current directory if
+/// there are none, as a table or as JSON
+pub fn handle_list_command(paths: &[PathBuf], json: bool) -> Result<(), String> {
+    let entries = list(paths)?;
+    if json {
+        let json = serde_json::to_string_pretty(&entries)
+            .map_err(|e| format!("Failed to serialize workflow list: {}", e))?;
+        println!("{}", json);
+    } else if entries.is_empty() {
+        println!("No workflows or pipelines found");
+    } else {
+        print_table(&entries);
+    }
+    Ok(())
+}
+
+pub fn list(paths: &[PathBuf]) -> Result<Vec<ListEntry>, String> {
+    let default = [PathBuf::from(".")];
+    let paths = if paths.is_empty() {
+        &default[..]
+    } else {
+        paths
+    };
+    let mut entries = Vec::new();
+    for path in paths {
+        if !path.exists() {
+            return Err(format!("{} does not exist", path.display()));
+        }
+        for file in workflow_files_recursive(path) {
+            let platform = Platform::of(&file, None);
+            // Actions aren't run on their own
+            if platform == Platform::Action {
+                continue;
+            }
+            let summary =
+                wrkflw_utils::cache::cached_for_file("list", &file, &[platform.name()], || {
+                    summarize(&file, platform)
+                });
+            entries.push(ListEntry {
+                path: relative(&file).to_path_buf(),
+                platform: platform.name().to_string(),
+                name: summary.name,
+                triggers: summary.triggers,
+                jobs: summary.jobs,
+                error: summary.error,
+            });
+        }
+    }
+    Ok(entries)
+}
+
+fn print_table(entries: &[ListEntry]) {
+    let rows: Vec<[String; 5]> = entries
+        .iter()
+        .map(|entry| {
+            let name = match (&entry.error, &entry.name) {
+                (Some(error), _) => format!("❌ {}", error),
+                (None, Some(name)) => name.clone(),
+                (None, None) => "-".to_string(),
+            };
+            let triggers = if entry.triggers.is_empty() {
+                "-".to_string()
+            } else {
+                entry.triggers.join(", ")
+            };
+            [
+                entry.path.display().to_string(),
+                entry.platform.clone(),
+                name,
+                triggers,
+                entry.jobs.to_string(),
+            ]
+        })
+        .collect();
+    let header = ["Path", "Platform", "Name", "Triggers", "Jobs"];
+    let mut widths = header.map(|title| title.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let print_row = |cells: [&str; 5]| {
+        println!(
+            "{:<w0$}  {:<w1$}  {:<w2$}  {:<w3$}  {:>w4$}",
+            cells[0],
+            cells[1],
+            cells[2],
+            cells[3],
+            cells[4],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3],
+            w4 = widths[4],
+        )
+    };
+    print_row(header);
+    for row in &rows {
+        print_row([&row[0], &row[1], &row[2], &row[3], &row[4]]);
+    }
+    println!("\n{} workflow(s) and pipeline(s)", entries.len());
+}
+
+fn summarize(path: &Path, platform: Platform) -> Summary {
+    let summary = match platform {
+        Platform::Jenkins => summarize_jenkinsfile(path),
+        _ => std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read file: {}", e))
+            .and_then(|content| {
+                serde_yaml::from_str::<Value>(&content).map_err(|e| format!("Invalid YAML: {}", e))
+            })
+            .map(|yaml| match platform {
+                Platform::GitLab => summarize_gitlab(&yaml),
+                Platform::Azure => summarize_azure(&yaml),
+                _ => summarize_github(&yaml),
+            }),
+    };
+    summary.unwrap_or_else(|error| Summary {
+        error: Some(error),
+        ..Summary::default()
+    })
+}
+
+fn summarize_github(workflow: &Value) -> Summary {
+    let triggers = match workflow.get("on") {
+        Some(Value::String(event)) => vec![event.clone()],
+        Some(Value::Sequence(events)) => events
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        Some(Value::Mapping(events)) => events
+            .keys()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    };
+    Summary {
+        name: workflow
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        triggers,
+        jobs: workflow
+            .get("jobs")
+            .and_then(Value::as_mapping)
+            .map_or(0, |jobs| jobs.len()),
+        error: None,
+    }
+}
+
+fn summarize_gitlab(pipeline: &Value) -> Summary {
+    let workflow = pipeline.get("workflow");
+    // The sources the workflow rules compare `$CI_PIPELINE_SOURCE` to; without
+    // any, pipelines run for pushes
+    let mut triggers = Vec::new();
+    for rule in workflow
+        .and_then(|workflow| workflow.get("rules"))
+        .and_then(Value::as_sequence)
+        .into_iter()
+        .flatten()
+    {
+        let Some(condition) = rule.get("if").and_then(Value::as_str) else {
+            continue;
+        };
+        for part in condition.split("$CI_PIPELINE_SOURCE").skip(1) {
+            let Some(source) = part
+                .trim_start()
+                .strip_prefix("==")
+                .map(|rest| rest.trim_start().trim_start_matches(['"', '\'']))
+                .and_then(|rest| rest.split(['"', '\'']).next())
+            else {
+                continue;
+            };
+            if !source.is_empty() && !triggers.iter().any(|trigger| trigger == source) {
+                triggers.push(source.to_string());
+            }
+        }
+    }
+    if triggers.is_empty() {
+        triggers.push("push".to_string());
+    }
+    let jobs = pipeline.as_mapping().map_or(0, |keys| {
+        keys.iter()
+            .filter(|(key, value)| {
+                key.as_str()
+                    .is_some_and(|key| !key.starts_with('.') && !RESERVED_KEYS.contains(&key))
+                    && value.is_mapping()
+            })
+            .count()
+    });
+    Summary {
+        name: workflow
+            .and_then(|workflow| workflow.get("name"))
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        triggers,
+        jobs,
+        error: None,
+    }
+}
+
+fn summarize_azure(pipeline: &Value) -> Summary {
+    let is_none = |value: &Value| value.as_str() == Some("none");
+    let mut triggers = Vec::new();
+    // Pipelines run on every push unless `trigger: none`
+    if !pipeline.get("trigger").is_some_and(is_none) {
+        triggers.push("push".to_string());
+    }
+    if pipeline.get("pr").is_some_and(|pr| !is_none(pr)) {
+        triggers.push("pr".to_string());
+    }
+    if pipeline.get("schedules").is_some() {
+        triggers.push("schedule".to_string());
+    }
+    let count = |value: Option<&Value>| value.and_then(Value::as_sequence).map(Vec::len);
+    let jobs = match pipeline.get("stages").and_then(Value::as_sequence) {
+        Some(stages) => stages
+            .iter()
+            .map(|stage| count(stage.get("jobs")).unwrap_or(0))
+            .sum(),
+        // Steps alone are one implied job
+        None => count(pipeline.get("jobs"))
+            .unwrap_or_else(|| usize::from(pipeline.get("steps").is_some())),
+    };
+    Summary {
+        name: None,
+        triggers,
+        jobs,
+        error: None,
+    }
+}
+
+/// A Jenkinsfile's stages count as its jobs, its `triggers` as its triggers
+fn summarize_jenkinsfile(path: &Path) -> Result<Summary, String> {
+    let file = wrkflw_parser::jenkins::parse_pipeline(path).map_err(|e| e.to_string())?;
+    let pipeline = file
+        .pipeline()
+        .ok_or_else(|| "Not a declarative pipeline".to_string())?;
+    let triggers = pipeline
+        .child("triggers")
+        .and_then(|triggers| triggers.body.as_ref())
+        .map(|triggers| {
+            triggers
+                .iter()
+                .map(|trigger| trigger.name.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(Summary {
+        name: None,
+        triggers,
+        jobs: pipeline
+            .child("stages")
+            .map_or(0, |stages| stages.children("stage").count()),
+        error: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(text: &str) -> Value {
+        serde_yaml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_summaries() {
+        let github = summarize_github(&yaml(
+            "name: CI\non:\n  push:\n  pull_request:\njobs:\n  a: {}\n  b: {}\n",
+        ));
+        assert_eq!(github.name.as_deref(), Some("CI"));
+        assert_eq!(github.triggers, vec!["push", "pull_request"]);
+        assert_eq!(github.jobs, 2);
+
+        let gitlab = summarize_gitlab(&yaml(
+            r#"
+workflow:
+  name: Pipeline
+  rules:
+    - if: $CI_PIPELINE_SOURCE == "merge_request_event"
+    - if: $CI_PIPELINE_SOURCE == 'schedule' || $CI_PIPELINE_SOURCE == "merge_request_event"
+stages: [build]
+.template:
+  script: [make]
+build:
+  script: [make]
+"#,
+        ));
+        assert_eq!(gitlab.name.as_deref(), Some("Pipeline"));
+        assert_eq!(gitlab.triggers, vec!["merge_request_event", "schedule"]);
+        assert_eq!(gitlab.jobs, 1);
+
+        let azure = summarize_azure(&yaml(
+            "trigger: none\npr: [main]\nstages:\n  - stage: A\n    jobs: [{job: x}, {job: y}]\n  - stage: B\n    jobs: [{job: z}]\n",
+        ));
+        assert_eq!(azure.triggers, vec!["pr"]);
+        assert_eq!(azure.jobs, 3);
+        assert_eq!(summarize_azure(&yaml("steps: [{script: make}]")).jobs, 1);
+    }
+}
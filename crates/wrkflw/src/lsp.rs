@@ -0,0 +1,722 @@
+//! `wrkflw lsp`: a language server giving editors the diagnostics `wrkflw validate`
+//! reports, docs for workflow keys on hover, and completion of job and secret names
+
+use lazy_static::lazy_static;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+    Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{Completion, HoverRequest, Request as _};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, CompletionResponse,
+    Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, DidSaveTextDocumentParams, Hover, HoverContents, HoverParams,
+    HoverProviderCapability, MarkupContent, MarkupKind, Position, PublishDiagnosticsParams, Range,
+    ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, Uri,
+};
+use regex::Regex;
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use wrkflw_parser::gitlab::RESERVED_KEYS;
+use wrkflw_parser::yaml;
+
+use crate::validation::{file_issues, Platform};
+
+lazy_static! {
+    static ref SECRET_REF: Regex = Regex::new(r"secrets\.([A-Za-z_][A-Za-z0-9_]*)").unwrap();
+    static ref NEEDS_EXPR: Regex = Regex::new(r"needs\.[A-Za-z0-9_-]*$").unwrap();
+    static ref SECRETS_EXPR: Regex = Regex::new(r"secrets\.[A-Za-z0-9_]*$").unwrap();
+}
+
+/// Keys of GitHub workflows and what they do, shown on hover
+const GITHUB_KEYS: &[(&str, &str)] = &[
+    ("name", "Name of the workflow, job or step as shown in the UI."),
+    ("on", "Events that trigger the workflow, e.g. `push`, `pull_request` or `workflow_dispatch`, with their filters."),
+    ("jobs", "Jobs of the workflow, keyed by job id. Jobs run in parallel unless `needs` orders them."),
+    ("runs-on", "Runner the job runs on: a label such as `ubuntu-latest`, a list of labels, or a runner group."),
+    ("needs", "Jobs that have to complete successfully before this one starts. Their outputs are available as `needs.<job>.outputs`."),
+    ("steps", "Steps of the job, each either a `run` command or an action it `uses`, run in order."),
+    ("uses", "Action (`owner/repo@ref`, `./path` or `docker://image`) a step runs, or reusable workflow a job calls."),
+    ("run", "Commands a step runs in the job's shell."),
+    ("with", "Inputs passed to the action or reusable workflow."),
+    ("env", "Environment variables for the workflow, job or step; narrower scopes override wider ones."),
+    ("if", "Condition under which the job or step runs, as an expression; `${{ }}` is optional."),
+    ("strategy", "Matrix of configurations to run the job with, and how failures among them are handled."),
+    ("matrix", "Variables whose combinations each run a copy of the job; `include` and `exclude` adjust the combinations."),
+    ("fail-fast", "Whether a failing matrix job cancels the others. Defaults to true."),
+    ("max-parallel", "Maximum number of matrix jobs running at the same time."),
+    ("timeout-minutes", "Minutes after which the job or step is cancelled. Jobs default to 360."),
+    ("continue-on-error", "Let the workflow go on when this job or step fails."),
+    ("container", "Container image the job's steps run in."),
+    ("services", "Service containers, such as databases, started for the job."),
+    ("outputs", "Values a job exposes to jobs that need it, usually from step outputs."),
+    ("permissions", "Permissions of the `GITHUB_TOKEN`, per scope (`read`, `write` or `none`), or `read-all`/`write-all`."),
+    ("concurrency", "Group of which only one workflow run or job runs at a time; `cancel-in-progress` cancels the running one."),
+    ("defaults", "Default `shell` and `working-directory` of `run` steps."),
+    ("shell", "Shell `run` commands are executed with, e.g. `bash`, `pwsh` or `python`."),
+    ("working-directory", "Directory `run` commands start in."),
+    ("id", "Identifier of a step, to refer to it as `steps.<id>` in expressions."),
+    ("environment", "Deployment environment the job deploys to, with its protection rules and secrets."),
+    ("secrets", "Secrets passed to a reusable workflow, or `inherit` to pass all of the caller's."),
+    ("workflow_dispatch", "Trigger the workflow manually, optionally with `inputs`."),
+    ("workflow_call", "Make the workflow reusable from other workflows, with `inputs`, `secrets` and `outputs`."),
+    ("schedule", "Trigger the workflow on `cron` schedules, in UTC."),
+    ("branches", "Branches (glob patterns) whose events trigger the workflow."),
+    ("paths", "Trigger only when a changed file matches one of these patterns."),
+    ("paths-ignore", "Don't trigger when every changed file matches one of these patterns."),
+];
+
+/// Keys of GitLab pipelines and what they do, shown on hover
+const GITLAB_KEYS: &[(&str, &str)] = &[
+    ("stages", "Stages of the pipeline in order; jobs of a stage start when the previous stage succeeded."),
+    ("stage", "Stage the job belongs to. Defaults to `test`."),
+    ("script", "Commands the job runs. Required for every job that doesn't `trigger` a pipeline."),
+    ("before_script", "Commands run before each job's `script`, in the same shell."),
+    ("after_script", "Commands run after the job's `script`, even when it failed, in a new shell."),
+    ("image", "Docker image the job runs in."),
+    ("services", "Service containers, such as databases, started for the job."),
+    ("variables", "CI/CD variables for the pipeline or job."),
+    ("needs", "Jobs this job waits for, letting it start before its stage would, with their artifacts."),
+    ("rules", "Conditions deciding whether the job is added to the pipeline; the first matching rule applies."),
+    ("only", "Refs, or `changes:` of files, for which the job is added. Superseded by `rules`."),
+    ("except", "Refs, or `changes:` of files, for which the job isn't added. Superseded by `rules`."),
+    ("changes", "File patterns of which at least one has to have changed."),
+    ("when", "When the job runs: `on_success`, `on_failure`, `always`, `manual`, `delayed` or `never`."),
+    ("artifacts", "Files the job keeps after it ends, for later jobs and downloads."),
+    ("cache", "Files kept between pipelines, keyed by `key`."),
+    ("dependencies", "Jobs whose artifacts this job downloads."),
+    ("extends", "Jobs or hidden (`.`) templates whose configuration this job inherits."),
+    ("include", "Other files whose configuration is merged into this one."),
+    ("tags", "Tags selecting the runners that can run the job."),
+    ("allow_failure", "Let the pipeline pass when this job fails."),
+    ("timeout", "Time after which the job is cancelled, e.g. `1h 30m`."),
+    ("retry", "How many times the job is retried when it fails, and on which failures."),
+    ("parallel", "Number of copies of the job to run, or a `matrix` of variables."),
+    ("environment", "Environment the job deploys to."),
+    ("trigger", "Downstream or child pipeline the job starts."),
+    ("default", "Defaults of job keywords for every job."),
+    ("workflow", "Rules deciding whether the pipeline is created at all."),
+];
+
+/// Serve the language server protocol on stdin and stdout until the client exits
+pub fn serve() -> Result<(), String> {
+    // stdout carries the protocol; keep informational logs off it
+    wrkflw_logging::set_log_level(wrkflw_logging::LogLevel::Warning);
+    let (connection, io_threads) = Connection::stdio();
+    // The writer thread ends once the connection, and with it the sender, is dropped
+    run(connection)?;
+    io_threads
+        .join()
+        .map_err(|e| format!("Language server I/O failed: {}", e))
+}
+
+fn run(connection: Connection) -> Result<(), String> {
+    let capabilities = serde_json::to_value(ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![".".to_string(), " ".to_string(), "[".to_string()]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    })
+    .map_err(|e| e.to_string())?;
+    connection
+        .initialize(capabilities)
+        .map_err(|e| format!("Language server initialization failed: {}", e))?;
+
+    // Keyed by URI string, as `Uri` hashes through interior mutability
+    let mut documents: HashMap<String, String> = HashMap::new();
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection
+                    .handle_shutdown(&request)
+                    .map_err(|e| e.to_string())?
+                {
+                    return Ok(());
+                }
+                let response = handle_request(&documents, request);
+                send(&connection, Message::Response(response))?;
+            }
+            Message::Notification(notification) => {
+                if let Some(uri) = handle_notification(&mut documents, notification) {
+                    let diagnostics = match documents.get(uri.as_str()) {
+                        Some(text) => diagnostics(&uri, text),
+                        None => Vec::new(),
+                    };
+                    let params = PublishDiagnosticsParams {
+                        uri,
+                        diagnostics,
+                        version: None,
+                    };
+                    send(
+                        &connection,
+                        Message::Notification(Notification::new(
+                            PublishDiagnostics::METHOD.to_string(),
+                            params,
+                        )),
+                    )?;
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+    Ok(())
+}
+
+fn send(connection: &Connection, message: Message) -> Result<(), String> {
+    connection
+        .sender
+        .send(message)
+        .map_err(|e| format!("Failed to send to the client: {}", e))
+}
+
+fn handle_request(documents: &HashMap<String, String>, request: Request) -> Response {
+    let id = request.id.clone();
+    let result = match request.method.as_str() {
+        HoverRequest::METHOD => {
+            serde_json::from_value::<HoverParams>(request.params).map(|params| {
+                let position = params.text_document_position_params;
+                let hover = documents
+                    .get(position.text_document.uri.as_str())
+                    .and_then(|text| {
+                        hover(
+                            text,
+                            position.position,
+                            is_gitlab(&position.text_document.uri, text),
+                        )
+                    });
+                serde_json::to_value(hover)
+            })
+        }
+        Completion::METHOD => {
+            serde_json::from_value::<CompletionParams>(request.params).map(|params| {
+                let position = params.text_document_position;
+                let items = documents
+                    .get(position.text_document.uri.as_str())
+                    .map(|text| {
+                        completions(
+                            text,
+                            position.position,
+                            is_gitlab(&position.text_document.uri, text),
+                        )
+                    })
+                    .unwrap_or_default();
+                serde_json::to_value(CompletionResponse::Array(items))
+            })
+        }
+        method => {
+            return Response::new_err(
+                id,
+                ErrorCode::MethodNotFound as i32,
+                format!("Unsupported request: {}", method),
+            )
+        }
+    };
+    match result {
+        Ok(Ok(value)) => Response::new_ok(id, value),
+        Ok(Err(e)) | Err(e) => {
+            Response::new_err(id, ErrorCode::InvalidParams as i32, e.to_string())
+        }
+    }
+}
+
+/// Track the text of open documents; returns the document whose diagnostics changed
+fn handle_notification(
+    documents: &mut HashMap<String, String>,
+    notification: Notification,
+) -> Option<Uri> {
+    match notification.method.as_str() {
+        DidOpenTextDocument::METHOD => {
+            let params: DidOpenTextDocumentParams =
+                serde_json::from_value(notification.params).ok()?;
+            let document = params.text_document;
+            documents.insert(document.uri.to_string(), document.text);
+            Some(document.uri)
+        }
+        DidChangeTextDocument::METHOD => {
+            let params: DidChangeTextDocumentParams =
+                serde_json::from_value(notification.params).ok()?;
+            // Full sync: the last change holds the whole text
+            let text = params.content_changes.into_iter().last()?.text;
+            documents.insert(params.text_document.uri.to_string(), text);
+            Some(params.text_document.uri)
+        }
+        DidSaveTextDocument::METHOD => {
+            let params: DidSaveTextDocumentParams =
+                serde_json::from_value(notification.params).ok()?;
+            if let Some(text) = params.text {
+                documents.insert(params.text_document.uri.to_string(), text);
+            }
+            Some(params.text_document.uri)
+        }
+        DidCloseTextDocument::METHOD => {
+            let params: DidCloseTextDocumentParams =
+                serde_json::from_value(notification.params).ok()?;
+            documents.remove(params.text_document.uri.as_str());
+            Some(params.text_document.uri)
+        }
+        _ => None,
+    }
+}
+
+fn uri_path(uri: &Uri) -> PathBuf {
+    let path = uri.as_str().strip_prefix("file://").unwrap_or(uri.as_str());
+    PathBuf::from(
+        urlencoding::decode(path)
+            .map(|path| path.into_owned())
+            .unwrap_or_else(|_| path.to_string()),
+    )
+}
+
+fn is_gitlab(uri: &Uri, text: &str) -> bool {
+    let path = uri_path(uri);
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("");
+    name.ends_with("gitlab-ci.yml")
+        || path.parent().is_some_and(|dir| dir.ends_with(".gitlab/ci"))
+        || (!text.contains("runs-on:") && !text.contains("\non:") && text.contains("script:"))
+}
+
+/// Validate the editor's text of a document the way `wrkflw validate` validates files
+fn diagnostics(uri: &Uri, text: &str) -> Vec<Diagnostic> {
+    let path = uri_path(uri);
+    let Ok(dir) = tempfile::tempdir() else {
+        return Vec::new();
+    };
+    // Keep the file name, as GitLab and Azure pipelines and Jenkinsfiles are
+    // recognized by it
+    let copy = dir
+        .path()
+        .join(path.file_name().unwrap_or("workflow.yml".as_ref()));
+    if std::fs::write(&copy, text).is_err() {
+        return Vec::new();
+    }
+    let forced = if crate::is_jenkinsfile(&copy) {
+        Some(Platform::Jenkins)
+    } else if crate::is_azure_pipeline(&copy) {
+        Some(Platform::Azure)
+    } else {
+        is_gitlab(uri, text).then_some(Platform::GitLab)
+    };
+    file_issues(&copy, forced)
+        .into_iter()
+        .map(|issue| Diagnostic {
+            range: issue_range(text, &issue),
+            severity: Some(DiagnosticSeverity::ERROR),
+            source: Some("wrkflw".to_string()),
+            message: issue,
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Where in `text` an issue is: the position of a YAML error, else the key of the
+/// first name the issue quotes, else the start of the file
+fn issue_range(text: &str, issue: &str) -> Range {
+    if let Some((line, column)) = yaml::message_position(issue) {
+        let line = line.saturating_sub(1) as u32;
+        let column = column.saturating_sub(1) as u32;
+        let end = text
+            .lines()
+            .nth(line as usize)
+            .map(|line| line.encode_utf16().count() as u32)
+            .unwrap_or(column)
+            .max(column);
+        return Range::new(Position::new(line, column), Position::new(line, end));
+    }
+    yaml::quoted_names(issue)
+        .find_map(|name| find_key(text, name))
+        .unwrap_or_default()
+}
+
+/// Range of the first `name:` key in `text`, a list item's included
+fn find_key(text: &str, name: &str) -> Option<Range> {
+    text.lines().enumerate().find_map(|(idx, line)| {
+        let trimmed = line.trim_start();
+        let item = trimmed.strip_prefix("- ").unwrap_or(trimmed);
+        let key = item.trim_start_matches(['"', '\'']);
+        let rest = key.strip_prefix(name)?;
+        if !rest
+            .trim_start_matches(['"', '\''])
+            .trim_start()
+            .starts_with(':')
+        {
+            return None;
+        }
+        let start = line[..line.len() - key.len()].encode_utf16().count() as u32;
+        let end = start + name.encode_utf16().count() as u32;
+        Some(Range::new(
+            Position::new(idx as u32, start),
+            Position::new(idx as u32, end),
+        ))
+    })
+}
+
+/// Byte offset in `line` of an LSP position's UTF-16 `character`
+fn byte_offset(line: &str, character: u32) -> usize {
+    let mut units = 0;
+    for (offset, c) in line.char_indices() {
+        if units >= character as usize {
+            return offset;
+        }
+        units += c.len_utf16();
+    }
+    line.len()
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
+}
+
+/// Docs of the workflow key under the cursor
+fn hover(text: &str, position: Position, gitlab: bool) -> Option<Hover> {
+    let line = text.lines().nth(position.line as usize)?;
+    let offset = byte_offset(line, position.character);
+    let start = line[..offset]
+        .rfind(|c| !is_key_char(c))
+        .map_or(0, |idx| idx + 1);
+    let end = line[offset..]
+        .find(|c| !is_key_char(c))
+        .map_or(line.len(), |idx| offset + idx);
+    let word = &line[start..end];
+    if word.is_empty() || !line[end..].trim_start().starts_with(':') {
+        return None;
+    }
+    let keys = if gitlab { GITLAB_KEYS } else { GITHUB_KEYS };
+    let (_, doc) = keys.iter().find(|(key, _)| *key == word)?;
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: format!("**{}**\n\n{}", word, doc),
+        }),
+        range: Some(Range::new(
+            Position::new(position.line, line[..start].encode_utf16().count() as u32),
+            Position::new(position.line, line[..end].encode_utf16().count() as u32),
+        )),
+    })
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+fn is_content(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    !trimmed.is_empty() && !trimmed.starts_with('#')
+}
+
+fn key_of(line: &str) -> Option<&str> {
+    let key = line
+        .trim_start()
+        .split(':')
+        .next()?
+        .trim()
+        .trim_matches(['"', '\'']);
+    (line.contains(':') && !key.is_empty() && !key.starts_with('-')).then_some(key)
+}
+
+/// Names of the jobs defined in `text`, read line by line so that a document that
+/// doesn't parse while it's being edited still has them
+fn job_names(text: &str, gitlab: bool) -> Vec<String> {
+    let lines: Vec<&str> = text.lines().filter(|line| is_content(line)).collect();
+    if gitlab {
+        return lines
+            .iter()
+            .filter(|line| indent(line) == 0)
+            .filter_map(|line| key_of(line))
+            .filter(|key| !key.starts_with('.') && !RESERVED_KEYS.contains(key))
+            .map(str::to_string)
+            .collect();
+    }
+    let Some(jobs) = lines
+        .iter()
+        .position(|line| indent(line) == 0 && key_of(line) == Some("jobs"))
+    else {
+        return Vec::new();
+    };
+    let body: Vec<&str> = lines[jobs + 1..]
+        .iter()
+        .take_while(|line| indent(line) > 0)
+        .copied()
+        .collect();
+    let Some(job_indent) = body.first().map(|line| indent(line)) else {
+        return Vec::new();
+    };
+    body.iter()
+        .filter(|line| indent(line) == job_indent)
+        .filter_map(|line| key_of(line))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Names of the secrets the document refers to or a reusable workflow declares,
+/// and the token every workflow has
+fn secret_names(text: &str) -> BTreeSet<String> {
+    let mut names: BTreeSet<String> = SECRET_REF
+        .captures_iter(text)
+        .map(|captures| captures[1].to_string())
+        .collect();
+    if let Ok(workflow) = serde_yaml::from_str::<serde_yaml::Value>(text) {
+        let declared = workflow
+            .get("on")
+            .and_then(|on| on.get("workflow_call"))
+            .and_then(|call| call.get("secrets"))
+            .and_then(|secrets| secrets.as_mapping());
+        for name in declared.into_iter().flat_map(|secrets| secrets.keys()) {
+            if let Some(name) = name.as_str() {
+                names.insert(name.to_string());
+            }
+        }
+    }
+    names.insert("GITHUB_TOKEN".to_string());
+    names
+}
+
+/// Whether the cursor on line `line_idx` is in the value of a `needs:` key
+fn in_needs(lines: &[&str], line_idx: usize, prefix: &str) -> bool {
+    if let Some((key, _)) = prefix.split_once(':') {
+        return key.trim().trim_start_matches("- ") == "needs";
+    }
+    // A list item below `needs:`
+    if !prefix.trim_start().starts_with('-') {
+        return false;
+    }
+    let item_indent = indent(prefix);
+    lines[..line_idx]
+        .iter()
+        .rev()
+        .filter(|line| is_content(line))
+        .find(|line| indent(line) < item_indent || !line.trim_start().starts_with('-'))
+        .is_some_and(|line| key_of(line) == Some("needs"))
+}
+
+/// The job whose definition `line_idx` is in, so it isn't offered as its own need
+fn current_job(lines: &[&str], line_idx: usize, jobs: &[String]) -> Option<String> {
+    lines[..=line_idx.min(lines.len().saturating_sub(1))]
+        .iter()
+        .rev()
+        .filter_map(|line| key_of(line))
+        .find(|key| jobs.iter().any(|job| job == key))
+        .map(str::to_string)
+}
+
+/// Job names after `needs:` and in `needs.` expressions, secret names in `secrets.`
+/// expressions
+fn completions(text: &str, position: Position, gitlab: bool) -> Vec<CompletionItem> {
+    let lines: Vec<&str> = text.lines().collect();
+    let line_idx = position.line as usize;
+    let Some(line) = lines.get(line_idx) else {
+        return Vec::new();
+    };
+    let prefix = &line[..byte_offset(line, position.character)];
+
+    if !gitlab && SECRETS_EXPR.is_match(prefix) {
+        return secret_names(text)
+            .into_iter()
+            .map(|name| CompletionItem {
+                label: name,
+                kind: Some(CompletionItemKind::CONSTANT),
+                detail: Some("secret".to_string()),
+                ..Default::default()
+            })
+            .collect();
+    }
+    let needs_expr = !gitlab && NEEDS_EXPR.is_match(prefix);
+    if needs_expr || in_needs(&lines, line_idx, prefix) {
+        let jobs = job_names(text, gitlab);
+        let current = current_job(&lines, line_idx, &jobs);
+        return jobs
+            .into_iter()
+            .filter(|job| Some(job) != current.as_ref())
+            .map(|job| CompletionItem {
+                label: job,
+                kind: Some(CompletionItemKind::MODULE),
+                detail: Some("job".to_string()),
+                ..Default::default()
+            })
+            .collect();
+    }
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::{
+        ClientCapabilities, InitializeParams, InitializedParams, TextDocumentIdentifier,
+        TextDocumentItem, TextDocumentPositionParams,
+    };
+    use std::str::FromStr;
+
+    const WORKFLOW: &str = "name: CI\non:\n  workflow_call:\n    secrets:\n      DEPLOY_KEY:\n        required: true\njobs:\n  build:\n    runs-on: ubuntu-latest\n    steps:\n      - run: echo ${{ secrets.NPM_TOKEN }}\n  test:\n    needs: \n    steps:\n      - run: echo ${{ needs. }}\n  deploy:\n    needs:\n      - \n    runs-on: ubuntu-latest\n    steps:\n      - run: echo ${{ secrets. }}\n";
+
+    fn labels(items: Vec<CompletionItem>) -> Vec<String> {
+        items.into_iter().map(|item| item.label).collect()
+    }
+
+    #[test]
+    fn test_issue_range() {
+        let range = issue_range(WORKFLOW, "Job 'test' is missing 'runs-on' field");
+        assert_eq!(
+            range,
+            Range::new(Position::new(11, 2), Position::new(11, 6))
+        );
+
+        let range = issue_range(
+            "a: 1\nb: [\n",
+            "Invalid YAML: did not find expected node at line 2 column 4",
+        );
+        assert_eq!(range.start, Position::new(1, 3));
+
+        assert_eq!(issue_range(WORKFLOW, "Something odd"), Range::default());
+    }
+
+    #[test]
+    fn test_hover() {
+        let hover = hover(WORKFLOW, Position::new(8, 6), false).unwrap();
+        let HoverContents::Markup(content) = hover.contents else {
+            panic!("expected markup");
+        };
+        assert!(content.value.starts_with("**runs-on**"));
+        assert_eq!(
+            hover.range,
+            Some(Range::new(Position::new(8, 4), Position::new(8, 11)))
+        );
+
+        // Values and unknown keys have no docs
+        assert!(super::hover(WORKFLOW, Position::new(8, 16), false).is_none());
+        assert!(super::hover(WORKFLOW, Position::new(7, 3), false).is_none());
+
+        let pipeline = "build:\n  script: make\n";
+        assert!(super::hover(pipeline, Position::new(1, 4), true).is_some());
+    }
+
+    #[test]
+    fn test_completions() {
+        assert_eq!(
+            labels(completions(WORKFLOW, Position::new(12, 11), false)),
+            vec!["build", "deploy"]
+        );
+        assert_eq!(
+            labels(completions(WORKFLOW, Position::new(14, 28), false)),
+            vec!["build", "deploy"]
+        );
+        assert_eq!(
+            labels(completions(WORKFLOW, Position::new(17, 8), false)),
+            vec!["build", "test"]
+        );
+        assert_eq!(
+            labels(completions(WORKFLOW, Position::new(20, 30), false)),
+            vec!["DEPLOY_KEY", "GITHUB_TOKEN", "NPM_TOKEN"]
+        );
+        assert!(completions(WORKFLOW, Position::new(8, 13), false).is_empty());
+
+        let pipeline = "stages: [build, test]\n.template:\n  image: alpine\nbuild:\n  script: make\ntest:\n  needs: [\n  script: make test\n";
+        assert_eq!(
+            labels(completions(pipeline, Position::new(6, 10), true)),
+            vec!["build"]
+        );
+    }
+
+    #[test]
+    fn test_server_publishes_diagnostics() {
+        let (server, client) = Connection::memory();
+        let handle = std::thread::spawn(move || run(server));
+
+        let request = |id: i32, method: &str, params: serde_json::Value| {
+            Message::Request(Request::new(id.into(), method.to_string(), params))
+        };
+        let notify = |method: &str, params: serde_json::Value| {
+            Message::Notification(Notification::new(method.to_string(), params))
+        };
+        let initialize = InitializeParams {
+            capabilities: ClientCapabilities::default(),
+            ..Default::default()
+        };
+        client
+            .sender
+            .send(request(
+                1,
+                "initialize",
+                serde_json::to_value(initialize).unwrap(),
+            ))
+            .unwrap();
+        assert!(matches!(
+            client.receiver.recv().unwrap(),
+            Message::Response(_)
+        ));
+        client
+            .sender
+            .send(notify(
+                "initialized",
+                serde_json::to_value(InitializedParams {}).unwrap(),
+            ))
+            .unwrap();
+
+        let uri = Uri::from_str("file:///repo/.github/workflows/ci.yml").unwrap();
+        let open = DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(
+                uri.clone(),
+                "yaml".to_string(),
+                1,
+                WORKFLOW.to_string(),
+            ),
+        };
+        client
+            .sender
+            .send(notify(
+                DidOpenTextDocument::METHOD,
+                serde_json::to_value(open).unwrap(),
+            ))
+            .unwrap();
+        let Message::Notification(published) = client.receiver.recv().unwrap() else {
+            panic!("expected diagnostics");
+        };
+        let params: PublishDiagnosticsParams = serde_json::from_value(published.params).unwrap();
+        assert_eq!(params.uri, uri);
+        assert!(params
+            .diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.message.contains("'test'")
+                && diagnostic.range.start == Position::new(11, 2)));
+
+        let hover = HoverParams {
+            text_document_position_params: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(uri),
+                Position::new(8, 6),
+            ),
+            work_done_progress_params: Default::default(),
+        };
+        client
+            .sender
+            .send(request(
+                2,
+                HoverRequest::METHOD,
+                serde_json::to_value(hover).unwrap(),
+            ))
+            .unwrap();
+        let Message::Response(response) = client.receiver.recv().unwrap() else {
+            panic!("expected a hover response");
+        };
+        assert!(response.result.is_some_and(|result| !result.is_null()));
+
+        client
+            .sender
+            .send(request(3, "shutdown", serde_json::Value::Null))
+            .unwrap();
+        assert!(matches!(
+            client.receiver.recv().unwrap(),
+            Message::Response(_)
+        ));
+        client
+            .sender
+            .send(notify("exit", serde_json::Value::Null))
+            .unwrap();
+        handle.join().unwrap().unwrap();
+    }
+}
@@ -3,6 +3,33 @@ use clap::{Parser, Subcommand, ValueEnum};
 use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
+use validation::Platform;
+use wrkflw_models::Severity;
+
+mod audit;
+mod auth;
+mod baseline;
+mod cache;
+mod convert;
+mod doctor;
+mod estimate;
+mod explain;
+mod hook;
+mod images;
+mod init;
+mod list;
+mod lsp;
+mod pin;
+mod remote;
+mod rerun;
+mod runs;
+mod schedule;
+mod secrets;
+mod serve;
+mod validation;
+mod vars;
+mod watch;
+mod webhook;
 
 #[derive(Debug, Clone, ValueEnum)]
 enum RuntimeChoice {
@@ -14,6 +41,8 @@ enum RuntimeChoice {
     Emulation,
     /// Use secure emulation mode with sandboxing (recommended for untrusted code)
     SecureEmulation,
+    /// Run plain commands as WASI modules (experimental, needs the `wasm` feature)
+    Wasm,
 }
 
 impl From<RuntimeChoice> for wrkflw_executor::RuntimeType {
@@ -23,6 +52,152 @@ impl From<RuntimeChoice> for wrkflw_executor::RuntimeType {
             RuntimeChoice::Podman => wrkflw_executor::RuntimeType::Podman,
             RuntimeChoice::Emulation => wrkflw_executor::RuntimeType::Emulation,
             RuntimeChoice::SecureEmulation => wrkflw_executor::RuntimeType::SecureEmulation,
+            RuntimeChoice::Wasm => wrkflw_executor::RuntimeType::Wasm,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum NetworkChoice {
+    /// Default bridge network
+    Bridge,
+    /// No network access
+    None,
+    /// Share the host's network stack
+    Host,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum PullChoice {
+    /// Pull before every use
+    Always,
+    /// Pull images not present locally
+    Missing,
+    /// Never pull; images have to be present locally
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum WorkspaceChoice {
+    /// Mount the project directory itself
+    Bind,
+    /// Check out a copy of the project, uncommitted changes included
+    Copy,
+    /// Check out a fresh clone of the project's committed history
+    Clone,
+}
+
+impl From<WorkspaceChoice> for wrkflw_executor::WorkspaceStrategy {
+    fn from(choice: WorkspaceChoice) -> Self {
+        match choice {
+            WorkspaceChoice::Bind => wrkflw_executor::WorkspaceStrategy::Bind,
+            WorkspaceChoice::Copy => wrkflw_executor::WorkspaceStrategy::Copy,
+            WorkspaceChoice::Clone => wrkflw_executor::WorkspaceStrategy::Clone,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum FileOwnershipChoice {
+    /// Whoever the image runs commands as, often root
+    Container,
+    /// You: containers run as your uid and gid
+    User,
+    /// You: the workspace is handed back to you after each job
+    Chown,
+}
+
+impl From<FileOwnershipChoice> for wrkflw_executor::FileOwnership {
+    fn from(choice: FileOwnershipChoice) -> Self {
+        match choice {
+            FileOwnershipChoice::Container => wrkflw_executor::FileOwnership::Container,
+            FileOwnershipChoice::User => wrkflw_executor::FileOwnership::User,
+            FileOwnershipChoice::Chown => wrkflw_executor::FileOwnership::Chown,
+        }
+    }
+}
+
+impl From<PullChoice> for wrkflw_executor::PullPolicy {
+    fn from(choice: PullChoice) -> Self {
+        match choice {
+            PullChoice::Always => wrkflw_executor::PullPolicy::Always,
+            PullChoice::Missing => wrkflw_executor::PullPolicy::Missing,
+            PullChoice::Never => wrkflw_executor::PullPolicy::Never,
+        }
+    }
+}
+
+impl From<NetworkChoice> for wrkflw_executor::NetworkMode {
+    fn from(choice: NetworkChoice) -> Self {
+        match choice {
+            NetworkChoice::Bridge => wrkflw_executor::NetworkMode::Bridge,
+            NetworkChoice::None => wrkflw_executor::NetworkMode::None,
+            NetworkChoice::Host => wrkflw_executor::NetworkMode::Host,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum SelinuxLabelChoice {
+    /// Relabel with :z (shared between containers)
+    Shared,
+    /// Relabel with :Z (private to the container)
+    Private,
+    /// Never relabel volume mounts
+    None,
+}
+
+impl From<SelinuxLabelChoice> for wrkflw_executor::podman::SelinuxLabel {
+    fn from(choice: SelinuxLabelChoice) -> Self {
+        match choice {
+            SelinuxLabelChoice::Shared => wrkflw_executor::podman::SelinuxLabel::Shared,
+            SelinuxLabelChoice::Private => wrkflw_executor::podman::SelinuxLabel::Private,
+            SelinuxLabelChoice::None => wrkflw_executor::podman::SelinuxLabel::Disabled,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ValidateFormat {
+    /// Status and issues of each file
+    Text,
+    /// A JSON report, e.g. for editor integrations and git hooks
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum LogFormatChoice {
+    /// Timestamped lines, warnings and errors on stderr
+    Text,
+    /// One JSON object per line on stderr, with the job, step and runtime as fields
+    Json,
+}
+
+impl From<LogFormatChoice> for wrkflw_logging::LogFormat {
+    fn from(choice: LogFormatChoice) -> Self {
+        match choice {
+            LogFormatChoice::Text => wrkflw_logging::LogFormat::Text,
+            LogFormatChoice::Json => wrkflw_logging::LogFormat::Json,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum GraphFormatChoice {
+    /// Indented text
+    Ascii,
+    /// Graphviz DOT, e.g. for `dot -Tsvg`
+    Dot,
+    /// Mermaid flowchart, e.g. for Markdown on GitHub
+    Mermaid,
+}
+
+impl From<GraphFormatChoice> for wrkflw_executor::GraphFormat {
+    fn from(choice: GraphFormatChoice) -> Self {
+        match choice {
+            GraphFormatChoice::Ascii => wrkflw_executor::GraphFormat::Ascii,
+            GraphFormatChoice::Dot => wrkflw_executor::GraphFormat::Dot,
+            GraphFormatChoice::Mermaid => wrkflw_executor::GraphFormat::Mermaid,
         }
     }
 }
@@ -45,9 +220,23 @@ struct Wrkflw {
     /// Run in debug mode with extensive execution details
     #[arg(short, long, global = true)]
     debug: bool,
+
+    /// Format of printed logs, and of the log file
+    #[arg(long, value_enum, default_value = "text", global = true)]
+    log_format: LogFormatChoice,
+
+    /// Also write all logs, debug ones included, to this file; it's rotated at 10 MiB,
+    /// keeping 5 older files as FILE.1 to FILE.5
+    #[arg(long, value_name = "FILE", global = true)]
+    log_file: Option<PathBuf>,
+
+    /// Don't use or fill the cache of parsed and validated workflows in .wrkflw/cache
+    #[arg(long, global = true)]
+    no_cache: bool,
 }
 
 #[derive(Debug, Subcommand)]
+#[allow(clippy::large_enum_variant)] // Parsed once; `Run` simply has many options
 enum Commands {
     /// Validate workflow or pipeline files
     Validate {
@@ -59,6 +248,14 @@ enum Commands {
         #[arg(long)]
         gitlab: bool,
 
+        /// Explicitly validate as Azure Pipelines file (defaults to azure-pipelines.yml if no path is provided)
+        #[arg(long, conflicts_with = "gitlab")]
+        azure: bool,
+
+        /// Explicitly validate as declarative Jenkinsfile (defaults to Jenkinsfile if no path is provided)
+        #[arg(long, conflicts_with_all = ["gitlab", "azure"])]
+        jenkins: bool,
+
         /// Set exit code to 1 on validation failure
         #[arg(long = "exit-code", default_value_t = true)]
         exit_code: bool,
@@ -66,6 +263,50 @@ enum Commands {
         /// Don't set exit code to 1 on validation failure (overrides --exit-code)
         #[arg(long = "no-exit-code", conflicts_with = "exit_code")]
         no_exit_code: bool,
+
+        /// Keep running and validate files again whenever they change
+        #[arg(long)]
+        watch: bool,
+
+        /// Fail when there are more than this many warnings, counted across all files
+        #[arg(long, value_name = "N", conflicts_with = "watch")]
+        max_warnings: Option<usize>,
+
+        /// Fail on findings of this severity or worse: error, warning or info (defaults to `fail_on` in the `[validation]` section of .wrkflw.toml, else error)
+        #[arg(long, value_name = "SEVERITY", value_parser = parse_severity, conflicts_with = "watch")]
+        fail_on: Option<Severity>,
+
+        /// Also print the findings suppressed by `# wrkflw-disable` comments, to audit them
+        #[arg(long, conflicts_with = "watch")]
+        show_suppressed: bool,
+
+        /// Only fail on findings not in this baseline file; it's created with the current findings when it doesn't exist
+        #[arg(long, value_name = "FILE", conflicts_with = "watch")]
+        baseline: Option<PathBuf>,
+
+        /// Record the current findings in the --baseline file, replacing what it had
+        #[arg(long, requires = "baseline")]
+        update_baseline: bool,
+
+        /// Validate every workflow, pipeline and action.yml in the directory trees given (defaults to the current directory)
+        #[arg(short, long, conflicts_with_all = ["watch", "staged_only"])]
+        recursive: bool,
+
+        /// Also check GitLab pipelines with GitLab's CI lint API, which catches server-side errors like missing includes (needs a GitLab token and an origin on GitLab)
+        #[arg(long, conflicts_with_all = ["watch", "staged_only", "azure", "jenkins"])]
+        remote_lint: bool,
+
+        /// Only validate workflow files with changes staged in the git index, as staged
+        #[arg(long, conflicts_with_all = ["paths", "watch"])]
+        staged_only: bool,
+
+        /// Also check that GitHub workflows meet the requirements in the `[policy]` section of .wrkflw.toml, failing with its exit codes when they don't
+        #[arg(long, conflicts_with = "watch")]
+        policy: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text", conflicts_with = "watch")]
+        format: ValidateFormat,
     },
 
     /// Execute workflow or pipeline files locally
@@ -85,9 +326,195 @@ enum Commands {
         #[arg(long)]
         preserve_containers_on_failure: bool,
 
+        /// When a step fails, open a shell in its container, in its working directory
+        /// and with its environment, before the run goes on (Docker and Podman only)
+        #[arg(long)]
+        debug_on_failure: bool,
+
         /// Explicitly run as GitLab CI/CD pipeline
         #[arg(long)]
         gitlab: bool,
+
+        /// Token exposed as secrets.GITHUB_TOKEN (defaults to a placeholder no API accepts)
+        #[arg(long, value_name = "TOKEN")]
+        github_token: Option<String>,
+
+        /// Without --github-token, expose the token `wrkflw auth status` reports
+        /// ($GITHUB_TOKEN, $GH_TOKEN or the gh CLI's) to the run
+        #[arg(long, conflicts_with = "github_token")]
+        github_token_from_env: bool,
+
+        /// CPU limit for job containers (e.g. 2 or 0.5)
+        #[arg(long, value_name = "CPUS")]
+        cpus: Option<f64>,
+
+        /// Memory limit for job containers (e.g. 512m, 2g)
+        #[arg(long, value_name = "SIZE", value_parser = parse_memory_limit)]
+        memory: Option<String>,
+
+        /// Maximum number of processes in job containers
+        #[arg(long, value_name = "N")]
+        pids_limit: Option<i64>,
+
+        /// Network mode for job containers; `none` also disables networking in secure emulation
+        #[arg(long, value_enum, default_value = "bridge")]
+        network: NetworkChoice,
+
+        /// When Docker and Podman pull job images
+        #[arg(long, value_enum, default_value = "missing")]
+        pull: PullChoice,
+
+        /// What Docker and Podman jobs get as their workspace
+        #[arg(long, value_enum, default_value = "copy")]
+        workspace: WorkspaceChoice,
+
+        /// Who owns the files Docker and Podman jobs create in their workspace
+        #[arg(long, value_enum, default_value = "container")]
+        file_ownership: FileOwnershipChoice,
+
+        /// Extra bind mount for Docker and Podman containers as HOST:CONTAINER[:ro] (can be repeated)
+        #[arg(long, value_name = "MOUNT", value_parser = parse_mount)]
+        mount: Vec<String>,
+
+        /// Sandbox policy file (YAML or TOML) for secure emulation
+        #[arg(long, value_name = "FILE")]
+        sandbox_policy: Option<PathBuf>,
+
+        /// Only allow a minimal whitelist of commands in secure emulation
+        #[arg(long)]
+        sandbox_strict: bool,
+
+        /// What to do with `runs-on: windows-*` jobs that can't run natively: fail, skip, or an image to run them in
+        #[arg(long, value_name = "ACTION", default_value = "fail")]
+        windows_runners: wrkflw_executor::ForeignRunnerAction,
+
+        /// What to do with `runs-on: macos-*` jobs that can't run natively: fail, skip, or an image to run them in
+        #[arg(long, value_name = "ACTION", default_value = "fail")]
+        macos_runners: wrkflw_executor::ForeignRunnerAction,
+
+        /// User Docker containers run commands as (e.g. 1000:1000); the image's default when unset
+        #[arg(long, value_name = "USER")]
+        docker_user: Option<String>,
+
+        /// User namespace mode for Docker containers (e.g. host)
+        #[arg(long, value_name = "MODE")]
+        docker_userns: Option<String>,
+
+        /// User namespace mode for Podman containers (e.g. keep-id, auto, host)
+        #[arg(long, value_name = "MODE")]
+        podman_userns: Option<String>,
+
+        /// Extra --security-opt for Podman containers (can be repeated)
+        #[arg(long, value_name = "OPT")]
+        podman_security_opt: Vec<String>,
+
+        /// SELinux relabeling for Podman volume mounts (defaults to shared when SELinux is enforcing)
+        #[arg(long, value_enum, value_name = "LABEL")]
+        selinux_label: Option<SelinuxLabelChoice>,
+
+        /// Never fetch anything over the network; fail if a reusable workflow or tool isn't cached
+        #[arg(long)]
+        offline: bool,
+
+        /// Print the jobs, steps, images and missing secrets of the run without executing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Save the step logs, zipped as GitHub does, under .wrkflw/runs/ (see `wrkflw runs`)
+        #[arg(long)]
+        save_logs: bool,
+
+        /// Don't record the run in the run history (see `wrkflw history`)
+        #[arg(long, conflicts_with = "save_logs")]
+        no_history: bool,
+
+        /// Don't write the logs of the run under .wrkflw/logs/ (see `wrkflw logs`)
+        #[arg(long)]
+        no_log_files: bool,
+
+        /// Output of each step stream kept in memory (e.g. 512k, 10m; 0 for no limit);
+        /// longer output keeps its start and end, with the whole saved to a file
+        #[arg(long, value_name = "SIZE", default_value = "10m", value_parser = parse_output_limit)]
+        output_limit: usize,
+
+        /// Seconds a step may run in a container, over .wrkflw.toml's `[timeouts]`
+        /// (default 360; 0 for no limit)
+        #[arg(long, value_name = "SECS")]
+        step_timeout: Option<u64>,
+
+        /// Seconds an image pull may take before the image already present is used,
+        /// over .wrkflw.toml's `[timeouts]` (default 30; 0 for no limit)
+        #[arg(long, value_name = "SECS")]
+        pull_timeout: Option<u64>,
+
+        /// Set an environment variable in every job, over the env the workflow defines; can be repeated
+        #[arg(short, long = "env", value_name = "KEY=VALUE", value_parser = parse_key_val)]
+        env: Vec<(String, String)>,
+
+        /// Read environment variables from a .env file; can be repeated, --env wins over it
+        #[arg(long, value_name = "FILE")]
+        env_file: Vec<PathBuf>,
+
+        /// Set a configuration variable (`${{ vars.NAME }}`) over those of .wrkflw/vars.toml; can be repeated
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_key_val)]
+        var: Vec<(String, String)>,
+
+        /// Set a GitLab CI/CD variable over all others, the job's `variables:` included; can be repeated
+        #[arg(long = "gitlab-var", value_name = "NAME=VALUE", value_parser = parse_key_val)]
+        gitlab_var: Vec<(String, String)>,
+
+        /// Also use the repository's variables on GitHub, under those of .wrkflw/vars.toml
+        #[arg(long, conflicts_with = "offline")]
+        github_vars: bool,
+
+        /// Only run this job (repeatable); the jobs it needs are skipped, and taken as succeeded
+        #[arg(long = "job", value_name = "ID")]
+        jobs: Vec<String>,
+
+        /// Also run the jobs the `--job` jobs need, and the jobs those need
+        #[arg(long, requires = "jobs")]
+        with_needs: bool,
+
+        /// Don't run this job (repeatable)
+        #[arg(long = "skip-job", value_name = "ID")]
+        skip_jobs: Vec<String>,
+
+        /// Only run this step of the jobs that run (repeatable), by id, name or number from 1
+        #[arg(long = "step", value_name = "STEP")]
+        steps: Vec<String>,
+
+        /// Don't run this step of the jobs that run (repeatable), by id, name or number from 1
+        #[arg(long = "skip-step", value_name = "STEP")]
+        skip_steps: Vec<String>,
+
+        /// Run the manual (`when: manual`) jobs of a GitLab pipeline rather than skip them
+        #[arg(long)]
+        run_manual: bool,
+
+        /// Simulate a push of the changes since this ref (e.g. origin/main): skip the workflow or GitLab jobs such a push wouldn't trigger
+        #[arg(long, value_name = "REF", conflicts_with = "watch")]
+        since: Option<String>,
+
+        /// Run again whenever files under these paths (default: the current directory) or the workflow change
+        #[arg(
+            long,
+            value_name = "PATH",
+            num_args = 0..,
+            conflicts_with_all = ["dry_run", "save_logs", "report", "timing_json", "timing_folded"]
+        )]
+        watch: Option<Vec<PathBuf>>,
+
+        /// Write a report of the run, e.g. `junit=results.xml`; can be repeated
+        #[arg(long, value_name = "FORMAT=FILE", value_parser = parse_report)]
+        report: Vec<(ReportFormat, PathBuf)>,
+
+        /// Also write the timing report as JSON to this file
+        #[arg(long, value_name = "FILE")]
+        timing_json: Option<PathBuf>,
+
+        /// Also write the timing report as folded stacks (for flamegraph.pl, inferno or speedscope) to this file
+        #[arg(long, value_name = "FILE")]
+        timing_folded: Option<PathBuf>,
     },
 
     /// Open TUI interface to manage workflows
@@ -106,6 +533,22 @@ enum Commands {
         /// Preserve Docker containers on failure for debugging (Docker mode only)
         #[arg(long)]
         preserve_containers_on_failure: bool,
+
+        /// Sandbox policy file (YAML or TOML) for secure emulation
+        #[arg(long, value_name = "FILE")]
+        sandbox_policy: Option<PathBuf>,
+
+        /// Only allow a minimal whitelist of commands in secure emulation
+        #[arg(long)]
+        sandbox_strict: bool,
+
+        /// Set an environment variable in every job, over the env the workflow defines; can be repeated
+        #[arg(short, long = "env", value_name = "KEY=VALUE", value_parser = parse_key_val)]
+        env: Vec<(String, String)>,
+
+        /// Read environment variables from a .env file; can be repeated, --env wins over it
+        #[arg(long, value_name = "FILE")]
+        env_file: Vec<PathBuf>,
     },
 
     /// Trigger a GitHub workflow remotely
@@ -120,6 +563,10 @@ enum Commands {
         /// Key-value inputs for the workflow in format key=value
         #[arg(short, long, value_parser = parse_key_val)]
         input: Option<Vec<(String, String)>>,
+
+        /// Follow the run until it completes, download its logs and exit with its outcome
+        #[arg(long)]
+        watch: bool,
     },
 
     /// Trigger a GitLab pipeline remotely
@@ -131,10 +578,341 @@ enum Commands {
         /// Key-value variables for the pipeline in format key=value
         #[arg(short = 'V', long, value_parser = parse_key_val)]
         variable: Option<Vec<(String, String)>>,
+
+        /// Follow the pipeline until it finishes, printing job traces as jobs end,
+        /// and exit with its outcome
+        #[arg(long)]
+        watch: bool,
+    },
+
+    /// Inspect pipelines on GitLab
+    Gitlab {
+        #[command(subcommand)]
+        command: remote::GitlabCommand,
     },
 
-    /// List available workflows and pipelines
-    List,
+    /// List the workflows and pipelines with their names, triggers and number of jobs
+    List {
+        /// Directory to search, the current one by default; can be repeated
+        #[arg(long = "path", value_name = "DIR")]
+        paths: Vec<PathBuf>,
+
+        /// Print the list as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Describe what a workflow does: its triggers, when and in what order its
+    /// jobs run, the actions, secrets and variables it uses, and how long it takes
+    Explain {
+        /// Path to the workflow file
+        path: PathBuf,
+    },
+
+    /// Estimate the billable minutes and cost of a workflow's runs on GitHub-hosted
+    /// runners, per run and per month
+    Estimate {
+        /// Path to the workflow file
+        path: PathBuf,
+
+        /// Runs a month of an event, e.g. `push=200`; can be repeated (default: counted from the crons for `schedule`, unknown for other events)
+        #[arg(long, value_name = "EVENT=N", value_parser = estimate::parse_per_month)]
+        per_month: Vec<(String, u64)>,
+
+        /// Minutes a job takes when no successful run of it is recorded
+        #[arg(long, value_name = "MINUTES", default_value_t = 5.0)]
+        assume_minutes: f64,
+
+        /// Per-minute rate in USD of a kind of runner (linux, linux-arm, windows, windows-arm, macos, macos-large, macos-xlarge, self-hosted) over GitHub's; can be repeated
+        #[arg(long, value_name = "RUNNER=USD", value_parser = estimate::parse_price)]
+        price: Vec<(String, f64)>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: estimate::EstimateFormat,
+    },
+
+    /// Show the job dependency graph of a workflow or pipeline
+    Graph {
+        /// Path to workflow/pipeline file
+        path: PathBuf,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = GraphFormatChoice::Ascii)]
+        format: GraphFormatChoice,
+    },
+
+    /// Show when the `schedule` triggers of a workflow fire next, or run scheduled
+    /// workflows at those times
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Schedule {
+        #[command(subcommand)]
+        command: Option<schedule::ScheduleCommand>,
+
+        /// Path to the workflow file
+        #[arg(required = true)]
+        path: Option<PathBuf>,
+
+        /// Number of trigger times to show for each cron entry
+        #[arg(short = 'n', long, default_value_t = 5)]
+        count: usize,
+    },
+
+    /// Manage the encrypted local secret store
+    Secrets {
+        #[command(subcommand)]
+        command: secrets::SecretsCommand,
+    },
+
+    /// Manage the configuration variables (`${{ vars.NAME }}`) of local runs
+    Vars {
+        #[command(subcommand)]
+        command: vars::VarsCommand,
+    },
+
+    /// Browse runs saved with `run --save-logs`, or list the runs on GitHub
+    #[command(args_conflicts_with_subcommands = true)]
+    Runs {
+        #[command(subcommand)]
+        command: Option<runs::RunsCommand>,
+
+        /// List the latest GitHub Actions runs of the repository instead
+        #[arg(long)]
+        remote: bool,
+
+        /// Number of remote runs to list
+        #[arg(short = 'n', long, default_value_t = 20, requires = "remote")]
+        limit: usize,
+    },
+
+    /// Re-run a completed GitHub Actions run, or with `--from-failed` a failed local run
+    Rerun {
+        /// Id of the run, as listed by `wrkflw runs --remote`, or with `--from-failed`
+        /// by `wrkflw history` (default: the latest local run)
+        #[arg(required_unless_present = "from_failed")]
+        run_id: Option<String>,
+
+        /// Only re-run the failed jobs and the jobs depending on them
+        #[arg(long, conflicts_with = "from_failed")]
+        failed_only: bool,
+
+        /// Run a failed local run again, skipping the jobs and steps that succeeded in
+        /// it; failed jobs restart at their failed step, in the workspace it left
+        #[arg(long)]
+        from_failed: bool,
+
+        /// Options of `wrkflw run` for the rerun, after `--`; the runtime is the run's
+        #[arg(last = true, requires = "from_failed")]
+        run_args: Vec<String>,
+    },
+
+    /// Show the history of local runs, or compare two of them
+    History {
+        #[command(subcommand)]
+        command: Option<runs::HistoryCommand>,
+
+        /// Only list runs of workflows whose file name contains this
+        #[arg(long)]
+        workflow: Option<String>,
+
+        /// Number of runs to list
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+
+    /// Print the logs of a local run, or follow them while it runs
+    Logs {
+        /// Run id as listed by `--list`, or `latest`
+        #[arg(default_value = "latest")]
+        id: String,
+
+        /// Print the log of this job instead of the combined log
+        #[arg(long)]
+        job: Option<String>,
+
+        /// Keep printing what's logged until the run finishes
+        #[arg(short, long)]
+        follow: bool,
+
+        /// List the runs with logs instead
+        #[arg(long, conflicts_with_all = ["job", "follow"])]
+        list: bool,
+    },
+
+    /// Inspect and clean up local caches
+    Cache {
+        #[command(subcommand)]
+        command: cache::CacheCommand,
+    },
+
+    /// Flag deprecated runner images, action versions and workflow commands, and
+    /// suggest replacements
+    Audit {
+        /// Workflow files or directories (defaults to .github/workflows if none provided)
+        #[arg(value_name = "path", num_args = 0..)]
+        paths: Vec<PathBuf>,
+
+        /// Rewrite the files with the fixable findings replaced
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Pin `uses:` references to commit SHAs and check existing pins still match
+    /// their versions
+    Pin {
+        /// Workflow files or directories (defaults to .github/workflows if none provided)
+        #[arg(value_name = "path", num_args = 0..)]
+        paths: Vec<PathBuf>,
+
+        /// Don't rewrite anything; fail on unpinned third-party actions and stale pins
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Check that Docker/Podman, disk space, the network, tools and secret providers
+    /// are ready for runs, printing how to fix what isn't
+    Doctor,
+
+    /// List and remove the language environment images wrkflw builds
+    Images {
+        #[command(subcommand)]
+        command: images::ImagesCommand,
+    },
+
+    /// Translate a workflow between GitHub Actions and GitLab CI/CD, leaving TODO
+    /// comments where there's no equivalent
+    Convert {
+        /// Format of the input, detected from its name and content if not given
+        #[arg(long, value_enum)]
+        from: Option<convert::ConvertFormat>,
+
+        /// Format to convert into
+        #[arg(long, value_enum)]
+        to: convert::ConvertFormat,
+
+        /// Workflow or pipeline file to convert
+        path: PathBuf,
+
+        /// Write the result to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Scaffold a GitHub workflow in .github/workflows from a built-in template,
+    /// asking for what the options leave out, and validate it
+    Init {
+        /// Language of the project, detected from its manifest if not given
+        #[arg(long, value_enum)]
+        language: Option<init::Language>,
+
+        /// Build tool of the project, detected from its lock and build files if not
+        /// given
+        #[arg(long, value_enum)]
+        build_tool: Option<init::BuildTool>,
+
+        /// Events to run on, comma-separated (default: push, pull_request)
+        #[arg(long = "on", value_enum, value_delimiter = ',', value_name = "EVENT")]
+        events: Vec<init::Event>,
+
+        /// Runners of the matrix, comma-separated (default: ubuntu-latest)
+        #[arg(long, value_delimiter = ',', value_name = "RUNNER")]
+        os: Vec<String>,
+
+        /// Language versions of the matrix, comma-separated
+        #[arg(long = "versions", value_delimiter = ',', value_name = "VERSION")]
+        versions: Vec<String>,
+        /// File name of the workflow in .github/workflows; `.yml` is added without
+        /// a .yml or .yaml extension
+        /// File name of the workflow
+        #[arg(long, default_value = "ci.yml")]
+        name: String,
+
+        /// Overwrite the workflow if it exists
+        #[arg(long)]
+        force: bool,
+
+        /// Take the detected and default answers instead of asking
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Show which GitHub and GitLab credentials will be used
+    Auth {
+        #[command(subcommand)]
+        command: auth::AuthCommand,
+    },
+
+    /// Install git hooks that validate workflow files before commits or pushes
+    Hook {
+        #[command(subcommand)]
+        command: hook::HookCommand,
+    },
+
+    /// Run a language server giving editors diagnostics, hover docs and completion
+    /// for workflow files, over stdin and stdout
+    Lsp,
+
+    /// Serve an HTTP API to list, validate and run workflows, stream their logs and
+    /// query the run history
+    Serve {
+        /// Port to listen on
+        #[arg(short, long, default_value_t = 8080)]
+        port: u16,
+
+        /// Address to listen on; other machines can reach the API unless it's a
+        /// loopback address
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+
+        /// Container runtime runs use (docker, podman, emulation, secure-emulation)
+        #[arg(short, long, value_enum, default_value = "docker")]
+        runtime: RuntimeChoice,
+
+        /// Require `Authorization: Bearer <TOKEN>` on every request; one is made up
+        /// when listening on other than a loopback address without it
+        #[arg(
+            long,
+            value_name = "TOKEN",
+            env = "WRKFLW_API_TOKEN",
+            hide_env_values = true
+        )]
+        token: Option<String>,
+
+        /// Take GitHub and GitLab webhooks at /webhook, signed with this secret, and
+        /// run the workflows their pushes and pull requests trigger
+        #[arg(
+            long,
+            value_name = "SECRET",
+            env = "WRKFLW_WEBHOOK_SECRET",
+            hide_env_values = true
+        )]
+        webhook_secret: Option<String>,
+    },
+}
+
+/// Report formats `run --report` can write
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReportFormat {
+    Junit,
+}
+
+fn parse_report(s: &str) -> Result<(ReportFormat, PathBuf), String> {
+    let (format, path) = s
+        .split_once('=')
+        .ok_or_else(|| format!("invalid FORMAT=FILE: no `=` found in `{}`", s))?;
+    let format = match format {
+        "junit" => ReportFormat::Junit,
+        other => {
+            return Err(format!(
+                "unknown report format `{}` (expected junit)",
+                other
+            ))
+        }
+    };
+    if path.is_empty() {
+        return Err("the report file name is empty".to_string());
+    }
+    Ok((format, PathBuf::from(path)))
 }
 
 // Parser function for key-value pairs
@@ -146,6 +924,71 @@ fn parse_key_val(s: &str) -> Result<(String, String), String> {
     Ok((s[..pos].to_string(), s[pos + 1..].to_string()))
 }
 
+/// Sandbox for secure emulation from --sandbox-policy/--sandbox-strict; exits on an invalid policy
+fn sandbox_config_from_args(
+    policy: Option<&std::path::Path>,
+    strict: bool,
+) -> Option<wrkflw_executor::SandboxConfig> {
+    let mut config = match policy {
+        Some(path) => match wrkflw_runtime::policy::load_sandbox_policy(path) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None if strict => return Some(wrkflw_runtime::sandbox::create_strict_sandbox_config()),
+        None => return None,
+    };
+    if strict {
+        config.strict_mode = true;
+    }
+    Some(config)
+}
+
+/// Variables from --env-file files, in order, then --env; exits on an unreadable file
+fn user_env_from_args(env: &[(String, String)], env_files: &[PathBuf]) -> HashMap<String, String> {
+    let mut vars = HashMap::new();
+    for file in env_files {
+        match wrkflw_executor::environment::load_dotenv(file) {
+            Ok(file_vars) => vars.extend(file_vars),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    vars.extend(env.iter().cloned());
+    vars
+}
+
+fn parse_memory_limit(s: &str) -> Result<String, String> {
+    wrkflw_executor::docker::parse_memory_limit(s).map(|_| s.to_string())
+}
+
+fn parse_mount(s: &str) -> Result<String, String> {
+    let current_dir = std::env::current_dir().unwrap_or_default();
+    wrkflw_executor::workspace::parse_mount(s, &current_dir)
+}
+
+fn parse_severity(s: &str) -> Result<Severity, String> {
+    Severity::parse(s).ok_or_else(|| {
+        format!(
+            "Invalid severity '{}', expected one of: error, warning, info",
+            s
+        )
+    })
+}
+
+fn parse_output_limit(s: &str) -> Result<usize, String> {
+    if s.trim() == "0" {
+        return Ok(0);
+    }
+    wrkflw_executor::docker::parse_memory_limit(s)
+        .map(|bytes| bytes as usize)
+        .map_err(|_| format!("Invalid output limit '{}'", s))
+}
+
 // Make this function public for testing? Or move to a utils/cleanup mod?
 // Or call wrkflw_executor::cleanup and wrkflw_runtime::cleanup directly?
 // Let's try calling them directly for now.
@@ -186,40 +1029,161 @@ async fn cleanup_on_exit() {
     wrkflw_logging::info("Resource cleanup completed");
 }
 
-async fn handle_signals() {
+/// Exit code after Ctrl+C, as shells report a process ended by SIGINT
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+async fn wait_for_ctrl_c() {
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        // Log the error but continue with cleanup
+        eprintln!("Warning: Failed to properly listen for ctrl+c event: {}", e);
+    }
+}
+
+async fn handle_signals(cancel: wrkflw_executor::cancel::RunCancel) {
     // Set up a hard exit timer in case cleanup takes too long
     // This ensures the app always exits even if Docker operations are stuck
     let hard_exit_time = std::time::Duration::from_secs(10);
 
-    // Wait for Ctrl+C
-    match tokio::signal::ctrl_c().await {
-        Ok(_) => {
-            println!("Received Ctrl+C, shutting down and cleaning up...");
-        }
-        Err(e) => {
-            // Log the error but continue with cleanup
-            eprintln!("Warning: Failed to properly listen for ctrl+c event: {}", e);
-            println!("Shutting down and cleaning up...");
-        }
+    wait_for_ctrl_c().await;
+
+    // A run is cancelled rather than cut short: its steps are killed, the rest
+    // are skipped, and the command reports it and exits with INTERRUPTED_EXIT_CODE
+    // once the run returns, as do commands that would go on after it, like `watch`
+    if cancel.is_running() {
+        println!("Received Ctrl+C, cancelling the run (press Ctrl+C again to exit at once)...");
+        cancel.cancel_run().await;
+        wait_for_ctrl_c().await;
+        println!("Exiting without waiting for the run...");
     }
+    println!("Shutting down and cleaning up...");
 
     // Set up a watchdog thread that will force exit if cleanup takes too long
     // This is important because Docker operations can sometimes hang indefinitely
     let _ = std::thread::spawn(move || {
         std::thread::sleep(hard_exit_time);
+        wrkflw_ui::restore_terminal();
         eprintln!(
             "Cleanup taking too long (over {} seconds), forcing exit...",
             hard_exit_time.as_secs()
         );
         wrkflw_logging::error("Forced exit due to cleanup timeout");
-        std::process::exit(1);
+        std::process::exit(INTERRUPTED_EXIT_CODE);
     });
 
     // Clean up containers
     cleanup_on_exit().await;
 
-    // Exit with success status - the force exit thread will be terminated automatically
-    std::process::exit(0);
+    wrkflw_ui::restore_terminal();
+    std::process::exit(INTERRUPTED_EXIT_CODE);
+}
+
+/// Determines if a file is a Jenkinsfile based on its name, e.g. `Jenkinsfile`,
+/// `Jenkinsfile.release` or `deploy.jenkinsfile`
+fn is_jenkinsfile(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| {
+            name.starts_with("Jenkinsfile") || name.to_lowercase().ends_with(".jenkinsfile")
+        })
+}
+
+/// Whether `path` is an action's metadata file
+fn is_action_metadata(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name == "action.yml" || name == "action.yaml")
+}
+
+/// Whether `path` is a fragment of a GitLab pipeline in `.gitlab/ci`, which the
+/// pipeline includes
+fn is_gitlab_fragment(path: &Path) -> bool {
+    path.parent()
+        .is_some_and(|parent| parent.ends_with(".gitlab/ci"))
+}
+
+/// Determines if a file is an Azure Pipelines file based on its name, location and content
+fn is_azure_pipeline(path: &Path) -> bool {
+    let is_yaml = path
+        .extension()
+        .is_some_and(|ext| ext == "yml" || ext == "yaml");
+    if !is_yaml {
+        return false;
+    }
+
+    // azure-pipelines.yml, or a variant such as azure-pipelines-release.yml
+    if path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with("azure-pipelines"))
+    {
+        return true;
+    }
+
+    // Pipelines kept in the directories Azure DevOps suggests
+    if path.parent().is_some_and(|parent| {
+        parent.ends_with(".azure-pipelines") || parent.ends_with(".azuredevops")
+    }) {
+        return true;
+    }
+
+    // Tasks and Microsoft-hosted agent images only exist in Azure Pipelines
+    if let Ok(content) = std::fs::read_to_string(path) {
+        let azure_only = content.lines().any(|line| {
+            let line = line.trim_start();
+            line.starts_with("- task:") || line.starts_with("vmImage:")
+        });
+        return azure_only && !content.contains("runs-on:");
+    }
+
+    false
+}
+
+/// Print the stages of the GitLab pipeline being run as they start and finish,
+/// until the returned task is aborted
+fn spawn_stage_printer() -> tokio::task::JoinHandle<()> {
+    wrkflw_executor::progress::listen();
+    tokio::spawn(async {
+        loop {
+            print_stage_events();
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+    })
+}
+
+fn print_stage_events() {
+    use wrkflw_executor::progress::ProgressEvent;
+    for event in wrkflw_executor::progress::take() {
+        match event {
+            ProgressEvent::StageStarted {
+                stage,
+                index,
+                total,
+                jobs,
+            } => println!(
+                "▶ Stage {}/{} {}: {}",
+                index + 1,
+                total,
+                stage,
+                jobs.join(", ")
+            ),
+            ProgressEvent::StageFinished { stage, status } => println!(
+                "{} Stage {} {}",
+                match status {
+                    wrkflw_executor::JobStatus::Success => "✅",
+                    wrkflw_executor::JobStatus::Failure => "❌",
+                    wrkflw_executor::JobStatus::Skipped => "⏭️",
+                    wrkflw_executor::JobStatus::Cancelled => "🚫",
+                },
+                stage,
+                match status {
+                    wrkflw_executor::JobStatus::Success => "passed",
+                    wrkflw_executor::JobStatus::Failure => "failed",
+                    wrkflw_executor::JobStatus::Skipped => "skipped",
+                    wrkflw_executor::JobStatus::Cancelled => "cancelled",
+                }
+            ),
+            _ => {}
+        }
+    }
 }
 
 /// Determines if a file is a GitLab CI/CD pipeline based on its name and content
@@ -292,10 +1256,40 @@ async fn main() {
         default_panic_hook(info);
     }));
 
-    let cli = Wrkflw::parse();
+    let mut cli = Wrkflw::parse();
+
+    // A local rerun is a run resuming the run it reruns
+    let mut resume = None;
+    if let Some(Commands::Rerun {
+        run_id,
+        from_failed: true,
+        run_args,
+        ..
+    }) = &cli.command
+    {
+        match rerun::from_failed(run_id.as_deref().unwrap_or("latest"), run_args) {
+            Ok((command, from)) => {
+                cli.command = Some(command);
+                resume = Some(from);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
     let verbose = cli.verbose;
     let debug = cli.debug;
 
+    wrkflw_logging::set_log_format(cli.log_format.into());
+    if let Some(path) = &cli.log_file {
+        if let Err(e) = wrkflw_logging::log_to_file(path, wrkflw_logging::LogFileOptions::default())
+        {
+            eprintln!("Cannot write logs to {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    }
+
     // Set log level based on command line flags
     if debug {
         wrkflw_logging::set_log_level(wrkflw_logging::LogLevel::Debug);
@@ -307,88 +1301,212 @@ async fn main() {
         wrkflw_logging::set_log_level(wrkflw_logging::LogLevel::Warning);
     }
 
-    // Setup a Ctrl+C handler that runs in the background
-    tokio::spawn(handle_signals());
+    // The language server validates a copy of the document at every change,
+    // which would only fill the cache
+    wrkflw_utils::cache::set_enabled(!cli.no_cache && !matches!(cli.command, Some(Commands::Lsp)));
+
+    // Setup a Ctrl+C handler that runs in the background; it cancels the runs
+    // given `cancel`
+    let cancel = wrkflw_executor::cancel::RunCancel::new();
+    tokio::spawn(handle_signals(cancel.clone()));
 
     match &cli.command {
         Some(Commands::Validate {
             paths,
             gitlab,
+            azure,
+            jenkins,
             exit_code,
             no_exit_code,
+            watch,
+            recursive,
+            remote_lint,
+            max_warnings,
+            fail_on,
+            show_suppressed,
+            baseline,
+            update_baseline,
+            staged_only,
+            policy,
+            format,
         }) => {
-            // Determine the paths to validate (default to .github/workflows when none provided)
-            let validate_paths: Vec<PathBuf> = if paths.is_empty() {
-                vec![PathBuf::from(".github/workflows")]
-            } else {
+            // Determine the paths to validate (default to .github/workflows, or
+            // azure-pipelines.yml with --azure, Jenkinsfile with --jenkins and the
+            // current directory with --recursive, when none provided)
+            let validate_paths: Vec<PathBuf> = if !paths.is_empty() {
                 paths.clone()
+            } else if *recursive {
+                vec![PathBuf::from(".")]
+            } else if *azure {
+                vec![PathBuf::from("azure-pipelines.yml")]
+            } else if *jenkins {
+                vec![PathBuf::from("Jenkinsfile")]
+            } else {
+                vec![PathBuf::from(".github/workflows")]
             };
 
-            // Determine if we're validating a GitLab pipeline based on the --gitlab flag or file detection
-            let force_gitlab = *gitlab;
-            let mut validation_failed = false;
-
-            for validate_path in validate_paths {
-                // Check if the path exists; if not, mark failure but continue
-                if !validate_path.exists() {
-                    eprintln!("Error: Path does not exist: {}", validate_path.display());
-                    validation_failed = true;
-                    continue;
-                }
+            // Platform given by --gitlab, --azure or --jenkins; without any it's
+            // detected per file
+            let forced = if *gitlab {
+                Some(Platform::GitLab)
+            } else if *azure {
+                Some(Platform::Azure)
+            } else if *jenkins {
+                Some(Platform::Jenkins)
+            } else {
+                None
+            };
 
-                if validate_path.is_dir() {
-                    // Validate all workflow files in the directory
-                    let entries = std::fs::read_dir(&validate_path)
-                        .expect("Failed to read directory")
-                        .filter_map(|entry| entry.ok())
-                        .filter(|entry| {
-                            entry.path().is_file()
-                                && entry
-                                    .path()
-                                    .extension()
-                                    .is_some_and(|ext| ext == "yml" || ext == "yaml")
-                        })
-                        .collect::<Vec<_>>();
-
-                    println!(
-                        "Validating {} workflow file(s) in {}...",
-                        entries.len(),
-                        validate_path.display()
+            if *watch {
+                // Without paths, watch whichever of the GitHub and GitLab files exist
+                let watch_paths: Vec<PathBuf> = if paths.is_empty() {
+                    [
+                        ".github/workflows",
+                        ".gitlab-ci.yml",
+                        "azure-pipelines.yml",
+                        "Jenkinsfile",
+                    ]
+                    .into_iter()
+                    .map(PathBuf::from)
+                    .filter(|path| path.exists())
+                    .collect()
+                } else {
+                    validate_paths
+                };
+                if watch_paths.is_empty() {
+                    eprintln!(
+                        "Error: No .github/workflows, .gitlab-ci.yml, azure-pipelines.yml or Jenkinsfile to watch"
                     );
-
-                    for entry in entries {
-                        let path = entry.path();
-                        let is_gitlab = force_gitlab || is_gitlab_pipeline(&path);
-
-                        let file_failed = if is_gitlab {
-                            validate_gitlab_pipeline(&path, verbose)
-                        } else {
-                            validate_github_workflow(&path, verbose)
-                        };
-
-                        if file_failed {
-                            validation_failed = true;
-                        }
+                    std::process::exit(1);
+                }
+                if let Err(e) = watch::validate_on_change(&watch_paths, forced) {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+                return;
+            }
+            let report = if *staged_only {
+                validation::ValidationReport::for_staged(forced)
+            } else {
+                Ok(validation::ValidationReport::for_paths(
+                    &validate_paths,
+                    forced,
+                    *recursive,
+                ))
+            };
+            let mut report = match report {
+                Ok(report) => report,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            if *remote_lint {
+                report.add_remote_lint(forced).await;
+            }
+            // The requirements are only checked, and their exit codes used, with --policy
+            let policy = if *policy {
+                let config = std::env::current_dir()
+                    .map_err(|e| e.to_string())
+                    .and_then(|dir| wrkflw_executor::config::ProjectConfig::load(&dir));
+                match config {
+                    Ok(config) if config.policy.is_empty() => {
+                        eprintln!(
+                            "Error: --policy needs requirements in the [policy] section of .wrkflw.toml"
+                        );
+                        std::process::exit(1);
                     }
-                } else {
-                    // Validate a single workflow file
-                    let is_gitlab = force_gitlab || is_gitlab_pipeline(&validate_path);
-
-                    let file_failed = if is_gitlab {
-                        validate_gitlab_pipeline(&validate_path, verbose)
-                    } else {
-                        validate_github_workflow(&validate_path, verbose)
-                    };
-
-                    if file_failed {
-                        validation_failed = true;
+                    Ok(config) => config.policy,
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            } else {
+                wrkflw_executor::policy::WorkflowPolicy::default()
+            };
+            if !policy.is_empty() {
+                report.add_policy(&policy, forced, *staged_only);
+            }
+            let text = matches!(format, ValidateFormat::Text);
+            let mut baseline_notes = Vec::new();
+            if let Some(baseline_path) = baseline {
+                let existing = match baseline::Baseline::load(baseline_path) {
+                    Ok(existing) => existing.filter(|_| !*update_baseline),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                };
+                let recorded = existing.is_none();
+                let baseline = existing.unwrap_or_else(|| baseline::Baseline::of(&report));
+                if recorded {
+                    if let Err(e) = baseline.save(baseline_path) {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                    baseline_notes.push(format!(
+                        "Recorded {} finding(s) in {}",
+                        baseline.findings.len(),
+                        baseline_path.display()
+                    ));
+                }
+                let gone = baseline.apply(&mut report);
+                if !recorded && report.baselined_count() > 0 {
+                    baseline_notes.push(format!(
+                        "{} finding(s) already in {} not reported",
+                        report.baselined_count(),
+                        baseline_path.display()
+                    ));
+                }
+                if gone > 0 {
+                    baseline_notes.push(format!(
+                        "{} finding(s) of {} are fixed; run with --update-baseline to drop them",
+                        gone,
+                        baseline_path.display()
+                    ));
+                }
+            }
+            match format {
+                ValidateFormat::Json => match serde_json::to_string_pretty(&report) {
+                    Ok(json) => println!("{}", json),
+                    Err(e) => {
+                        eprintln!("Error: Failed to serialize validation report: {}", e);
+                        std::process::exit(1);
+                    }
+                },
+                ValidateFormat::Text if *staged_only => {
+                    if report.files.is_empty() {
+                        println!("No staged workflow files to validate");
                     }
+                    report.print();
+                }
+                ValidateFormat::Text => report.print_summary(*show_suppressed),
+            }
+            if text {
+                for note in &baseline_notes {
+                    println!("ℹ️  {}", note);
                 }
             }
 
-            // Set exit code if validation failed and exit_code flag is true (and no_exit_code is false)
-            if validation_failed && *exit_code && !*no_exit_code {
-                std::process::exit(1);
+            // --fail-on overrides the project's threshold
+            let fail_on = fail_on
+                .or(validation::validation_config().fail_on)
+                .unwrap_or(Severity::Error);
+            let warnings = report.warning_count();
+            let too_many_warnings = max_warnings.is_some_and(|max| warnings > max);
+            if too_many_warnings && text {
+                println!(
+                    "⚠️  {} warning(s), more than --max-warnings allows",
+                    warnings
+                );
+            }
+            let failed = report.fails(fail_on) || too_many_warnings;
+            if failed && *exit_code && !*no_exit_code {
+                if too_many_warnings {
+                    std::process::exit(1);
+                }
+                std::process::exit(report.exit_code(fail_on, &policy));
             }
         }
         Some(Commands::Run {
@@ -396,16 +1514,155 @@ async fn main() {
             runtime,
             show_action_messages: _,
             preserve_containers_on_failure,
+            debug_on_failure,
             gitlab,
+            github_token,
+            github_token_from_env,
+            cpus,
+            memory,
+            pids_limit,
+            network,
+            pull,
+            workspace,
+            file_ownership,
+            mount,
+            sandbox_policy,
+            sandbox_strict,
+            windows_runners,
+            macos_runners,
+            docker_user,
+            docker_userns,
+            podman_userns,
+            podman_security_opt,
+            selinux_label,
+            offline,
+            dry_run,
+            save_logs,
+            no_history,
+            no_log_files,
+            output_limit,
+            step_timeout,
+            pull_timeout,
+            env,
+            env_file,
+            var,
+            gitlab_var,
+            github_vars,
+            jobs,
+            with_needs,
+            skip_jobs,
+            steps,
+            skip_steps,
+            run_manual,
+            since,
+            watch,
+            report,
+            timing_json,
+            timing_folded,
         }) => {
+            let sandbox_config =
+                sandbox_config_from_args(sandbox_policy.as_deref(), *sandbox_strict);
+            let env = user_env_from_args(env, env_file);
+            let vars = match vars::run_vars(*github_vars, var).await {
+                Ok(vars) => vars,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            let changed_files =
+                since
+                    .as_deref()
+                    .map(|base| match wrkflw_executor::paths::changed_since(base) {
+                        Ok(files) => {
+                            println!("🔍 {} file(s) changed since {}", files.len(), base);
+                            files
+                        }
+                        Err(e) => {
+                            eprintln!("Error finding the files changed since {}: {}", base, e);
+                            std::process::exit(1);
+                        }
+                    });
+
             // Create execution configuration
+            let runtime_type: wrkflw_executor::RuntimeType = runtime.clone().into();
+            let runtime_mode = runtime_type.mode();
             let config = wrkflw_executor::ExecutionConfig {
-                runtime_type: runtime.clone().into(),
+                runtime_type,
                 verbose,
                 preserve_containers_on_failure: *preserve_containers_on_failure,
-                secrets_config: None, // Use default secrets configuration
+                github_token: github_token.clone(),
+                github_token_from_env: *github_token_from_env,
+                cpu_limit: *cpus,
+                memory_limit: memory.clone(),
+                pids_limit: *pids_limit,
+                network_mode: (*network).into(),
+                pull_policy: (*pull).into(),
+                workspace: (*workspace).into(),
+                file_ownership: (*file_ownership).into(),
+                resume: resume.take(),
+                run_manual: *run_manual,
+                gitlab_vars: gitlab_var.iter().cloned().collect(),
+                filter: wrkflw_executor::filter::RunFilter {
+                    jobs: jobs.clone(),
+                    with_needs: *with_needs,
+                    skip_jobs: skip_jobs.clone(),
+                    steps: wrkflw_executor::filter::StepFilter {
+                        only: steps.clone(),
+                        skip: skip_steps.clone(),
+                    },
+                },
+                sandbox_config,
+                runner_os_policy: wrkflw_executor::RunnerOsPolicy {
+                    windows: windows_runners.clone(),
+                    macos: macos_runners.clone(),
+                },
+                podman_options: wrkflw_executor::podman::PodmanOptions {
+                    userns: podman_userns.clone(),
+                    security_opts: podman_security_opt.clone(),
+                    volume_label: selinux_label.map(Into::into),
+                    extra_mounts: mount.clone(),
+                    debug_on_failure: *debug_on_failure,
+                    ..Default::default()
+                },
+                docker_options: wrkflw_executor::docker::DockerRuntimeConfig {
+                    debug_on_failure: *debug_on_failure,
+                    extra_mounts: mount.clone(),
+                    user: docker_user.clone(),
+                    userns_mode: docker_userns.clone(),
+                    ..Default::default()
+                },
+                offline: *offline,
+                changed_files,
+                log_files: !*no_log_files,
+                env,
+                vars,
+                output_limit: *output_limit,
+                timeouts: wrkflw_executor::Timeouts {
+                    step: *step_timeout,
+                    pull: *pull_timeout,
+                    ..Default::default()
+                },
+                cancel: cancel.clone(),
+                ..Default::default()
             };
 
+            if !*gitlab && is_azure_pipeline(path) {
+                eprintln!(
+                    "Error: {} is an Azure Pipelines file; these can be validated but not run",
+                    path.display()
+                );
+                std::process::exit(1);
+            }
+            if !*gitlab && is_jenkinsfile(path) {
+                eprintln!(
+                    "Error: {} is a Jenkinsfile; these can be validated but not run",
+                    path.display()
+                );
+                std::process::exit(1);
+            }
+
             // Check if we're explicitly or implicitly running a GitLab pipeline
             let is_gitlab = *gitlab || is_gitlab_pipeline(path);
             let workflow_type = if is_gitlab {
@@ -414,8 +1671,33 @@ async fn main() {
                 "GitHub workflow"
             };
 
+            if *dry_run {
+                match wrkflw_executor::plan_workflow(path, &config).await {
+                    Ok(plan) => print!("{}", plan),
+                    Err(e) => {
+                        eprintln!("Error planning {}: {}", workflow_type, e);
+                        std::process::exit(1);
+                    }
+                }
+                return;
+            }
+
+            if let Some(sources) = watch {
+                if let Err(e) = watch::run_on_change(path, config, sources).await {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+                if cancel.is_run_cancelled() {
+                    std::process::exit(INTERRUPTED_EXIT_CODE);
+                }
+                return;
+            }
+
             wrkflw_logging::info(&format!("Running {} at: {}", workflow_type, path.display()));
 
+            // Show the stages of a pipeline as they start and finish
+            let stage_printer = is_gitlab.then(spawn_stage_printer);
+
             // Execute the workflow
             let result = wrkflw_executor::execute_workflow(path, config)
                 .await
@@ -423,9 +1705,68 @@ async fn main() {
                     eprintln!("Error executing workflow: {}", e);
                     std::process::exit(1);
                 });
+            if let Some(printer) = stage_printer {
+                printer.abort();
+                print_stage_events();
+                wrkflw_executor::progress::stop();
+            }
+
+            let workflow_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let timing = wrkflw_executor::TimingReport::new(&workflow_name, &result);
+            let reports = report.iter().map(|(format, file)| {
+                let contents = match format {
+                    ReportFormat::Junit => {
+                        wrkflw_executor::junit::junit_report(&workflow_name, &result)
+                    }
+                };
+                (Some(file), contents)
+            });
+            let timing_files = [
+                (timing_json.as_ref(), timing.to_json()),
+                (timing_folded.as_ref(), timing.to_folded()),
+            ];
+            for (file, contents) in reports.chain(timing_files) {
+                if let Some(file) = file {
+                    if let Err(e) = std::fs::write(file, contents) {
+                        eprintln!("Error writing {}: {}", file.display(), e);
+                    }
+                }
+            }
+
+            if !*no_history {
+                let store = std::env::current_dir()
+                    .map(|dir| wrkflw_executor::runs::RunStore::for_project(&dir))
+                    .map_err(|e| e.to_string());
+                match store.and_then(|store| store.save(path, &result, runtime_mode, *save_logs)) {
+                    Ok(run) if *save_logs => {
+                        println!("📁 Logs saved as run {} (see `wrkflw runs show`)", run.id)
+                    }
+                    Ok(run) => wrkflw_logging::info(&format!("Recorded as run {}", run.id)),
+                    Err(e) => eprintln!("Error recording the run: {}", e),
+                }
+            }
+            if !*no_log_files {
+                if let Some(id) = wrkflw_executor::logs::last_run_id() {
+                    wrkflw_logging::info(&format!(
+                        "Logs written to .wrkflw/logs/{} (see `wrkflw logs {}`)",
+                        id, id
+                    ));
+                }
+            }
+
+            if !result.deployments.is_empty() {
+                println!(
+                    "\n{}",
+                    wrkflw_executor::deployments::summary(&result.deployments)
+                );
+            }
 
             // Print execution summary
             if result.failure_details.is_some() {
+                eprintln!("\n{}", timing);
                 eprintln!("❌ Workflow execution failed:");
                 if let Some(details) = result.failure_details {
                     if verbose {
@@ -435,7 +1776,11 @@ async fn main() {
                         // Show simplified error info in non-verbose mode
                         let simplified_error = details
                             .lines()
-                            .filter(|line| line.contains("❌") || line.trim().starts_with("Error:"))
+                            .filter(|line| {
+                                line.contains("❌")
+                                    || line.contains("🚫")
+                                    || line.trim().starts_with("Error:")
+                            })
                             .take(5) // Limit to the first 5 error lines
                             .collect::<Vec<&str>>()
                             .join("\n");
@@ -447,6 +1792,9 @@ async fn main() {
                         }
                     }
                 }
+                if cancel.is_run_cancelled() {
+                    std::process::exit(INTERRUPTED_EXIT_CODE);
+                }
                 std::process::exit(1);
             } else {
                 println!("✅ Workflow execution completed successfully!");
@@ -457,17 +1805,23 @@ async fn main() {
                     println!("\nJob summary:");
                     for job in result.jobs {
                         println!(
-                            "  {} {} ({})",
+                            "  {} {}{} ({})",
                             match job.status {
                                 wrkflw_executor::JobStatus::Success => "✅",
                                 wrkflw_executor::JobStatus::Failure => "❌",
                                 wrkflw_executor::JobStatus::Skipped => "⏭️",
+                                wrkflw_executor::JobStatus::Cancelled => "🚫",
                             },
                             job.name,
+                            job.environment
+                                .as_ref()
+                                .map(|environment| format!(" → {}", environment))
+                                .unwrap_or_default(),
                             match job.status {
                                 wrkflw_executor::JobStatus::Success => "success",
                                 wrkflw_executor::JobStatus::Failure => "failure",
                                 wrkflw_executor::JobStatus::Skipped => "skipped",
+                                wrkflw_executor::JobStatus::Cancelled => "cancelled",
                             }
                         );
 
@@ -480,7 +1834,12 @@ async fn main() {
                                 wrkflw_executor::StepStatus::Skipped => "⏭️",
                             };
 
-                            println!("    {} {}", step_status, step.name);
+                            let truncated = if wrkflw_runtime::output::is_truncated(&step.output) {
+                                " ✂️  output truncated"
+                            } else {
+                                ""
+                            };
+                            println!("    {} {}{}", step_status, step.name, truncated);
 
                             // If step failed and we're not in verbose mode, show condensed error info
                             if step.status == wrkflw_executor::StepStatus::Failure && !verbose {
@@ -511,16 +1870,36 @@ async fn main() {
                         }
                     }
                 }
+
+                println!("\n{}", timing);
             }
 
-            // Cleanup is handled automatically via the signal handler
+            if cancel.is_run_cancelled() {
+                std::process::exit(INTERRUPTED_EXIT_CODE);
+            }
         }
-        Some(Commands::TriggerGitlab { branch, variable }) => {
+        Some(Commands::TriggerGitlab {
+            branch,
+            variable,
+            watch,
+        }) => {
             // Convert optional Vec<(String, String)> to Option<HashMap<String, String>>
             let variables = variable
                 .as_ref()
                 .map(|v| v.iter().cloned().collect::<HashMap<String, String>>());
 
+            if *watch {
+                match remote::trigger_gitlab_and_watch(branch.as_deref(), variables).await {
+                    Ok(true) => {}
+                    Ok(false) => std::process::exit(1),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                return;
+            }
+
             // Trigger the pipeline
             if let Err(e) = wrkflw_gitlab::trigger_pipeline(branch.as_deref(), variables).await {
                 eprintln!("Error triggering GitLab pipeline: {}", e);
@@ -532,9 +1911,16 @@ async fn main() {
             runtime,
             show_action_messages: _,
             preserve_containers_on_failure,
+            sandbox_policy,
+            sandbox_strict,
+            env,
+            env_file,
         }) => {
             // Set runtime type based on the runtime choice
             let runtime_type = runtime.clone().into();
+            let sandbox_config =
+                sandbox_config_from_args(sandbox_policy.as_deref(), *sandbox_strict);
+            let env = user_env_from_args(env, env_file);
 
             // Call the TUI implementation from the ui crate
             if let Err(e) = wrkflw_ui::run_wrkflw_tui(
@@ -542,6 +1928,8 @@ async fn main() {
                 runtime_type,
                 verbose,
                 *preserve_containers_on_failure,
+                sandbox_config,
+                env,
             )
             .await
             {
@@ -553,12 +1941,25 @@ async fn main() {
             workflow,
             branch,
             input,
+            watch,
         }) => {
             // Convert optional Vec<(String, String)> to Option<HashMap<String, String>>
             let inputs = input
                 .as_ref()
                 .map(|i| i.iter().cloned().collect::<HashMap<String, String>>());
 
+            if *watch {
+                match remote::trigger_and_watch(workflow, branch.as_deref(), inputs).await {
+                    Ok(true) => {}
+                    Ok(false) => std::process::exit(1),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                return;
+            }
+
             // Trigger the workflow
             if let Err(e) =
                 wrkflw_github::trigger_workflow(workflow, branch.as_deref(), inputs).await
@@ -567,141 +1968,256 @@ async fn main() {
                 std::process::exit(1);
             }
         }
-        Some(Commands::List) => {
-            list_workflows_and_pipelines(verbose);
-        }
-        None => {
-            // Launch TUI by default when no command is provided
-            let runtime_type = wrkflw_executor::RuntimeType::Docker;
-
-            // Call the TUI implementation from the ui crate with default path
-            if let Err(e) = wrkflw_ui::run_wrkflw_tui(None, runtime_type, verbose, false).await {
-                eprintln!("Error running TUI: {}", e);
+        Some(Commands::List { paths, json }) => {
+            if let Err(e) = list::handle_list_command(paths, *json) {
+                eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
         }
-    }
-}
-
-/// Validate a GitHub workflow file
-/// Returns true if validation failed, false if it passed
-fn validate_github_workflow(path: &Path, verbose: bool) -> bool {
-    print!("Validating GitHub workflow file: {}... ", path.display());
-
-    // Use the ui crate's validate_workflow function
-    match wrkflw_ui::validate_workflow(path, verbose) {
-        Ok(_) => {
-            // The detailed validation output is already printed by the function
-            // We need to check if there were validation issues
-            // Since wrkflw_ui::validate_workflow doesn't return the validation result directly,
-            // we need to call the evaluator directly to get the result
-            match wrkflw_evaluator::evaluate_workflow_file(path, verbose) {
-                Ok(result) => !result.is_valid,
-                Err(_) => true, // Parse errors count as validation failure
+        Some(Commands::Explain { path }) => {
+            if let Err(e) = explain::handle_explain_command(path) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
             }
         }
-        Err(e) => {
-            eprintln!("Error validating workflow: {}", e);
-            true // Any error counts as validation failure
+        Some(Commands::Estimate {
+            path,
+            per_month,
+            assume_minutes,
+            price,
+            format,
+        }) => {
+            let options = estimate::EstimateOptions {
+                path: path.clone(),
+                per_month: per_month.clone(),
+                assumed_minutes: *assume_minutes,
+                prices: price.clone(),
+                format: *format,
+            };
+            if let Err(e) = estimate::handle_estimate_command(&options) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
         }
-    }
-}
-
-/// Validate a GitLab CI/CD pipeline file
-/// Returns true if validation failed, false if it passed
-fn validate_gitlab_pipeline(path: &Path, verbose: bool) -> bool {
-    print!("Validating GitLab CI pipeline file: {}... ", path.display());
-
-    // Parse and validate the pipeline file
-    match wrkflw_parser::gitlab::parse_pipeline(path) {
-        Ok(pipeline) => {
-            println!("✅ Valid syntax");
-
-            // Additional structural validation
-            let validation_result = wrkflw_validators::validate_gitlab_pipeline(&pipeline);
-
-            if !validation_result.is_valid {
-                println!("⚠️  Validation issues:");
-                for issue in validation_result.issues {
-                    println!("   - {}", issue);
+        Some(Commands::Graph { path, format }) => match wrkflw_executor::build_graph(path) {
+            Ok(graph) => print!("{}", graph.render((*format).into())),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Some(Commands::Schedule {
+            command,
+            path,
+            count,
+        }) => {
+            let result = match (command, path) {
+                (Some(command), _) => {
+                    schedule::handle_schedule_command(command, verbose, cancel.clone()).await
                 }
-                true // Validation failed
+                (None, Some(path)) => schedule::print_schedule(path, *count),
+                (None, None) => unreachable!("clap requires a path without a subcommand"),
+            };
+            if let Err(e) = result {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            if cancel.is_run_cancelled() {
+                std::process::exit(INTERRUPTED_EXIT_CODE);
+            }
+        }
+        Some(Commands::Secrets { command }) => {
+            if let Err(e) = secrets::handle_secrets_command(command).await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Vars { command }) => {
+            if let Err(e) = vars::handle_vars_command(command).await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Runs {
+            command,
+            remote,
+            limit,
+        }) => {
+            let result = if *remote {
+                remote::list_remote_runs(*limit).await
             } else {
-                if verbose {
-                    println!("✅ All validation checks passed");
+                runs::handle_runs_command(command.as_ref().unwrap_or(&runs::RunsCommand::List))
+            };
+            if let Err(e) = result {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Gitlab { command }) => {
+            if let Err(e) = remote::handle_gitlab_command(command).await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Rerun {
+            run_id,
+            failed_only,
+            ..
+        }) => {
+            let run_id = match run_id.as_deref().unwrap_or_default().parse() {
+                Ok(run_id) => run_id,
+                Err(_) => {
+                    eprintln!(
+                        "Error: GitHub run ids are numbers; rerun local runs with --from-failed"
+                    );
+                    std::process::exit(1);
+                }
+            };
+            if let Err(e) = remote::rerun(run_id, *failed_only).await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::History {
+            command,
+            workflow,
+            limit,
+        }) => {
+            if let Err(e) =
+                runs::handle_history_command(command.as_ref(), workflow.as_deref(), *limit)
+            {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Logs {
+            id,
+            job,
+            follow,
+            list,
+        }) => {
+            if let Err(e) = runs::handle_logs_command(id, job.as_deref(), *follow, *list) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Cache { command }) => {
+            if let Err(e) = cache::handle_cache_command(command) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Audit { paths, fix }) => {
+            if let Err(e) = audit::handle_audit_command(paths, *fix) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Pin { paths, check }) => {
+            if let Err(e) = pin::handle_pin_command(paths, *check) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Doctor) => {
+            if let Err(e) = doctor::handle_doctor_command().await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Images { command }) => {
+            if let Err(e) = images::handle_images_command(command).await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Convert {
+            from,
+            to,
+            path,
+            output,
+        }) => {
+            if let Err(e) = convert::handle_convert_command(*from, *to, path, output.as_deref()) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Init {
+            language,
+            build_tool,
+            events,
+            os,
+            versions,
+            name,
+            force,
+            yes,
+        }) => {
+            let options = init::InitOptions {
+                language: *language,
+                build_tool: *build_tool,
+                events: events.clone(),
+                os: os.clone(),
+                versions: versions.clone(),
+                name: name.clone(),
+                force: *force,
+                yes: *yes,
+            };
+            match init::handle_init_command(options) {
+                Ok(true) => {}
+                Ok(false) => std::process::exit(1),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
                 }
-                false // Validation passed
             }
         }
-        Err(e) => {
-            println!("❌ Invalid");
-            eprintln!("Validation failed: {}", e);
-            true // Parse error counts as validation failure
+        Some(Commands::Auth { command }) => auth::handle_auth_command(command),
+        Some(Commands::Lsp) => {
+            if let Err(e) = lsp::serve() {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
         }
-    }
-}
-
-/// List available workflows and pipelines in the repository
-fn list_workflows_and_pipelines(verbose: bool) {
-    // Check for GitHub workflows
-    let github_path = PathBuf::from(".github/workflows");
-    if github_path.exists() && github_path.is_dir() {
-        println!("GitHub Workflows:");
-
-        let entries = std::fs::read_dir(&github_path)
-            .expect("Failed to read directory")
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| {
-                entry.path().is_file()
-                    && entry
-                        .path()
-                        .extension()
-                        .is_some_and(|ext| ext == "yml" || ext == "yaml")
-            })
-            .collect::<Vec<_>>();
-
-        if entries.is_empty() {
-            println!("  No workflow files found in .github/workflows");
-        } else {
-            for entry in entries {
-                println!("  - {}", entry.path().display());
+        Some(Commands::Serve {
+            port,
+            host,
+            runtime,
+            token,
+            webhook_secret,
+        }) => {
+            let result = serve::serve(
+                host,
+                *port,
+                runtime.clone().into(),
+                token.clone(),
+                webhook_secret.clone(),
+                verbose,
+            )
+            .await;
+            if let Err(e) = result {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
             }
         }
-    } else {
-        println!("GitHub Workflows: No .github/workflows directory found");
-    }
-
-    // Check for GitLab CI pipeline
-    let gitlab_path = PathBuf::from(".gitlab-ci.yml");
-    if gitlab_path.exists() && gitlab_path.is_file() {
-        println!("GitLab CI Pipeline:");
-        println!("  - {}", gitlab_path.display());
-    } else {
-        println!("GitLab CI Pipeline: No .gitlab-ci.yml file found");
-    }
+        Some(Commands::Hook { command }) => match hook::handle_hook_command(command) {
+            Ok(true) => {}
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => {
+            // Launch TUI by default when no command is provided
+            let runtime_type = wrkflw_executor::RuntimeType::Docker;
 
-    // Check for other GitLab CI pipeline files
-    if verbose {
-        println!("Searching for other GitLab CI pipeline files...");
-
-        let entries = walkdir::WalkDir::new(".")
-            .follow_links(true)
-            .into_iter()
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| {
-                entry.path().is_file()
-                    && entry
-                        .file_name()
-                        .to_string_lossy()
-                        .ends_with("gitlab-ci.yml")
-                    && entry.path() != gitlab_path
-            })
-            .collect::<Vec<_>>();
-
-        if !entries.is_empty() {
-            println!("Additional GitLab CI Pipeline files:");
-            for entry in entries {
-                println!("  - {}", entry.path().display());
+            // Call the TUI implementation from the ui crate with default path
+            if let Err(e) =
+                wrkflw_ui::run_wrkflw_tui(None, runtime_type, verbose, false, None, HashMap::new())
+                    .await
+            {
+                eprintln!("Error running TUI: {}", e);
+                std::process::exit(1);
             }
         }
     }
@@ -0,0 +1,180 @@
+//! `wrkflw pin`: pin `uses:` references to commit SHAs, verify existing pins still
+//! match the versions they were pinned from, and lint unpinned third-party actions.
+
+use crate::validation::{relative, workflow_files};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use wrkflw_executor::action_cache::{self, ActionCache};
+use wrkflw_parser::pin::{self, UsesRef};
+
+/// Pin the references in `paths` (or `.github/workflows`); with `check`, only report
+/// unpinned third-party references and stale pins. Fails on what needs attention.
+pub fn handle_pin_command(paths: &[PathBuf], check: bool) -> Result<(), String> {
+    let paths = if paths.is_empty() {
+        vec![PathBuf::from(".github/workflows")]
+    } else {
+        paths.to_vec()
+    };
+    let mut files = Vec::new();
+    for path in &paths {
+        if !path.exists() {
+            return Err(format!("{} does not exist", path.display()));
+        }
+        files.extend(workflow_files(path));
+    }
+
+    let mut resolver = Resolver::new();
+    let (mut pinned, mut problems, mut unpinned) = (0, 0, 0);
+    for file in &files {
+        let content = fs::read_to_string(file)
+            .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+        let mut pins = HashMap::new();
+        let mut lines = Vec::new();
+        for uses in pin::uses_refs(&content) {
+            if uses.is_pinned() {
+                match resolver.verify(&uses) {
+                    Ok(None) => {}
+                    Ok(Some(warning)) => lines.push(format!("  {}: ⚠️  {}", uses.line, warning)),
+                    Err(e) => {
+                        problems += 1;
+                        lines.push(format!("  {}: ❌ {}", uses.line, e));
+                    }
+                }
+            } else if check {
+                if uses.is_third_party() {
+                    unpinned += 1;
+                    lines.push(format!(
+                        "  {}: ❌ {}@{} is a third-party action not pinned to a commit; its owner can change what runs",
+                        uses.line, uses.action, uses.git_ref
+                    ));
+                }
+            } else {
+                match resolver.pin(&uses) {
+                    Ok((commit, version)) => {
+                        lines.push(format!(
+                            "  {}: 📌 {}@{} → {} # {}",
+                            uses.line, uses.action, uses.git_ref, commit, version
+                        ));
+                        pins.insert(uses.line, (commit, version));
+                    }
+                    Err(e) => {
+                        problems += 1;
+                        lines.push(format!("  {}: ❌ {}", uses.line, e));
+                    }
+                }
+            }
+        }
+
+        if lines.is_empty() {
+            continue;
+        }
+        println!("{}", relative(file).display());
+        for line in lines {
+            println!("{}", line);
+        }
+        println!();
+        if !pins.is_empty() {
+            fs::write(file, pin::pin(&content, &pins))
+                .map_err(|e| format!("Failed to write {}: {}", file.display(), e))?;
+            pinned += pins.len();
+        }
+    }
+
+    if pinned > 0 {
+        println!("📌 Pinned {} reference(s)", pinned);
+    }
+    match (problems, unpinned) {
+        (0, 0) => {
+            if pinned == 0 {
+                println!("✅ All references in {} file(s) are pinned", files.len());
+            }
+            Ok(())
+        }
+        (problems, 0) => Err(format!("{} reference(s) need attention", problems)),
+        (problems, unpinned) => Err(format!(
+            "{} unpinned third-party reference(s) and {} other problem(s); `wrkflw pin` pins them",
+            unpinned, problems
+        )),
+    }
+}
+
+/// Resolves references with one `git ls-remote` per repository
+struct Resolver {
+    cache: ActionCache,
+    token: Option<String>,
+    refs: HashMap<(String, String), Result<HashMap<String, String>, String>>,
+}
+
+impl Resolver {
+    fn new() -> Self {
+        Resolver {
+            cache: ActionCache::new(ActionCache::default_root()),
+            token: wrkflw_utils::auth::github_token().map(|credential| credential.token),
+            refs: HashMap::new(),
+        }
+    }
+
+    fn refs(&mut self, uses: &UsesRef) -> Result<&HashMap<String, String>, String> {
+        let key = (uses.owner().to_string(), uses.repo().to_string());
+        if !self.refs.contains_key(&key) {
+            let refs = self
+                .cache
+                .remote_refs(&key.0, &key.1, self.token.as_deref());
+            self.refs.insert(key.clone(), refs);
+        }
+        self.refs[&key].as_ref().map_err(|e| {
+            format!(
+                "Can't list the refs of {}/{}: {}",
+                uses.owner(),
+                uses.repo(),
+                e
+            )
+        })
+    }
+
+    /// The commit `uses` points to and the version to note next to it: the most
+    /// specific tag of the commit extending the reference, such as v4.2.2 for v4
+    fn pin(&mut self, uses: &UsesRef) -> Result<(String, String), String> {
+        let refs = self.refs(uses)?;
+        let commit = action_cache::commit_of(refs, &uses.git_ref).ok_or_else(|| {
+            format!(
+                "{}@{}: no branch or tag named {}",
+                uses.action, uses.git_ref, uses.git_ref
+            )
+        })?;
+        let version = action_cache::tags_at(refs, &commit)
+            .into_iter()
+            .filter(|tag| tag.starts_with(&uses.git_ref))
+            .max_by_key(|tag| tag.len())
+            .unwrap_or_else(|| uses.git_ref.clone());
+        Ok((commit.to_lowercase(), version))
+    }
+
+    /// Check that a pinned reference still matches the version in its comment,
+    /// returning a warning when it can't be checked
+    fn verify(&mut self, uses: &UsesRef) -> Result<Option<String>, String> {
+        let Some(version) = uses.pinned_version() else {
+            return Ok(Some(format!(
+                "{}@{} has no version comment to check the pin against",
+                uses.action, uses.git_ref
+            )));
+        };
+        let refs = self.refs(uses)?;
+        match action_cache::commit_of(refs, version) {
+            Some(commit) if commit.eq_ignore_ascii_case(&uses.git_ref) => Ok(None),
+            Some(commit) => Err(format!(
+                "{}@{} no longer matches {}, which is now at {}",
+                uses.action, uses.git_ref, version, commit
+            )),
+            None => Ok(Some(format!(
+                "{}@{}: {} no longer exists in {}/{}",
+                uses.action,
+                uses.git_ref,
+                version,
+                uses.owner(),
+                uses.repo()
+            ))),
+        }
+    }
+}
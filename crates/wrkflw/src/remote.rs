@@ -0,0 +1,280 @@
+use clap::Subcommand;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use wrkflw_github::runs::{RunUpdate, RunsClient};
+use wrkflw_gitlab::pipelines::{PipelineUpdate, PipelinesClient};
+
+#[derive(Debug, Subcommand)]
+pub enum GitlabCommand {
+    /// Inspect the project's pipelines on GitLab
+    Pipelines {
+        #[command(subcommand)]
+        command: PipelinesCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PipelinesCommand {
+    /// List the latest pipelines, newest first
+    List {
+        /// Number of pipelines to list
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+}
+
+/// Icon of a GitHub run, job or step state or a GitLab pipeline or job status
+pub fn state_icon(state: &str) -> &'static str {
+    match state {
+        "success" => "✅",
+        "failure" | "failed" | "timed_out" | "startup_failure" => "❌",
+        "cancelled" | "canceled" => "🚫",
+        "skipped" | "neutral" => "⏭️",
+        "manual" => "✋",
+        "in_progress" | "running" => "🔄",
+        _ => "⏳",
+    }
+}
+
+/// Where the logs of a remote run are saved
+pub fn logs_path(run_id: u64) -> Result<PathBuf, String> {
+    let project_dir =
+        std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?;
+    Ok(wrkflw_github::runs::logs_file(&project_dir, run_id))
+}
+
+fn print_update(update: &RunUpdate) {
+    let indent = match update {
+        RunUpdate::Job { .. } => "",
+        RunUpdate::Step { .. } => "  ",
+    };
+    println!(
+        "[{}] {}{} {}",
+        chrono::Local::now().format("%H:%M:%S"),
+        indent,
+        state_icon(update.state()),
+        update
+    );
+}
+
+/// Trigger a workflow on GitHub and follow the run it started until it completes,
+/// then download its logs; returns whether the run succeeded
+pub async fn trigger_and_watch(
+    workflow: &str,
+    branch: Option<&str>,
+    inputs: Option<HashMap<String, String>>,
+) -> Result<bool, String> {
+    let client = client()?;
+    // Runs from before the dispatch, to tell the new one apart
+    let known: HashSet<u64> = client
+        .workflow_runs(workflow)
+        .await
+        .map_err(|e| format!("Failed to list runs of {}: {}", workflow, e))?
+        .into_iter()
+        .map(|run| run.id)
+        .collect();
+
+    wrkflw_github::trigger_workflow(workflow, branch, inputs)
+        .await
+        .map_err(|e| format!("Error triggering GitHub workflow: {}", e))?;
+
+    let run = client
+        .wait_for_dispatched_run(workflow, &known)
+        .await
+        .map_err(|e| e.to_string())?;
+    println!("\n👀 Following run #{}: {}", run.run_number, run.html_url);
+    let (run, _) = client
+        .follow(run.id, print_update)
+        .await
+        .map_err(|e| format!("Failed to follow run #{}: {}", run.run_number, e))?;
+
+    let logs = logs_path(run.id)?;
+    match client.download_logs(run.id, &logs).await {
+        Ok(()) => println!("\n📦 Logs saved to {}", logs.display()),
+        Err(e) => eprintln!("\nCould not download the logs: {}", e),
+    }
+    println!(
+        "{} Run #{} finished: {}",
+        state_icon(run.state()),
+        run.run_number,
+        run.state()
+    );
+    Ok(run.succeeded())
+}
+
+pub(crate) fn client() -> Result<RunsClient, String> {
+    let repo = wrkflw_github::get_repo_info().map_err(|e| e.to_string())?;
+    RunsClient::from_env(repo).map_err(|e| e.to_string())
+}
+
+/// Time between two API timestamps, e.g. `4m 05s`
+fn duration_between(start: Option<&str>, end: Option<&str>) -> Option<String> {
+    let parse = |time: Option<&str>| chrono::DateTime::parse_from_rfc3339(time?).ok();
+    let secs = (parse(end)? - parse(start)?).num_seconds().max(0);
+    Some(if secs >= 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    })
+}
+
+/// List the latest runs of the repository's workflows on GitHub
+pub async fn list_remote_runs(limit: usize) -> Result<(), String> {
+    let client = client()?;
+    let runs = client.recent_runs(limit).await.map_err(|e| e.to_string())?;
+    if runs.is_empty() {
+        println!(
+            "No runs on GitHub for {}/{}",
+            client.repo().owner,
+            client.repo().repo
+        );
+    }
+    for run in runs {
+        // Running runs haven't ended; show how long they've been going
+        let duration = if run.is_completed() {
+            duration_between(run.run_started_at.as_deref(), run.updated_at.as_deref())
+        } else {
+            duration_between(
+                run.run_started_at.as_deref(),
+                Some(&chrono::Utc::now().to_rfc3339()),
+            )
+        };
+        println!(
+            "{} {:<12} #{:<5} {:<20} {:<20} {:<18} {:>8}  {}",
+            state_icon(run.state()),
+            run.id,
+            run.run_number,
+            run.name.as_deref().unwrap_or("-"),
+            run.head_branch.as_deref().unwrap_or("-"),
+            run.event,
+            duration.unwrap_or_else(|| "-".to_string()),
+            run.state()
+        );
+    }
+    Ok(())
+}
+
+/// Re-run a completed run on GitHub, or only its failed jobs
+pub async fn rerun(run_id: u64, failed_only: bool) -> Result<(), String> {
+    let client = client()?;
+    let run = client.run(run_id).await.map_err(|e| e.to_string())?;
+    if !run.is_completed() {
+        return Err(format!(
+            "Run {} is still {}; only completed runs can be re-run",
+            run_id,
+            run.state()
+        ));
+    }
+    client
+        .rerun(run_id, failed_only)
+        .await
+        .map_err(|e| format!("Failed to re-run run {}: {}", run_id, e))?;
+    println!(
+        "🔁 Re-running {} of run #{} ({}): {}",
+        if failed_only {
+            "the failed jobs"
+        } else {
+            "all jobs"
+        },
+        run.run_number,
+        run.name.as_deref().unwrap_or("workflow"),
+        run.html_url
+    );
+    Ok(())
+}
+
+fn gitlab_client() -> Result<PipelinesClient, String> {
+    let repo = wrkflw_gitlab::get_repo_info().map_err(|e| e.to_string())?;
+    PipelinesClient::from_env(repo).map_err(|e| e.to_string())
+}
+
+fn print_pipeline_update(update: &PipelineUpdate) {
+    match update {
+        PipelineUpdate::Job(job) => println!(
+            "[{}] {} {}",
+            chrono::Local::now().format("%H:%M:%S"),
+            state_icon(&job.status),
+            update
+        ),
+        PipelineUpdate::Trace { .. } => println!("{}", update),
+    }
+}
+
+/// Trigger a pipeline on GitLab and follow it until it finishes, printing the
+/// trace of each job as it ends; returns whether the pipeline succeeded
+pub async fn trigger_gitlab_and_watch(
+    branch: Option<&str>,
+    variables: Option<HashMap<String, String>>,
+) -> Result<bool, String> {
+    let client = gitlab_client()?;
+    let pipeline_id = wrkflw_gitlab::trigger_pipeline(branch, variables)
+        .await
+        .map_err(|e| format!("Error triggering GitLab pipeline: {}", e))?;
+    println!("\n👀 Following pipeline {}", pipeline_id);
+    let pipeline = client
+        .follow(pipeline_id, print_pipeline_update)
+        .await
+        .map_err(|e| format!("Failed to follow pipeline {}: {}", pipeline_id, e))?;
+    println!(
+        "\n{} Pipeline {} finished: {}",
+        state_icon(&pipeline.status),
+        pipeline.id,
+        pipeline.status
+    );
+    Ok(pipeline.succeeded())
+}
+
+/// Execute a `wrkflw gitlab` subcommand
+pub async fn handle_gitlab_command(command: &GitlabCommand) -> Result<(), String> {
+    let GitlabCommand::Pipelines {
+        command: PipelinesCommand::List { limit },
+    } = command;
+    let client = gitlab_client()?;
+    let pipelines = client
+        .recent_pipelines(*limit)
+        .await
+        .map_err(|e| e.to_string())?;
+    if pipelines.is_empty() {
+        println!(
+            "No pipelines on GitLab for {}/{}",
+            client.repo().namespace,
+            client.repo().project
+        );
+    }
+    for pipeline in pipelines {
+        // The list leaves out durations; the time between creation and the last
+        // update is that of finished pipelines
+        let duration = duration_between(
+            pipeline.created_at.as_deref(),
+            pipeline.updated_at.as_deref(),
+        );
+        println!(
+            "{} {:<12} {:<20} {:<20} {:>8}  {}",
+            state_icon(&pipeline.status),
+            pipeline.id,
+            pipeline.git_ref.as_deref().unwrap_or("-"),
+            pipeline.source.as_deref().unwrap_or("-"),
+            duration.unwrap_or_else(|| "-".to_string()),
+            pipeline.status
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration_between() {
+        assert_eq!(
+            duration_between(Some("2024-05-01T10:00:00Z"), Some("2024-05-01T10:04:05Z")).as_deref(),
+            Some("4m 05s")
+        );
+        assert_eq!(
+            duration_between(Some("2024-05-01T10:00:00Z"), Some("2024-05-01T10:00:42Z")).as_deref(),
+            Some("42s")
+        );
+        assert!(duration_between(None, Some("2024-05-01T10:00:42Z")).is_none());
+    }
+}
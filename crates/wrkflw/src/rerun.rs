@@ -0,0 +1,46 @@
+use crate::{Commands, Wrkflw};
+use clap::Parser;
+use wrkflw_executor::resume::Resume;
+use wrkflw_executor::runs::RunStore;
+
+/// The `run` command replaying the failed local run `id` (or `latest`) with
+/// `run_args`, and what it resumes: the jobs and steps that succeeded are skipped
+pub fn from_failed(id: &str, run_args: &[String]) -> Result<(Commands, Resume), String> {
+    let project_dir =
+        std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?;
+    let store = RunStore::for_project(&project_dir);
+    let run = store.load(id)?;
+    let resume = store.resume(&run)?;
+
+    let mut args = vec!["wrkflw".to_string(), "run".to_string()];
+    if !run.runtime.is_empty() {
+        args.extend(["--runtime".to_string(), run.runtime.replace('_', "-")]);
+    }
+    args.extend(run_args.iter().cloned());
+    args.push(run.path.to_string_lossy().into_owned());
+    let command = Wrkflw::try_parse_from(&args)
+        .map_err(|e| format!("Invalid options for the rerun: {}", e))?
+        .command
+        .ok_or("Invalid options for the rerun")?;
+
+    let failed: Vec<&str> = run
+        .jobs
+        .iter()
+        .filter(|job| job.status != "success" && !resume.succeeded.contains(&job.name))
+        .map(|job| job.name.as_str())
+        .collect();
+    println!(
+        "🔁 Rerunning {} from run {}: {}",
+        run.workflow,
+        run.id,
+        if failed.is_empty() {
+            "all jobs".to_string()
+        } else {
+            failed.join(", ")
+        }
+    );
+    for (job, point) in &resume.jobs {
+        println!("   {} resumes at step {}", job, point.step + 1);
+    }
+    Ok((command, resume))
+}
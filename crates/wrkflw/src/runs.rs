@@ -0,0 +1,283 @@
+use clap::Subcommand;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::time::Duration;
+use wrkflw_executor::logs::{LogRun, LogStore};
+use wrkflw_executor::runs::{diff_runs, RunRecord, RunStore};
+
+/// How often a followed log is checked for more
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Subcommand)]
+pub enum RunsCommand {
+    /// List runs saved with `wrkflw run --save-logs`, newest first
+    List,
+
+    /// Show the jobs and steps of a saved run, or print one of their logs
+    Show {
+        /// Run id as listed, or `latest`
+        #[arg(default_value = "latest")]
+        id: String,
+
+        /// Print the log of this job
+        #[arg(long)]
+        job: Option<String>,
+
+        /// Print the log of this step of the job (numbered from 1)
+        #[arg(long, requires = "job")]
+        step: Option<usize>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum HistoryCommand {
+    /// Show the jobs and steps of a run
+    Show {
+        /// Run id as listed, or `latest`
+        #[arg(default_value = "latest")]
+        id: String,
+    },
+
+    /// Compare a run with another one, by default the run of the same workflow before it
+    Diff {
+        /// Run id as listed, or `latest`
+        #[arg(default_value = "latest")]
+        id: String,
+
+        /// Run to compare with
+        #[arg(long)]
+        with: Option<String>,
+    },
+}
+
+fn status_icon(status: &str) -> &'static str {
+    match status {
+        "success" => "✅",
+        "failure" => "❌",
+        _ => "⏭️",
+    }
+}
+
+fn print_run(run: &RunRecord) {
+    let commit = run
+        .commit
+        .as_deref()
+        .map(|sha| format!("  {}", &sha[..sha.len().min(8)]))
+        .unwrap_or_default();
+    let runtime = if run.runtime.is_empty() {
+        String::new()
+    } else {
+        format!("  [{}]", run.runtime)
+    };
+    println!(
+        "{} {}  {}  {}  {:.1}s{}{}",
+        if run.success { "✅" } else { "❌" },
+        run.id,
+        run.started.format("%Y-%m-%d %H:%M:%S"),
+        run.path.display(),
+        run.duration_secs,
+        commit,
+        runtime
+    );
+}
+
+fn print_jobs(run: &RunRecord) {
+    for job in &run.jobs {
+        println!(
+            "  {} {}{} ({:.1}s)",
+            status_icon(&job.status),
+            job.name,
+            job.environment
+                .as_ref()
+                .map(|environment| format!(" → {}", environment))
+                .unwrap_or_default(),
+            job.duration_secs
+        );
+        for (idx, step) in job.steps.iter().enumerate() {
+            println!(
+                "    {}. {} {} ({:.1}s)",
+                idx + 1,
+                status_icon(&step.status),
+                step.name,
+                step.duration_secs
+            );
+        }
+    }
+}
+
+fn project_store() -> Result<RunStore, String> {
+    let project_dir =
+        std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?;
+    Ok(RunStore::for_project(&project_dir))
+}
+
+/// Execute a `wrkflw runs` subcommand
+pub fn handle_runs_command(command: &RunsCommand) -> Result<(), String> {
+    let store = project_store()?;
+    match command {
+        RunsCommand::List => {
+            let runs: Vec<RunRecord> = store
+                .list()
+                .into_iter()
+                .filter(|run| store.has_logs(run))
+                .collect();
+            if runs.is_empty() {
+                println!(
+                    "No runs saved in {}; use `wrkflw run --save-logs`",
+                    store.root().display()
+                );
+            }
+            for run in &runs {
+                print_run(run);
+            }
+            Ok(())
+        }
+        RunsCommand::Show { id, job, step } => {
+            let run = store.load(id)?;
+            if let Some(job) = job {
+                print!("{}", store.read_log(&run, job, *step)?);
+                return Ok(());
+            }
+            print_run(&run);
+            print_jobs(&run);
+            if store.has_logs(&run) {
+                println!(
+                    "\nLogs: {}",
+                    store
+                        .root()
+                        .join(&run.id)
+                        .join(wrkflw_executor::runs::LOGS_FILE)
+                        .display()
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+fn print_log_run(run: &LogRun) {
+    let status = match run.success {
+        Some(true) => "✅",
+        Some(false) => "❌",
+        None => "⏳",
+    };
+    println!(
+        "{} {}  {}  {}",
+        status,
+        run.id,
+        run.started.format("%Y-%m-%d %H:%M:%S"),
+        run.path.display()
+    );
+}
+
+/// Execute `wrkflw logs`: print the combined log of a run, or that of one of its
+/// jobs, and with `follow` keep printing what's written until the run finishes
+pub fn handle_logs_command(
+    id: &str,
+    job: Option<&str>,
+    follow: bool,
+    list: bool,
+) -> Result<(), String> {
+    let project_dir =
+        std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?;
+    let store = LogStore::for_project(&project_dir);
+    if list {
+        let runs = store.list();
+        if runs.is_empty() {
+            println!("No run logs in {}", store.root().display());
+        }
+        for run in &runs {
+            print_log_run(run);
+        }
+        return Ok(());
+    }
+
+    let mut run = store.load(id)?;
+    let path = store.log_file(&run, job)?;
+    let mut file =
+        File::open(&path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut stdout = std::io::stdout();
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        file.read_to_end(&mut buf)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        // Only print whole lines, leaving a line still being written for later
+        let end = match buf.iter().rposition(|&b| b == b'\n') {
+            Some(pos) if follow && !run.is_finished() => pos + 1,
+            _ => buf.len(),
+        };
+        stdout
+            .write_all(&buf[..end])
+            .and_then(|()| stdout.flush())
+            .map_err(|e| e.to_string())?;
+        if end < buf.len() {
+            file.seek(SeekFrom::Current(end as i64 - buf.len() as i64))
+                .map_err(|e| e.to_string())?;
+        }
+        if !follow || run.is_finished() {
+            return Ok(());
+        }
+        std::thread::sleep(FOLLOW_INTERVAL);
+        // Read once more after the run finishes, for what it logged last
+        run = store.load(&run.id)?;
+    }
+}
+
+/// Execute `wrkflw history`, listing runs when no subcommand is given
+pub fn handle_history_command(
+    command: Option<&HistoryCommand>,
+    workflow: Option<&str>,
+    limit: usize,
+) -> Result<(), String> {
+    let store = project_store()?;
+    match command {
+        None => {
+            let runs: Vec<RunRecord> = store
+                .list()
+                .into_iter()
+                .filter(|run| workflow.is_none_or(|name| run.workflow.contains(name)))
+                .take(limit)
+                .collect();
+            if runs.is_empty() {
+                println!("No runs recorded in {}", store.root().display());
+            }
+            for run in &runs {
+                print_run(run);
+            }
+            Ok(())
+        }
+        Some(HistoryCommand::Show { id }) => {
+            let run = store.load(id)?;
+            print_run(&run);
+            print_jobs(&run);
+            Ok(())
+        }
+        Some(HistoryCommand::Diff { id, with }) => {
+            let run = store.load(id)?;
+            let before = match with {
+                Some(other) => store.load(other)?,
+                None => store.previous(&run).ok_or_else(|| {
+                    format!("No earlier run of {} to compare with", run.path.display())
+                })?,
+            };
+            print_run(&before);
+            print_run(&run);
+            println!();
+            let changes = diff_runs(&before, &run);
+            if changes.is_empty() {
+                println!("No changes in job or step results");
+            }
+            for change in changes {
+                println!("  {}", change);
+            }
+            println!(
+                "\nTotal: {:.1}s → {:.1}s ({:+.1}s)",
+                before.duration_secs,
+                run.duration_secs,
+                run.duration_secs - before.duration_secs
+            );
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use chrono::{DateTime, Local, Utc};
+use clap::Subcommand;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use wrkflw_executor::cancel::RunCancel;
+use wrkflw_executor::engine::ExecutionError;
+use wrkflw_executor::runs::RunStore;
+use wrkflw_executor::ExecutionConfig;
+use wrkflw_parser::cron::CronSchedule;
+use wrkflw_parser::workflow::parse_workflow;
+
+use crate::validation::workflow_files;
+use crate::watch::{print_run, RunResult};
+
+/// The scheduler wakes at least this often to notice edited workflows
+const RESCAN_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Subcommand)]
+pub enum ScheduleCommand {
+    /// Stay running and execute the workflows with a `schedule` trigger at their
+    /// cron times
+    Run {
+        /// Workflow files, or directories of them
+        #[arg(default_value = ".github/workflows")]
+        paths: Vec<PathBuf>,
+
+        /// Container runtime to use (docker, podman, emulation, secure-emulation)
+        #[arg(short, long, value_enum, default_value = "docker")]
+        runtime: crate::RuntimeChoice,
+
+        /// Set an environment variable in every job, over the env the workflow defines; can be repeated
+        #[arg(short, long = "env", value_name = "KEY=VALUE", value_parser = crate::parse_key_val)]
+        env: Vec<(String, String)>,
+
+        /// Read environment variables from a .env file; can be repeated, --env wins over it
+        #[arg(long, value_name = "FILE")]
+        env_file: Vec<PathBuf>,
+
+        /// Set a configuration variable (`${{ vars.NAME }}`) over those of .wrkflw/vars.toml; can be repeated
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = crate::parse_key_val)]
+        var: Vec<(String, String)>,
+
+        /// Save the step logs of each run under .wrkflw/runs/ (see `wrkflw runs`)
+        #[arg(long)]
+        save_logs: bool,
+    },
+}
+
+/// Execute a `wrkflw schedule` subcommand
+pub async fn handle_schedule_command(
+    command: &ScheduleCommand,
+    verbose: bool,
+    cancel: RunCancel,
+) -> Result<(), String> {
+    match command {
+        ScheduleCommand::Run {
+            paths,
+            runtime,
+            env,
+            env_file,
+            var,
+            save_logs,
+        } => {
+            let config = ExecutionConfig {
+                runtime_type: runtime.clone().into(),
+                verbose,
+                event: Some("schedule".to_string()),
+                log_files: true,
+                env: crate::user_env_from_args(env, env_file),
+                vars: crate::vars::run_vars(false, var).await?,
+                cancel,
+                ..Default::default()
+            };
+            run_scheduler(paths, config, *save_logs).await
+        }
+    }
+}
+
+/// Print the next `count` times each `on.schedule` entry of a workflow triggers,
+/// in local time and UTC
+pub fn print_schedule(path: &Path, count: usize) -> Result<(), String> {
+    let workflow = parse_workflow(path)?;
+    let crons = workflow.schedules();
+    if crons.is_empty() {
+        return Err(format!("{} has no `schedule` trigger", path.display()));
+    }
+
+    let now = Utc::now();
+    let mut invalid = 0;
+    for (idx, cron) in crons.iter().enumerate() {
+        if idx > 0 {
+            println!();
+        }
+        println!("{}", cron);
+        let schedule = match CronSchedule::parse(cron) {
+            Ok(schedule) => schedule,
+            Err(e) => {
+                println!("  ❌ {}", e);
+                invalid += 1;
+                continue;
+            }
+        };
+        let times = schedule.upcoming(&now, count);
+        if times.is_empty() {
+            println!("  never runs");
+        }
+        for time in times {
+            println!("  {}", format_time(&time));
+        }
+    }
+
+    if invalid > 0 {
+        return Err(format!(
+            "{} of {} cron expressions are invalid",
+            invalid,
+            crons.len()
+        ));
+    }
+    Ok(())
+}
+
+fn format_time(time: &DateTime<Utc>) -> String {
+    format!(
+        "{}   {}",
+        time.with_timezone(&Local).format("%a %Y-%m-%d %H:%M %:z"),
+        time.format("%a %Y-%m-%d %H:%M UTC")
+    )
+}
+
+/// The valid `schedule` entries of the workflows in `paths`, by workflow file,
+/// and what's wrong with the others
+fn scheduled_workflows(paths: &[PathBuf]) -> (BTreeMap<PathBuf, Vec<CronSchedule>>, Vec<String>) {
+    let mut scheduled = BTreeMap::new();
+    let mut problems = Vec::new();
+    for path in paths.iter().flat_map(|path| workflow_files(path)) {
+        if crate::is_gitlab_pipeline(&path) || crate::is_jenkinsfile(&path) {
+            continue;
+        }
+        let workflow = match parse_workflow(&path) {
+            Ok(workflow) => workflow,
+            Err(e) => {
+                problems.push(format!("{}: {}", path.display(), e));
+                continue;
+            }
+        };
+        let mut schedules = Vec::new();
+        for cron in workflow.schedules() {
+            match CronSchedule::parse(&cron) {
+                Ok(schedule) => schedules.push(schedule),
+                Err(e) => problems.push(format!(
+                    "{}: invalid cron syntax '{}': {}",
+                    path.display(),
+                    cron,
+                    e
+                )),
+            }
+        }
+        if !schedules.is_empty() {
+            scheduled.insert(path, schedules);
+        }
+    }
+    (scheduled, problems)
+}
+
+fn print_scheduled(scheduled: &BTreeMap<PathBuf, Vec<CronSchedule>>, now: &DateTime<Utc>) {
+    for (path, schedules) in scheduled {
+        println!("  {}", path.display());
+        for schedule in schedules {
+            let next = schedule
+                .next_after(now)
+                .map(|time| format_time(&time))
+                .unwrap_or_else(|| "never".to_string());
+            println!("    {:<20} next: {}", schedule.to_string(), next);
+        }
+    }
+}
+
+/// Run `path` on a thread of its own, so that the scheduler keeps time while its
+/// steps block, and send the result to `finished`
+fn spawn_run(
+    path: &Path,
+    config: &ExecutionConfig,
+    finished: mpsc::UnboundedSender<(PathBuf, RunResult)>,
+) {
+    let path = path.to_path_buf();
+    let config = config.clone();
+    std::thread::spawn(move || {
+        let result = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(runtime) => runtime.block_on(wrkflw_executor::execute_workflow(&path, config)),
+            Err(e) => Err(ExecutionError::Execution(format!(
+                "Failed to create Tokio runtime: {}",
+                e
+            ))),
+        };
+        let _ = finished.send((path, result));
+    });
+}
+
+/// Execute the workflows in `paths` at the times their `schedule` triggers fire,
+/// until interrupted, or the runs going are cancelled through `config.cancel`
+///
+/// Workflows are read again every minute, so edits apply without a restart. A
+/// workflow whose previous scheduled run hasn't finished is not started again,
+/// and triggers missed while the machine was asleep are skipped. Each run is
+/// recorded in the run history.
+pub async fn run_scheduler(
+    paths: &[PathBuf],
+    config: ExecutionConfig,
+    save_logs: bool,
+) -> Result<(), String> {
+    let project_dir =
+        std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?;
+    let store = RunStore::for_project(&project_dir);
+    let runtime_mode = config.runtime_type.mode();
+
+    let (scheduled, problems) = scheduled_workflows(paths);
+    for problem in &problems {
+        eprintln!("⚠️  {}", problem);
+    }
+    if scheduled.is_empty() {
+        return Err(format!(
+            "No workflows with a valid `schedule` trigger in {}",
+            paths
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    let mut last_check = Utc::now();
+    println!("📅 Scheduled workflows (cron times are UTC):");
+    print_scheduled(&scheduled, &last_check);
+    println!("\n⏳ Waiting for the next trigger; press Ctrl+C to stop");
+
+    let mut known = scheduled;
+    let mut known_problems = problems;
+    let mut running = BTreeSet::new();
+    let (finished_tx, mut finished) = mpsc::unbounded_channel();
+    loop {
+        let (scheduled, problems) = scheduled_workflows(paths);
+        if scheduled != known || problems != known_problems {
+            println!(
+                "\n[{}] 📅 Workflows changed; now scheduled:",
+                Local::now().format("%H:%M:%S")
+            );
+            for problem in problems.iter().filter(|p| !known_problems.contains(p)) {
+                eprintln!("⚠️  {}", problem);
+            }
+            print_scheduled(&scheduled, &last_check);
+            known = scheduled;
+            known_problems = problems;
+        }
+
+        let next = known
+            .values()
+            .flatten()
+            .filter_map(|schedule| schedule.next_after(&last_check))
+            .min();
+        let now = Utc::now();
+        let wait = match next {
+            Some(next) if next <= now => {
+                last_check = next;
+                if (now - next).num_seconds() >= 60 {
+                    wrkflw_logging::warning(&format!(
+                        "Skipping the triggers of {} UTC, which passed while the machine was asleep",
+                        next.format("%Y-%m-%d %H:%M")
+                    ));
+                    continue;
+                }
+                for (path, schedules) in &known {
+                    let Some(schedule) = schedules.iter().find(|s| s.matches(&next)) else {
+                        continue;
+                    };
+                    println!(
+                        "\n[{}] ⏰ {} ({})",
+                        Local::now().format("%H:%M:%S"),
+                        path.display(),
+                        schedule
+                    );
+                    if !running.insert(path.clone()) {
+                        println!("⏭️  Its previous run is still going; not starting another");
+                        continue;
+                    }
+                    spawn_run(path, &config, finished_tx.clone());
+                }
+                continue;
+            }
+            Some(next) => (next - now)
+                .to_std()
+                .unwrap_or_default()
+                .min(RESCAN_INTERVAL),
+            None => RESCAN_INTERVAL,
+        };
+
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            Some((path, result)) = finished.recv() => {
+                running.remove(&path);
+                println!(
+                    "\n[{}] 🏁 {} finished",
+                    Local::now().format("%H:%M:%S"),
+                    path.display()
+                );
+                print_run(&result);
+                if let Ok(result) = &result {
+                    if let Err(e) = store.save(&path, result, runtime_mode, save_logs) {
+                        eprintln!("Error recording the run: {}", e);
+                    }
+                }
+                if config.cancel.is_run_cancelled() && running.is_empty() {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
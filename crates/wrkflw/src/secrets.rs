@@ -0,0 +1,405 @@
+use crate::validation::{relative, workflow_files_recursive, Platform};
+use clap::Subcommand;
+use serde::Serialize;
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use wrkflw_secrets::providers::encrypted::{
+    generate_key_file, EncryptedProvider, KeySource, DEFAULT_PASSWORD_ENV,
+};
+use wrkflw_secrets::{SecretError, SecretManager, SecretProvider, SecretSubstitution};
+
+/// Default location of the encrypted secret store
+const DEFAULT_STORE_PATH: &str = "~/.wrkflw/secrets.enc";
+
+#[derive(Debug, Subcommand)]
+pub enum SecretsCommand {
+    /// Initialize a new encrypted secret store
+    Init {
+        /// Path to the encrypted store file
+        #[arg(long, default_value = DEFAULT_STORE_PATH)]
+        store: String,
+
+        /// Generate a random key into this file instead of using a password
+        #[arg(long)]
+        key_file: Option<PathBuf>,
+
+        /// Overwrite an existing store
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Add or update a secret in the encrypted store
+    Set {
+        /// Name of the secret
+        name: String,
+
+        /// Path to the encrypted store file
+        #[arg(long, default_value = DEFAULT_STORE_PATH)]
+        store: String,
+
+        /// Key file to use instead of a password
+        #[arg(long)]
+        key_file: Option<PathBuf>,
+    },
+
+    /// Remove a secret from the encrypted store
+    Remove {
+        /// Name of the secret
+        name: String,
+
+        /// Path to the encrypted store file
+        #[arg(long, default_value = DEFAULT_STORE_PATH)]
+        store: String,
+    },
+
+    /// List secret names in the encrypted store
+    List {
+        /// Path to the encrypted store file
+        #[arg(long, default_value = DEFAULT_STORE_PATH)]
+        store: String,
+    },
+
+    /// Report the secrets the workflows reference, the jobs using them and whether
+    /// the secret providers have them, and the secrets no workflow uses
+    Report {
+        /// Directory to search, the current one by default; can be repeated
+        #[arg(long = "path", value_name = "DIR")]
+        paths: Vec<PathBuf>,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// A secret the workflows reference
+#[derive(Debug, Serialize)]
+struct SecretUsage {
+    /// `NAME`, or `provider:NAME` for a secret of a given provider
+    name: String,
+    status: SecretStatus,
+    /// Why the secret couldn't be looked up
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    used_by: UsedBy,
+}
+
+/// The files referencing a secret, with the jobs that do; a reference outside
+/// of jobs has none
+type UsedBy = BTreeMap<PathBuf, Vec<String>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum SecretStatus {
+    Resolved,
+    /// `GITHUB_TOKEN`, which runs always get
+    Provided,
+    Missing,
+    Error,
+}
+
+/// A secret of a provider that no workflow references
+#[derive(Debug, Serialize)]
+struct UnusedSecret {
+    provider: String,
+    name: String,
+}
+
+#[derive(Debug, Serialize)]
+struct SecretsReport {
+    secrets: Vec<SecretUsage>,
+    unused: Vec<UnusedSecret>,
+}
+
+/// Execute a `wrkflw secrets` subcommand
+pub async fn handle_secrets_command(command: &SecretsCommand) -> Result<(), String> {
+    match command {
+        SecretsCommand::Init {
+            store,
+            key_file,
+            force,
+        } => {
+            let provider = match key_file {
+                Some(key_file) => {
+                    let key_file = key_file.to_string_lossy().to_string();
+                    if !std::path::Path::new(&key_file).exists() {
+                        generate_key_file(&key_file).map_err(|e| e.to_string())?;
+                        println!("🔑 Generated new key file: {}", key_file);
+                    }
+                    EncryptedProvider::new(store, KeySource::KeyFile(key_file))
+                }
+                None => {
+                    let password = read_password(true)?;
+                    EncryptedProvider::new(store, KeySource::Password(password))
+                }
+            };
+
+            provider.init(*force).await.map_err(|e| e.to_string())?;
+            println!(
+                "✅ Initialized encrypted secret store at {}",
+                provider.expand_path()
+            );
+            Ok(())
+        }
+        SecretsCommand::Set {
+            name,
+            store,
+            key_file,
+        } => {
+            let key_source = match key_file {
+                Some(key_file) => KeySource::KeyFile(key_file.to_string_lossy().to_string()),
+                None => KeySource::Password(read_password(false)?),
+            };
+            let provider = EncryptedProvider::new(store, key_source);
+
+            let value = rpassword::prompt_password(format!("Value for {}: ", name))
+                .map_err(|e| format!("Failed to read secret value: {}", e))?;
+            provider
+                .set(name, &value)
+                .await
+                .map_err(|e| e.to_string())?;
+            println!("✅ Stored secret '{}'", name);
+            Ok(())
+        }
+        SecretsCommand::Remove { name, store } => {
+            // Removing only touches secret names, so no key is needed
+            let provider =
+                EncryptedProvider::new(store, KeySource::PasswordEnv(DEFAULT_PASSWORD_ENV.into()));
+            if provider.remove(name).await.map_err(|e| e.to_string())? {
+                println!("✅ Removed secret '{}'", name);
+            } else {
+                println!("Secret '{}' not found in store", name);
+            }
+            Ok(())
+        }
+        SecretsCommand::List { store } => {
+            let provider =
+                EncryptedProvider::new(store, KeySource::PasswordEnv(DEFAULT_PASSWORD_ENV.into()));
+            if !provider.exists() {
+                return Err(format!(
+                    "Encrypted secret store not found at {}. Run 'wrkflw secrets init' first",
+                    provider.expand_path()
+                ));
+            }
+
+            let mut names = provider.list_secrets().await.map_err(|e| e.to_string())?;
+            names.sort();
+            if names.is_empty() {
+                println!("No secrets stored in {}", provider.expand_path());
+            } else {
+                for name in names {
+                    println!("  - {}", name);
+                }
+            }
+            Ok(())
+        }
+        SecretsCommand::Report { paths, json } => {
+            let report = secrets_report(paths).await?;
+            if *json {
+                let json = serde_json::to_string_pretty(&report)
+                    .map_err(|e| format!("Failed to serialize secrets report: {}", e))?;
+                println!("{}", json);
+            } else {
+                print_report(&report);
+            }
+            Ok(())
+        }
+    }
+}
+
+async fn secrets_report(paths: &[PathBuf]) -> Result<SecretsReport, String> {
+    let default = [PathBuf::from(".")];
+    let paths = if paths.is_empty() {
+        &default[..]
+    } else {
+        paths
+    };
+    // By cache key, with the provider and name of the reference
+    let mut references: BTreeMap<String, (Option<String>, String, UsedBy)> = BTreeMap::new();
+    for path in paths {
+        if !path.exists() {
+            return Err(format!("{} does not exist", path.display()));
+        }
+        for file in workflow_files_recursive(path) {
+            // Actions get secrets as inputs, and Jenkinsfiles aren't YAML
+            if matches!(
+                Platform::of(&file, None),
+                Platform::Action | Platform::Jenkins
+            ) {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(&file) else {
+                continue;
+            };
+            for (job, text) in job_texts(&content) {
+                for secret in SecretSubstitution::extract_secret_refs(&text) {
+                    let (_, _, used_by) =
+                        references.entry(secret.cache_key()).or_insert_with(|| {
+                            (
+                                secret.provider.clone(),
+                                secret.name.clone(),
+                                BTreeMap::new(),
+                            )
+                        });
+                    let jobs = used_by.entry(relative(&file).to_path_buf()).or_default();
+                    if let Some(job) = &job {
+                        if !jobs.contains(job) {
+                            jobs.push(job.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let manager = SecretManager::default()
+        .await
+        .map_err(|e| format!("Failed to set up the secret providers: {}", e))?;
+    let mut secrets = Vec::new();
+    for (key, (provider, name, used_by)) in references.iter() {
+        let found = match provider {
+            None if name == "GITHUB_TOKEN" => Ok(SecretStatus::Provided),
+            None => manager
+                .get_secret(name)
+                .await
+                .map(|_| SecretStatus::Resolved),
+            Some(provider) => manager
+                .get_secret_from_provider(provider, name)
+                .await
+                .map(|_| SecretStatus::Resolved),
+        };
+        let (status, error) = match found {
+            Ok(status) => (status, None),
+            Err(SecretError::NotFound { .. }) => (SecretStatus::Missing, None),
+            Err(e) => (SecretStatus::Error, Some(e.to_string())),
+        };
+        secrets.push(SecretUsage {
+            name: key.clone(),
+            status,
+            error,
+            used_by: used_by.clone(),
+        });
+    }
+
+    let mut unused = Vec::new();
+    let mut listed: Vec<_> = manager
+        .list_all_secrets()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .collect();
+    listed.sort();
+    for (provider, mut names) in listed {
+        names.sort();
+        for name in names {
+            // A reference without a provider may be served by any of them
+            let used = references.values().any(|(referenced, referenced_name, _)| {
+                *referenced_name == name
+                    && referenced
+                        .as_ref()
+                        .is_none_or(|referenced| *referenced == provider)
+            });
+            if !used {
+                unused.push(UnusedSecret {
+                    provider: provider.clone(),
+                    name,
+                });
+            }
+        }
+    }
+    Ok(SecretsReport { secrets, unused })
+}
+
+/// The text of each GitHub job of a workflow, by job id, and the rest of the
+/// file without a job; other files are all without a job
+fn job_texts(content: &str) -> Vec<(Option<String>, String)> {
+    let Ok(Value::Mapping(mut workflow)) = serde_yaml::from_str::<Value>(content) else {
+        return vec![(None, content.to_string())];
+    };
+    let Some(Value::Mapping(jobs)) = workflow.remove("jobs") else {
+        return vec![(None, content.to_string())];
+    };
+    let mut texts = vec![(None, serde_yaml::to_string(&workflow).unwrap_or_default())];
+    for (id, job) in jobs {
+        if let Some(id) = id.as_str() {
+            texts.push((
+                Some(id.to_string()),
+                serde_yaml::to_string(&job).unwrap_or_default(),
+            ));
+        }
+    }
+    texts
+}
+
+fn print_report(report: &SecretsReport) {
+    if report.secrets.is_empty() {
+        println!("No workflow references a secret");
+    }
+    for secret in &report.secrets {
+        let status = match secret.status {
+            SecretStatus::Resolved | SecretStatus::Provided => "✅",
+            SecretStatus::Missing => "❌",
+            SecretStatus::Error => "⚠️",
+        };
+        let note = match (secret.status, &secret.error) {
+            (SecretStatus::Provided, _) => " (provided by wrkflw)".to_string(),
+            (SecretStatus::Missing, _) => " (missing from the secret providers)".to_string(),
+            (_, Some(error)) => format!(" (can't be looked up: {})", error),
+            _ => String::new(),
+        };
+        println!("{} {}{}", status, secret.name, note);
+        for (path, jobs) in &secret.used_by {
+            if jobs.is_empty() {
+                println!("     {}", path.display());
+            } else {
+                println!("     {}: {}", path.display(), jobs.join(", "));
+            }
+        }
+    }
+    if !report.unused.is_empty() {
+        println!("\n🗑️  Secrets no workflow references:");
+        for secret in &report.unused {
+            println!("  {}: {}", secret.provider, secret.name);
+        }
+    }
+    let missing = report
+        .secrets
+        .iter()
+        .filter(|secret| {
+            !matches!(
+                secret.status,
+                SecretStatus::Resolved | SecretStatus::Provided
+            )
+        })
+        .count();
+    println!(
+        "\n{} secret(s) referenced, {} not resolved, {} unused",
+        report.secrets.len(),
+        missing,
+        report.unused.len()
+    );
+}
+
+/// Read the store password from the environment, or prompt for it
+fn read_password(confirm: bool) -> Result<String, String> {
+    if let Ok(password) = std::env::var(DEFAULT_PASSWORD_ENV) {
+        return Ok(password);
+    }
+
+    let password = rpassword::prompt_password("Secret store password: ")
+        .map_err(|e| format!("Failed to read password: {}", e))?;
+    if password.is_empty() {
+        return Err("Password must not be empty".to_string());
+    }
+
+    if confirm {
+        let again = rpassword::prompt_password("Confirm password: ")
+            .map_err(|e| format!("Failed to read password: {}", e))?;
+        if again != password {
+            return Err("Passwords do not match".to_string());
+        }
+    }
+
+    Ok(password)
+}
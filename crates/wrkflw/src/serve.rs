@@ -0,0 +1,1033 @@
+//! `wrkflw serve`: a small HTTP API over the project in the current directory,
+//! for editors, dashboards and scripts.
+//!
+//! | Request                         | Response                                          |
+//! |---------------------------------|---------------------------------------------------|
+//! | `GET /api`                      | name and version of the server                    |
+//! | `GET /api/workflows`            | workflow and pipeline files of the project        |
+//! | `POST /api/validate`            | validation report of `{"paths": [...]}`           |
+//! | `POST /api/runs`                | starts `{"path", "event"?, "inputs"?, "env"?}`    |
+//! | `GET /api/runs`                 | runs with logs, newest first                      |
+//! | `GET /api/runs/<id>`            | a run with logs and its jobs                      |
+//! | `GET /api/runs/<id>/logs?job=`  | its log as server-sent events, until it finishes  |
+//! | `GET /api/history?workflow=&limit=` | recorded runs, newest first                   |
+//! | `GET /api/history/<id>`         | a recorded run with its jobs and steps            |
+//! | `POST /webhook`                 | runs what a GitHub or GitLab push or pull request triggers |
+//!
+//! Runs go one at a time, as they share the logging of the process. The server
+//! listens on the loopback interface unless told otherwise, and there only takes
+//! requests whose `Host` is a loopback name on its port, so that web pages can't
+//! reach it by DNS rebinding. Listening elsewhere needs a token, made up if none
+//! is given; with a token, every request needs `Authorization: Bearer <token>`.
+//! Requests with a body must be JSON, which browsers don't send across origins
+//! without asking first. Webhook deliveries prove themselves with the webhook
+//! secret instead of the token or the `Host`.
+
+use chrono::Local;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use wrkflw_executor::engine::ExecutionError;
+use wrkflw_executor::logs::{self, LogRun, LogStore};
+use wrkflw_executor::runs::RunStore;
+use wrkflw_executor::{ExecutionConfig, RuntimeType};
+use wrkflw_parser::workflow::parse_workflow;
+
+use crate::validation::{workflow_files, Platform, ValidationReport};
+use crate::watch::print_run;
+use crate::webhook::{self, constant_time_eq, Delivery};
+
+/// Largest request, head and body, the server will read
+const MAX_REQUEST_BYTES: usize = 1024 * 1024;
+
+/// How often a streamed log is checked for new lines
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long a started run may take to open its logs
+const RUN_START_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a client may take to send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Runs listed by `/api/history` unless the request sets `limit`
+const DEFAULT_HISTORY_LIMIT: usize = 20;
+
+struct Server {
+    project_dir: PathBuf,
+    runtime_type: RuntimeType,
+    verbose: bool,
+    /// Port the server listens on, and whether on a loopback address
+    port: u16,
+    loopback: bool,
+    token: Option<String>,
+    /// Secret webhook deliveries must be signed with; no webhooks without one
+    webhook_secret: Option<String>,
+    /// Runs started through the API or a webhook
+    runs: RunQueue,
+}
+
+/// Runs started through the API or webhooks go one at a time; those of webhooks
+/// wait their turn, in the order the deliveries arrived
+#[derive(Default)]
+struct RunQueue {
+    state: Mutex<QueueState>,
+    turn: Condvar,
+}
+
+#[derive(Default)]
+struct QueueState {
+    running: bool,
+    /// Turns handed out to waiting runs, and turns taken
+    queued: u64,
+    served: u64,
+}
+
+impl RunQueue {
+    /// Start a run now, unless one is going or waiting
+    fn try_start(&self) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.running || state.queued != state.served {
+            return false;
+        }
+        state.running = true;
+        true
+    }
+
+    /// Block until the run going on and those queued before are done, then start
+    fn wait_turn(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let ticket = state.queued;
+        state.queued += 1;
+        let mut state = self
+            .turn
+            .wait_while(state, |state| state.running || state.served != ticket)
+            .unwrap_or_else(|e| e.into_inner());
+        state.served += 1;
+        state.running = true;
+    }
+
+    /// The run is over; wake the runs waiting their turn
+    fn finish(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.running = false;
+        self.turn.notify_all();
+    }
+}
+
+impl Server {
+    /// Configuration of a run with the project's `vars`
+    fn execution_config(&self, vars: HashMap<String, String>) -> ExecutionConfig {
+        ExecutionConfig {
+            runtime_type: self.runtime_type.clone(),
+            verbose: self.verbose,
+            log_files: true,
+            vars,
+            ..Default::default()
+        }
+    }
+}
+
+/// A request as far as the API looks at it
+#[derive(Debug, Default, PartialEq)]
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    /// Headers by lowercase name
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    /// The request line and headers of `head`, without the body
+    fn parse_head(head: &str) -> Option<Request> {
+        let mut lines = head.lines();
+        let mut request_line = lines.next()?.split_whitespace();
+        let method = request_line.next()?.to_string();
+        let target = request_line.next()?;
+        if !request_line.next()?.starts_with("HTTP/") {
+            return None;
+        }
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let query = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (decode(key), decode(value))
+            })
+            .collect();
+        let headers = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+            .collect();
+        Some(Request {
+            method,
+            path: decode(path),
+            query,
+            headers,
+            body: Vec::new(),
+        })
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+}
+
+fn decode(value: &str) -> String {
+    let value = value.replace('+', " ");
+    urlencoding::decode(&value)
+        .map(|v| v.into_owned())
+        .unwrap_or(value)
+}
+
+#[derive(Debug, PartialEq)]
+struct Response {
+    status: u16,
+    body: Value,
+}
+
+impl Response {
+    fn json(status: u16, body: impl Serialize) -> Self {
+        Response {
+            status,
+            body: serde_json::to_value(body).unwrap_or(Value::Null),
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self::json(status, json!({ "message": message.into() }))
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        422 => "Unprocessable Entity",
+        _ => "Internal Server Error",
+    }
+}
+
+enum Reply {
+    Json(Response),
+    /// Stream the log `file` of `run` as server-sent events
+    Events {
+        run: LogRun,
+        file: PathBuf,
+    },
+}
+
+/// Serve the API on `host`:`port` until interrupted, running workflows with
+/// `runtime_type`, and take webhooks if given their secret
+pub async fn serve(
+    host: &str,
+    port: u16,
+    runtime_type: RuntimeType,
+    token: Option<String>,
+    webhook_secret: Option<String>,
+    verbose: bool,
+) -> Result<(), String> {
+    let project_dir =
+        std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?;
+    let listener = TcpListener::bind((host, port))
+        .await
+        .map_err(|e| format!("Failed to listen on {}:{}: {}", host, port, e))?;
+    let addr = listener.local_addr().map_err(|e| e.to_string())?;
+    let loopback = addr.ip().is_loopback();
+    println!(
+        "🌐 Serving the wrkflw API on http://{}/api; press Ctrl+C to stop",
+        addr
+    );
+    // Anyone who can reach the server could run workflows on this machine
+    let token = match token {
+        None if !loopback => {
+            let token = uuid::Uuid::new_v4().simple().to_string();
+            println!(
+                "🔑 Requests need `Authorization: Bearer {}`; set --token or WRKFLW_API_TOKEN to choose the token",
+                token
+            );
+            Some(token)
+        }
+        token => token,
+    };
+
+    let server = Arc::new(Server {
+        project_dir,
+        runtime_type,
+        verbose,
+        port: addr.port(),
+        loopback,
+        token,
+        webhook_secret,
+        runs: RunQueue::default(),
+    });
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                wrkflw_logging::debug(&format!("API server connection error: {}", e));
+                continue;
+            }
+        };
+        let server = server.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, &server).await {
+                wrkflw_logging::debug(&format!("API server connection error: {}", e));
+            }
+        });
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, server: &Arc<Server>) -> io::Result<()> {
+    let request = tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut stream))
+        .await
+        .unwrap_or_else(|_| Ok(Err(Response::error(408, "Request Timeout"))))?;
+    let reply = match request {
+        Ok(request) => route(&request, server).await,
+        Err(response) => Reply::Json(response),
+    };
+    match reply {
+        Reply::Json(response) => {
+            let body = response.body.to_string();
+            let head = format!(
+                "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                response.status,
+                reason(response.status),
+                body.len()
+            );
+            stream.write_all(head.as_bytes()).await?;
+            stream.write_all(body.as_bytes()).await?;
+        }
+        Reply::Events { run, file } => {
+            let store = LogStore::for_project(&server.project_dir);
+            stream_log(&mut stream, &store, run, &file).await?;
+        }
+    }
+    stream.shutdown().await
+}
+
+/// Read a request with its body, or the response that refuses it
+async fn read_request(stream: &mut TcpStream) -> io::Result<Result<Request, Response>> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if buf.len() > MAX_REQUEST_BYTES {
+            return Ok(Err(Response::error(413, "Request too large")));
+        }
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(Err(Response::error(400, "Incomplete request")));
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let Some(mut request) = Request::parse_head(&String::from_utf8_lossy(&buf[..head_end])) else {
+        return Ok(Err(Response::error(400, "Malformed request")));
+    };
+    let length = match request.header("content-length").map(str::parse::<usize>) {
+        None => 0,
+        Some(Ok(length)) => length,
+        Some(Err(_)) => return Ok(Err(Response::error(400, "Invalid Content-Length"))),
+    };
+    if head_end + length > MAX_REQUEST_BYTES {
+        return Ok(Err(Response::error(413, "Request too large")));
+    }
+    let mut body = buf.split_off(head_end);
+    while body.len() < length {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(Err(Response::error(400, "Incomplete request body")));
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(length);
+    request.body = body;
+    Ok(Ok(request))
+}
+
+async fn route(request: &Request, server: &Arc<Server>) -> Reply {
+    let is_webhook = request.path == "/webhook";
+    let allowed_host = request
+        .header("host")
+        .is_some_and(|host| is_loopback_host(host, server.port));
+    if server.loopback && !is_webhook && !allowed_host {
+        return Reply::Json(Response::error(403, "Host not allowed"));
+    }
+    if let Some(token) = server.token.as_ref().filter(|_| !is_webhook) {
+        let bearer = request
+            .header("authorization")
+            .and_then(|value| value.strip_prefix("Bearer "));
+        let authorized =
+            bearer.is_some_and(|bearer| constant_time_eq(bearer.as_bytes(), token.as_bytes()));
+        if !authorized {
+            return Reply::Json(Response::error(401, "Bad credentials"));
+        }
+    }
+    let is_json = request.header("content-type").is_some_and(|value| {
+        let media_type = value.split(';').next().unwrap_or_default();
+        media_type.trim().eq_ignore_ascii_case("application/json")
+    });
+    if request.method == "POST" && !is_json {
+        return Reply::Json(Response::error(
+            415,
+            "Expected a JSON body (Content-Type: application/json)",
+        ));
+    }
+
+    let logs = LogStore::for_project(&server.project_dir);
+    let history = RunStore::for_project(&server.project_dir);
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    let response = match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["api"]) => Response::json(
+            200,
+            json!({ "name": "wrkflw", "version": env!("CARGO_PKG_VERSION") }),
+        ),
+        ("GET", ["api", "workflows"]) => list_workflows(),
+        ("POST", ["api", "validate"]) => validate(&request.body),
+        ("POST", ["api", "runs"]) => start_run(&request.body, server).await,
+        ("GET", ["api", "runs"]) => Response::json(200, logs.list()),
+        ("GET", ["api", "runs", id]) => show_run(&logs, id),
+        ("GET", ["api", "runs", id, "logs"]) => {
+            return run_log(&logs, id, request.query.get("job").map(String::as_str))
+        }
+        ("GET", ["api", "history"]) => list_history(&history, &request.query),
+        ("GET", ["api", "history", id]) => match check_id(id) {
+            Ok(()) => history
+                .load(id)
+                .map(|run| Response::json(200, run))
+                .unwrap_or_else(|e| Response::error(404, e)),
+            Err(response) => response,
+        },
+        ("POST", ["webhook"]) => receive_webhook(request, server).await,
+        (_, ["webhook"]) => Response::error(405, "Method Not Allowed"),
+        (
+            _,
+            ["api"]
+            | ["api", "workflows" | "validate" | "runs" | "history"]
+            | ["api", "runs" | "history", _]
+            | ["api", "runs", _, "logs"],
+        ) => Response::error(405, "Method Not Allowed"),
+        _ => Response::error(404, "Not Found"),
+    };
+    Reply::Json(response)
+}
+
+/// Whether `host`, a `Host` header, names the loopback interface on `port`
+fn is_loopback_host(host: &str, port: u16) -> bool {
+    let (name, host_port) = match host.rsplit_once(':') {
+        Some((name, host_port)) if !host_port.ends_with(']') => (name, Some(host_port)),
+        _ => (host, None),
+    };
+    let port_matches = match host_port {
+        Some(host_port) => host_port.parse() == Ok(port),
+        None => port == 80,
+    };
+    port_matches && ["localhost", "127.0.0.1", "[::1]"].contains(&name.to_lowercase().as_str())
+}
+
+/// `raw` as a path in the project, which requests may not leave
+fn project_path(raw: &str) -> Result<PathBuf, Response> {
+    let path = PathBuf::from(raw);
+    let inside = !raw.is_empty()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if inside {
+        Ok(path)
+    } else {
+        Err(Response::error(
+            400,
+            format!("'{}' is not a path in the project", raw),
+        ))
+    }
+}
+
+/// Run ids name directories, so only ids that can't point elsewhere are looked up
+fn check_id(id: &str) -> Result<(), Response> {
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(Response::error(400, format!("Invalid run id '{}'", id)))
+    }
+}
+
+fn parse_body<T: DeserializeOwned>(body: &[u8]) -> Result<T, Response> {
+    let body = if body.is_empty() { b"{}" } else { body };
+    serde_json::from_slice(body)
+        .map_err(|e| Response::error(400, format!("Invalid request body: {}", e)))
+}
+
+fn list_workflows() -> Response {
+    let github_dir = Path::new(".github/workflows");
+    let mut files = if github_dir.is_dir() {
+        workflow_files(github_dir)
+    } else {
+        Vec::new()
+    };
+    for file in [".gitlab-ci.yml", "azure-pipelines.yml", "Jenkinsfile"] {
+        if Path::new(file).is_file() {
+            files.push(PathBuf::from(file));
+        }
+    }
+    let workflows: Vec<Value> = files
+        .iter()
+        .map(|path| {
+            let platform = Platform::of(path, None);
+            let name = match platform {
+                Platform::GitHub => parse_workflow(path).ok().map(|workflow| workflow.name),
+                _ => None,
+            };
+            json!({
+                "path": path,
+                "platform": platform.name(),
+                "name": name,
+                "runnable": matches!(platform, Platform::GitHub | Platform::GitLab),
+            })
+        })
+        .collect();
+    Response::json(200, workflows)
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ValidateRequest {
+    /// Files or directories; `.github/workflows` when empty
+    #[serde(default)]
+    paths: Vec<String>,
+}
+
+fn validate(body: &[u8]) -> Response {
+    let request: ValidateRequest = match parse_body(body) {
+        Ok(request) => request,
+        Err(response) => return response,
+    };
+    let paths = if request.paths.is_empty() {
+        vec![PathBuf::from(".github/workflows")]
+    } else {
+        match request
+            .paths
+            .iter()
+            .map(|path| project_path(path))
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(paths) => paths,
+            Err(response) => return response,
+        }
+    };
+    Response::json(200, ValidationReport::for_paths(&paths, None, false))
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RunRequest {
+    path: String,
+    /// Event that triggers the run, like `--event`
+    #[serde(default)]
+    event: Option<String>,
+    /// Inputs of a `workflow_dispatch` run
+    #[serde(default)]
+    inputs: HashMap<String, String>,
+    /// Environment variables set in every job
+    #[serde(default)]
+    env: HashMap<String, String>,
+}
+
+async fn start_run(body: &[u8], server: &Arc<Server>) -> Response {
+    let request: RunRequest = match parse_body(body) {
+        Ok(request) => request,
+        Err(response) => return response,
+    };
+    let path = match project_path(&request.path) {
+        Ok(path) => path,
+        Err(response) => return response,
+    };
+    if !path.is_file() {
+        return Response::error(404, format!("No workflow at {}", path.display()));
+    }
+    match Platform::of(&path, None) {
+        Platform::Azure => {
+            return Response::error(
+                422,
+                format!(
+                    "{} is an Azure Pipelines file; these can be validated but not run",
+                    path.display()
+                ),
+            )
+        }
+        Platform::Jenkins => {
+            return Response::error(
+                422,
+                format!(
+                    "{} is a Jenkinsfile; these can be validated but not run",
+                    path.display()
+                ),
+            )
+        }
+        Platform::Action => {
+            return Response::error(
+                422,
+                format!(
+                    "{} is an action's metadata file, not a workflow",
+                    path.display()
+                ),
+            )
+        }
+        Platform::GitHub | Platform::GitLab => {}
+    }
+    let vars = match crate::vars::run_vars(false, &[]).await {
+        Ok(vars) => vars,
+        Err(e) => return Response::error(500, e),
+    };
+
+    if !server.runs.try_start() {
+        return Response::error(409, "Another run is in progress; runs go one at a time");
+    }
+    let config = ExecutionConfig {
+        event: request.event,
+        inputs: request.inputs,
+        env: request.env,
+        ..server.execution_config(vars)
+    };
+    let previous = logs::last_run_id();
+    let server = server.clone();
+    std::thread::spawn(move || {
+        run_workflow(&path, config, &server.project_dir);
+        server.runs.finish();
+    });
+
+    // The run opens its logs, and so gets its id, as soon as it starts
+    let started = Instant::now();
+    loop {
+        match logs::last_run_id() {
+            Some(id) if Some(&id) != previous.as_ref() => {
+                return Response::json(
+                    202,
+                    json!({
+                        "id": id,
+                        "path": request.path,
+                        "logs": format!("/api/runs/{}/logs", id),
+                    }),
+                );
+            }
+            _ if started.elapsed() > RUN_START_TIMEOUT => {
+                return Response::error(
+                    500,
+                    "The run didn't start in time; see the output of the server",
+                );
+            }
+            _ => tokio::time::sleep(Duration::from_millis(50)).await,
+        }
+    }
+}
+
+/// Run `path` with a runtime of its own, as it's called on a thread of its own for
+/// steps that block, then record it in the run history of `project_dir`
+fn run_workflow(path: &Path, config: ExecutionConfig, project_dir: &Path) {
+    println!(
+        "\n[{}] ▶️  {}",
+        Local::now().format("%H:%M:%S"),
+        path.display()
+    );
+    let runtime_mode = config.runtime_type.mode();
+    let result = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime.block_on(wrkflw_executor::execute_workflow(path, config)),
+        Err(e) => Err(ExecutionError::Execution(format!(
+            "Failed to create Tokio runtime: {}",
+            e
+        ))),
+    };
+    println!(
+        "\n[{}] 🏁 {} finished",
+        Local::now().format("%H:%M:%S"),
+        path.display()
+    );
+    print_run(&result);
+    if let Ok(result) = &result {
+        let store = RunStore::for_project(project_dir);
+        if let Err(e) = store.save(path, result, runtime_mode, false) {
+            eprintln!("Error recording the run: {}", e);
+        }
+    }
+}
+
+/// Run the workflows a verified webhook delivery triggers, after the run going on
+/// and those queued before them
+async fn receive_webhook(request: &Request, server: &Arc<Server>) -> Response {
+    let Some(secret) = &server.webhook_secret else {
+        return Response::error(
+            403,
+            "Webhooks are off; start the server with --webhook-secret",
+        );
+    };
+    let platform = match webhook::verify(&request.headers, &request.body, secret) {
+        Ok(platform) => platform,
+        Err(e) => return Response::error(401, e),
+    };
+    let event = match webhook::parse(platform, &request.headers, &request.body) {
+        Ok(Delivery::Ping) => return Response::json(200, json!({ "message": "pong" })),
+        Ok(Delivery::Ignored(reason)) => {
+            return Response::json(200, json!({ "message": format!("Ignored: {}", reason) }))
+        }
+        Ok(Delivery::Event(event)) => event,
+        Err(e) => return Response::error(400, e),
+    };
+
+    let workflows = webhook::triggered_workflows(&event);
+    let body = json!({
+        "event": event.event,
+        "ref": event.git_ref,
+        "workflows": workflows,
+    });
+    if workflows.is_empty() {
+        return Response::json(200, body);
+    }
+    let vars = match crate::vars::run_vars(false, &[]).await {
+        Ok(vars) => vars,
+        Err(e) => return Response::error(500, e),
+    };
+    if let Some(sha) = &event.sha {
+        if webhook::checked_out_commit().is_some_and(|head| head != *sha) {
+            wrkflw_logging::warning(&format!(
+                "Running the project as checked out rather than the delivered commit {}",
+                sha
+            ));
+        }
+    }
+    println!(
+        "\n[{}] 🪝 {} to {}: {}",
+        Local::now().format("%H:%M:%S"),
+        event.event,
+        event.git_ref,
+        workflows
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let config = ExecutionConfig {
+        event: Some(event.event),
+        changed_files: event.changed_files,
+        ..server.execution_config(vars)
+    };
+    let server = server.clone();
+    std::thread::spawn(move || {
+        server.runs.wait_turn();
+        for path in &workflows {
+            run_workflow(path, config.clone(), &server.project_dir);
+        }
+        server.runs.finish();
+    });
+    Response::json(202, body)
+}
+
+fn show_run(store: &LogStore, id: &str) -> Response {
+    if let Err(response) = check_id(id) {
+        return response;
+    }
+    match store.load(id) {
+        Ok(run) => {
+            let mut body = json!(run);
+            body["jobs"] = json!(store.jobs(&run));
+            Response::json(200, body)
+        }
+        Err(e) => Response::error(404, e),
+    }
+}
+
+fn run_log(store: &LogStore, id: &str, job: Option<&str>) -> Reply {
+    if let Err(response) = check_id(id) {
+        return Reply::Json(response);
+    }
+    let log = store
+        .load(id)
+        .and_then(|run| store.log_file(&run, job).map(|file| (run, file)));
+    match log {
+        Ok((run, file)) => Reply::Events { run, file },
+        Err(e) => Reply::Json(Response::error(404, e)),
+    }
+}
+
+fn list_history(store: &RunStore, query: &HashMap<String, String>) -> Response {
+    let limit = match query.get("limit").map(|limit| limit.parse::<usize>()) {
+        None => DEFAULT_HISTORY_LIMIT,
+        Some(Ok(limit)) => limit,
+        Some(Err(_)) => return Response::error(400, "limit must be a number"),
+    };
+    let workflow = query.get("workflow");
+    let runs: Vec<_> = store
+        .list()
+        .into_iter()
+        .filter(|run| workflow.is_none_or(|name| run.workflow.contains(name.as_str())))
+        .take(limit)
+        .collect();
+    Response::json(200, runs)
+}
+
+/// Send each line of the log `path` of `run` as a `data` event while the run goes
+/// on, then an `end` event telling whether it succeeded
+async fn stream_log(
+    stream: &mut TcpStream,
+    store: &LogStore,
+    mut run: LogRun,
+    path: &Path,
+) -> io::Result<()> {
+    stream
+        .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n")
+        .await?;
+    let mut file = File::open(path)?;
+    let mut pending = Vec::new();
+    loop {
+        // Known before reading, so that what a run logged before finishing is read
+        let finished = run.is_finished();
+        file.read_to_end(&mut pending)?;
+        let mut events = String::new();
+        while let Some(pos) = pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=pos).collect();
+            events.push_str(&data_event(&line));
+        }
+        if finished {
+            if !pending.is_empty() {
+                events.push_str(&data_event(&pending));
+            }
+            events.push_str(&format!(
+                "event: end\ndata: {}\n\n",
+                json!({ "success": run.success })
+            ));
+            return stream.write_all(events.as_bytes()).await;
+        }
+        if !events.is_empty() {
+            stream.write_all(events.as_bytes()).await?;
+        }
+        tokio::time::sleep(FOLLOW_INTERVAL).await;
+        run = store.load(&run.id).map_err(io::Error::other)?;
+    }
+}
+
+/// A log line as a server-sent event; carriage returns would end the event's line
+fn data_event(line: &[u8]) -> String {
+    let line = String::from_utf8_lossy(line);
+    format!("data: {}\n\n", line.trim_end().replace('\r', ""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(token: Option<&str>) -> Arc<Server> {
+        Arc::new(Server {
+            project_dir: tempfile::tempdir().unwrap().keep(),
+            runtime_type: RuntimeType::Emulation,
+            verbose: false,
+            port: 8080,
+            loopback: true,
+            token: token.map(str::to_string),
+            webhook_secret: Some("hook".to_string()),
+            runs: RunQueue::default(),
+        })
+    }
+
+    fn request(method: &str, path: &str, headers: &[(&str, &str)]) -> Request {
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            headers: [("host", "localhost:8080")]
+                .iter()
+                .chain(headers)
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    async fn status(request: &Request, server: &Arc<Server>) -> u16 {
+        match route(request, server).await {
+            Reply::Json(response) => response.status,
+            Reply::Events { .. } => 200,
+        }
+    }
+
+    #[test]
+    fn test_parse_head() {
+        let request = Request::parse_head(
+            "GET /api/runs/abc/logs?job=build%20linux&follow HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer s3cret\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/api/runs/abc/logs");
+        assert_eq!(request.query["job"], "build linux");
+        assert_eq!(request.query["follow"], "");
+        assert_eq!(request.header("authorization"), Some("Bearer s3cret"));
+        assert_eq!(request.header("host"), Some("localhost"));
+
+        assert!(Request::parse_head("GET /api\r\n\r\n").is_none());
+        assert!(Request::parse_head("\r\n\r\n").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_route_checks_host() {
+        let server = server(None);
+        for host in [
+            "localhost:8080",
+            "127.0.0.1:8080",
+            "[::1]:8080",
+            "LocalHost:8080",
+        ] {
+            let request = request("GET", "/api", &[("host", host)]);
+            assert_eq!(status(&request, &server).await, 200, "{}", host);
+        }
+        // Names a page rebinds to the loopback address, other ports and none at all
+        for host in [
+            "evil.example:8080",
+            "localhost:9090",
+            "localhost",
+            "127.0.0.1.nip.io:8080",
+        ] {
+            let request = request("GET", "/api", &[("host", host)]);
+            assert_eq!(status(&request, &server).await, 403, "{}", host);
+        }
+        let mut no_host = request("GET", "/api", &[]);
+        no_host.headers.clear();
+        assert_eq!(status(&no_host, &server).await, 403);
+
+        // Webhooks come through tunnels and proxies, and are signed instead
+        let json = [
+            ("host", "ci.example.com"),
+            ("content-type", "application/json"),
+        ];
+        assert_eq!(
+            status(&request("POST", "/webhook", &json), &server).await,
+            401
+        );
+    }
+
+    #[test]
+    fn test_project_path() {
+        assert!(project_path(".github/workflows/ci.yml").is_ok());
+        assert!(project_path("./.gitlab-ci.yml").is_ok());
+        for outside in ["", "/etc/passwd", "../ci.yml", ".github/../../ci.yml"] {
+            assert_eq!(
+                project_path(outside).unwrap_err().status,
+                400,
+                "{}",
+                outside
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_id() {
+        assert!(check_id("latest").is_ok());
+        assert!(check_id("20240101-120000-ci_2").is_ok());
+        for invalid in ["", "..", "a/b", "a%2Fb"] {
+            assert!(check_id(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_run_queue() {
+        let queue = Arc::new(RunQueue::default());
+        assert!(queue.try_start());
+        assert!(!queue.try_start());
+
+        // Webhook runs wait for the run going on, then take turns in order
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut waiting = Vec::new();
+        for n in 0..3 {
+            let (runs, order) = (queue.clone(), order.clone());
+            waiting.push(std::thread::spawn(move || {
+                runs.wait_turn();
+                order.lock().unwrap().push(n);
+                runs.finish();
+            }));
+            // Each has its turn before the next one arrives
+            while queue.state.lock().unwrap().queued <= n {
+                std::thread::yield_now();
+            }
+        }
+        // Nothing starts through the API while runs are waiting
+        assert!(!queue.try_start());
+        queue.finish();
+        for thread in waiting {
+            thread.join().unwrap();
+        }
+        assert_eq!(*order.lock().unwrap(), vec![0, 1, 2]);
+        assert!(queue.try_start());
+    }
+
+    #[tokio::test]
+    async fn test_route_checks_token_and_content_type() {
+        let open = server(None);
+        assert_eq!(status(&request("GET", "/api", &[]), &open).await, 200);
+        assert_eq!(status(&request("GET", "/nope", &[]), &open).await, 404);
+        assert_eq!(
+            status(&request("DELETE", "/api/runs", &[]), &open).await,
+            405
+        );
+        assert_eq!(
+            status(&request("POST", "/api/validate", &[]), &open).await,
+            415
+        );
+        assert_eq!(
+            status(&request("GET", "/api/runs/..", &[]), &open).await,
+            400
+        );
+        assert_eq!(
+            status(&request("GET", "/api/history/a.b", &[]), &open).await,
+            400
+        );
+
+        let protected = server(Some("s3cret"));
+        assert_eq!(status(&request("GET", "/api", &[]), &protected).await, 401);
+        for wrong in ["Bearer other", "Bearer s3cre", "Bearer s3cret2", "s3cret"] {
+            let wrong = [("authorization", wrong)];
+            assert_eq!(
+                status(&request("GET", "/api", &wrong), &protected).await,
+                401
+            );
+        }
+        let right = [("authorization", "Bearer s3cret")];
+        assert_eq!(
+            status(&request("GET", "/api", &right), &protected).await,
+            200
+        );
+
+        // Webhooks prove themselves with their own secret rather than the token
+        let json = [("content-type", "application/json")];
+        assert_eq!(
+            status(&request("POST", "/webhook", &json), &protected).await,
+            401
+        );
+        assert_eq!(
+            status(&request("GET", "/webhook", &[]), &protected).await,
+            405
+        );
+    }
+}
@@ -0,0 +1,720 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+use wrkflw_executor::config::{ProjectConfig, ValidationConfig};
+use wrkflw_executor::policy::WorkflowPolicy;
+use wrkflw_gitlab::lint::LintResult;
+use wrkflw_gitlab::pipelines::PipelinesClient;
+use wrkflw_models::{Finding, Severity, ValidationResult};
+use wrkflw_utils::cache;
+use wrkflw_validators::Suppressions;
+
+/// CI system a workflow or pipeline file is written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    GitHub,
+    GitLab,
+    Azure,
+    Jenkins,
+    /// An action's metadata file, `action.yml`
+    Action,
+}
+
+impl Platform {
+    /// `forced` if given, else the platform the file's name, location and content suggest
+    pub fn of(path: &Path, forced: Option<Platform>) -> Platform {
+        forced.unwrap_or_else(|| {
+            // Azure before GitLab, as its stages would also pass for GitLab's
+            if crate::is_action_metadata(path) {
+                Platform::Action
+            } else if crate::is_jenkinsfile(path) {
+                Platform::Jenkins
+            } else if crate::is_azure_pipeline(path) {
+                Platform::Azure
+            } else if crate::is_gitlab_pipeline(path) {
+                Platform::GitLab
+            } else {
+                Platform::GitHub
+            }
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Platform::GitHub => "github",
+            Platform::GitLab => "gitlab",
+            Platform::Azure => "azure",
+            Platform::Jenkins => "jenkins",
+            Platform::Action => "action",
+        }
+    }
+}
+
+/// Outcome of `wrkflw validate --format json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub valid: bool,
+    pub files: Vec<FileReport>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileReport {
+    pub path: PathBuf,
+    pub issues: Vec<String>,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    #[serde(default)]
+    pub infos: Vec<String>,
+    /// The issues, warnings and infos with their rules and severities
+    #[serde(default)]
+    pub findings: Vec<Finding>,
+    /// Findings suppressed by `wrkflw-disable` comments
+    #[serde(default)]
+    pub suppressed: Vec<Finding>,
+    /// Findings already in the baseline given with `--baseline`
+    #[serde(default)]
+    pub baselined: Vec<Finding>,
+    /// How long validating the file took
+    #[serde(skip)]
+    pub elapsed: Duration,
+}
+
+impl FileReport {
+    fn validate(path: PathBuf, forced: Option<Platform>) -> Self {
+        let start = Instant::now();
+        let result = file_result(&path, forced);
+        FileReport {
+            path,
+            issues: result.issues,
+            warnings: result.warnings,
+            infos: result.infos,
+            findings: result.findings,
+            suppressed: result.suppressed,
+            baselined: Vec::new(),
+            elapsed: start.elapsed(),
+        }
+    }
+
+    pub fn add(&mut self, finding: Finding) {
+        match finding.severity {
+            Severity::Error => self.issues.push(finding.message.clone()),
+            Severity::Warning => self.warnings.push(finding.message.clone()),
+            Severity::Info => self.infos.push(finding.message.clone()),
+        }
+        self.findings.push(finding);
+    }
+}
+
+impl ValidationReport {
+    pub fn new(files: Vec<FileReport>) -> Self {
+        ValidationReport {
+            valid: files.iter().all(|file| file.issues.is_empty()),
+            files,
+        }
+    }
+
+    /// Validate the workflow files at `paths`, directories meaning the files in them,
+    /// or in the whole tree under them when `recursive`. Files are validated
+    /// concurrently, one thread per core.
+    pub fn for_paths(paths: &[PathBuf], forced: Option<Platform>, recursive: bool) -> Self {
+        let mut missing = Vec::new();
+        let mut files = Vec::new();
+        for path in paths {
+            if !path.exists() {
+                missing.push(FileReport {
+                    path: path.clone(),
+                    issues: vec!["Path does not exist".to_string()],
+                    ..FileReport::default()
+                });
+            } else if recursive {
+                files.extend(workflow_files_recursive(path));
+            } else {
+                files.extend(workflow_files(path));
+            }
+        }
+
+        let workers = std::thread::available_parallelism()
+            .map_or(1, usize::from)
+            .min(files.len())
+            .max(1);
+        let mut reports: Vec<(usize, FileReport)> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..workers)
+                .map(|worker| {
+                    let files = &files;
+                    s.spawn(move || {
+                        (worker..files.len())
+                            .step_by(workers)
+                            .map(|idx| (idx, FileReport::validate(files[idx].clone(), forced)))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap_or_default())
+                .collect()
+        });
+        reports.sort_by_key(|(idx, _)| *idx);
+        missing.extend(reports.into_iter().map(|(_, report)| report));
+        Self::new(missing)
+    }
+
+    /// Validate the staged version of each workflow file changed in the git index,
+    /// which is what a commit made now would contain
+    pub fn for_staged(forced: Option<Platform>) -> Result<Self, String> {
+        let staged = git(&["diff", "--cached", "--name-only", "--diff-filter=ACMR"])?;
+        let dir = tempfile::tempdir()
+            .map_err(|e| format!("Failed to create temporary directory: {}", e))?;
+        let mut files = Vec::new();
+        for path in staged.lines().map(PathBuf::from) {
+            if !is_tracked_workflow(&path) {
+                continue;
+            }
+            let content = git(&["show", &format!(":{}", path.display())])?;
+            // At the same place in the copy, for what's detected from the location
+            let staged_copy = dir.path().join(&path);
+            if let Some(parent) = staged_copy.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+            std::fs::write(&staged_copy, content)
+                .map_err(|e| format!("Failed to write {}: {}", staged_copy.display(), e))?;
+            // Detected from the path in the repository, as the content may differ
+            let platform = Platform::of(&path, forced);
+            files.push(FileReport {
+                path,
+                ..FileReport::validate(staged_copy, Some(platform))
+            });
+        }
+        Ok(Self::new(files))
+    }
+
+    /// Add the errors GitLab's CI lint API finds in the GitLab pipelines of the
+    /// report, under the rule `gitlab-lint`; pipelines that can't be linted
+    /// remotely keep their local findings
+    pub async fn add_remote_lint(&mut self, forced: Option<Platform>) {
+        let severity = match validation_config().severities().get(REMOTE_LINT_RULE) {
+            Some(Some(severity)) => *severity,
+            Some(None) => return,
+            None => Severity::Error,
+        };
+        for file in &mut self.files {
+            if Platform::of(&file.path, forced) != Platform::GitLab || !file.path.is_file() {
+                continue;
+            }
+            match remote_lint(&file.path).await {
+                Ok(lint) => {
+                    let suppressions = suppressions(&file.path, forced);
+                    let mut errors = file.issues.clone();
+                    lint.merge_into(&mut errors);
+                    for message in errors.split_off(file.issues.len()) {
+                        let finding = Finding {
+                            rule: REMOTE_LINT_RULE.to_string(),
+                            severity,
+                            message,
+                        };
+                        if suppressions.suppresses(&finding) {
+                            file.suppressed.push(finding);
+                        } else {
+                            file.add(finding);
+                        }
+                    }
+                }
+                Err(e) => eprintln!(
+                    "Warning: Skipping GitLab lint of {}: {}",
+                    file.path.display(),
+                    e
+                ),
+            }
+        }
+        self.valid = self.files.iter().all(|file| file.issues.is_empty());
+    }
+
+    /// Add the requirements of `policy` the GitHub workflows of the report don't
+    /// meet, with the project's severities and the files' suppressions like other
+    /// findings. With `staged`, the files are checked as staged.
+    pub fn add_policy(&mut self, policy: &WorkflowPolicy, forced: Option<Platform>, staged: bool) {
+        let severities = validation_config().severities();
+        for file in &mut self.files {
+            if Platform::of(&file.path, forced) != Platform::GitHub {
+                continue;
+            }
+            let content = if staged {
+                git(&["show", &format!(":{}", file.path.display())]).ok()
+            } else {
+                std::fs::read_to_string(&file.path).ok()
+            };
+            let Some(content) = content else {
+                continue;
+            };
+            let suppressions = Suppressions::parse(&content);
+            let result = policy
+                .check(&content)
+                .with_severities(&severities)
+                .without_suppressed(|finding| suppressions.suppresses(finding));
+            file.suppressed.extend(result.suppressed);
+            for finding in result.findings {
+                file.add(finding);
+            }
+        }
+        self.valid = self.files.iter().all(|file| file.issues.is_empty());
+    }
+
+    /// Exit code of the report failing at `fail_on`: that of `policy` for the
+    /// first of its requirements failing when only requirements fail, else 1
+    pub fn exit_code(&self, fail_on: Severity, policy: &WorkflowPolicy) -> i32 {
+        let failing: Vec<&str> = self
+            .files
+            .iter()
+            .flat_map(|file| &file.findings)
+            .filter(|finding| finding.severity >= fail_on)
+            .map(|finding| finding.rule.as_str())
+            .collect();
+        let rules = policy.rules();
+        if failing.iter().any(|rule| !rules.contains(rule)) {
+            return 1;
+        }
+        rules
+            .into_iter()
+            .find(|rule| failing.contains(rule))
+            .map_or(1, |rule| policy.exit_code(rule))
+    }
+
+    /// Move the findings `is_baselined` holds for, given the path of their
+    /// file, to the `baselined` of the file
+    pub fn move_to_baseline(&mut self, mut is_baselined: impl FnMut(&Path, &Finding) -> bool) {
+        for file in &mut self.files {
+            let findings = std::mem::take(&mut file.findings);
+            file.issues.clear();
+            file.warnings.clear();
+            file.infos.clear();
+            for finding in findings {
+                if is_baselined(&file.path, &finding) {
+                    file.baselined.push(finding);
+                } else {
+                    file.add(finding);
+                }
+            }
+        }
+        self.valid = self.files.iter().all(|file| file.issues.is_empty());
+    }
+
+    pub fn baselined_count(&self) -> usize {
+        self.files.iter().map(|file| file.baselined.len()).sum()
+    }
+
+    pub fn issue_count(&self) -> usize {
+        self.files.iter().map(|file| file.issues.len()).sum()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.files.iter().map(|file| file.warnings.len()).sum()
+    }
+
+    pub fn info_count(&self) -> usize {
+        self.files.iter().map(|file| file.infos.len()).sum()
+    }
+
+    /// Whether any file has a finding at least as severe as `fail_on`
+    pub fn fails(&self, fail_on: Severity) -> bool {
+        self.files
+            .iter()
+            .flat_map(|file| &file.findings)
+            .any(|finding| finding.severity >= fail_on)
+    }
+
+    /// Print the issues and warnings of each file, then a table of the files with
+    /// their counts and how long they took
+    /// Print the findings of each file, the suppressed ones too with
+    /// `show_suppressed`, then a table of the files when there are several
+    pub fn print_summary(&self, show_suppressed: bool) {
+        for file in &self.files {
+            if self.files.len() > 1
+                && file.issues.is_empty()
+                && file.warnings.is_empty()
+                && file.infos.is_empty()
+                && (!show_suppressed || file.suppressed.is_empty())
+            {
+                continue;
+            }
+            print_status(&file.path, &file.issues);
+            for issue in &file.issues {
+                println!("   - {}", issue);
+            }
+            for warning in &file.warnings {
+                println!("   ⚠️  {}", warning);
+            }
+            for info in &file.infos {
+                println!("   ℹ️  {}", info);
+            }
+            if show_suppressed {
+                for finding in &file.suppressed {
+                    println!(
+                        "   🔇 {} [{}, suppressed]: {}",
+                        finding.severity, finding.rule, finding.message
+                    );
+                }
+            }
+        }
+        if self.files.len() == 1 {
+            return;
+        }
+
+        let paths: Vec<String> = self
+            .files
+            .iter()
+            .map(|file| relative(&file.path).display().to_string())
+            .collect();
+        let width = paths
+            .iter()
+            .map(|path| path.chars().count())
+            .max()
+            .unwrap_or(0);
+        let width = width.max("File".len());
+        println!(
+            "\n{:<width$}  {:>6}  {:>8}  {:>8}",
+            "File",
+            "Errors",
+            "Warnings",
+            "Time",
+            width = width
+        );
+        for (file, path) in self.files.iter().zip(&paths) {
+            println!(
+                "{:<width$}  {:>6}  {:>8}  {:>8}",
+                path,
+                file.issues.len(),
+                file.warnings.len(),
+                format!("{} ms", file.elapsed.as_millis()),
+                width = width
+            );
+        }
+        let invalid = self
+            .files
+            .iter()
+            .filter(|file| !file.issues.is_empty())
+            .count();
+        println!(
+            "\n{} file(s): {} valid, {} invalid, {} warning(s), {} info(s)",
+            self.files.len(),
+            self.files.len() - invalid,
+            invalid,
+            self.warning_count(),
+            self.info_count()
+        );
+    }
+
+    pub fn print(&self) {
+        for file in &self.files {
+            print_status(&file.path, &file.issues);
+            for issue in &file.issues {
+                println!("   - {}", issue);
+            }
+        }
+    }
+}
+
+/// Whether a path relative to the repository root is one of the places workflows
+/// and pipelines live
+fn is_tracked_workflow(path: &Path) -> bool {
+    let is_yaml = path
+        .extension()
+        .is_some_and(|ext| ext == "yml" || ext == "yaml");
+    let in_dir = |dir: &str| path.parent() == Some(Path::new(dir));
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let gitlab_ci = name.ends_with("gitlab-ci.yml");
+    let azure = is_yaml && name.starts_with("azure-pipelines");
+    gitlab_ci
+        || azure
+        || crate::is_jenkinsfile(path)
+        || crate::is_action_metadata(path)
+        || (is_yaml && (in_dir(".github/workflows") || in_dir(".gitlab/ci")))
+}
+
+fn git(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Issues found in a workflow or pipeline file, a parse error being one
+pub fn file_issues(path: &Path, forced: Option<Platform>) -> Vec<String> {
+    file_result(path, forced).issues
+}
+
+/// Rule of the errors GitLab's CI lint API finds
+const REMOTE_LINT_RULE: &str = "gitlab-lint";
+
+/// The `[validation]` section of the project's `.wrkflw.toml`, the current
+/// directory like for runs; a config that doesn't load is reported by the
+/// action policy check and leaves the rules at their defaults
+pub fn validation_config() -> ValidationConfig {
+    std::env::current_dir()
+        .ok()
+        .and_then(|dir| ProjectConfig::load(&dir).ok())
+        .map(|config| config.validation)
+        .unwrap_or_default()
+}
+
+/// Issues, warnings and infos found in a workflow or pipeline file, with the
+/// severities the project's `[validation]` config sets for their rules, less
+/// those the file's `wrkflw-disable` comments suppress
+pub fn file_result(path: &Path, forced: Option<Platform>) -> ValidationResult {
+    let result = cached_result(path, forced).with_severities(&validation_config().severities());
+    let suppressions = suppressions(path, forced);
+    if suppressions.is_empty() {
+        return result;
+    }
+    result.without_suppressed(|finding| suppressions.suppresses(finding))
+}
+
+/// The suppression comments of a YAML file; Jenkinsfiles have none
+fn suppressions(path: &Path, forced: Option<Platform>) -> Suppressions {
+    if Platform::of(path, forced) == Platform::Jenkins {
+        return Suppressions::default();
+    }
+    std::fs::read_to_string(path)
+        .map(|text| Suppressions::parse(&text))
+        .unwrap_or_default()
+}
+
+/// The findings of the file's validator, from the cache when nothing they
+/// depend on changed. Azure pipelines aren't cached, as their templates are
+/// other files.
+fn cached_result(path: &Path, forced: Option<Platform>) -> ValidationResult {
+    let platform = Platform::of(path, forced);
+    if platform == Platform::Azure {
+        return platform_result(path, forced);
+    }
+    let mut inputs = cache::workflow_inputs(path);
+    inputs.push(format!("{:?}", platform));
+    inputs.push(crate::is_gitlab_fragment(path).to_string());
+    let inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+    let findings = cache::cached_for_file("validation", path, &inputs, || {
+        platform_result(path, forced).findings
+    });
+    ValidationResult::from_findings(findings)
+}
+
+fn platform_result(path: &Path, forced: Option<Platform>) -> ValidationResult {
+    let failed = |error: String| {
+        let mut result = ValidationResult::new();
+        result.add_for("syntax", Severity::Error, error);
+        result
+    };
+    match Platform::of(path, forced) {
+        Platform::GitLab => {
+            // Duplicate keys and broken anchors and merge keys, where they are,
+            // rather than where the parser stops
+            let checked = yaml_result(path);
+            if !checked.is_valid {
+                return checked;
+            }
+            match wrkflw_parser::gitlab::parse_pipeline(path) {
+                Ok(pipeline) if crate::is_gitlab_fragment(path) => {
+                    wrkflw_validators::validate_gitlab_fragment(&pipeline)
+                }
+                Ok(pipeline) => wrkflw_validators::validate_gitlab_pipeline(&pipeline),
+                Err(e) => failed(e.to_string()),
+            }
+        }
+        Platform::Action => match parse_action_metadata(path) {
+            Ok(action) => wrkflw_validators::validate_action_metadata(&action),
+            Err(e) => failed(e),
+        },
+        Platform::Azure => match wrkflw_parser::azure::parse_pipeline(path) {
+            Ok(pipeline) => {
+                let mut result = wrkflw_validators::validate_azure_pipeline(&pipeline);
+                for issue in wrkflw_parser::azure::missing_templates(&pipeline, path) {
+                    result.add_issue(issue);
+                }
+                result
+            }
+            Err(e) => failed(e.to_string()),
+        },
+        Platform::Jenkins => match wrkflw_parser::jenkins::parse_pipeline(path) {
+            Ok(file) => wrkflw_validators::validate_jenkins_pipeline(&file),
+            Err(e) => failed(e.to_string()),
+        },
+        Platform::GitHub => match wrkflw_evaluator::evaluate_workflow_file(path, false) {
+            Ok(mut result) => {
+                for issue in wrkflw_executor::policy::workflow_file_issues(path) {
+                    result.add_for("action-policy", Severity::Error, issue);
+                }
+                result
+            }
+            Err(e) => failed(e),
+        },
+    }
+}
+
+/// The findings of the checks of the YAML of the GitLab pipeline at `path`, which
+/// may use merge keys
+fn yaml_result(path: &Path) -> ValidationResult {
+    let mut result = ValidationResult::new();
+    if let Ok(content) = std::fs::read_to_string(path) {
+        result.in_rule("yaml", |result| {
+            wrkflw_validators::validate_yaml(&content, true, result)
+        });
+    }
+    result
+}
+
+/// GitLab's lint of the pipeline at `path` as part of the project `origin` points
+/// to, with the token `wrkflw_utils::auth::gitlab_token` finds
+pub async fn remote_lint(path: &Path) -> Result<LintResult, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let repo = wrkflw_gitlab::get_repo_info().map_err(|e| e.to_string())?;
+    let client = PipelinesClient::from_env(repo).map_err(|e| e.to_string())?;
+    client.lint(&content).await.map_err(|e| e.to_string())
+}
+
+pub fn is_workflow_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "yml" || ext == "yaml")
+        || crate::is_jenkinsfile(path)
+}
+
+/// The workflow files in `path` if it's a directory, else `path` itself
+pub fn workflow_files(path: &Path) -> Vec<PathBuf> {
+    if !path.is_dir() {
+        return vec![path.to_path_buf()];
+    }
+    let mut files: Vec<PathBuf> = std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file() && is_workflow_file(path))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+/// Directories never searched for workflows
+const SKIPPED_DIRS: &[&str] = &[".git", "target", "node_modules"];
+
+/// The workflows, pipelines and action metadata files anywhere under `path`, or
+/// `path` itself if it's a file. YAML files are only taken when they are one of
+/// these, GitHub workflows (templates included) being recognized by their `jobs`
+pub fn workflow_files_recursive(path: &Path) -> Vec<PathBuf> {
+    if !path.is_dir() {
+        return vec![path.to_path_buf()];
+    }
+    let mut files = Vec::new();
+    let mut dirs = vec![path.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            let name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default();
+            if path.is_dir() {
+                if !SKIPPED_DIRS.contains(&name) {
+                    dirs.push(path);
+                }
+            } else if is_workflow_file(&path) && is_pipeline(&path) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Whether a workflow file found while searching a tree is one to validate
+fn is_pipeline(path: &Path) -> bool {
+    if crate::is_jenkinsfile(path)
+        || crate::is_action_metadata(path)
+        || crate::is_gitlab_pipeline(path)
+        || crate::is_azure_pipeline(path)
+    {
+        return true;
+    }
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_yaml::from_str::<serde_yaml::Value>(&content).ok())
+        .is_some_and(|yaml| yaml.get("jobs").is_some_and(serde_yaml::Value::is_mapping))
+}
+
+pub fn parse_action_metadata(path: &Path) -> Result<serde_yaml::Value, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_yaml::from_str(&content).map_err(|e| format!("YAML parsing error: {}", e))
+}
+
+/// `path` relative to the current directory, as changes come with absolute paths
+pub fn relative(path: &Path) -> &Path {
+    let cwd = std::env::current_dir().and_then(|dir| dir.canonicalize());
+    cwd.ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok())
+        .unwrap_or(path)
+}
+
+pub fn print_status(path: &Path, issues: &[String]) {
+    let path = relative(path);
+    if issues.is_empty() {
+        println!("✅ {}", path.display());
+    } else {
+        println!("❌ {} ({} issue(s))", path.display(), issues.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_tracked_workflow() {
+        assert!(is_tracked_workflow(Path::new(".github/workflows/ci.yml")));
+        assert!(is_tracked_workflow(Path::new(".github/workflows/ci.yaml")));
+        assert!(is_tracked_workflow(Path::new(".gitlab-ci.yml")));
+        assert!(is_tracked_workflow(Path::new(".gitlab/ci/build.yml")));
+        assert!(is_tracked_workflow(Path::new("azure-pipelines.yml")));
+        assert!(is_tracked_workflow(Path::new(
+            "ci/azure-pipelines-release.yaml"
+        )));
+        assert!(is_tracked_workflow(Path::new("Jenkinsfile")));
+        assert!(is_tracked_workflow(Path::new("ci/deploy.jenkinsfile")));
+        assert!(!is_tracked_workflow(Path::new(
+            ".github/workflows/scripts/x.yml"
+        )));
+        assert!(!is_tracked_workflow(Path::new(".github/dependabot.yml")));
+        assert!(!is_tracked_workflow(Path::new("src/main.rs")));
+    }
+
+    #[test]
+    fn test_report_fails_on_threshold() {
+        let mut file = FileReport::default();
+        file.add(Finding {
+            rule: "secret-leaks".to_string(),
+            severity: Severity::Warning,
+            message: "prints a secret".to_string(),
+        });
+        let report = ValidationReport::new(vec![file]);
+        assert!(report.valid);
+        assert_eq!(report.warning_count(), 1);
+        assert!(!report.fails(Severity::Error));
+        assert!(report.fails(Severity::Warning));
+        assert!(report.fails(Severity::Info));
+    }
+}
@@ -0,0 +1,114 @@
+use clap::Subcommand;
+use std::collections::{BTreeMap, HashMap};
+use wrkflw_executor::vars::{self, VarsFile};
+
+#[derive(Debug, Subcommand)]
+pub enum VarsCommand {
+    /// List the variables in .wrkflw/vars.toml
+    List {
+        /// List the repository's variables on GitHub instead
+        #[arg(long)]
+        remote: bool,
+    },
+
+    /// Add or change a variable in .wrkflw/vars.toml
+    Set {
+        /// Name of the variable
+        name: String,
+
+        /// Its value
+        value: String,
+    },
+
+    /// Remove a variable from .wrkflw/vars.toml
+    Remove {
+        /// Name of the variable
+        name: String,
+    },
+
+    /// Copy the repository's variables on GitHub into .wrkflw/vars.toml, keeping
+    /// local ones GitHub doesn't have
+    Pull,
+}
+
+/// Execute a `wrkflw vars` subcommand
+pub async fn handle_vars_command(command: &VarsCommand) -> Result<(), String> {
+    let file = vars_file()?;
+    match command {
+        VarsCommand::List { remote } => {
+            let vars = if *remote {
+                github_vars().await?
+            } else {
+                file.load()?
+            };
+            if vars.is_empty() {
+                if *remote {
+                    println!("No variables on GitHub");
+                } else {
+                    println!("No variables in {}", file.path().display());
+                }
+            }
+            for (name, value) in vars {
+                println!("  {} = {}", name, value);
+            }
+            Ok(())
+        }
+        VarsCommand::Set { name, value } => {
+            file.set(name, value)?;
+            println!("✅ Set variable '{}'", name);
+            Ok(())
+        }
+        VarsCommand::Remove { name } => {
+            if file.remove(name)? {
+                println!("✅ Removed variable '{}'", name);
+            } else {
+                println!("Variable '{}' not found in {}", name, file.path().display());
+            }
+            Ok(())
+        }
+        VarsCommand::Pull => {
+            let remote = github_vars().await?;
+            let mut local = file.load()?;
+            let count = remote.len();
+            local.extend(remote);
+            file.save(&local)?;
+            println!(
+                "✅ Pulled {} variable(s) from GitHub into {}",
+                count,
+                file.path().display()
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Variables of a run: the repository's on GitHub when asked for, then those of
+/// .wrkflw/vars.toml, then the ones given with --var
+pub async fn run_vars(
+    from_github: bool,
+    overrides: &[(String, String)],
+) -> Result<HashMap<String, String>, String> {
+    let mut run_vars = HashMap::new();
+    if from_github {
+        run_vars.extend(github_vars().await?);
+    }
+    run_vars.extend(vars_file()?.load()?);
+    for (name, value) in overrides {
+        vars::validate_name(name)?;
+        run_vars.insert(name.clone(), value.clone());
+    }
+    Ok(run_vars)
+}
+
+fn vars_file() -> Result<VarsFile, String> {
+    let dir =
+        std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?;
+    Ok(VarsFile::for_project(&dir))
+}
+
+async fn github_vars() -> Result<BTreeMap<String, String>, String> {
+    crate::remote::client()?
+        .variables()
+        .await
+        .map_err(|e| format!("Failed to fetch the variables from GitHub: {}", e))
+}
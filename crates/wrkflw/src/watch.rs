@@ -0,0 +1,389 @@
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use wrkflw_executor::engine::{ExecutionError, ExecutionResult};
+use wrkflw_executor::paths::PathFilter;
+use wrkflw_executor::{ExecutionConfig, JobStatus};
+
+use crate::validation::{
+    file_issues, is_workflow_file, print_status, relative, workflow_files, Platform,
+};
+
+/// Editors write a file in several steps; changes this close together are one save
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Directories whose changes are never sources of a run
+const IGNORED_DIRS: &[&str] = &[".git", "target", ".wrkflw", "node_modules"];
+
+/// Watches files, and directories of them, for changes
+pub struct FileWatcher {
+    // Kept alive for as long as events are wanted
+    _watcher: RecommendedWatcher,
+    events: mpsc::Receiver<notify::Result<Event>>,
+    dirs: Vec<PathBuf>,
+    files: Vec<PathBuf>,
+    /// Whether any file below `dirs` counts, rather than workflow files directly in them
+    recursive: bool,
+}
+
+impl FileWatcher {
+    /// Watch the workflow files in the directories of `paths` and the files among them
+    pub fn workflows(paths: &[PathBuf]) -> Result<Self, String> {
+        Self::new(paths, false)
+    }
+
+    /// Watch every file below the directories of `paths` and the files among them
+    pub fn sources(paths: &[PathBuf]) -> Result<Self, String> {
+        Self::new(paths, true)
+    }
+
+    fn new(paths: &[PathBuf], recursive: bool) -> Result<Self, String> {
+        let (tx, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)
+            .map_err(|e| format!("Failed to start the file watcher: {}", e))?;
+
+        let mut dirs = Vec::new();
+        let mut files = Vec::new();
+        for path in paths {
+            let path = path
+                .canonicalize()
+                .map_err(|e| format!("Can't watch {}: {}", path.display(), e))?;
+            // Editors often save by replacing the file, which ends a watch on the file
+            // itself, so files are watched through their directory
+            let (dir, mode) = if path.is_dir() {
+                dirs.push(path.clone());
+                let mode = if recursive {
+                    RecursiveMode::Recursive
+                } else {
+                    RecursiveMode::NonRecursive
+                };
+                (path, mode)
+            } else {
+                files.push(path.clone());
+                let dir = path.parent().map(Path::to_path_buf).unwrap_or(path);
+                (dir, RecursiveMode::NonRecursive)
+            };
+            watcher
+                .watch(&dir, mode)
+                .map_err(|e| format!("Can't watch {}: {}", dir.display(), e))?;
+        }
+
+        Ok(FileWatcher {
+            _watcher: watcher,
+            events,
+            dirs,
+            files,
+            recursive,
+        })
+    }
+
+    fn is_watched(&self, path: &Path) -> bool {
+        if self.files.iter().any(|file| file == path) {
+            return true;
+        }
+        if !self.recursive {
+            return is_workflow_file(path)
+                && path
+                    .parent()
+                    .is_some_and(|parent| self.dirs.iter().any(|dir| dir == parent));
+        }
+        self.dirs.iter().any(|dir| {
+            path.strip_prefix(dir).is_ok_and(|relative| {
+                !relative.components().any(|part| {
+                    IGNORED_DIRS
+                        .iter()
+                        .any(|ignored| part.as_os_str() == *ignored)
+                })
+            })
+        })
+    }
+
+    fn watched_paths(&self, event: notify::Result<Event>) -> Vec<PathBuf> {
+        match event {
+            Ok(event) if !matches!(event.kind, EventKind::Access(_)) => event
+                .paths
+                .into_iter()
+                .filter(|path| self.is_watched(path))
+                .collect(),
+            Ok(_) => Vec::new(),
+            Err(e) => {
+                wrkflw_logging::warning(&format!("File watcher error: {}", e));
+                Vec::new()
+            }
+        }
+    }
+
+    /// Wait for watched files to change; None once the watcher stops
+    pub fn next_change(&self) -> Option<BTreeSet<PathBuf>> {
+        let mut changed = BTreeSet::new();
+        while changed.is_empty() {
+            let event = self.events.recv().ok()?;
+            changed.extend(self.watched_paths(event));
+        }
+        while let Ok(event) = self.events.recv_timeout(DEBOUNCE) {
+            changed.extend(self.watched_paths(event));
+        }
+        Some(changed)
+    }
+
+    /// Wait for changes on a thread of their own, to await them alongside a run
+    pub fn into_changes(self) -> tokio::sync::mpsc::UnboundedReceiver<BTreeSet<PathBuf>> {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        std::thread::spawn(move || {
+            while let Some(changed) = self.next_change() {
+                if tx.send(changed).is_err() {
+                    break;
+                }
+            }
+        });
+        rx
+    }
+}
+
+/// Validate `paths`, then validate each file again whenever it changes, printing
+/// the issues that appeared and went away since its last validation
+pub fn validate_on_change(paths: &[PathBuf], forced: Option<Platform>) -> Result<(), String> {
+    let watcher = FileWatcher::workflows(paths)?;
+
+    // Last issues of each file, by canonical path
+    let mut known: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+    for path in paths.iter().flat_map(|path| workflow_files(path)) {
+        let issues = file_issues(&path, forced);
+        print_status(&path, &issues);
+        for issue in &issues {
+            println!("   - {}", issue);
+        }
+        known.insert(path.canonicalize().unwrap_or(path), issues);
+    }
+    let failing = known.values().filter(|issues| !issues.is_empty()).count();
+    println!(
+        "\n👀 Watching {} file(s), {} with issues; press Ctrl+C to stop",
+        known.len(),
+        failing
+    );
+
+    while let Some(changed) = watcher.next_change() {
+        println!("\n[{}] Changed:", chrono::Local::now().format("%H:%M:%S"));
+        for path in changed {
+            if !path.exists() {
+                if known.remove(&path).is_some() {
+                    println!("🗑  {} removed", path.display());
+                }
+                continue;
+            }
+            let issues = file_issues(&path, forced);
+            let before = known.get(&path).cloned().unwrap_or_default();
+            print_status(&path, &issues);
+            for issue in issues.iter().filter(|issue| !before.contains(issue)) {
+                println!("   + {}", issue);
+            }
+            for issue in before.iter().filter(|issue| !issues.contains(issue)) {
+                println!("   ✓ fixed: {}", issue);
+            }
+            if !issues.is_empty() && issues == before {
+                println!("   (issues unchanged)");
+            }
+            known.insert(path, issues);
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn print_run(result: &RunResult) {
+    let result = match result {
+        Ok(result) => result,
+        Err(e) => {
+            println!("❌ Error executing workflow: {}", e);
+            return;
+        }
+    };
+    for job in &result.jobs {
+        println!(
+            "  {} {} ({:.1}s)",
+            match job.status {
+                JobStatus::Success => "✅",
+                JobStatus::Failure => "❌",
+                JobStatus::Skipped => "⏭️",
+                JobStatus::Cancelled => "🚫",
+            },
+            job.name,
+            job.timing.duration.as_secs_f64()
+        );
+    }
+    match &result.failure_details {
+        None => println!("✅ Run succeeded in {:.1}s", result.duration.as_secs_f64()),
+        Some(details) => {
+            println!("❌ Run failed in {:.1}s", result.duration.as_secs_f64());
+            for line in details
+                .lines()
+                .filter(|line| line.contains("❌") || line.trim().starts_with("Error:"))
+                .take(5)
+            {
+                println!("{}", line);
+            }
+        }
+    }
+}
+
+/// Files among `files` that git ignores, such as build output
+fn git_ignored(files: &BTreeSet<PathBuf>) -> BTreeSet<PathBuf> {
+    let output = std::process::Command::new("git")
+        .arg("check-ignore")
+        .args(files)
+        .output();
+    match output {
+        Ok(output) => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(PathBuf::from)
+            .collect(),
+        Err(_) => BTreeSet::new(),
+    }
+}
+
+/// What a change calls for
+enum Trigger {
+    /// Nothing: only ignored or filtered out files changed
+    None,
+    /// A run of the whole workflow, which changed itself
+    Workflow,
+    /// A run for these changed files, relative to the current directory
+    Files(Vec<String>),
+}
+
+fn trigger(path: &Path, filter: Option<&PathFilter>, changed: &BTreeSet<PathBuf>) -> Trigger {
+    let workflow = path.canonicalize().ok();
+    if changed.iter().any(|file| Some(file) == workflow.as_ref()) {
+        return Trigger::Workflow;
+    }
+    let ignored = git_ignored(changed);
+    let files: Vec<String> = changed
+        .difference(&ignored)
+        .map(|file| relative(file).to_string_lossy().replace('\\', "/"))
+        .collect();
+    if files.is_empty() || filter.is_some_and(|filter| !filter.matches(&files)) {
+        return Trigger::None;
+    }
+    Trigger::Files(files)
+}
+
+pub(crate) type RunResult = Result<ExecutionResult, ExecutionError>;
+
+/// Run the workflow on a thread of its own, so that changes are noticed while steps
+/// block; the run is dropped once cancelled
+fn spawn_run(
+    path: &Path,
+    config: &ExecutionConfig,
+) -> (oneshot::Receiver<RunResult>, oneshot::Sender<()>) {
+    let (result_tx, result_rx) = oneshot::channel();
+    let (cancel_tx, cancel_rx) = oneshot::channel::<()>();
+    let path = path.to_path_buf();
+    let config = config.clone();
+    std::thread::spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                let _ = result_tx.send(Err(ExecutionError::Execution(format!(
+                    "Failed to create Tokio runtime: {}",
+                    e
+                ))));
+                return;
+            }
+        };
+        runtime.block_on(async {
+            tokio::select! {
+                result = wrkflw_executor::execute_workflow(&path, config) => {
+                    let _ = result_tx.send(result);
+                }
+                _ = cancel_rx => {}
+            }
+        });
+    });
+    (result_rx, cancel_tx)
+}
+
+/// Run the workflow at `path`, then again whenever files under `sources` (the
+/// current directory if none) or the workflow itself change. Changes that the
+/// workflow's `on.push` path filters ignore don't start a run, GitLab jobs whose
+/// `changes:` they don't match are skipped, and a change during a run cancels it.
+/// Returns once a run is cancelled through `config.cancel`.
+pub async fn run_on_change(
+    path: &Path,
+    mut config: ExecutionConfig,
+    sources: &[PathBuf],
+) -> Result<(), String> {
+    let mut watched = if sources.is_empty() {
+        vec![PathBuf::from(".")]
+    } else {
+        sources.to_vec()
+    };
+    watched.push(path.to_path_buf());
+    let mut changes = FileWatcher::sources(&watched)?.into_changes();
+    let filter = wrkflw_executor::paths::workflow_push_filter(path);
+    let gitlab = crate::is_gitlab_pipeline(path);
+
+    loop {
+        println!(
+            "\n[{}] ▶️  Running {}",
+            chrono::Local::now().format("%H:%M:%S"),
+            path.display()
+        );
+        let (mut run, cancel) = spawn_run(path, &config);
+        let restart = loop {
+            tokio::select! {
+                result = &mut run => {
+                    match result {
+                        Ok(result) => print_run(&result),
+                        Err(_) => println!("❌ The run stopped unexpectedly"),
+                    }
+                    // Cancelled with Ctrl+C rather than by a change
+                    if config.cancel.is_run_cancelled() {
+                        return Ok(());
+                    }
+                    break None;
+                }
+                changed = changes.recv() => {
+                    let Some(changed) = changed else {
+                        return Ok(());
+                    };
+                    match trigger(path, filter.as_ref(), &changed) {
+                        Trigger::None => {}
+                        next => {
+                            println!("🔁 Files changed; cancelling the run");
+                            let _ = cancel.send(());
+                            break Some(next);
+                        }
+                    }
+                }
+            }
+        };
+        let next = match restart {
+            Some(next) => {
+                // Containers of the dropped run are left behind
+                crate::cleanup_on_exit().await;
+                next
+            }
+            None => {
+                println!("👀 Waiting for changes; press Ctrl+C to stop");
+                loop {
+                    let Some(changed) = changes.recv().await else {
+                        return Ok(());
+                    };
+                    match trigger(path, filter.as_ref(), &changed) {
+                        Trigger::None => println!("⏭️  Changed files are git-ignored or don't match the workflow's push path filters; not running"),
+                        next => break next,
+                    }
+                }
+            }
+        };
+        config.changed_files = match next {
+            Trigger::Files(files) if gitlab => Some(files),
+            _ => None,
+        };
+    }
+}
@@ -0,0 +1,433 @@
+//! Webhook deliveries from GitHub and GitLab, for `wrkflw serve --webhook-secret`:
+//! checking they come from the forge, reading them as the push or pull request
+//! events wrkflw simulates, and finding the workflows those trigger.
+
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use wrkflw_executor::paths::{PathFilter, RefFilter};
+use wrkflw_parser::workflow::parse_workflow;
+
+use crate::validation::{workflow_files, Platform};
+
+/// Pull request activity that triggers a workflow without `types:`
+const DEFAULT_PULL_REQUEST_TYPES: [&str; 3] = ["opened", "synchronize", "reopened"];
+
+/// A push or pull request a delivery reports
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookEvent {
+    pub platform: Platform,
+    /// Simulated event: `push` or `pull_request`
+    pub event: String,
+    /// Activity of a pull request, as GitHub names it (`opened`, `synchronize`, ...)
+    pub action: Option<String>,
+    /// Ref pushed to, or the base branch of a pull request
+    pub git_ref: String,
+    /// Commit pushed, or the head of the pull request
+    pub sha: Option<String>,
+    /// Files the pushed commits changed; None when the delivery doesn't list them
+    pub changed_files: Option<Vec<String>>,
+}
+
+/// What a delivery asks for
+#[derive(Debug, Clone, PartialEq)]
+pub enum Delivery {
+    /// GitHub checking that the webhook works
+    Ping,
+    /// An event no workflow is run for, with why
+    Ignored(String),
+    Event(WebhookEvent),
+}
+
+/// The platform a delivery with `headers` (by lowercase name) and `body` comes from,
+/// if it proves to know `secret`: GitHub signs the body with it, GitLab sends it
+pub fn verify(
+    headers: &HashMap<String, String>,
+    body: &[u8],
+    secret: &str,
+) -> Result<Platform, String> {
+    if let Some(signature) = headers.get("x-hub-signature-256") {
+        let signature = signature
+            .strip_prefix("sha256=")
+            .and_then(decode_hex)
+            .ok_or("Malformed X-Hub-Signature-256")?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(body);
+        return mac
+            .verify_slice(&signature)
+            .map(|()| Platform::GitHub)
+            .map_err(|_| "Signature doesn't match the webhook secret".to_string());
+    }
+    if let Some(token) = headers.get("x-gitlab-token") {
+        return if constant_time_eq(token.as_bytes(), secret.as_bytes()) {
+            Ok(Platform::GitLab)
+        } else {
+            Err("X-Gitlab-Token doesn't match the webhook secret".to_string())
+        };
+    }
+    Err("Deliveries must be signed (X-Hub-Signature-256) or carry X-Gitlab-Token".to_string())
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Compare secrets in time that doesn't depend on where they differ
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Read a verified delivery from `platform`
+pub fn parse(
+    platform: Platform,
+    headers: &HashMap<String, String>,
+    body: &[u8],
+) -> Result<Delivery, String> {
+    let payload: Value =
+        serde_json::from_slice(body).map_err(|e| format!("Invalid payload: {}", e))?;
+    let str_at = |pointer: &str| {
+        payload
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+
+    let (header, kind) = match platform {
+        Platform::GitLab => ("x-gitlab-event", "GitLab"),
+        _ => ("x-github-event", "GitHub"),
+    };
+    let name = headers
+        .get(header)
+        .ok_or_else(|| format!("{} delivery without an event header", kind))?;
+    let event = match (platform, name.as_str()) {
+        (Platform::GitHub, "ping") => return Ok(Delivery::Ping),
+        (Platform::GitHub, "push") => {
+            if payload.get("deleted").and_then(Value::as_bool) == Some(true) {
+                return Ok(Delivery::Ignored("the push deleted the ref".to_string()));
+            }
+            WebhookEvent {
+                platform,
+                event: "push".to_string(),
+                action: None,
+                git_ref: str_at("/ref").ok_or("Push without a ref")?,
+                sha: str_at("/after"),
+                changed_files: Some(changed_files(&payload)),
+            }
+        }
+        (Platform::GitHub, "pull_request") => WebhookEvent {
+            platform,
+            event: "pull_request".to_string(),
+            action: str_at("/action"),
+            git_ref: format!(
+                "refs/heads/{}",
+                str_at("/pull_request/base/ref").ok_or("Pull request without a base")?
+            ),
+            sha: str_at("/pull_request/head/sha"),
+            changed_files: None,
+        },
+        (Platform::GitLab, "Push Hook" | "Tag Push Hook") => {
+            if str_at("/after").is_some_and(|sha| sha.bytes().all(|b| b == b'0')) {
+                return Ok(Delivery::Ignored("the push deleted the ref".to_string()));
+            }
+            WebhookEvent {
+                platform,
+                event: "push".to_string(),
+                action: None,
+                git_ref: str_at("/ref").ok_or("Push without a ref")?,
+                sha: str_at("/checkout_sha").or_else(|| str_at("/after")),
+                changed_files: Some(changed_files(&payload)),
+            }
+        }
+        (Platform::GitLab, "Merge Request Hook") => {
+            let action = match str_at("/object_attributes/action").as_deref() {
+                Some("open") => "opened",
+                Some("update") => "synchronize",
+                Some("reopen") => "reopened",
+                Some("close") | Some("merge") => "closed",
+                Some(other) => {
+                    return Ok(Delivery::Ignored(format!(
+                        "merge request activity '{}'",
+                        other
+                    )))
+                }
+                None => return Err("Merge request without an action".to_string()),
+            };
+            WebhookEvent {
+                platform,
+                event: "pull_request".to_string(),
+                action: Some(action.to_string()),
+                git_ref: format!(
+                    "refs/heads/{}",
+                    str_at("/object_attributes/target_branch")
+                        .ok_or("Merge request without a target branch")?
+                ),
+                sha: str_at("/object_attributes/last_commit/id"),
+                changed_files: None,
+            }
+        }
+        (_, other) => return Ok(Delivery::Ignored(format!("{} '{}' events", kind, other))),
+    };
+    Ok(Delivery::Event(event))
+}
+
+/// Files added, changed or removed by the commits of a push; both forges list
+/// them the same way
+fn changed_files(payload: &Value) -> Vec<String> {
+    let mut files: Vec<String> = payload
+        .get("commits")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .flat_map(|commit| ["added", "modified", "removed"].map(|key| commit.get(key)))
+        .flatten()
+        .filter_map(Value::as_array)
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// Whether a workflow with triggers `on` (its `on:` as written: `on_raw`) runs for
+/// `event`, going by its activity types, branch and tag filters and, when the
+/// changed files are known, path filters
+pub fn triggers(on: &[String], on_raw: &serde_yaml::Value, event: &WebhookEvent) -> bool {
+    if !on.contains(&event.event) {
+        return false;
+    }
+    if let Some(action) = &event.action {
+        let types: Vec<&str> = on_raw
+            .get(&event.event)
+            .and_then(|config| config.get("types"))
+            .and_then(serde_yaml::Value::as_sequence)
+            .map(|types| types.iter().filter_map(serde_yaml::Value::as_str).collect())
+            .unwrap_or_else(|| DEFAULT_PULL_REQUEST_TYPES.to_vec());
+        if !types.contains(&action.as_str()) {
+            return false;
+        }
+    }
+    if RefFilter::for_event(on_raw, &event.event).is_some_and(|f| !f.matches(&event.git_ref)) {
+        return false;
+    }
+    match &event.changed_files {
+        Some(files) => PathFilter::for_event(on_raw, &event.event).is_none_or(|f| f.matches(files)),
+        None => true,
+    }
+}
+
+/// Workflows of the project that `event` triggers: those in `.github/workflows`
+/// for GitHub, `.gitlab-ci.yml` for GitLab
+pub fn triggered_workflows(event: &WebhookEvent) -> Vec<PathBuf> {
+    if event.platform == Platform::GitLab {
+        let pipeline = PathBuf::from(".gitlab-ci.yml");
+        return if pipeline.is_file() {
+            vec![pipeline]
+        } else {
+            Vec::new()
+        };
+    }
+    let dir = Path::new(".github/workflows");
+    if !dir.is_dir() {
+        return Vec::new();
+    }
+    workflow_files(dir)
+        .into_iter()
+        .filter(|path| Platform::of(path, None) == Platform::GitHub)
+        .filter(|path| {
+            parse_workflow(path)
+                .is_ok_and(|workflow| triggers(&workflow.on, &workflow.on_raw, event))
+        })
+        .collect()
+}
+
+/// The commit checked out in the current directory, if it's in a git repository
+pub fn checked_out_commit() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn push(git_ref: &str, files: &[&str]) -> WebhookEvent {
+        WebhookEvent {
+            platform: Platform::GitHub,
+            event: "push".to_string(),
+            action: None,
+            git_ref: git_ref.to_string(),
+            sha: None,
+            changed_files: Some(files.iter().map(|file| file.to_string()).collect()),
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let body = br#"{"zen":"Keep it logically awesome."}"#;
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"s3cret").unwrap();
+        mac.update(body);
+        let signature: String = mac
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        let signed = headers(&[("x-hub-signature-256", &format!("sha256={}", signature))]);
+        assert_eq!(verify(&signed, body, "s3cret"), Ok(Platform::GitHub));
+        assert!(verify(&signed, body, "other").is_err());
+        assert!(verify(&signed, b"{}", "s3cret").is_err());
+        let malformed = headers(&[("x-hub-signature-256", "sha256=xyz")]);
+        assert!(verify(&malformed, body, "s3cret").is_err());
+
+        let gitlab = headers(&[("x-gitlab-token", "s3cret")]);
+        assert_eq!(verify(&gitlab, body, "s3cret"), Ok(Platform::GitLab));
+        assert!(verify(&gitlab, body, "s3cre").is_err());
+        assert!(verify(&HashMap::new(), body, "s3cret").is_err());
+    }
+
+    #[test]
+    fn test_parse_github() {
+        let github = |event: &str| headers(&[("x-github-event", event)]);
+        let payload = br#"{"ref":"refs/heads/main","after":"abc123","commits":[
+            {"added":["src/new.rs"],"modified":["src/lib.rs"],"removed":[]},
+            {"added":[],"modified":["src/lib.rs"],"removed":["old.txt"]}]}"#;
+        let Delivery::Event(event) = parse(Platform::GitHub, &github("push"), payload).unwrap()
+        else {
+            panic!("expected an event");
+        };
+        assert_eq!(event.event, "push");
+        assert_eq!(event.git_ref, "refs/heads/main");
+        assert_eq!(event.sha.as_deref(), Some("abc123"));
+        assert_eq!(
+            event.changed_files.unwrap(),
+            vec!["old.txt", "src/lib.rs", "src/new.rs"]
+        );
+
+        let payload = br#"{"action":"synchronize","pull_request":{"base":{"ref":"main"},"head":{"sha":"def456"}}}"#;
+        let Delivery::Event(event) =
+            parse(Platform::GitHub, &github("pull_request"), payload).unwrap()
+        else {
+            panic!("expected an event");
+        };
+        assert_eq!(event.event, "pull_request");
+        assert_eq!(event.action.as_deref(), Some("synchronize"));
+        assert_eq!(event.git_ref, "refs/heads/main");
+        assert_eq!(event.changed_files, None);
+
+        assert_eq!(
+            parse(Platform::GitHub, &github("ping"), b"{}").unwrap(),
+            Delivery::Ping
+        );
+        assert!(matches!(
+            parse(Platform::GitHub, &github("issues"), b"{}").unwrap(),
+            Delivery::Ignored(_)
+        ));
+        assert!(matches!(
+            parse(
+                Platform::GitHub,
+                &github("push"),
+                br#"{"ref":"refs/heads/x","deleted":true}"#
+            )
+            .unwrap(),
+            Delivery::Ignored(_)
+        ));
+        assert!(parse(Platform::GitHub, &HashMap::new(), b"{}").is_err());
+        assert!(parse(Platform::GitHub, &github("push"), b"not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_gitlab() {
+        let gitlab = |event: &str| headers(&[("x-gitlab-event", event)]);
+        let payload = br#"{"ref":"refs/heads/main","after":"abc","checkout_sha":"abc",
+            "commits":[{"added":["a.txt"],"modified":[],"removed":[]}]}"#;
+        let Delivery::Event(event) =
+            parse(Platform::GitLab, &gitlab("Push Hook"), payload).unwrap()
+        else {
+            panic!("expected an event");
+        };
+        assert_eq!(event.platform, Platform::GitLab);
+        assert_eq!(event.changed_files.unwrap(), vec!["a.txt"]);
+
+        let payload = br#"{"object_attributes":{"action":"update","target_branch":"develop","last_commit":{"id":"fed"}}}"#;
+        let Delivery::Event(event) =
+            parse(Platform::GitLab, &gitlab("Merge Request Hook"), payload).unwrap()
+        else {
+            panic!("expected an event");
+        };
+        assert_eq!(event.event, "pull_request");
+        assert_eq!(event.action.as_deref(), Some("synchronize"));
+        assert_eq!(event.git_ref, "refs/heads/develop");
+        assert_eq!(event.sha.as_deref(), Some("fed"));
+
+        let deleted =
+            br#"{"ref":"refs/heads/x","after":"0000000000000000000000000000000000000000"}"#;
+        assert!(matches!(
+            parse(Platform::GitLab, &gitlab("Push Hook"), deleted).unwrap(),
+            Delivery::Ignored(_)
+        ));
+    }
+
+    #[test]
+    fn test_triggers() {
+        let on_raw: serde_yaml::Value = serde_yaml::from_str(
+            "push:\n  branches: [main]\n  paths: ['src/**']\npull_request:\n  types: [opened]\n",
+        )
+        .unwrap();
+        let on = vec!["push".to_string(), "pull_request".to_string()];
+        assert!(triggers(
+            &on,
+            &on_raw,
+            &push("refs/heads/main", &["src/lib.rs"])
+        ));
+        assert!(!triggers(
+            &on,
+            &on_raw,
+            &push("refs/heads/dev", &["src/lib.rs"])
+        ));
+        assert!(!triggers(
+            &on,
+            &on_raw,
+            &push("refs/heads/main", &["README.md"])
+        ));
+
+        let mut pull_request = push("refs/heads/main", &[]);
+        pull_request.event = "pull_request".to_string();
+        pull_request.changed_files = None;
+        pull_request.action = Some("opened".to_string());
+        assert!(triggers(&on, &on_raw, &pull_request));
+        pull_request.action = Some("synchronize".to_string());
+        assert!(!triggers(&on, &on_raw, &pull_request));
+
+        let on_raw = serde_yaml::Value::String("pull_request".to_string());
+        let on = vec!["pull_request".to_string()];
+        assert!(triggers(&on, &on_raw, &pull_request));
+        pull_request.action = Some("closed".to_string());
+        assert!(!triggers(&on, &on_raw, &pull_request));
+        assert!(!triggers(&on, &on_raw, &push("refs/heads/main", &[])));
+    }
+}